@@ -0,0 +1,65 @@
+//! Compares storing the names of a large source as strings with interning them.
+//! Run with `cargo bench --bench intern`.
+use std::{hint::black_box, time::Instant};
+
+use surn::{compiler::lexer::tokenizer::tokenize, util::intern::Symbol};
+
+/// How many times the names are stored, to even out the timings.
+const ROUNDS: usize = 50;
+
+/// A source where every function calls the one before it, so most names are written often.
+fn source(functions: usize) -> String {
+    let mut source = String::new();
+    for i in 0..functions {
+        source.push_str(&format!(
+            "fn handler_{i}(request: int): int {{\n    var response = request + {i};\n    var total = response * 2;\n    return {}(total);\n}}\n",
+            match i {
+                0 => "abs".to_string(),
+                _ => format!("handler_{}", i - 1),
+            }
+        ));
+    }
+    source
+}
+
+fn main() {
+    let source = source(200);
+    let tokens = tokenize(&source);
+    let names: Vec<_> = tokens.iter().filter(|t| t.kind().is_identifier()).collect();
+    let bytes: usize = names.iter().map(|t| t.value().unwrap().len()).sum();
+
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        let strings: Vec<String> = names.iter().map(|t| t.value().unwrap()).collect();
+        black_box(strings);
+    }
+    let strings = start.elapsed() / ROUNDS as u32;
+
+    let interned = Symbol::count();
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        let symbols: Vec<Symbol> = names.iter().map(|t| t.symbol().unwrap()).collect();
+        black_box(symbols);
+    }
+    let symbols = start.elapsed() / ROUNDS as u32;
+    let distinct = Symbol::count() - interned;
+    let distinct_bytes: usize = names
+        .iter()
+        .map(|t| t.value().unwrap())
+        .collect::<std::collections::HashSet<_>>()
+        .iter()
+        .map(String::len)
+        .sum();
+
+    println!("{} names, {} of them distinct", names.len(), distinct);
+    println!(
+        "strings:  {:>6} allocations, {:>7} bytes, {:?} per pass",
+        names.len(),
+        bytes,
+        strings
+    );
+    println!(
+        "interned: {:>6} allocations, {:>7} bytes, {:?} per pass",
+        distinct, distinct_bytes, symbols
+    );
+}
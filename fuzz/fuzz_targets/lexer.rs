@@ -0,0 +1,17 @@
+//! Any source can be tokenized, and every token is a part of the source.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use surn::compiler::lexer::tokenizer::tokenize;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(source) = std::str::from_utf8(data) else {
+        return;
+    };
+    for token in tokenize(source) {
+        assert!(token.range().end <= source.len());
+        if let Some(text) = token.text() {
+            assert!(text.len() <= source.len());
+        }
+    }
+});
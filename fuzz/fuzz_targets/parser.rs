@@ -0,0 +1,22 @@
+//! Any source is parsed or reported, the parser never panics.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use surn::{
+    compiler::{
+        lexer::tokenizer::tokenize,
+        parser::{context::SourceOrigin, generator::AstGenerator},
+    },
+    util::token_stream::TokenStream,
+};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(source) = std::str::from_utf8(data) else {
+        return;
+    };
+    let mut generator = AstGenerator::new(
+        SourceOrigin::new_virtual("fuzz.surn".to_string(), source.to_string()),
+        1,
+    );
+    let _ = generator.begin_parse(TokenStream::new(tokenize(source)));
+});
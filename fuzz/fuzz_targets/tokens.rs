@@ -0,0 +1,99 @@
+//! Sequences of tokens the parser knows, random bytes rarely get past the first keyword.
+//! Every byte picks the next piece of the source.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use surn::{
+    compiler::{
+        lexer::tokenizer::tokenize,
+        parser::{context::SourceOrigin, generator::AstGenerator},
+    },
+    util::token_stream::TokenStream,
+};
+
+const PIECES: &[&str] = &[
+    "namespace",
+    "use",
+    "fn",
+    "class",
+    "interface",
+    "enum",
+    "const",
+    "var",
+    "static",
+    "public",
+    "private",
+    "protected",
+    "extends",
+    "implements",
+    "new",
+    "return",
+    "if",
+    "else",
+    "while",
+    "for",
+    "in",
+    "await",
+    "async",
+    "this",
+    "null",
+    "true",
+    "false",
+    "x",
+    "Y",
+    "main",
+    "int",
+    "string",
+    "bool",
+    "float",
+    "any",
+    "1",
+    "2.5",
+    "'a'",
+    "\"b\"",
+    "(",
+    ")",
+    "{",
+    "}",
+    "[",
+    "]",
+    "<",
+    ">",
+    ",",
+    ";",
+    ":",
+    "::",
+    ".",
+    "..",
+    "=",
+    "==",
+    "+",
+    "-",
+    "*",
+    "/",
+    "!",
+    "?",
+    "&&",
+    "||",
+    "->",
+    "=>",
+    "#",
+    "@",
+    "// c",
+    "/* c */",
+    " ",
+    "\n",
+];
+
+fuzz_target!(|data: &[u8]| {
+    let source: String = data
+        .iter()
+        .map(|byte| PIECES[*byte as usize % PIECES.len()])
+        .collect::<Vec<&str>>()
+        .join(" ");
+    let mut generator = AstGenerator::new(
+        SourceOrigin::new_virtual("fuzz.surn".to_string(), source.clone()),
+        1,
+    );
+    let _ = generator.begin_parse(TokenStream::new(tokenize(&source)));
+});
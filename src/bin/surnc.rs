@@ -0,0 +1,380 @@
+// The surn compiler command line.
+use std::{
+    env, fs,
+    io::{self, BufRead, Write},
+    path::Path,
+    process,
+};
+
+use surn::{
+    compiler::{
+        lints::LintLevel,
+        optimize::{optimize, profile::Profile},
+        parser::Parser,
+        CompilerOptions, CompilerOptionsBuilder, PhpTarget,
+    },
+    convert::{declarations::write_declarations, php_to_surn::php_to_surn},
+    ide::{apply_edits, imports::organize_imports},
+    project::{config::Config, find_root, new_project, Project, MANIFEST_FILE},
+    repl::{Repl, ReplMode},
+    report::{Report, ReportKind},
+    transpiler::{
+        audit::audit,
+        driver::failing_notices,
+        format::{printer::format_source, FormatOptions},
+        frameworks::Framework,
+        scaffold::{scaffold, Package, Template},
+        Transpiler,
+    },
+};
+
+const USAGE: &str = "Usage: surnc <command> [options]
+
+Commands:
+    new <dir>                            Creates a project with a surn.toml manifest.
+    build [path] [--lang <lang>]         Transpiles a file, directory or project, php by default.
+                                         Without a path, the current project is built.
+          [--profile <file>]             Specializes the functions the profile finds hot.
+          [--preload]                    Writes a preload.php for opcache next to the output.
+          [--framework <name>]           Writes the glue code of a framework, eg: laravel.
+          [--target <version>]           The php version to generate for, eg: 8.1.
+          [--strict-types]               Starts every file with declare(strict_types=1).
+          [--runtime-checks]             Checks the values given to runtime types as they run.
+          [--allow|--warn|--deny <lint>] Sets the level of a lint, eg: --allow unused_variable.
+          [--fail-on <kind>]             Fails on warnings or notices too, eg: --fail-on warning.
+    init <dir> [--template <name>]       Creates a new project, composer-lib by default.
+         [--name <vendor/name>]          The composer package name, surn/<dir> by default.
+    fmt <file> [--write]                 Formats a surn file.
+                                         Prints the result unless --write is given.
+    organize-imports <file> [--write]    Sorts, merges and removes unused imports.
+                                         Prints the result unless --write is given.
+    declare <path> [--out <dir>]         Writes .d.surn declarations for php files,
+                                         next to each file unless --out is given.
+    convert <file> [--write]             Converts a php file to surn, experimental.
+                                         Prints the result unless --write is given.
+    stats <file> --profile <file>        Shows which hot functions of a file get optimized.
+    backend-audit <lang>                 Shows which variants of the ast a backend generates.
+    repl [--php]                         Evaluates surn a line at a time, or prints the php
+                                         of every line with --php. :help lists the commands.
+    trace <file> [--rule <name>]         Shows every rule the parser tried on a file, or why
+          [--json]                       the given rule rejected it. --json prints the events.
+    --explain <code>                     Explains an error code, eg: E0102.";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(|a| a.as_str()) {
+        Some("build") => build(&args[1..]),
+        Some("new") => new(&args[1..]),
+        Some("init") => init(&args[1..]),
+        Some("fmt") => fmt(&args[1..]),
+        Some("organize-imports") => organize(&args[1..]),
+        Some("stats") => stats(&args[1..]),
+        Some("declare") => declare(&args[1..]),
+        Some("convert") => convert(&args[1..]),
+        Some("backend-audit") => backend_audit(&args[1..]),
+        Some("trace") => trace(&args[1..]),
+        Some("repl") => repl(&args[1..]),
+        Some("--explain") => explain(&args[1..]),
+        _ => Err(USAGE.to_string()),
+    };
+
+    if let Err(message) = result {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
+
+fn repl(args: &[String]) -> Result<(), String> {
+    let mut repl = Repl::new(CompilerOptions::default());
+    if args.iter().any(|a| a == "--php") {
+        repl.set_mode(ReplMode::Php);
+    }
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("> ");
+        io::stdout().flush().map_err(|e| e.to_string())?;
+        let Some(line) = lines.next() else {
+            println!();
+            return Ok(());
+        };
+        let line = line.map_err(|e| format!("Could not read the line: {}", e))?;
+        if line.trim() == ":quit" {
+            return Ok(());
+        }
+        match repl.eval_line(&line) {
+            Ok(Some(printed)) => println!("{}", printed),
+            Ok(None) => {}
+            Err(report) => report.print(),
+        }
+    }
+}
+
+fn explain(args: &[String]) -> Result<(), String> {
+    let code = args.first().ok_or(USAGE)?;
+    let explanation = Report::explain(code).ok_or(format!("Unknown error code: {}", code))?;
+    print!("{}", explanation);
+    Ok(())
+}
+
+fn build(args: &[String]) -> Result<(), String> {
+    // without a path, the project in the current directory is built.
+    let path = match args.first() {
+        Some(path) if !path.starts_with("--") => path.as_str(),
+        _ if Path::new(MANIFEST_FILE).is_file() => ".",
+        _ => return Err(USAGE.to_string()),
+    };
+    let project = find_root(Path::new(path))
+        .map(|root| Project::load(&root))
+        .transpose()?;
+    let mut config = match &project {
+        Some(project) => project.config()?,
+        None => Config::default(),
+    };
+    if let Some(lang) = flag_value(args, "--lang") {
+        config.lang = lang.to_string();
+    }
+
+    // the flags are applied over the options of the project.
+    let mut options = profiled_options(config.options, args)?;
+    if args.iter().any(|a| a == "--preload") {
+        options = options.preload(true);
+    }
+    if args.iter().any(|a| a == "--strict-types") {
+        options = options.strict_types(true);
+    }
+    if args.iter().any(|a| a == "--runtime-checks") {
+        options = options.runtime_checks(true);
+    }
+    if let Some(name) = flag_value(args, "--framework") {
+        options = options
+            .framework(Framework::from_name(name).ok_or(format!("Unknown framework: {}", name))?);
+    }
+    if let Some(kind) = flag_value(args, "--fail-on") {
+        options = options.fail_on(
+            ReportKind::from_name(kind).ok_or(format!("Unknown kind of report: {}", kind))?,
+        );
+    }
+    if let Some(version) = flag_value(args, "--target") {
+        options = options.target(
+            PhpTarget::from_version(version).ok_or(format!("Unknown php version: {}", version))?,
+        );
+    }
+    for (i, arg) in args.iter().enumerate() {
+        if let (Some(level), Some(lint)) = (
+            arg.strip_prefix("--").and_then(LintLevel::from_name),
+            args.get(i + 1),
+        ) {
+            options = options.lint(lint, level);
+        }
+    }
+    config.options = options.build().map_err(|report| report.message)?;
+
+    let mut transpiler = Transpiler::new();
+    transpiler.register_defaults();
+    match project {
+        // a whole project is built into its output directory.
+        Some(project) if project.root == Path::new(path) => {
+            let report = project.build(&transpiler, &config)?;
+            report.print_notices();
+            report.print_errors();
+            match report.failed().len() {
+                0 => failing_notices(report.failing(&config.options).len(), &config.options),
+                failed => Err(format!(
+                    "{} of {} files failed to transpile.",
+                    failed,
+                    report.files.len()
+                )),
+            }
+        }
+        _ => transpiler.generate(path, &config),
+    }
+}
+
+/// The options given, with specialization enabled when a profile is given.
+fn profiled_options(
+    options: CompilerOptions,
+    args: &[String],
+) -> Result<CompilerOptionsBuilder, String> {
+    let mut options = CompilerOptionsBuilder::from(options);
+    if let Some(profile) = flag_value(args, "--profile") {
+        options = options
+            .profile(Profile::load(Path::new(profile))?)
+            .specialize(true);
+    }
+    Ok(options)
+}
+
+/// The config of the project the path is in, the defaults outside of a project.
+fn config_for(path: &str) -> Result<Config, String> {
+    match find_root(Path::new(path)) {
+        Some(root) => Project::load(&root)?.config(),
+        None => Ok(Config::default()),
+    }
+}
+
+fn stats(args: &[String]) -> Result<(), String> {
+    let path = args.first().ok_or(USAGE)?;
+    let source = fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+    let options = profiled_options(config_for(path)?.options, args)?
+        .build()
+        .map_err(|report| report.message)?;
+
+    let mut parser = Parser::new(options.clone());
+    let mut body = parser
+        .try_parse_script(path.clone(), source)
+        .map_err(|report| {
+            report.print();
+            format!("Could not parse {}", path)
+        })?;
+    let stats = optimize(&mut body, &options);
+    println!("{}", stats.report(options.profile.as_ref()));
+    Ok(())
+}
+
+fn trace(args: &[String]) -> Result<(), String> {
+    let path = args.first().ok_or(USAGE)?;
+    let source = fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+    let options = CompilerOptions::builder()
+        .dev()
+        .trace_parse(true)
+        .build()
+        .map_err(|report| report.message)?;
+
+    let mut parser = Parser::new(options);
+    if let Err(report) = parser.try_parse_script(path.clone(), source) {
+        report.print();
+    }
+    let trace = parser.trace().unwrap();
+    if args.iter().any(|a| a == "--json") {
+        println!("{}", trace.to_json());
+    } else if let Some(rule) = flag_value(args, "--rule") {
+        print!("{}", trace.explain(rule));
+    } else {
+        print!("{}", trace.render());
+    }
+    Ok(())
+}
+
+fn backend_audit(args: &[String]) -> Result<(), String> {
+    let lang = args.first().ok_or(USAGE)?;
+    let mut transpiler = Transpiler::new();
+    transpiler.register_defaults();
+    let language = transpiler
+        .get(lang)
+        .ok_or(format!("Unknown language: {}", lang))?;
+    let audit = audit(language.generator.as_ref());
+    println!("{}", audit.report());
+    if audit.panics().is_empty() {
+        Ok(())
+    } else {
+        Err(format!("The {} backend panics on some variants.", lang))
+    }
+}
+
+fn new(args: &[String]) -> Result<(), String> {
+    let root = Path::new(args.first().ok_or(USAGE)?);
+    let name = root
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or(format!("{} is not a directory name.", root.display()))?;
+    for path in new_project(root, &name)? {
+        println!("Created {}", path.display());
+    }
+    Ok(())
+}
+
+fn init(args: &[String]) -> Result<(), String> {
+    let root = Path::new(args.first().ok_or(USAGE)?);
+    let template = flag_value(args, "--template").unwrap_or("composer-lib");
+    let template =
+        Template::from_name(template).ok_or(format!("Unknown template: {}", template))?;
+    let package = match flag_value(args, "--name") {
+        Some(name) => Package::parse(name)?,
+        None => {
+            let dir = root
+                .canonicalize()
+                .unwrap_or(root.to_path_buf())
+                .file_name()
+                .map(|n| n.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            Package::parse(&format!("surn/{}", dir))?
+        }
+    };
+
+    for path in scaffold(root, template, &package)? {
+        println!("Created {}", path.display());
+    }
+    Ok(())
+}
+
+fn fmt(args: &[String]) -> Result<(), String> {
+    let path = args.first().ok_or(USAGE)?;
+    let source = fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+
+    let formatted = format_source(path, &source, &FormatOptions::default()).map_err(|report| {
+        report.print();
+        format!("Could not parse {}", path)
+    })?;
+
+    if args.iter().any(|a| a == "--write") {
+        fs::write(path, formatted).map_err(|e| format!("Could not write {}: {}", path, e))
+    } else {
+        print!("{}", formatted);
+        Ok(())
+    }
+}
+
+fn organize(args: &[String]) -> Result<(), String> {
+    let path = args.first().ok_or(USAGE)?;
+    let source = fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+
+    let mut parser = Parser::with_config(&config_for(path)?);
+    let body = parser
+        .try_parse_script(path.clone(), source.clone())
+        .map_err(|report| {
+            report.print();
+            format!("Could not parse {}", path)
+        })?;
+    let organized = apply_edits(&source, &organize_imports(&source, &body));
+
+    if args.iter().any(|a| a == "--write") {
+        fs::write(path, organized).map_err(|e| format!("Could not write {}: {}", path, e))
+    } else {
+        print!("{}", organized);
+        Ok(())
+    }
+}
+
+fn declare(args: &[String]) -> Result<(), String> {
+    let path = args.first().ok_or(USAGE)?;
+    let output = flag_value(args, "--out").map(Path::new);
+    for path in write_declarations(Path::new(path), output)? {
+        println!("Declared {}", path.display());
+    }
+    Ok(())
+}
+
+fn convert(args: &[String]) -> Result<(), String> {
+    let path = args.first().ok_or(USAGE)?;
+    let source = fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+    let converted = php_to_surn(&source);
+
+    if args.iter().any(|a| a == "--write") {
+        let output = Path::new(path).with_extension("surn");
+        fs::write(&output, converted)
+            .map_err(|e| format!("Could not write {}: {}", output.display(), e))?;
+        println!("Converted {}", output.display());
+        Ok(())
+    } else {
+        print!("{}", converted);
+        Ok(())
+    }
+}
+
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == flag)
+        .and_then(|i| args.get(i + 1))
+        .map(|v| v.as_str())
+}
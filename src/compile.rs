@@ -0,0 +1,101 @@
+//! The compiler as a single call, for programs that embed it instead of running `surnc`.
+//!
+//! A source is parsed, checked and generated by every default language the same way
+//! `surnc` does for a file, without reading or writing anything.
+use std::{collections::BTreeMap, path::Path, sync::Arc};
+
+use crate::{
+    compiler::{
+        ast::AstBody, lexer::analysis::analyze_source, parser::Parser, symbols::SymbolTable,
+        CompilerOptions,
+    },
+    report::{diagnostic::Diagnostic, Report},
+    transpiler::{
+        driver::{generate_source, warning_notices},
+        format::FormatOptions,
+        Transpiler,
+    },
+    util::source::SourceBuffer,
+};
+
+/// The name reports of `compile_str` are given.
+pub const SOURCE_NAME: &str = "main.surn";
+
+/// Everything a source compiled to.
+#[derive(Debug)]
+pub struct CompilationOutput {
+    pub ast: AstBody,
+    pub symbols: SymbolTable,
+    /// The warnings and notices of the source, errors are returned instead.
+    pub diagnostics: Vec<Report>,
+    /// The generated code by the name of its language, eg: `php`.
+    /// Nothing is generated when `CompilerOptions::ast_only` is set.
+    pub code: BTreeMap<String, String>,
+}
+
+/// Compiles a source, the errors are returned as reports.
+/// When a warning or notice fails the compile, see `CompilerOptions::fail_on`,
+/// every warning and notice is returned instead of the output.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::CompilerOptions;
+/// let output = surn::compile_str("var x = 5;", &CompilerOptions::default())?;
+/// println!("{}", output.code["php"]);
+/// # Ok::<(), Vec<surn::report::Report>>(())
+/// ```
+pub fn compile_str(
+    source: &str,
+    options: &CompilerOptions,
+) -> Result<CompilationOutput, Vec<Report>> {
+    let name = Path::new(SOURCE_NAME);
+    let mut parser = Parser::new(options.clone());
+    let ast = parser
+        .try_parse_script(SOURCE_NAME.to_string(), source.to_string())
+        .map_err(|report| errors(report, source, options))?;
+    let mut diagnostics = warning_notices(parser.warnings(), name, source);
+
+    let mut code = BTreeMap::new();
+    if !options.ast_only {
+        let mut transpiler = Transpiler::new();
+        transpiler.register_defaults();
+        let format = FormatOptions::default();
+        for (lang, language) in transpiler.languages() {
+            let generator = language.generator.as_ref();
+            let (generated, _, notices) =
+                generate_source(generator, ast.clone(), name, source, options, &format)
+                    .map_err(|report| vec![report])?;
+            diagnostics.extend(notices);
+            code.insert(lang.to_string(), generated);
+        }
+    }
+
+    if diagnostics.iter().any(|report| options.fails(report.kind)) {
+        return Err(diagnostics);
+    }
+    Ok(CompilationOutput {
+        symbols: SymbolTable::build(&ast),
+        ast,
+        diagnostics,
+        code,
+    })
+}
+
+/// The errors of a source that failed to parse, the parser stops at the first one
+/// but every problem the analyzer finds is reported.
+fn errors(report: Report, source: &str, options: &CompilerOptions) -> Vec<Report> {
+    let found = match options.semantic_checks {
+        true => analyze_source(source).err().unwrap_or_default(),
+        false => Vec::new(),
+    };
+    let found: Vec<Diagnostic> = found.into_iter().filter(Diagnostic::is_error).collect();
+    // the parser reports the first problem of the analyzer, unless it refused the source first.
+    if found.first().map(|diagnostic| diagnostic.code) != Some(report.code) {
+        return vec![report];
+    }
+    let buffer = Arc::new(SourceBuffer::new(source.to_string()));
+    found
+        .into_iter()
+        .map(|diagnostic| diagnostic.into_report(SOURCE_NAME.to_string(), buffer.clone()))
+        .collect()
+}
@@ -0,0 +1,142 @@
+//! The storage of the top level nodes of a body.
+//!
+//! Nodes are allocated once and referred to by their `NodeId` from then on. The arena
+//! is shared by every clone of a body until one of them changes a node, so cloning a
+//! body doesn't copy its nodes, and an analysis keeps what it finds in a `NodeMap`
+//! instead of in the nodes themselves.
+use std::{collections::BTreeMap, num::NonZeroU32};
+
+use super::Node;
+
+/// The id of a node in its arena, ids start at 1 so `0` is never a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(NonZeroU32);
+
+impl NodeId {
+    /// The id of the node allocated at the given index.
+    pub fn from_index(index: usize) -> Self {
+        let id = u32::try_from(index + 1).expect("an arena has less than u32::MAX nodes");
+        NodeId(NonZeroU32::new(id).unwrap())
+    }
+
+    pub fn index(self) -> usize {
+        self.0.get() as usize - 1
+    }
+
+    /// The id the way a declaration keeps it, see `Function::node_id`.
+    pub fn as_u64(self) -> u64 {
+        self.0.get() as u64
+    }
+
+    /// The id of a declaration, `None` for `0`, the id of a declaration that isn't a node.
+    pub fn from_u64(id: u64) -> Option<Self> {
+        u32::try_from(id).ok().and_then(NonZeroU32::new).map(NodeId)
+    }
+}
+
+/// Every node allocated for a body, in the order they were allocated.
+/// Nodes are never removed, so an id stays valid for as long as the arena.
+#[derive(Debug, Clone, Default)]
+pub struct AstArena {
+    nodes: Vec<Node>,
+}
+
+impl AstArena {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn alloc(&mut self, node: Node) -> NodeId {
+        self.nodes.push(node);
+        NodeId::from_index(self.nodes.len() - 1)
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.get(id.index())
+    }
+
+    pub fn get_mut(&mut self, id: NodeId) -> Option<&mut Node> {
+        self.nodes.get_mut(id.index())
+    }
+
+    /// Mutable references to the nodes with the given ids, in the order of the ids.
+    /// Every id is given once.
+    pub fn get_many_mut(&mut self, ids: &[NodeId]) -> Vec<&mut Node> {
+        let mut slots: Vec<Option<&mut Node>> = self.nodes.iter_mut().map(Some).collect();
+        ids.iter()
+            .map(|id| slots[id.index()].take().expect("every id is given once"))
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+/// What an analysis found about nodes, by their id.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, CompilerOptions};
+/// # use surn::compiler::ast::arena::NodeMap;
+/// # let source = "var x = 1;\nvar y = x;\n";
+/// # let body = Parser::new(CompilerOptions::default())
+/// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+/// let mut lines: NodeMap<usize> = NodeMap::new();
+/// for (id, node) in body.nodes() {
+///     lines.insert(id, node.position.line);
+/// }
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct NodeMap<T> {
+    values: BTreeMap<NodeId, T>,
+}
+
+impl<T> Default for NodeMap<T> {
+    fn default() -> Self {
+        Self {
+            values: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T> NodeMap<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the value of a node, returning the one it had.
+    pub fn insert(&mut self, id: NodeId, value: T) -> Option<T> {
+        self.values.insert(id, value)
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&T> {
+        self.values.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: NodeId) -> Option<&mut T> {
+        self.values.get_mut(&id)
+    }
+
+    pub fn remove(&mut self, id: NodeId) -> Option<T> {
+        self.values.remove(&id)
+    }
+
+    /// The values by id, in the order of the ids.
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, &T)> {
+        self.values.iter().map(|(id, value)| (*id, value))
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
@@ -0,0 +1,298 @@
+//! Builders for declarations and bodies, for tools that generate code instead of parsing it,
+//! eg: scaffolders, macro expansions and codemods. See `expr` for expressions.
+//!
+//! Built nodes have no source, their spans are empty and their position is line 0.
+//!
+//! **Example:**
+//! ```rust no_run
+//! # use surn::compiler::ast::{
+//! #     build::{BodyBuilder, FunctionBuilder},
+//! #     expr,
+//! #     ops::BinOp,
+//! #     types::{BuiltInType, TypeKind},
+//! # };
+//! // fn add(a: int, b: int): int { return a + b; }
+//! let int = || TypeKind::BuiltIn(BuiltInType::Int);
+//! let add = FunctionBuilder::new("add")
+//!     .input("a", int())
+//!     .input("b", int())
+//!     .returns(int())
+//!     .returning(expr::binary(expr::name("a"), BinOp::Plus, expr::name("b")))
+//!     .build();
+//! let body = BodyBuilder::new().function(add).build();
+//! ```
+use super::{
+    types::{TypeKind, TypeParam},
+    AstBody, Attribute, Class, ClassAllowedStatement, ClassProperty, Expression, Function,
+    FunctionInput, Node, NodeKind, Purity, Return, Statement, Variable, Visibility,
+};
+
+/// A `var` declaration, eg: `var count: int = 0;`
+pub fn var(name: &str, ty: Option<TypeKind>, value: Option<Expression>) -> Statement {
+    Statement::Var(Variable::new(
+        name.to_string(),
+        ty,
+        Visibility::Module,
+        value,
+    ))
+}
+
+/// A `const` declaration, eg: `const MAX = 10;`
+pub fn constant(name: &str, value: Expression) -> Statement {
+    Statement::Const(Variable::new(
+        name.to_string(),
+        None,
+        Visibility::Module,
+        Some(value),
+    ))
+}
+
+/// A `return` statement, `None` returns nothing.
+pub fn ret(value: Option<Expression>) -> Statement {
+    Statement::Return(Return::new(value))
+}
+
+/// A statement in a block, the way the parser puts it there.
+/// Expressions are followed by the end of their line.
+fn push_statement(block: &mut Vec<Expression>, stmt: Statement) {
+    block.push(Expression::Statement(Box::new(stmt)));
+}
+
+fn push_expression(block: &mut Vec<Expression>, expr: Expression) {
+    match expr {
+        Expression::Statement(stmt) => push_statement(block, *stmt),
+        expr => {
+            block.push(expr);
+            block.push(Expression::EndOfLine);
+        }
+    }
+}
+
+/// Builds a `Function`, a public function with an empty body until told otherwise.
+#[derive(Debug, Clone)]
+pub struct FunctionBuilder {
+    function: Function,
+    block: Vec<Expression>,
+}
+
+impl FunctionBuilder {
+    pub fn new(name: &str) -> Self {
+        let mut builder = Self::anonymous();
+        builder.function.name = Some(name.to_string());
+        builder
+    }
+
+    /// A function without a name, eg: a closure.
+    pub fn anonymous() -> Self {
+        Self {
+            function: Function {
+                name: None,
+                generics: Vec::new(),
+                inputs: Vec::new(),
+                body: Box::new(Statement::Block(Vec::new())),
+                outputs: None,
+                visibility: Visibility::Public,
+                node_id: 0,
+                span: 0..0,
+                body_span: 0..0,
+                purity: Purity::Unknown,
+                is_async: false,
+                attributes: Vec::new(),
+            },
+            block: Vec::new(),
+        }
+    }
+
+    /// A type parameter bound by a type, `any` when it is unbounded.
+    pub fn generic(mut self, name: &str, bound: TypeKind) -> Self {
+        self.function
+            .generics
+            .push(TypeParam::named(name.to_string(), bound));
+        self
+    }
+
+    pub fn input(mut self, name: &str, ty: TypeKind) -> Self {
+        self.function
+            .inputs
+            .push(FunctionInput::new(name.to_string(), Some(ty)));
+        self
+    }
+
+    pub fn returns(mut self, ty: TypeKind) -> Self {
+        self.function.outputs = Some(ty);
+        self
+    }
+
+    pub fn visibility(mut self, visibility: Visibility) -> Self {
+        self.function.visibility = visibility;
+        self
+    }
+
+    /// Declares the function `async`, so its body can `await`.
+    pub fn asynchronous(mut self) -> Self {
+        self.function.is_async = true;
+        self
+    }
+
+    /// Adds a statement to the end of the body.
+    pub fn statement(mut self, stmt: Statement) -> Self {
+        push_statement(&mut self.block, stmt);
+        self
+    }
+
+    /// Adds an expression statement to the end of the body, eg: `log("a");`
+    pub fn expression(mut self, expr: Expression) -> Self {
+        push_expression(&mut self.block, expr);
+        self
+    }
+
+    /// Ends the body with a `return` of the value.
+    pub fn returning(self, value: Expression) -> Self {
+        self.statement(ret(Some(value)))
+    }
+
+    pub fn build(mut self) -> Function {
+        self.function.body = Box::new(Statement::Block(self.block));
+        self.function
+    }
+}
+
+/// Builds a `Class`.
+#[derive(Debug, Clone)]
+pub struct ClassBuilder {
+    class: Class,
+}
+
+impl ClassBuilder {
+    pub fn new(name: &str) -> Self {
+        let mut class = Class::new();
+        class.name = name.to_string();
+        Self { class }
+    }
+
+    /// An attribute placed before the class, eg: `#[Singleton(CacheContract)]`.
+    pub fn attribute(mut self, name: &str, arguments: &[&str]) -> Self {
+        self.class.attributes.push(Attribute::new(
+            name.to_string(),
+            arguments.iter().map(|a| a.to_string()).collect(),
+        ));
+        self
+    }
+
+    pub fn generic(mut self, name: &str, bound: TypeKind) -> Self {
+        self.class
+            .generics
+            .push(TypeParam::named(name.to_string(), bound));
+        self
+    }
+
+    pub fn extends(mut self, parent: &str) -> Self {
+        self.class.extends = Some(parent.to_string());
+        self
+    }
+
+    pub fn implements(mut self, interface: &str) -> Self {
+        self.class
+            .implements
+            .get_or_insert_with(Vec::new)
+            .push(interface.to_string());
+        self
+    }
+
+    pub fn property(
+        mut self,
+        name: &str,
+        visibility: Visibility,
+        ty: Option<TypeKind>,
+        value: Option<Expression>,
+    ) -> Self {
+        self.class.body.properties.push(ClassProperty::new(
+            name.to_string(),
+            visibility,
+            ty,
+            value,
+        ));
+        self
+    }
+
+    pub fn method(mut self, method: Function) -> Self {
+        self.class.body.methods.push(method);
+        self
+    }
+
+    /// A property of the class instead of an instance, eg: `User::count`.
+    pub fn static_property(
+        mut self,
+        name: &str,
+        visibility: Visibility,
+        ty: Option<TypeKind>,
+        value: Option<Expression>,
+    ) -> Self {
+        self.class
+            .body
+            .other
+            .push(ClassAllowedStatement::new_static(
+                ClassAllowedStatement::Property(ClassProperty::new(
+                    name.to_string(),
+                    visibility,
+                    ty,
+                    value,
+                )),
+            ));
+        self
+    }
+
+    /// A method called on the class instead of an instance, eg: `User::find(1)`.
+    pub fn static_method(mut self, method: Function) -> Self {
+        self.class
+            .body
+            .other
+            .push(ClassAllowedStatement::new_static(
+                ClassAllowedStatement::Method(method),
+            ));
+        self
+    }
+
+    pub fn build(self) -> Class {
+        self.class
+    }
+}
+
+/// Builds an `AstBody` out of top level statements.
+/// Declarations are given the id of their node, see `AstBody::push_node`.
+#[derive(Debug, Clone, Default)]
+pub struct BodyBuilder {
+    nodes: Vec<NodeKind>,
+}
+
+impl BodyBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn statement(mut self, stmt: Statement) -> Self {
+        self.nodes.push(NodeKind::Statement(stmt));
+        self
+    }
+
+    pub fn function(self, function: Function) -> Self {
+        self.statement(Statement::Function(function))
+    }
+
+    pub fn class(self, class: Class) -> Self {
+        self.statement(Statement::Class(class))
+    }
+
+    pub fn expression(mut self, expr: Expression) -> Self {
+        self.nodes.push(NodeKind::Expression(expr));
+        self
+    }
+
+    pub fn build(self) -> AstBody {
+        let mut body = AstBody::new();
+        for node in self.nodes {
+            body.push_node(Node::new(node, 0..0, 0..0));
+        }
+        body
+    }
+}
@@ -0,0 +1,145 @@
+//! Shorthands for building expressions in code, see `build` for declarations.
+//!
+//! **Example:**
+//! ```rust no_run
+//! # use surn::compiler::ast::{expr, ops::BinOp};
+//! // max(a, 1) + 2
+//! let sum = expr::binary(
+//!     expr::call("max", [expr::name("a"), expr::int(1)]),
+//!     BinOp::Plus,
+//!     expr::int(2),
+//! );
+//! ```
+use crate::compiler::lexer::{
+    pos::Position,
+    token::{Token, TokenType},
+};
+
+use super::{
+    ops::{AnyOperation, AssignmentOp, BinOp},
+    types::{BuiltInType, TypeKind},
+    Array, Call, Expression, Literal, MemberListNode, MemberLookup, MethodCall, NewCall, Object,
+    ObjectProperty, Operation,
+};
+
+fn literal(value: String, ty: BuiltInType) -> Expression {
+    Expression::Literal(Literal::new(value, Some(TypeKind::BuiltIn(ty))))
+}
+
+/// A variable or constant, eg: `count`.
+pub fn name(name: &str) -> Expression {
+    Expression::Literal(Literal::new(name.to_string(), None))
+}
+
+pub fn int(value: i64) -> Expression {
+    literal(value.to_string(), BuiltInType::Int)
+}
+
+pub fn float(value: f64) -> Expression {
+    // `1.0` and not `1`, the parser tells floats apart by the `.`
+    literal(format!("{:?}", value), BuiltInType::Float)
+}
+
+/// A string literal, the value is the string itself, without quotes or escapes.
+pub fn string(value: &str) -> Expression {
+    literal(value.to_string(), BuiltInType::String)
+}
+
+pub fn bool(value: bool) -> Expression {
+    literal(value.to_string(), BuiltInType::Bool)
+}
+
+pub fn null() -> Expression {
+    literal("null".to_string(), BuiltInType::Null)
+}
+
+/// A call of a function, eg: `foo(1, 2)`.
+pub fn call(name: &str, arguments: impl IntoIterator<Item = Expression>) -> Expression {
+    Expression::Call(Call::new(name.to_string(), arguments.into_iter().collect()))
+}
+
+/// A call of a method, eg: `user.save()`.
+pub fn method(
+    callee: Expression,
+    name: &str,
+    arguments: impl IntoIterator<Item = Expression>,
+) -> Expression {
+    Expression::MethodCall(MethodCall {
+        name: name.to_string(),
+        arguments: arguments.into_iter().collect(),
+        callee: Box::new(callee),
+    })
+}
+
+/// A new instance of a class, eg: `new User("a")`.
+pub fn new(class: &str, arguments: impl IntoIterator<Item = Expression>) -> Expression {
+    Expression::New(NewCall::new(
+        class.to_string(),
+        arguments.into_iter().collect(),
+    ))
+}
+
+/// A property of a value, eg: `user.name`.
+pub fn member(origin: &str, name: &str) -> Expression {
+    Expression::Member(MemberListNode::new(
+        self::name(name),
+        Token(
+            TokenType::Identifier,
+            0..0,
+            Some(origin.into()),
+            Position::default(),
+        ),
+        MemberLookup::Dynamic,
+    ))
+}
+
+/// A static property of a class, eg: `User::count`.
+pub fn static_member(class: &str, name: &str) -> Expression {
+    Expression::Member(MemberListNode::new(
+        self::name(name),
+        Token(
+            TokenType::Identifier,
+            0..0,
+            Some(class.into()),
+            Position::default(),
+        ),
+        MemberLookup::Static,
+    ))
+}
+
+pub fn array(values: impl IntoIterator<Item = Expression>) -> Expression {
+    Expression::Array(Array::new(values.into_iter().collect(), None))
+}
+
+/// A tuple, it needs at least two values.
+pub fn tuple(values: impl IntoIterator<Item = Expression>) -> Expression {
+    Expression::Tuple(values.into_iter().collect())
+}
+
+/// An object literal, eg: `{ x: 1, y: 2 }`.
+pub fn object<'a>(properties: impl IntoIterator<Item = (&'a str, Expression)>) -> Expression {
+    Expression::Object(Object::new(
+        properties
+            .into_iter()
+            .map(|(name, value)| ObjectProperty::new(name.to_string(), value))
+            .collect(),
+        None,
+    ))
+}
+
+pub fn binary(left: Expression, op: BinOp, right: Expression) -> Expression {
+    Expression::Operation(Operation::new(left, AnyOperation::BinOp(op), right))
+}
+
+/// An assignment, eg: `count = 1`.
+pub fn assign(target: Expression, value: Expression) -> Expression {
+    Expression::Operation(Operation::new(
+        target,
+        AnyOperation::AssignmentOp(AssignmentOp::Eq),
+        value,
+    ))
+}
+
+pub fn awaited(value: Expression) -> Expression {
+    Expression::Await(Box::new(value))
+}
@@ -1,12 +1,24 @@
+pub mod arena;
+pub mod build;
+pub mod expr;
 pub mod ops;
+pub mod pretty;
 pub mod types;
+pub mod visit;
 
-use std::ops::Range;
+use std::{fmt, ops::Range, sync::Arc};
 
-use crate::compiler::lexer::{keyword::KeyWord, token::Token};
+use crate::{
+    compiler::{
+        lexer::{keyword::KeyWord, pos::Position, token::Token},
+        lints::LintScope,
+    },
+    util::intern::Symbol,
+};
 
+use self::arena::{AstArena, NodeId};
 use self::ops::AnyOperation;
-use self::types::{BuiltInType, TypeDefinition, TypeKind};
+use self::types::{BuiltInType, TypeDefinition, TypeKind, TypeParam};
 
 #[derive(Debug, Clone)]
 pub enum NodeKind {
@@ -30,12 +42,25 @@ impl From<Statement> for NodeKind {
 pub struct Node {
     pub start: Range<usize>,
     pub end: Range<usize>,
+    /// The line and column the node starts at, line 0 if it wasn't parsed from a source.
+    pub position: Position,
     pub inner: NodeKind,
 }
 
 impl Node {
     pub fn new(inner: NodeKind, start: Range<usize>, end: Range<usize>) -> Self {
-        Self { start, end, inner }
+        Self {
+            start,
+            end,
+            position: Position::default(),
+            inner,
+        }
+    }
+
+    /// Sets the position the node starts at.
+    pub fn at(mut self, position: Position) -> Self {
+        self.position = position;
+        self
     }
 
     pub fn inner(&self) -> NodeKind {
@@ -66,6 +91,11 @@ pub enum Expression {
     /// await something();
     /// ```
     Await(Box<Expression>),
+    /// The values of an array given as separate arguments or elements.
+    /// For example:
+    /// - `sum(...numbers)`
+    /// - `[...first, ...second]`
+    Spread(Box<Expression>),
     /// A regular function call.
     ///
     /// For example:
@@ -86,6 +116,11 @@ pub enum Expression {
     /// - `[1, 2, 3]`
     /// - `[1; 10]`
     Array(Array),
+    /// A tuple literal, it has at least two values.
+    ///
+    /// For example:
+    /// - `(1, "a")`
+    Tuple(Vec<Expression>),
     /// An Object Literal.
     /// For example:
     /// - `{ key: "value" }`
@@ -115,6 +150,12 @@ pub enum Expression {
     /// - `true`
     /// - `false`
     Literal(Literal),
+    /// Whether a value is of a type, `instanceof` is the same test.
+    ///
+    /// For example:
+    /// - `x is int`
+    /// - `user instanceof Admin`
+    Is(TypeTest),
     /// A end of statement,
     ///
     /// For example:
@@ -122,16 +163,59 @@ pub enum Expression {
     EndOfLine,
 }
 
+impl Expression {
+    /// The span of the expression in its source, `None` for expressions that don't keep one
+    /// and for expressions that weren't parsed from a source.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::compiler::{parser::Parser, CompilerOptions};
+    /// # use surn::compiler::ast::{NodeKind, Statement};
+    /// // var total = price * 2;
+    /// # let source = "var total = price * 2;\n";
+    /// # let body = Parser::new(CompilerOptions::default())
+    /// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+    /// # let NodeKind::Statement(Statement::Var(var)) = &body.get_program()[0].inner else { unreachable!() };
+    /// # let expr = var.assignment.as_ref().unwrap();
+    /// expr.span(); // Some(12..21), `price * 2`
+    /// # Ok::<(), surn::report::Report>(())
+    /// ```
+    pub fn span(&self) -> Option<Range<usize>> {
+        let span = match self {
+            // the value, the keyword isn't kept.
+            Expression::Await(inner) | Expression::Spread(inner) => return inner.span(),
+            Expression::Call(call) => call.span.clone(),
+            Expression::New(call) => call.span.clone(),
+            Expression::Array(array) => array.span.clone(),
+            Expression::Object(object) => object.span.clone(),
+            Expression::Operation(op) => op.span.clone(),
+            Expression::Literal(literal) => literal.span.clone(),
+            Expression::Is(test) => test.span.clone(),
+            Expression::Member(member) => member.origin.range().start..member.name.span()?.end,
+            Expression::Tuple(values) => values.first()?.span()?.start..values.last()?.span()?.end,
+            Expression::Statement(stmt) => return stmt.span(),
+            Expression::MethodCall(_) | Expression::EndOfLine => return None,
+        };
+        Some(span).filter(|span| !span.is_empty())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Literal {
     pub value: String,
     /// The type of the literal assumed by the compiler
     pub ty: Option<TypeKind>,
+    /// The range of the token of the literal.
+    pub span: Range<usize>,
 }
 
 impl Literal {
     pub fn new(value: String, ty: Option<TypeKind>) -> Self {
-        Self { value, ty }
+        Self {
+            value,
+            ty,
+            span: 0..0,
+        }
     }
 }
 
@@ -188,11 +272,17 @@ impl MemberListNode {
 pub struct Array {
     pub values: Vec<Expression>,
     pub ty: Option<TypeKind>,
+    /// The span of the array, from `[` to `]`.
+    pub span: Range<usize>,
 }
 
 impl Array {
     pub fn new(values: Vec<Expression>, ty: Option<TypeKind>) -> Array {
-        Array { values, ty }
+        Array {
+            values,
+            ty,
+            span: 0..0,
+        }
     }
 }
 
@@ -204,17 +294,24 @@ pub struct Object {
     /// This is used to validate the object.
     /// However it can be None if the object is annonymous.
     pub ty: Option<TypeKind>,
+    /// The span of the object, from `{` to `}`.
+    pub span: Range<usize>,
 }
 
 impl Object {
     pub fn new(properties: Vec<ObjectProperty>, ty: Option<TypeKind>) -> Object {
-        Object { properties, ty }
+        Object {
+            properties,
+            ty,
+            span: 0..0,
+        }
     }
 
     pub fn empty() -> Object {
         Object {
             properties: Vec::new(),
             ty: None,
+            span: 0..0,
         }
     }
 }
@@ -225,11 +322,17 @@ pub struct ObjectProperty {
     pub name: String,
     /// The value of the property.
     pub value: Expression,
+    /// The span of the property, from its name to the end of its value.
+    pub span: Range<usize>,
 }
 
 impl ObjectProperty {
     pub fn new(name: String, value: Expression) -> ObjectProperty {
-        ObjectProperty { name, value }
+        ObjectProperty {
+            name,
+            value,
+            span: 0..0,
+        }
     }
 }
 #[derive(Debug, Clone)]
@@ -237,6 +340,8 @@ pub struct Operation {
     pub left: Box<Expression>,
     pub right: Box<Expression>,
     pub op: AnyOperation,
+    /// The span of the operation, from the left operand to the right one.
+    pub span: Range<usize>,
 }
 
 impl Operation {
@@ -245,6 +350,7 @@ impl Operation {
             left: Box::new(left),
             right: Box::new(right),
             op,
+            span: 0..0,
         }
     }
 }
@@ -265,6 +371,10 @@ pub enum Statement {
     Var(Variable),
     /// A const statement.
     Const(Variable),
+    /// A var statement that destructures a tuple.
+    /// For example:
+    /// - `var (a, b) = pair;`
+    Destructure(Destructure),
     /// A static statement.
     Static(Static),
     /// A function declaration.
@@ -302,6 +412,21 @@ pub enum Statement {
     /// For example:
     /// - `return 1`
     Return(Return),
+    /// An if statement, an `else if` is the `else` block of the statement.
+    /// For example:
+    /// - `if (x is int) { log(x); }`
+    /// - `if (ready) { start(); } else { wait(); }`
+    If(If),
+    /// A try statement, with the errors it catches.
+    /// For example:
+    /// - `try { load(); } catch (e: IOError) { log(e); }`
+    /// - `try { load(); } finally { close(); }`
+    Try(Try),
+    /// A throw statement.
+    ///
+    /// For example:
+    /// - `throw new Error("failed");`
+    Throw(Expression),
     /// A macro invocation.
     /// For example:
     /// - `php!( "hello" )`
@@ -310,6 +435,23 @@ pub enum Statement {
 }
 
 impl Statement {
+    /// The span of the statement in its source, `None` for statements that don't keep one
+    /// and for statements that weren't parsed from a source.
+    pub fn span(&self) -> Option<Range<usize>> {
+        let span = match self {
+            Statement::Var(var) | Statement::Const(var) => var.span.clone(),
+            Statement::Destructure(destructure) => destructure.span.clone(),
+            Statement::Function(func) => func.span.start..func.body_span.end,
+            Statement::Class(class) => class.span.start..class.body_span.end,
+            Statement::Return(ret) => ret.span.clone(),
+            Statement::If(i) => i.span.clone(),
+            Statement::Throw(expr) => return expr.span(),
+            Statement::Static(s) => return s.statement.span(),
+            _ => return None,
+        };
+        Some(span).filter(|span| !span.is_empty())
+    }
+
     pub fn get_block(&self) -> Option<Vec<Expression>> {
         match self {
             Statement::Block(v) => Some(v.clone()),
@@ -439,7 +581,7 @@ impl Statement {
 // }}
 
 // Visibility {{
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Visibility {
     /// Public visibility. Every module can see this.
     Public,
@@ -481,23 +623,55 @@ impl Static {
 }
 
 // Classes {{
+/// An attribute placed before a class.
+/// For example:
+/// - `#[Service]`
+/// - `#[Singleton(CacheContract)]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribute {
+    pub name: String,
+    /// The arguments given in parenthesis, eg: `CacheContract` in `#[Singleton(CacheContract)]`
+    pub arguments: Vec<String>,
+}
+
+impl Attribute {
+    pub fn new(name: String, arguments: Vec<String>) -> Self {
+        Attribute { name, arguments }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Class {
     pub name: String,
+    /// The attributes placed before the class.
+    pub attributes: Vec<Attribute>,
+    /// The type parameters of the class.
+    /// For example:
+    /// - `T` in `class Box<T> {}`
+    pub generics: Vec<TypeParam>,
     pub extends: Option<String>,
     pub implements: Option<Vec<String>>,
     pub body: ClassBody,
+    /// The id of the node of the class, see `AstBody::declaration`.
     pub node_id: u64,
+    /// The range of the `class` keyword and the name.
+    pub span: Range<usize>,
+    /// The range of the body, from `{` to `}`.
+    pub body_span: Range<usize>,
 }
 
 impl Class {
     pub fn new() -> Self {
         Class {
             name: String::new(),
+            attributes: Vec::new(),
+            generics: Vec::new(),
             extends: None,
             implements: None,
             body: ClassBody::new(),
             node_id: 0,
+            span: 0..0,
+            body_span: 0..0,
         }
     }
 }
@@ -508,6 +682,36 @@ pub struct ClassProperty {
     pub visibility: Visibility,
     pub ty: Option<TypeKind>,
     pub assignment: Option<Expression>,
+    /// How the property is read and written, eg: `name: string { get; set; }`.
+    /// A property without accessors is read and written directly.
+    pub accessors: Vec<Accessor>,
+    /// The span of the declaration, from the name to the semicolon or the accessors.
+    pub span: Range<usize>,
+}
+
+/// Whether an accessor reads or writes its property.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AccessorKind {
+    Get,
+    Set,
+}
+
+impl fmt::Display for AccessorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AccessorKind::Get => write!(f, "get"),
+            AccessorKind::Set => write!(f, "set"),
+        }
+    }
+}
+
+/// An accessor of a property, eg: `get;` or `get name(): string { return this.name; }`.
+#[derive(Debug, Clone)]
+pub struct Accessor {
+    pub kind: AccessorKind,
+    /// The explicit accessor, named after its property.
+    /// `None` when it reads or writes the property as it is, eg: `get;`.
+    pub body: Option<Function>,
 }
 
 impl ClassProperty {
@@ -522,8 +726,14 @@ impl ClassProperty {
             visibility,
             ty,
             assignment,
+            accessors: Vec::new(),
+            span: 0..0,
         }
     }
+
+    pub fn accessor(&self, kind: AccessorKind) -> Option<&Accessor> {
+        self.accessors.iter().find(|accessor| accessor.kind == kind)
+    }
 }
 
 /// Unlike the Statement enum, this contains a special list of statements.
@@ -543,6 +753,37 @@ impl ClassBody {
             other: Vec::new(),
         }
     }
+
+    /// The instance properties of the class, with or without a visibility.
+    pub fn all_properties(&self) -> impl Iterator<Item = &ClassProperty> {
+        self.properties
+            .iter()
+            .chain(self.other.iter().filter_map(|other| match other {
+                ClassAllowedStatement::Property(property) => Some(property),
+                _ => None,
+            }))
+    }
+
+    /// The static properties of the class, read as `User::count`.
+    pub fn static_properties(&self) -> impl Iterator<Item = &ClassProperty> {
+        self.other.iter().filter_map(|other| match other {
+            ClassAllowedStatement::Static(inner) => match inner.as_ref() {
+                ClassAllowedStatement::Property(property) => Some(property),
+                _ => None,
+            },
+            _ => None,
+        })
+    }
+
+    pub fn property_mut(&mut self, name: &str) -> Option<&mut ClassProperty> {
+        self.properties
+            .iter_mut()
+            .chain(self.other.iter_mut().filter_map(|other| match other {
+                ClassAllowedStatement::Property(property) => Some(property),
+                _ => None,
+            }))
+            .find(|property| property.name == name)
+    }
 }
 
 /// Class bodies ares special because they can contain certain statements,
@@ -551,6 +792,8 @@ impl ClassBody {
 pub enum ClassAllowedStatement {
     Property(ClassProperty),
     Method(Function),
+    /// A constant of the class, eg: `const LIMIT = 10;`, accessed as `User::LIMIT`.
+    Constant(Variable),
     Macro(CompilerMacro),
     Import(Path),
     Static(Box<ClassAllowedStatement>),
@@ -560,6 +803,23 @@ impl ClassAllowedStatement {
     pub fn new_static(s: ClassAllowedStatement) -> Self {
         ClassAllowedStatement::Static(Box::new(s))
     }
+
+    /// The method the statement declares, static or not.
+    pub fn method(&self) -> Option<&Function> {
+        match self {
+            ClassAllowedStatement::Method(method) => Some(method),
+            ClassAllowedStatement::Static(inner) => inner.method(),
+            _ => None,
+        }
+    }
+
+    pub fn method_mut(&mut self) -> Option<&mut Function> {
+        match self {
+            ClassAllowedStatement::Method(method) => Some(method),
+            ClassAllowedStatement::Static(inner) => inner.method_mut(),
+            _ => None,
+        }
+    }
 }
 
 /// An enum.
@@ -592,21 +852,85 @@ pub struct EnumVariant {
 #[derive(Debug, Clone)]
 pub struct Return {
     pub expression: Option<Expression>,
+    /// The span of the statement, from `return` to the semicolon.
+    pub span: Range<usize>,
 }
 
 impl Return {
     pub fn new(expression: Option<Expression>) -> Self {
-        Return { expression }
+        Return {
+            expression,
+            span: 0..0,
+        }
     }
 }
 // }}
 
+/// An `if` statement, eg: `if (x is int) { ... } else { ... }`
+#[derive(Debug, Clone)]
+pub struct If {
+    pub condition: Expression,
+    pub body: Box<Statement>,
+    /// The `else` block, or the next `if` of an `else if`.
+    pub otherwise: Option<Box<Statement>>,
+    /// The range of the statement, from the `if` keyword to the end of the last block.
+    pub span: Range<usize>,
+}
+
+/// A test of whether a value is of a type, eg: `x is int`.
+#[derive(Debug, Clone)]
+pub struct TypeTest {
+    pub value: Box<Expression>,
+    pub ty: TypeKind,
+    /// The range of the test, from the value to the end of the type.
+    pub span: Range<usize>,
+}
+
+/// A `try` block, it needs at least a `catch` or a `finally`.
+#[derive(Debug, Clone)]
+pub struct Try {
+    pub body: Box<Statement>,
+    /// The catch clauses, in the order they are tried.
+    pub catches: Vec<Catch>,
+    pub finally: Option<Box<Statement>>,
+    /// The range of the statement, from the `try` keyword to the end of the last block.
+    pub span: Range<usize>,
+}
+
+/// A `catch` clause, eg: `catch (e: IOError) { ... }`
+#[derive(Debug, Clone)]
+pub struct Catch {
+    /// The name the error is bound to in the body.
+    pub name: String,
+    /// The type of error caught, every error when `None`.
+    pub ty: Option<TypeKind>,
+    pub body: Box<Statement>,
+}
+// }}
+
+/// A declaration that binds each value of a tuple to its own name.
+#[derive(Debug, Clone)]
+pub struct Destructure {
+    /// The names in the order of the tuple values, eg: `a, b` in `var (a, b) = pair;`
+    pub names: Vec<String>,
+    /// The tuple type, if annotated, eg: `var (a, b): (int, string) = pair;`
+    pub ty: Option<TypeKind>,
+    pub assignment: Expression,
+    /// The range of the declaration, from the `var` keyword to the statement end.
+    pub span: Range<usize>,
+}
+// }}
+
 // Functions {{
 /// A function call or method call.
 #[derive(Debug, Clone)]
 pub struct Function {
     /// The name of the function.
     pub name: Option<String>,
+    /// The type parameters of the function.
+    /// For example:
+    /// - `T, U` in `fn map<T, U>(items: T[], f: any): U[] {}`
+    pub generics: Vec<TypeParam>,
     /// The arguments to the function.
     pub inputs: Vec<FunctionInput>,
     /// The body of the function,
@@ -615,19 +939,78 @@ pub struct Function {
     pub outputs: Option<TypeKind>,
     /// The visibilty of the function.
     pub visibility: Visibility,
-    /// The id for the given function.
+    /// The id of the node of the function, `0` for a function that isn't at the top level.
+    /// See `AstBody::declaration`.
     pub node_id: u64,
+    /// The range of the `fn` keyword and the name, if the function has one, `async` included.
+    pub span: Range<usize>,
+    /// The range of the body, from `{` to `}`.
+    pub body_span: Range<usize>,
+    /// Whether calling the function has side effects, see `infer::purity`.
+    pub purity: Purity,
+    /// Whether the function was declared `async`, only async functions can `await`.
+    pub is_async: bool,
+    /// The attributes placed before a method, eg: `#[Override]`.
+    pub attributes: Vec<Attribute>,
+}
+
+/// Whether a function has side effects.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Purity {
+    /// The function hasn't been analyzed.
+    #[default]
+    Unknown,
+    /// Calling the function only computes its result.
+    Pure,
+    /// Calling the function has a side effect, with the first one found.
+    /// For example:
+    /// - calls `echo`, which does I/O
+    Impure(String),
+}
+
+impl Purity {
+    pub fn is_pure(&self) -> bool {
+        *self == Purity::Pure
+    }
+}
+
+impl fmt::Display for Purity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Purity::Unknown => write!(f, "unknown"),
+            Purity::Pure => write!(f, "pure"),
+            Purity::Impure(reason) => write!(f, "impure, {}", reason),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct FunctionInput {
     pub name: String,
     pub ty: Option<TypeKind>,
+    /// Whether the input collects the rest of the arguments, eg: `...names: string`.
+    /// Only the last input of a function can.
+    pub variadic: bool,
+    /// The span of the input, from the name or the `...` to the type.
+    pub span: Range<usize>,
 }
 
 impl FunctionInput {
     pub fn new(name: String, ty: Option<TypeKind>) -> Self {
-        FunctionInput { name, ty }
+        FunctionInput {
+            name,
+            ty,
+            variadic: false,
+            span: 0..0,
+        }
+    }
+
+    /// The type of the input inside of the function, a variadic input is an array of its type.
+    pub fn body_type(&self) -> Option<TypeKind> {
+        match (&self.ty, self.variadic) {
+            (Some(ty), true) => Some(TypeKind::BuiltIn(BuiltInType::Array(Box::new(ty.clone())))),
+            (ty, _) => ty.clone(),
+        }
     }
 }
 
@@ -639,14 +1022,25 @@ impl FunctionInput {
 pub struct Call {
     /// The name of the function being called.
     /// This is the name of the function, not the name of the variable.
-    pub name: String,
+    pub name: Symbol,
     /// The arugments being passed to the function.
     pub arguments: Vec<Expression>,
+    /// The type arguments given explicitly.
+    /// For example:
+    /// - `int, string` in `map<int, string>(items, f)`
+    pub generics: Vec<TypeKind>,
+    /// The range of the call, from the name to the closing parenthesis.
+    pub span: Range<usize>,
 }
 
 impl Call {
-    pub fn new(name: String, arguments: Vec<Expression>) -> Self {
-        Call { name, arguments }
+    pub fn new(name: impl Into<Symbol>, arguments: Vec<Expression>) -> Self {
+        Call {
+            name: name.into(),
+            arguments,
+            generics: Vec::new(),
+            span: 0..0,
+        }
     }
 }
 
@@ -660,11 +1054,22 @@ pub struct NewCall {
     pub name: String,
     /// The arugments being passed to the constructor.
     pub arguments: Vec<Expression>,
+    /// The type arguments given explicitly.
+    /// For example:
+    /// - `int` in `new Box<int>(5)`
+    pub generics: Vec<TypeKind>,
+    /// The span of the call, from `new` to the closing parenthesis.
+    pub span: Range<usize>,
 }
 
 impl NewCall {
     pub fn new(name: String, arguments: Vec<Expression>) -> Self {
-        NewCall { name, arguments }
+        NewCall {
+            name,
+            arguments,
+            generics: Vec::new(),
+            span: 0..0,
+        }
     }
 }
 
@@ -686,32 +1091,47 @@ pub struct MethodCall {
 // Variables & Types {{
 #[derive(Debug, Clone)]
 pub struct Variable {
-    pub name: String,
+    pub name: Symbol,
+    /// The id of the node of the declaration, `0` for one that isn't at the top level.
     pub node_id: u64,
+    /// The type the variable is annotated with.
     pub ty: Option<TypeKind>,
+    /// The type of the assignment of a variable without an annotation,
+    /// `None` until `infer::infer_variables` runs or when it can't be told.
+    pub inferred: Option<TypeKind>,
     pub visibility: Visibility,
     pub assignment: Option<Expression>,
+    /// The range of the declaration, from the `var` or `const` keyword to the
+    /// statement end.
+    pub span: Range<usize>,
 }
 
 impl Variable {
     pub fn new(
-        name: String,
+        name: impl Into<Symbol>,
         ty: Option<TypeKind>,
         visibility: Visibility,
         assignment: Option<Expression>,
     ) -> Self {
         Self {
-            name,
+            name: name.into(),
             node_id: 0,
             ty,
+            inferred: None,
             visibility,
             assignment,
+            span: 0..0,
         }
     }
 
     pub fn is_uninit(&self) -> bool {
         self.assignment.is_none()
     }
+
+    /// The type of the variable, the annotation or else the inferred type.
+    pub fn type_of(&self) -> Option<&TypeKind> {
+        self.ty.as_ref().or(self.inferred.as_ref())
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -720,35 +1140,62 @@ pub struct Path {
     /// For example:
     /// - `foo`
     /// - `std` in `std::io` etc.
-    pub name: String,
+    pub name: Symbol,
     /// The parts of the import
     /// For example:
     /// - `foo` in `bar::foo`
     /// - `bar, baz` in `foo::{bar, baz}`
     pub parts: Vec<Path>,
+    /// The name the import is bound to instead of its own.
+    /// For example:
+    /// - `baz` in `foo::bar as baz`
+    pub alias: Option<String>,
+    /// The span of the whole path of an import, the parts of a path don't have one.
+    pub span: Range<usize>,
 }
 
 impl Path {
-    pub fn new(name: String) -> Self {
+    pub fn new(name: impl Into<Symbol>) -> Self {
         Self {
-            name,
+            name: name.into(),
             parts: Vec::new(),
+            alias: None,
+            span: 0..0,
         }
     }
 
     pub fn from(name: String, parts: Vec<String>) -> Self {
-        let mut path = Path {
-            name,
-            parts: Vec::new(),
-        };
+        let mut path = Path::new(name);
         for part in parts {
-            path.parts.push(Path {
-                name: part,
-                parts: Vec::new(),
-            });
+            path.parts.push(Path::new(part));
         }
         path
     }
+
+    /// Flattens the path into every single item it imports, eg:
+    /// `foo::{bar, baz::qux}` -> `[foo, bar]`, `[foo, baz, qux]`
+    pub fn leaves(&self) -> Vec<Vec<&Path>> {
+        if self.parts.is_empty() {
+            return vec![vec![self]];
+        }
+        let mut leaves = Vec::new();
+        for part in self.parts.iter() {
+            for mut leaf in part.leaves() {
+                leaf.insert(0, self);
+                leaves.push(leaf);
+            }
+        }
+        leaves
+    }
+
+    /// The name this path is bound to in the importing scope, eg: `bar` in `foo::bar`.
+    /// This is only meaningful for a single item import.
+    pub fn binding(&self) -> &str {
+        match self.parts.last() {
+            Some(part) => part.binding(),
+            None => self.alias.as_deref().unwrap_or(&self.name),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -772,6 +1219,11 @@ impl Namespace {
 // }}
 
 // Macros {{
+/// The macros the compiler knows how to invoke.
+/// - `php! { ... }` passes its body through to the generated php as is.
+/// - `target! { php => { ... }, _ => { ... } }` only compiles the arm of the language being generated.
+pub const KNOWN_MACROS: &[&str] = &["php", "target"];
+
 #[derive(Debug, Clone)]
 pub struct CompilerMacro {
     /// The name of the macro to invoke,
@@ -782,31 +1234,228 @@ pub struct CompilerMacro {
     /// The body of the macro, this will be traversed
     /// during macro invokations.
     pub body: String,
+    /// The parsed arms of a `target!` macro, empty for every other macro.
+    pub arms: Vec<TargetArm>,
+}
+
+impl CompilerMacro {
+    pub fn new(name: String, body: String) -> Self {
+        CompilerMacro {
+            name,
+            body,
+            arms: Vec::new(),
+        }
+    }
+
+    /// The arm that is compiled for the given language.
+    /// An arm that names the language wins over a `_` arm.
+    pub fn arm_for(&self, target: &str) -> Option<&TargetArm> {
+        self.arms
+            .iter()
+            .find(|arm| arm.targets.iter().any(|t| t == target))
+            .or_else(|| {
+                self.arms
+                    .iter()
+                    .find(|arm| arm.targets.iter().any(|t| t == "_"))
+            })
+    }
+}
+
+/// A single arm of a `target!` macro.
+/// For example:
+/// - `php | js => { ... }`
+/// - `_ => { ... }`
+#[derive(Debug, Clone)]
+pub struct TargetArm {
+    /// The languages the arm is compiled for, `_` is any language without an arm of its own.
+    pub targets: Vec<String>,
+    pub body: AstBody,
 }
 // }}
 
 // AST {{
-#[derive(Debug, Clone)]
+/// The top level nodes of a source, see `arena`.
+/// Clones share their nodes until one of them changes them, so a clone is cheap.
+#[derive(Clone)]
 pub struct AstBody {
     // todo: Compiler flags
     flags: u64,
-    program: Vec<Node>,
+    /// The id of the context the body was parsed in, see `NameGenerator`.
+    context: u64,
+    arena: Arc<AstArena>,
+    /// The ids of the nodes, in the order of the program.
+    program: Vec<NodeId>,
+    /// The lint levels set by attributes, eg: `#[allow(unused_variable)]`.
+    lint_scopes: Vec<LintScope>,
+}
+
+impl fmt::Debug for AstBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AstBody")
+            .field("flags", &self.flags)
+            .field("program", &self.get_program())
+            .field("lint_scopes", &self.lint_scopes)
+            .finish()
+    }
 }
 
 impl AstBody {
     pub fn new() -> Self {
+        Self::for_context(0)
+    }
+
+    /// An empty body of the source of a context.
+    pub fn for_context(context: u64) -> Self {
         AstBody {
             flags: 0,
+            context,
+            arena: Arc::new(AstArena::new()),
             program: Vec::new(),
+            lint_scopes: Vec::new(),
         }
     }
 
-    pub fn push_node(&mut self, node: Node) {
-        self.program.push(node);
+    /// The id of the context the body was parsed in, `0` for a body that was built.
+    pub fn context(&self) -> u64 {
+        self.context
     }
 
-    pub fn get_program(&self) -> &Vec<Node> {
+    pub fn push_lint_scope(&mut self, scope: LintScope) {
+        self.lint_scopes.push(scope);
+    }
+
+    pub fn lint_scopes(&self) -> &[LintScope] {
+        &self.lint_scopes
+    }
+
+    /// Adds a node at the end of the program.
+    /// A declaration is given the id of its node, eg: `Function::node_id`.
+    pub fn push_node(&mut self, node: Node) -> NodeId {
+        self.insert_node(self.program.len(), node)
+    }
+
+    /// Adds a node at the given position of the program, the ids of the others don't change.
+    pub fn insert_node(&mut self, index: usize, mut node: Node) -> NodeId {
+        let arena = Arc::make_mut(&mut self.arena);
+        let id = NodeId::from_index(arena.len());
+        if let NodeKind::Statement(stmt) = &mut node.inner {
+            match stmt {
+                Statement::Var(var) | Statement::Const(var) => var.node_id = id.as_u64(),
+                Statement::Function(func) => func.node_id = id.as_u64(),
+                Statement::Class(class) => class.node_id = id.as_u64(),
+                _ => {}
+            }
+        }
+        arena.alloc(node);
+        self.program.insert(index, id);
+        id
+    }
+
+    /// The nodes of the program, in order.
+    pub fn get_program(&self) -> Vec<&Node> {
+        self.program
+            .iter()
+            .map(|id| self.arena.get(*id).unwrap())
+            .collect()
+    }
+
+    /// The nodes of the program to change, in order.
+    /// The nodes are copied first when the arena is shared with another body.
+    pub fn get_program_mut(&mut self) -> Vec<&mut Node> {
+        Arc::make_mut(&mut self.arena).get_many_mut(&self.program)
+    }
+
+    /// The ids of the nodes of the program, in order.
+    pub fn ids(&self) -> &[NodeId] {
         &self.program
     }
+
+    /// The nodes of the program along with their ids, in order.
+    pub fn nodes(&self) -> impl Iterator<Item = (NodeId, &Node)> {
+        self.program
+            .iter()
+            .map(|id| (*id, self.arena.get(*id).unwrap()))
+    }
+
+    pub fn node(&self, id: NodeId) -> Option<&Node> {
+        self.arena.get(id)
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> Option<&mut Node> {
+        Arc::make_mut(&mut self.arena).get_mut(id)
+    }
+
+    /// The node of a declaration, by the id it was given, see `push_node`.
+    pub fn declaration(&self, node_id: u64) -> Option<&Node> {
+        self.node(NodeId::from_u64(node_id)?)
+    }
+
+    /// The nodes of the program a range overlaps, in order.
+    /// An empty range finds the nodes it is at, eg: the node under the cursor.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::compiler::{parser::Parser, CompilerOptions};
+    /// // var x = 1;
+    /// // fn main() {}
+    /// # let source = "var x = 1;\nfn main() {}\n";
+    /// # let body = Parser::new(CompilerOptions::default())
+    /// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+    /// body.nodes_in_range(14..14).next(); // the node of `main`
+    /// # Ok::<(), surn::report::Report>(())
+    /// ```
+    pub fn nodes_in_range(&self, range: Range<usize>) -> impl Iterator<Item = (NodeId, &Node)> {
+        self.nodes()
+            .filter(move |(_, node)| match range.is_empty() {
+                true => node.start() <= range.start && range.start <= node.end(),
+                false => node.start() < range.end && range.start < node.end(),
+            })
+    }
+
+    /// The functions declared at the top level and in namespaces, in order.
+    pub fn functions(&self) -> impl Iterator<Item = &Function> {
+        self.declarations().filter_map(|stmt| match stmt {
+            Statement::Function(func) => Some(func),
+            _ => None,
+        })
+    }
+
+    /// The classes declared at the top level and in namespaces, in order.
+    pub fn classes(&self) -> impl Iterator<Item = &Class> {
+        self.declarations().filter_map(|stmt| match stmt {
+            Statement::Class(class) => Some(class),
+            _ => None,
+        })
+    }
+
+    /// The statements of the program, with the statements of a namespace in its place.
+    fn declarations(&self) -> impl Iterator<Item = &Statement> {
+        self.nodes()
+            .filter_map(|(_, node)| match &node.inner {
+                NodeKind::Statement(stmt) => Some(stmt),
+                NodeKind::Expression(_) => None,
+            })
+            .flat_map(|stmt| match stmt {
+                Statement::Namespace(Namespace {
+                    body: Some(body), ..
+                }) => match body.as_ref() {
+                    Statement::Block(exprs) => exprs
+                        .iter()
+                        .filter_map(|expr| match expr {
+                            Expression::Statement(stmt) => Some(stmt.as_ref()),
+                            _ => None,
+                        })
+                        .collect(),
+                    stmt => vec![stmt],
+                },
+                stmt => vec![stmt],
+            })
+    }
+
+    /// Whether both bodies share the same nodes, which is true of a clone until one of
+    /// them is changed.
+    pub fn shares_nodes(&self, other: &AstBody) -> bool {
+        Arc::ptr_eq(&self.arena, &other.arena)
+    }
 }
 //}}
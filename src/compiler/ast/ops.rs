@@ -1,3 +1,5 @@
+use std::fmt;
+
 #[derive(Debug, Clone)]
 pub enum AnyOperation {
     BinOp(BinOp),
@@ -34,6 +36,7 @@ impl AnyOperation {
             "^" => Some(AnyOperation::BinOp(BinOp::Caret)),
             "&&" => Some(AnyOperation::LogicalOp(LogicalOp::And)),
             "||" => Some(AnyOperation::LogicalOp(LogicalOp::Or)),
+            "??" => Some(AnyOperation::LogicalOp(LogicalOp::Coalasce)),
             "!" => Some(AnyOperation::BinOp(BinOp::Not)),
             "~" => Some(AnyOperation::BinOp(BinOp::Flip)),
             "-" => Some(AnyOperation::BinOp(BinOp::Minus)),
@@ -46,6 +49,73 @@ impl AnyOperation {
     }
 }
 
+/// Writes the operator as it is written in surn source.
+/// Unary operators are written without their operand, eg: `++` for both `++x` and `x++`.
+impl fmt::Display for AnyOperation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let op = match self {
+            AnyOperation::BinOp(op) => match op {
+                BinOp::Plus => "+",
+                BinOp::Minus => "-",
+                BinOp::Star => "*",
+                BinOp::Slash => "/",
+                BinOp::Percent => "%",
+                BinOp::Caret => "^",
+                BinOp::Not => "!",
+                BinOp::Flip => "~",
+                BinOp::And => "&",
+                BinOp::Or => "|",
+                BinOp::Shl => "<<",
+                BinOp::Shr => ">>",
+                BinOp::UShr => ">>>",
+            },
+            AnyOperation::UnaryOp(op) => match op {
+                UnaryOp::IncP | UnaryOp::Inc => "++",
+                UnaryOp::DecP | UnaryOp::Dec => "--",
+                UnaryOp::Neg => "-",
+                UnaryOp::Pos => "+",
+                UnaryOp::Not => "!",
+                UnaryOp::Delete => "delete",
+                UnaryOp::Object => "{}",
+            },
+            AnyOperation::LogicalOp(op) => match op {
+                LogicalOp::And => "&&",
+                LogicalOp::Or => "||",
+                LogicalOp::Coalasce => "??",
+            },
+            AnyOperation::ComparisonOp(op) => match op {
+                ComparisonOp::Eq => "==",
+                ComparisonOp::NotEq => "!=",
+                ComparisonOp::GreaterThan => ">",
+                ComparisonOp::GreaterThanOrEqual => ">=",
+                ComparisonOp::LessThan => "<",
+                ComparisonOp::LessThanOrEqual => "<=",
+                ComparisonOp::Contains => "contains",
+                ComparisonOp::In => "in",
+                ComparisonOp::InstanceOf => "instanceof",
+            },
+            AnyOperation::AssignmentOp(op) => match op {
+                AssignmentOp::Eq => "=",
+                AssignmentOp::Add => "+=",
+                AssignmentOp::Sub => "-=",
+                AssignmentOp::Mul => "*=",
+                AssignmentOp::Div => "/=",
+                AssignmentOp::Rem => "%=",
+                AssignmentOp::BitAnd => "&=",
+                AssignmentOp::BitOr => "|=",
+                AssignmentOp::BitXor => "^=",
+                AssignmentOp::BitSh1 => "<<=",
+                AssignmentOp::BitShr => ">>=",
+                AssignmentOp::BitUshr => ">>>=",
+                AssignmentOp::BoolAnd => "&&=",
+                AssignmentOp::BoolOr => "||=",
+                AssignmentOp::Coalesce => "??=",
+            },
+        };
+        write!(f, "{}", op)
+    }
+}
+
 // Binary Operators
 #[derive(Clone, PartialEq, Debug)]
 pub enum BinOp {
@@ -0,0 +1,429 @@
+//! Readable dumps of a body, for debugging and for golden tests of the parser.
+//!
+//! `AstBody::to_pretty_string` prints a node per line, indented under the node it is in,
+//! with the span of the nodes that keep one:
+//! ```text
+//! function pub add: int @0..49
+//!   param a: int @7..13
+//!   param b: int @15..21
+//!   block
+//!     return @34..47
+//!       operation + @41..46
+//!         name a @41..42
+//!         name b @45..46
+//! ```
+//! `AstBody::to_sexpr_string` prints the same tree as S-expressions without spans,
+//! so moving code around doesn't change it, eg: `(function pub add: int (param a: int) ...)`.
+use std::{fmt::Write, ops::Range};
+
+use super::{
+    types::{BuiltInType, TypeKind, TypeParam},
+    Accessor, AstBody, Class, ClassAllowedStatement, ClassProperty, Expression, Function,
+    FunctionInput, MemberLookup, NodeKind, Path, Statement, Variable, Visibility,
+};
+
+impl AstBody {
+    /// The nodes of the body as an indented tree, a node per line.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::compiler::{parser::Parser, CompilerOptions};
+    /// // var count: int = 0;
+    /// # let source = "var count: int = 0;\n";
+    /// # let body = Parser::new(CompilerOptions::default())
+    /// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+    /// body.to_pretty_string(); // "var priv count: int @0..19\n  literal 0: int @17..18\n"
+    /// # Ok::<(), surn::report::Report>(())
+    /// ```
+    pub fn to_pretty_string(&self) -> String {
+        let mut out = String::new();
+        for tree in self.trees() {
+            tree.write_lines(&mut out, 0);
+        }
+        out
+    }
+
+    /// The nodes of the body as S-expressions without spans, a top level node per line.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::compiler::{parser::Parser, CompilerOptions};
+    /// // var count: int = 0;
+    /// # let source = "var count: int = 0;\n";
+    /// # let body = Parser::new(CompilerOptions::default())
+    /// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+    /// body.to_sexpr_string(); // "(var priv count: int (literal 0: int))\n"
+    /// # Ok::<(), surn::report::Report>(())
+    /// ```
+    pub fn to_sexpr_string(&self) -> String {
+        let mut out = String::new();
+        for tree in self.trees() {
+            tree.write_sexpr(&mut out);
+            out.push('\n');
+        }
+        out
+    }
+
+    fn trees(&self) -> Vec<Tree> {
+        self.get_program()
+            .into_iter()
+            .filter_map(|node| match &node.inner {
+                NodeKind::Statement(stmt) => Some(statement(stmt)),
+                NodeKind::Expression(expr) => expression(expr),
+            })
+            .collect()
+    }
+}
+
+/// A node as it is printed.
+struct Tree {
+    kind: &'static str,
+    /// What tells the node apart from others of its kind, eg: the name and the type.
+    detail: String,
+    span: Option<Range<usize>>,
+    children: Vec<Tree>,
+}
+
+impl Tree {
+    fn new(kind: &'static str, detail: impl Into<String>) -> Self {
+        Tree {
+            kind,
+            detail: detail.into(),
+            span: None,
+            children: Vec::new(),
+        }
+    }
+
+    /// Keeps the span, unless it is empty because the node wasn't parsed from a source.
+    fn span(mut self, span: Option<Range<usize>>) -> Self {
+        self.span = span.filter(|span| !span.is_empty());
+        self
+    }
+
+    fn child(mut self, child: Tree) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    fn children(mut self, children: impl IntoIterator<Item = Tree>) -> Self {
+        self.children.extend(children);
+        self
+    }
+
+    fn label(&self) -> String {
+        match self.detail.is_empty() {
+            true => self.kind.to_string(),
+            false => format!("{} {}", self.kind, self.detail),
+        }
+    }
+
+    fn write_lines(&self, out: &mut String, depth: usize) {
+        let _ = write!(out, "{}{}", "  ".repeat(depth), self.label());
+        if let Some(span) = &self.span {
+            let _ = write!(out, " @{}..{}", span.start, span.end);
+        }
+        out.push('\n');
+        for child in self.children.iter() {
+            child.write_lines(out, depth + 1);
+        }
+    }
+
+    fn write_sexpr(&self, out: &mut String) {
+        let _ = write!(out, "({}", self.label());
+        for child in self.children.iter() {
+            out.push(' ');
+            child.write_sexpr(out);
+        }
+        out.push(')');
+    }
+}
+
+fn visibility(visibility: &Visibility) -> &'static str {
+    match visibility {
+        Visibility::Public => "pub ",
+        Visibility::Private => "priv ",
+        Visibility::Protected => "prot ",
+        Visibility::Module => "",
+    }
+}
+
+/// The type of a declaration as it is written after its name, eg: `: int`.
+fn typed(ty: Option<&impl std::fmt::Display>) -> String {
+    ty.map(|ty| format!(": {}", ty)).unwrap_or_default()
+}
+
+fn generics(params: &[TypeParam]) -> String {
+    if params.is_empty() {
+        return String::new();
+    }
+    let names: Vec<String> = params
+        .iter()
+        .map(|param| match (&param.name, param.bound()) {
+            (Some(name), Some(bound)) => format!("{} extends {}", name, bound),
+            (Some(name), None) => name.clone(),
+            (None, _) => param.kind.to_string(),
+        })
+        .collect();
+    format!("<{}>", names.join(", "))
+}
+
+fn path(path: &Path) -> String {
+    let mut out = path.name.to_string();
+    match path.parts.len() {
+        0 => {}
+        1 => out = format!("{}::{}", out, self::path(&path.parts[0])),
+        _ => {
+            let parts: Vec<String> = path.parts.iter().map(self::path).collect();
+            out = format!("{}::{{{}}}", out, parts.join(", "));
+        }
+    }
+    if let Some(alias) = &path.alias {
+        out = format!("{} as {}", out, alias);
+    }
+    out
+}
+
+fn statement(stmt: &Statement) -> Tree {
+    match stmt {
+        Statement::Var(var) => variable("var", var),
+        Statement::Const(var) => variable("const", var),
+        Statement::Destructure(destructure) => Tree::new(
+            "destructure",
+            format!(
+                "{}{}",
+                destructure.names.join(", "),
+                typed(destructure.ty.as_ref())
+            ),
+        )
+        .span(Some(destructure.span.clone()))
+        .children(expression(&destructure.assignment)),
+        Statement::Static(s) => {
+            Tree::new("static", visibility(&s.visibility).trim_end()).child(statement(&s.statement))
+        }
+        Statement::Function(func) => function(func),
+        Statement::Class(class) => self::class(class),
+        Statement::Enum(e) => {
+            Tree::new("enum", e.name.clone()).children(e.variants.iter().map(|variant| {
+                Tree::new("variant", variant.name.clone())
+                    .children(variant.value.as_ref().and_then(expression))
+            }))
+        }
+        Statement::Block(exprs) => {
+            Tree::new("block", "").children(exprs.iter().filter_map(expression))
+        }
+        Statement::Import(import) => {
+            Tree::new("import", path(import)).span(Some(import.span.clone()))
+        }
+        Statement::Namespace(namespace) => Tree::new("namespace", path(&namespace.path))
+            .children(namespace.body.as_deref().map(statement)),
+        Statement::TypeDef(def) => Tree::new(
+            "type",
+            format!(
+                "{}{} = {}",
+                def.name,
+                generics(def.params.as_deref().unwrap_or_default()),
+                def.kind
+            ),
+        ),
+        Statement::Return(ret) => Tree::new("return", "")
+            .span(Some(ret.span.clone()))
+            .children(ret.expression.as_ref().and_then(expression)),
+        Statement::If(i) => Tree::new("if", "")
+            .span(Some(i.span.clone()))
+            .children(expression(&i.condition))
+            .child(statement(&i.body))
+            .children(
+                i.otherwise
+                    .as_deref()
+                    .map(|otherwise| Tree::new("else", "").child(statement(otherwise))),
+            ),
+        Statement::Try(t) => Tree::new("try", "")
+            .span(Some(t.span.clone()))
+            .child(statement(&t.body))
+            .children(t.catches.iter().map(|catch| {
+                Tree::new(
+                    "catch",
+                    format!("{}{}", catch.name, typed(catch.ty.as_ref())),
+                )
+                .child(statement(&catch.body))
+            }))
+            .children(
+                t.finally
+                    .as_deref()
+                    .map(|finally| Tree::new("finally", "").child(statement(finally))),
+            ),
+        Statement::Throw(expr) => Tree::new("throw", "")
+            .span(expr.span())
+            .children(expression(expr)),
+        Statement::MacroInvocation(invocation) => Tree::new("macro", invocation.name.clone())
+            .children(
+                invocation.arms.iter().map(|arm| {
+                    Tree::new("arm", arm.targets.join(" | ")).children(arm.body.trees())
+                }),
+            ),
+    }
+}
+
+fn variable(kind: &'static str, var: &Variable) -> Tree {
+    Tree::new(
+        kind,
+        format!(
+            "{}{}{}",
+            visibility(&var.visibility),
+            var.name,
+            typed(var.ty.as_ref())
+        ),
+    )
+    .span(Some(var.span.clone()))
+    .children(var.assignment.as_ref().and_then(expression))
+}
+
+fn function(func: &Function) -> Tree {
+    let name = func.name.as_deref().unwrap_or("<anonymous>");
+    let detail = format!(
+        "{}{}{}{}{}",
+        visibility(&func.visibility),
+        if func.is_async { "async " } else { "" },
+        name,
+        generics(&func.generics),
+        typed(func.outputs.as_ref())
+    );
+    Tree::new("function", detail)
+        .span(Some(func.span.start..func.body_span.end))
+        .children(func.inputs.iter().map(input))
+        .child(statement(&func.body))
+}
+
+fn input(input: &FunctionInput) -> Tree {
+    let spread = if input.variadic { "..." } else { "" };
+    Tree::new(
+        "param",
+        format!("{}{}{}", spread, input.name, typed(input.ty.as_ref())),
+    )
+    .span(Some(input.span.clone()))
+}
+
+fn class(class: &Class) -> Tree {
+    let mut detail = format!("{}{}", class.name, generics(&class.generics));
+    if let Some(extends) = &class.extends {
+        let _ = write!(detail, " extends {}", extends);
+    }
+    if let Some(implements) = &class.implements {
+        let _ = write!(detail, " implements {}", implements.join(", "));
+    }
+    Tree::new("class", detail)
+        .span(Some(class.span.start..class.body_span.end))
+        .children(class.body.properties.iter().map(property))
+        .children(class.body.methods.iter().map(function))
+        .children(class.body.other.iter().map(class_statement))
+}
+
+fn property(property: &ClassProperty) -> Tree {
+    let detail = format!(
+        "{}{}{}",
+        visibility(&property.visibility),
+        property.name,
+        typed(property.ty.as_ref())
+    );
+    Tree::new("property", detail)
+        .span(Some(property.span.clone()))
+        .children(property.assignment.as_ref().and_then(expression))
+        .children(property.accessors.iter().map(accessor))
+}
+
+fn accessor(accessor: &Accessor) -> Tree {
+    Tree::new("accessor", accessor.kind.to_string()).children(accessor.body.as_ref().map(function))
+}
+
+fn class_statement(stmt: &ClassAllowedStatement) -> Tree {
+    match stmt {
+        ClassAllowedStatement::Property(p) => property(p),
+        ClassAllowedStatement::Method(method) => function(method),
+        ClassAllowedStatement::Constant(constant) => variable("const", constant),
+        ClassAllowedStatement::Macro(invocation) => Tree::new("macro", invocation.name.clone()),
+        ClassAllowedStatement::Import(import) => Tree::new("import", path(import)),
+        ClassAllowedStatement::Static(inner) => {
+            Tree::new("static", "").child(class_statement(inner))
+        }
+    }
+}
+
+/// `None` for the ends of lines, which only separate the expressions of a block.
+fn expression(expr: &Expression) -> Option<Tree> {
+    let tree = match expr {
+        Expression::Await(inner) => Tree::new("await", "").children(expression(inner)),
+        Expression::Spread(inner) => Tree::new("spread", "").children(expression(inner)),
+        Expression::Call(call) => {
+            let generics = call.generics.iter().map(ToString::to_string);
+            let generics: Vec<String> = generics.collect();
+            let detail = match generics.is_empty() {
+                true => call.name.to_string(),
+                false => format!("{}<{}>", call.name, generics.join(", ")),
+            };
+            Tree::new("call", detail)
+                .span(Some(call.span.clone()))
+                .children(call.arguments.iter().filter_map(expression))
+        }
+        Expression::MethodCall(call) => Tree::new("method", call.name.clone())
+            .children(expression(&call.callee))
+            .children(call.arguments.iter().filter_map(expression)),
+        Expression::New(call) => Tree::new("new", call.name.clone())
+            .span(Some(call.span.clone()))
+            .children(call.arguments.iter().filter_map(expression)),
+        Expression::Array(array) => Tree::new(
+            "array",
+            array
+                .ty
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+        )
+        .span(Some(array.span.clone()))
+        .children(array.values.iter().filter_map(expression)),
+        Expression::Tuple(values) => Tree::new("tuple", "")
+            .span(expr.span())
+            .children(values.iter().filter_map(expression)),
+        Expression::Object(object) => Tree::new("object", "")
+            .span(Some(object.span.clone()))
+            .children(object.properties.iter().map(|property| {
+                Tree::new("key", property.name.clone())
+                    .span(Some(property.span.clone()))
+                    .children(expression(&property.value))
+            })),
+        Expression::Operation(operation) => Tree::new("operation", operation.op.to_string())
+            .span(Some(operation.span.clone()))
+            .children(expression(&operation.left))
+            .children(expression(&operation.right)),
+        Expression::Statement(stmt) => statement(stmt),
+        Expression::Is(test) => Tree::new("is", test.ty.to_string())
+            .span(Some(test.span.clone()))
+            .children(expression(&test.value)),
+        Expression::Member(member) => {
+            let lookup = match member.lookup {
+                MemberLookup::Static => "::",
+                MemberLookup::Dynamic => ".",
+                MemberLookup::Index => "[]",
+            };
+            let origin = member.origin.value().unwrap_or_default();
+            Tree::new("member", format!("{}{}", origin, lookup))
+                .span(expr.span())
+                .children(expression(&member.name))
+        }
+        Expression::Literal(literal) => {
+            let detail = match &literal.ty {
+                Some(ty @ TypeKind::BuiltIn(BuiltInType::String)) => {
+                    format!("{:?}: {}", literal.value, ty)
+                }
+                Some(ty) => format!("{}: {}", literal.value, ty),
+                None => literal.value.clone(),
+            };
+            let kind = match literal.ty {
+                Some(_) => "literal",
+                None => "name",
+            };
+            Tree::new(kind, detail).span(Some(literal.span.clone()))
+        }
+        Expression::EndOfLine => return None,
+    };
+    Some(tree)
+}
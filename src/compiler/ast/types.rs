@@ -1,6 +1,6 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt, ops::Range};
 
-use crate::compiler::ast::{Expression, Literal};
+use crate::compiler::ast::{AstBody, Expression, Literal, NodeKind, Statement};
 
 /// This is all the different kind of types that may exist.
 
@@ -25,12 +25,12 @@ pub enum TypeKind {
     Reference(TypeReference),
     /// A runtime type.
     /// This is a type that can be evaluated at runtime, and is not defined by an alias.
+    /// Its values are only checked with `runtime_checks`, see `CompilerOptions`.
     ///
     /// For Example:
     /// ```ts
     /// type AnyNumber(x) = std::isFloat(x) || std::isInt(x);
     /// ```
-    ///  > **⚠️This is not implemented yet!**
     RuntimeType(RuntimeType),
     /// A built in type.
     /// This is a type that is defined by the language.
@@ -39,6 +39,37 @@ pub enum TypeKind {
     /// type AnyNumber = string;
     /// ```
     BuiltIn(BuiltInType),
+    /// A type that may also be `null`.
+    ///
+    /// For example:
+    /// ```ts
+    /// var name: string? = null;
+    /// ```
+    Nullable(Box<TypeKind>),
+    /// The type of a function that can be called.
+    ///
+    /// For example:
+    /// ```ts
+    /// var callback: (int, string) => bool = check;
+    /// ```
+    Function {
+        params: Vec<TypeKind>,
+        ret: Box<TypeKind>,
+    },
+    /// The shape of an object literal, the properties it must have and their types.
+    ///
+    /// For example:
+    /// ```ts
+    /// type Point = { x: int, y: int };
+    /// ```
+    Object(ObjectShape),
+    /// A fixed amount of values, each with its own type.
+    ///
+    /// For example:
+    /// ```ts
+    /// fn divide(a: int, b: int): (int, int) {}
+    /// ```
+    Tuple(Vec<TypeKind>),
 }
 
 impl TypeKind {
@@ -57,6 +88,90 @@ impl TypeKind {
     pub fn built_in(name: String) -> Self {
         TypeKind::BuiltIn(BuiltInType::from_string(name).expect("Built in type not found."))
     }
+
+    pub fn nullable(inner: TypeKind) -> Self {
+        TypeKind::Nullable(Box::new(inner))
+    }
+
+    pub fn function(params: Vec<TypeKind>, ret: TypeKind) -> Self {
+        TypeKind::Function {
+            params,
+            ret: Box::new(ret),
+        }
+    }
+
+    /// Whether or not `null` can be assigned to this type.
+    /// `any` accepts `null` as well, like `mixed` does in php.
+    pub fn accepts_null(&self) -> bool {
+        match self {
+            TypeKind::Nullable(_) => true,
+            TypeKind::BuiltIn(BuiltInType::Null) | TypeKind::BuiltIn(BuiltInType::Any) => true,
+            TypeKind::Union(union) => union.types.iter().any(|ty| ty.accepts_null()),
+            TypeKind::Reference(_)
+            | TypeKind::RuntimeType(_)
+            | TypeKind::BuiltIn(_)
+            | TypeKind::Function { .. }
+            | TypeKind::Object(_)
+            | TypeKind::Tuple(_) => false,
+        }
+    }
+
+    /// Whether or not a value of this type may be `null`, `any` is not considered to be.
+    pub fn may_be_null(&self) -> bool {
+        match self {
+            TypeKind::Nullable(_) | TypeKind::BuiltIn(BuiltInType::Null) => true,
+            TypeKind::Union(union) => union.types.iter().any(|ty| ty.may_be_null()),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for TypeKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TypeKind::Union(union) => {
+                let types = union
+                    .types
+                    .iter()
+                    .map(|t| t.to_string())
+                    .collect::<Vec<String>>();
+                write!(f, "{}", types.join(" | "))
+            }
+            TypeKind::Reference(reference) => {
+                write!(f, "{}", reference.name)?;
+                if let Some(params) = &reference.params {
+                    let params = params
+                        .iter()
+                        .map(|p| p.kind.to_string())
+                        .collect::<Vec<String>>();
+                    write!(f, "<{}>", params.join(", "))?;
+                }
+                Ok(())
+            }
+            TypeKind::RuntimeType(_) => write!(f, "runtime"),
+            TypeKind::BuiltIn(built_in) => write!(f, "{}", built_in),
+            TypeKind::Nullable(inner) => write!(f, "{}?", inner),
+            TypeKind::Function { params, ret } => {
+                let params = params
+                    .iter()
+                    .map(|p| p.to_string())
+                    .collect::<Vec<String>>();
+                write!(f, "({}) => {}", params.join(", "), ret)
+            }
+            TypeKind::Object(shape) => {
+                let properties = shape
+                    .properties
+                    .iter()
+                    .map(|p| format!("{}: {}", p.name, p.ty))
+                    .collect::<Vec<String>>();
+                write!(f, "{{ {} }}", properties.join(", "))
+            }
+            TypeKind::Tuple(types) => {
+                let types = types.iter().map(|t| t.to_string()).collect::<Vec<String>>();
+                write!(f, "({})", types.join(", "))
+            }
+        }
+    }
 }
 
 /// A single type parameter
@@ -75,6 +190,24 @@ impl TypeParam {
     pub fn new(kind: TypeKind) -> Self {
         TypeParam { name: None, kind }
     }
+
+    /// A declared parameter, eg: `T` in `fn first<T>(items: T[])`.
+    /// The kind is what the parameter is bound by, `any` when it is unbounded.
+    pub fn named(name: String, kind: TypeKind) -> Self {
+        TypeParam {
+            name: Some(name),
+            kind,
+        }
+    }
+
+    /// The type a declared parameter extends, eg: `Comparable` in `T extends Comparable`.
+    /// `None` when it is unbounded.
+    pub fn bound(&self) -> Option<&TypeKind> {
+        match &self.kind {
+            TypeKind::BuiltIn(BuiltInType::Any) => None,
+            kind => self.name.as_ref().map(|_| kind),
+        }
+    }
 }
 
 /// A type union.
@@ -99,6 +232,38 @@ impl TypeUnion {
     }
 }
 
+/// The shape of an object.
+///
+/// For example:
+/// ```ts
+/// { x: int, y: int }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ObjectShape {
+    pub properties: Vec<ShapeProperty>,
+}
+
+impl ObjectShape {
+    pub fn new(properties: Vec<ShapeProperty>) -> Self {
+        ObjectShape { properties }
+    }
+
+    /// Gets the type of a property by its name.
+    pub fn property(&self, name: &str) -> Option<&TypeKind> {
+        self.properties
+            .iter()
+            .find(|p| p.name == name)
+            .map(|p| &p.ty)
+    }
+}
+
+/// A property of an object shape, eg: `x: int`
+#[derive(Debug, Clone)]
+pub struct ShapeProperty {
+    pub name: String,
+    pub ty: TypeKind,
+}
+
 /// A type that is defined by an alias.
 ///
 /// For Example:
@@ -127,7 +292,9 @@ impl TypeReference {
 /// ```
 #[derive(Debug, Clone)]
 pub struct RuntimeType {
+    /// The value that is checked, `x` in the example, bound by its type.
     pub params: Option<Vec<TypeParam>>,
+    /// Whether the value is one of the type.
     pub body: Box<Expression>,
 }
 
@@ -176,10 +343,14 @@ pub enum BuiltInType {
     Bool,
     /// Any string, this is a heap allocated string.
     String,
-    /// An array of a type.
+    /// An array of a type, eg: `int[]`.
     Array(Box<TypeKind>),
+    /// A map from keys of a type to values of a type, eg: `map<string, int>`.
+    Map(Box<TypeKind>, Box<TypeKind>),
     /// Any type, this is disabled in strict mode.
     Any,
+    /// The type of the `null` literal.
+    Null,
 }
 
 impl BuiltInType {
@@ -196,7 +367,12 @@ impl BuiltInType {
             "array" => Some(BuiltInType::Array(Box::new(TypeKind::built_in(
                 "any".to_string(),
             )))),
+            "map" => Some(BuiltInType::Map(
+                Box::new(TypeKind::built_in("any".to_string())),
+                Box::new(TypeKind::built_in("any".to_string())),
+            )),
             "any" => Some(BuiltInType::Any),
+            "null" => Some(BuiltInType::Null),
             "u8" => Some(BuiltInType::Strict(StrictBuiltInType::U8)),
             "u16" => Some(BuiltInType::Strict(StrictBuiltInType::U16)),
             "u32" => Some(BuiltInType::Strict(StrictBuiltInType::U32)),
@@ -214,6 +390,33 @@ impl BuiltInType {
     }
 }
 
+impl fmt::Display for BuiltInType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BuiltInType::Strict(strict) => write!(f, "{}", strict),
+            BuiltInType::Byte => write!(f, "byte"),
+            BuiltInType::Short => write!(f, "short"),
+            BuiltInType::Int => write!(f, "int"),
+            BuiltInType::Long => write!(f, "long"),
+            BuiltInType::Float => write!(f, "float"),
+            BuiltInType::Double => write!(f, "double"),
+            BuiltInType::Bool => write!(f, "bool"),
+            BuiltInType::String => write!(f, "string"),
+            BuiltInType::Array(inner) => match inner.as_ref() {
+                TypeKind::BuiltIn(BuiltInType::Any) => write!(f, "array"),
+                // `int | string[]` is a union with an array in it.
+                TypeKind::Union(_) | TypeKind::Function { .. } => {
+                    write!(f, "({})[]", inner)
+                }
+                _ => write!(f, "{}[]", inner),
+            },
+            BuiltInType::Map(key, value) => write!(f, "map<{}, {}>", key, value),
+            BuiltInType::Any => write!(f, "any"),
+            BuiltInType::Null => write!(f, "null"),
+        }
+    }
+}
+
 /// A strict built in type.
 /// This is a type that is defined when the "strict-types" compiler flag is enabled.
 ///
@@ -238,6 +441,26 @@ pub enum StrictBuiltInType {
     F64,
 }
 
+impl fmt::Display for StrictBuiltInType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            StrictBuiltInType::U8 => "u8",
+            StrictBuiltInType::U16 => "u16",
+            StrictBuiltInType::U32 => "u32",
+            StrictBuiltInType::U64 => "u64",
+            StrictBuiltInType::U128 => "u128",
+            StrictBuiltInType::I8 => "i8",
+            StrictBuiltInType::I16 => "i16",
+            StrictBuiltInType::I32 => "i32",
+            StrictBuiltInType::I64 => "i64",
+            StrictBuiltInType::I128 => "i128",
+            StrictBuiltInType::F32 => "f32",
+            StrictBuiltInType::F64 => "f64",
+        };
+        write!(f, "{}", s)
+    }
+}
+
 /// A literal type statement.
 /// This is a type that is defined by a anything.
 ///
@@ -283,35 +506,102 @@ impl TypeRef {
     }
 }
 
+/// A type defined twice in the same context, eg: `type Id = int;` then `type Id = string;`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateType {
+    pub name: String,
+    /// The span of the definition that was kept.
+    pub first: Range<usize>,
+    /// The span of the definition that was refused.
+    pub second: Range<usize>,
+}
+
+impl DuplicateType {
+    pub fn message(&self) -> String {
+        format!("The type `{}` is defined twice.", self.name)
+    }
+}
+
 /// This is a store that holds all the types for a given context.
-/// This is used to resolve types when they are outside of the current scope.
-#[derive(Debug, Clone)]
+/// A name is only defined once, see `add_type`.
+/// The types of other contexts are found with `resolve::types::TypeResolver`.
+#[derive(Debug, Clone, Default)]
 pub struct TypeStore {
     pub types: HashMap<u64, TypeDefinition>,
+    /// The id of every type, by its name.
+    names: HashMap<String, u64>,
+    /// The span of every definition, by the id of its type.
+    spans: HashMap<u64, Range<usize>>,
     next_id: u64,
 }
 
 impl TypeStore {
     pub fn new() -> Self {
-        Self {
-            types: HashMap::new(),
-            next_id: 0,
+        Self::default()
+    }
+
+    /// The types defined at the top level of a body, along with the definitions that
+    /// reuse a name, the first definition of a name is the one kept.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::compiler::{parser::Parser, CompilerOptions};
+    /// # use surn::compiler::ast::types::TypeStore;
+    /// // type Id = int; type Id = string;
+    /// # let source = "type Id = int; type Id = string;\n";
+    /// # let body = Parser::new(CompilerOptions::default())
+    /// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+    /// let (store, duplicates) = TypeStore::build(&body);
+    /// store.get("Id"); // Some(type Id = int)
+    /// duplicates[0].message(); // The type `Id` is defined twice.
+    /// # Ok::<(), surn::report::Report>(())
+    /// ```
+    pub fn build(body: &AstBody) -> (Self, Vec<DuplicateType>) {
+        let mut store = Self::new();
+        let mut duplicates = Vec::new();
+        for node in body.get_program() {
+            if let NodeKind::Statement(Statement::TypeDef(definition)) = &node.inner {
+                if let Err(duplicate) = store.add_type(definition.clone(), node.start()..node.end())
+                {
+                    duplicates.push(duplicate);
+                }
+            }
         }
+        (store, duplicates)
     }
 
+    /// Adds a definition, unless its name is already defined.
     pub fn add_type(
         &mut self,
-        name: String,
-        params: Option<Vec<TypeParam>>,
-        kind: TypeKind,
-    ) -> u64 {
+        definition: TypeDefinition,
+        span: Range<usize>,
+    ) -> Result<u64, DuplicateType> {
+        if let Some(first) = self.names.get(&definition.name) {
+            return Err(DuplicateType {
+                name: definition.name,
+                first: self.spans[first].clone(),
+                second: span,
+            });
+        }
         let id = self.next_id;
         self.next_id += 1;
-        self.types.insert(id, TypeDefinition { name, params, kind });
-        id
+        self.names.insert(definition.name.clone(), id);
+        self.spans.insert(id, span);
+        self.types.insert(id, definition);
+        Ok(id)
     }
 
     pub fn get_type(&self, id: u64) -> Option<&TypeDefinition> {
         self.types.get(&id)
     }
+
+    /// The type defined with a name.
+    pub fn get(&self, name: &str) -> Option<&TypeDefinition> {
+        self.names.get(name).and_then(|id| self.get_type(*id))
+    }
+
+    /// The span of the definition of a type.
+    pub fn span(&self, name: &str) -> Option<Range<usize>> {
+        self.names.get(name).map(|id| self.spans[id].clone())
+    }
 }
@@ -0,0 +1,223 @@
+//! A read-only traversal over the AST.
+//!
+//! Implement `Visitor` and override the `visit_*` methods for the nodes you care about.
+//! Each default method calls its matching `walk_*` function, which visits the children
+//! of the node, so overriding a method and calling `walk_*` inside of it keeps the traversal going.
+//!
+//! **Example:**
+//! ```rust no_run
+//! use surn::compiler::ast::{
+//!     visit::{walk_call, Visitor},
+//!     Call,
+//! };
+//!
+//! struct CallCounter(usize);
+//!
+//! impl Visitor for CallCounter {
+//!     fn visit_call(&mut self, call: &Call) {
+//!         self.0 += 1;
+//!         walk_call(self, call);
+//!     }
+//! }
+//! ```
+use super::{
+    AstBody, Call, Class, ClassAllowedStatement, ClassProperty, Expression, Function, Node,
+    NodeKind, Statement, Variable,
+};
+
+pub trait Visitor {
+    fn visit_node(&mut self, node: &Node) {
+        walk_node(self, node);
+    }
+
+    fn visit_statement(&mut self, stmt: &Statement) {
+        walk_statement(self, stmt);
+    }
+
+    fn visit_expression(&mut self, expr: &Expression) {
+        walk_expression(self, expr);
+    }
+
+    /// Visits both `var` and `const` declarations.
+    fn visit_variable(&mut self, var: &Variable) {
+        walk_variable(self, var);
+    }
+
+    fn visit_function(&mut self, func: &Function) {
+        walk_function(self, func);
+    }
+
+    fn visit_class(&mut self, class: &Class) {
+        walk_class(self, class);
+    }
+
+    fn visit_class_property(&mut self, property: &ClassProperty) {
+        walk_class_property(self, property);
+    }
+
+    /// Class constants aren't variables in scope, so they aren't given to `visit_variable`.
+    fn visit_class_constant(&mut self, constant: &Variable) {
+        walk_variable(self, constant);
+    }
+
+    fn visit_call(&mut self, call: &Call) {
+        walk_call(self, call);
+    }
+}
+
+/// Visits every node of the given body in source order.
+pub fn walk_body<V: Visitor + ?Sized>(visitor: &mut V, body: &AstBody) {
+    for node in body.get_program() {
+        visitor.visit_node(node);
+    }
+}
+
+pub fn walk_node<V: Visitor + ?Sized>(visitor: &mut V, node: &Node) {
+    match &node.inner {
+        NodeKind::Statement(stmt) => visitor.visit_statement(stmt),
+        NodeKind::Expression(expr) => visitor.visit_expression(expr),
+    }
+}
+
+pub fn walk_statement<V: Visitor + ?Sized>(visitor: &mut V, stmt: &Statement) {
+    match stmt {
+        Statement::Var(var) | Statement::Const(var) => visitor.visit_variable(var),
+        Statement::Destructure(destructure) => visitor.visit_expression(&destructure.assignment),
+        Statement::Static(s) => visitor.visit_statement(&s.statement),
+        Statement::Function(func) => visitor.visit_function(func),
+        Statement::Class(class) => visitor.visit_class(class),
+        Statement::Enum(e) => {
+            for variant in e.variants.iter() {
+                if let Some(value) = &variant.value {
+                    visitor.visit_expression(value);
+                }
+            }
+        }
+        Statement::Block(exprs) => {
+            for expr in exprs.iter() {
+                visitor.visit_expression(expr);
+            }
+        }
+        Statement::Namespace(namespace) => {
+            if let Some(body) = &namespace.body {
+                visitor.visit_statement(body);
+            }
+        }
+        Statement::Return(ret) => {
+            if let Some(expr) = &ret.expression {
+                visitor.visit_expression(expr);
+            }
+        }
+        Statement::If(i) => {
+            visitor.visit_expression(&i.condition);
+            visitor.visit_statement(&i.body);
+            if let Some(otherwise) = &i.otherwise {
+                visitor.visit_statement(otherwise);
+            }
+        }
+        Statement::Try(t) => {
+            visitor.visit_statement(&t.body);
+            for catch in t.catches.iter() {
+                visitor.visit_statement(&catch.body);
+            }
+            if let Some(finally) = &t.finally {
+                visitor.visit_statement(finally);
+            }
+        }
+        Statement::Throw(expr) => visitor.visit_expression(expr),
+        Statement::Import(_) | Statement::TypeDef(_) | Statement::MacroInvocation(_) => {}
+    }
+}
+
+pub fn walk_expression<V: Visitor + ?Sized>(visitor: &mut V, expr: &Expression) {
+    match expr {
+        Expression::Await(inner) | Expression::Spread(inner) => visitor.visit_expression(inner),
+        Expression::Call(call) => visitor.visit_call(call),
+        Expression::MethodCall(call) => {
+            visitor.visit_expression(&call.callee);
+            for arg in call.arguments.iter() {
+                visitor.visit_expression(arg);
+            }
+        }
+        Expression::New(call) => {
+            for arg in call.arguments.iter() {
+                visitor.visit_expression(arg);
+            }
+        }
+        Expression::Array(array) => {
+            for value in array.values.iter() {
+                visitor.visit_expression(value);
+            }
+        }
+        Expression::Tuple(values) => {
+            for value in values.iter() {
+                visitor.visit_expression(value);
+            }
+        }
+        Expression::Object(object) => {
+            for property in object.properties.iter() {
+                visitor.visit_expression(&property.value);
+            }
+        }
+        Expression::Operation(op) => {
+            visitor.visit_expression(&op.left);
+            visitor.visit_expression(&op.right);
+        }
+        Expression::Statement(stmt) => visitor.visit_statement(stmt),
+        Expression::Member(member) => visitor.visit_expression(&member.name),
+        Expression::Is(test) => visitor.visit_expression(&test.value),
+        Expression::Literal(_) | Expression::EndOfLine => {}
+    }
+}
+
+pub fn walk_variable<V: Visitor + ?Sized>(visitor: &mut V, var: &Variable) {
+    if let Some(assignment) = &var.assignment {
+        visitor.visit_expression(assignment);
+    }
+}
+
+pub fn walk_function<V: Visitor + ?Sized>(visitor: &mut V, func: &Function) {
+    visitor.visit_statement(&func.body);
+}
+
+pub fn walk_class<V: Visitor + ?Sized>(visitor: &mut V, class: &Class) {
+    for property in class.body.properties.iter() {
+        visitor.visit_class_property(property);
+    }
+    for method in class.body.methods.iter() {
+        visitor.visit_function(method);
+    }
+    for other in class.body.other.iter() {
+        walk_class_allowed_statement(visitor, other);
+    }
+}
+
+pub fn walk_class_allowed_statement<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    stmt: &ClassAllowedStatement,
+) {
+    match stmt {
+        ClassAllowedStatement::Property(property) => visitor.visit_class_property(property),
+        ClassAllowedStatement::Method(method) => visitor.visit_function(method),
+        ClassAllowedStatement::Constant(constant) => visitor.visit_class_constant(constant),
+        ClassAllowedStatement::Static(inner) => walk_class_allowed_statement(visitor, inner),
+        ClassAllowedStatement::Macro(_) | ClassAllowedStatement::Import(_) => {}
+    }
+}
+
+pub fn walk_class_property<V: Visitor + ?Sized>(visitor: &mut V, property: &ClassProperty) {
+    if let Some(assignment) = &property.assignment {
+        visitor.visit_expression(assignment);
+    }
+    for accessor in property.accessors.iter() {
+        if let Some(body) = &accessor.body {
+            visitor.visit_function(body);
+        }
+    }
+}
+
+pub fn walk_call<V: Visitor + ?Sized>(visitor: &mut V, call: &Call) {
+    for arg in call.arguments.iter() {
+        visitor.visit_expression(arg);
+    }
+}
@@ -1,6 +0,0 @@
-pub enum Errors {
-    DeclarationError(String),
-    TypeError(String),
-    InvalidToken(String),
-    ParserError(String),
-}
@@ -0,0 +1,657 @@
+use std::{borrow::Cow, collections::HashMap, fmt, ops::Range};
+
+use crate::compiler::{
+    ast::{
+        ops::{AnyOperation, BinOp, ComparisonOp, LogicalOp, UnaryOp},
+        types::{BuiltInType, TypeKind},
+        visit::{self, Visitor},
+        Array, AstBody, Class, ClassAllowedStatement, Expression, Function, Literal,
+        MemberListNode, MemberLookup, NodeKind, Operation, Statement, Variable,
+    },
+    lexer::escape::escape,
+};
+
+/// A value known at compile time.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstValue {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    String(String),
+    Null,
+    Array(Vec<ConstValue>),
+}
+
+impl ConstValue {
+    /// The type of the value, arrays are typed by their first value.
+    pub fn ty(&self) -> TypeKind {
+        let built_in = match self {
+            ConstValue::Int(_) => BuiltInType::Int,
+            ConstValue::Float(_) => BuiltInType::Float,
+            ConstValue::Bool(_) => BuiltInType::Bool,
+            ConstValue::String(_) => BuiltInType::String,
+            ConstValue::Null => BuiltInType::Null,
+            ConstValue::Array(values) => BuiltInType::Array(Box::new(
+                values
+                    .first()
+                    .map(|value| value.ty())
+                    .unwrap_or(TypeKind::BuiltIn(BuiltInType::Any)),
+            )),
+        };
+        TypeKind::BuiltIn(built_in)
+    }
+
+    /// The literal expression of the value, as the parser would have read it.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::compiler::eval::ConstValue;
+    /// ConstValue::Float(6.0).to_expression(); // Literal { value: "6.0", ty: Some(float) }
+    /// ```
+    pub fn to_expression(&self) -> Expression {
+        match self {
+            ConstValue::Array(values) => Expression::Array(Array::new(
+                values.iter().map(|value| value.to_expression()).collect(),
+                None,
+            )),
+            ConstValue::String(value) => {
+                Expression::Literal(Literal::new(value.clone(), Some(self.ty())))
+            }
+            value => Expression::Literal(Literal::new(value.to_string(), Some(self.ty()))),
+        }
+    }
+
+    /// Whether the value is true once converted to a bool, as php's `if` reads it.
+    pub fn truthy(&self) -> bool {
+        match self {
+            ConstValue::Int(value) => *value != 0,
+            ConstValue::Float(value) => *value != 0.0,
+            ConstValue::Bool(value) => *value,
+            ConstValue::String(value) => !value.is_empty() && value != "0",
+            ConstValue::Null => false,
+            ConstValue::Array(values) => !values.is_empty(),
+        }
+    }
+
+    /// The number a numeric string holds, eg: `" 1.5"`, php ignores the whitespace around it.
+    fn number(&self) -> Option<f64> {
+        match self {
+            ConstValue::Int(value) => Some(*value as f64),
+            ConstValue::Float(value) => Some(*value),
+            ConstValue::String(value) => {
+                let value = value.trim();
+                let numeric = value.bytes().any(|b| b.is_ascii_digit())
+                    && value.bytes().all(|b| {
+                        b.is_ascii_digit() || matches!(b, b'.' | b'e' | b'E' | b'+' | b'-')
+                    });
+                numeric.then(|| value.parse().ok()).flatten()
+            }
+            _ => None,
+        }
+    }
+
+    /// Compares two values the way php's `==` does, eg: `1 == 1.0` and `"1" == 1` are true.
+    pub fn loose_eq(&self, other: &ConstValue) -> bool {
+        use ConstValue::{Array, Bool, Int, Null, String};
+
+        match (self, other) {
+            (Int(l), Int(r)) => l == r,
+            (String(l), String(r)) => match (self.number(), other.number()) {
+                (Some(l), Some(r)) => l == r,
+                _ => l == r,
+            },
+            (Bool(value), other) | (other, Bool(value)) => *value == other.truthy(),
+            (Null, Null) => true,
+            (Null, String(value)) | (String(value), Null) => value.is_empty(),
+            (Null, other) | (other, Null) => !other.truthy(),
+            (Array(l), Array(r)) => {
+                l.len() == r.len() && l.iter().zip(r.iter()).all(|(l, r)| l.loose_eq(r))
+            }
+            (Array(_), _) | (_, Array(_)) => false,
+            // a string that isn't numeric never equals a number.
+            _ => match (self.number(), other.number()) {
+                (Some(l), Some(r)) => l == r,
+                _ => false,
+            },
+        }
+    }
+}
+
+/// Writes the value as it is written in surn source.
+impl fmt::Display for ConstValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConstValue::Int(value) => write!(f, "{}", value),
+            // debug keeps the `.0` of whole numbers, so the value is read back as a float.
+            ConstValue::Float(value) => write!(f, "{:?}", value),
+            ConstValue::Bool(value) => write!(f, "{}", value),
+            ConstValue::String(value) => write!(f, "\"{}\"", escape(value)),
+            ConstValue::Null => write!(f, "null"),
+            ConstValue::Array(values) => {
+                let values = values
+                    .iter()
+                    .map(|value| value.to_string())
+                    .collect::<Vec<String>>();
+                write!(f, "[{}]", values.join(", "))
+            }
+        }
+    }
+}
+
+/// A `const` that isn't initialized with a constant expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstError {
+    pub message: String,
+    /// The span of the `const` declaration.
+    pub span: Range<usize>,
+}
+
+/// Evaluates constant expressions: literals, arrays of them, operations on them
+/// and the consts that were defined before.
+///
+/// Chains of binary operations are evaluated with php's precedence,
+/// which is how the generated code reads them.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::ast::{expr, ops::BinOp};
+/// # use surn::compiler::eval::{ConstEvaluator, ConstValue};
+/// let mut evaluator = ConstEvaluator::new();
+/// evaluator.define("A", ConstValue::Int(6));
+/// let expression = expr::binary(expr::name("A"), BinOp::Plus, expr::int(1));
+/// evaluator.evaluate(&expression)?; // ConstValue::Int(7)
+/// # Ok::<(), String>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ConstEvaluator {
+    consts: HashMap<String, ConstValue>,
+}
+
+impl ConstEvaluator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn define(&mut self, name: &str, value: ConstValue) {
+        self.consts.insert(name.to_string(), value);
+    }
+
+    /// Defines a constant of a class, it is known as `Class::NAME`, and as `self::NAME`
+    /// and `static::NAME` until `forget_scope` is called.
+    pub fn define_class_constant(&mut self, class: &str, name: &str, value: ConstValue) {
+        for origin in [class, "self", "static"] {
+            self.define(&format!("{}::{}", origin, name), value.clone());
+        }
+    }
+
+    /// Forgets the constants defined as `self::NAME` and `static::NAME`, once the class
+    /// that defined them ends.
+    pub fn forget_scope(&mut self) {
+        self.consts
+            .retain(|name, _| !name.starts_with("self::") && !name.starts_with("static::"));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ConstValue> {
+        self.consts.get(name)
+    }
+
+    pub fn evaluate(&self, expr: &Expression) -> Result<ConstValue, String> {
+        match expr {
+            Expression::Literal(literal) => self.literal(literal),
+            Expression::Array(array) => Ok(ConstValue::Array(
+                array
+                    .values
+                    .iter()
+                    .map(|value| self.evaluate(value))
+                    .collect::<Result<Vec<ConstValue>, String>>()?,
+            )),
+            Expression::Operation(operation) => match &operation.op {
+                AnyOperation::UnaryOp(op) => unary(op, self.evaluate(&operation.right)?),
+                AnyOperation::AssignmentOp(_) => {
+                    Err("An assignment can't be evaluated at compile time.".to_string())
+                }
+                _ => self.chain(expr),
+            },
+            Expression::Call(_) | Expression::MethodCall(_) | Expression::New(_) => {
+                Err("A call can't be evaluated at compile time.".to_string())
+            }
+            Expression::Await(_) => Err("`await` can't be evaluated at compile time.".to_string()),
+            Expression::Spread(_) => {
+                Err("A spread can't be evaluated at compile time.".to_string())
+            }
+            Expression::Member(member)
+                if matches!(
+                    member.name.as_ref(),
+                    Expression::Operation(operation) if !is_assignment_or_unary(operation)
+                ) =>
+            {
+                self.chain(expr)
+            }
+            Expression::Member(member) => self
+                .class_constant(member)
+                .ok_or_else(|| "A member access can't be evaluated at compile time.".to_string()),
+            Expression::Is(_) => Err("A type test can't be evaluated at compile time.".to_string()),
+            Expression::Object(_) | Expression::Tuple(_) => {
+                Err("Only literals and arrays can be evaluated at compile time.".to_string())
+            }
+            Expression::Statement(_) | Expression::EndOfLine => {
+                Err("Expected an expression.".to_string())
+            }
+        }
+    }
+
+    /// The value of a class constant, eg: `User::LIMIT` or `self::LIMIT`.
+    fn class_constant(&self, member: &MemberListNode) -> Option<ConstValue> {
+        match (&member.lookup, member.name.as_ref()) {
+            (MemberLookup::Static, Expression::Literal(name)) if name.ty.is_none() => {
+                let origin = member.origin.value()?;
+                self.get(&format!("{}::{}", origin, name.value)).cloned()
+            }
+            _ => None,
+        }
+    }
+
+    fn literal(&self, literal: &Literal) -> Result<ConstValue, String> {
+        let value = literal.value.as_str();
+        match &literal.ty {
+            Some(TypeKind::BuiltIn(BuiltInType::Int)) => {
+                value.replace('_', "").parse::<i64>().map(ConstValue::Int).map_err(|_| {
+                    format!("`{}` does not fit in an int.", value)
+                })
+            }
+            Some(TypeKind::BuiltIn(BuiltInType::Float)) => value
+                .replace('_', "")
+                .parse::<f64>()
+                .map(ConstValue::Float)
+                .map_err(|_| format!("`{}` is not a valid float.", value)),
+            Some(TypeKind::BuiltIn(BuiltInType::Bool)) => Ok(ConstValue::Bool(value == "true")),
+            Some(TypeKind::BuiltIn(BuiltInType::String)) => Ok(ConstValue::String(value.into())),
+            Some(TypeKind::BuiltIn(BuiltInType::Null)) => Ok(ConstValue::Null),
+            Some(ty) => Err(format!("A `{}` can't be evaluated at compile time.", ty)),
+            None => self.get(value).cloned().ok_or_else(|| {
+                format!(
+                    "`{}` is not a constant, a const can only be initialized with literals and other consts.",
+                    value
+                )
+            }),
+        }
+    }
+
+    /// Evaluates a chain of binary operations with php's precedence.
+    fn chain(&self, expr: &Expression) -> Result<ConstValue, String> {
+        self.grouped(&Chain::group(expr)?)
+    }
+
+    fn grouped(&self, chain: &Chain) -> Result<ConstValue, String> {
+        match chain {
+            Chain::Operand(expr) => self.evaluate(expr),
+            Chain::Operation(op, left, right) => {
+                binary(op, self.grouped(left)?, self.grouped(right)?)
+            }
+        }
+    }
+}
+
+/// A chain of binary operations grouped by php's precedence, eg: `1 + 2 * 3` is
+/// `1 + (2 * 3)`. The parser nests every chain to the right.
+pub(crate) enum Chain<'a> {
+    Operand(Cow<'a, Expression>),
+    Operation(&'a AnyOperation, Box<Chain<'a>>, Box<Chain<'a>>),
+}
+
+impl<'a> Chain<'a> {
+    pub(crate) fn group(expr: &'a Expression) -> Result<Self, String> {
+        let mut operands = Vec::new();
+        let mut ops: Vec<&AnyOperation> = Vec::new();
+        let mut current = expr;
+        loop {
+            let operation = match current {
+                Expression::Operation(operation) => operation,
+                // the parser reads `self::MAX * 2` as the member `MAX * 2` of `self`,
+                // the member is only the first operand of the chain.
+                Expression::Member(member) => match member.name.as_ref() {
+                    Expression::Operation(operation) if !is_assignment_or_unary(operation) => {
+                        operands.push(Chain::Operand(Cow::Owned(Expression::Member(
+                            MemberListNode::new(
+                                *operation.left.clone(),
+                                member.origin.clone(),
+                                member.lookup.clone(),
+                            ),
+                        ))));
+                        ops.push(&operation.op);
+                        current = &operation.right;
+                        continue;
+                    }
+                    _ => break,
+                },
+                _ => break,
+            };
+            if is_assignment_or_unary(operation) {
+                break;
+            }
+            operands.push(Chain::Operand(Cow::Borrowed(&operation.left)));
+            ops.push(&operation.op);
+            current = &operation.right;
+        }
+        operands.push(Chain::Operand(Cow::Borrowed(current)));
+
+        // shunting yard, every operator is left associative.
+        let mut operands = operands.into_iter();
+        let mut output: Vec<Chain> = operands.next().into_iter().collect();
+        let mut pending: Vec<&AnyOperation> = Vec::new();
+        for (op, operand) in ops.into_iter().zip(operands) {
+            while let Some(top) = pending.last() {
+                if precedence(top)? < precedence(op)? {
+                    break;
+                }
+                let top = pending.pop().unwrap();
+                Self::reduce(&mut output, top);
+            }
+            pending.push(op);
+            output.push(operand);
+        }
+        while let Some(op) = pending.pop() {
+            Self::reduce(&mut output, op);
+        }
+        Ok(output.pop().unwrap())
+    }
+
+    fn reduce(output: &mut Vec<Chain<'a>>, op: &'a AnyOperation) {
+        let right = output.pop().unwrap();
+        let left = output.pop().unwrap();
+        output.push(Chain::Operation(op, Box::new(left), Box::new(right)));
+    }
+}
+
+fn is_assignment_or_unary(operation: &Operation) -> bool {
+    matches!(
+        operation.op,
+        AnyOperation::UnaryOp(_) | AnyOperation::AssignmentOp(_)
+    )
+}
+
+/// How tightly php binds an operator, the highest first.
+fn precedence(op: &AnyOperation) -> Result<u8, String> {
+    let level = match op {
+        AnyOperation::BinOp(BinOp::Star | BinOp::Slash | BinOp::Percent) => 10,
+        AnyOperation::BinOp(BinOp::Plus | BinOp::Minus) => 9,
+        AnyOperation::BinOp(BinOp::Shl | BinOp::Shr) => 8,
+        AnyOperation::ComparisonOp(
+            ComparisonOp::LessThan
+            | ComparisonOp::LessThanOrEqual
+            | ComparisonOp::GreaterThan
+            | ComparisonOp::GreaterThanOrEqual,
+        ) => 7,
+        AnyOperation::ComparisonOp(ComparisonOp::Eq | ComparisonOp::NotEq) => 6,
+        AnyOperation::BinOp(BinOp::And) => 5,
+        AnyOperation::BinOp(BinOp::Caret) => 4,
+        AnyOperation::BinOp(BinOp::Or) => 3,
+        AnyOperation::LogicalOp(LogicalOp::And) => 2,
+        AnyOperation::LogicalOp(LogicalOp::Or) => 1,
+        AnyOperation::LogicalOp(LogicalOp::Coalasce) => 0,
+        op => return Err(format!("`{}` can't be evaluated.", op)),
+    };
+    Ok(level)
+}
+
+pub(crate) fn unary(op: &UnaryOp, value: ConstValue) -> Result<ConstValue, String> {
+    match (op, value) {
+        (UnaryOp::Neg, ConstValue::Int(value)) => value
+            .checked_neg()
+            .map(ConstValue::Int)
+            .ok_or_else(|| "The negation overflows an int.".to_string()),
+        (UnaryOp::Neg, ConstValue::Float(value)) => Ok(ConstValue::Float(-value)),
+        (UnaryOp::Pos, value @ (ConstValue::Int(_) | ConstValue::Float(_))) => Ok(value),
+        (UnaryOp::Not, ConstValue::Bool(value)) => Ok(ConstValue::Bool(!value)),
+        (op, value) => Err(format!(
+            "`{}` can't be applied to `{}`.",
+            AnyOperation::UnaryOp(op.clone()),
+            value
+        )),
+    }
+}
+
+pub(crate) fn binary(
+    op: &AnyOperation,
+    left: ConstValue,
+    right: ConstValue,
+) -> Result<ConstValue, String> {
+    use ConstValue::{Bool, Float, Int, Null};
+
+    let overflow = || format!("`{}` overflows an int.", op);
+    let infinite = || format!("`{}` overflows a float.", op);
+    let mismatch = |left: &ConstValue, right: &ConstValue| {
+        format!("`{}` can't be applied to `{}` and `{}`.", op, left, right)
+    };
+    let float = |value: &ConstValue| match value {
+        Int(value) => Some(*value as f64),
+        Float(value) => Some(*value),
+        _ => None,
+    };
+
+    let value = match (op, &left, &right) {
+        (AnyOperation::LogicalOp(LogicalOp::Coalasce), Null, _) => right,
+        (AnyOperation::LogicalOp(LogicalOp::Coalasce), _, _) => left,
+        (AnyOperation::LogicalOp(LogicalOp::And), Bool(l), Bool(r)) => Bool(*l && *r),
+        (AnyOperation::LogicalOp(LogicalOp::Or), Bool(l), Bool(r)) => Bool(*l || *r),
+        (AnyOperation::ComparisonOp(ComparisonOp::Eq), _, _) => Bool(left.loose_eq(&right)),
+        (AnyOperation::ComparisonOp(ComparisonOp::NotEq), _, _) => Bool(!left.loose_eq(&right)),
+        (AnyOperation::ComparisonOp(op), _, _) => {
+            let (l, r) = match (float(&left), float(&right)) {
+                (Some(l), Some(r)) => (l, r),
+                _ => return Err(mismatch(&left, &right)),
+            };
+            Bool(match op {
+                ComparisonOp::LessThan => l < r,
+                ComparisonOp::LessThanOrEqual => l <= r,
+                ComparisonOp::GreaterThan => l > r,
+                ComparisonOp::GreaterThanOrEqual => l >= r,
+                _ => return Err(mismatch(&left, &right)),
+            })
+        }
+        (AnyOperation::BinOp(BinOp::Slash | BinOp::Percent), _, Int(0)) => {
+            return Err("Division by zero.".to_string())
+        }
+        (AnyOperation::BinOp(op), Int(l), Int(r)) => match op {
+            BinOp::Plus => Int(l.checked_add(*r).ok_or_else(overflow)?),
+            BinOp::Minus => Int(l.checked_sub(*r).ok_or_else(overflow)?),
+            BinOp::Star => Int(l.checked_mul(*r).ok_or_else(overflow)?),
+            // php only keeps the division an int when it is exact,
+            // the smallest int divided by `-1` is too large for one.
+            BinOp::Slash => match l.checked_rem(*r) {
+                None => return Err(overflow()),
+                Some(0) => Int(l / r),
+                Some(_) => Float(*l as f64 / *r as f64),
+            },
+            BinOp::Percent => Int(l.checked_rem(*r).ok_or_else(overflow)?),
+            BinOp::And => Int(l & r),
+            BinOp::Or => Int(l | r),
+            BinOp::Caret => Int(l ^ r),
+            BinOp::Shl if (0..64).contains(r) => Int(l << r),
+            BinOp::Shr if (0..64).contains(r) => Int(l >> r),
+            _ => return Err(mismatch(&left, &right)),
+        },
+        (AnyOperation::BinOp(op), _, _) => {
+            let (l, r) = match (float(&left), float(&right)) {
+                (Some(l), Some(r)) => (l, r),
+                _ => return Err(mismatch(&left, &right)),
+            };
+            let value = match op {
+                BinOp::Plus => l + r,
+                BinOp::Minus => l - r,
+                BinOp::Star => l * r,
+                BinOp::Slash if r == 0.0 => return Err("Division by zero.".to_string()),
+                BinOp::Slash => l / r,
+                _ => return Err(mismatch(&left, &right)),
+            };
+            if !value.is_finite() {
+                return Err(infinite());
+            }
+            Float(value)
+        }
+        _ => return Err(mismatch(&left, &right)),
+    };
+    Ok(value)
+}
+
+/// Checks that every `const` is initialized with a constant expression.
+/// The constants of a class can use the ones declared before them, eg: `self::LIMIT * 2`,
+/// and are known to the code that follows the class as `User::LIMIT`.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, CompilerOptions};
+/// # use surn::compiler::eval::check_consts;
+/// // const A = 2 * 3;
+/// // const B = now();
+/// # let source = "const A = 2 * 3;\nconst B = now();\n";
+/// # let body = Parser::new(CompilerOptions::default())
+/// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+/// check_consts(&body); // [A call can't be evaluated at compile time.]
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub fn check_consts(body: &AstBody) -> Vec<ConstError> {
+    let mut checker = ConstChecker {
+        evaluator: ConstEvaluator::new(),
+        class: None,
+        errors: Vec::new(),
+    };
+    visit::walk_body(&mut checker, body);
+    checker.errors
+}
+
+struct ConstChecker {
+    evaluator: ConstEvaluator,
+    /// The class whose constants are being checked.
+    class: Option<String>,
+    errors: Vec<ConstError>,
+}
+
+impl Visitor for ConstChecker {
+    fn visit_statement(&mut self, stmt: &Statement) {
+        if let Statement::Const(var) = stmt {
+            let value = match &var.assignment {
+                Some(expr) => self.evaluator.evaluate(expr),
+                None => Ok(ConstValue::Null),
+            };
+            match value {
+                Ok(value) => self.evaluator.define(&var.name, value),
+                Err(message) => self.errors.push(ConstError {
+                    message,
+                    span: var.span.clone(),
+                }),
+            }
+        }
+        visit::walk_statement(self, stmt);
+    }
+
+    fn visit_function(&mut self, func: &Function) {
+        // the consts of a function are only known inside its body.
+        let outer = self.evaluator.clone();
+        visit::walk_function(self, func);
+        self.evaluator = outer;
+    }
+
+    fn visit_class(&mut self, class: &Class) {
+        self.class = Some(class.name.clone());
+        visit::walk_class(self, class);
+        self.class = None;
+        self.evaluator.forget_scope();
+    }
+
+    fn visit_class_constant(&mut self, constant: &Variable) {
+        let value = match &constant.assignment {
+            Some(expr) => self.evaluator.evaluate(expr),
+            None => Err(format!("`{}` must be initialized.", constant.name)),
+        };
+        match (value, &self.class) {
+            (Ok(value), Some(class)) => {
+                self.evaluator
+                    .define_class_constant(class, &constant.name, value)
+            }
+            (Ok(_), None) => {}
+            (Err(message), _) => self.errors.push(ConstError {
+                message,
+                span: constant.span.clone(),
+            }),
+        }
+    }
+}
+
+/// Replaces the initializer of every `const` with the value it evaluates to.
+/// Consts that can't be evaluated are left as they are.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, CompilerOptions};
+/// # use surn::compiler::eval::fold_consts;
+/// // const A = 2 * 3;
+/// // const B = A + 1;
+/// # let source = "const A = 2 * 3;\nconst B = A + 1;\n";
+/// # let mut body = Parser::new(CompilerOptions::default())
+/// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+/// fold_consts(&mut body);
+/// // const A = 6;
+/// // const B = 7;
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub fn fold_consts(body: &mut AstBody) {
+    let mut evaluator = ConstEvaluator::new();
+    for node in body.get_program_mut().iter_mut() {
+        if let NodeKind::Statement(stmt) = &mut node.inner {
+            fold_statement(&mut evaluator, stmt);
+        }
+    }
+}
+
+fn fold_statement(evaluator: &mut ConstEvaluator, stmt: &mut Statement) {
+    match stmt {
+        Statement::Const(var) => {
+            let value = match &var.assignment {
+                Some(expr) => evaluator.evaluate(expr),
+                None => return,
+            };
+            if let Ok(value) = value {
+                var.assignment = Some(value.to_expression());
+                evaluator.define(&var.name, value);
+            }
+        }
+        Statement::Static(s) => fold_statement(evaluator, &mut s.statement),
+        Statement::Namespace(namespace) => {
+            if let Some(body) = &mut namespace.body {
+                fold_statement(evaluator, body);
+            }
+        }
+        Statement::Function(func) => fold_statement(&mut evaluator.clone(), &mut func.body),
+        Statement::Class(class) => {
+            for other in class.body.other.iter_mut() {
+                if let ClassAllowedStatement::Constant(constant) = other {
+                    fold_class_constant(evaluator, &class.name, constant);
+                }
+            }
+            evaluator.forget_scope();
+            for method in class.body.methods.iter_mut() {
+                fold_statement(&mut evaluator.clone(), &mut method.body);
+            }
+        }
+        Statement::Block(exprs) => {
+            for expr in exprs.iter_mut() {
+                if let Expression::Statement(stmt) = expr {
+                    fold_statement(evaluator, stmt);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn fold_class_constant(evaluator: &mut ConstEvaluator, class: &str, constant: &mut Variable) {
+    let value = match &constant.assignment {
+        Some(expr) => evaluator.evaluate(expr),
+        None => return,
+    };
+    if let Ok(value) = value {
+        constant.assignment = Some(value.to_expression());
+        evaluator.define_class_constant(class, &constant.name, value);
+    }
+}
@@ -0,0 +1,80 @@
+use std::ops::Range;
+
+use crate::compiler::ast::{
+    visit::{self, Visitor},
+    AstBody, Expression, Function,
+};
+
+/// An `await` in a function that isn't `async`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AwaitError {
+    /// The function the `await` is in, `None` for a closure.
+    pub function: Option<String>,
+    /// The span of the function.
+    pub span: Range<usize>,
+}
+
+impl AwaitError {
+    pub fn message(&self) -> String {
+        match &self.function {
+            Some(name) => format!("`await` is used in `{}`, which is not async.", name),
+            None => "`await` is used in a closure that is not async.".to_string(),
+        }
+    }
+}
+
+/// Checks that `await` is only used in `async` functions.
+///
+/// The top level of a script can `await`, functions declared in an async
+/// function have to be async themselves. Every function is reported once.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, CompilerOptions};
+/// # use surn::compiler::infer::awaits::check_awaits;
+/// // fn load(): string { return await fetch("/"); }
+/// # let source = "fn load(): string { return await fetch(\"/\"); }\n";
+/// # let body = Parser::new(CompilerOptions::default())
+/// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+/// check_awaits(&body); // [`await` is used in `load`, which is not async.]
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub fn check_awaits(body: &AstBody) -> Vec<AwaitError> {
+    let mut checker = AwaitChecker {
+        functions: Vec::new(),
+        errors: Vec::new(),
+    };
+    visit::walk_body(&mut checker, body);
+    checker.errors
+}
+
+struct AwaitChecker {
+    /// Every function being checked and whether it may `await`, the innermost last.
+    functions: Vec<(Option<String>, Range<usize>, bool)>,
+    errors: Vec<AwaitError>,
+}
+
+impl Visitor for AwaitChecker {
+    fn visit_function(&mut self, func: &Function) {
+        self.functions
+            .push((func.name.clone(), func.span.clone(), func.is_async));
+        visit::walk_function(self, func);
+        self.functions.pop();
+    }
+
+    fn visit_expression(&mut self, expr: &Expression) {
+        if let Expression::Await(_) = expr {
+            if let Some((name, span, allowed)) = self.functions.last_mut() {
+                if !*allowed {
+                    // only the first `await` of the function is reported.
+                    *allowed = true;
+                    self.errors.push(AwaitError {
+                        function: name.clone(),
+                        span: span.clone(),
+                    });
+                }
+            }
+        }
+        visit::walk_expression(self, expr);
+    }
+}
@@ -0,0 +1,695 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
+
+use crate::compiler::ast::{
+    ops::{AnyOperation, AssignmentOp, BinOp, LogicalOp},
+    types::{BuiltInType, TypeKind, TypeParam},
+    visit::{self, Visitor},
+    AstBody, Call, Class, ClassProperty, Expression, Function, Operation, Statement, TypeTest,
+    Variable,
+};
+
+use super::{
+    collect_signatures, infer_expression,
+    narrowing::{self, exits, tests, Test},
+    shapes::{assignable, resolve_alias, type_aliases},
+    FunctionSignature,
+};
+
+/// A value given where its type isn't allowed, or an operator applied to operands it
+/// can't be applied to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeError {
+    pub message: String,
+    /// The span of the operation, or of the declaration or call the value is in, or of
+    /// the top level node when there is neither.
+    pub span: Range<usize>,
+    /// The span of the declaration the value is checked against, when it isn't the
+    /// one the value is given in.
+    pub declaration: Option<Range<usize>>,
+}
+
+/// A name in scope, with the type it was declared with.
+#[derive(Debug, Clone)]
+struct Binding {
+    ty: TypeKind,
+    /// The span of the declaration, `None` when the type was inferred.
+    declaration: Option<Range<usize>>,
+    /// Whether the type was narrowed by an `is` test, values given to the name are
+    /// checked against the type it was declared with.
+    narrowed: bool,
+}
+
+/// Checks that values fit the types they are given to and that operators are applied
+/// to operands they can be applied to, eg: `"a" - 1`.
+///
+/// Values are checked when they are given to a declaration with a type annotation,
+/// assigned to a variable declared with one or given to a parameter of a declared function.
+/// Values whose type can't be inferred are not checked, object literals are left
+/// to `check_shapes` and `null` to `check_nulls`.
+///
+/// A union is narrowed by an `is` test, in the body of an `if` and on the right of
+/// an `&&`, the `else` and the right of an `||` get the types the test ruled out.
+/// An operator that can only be applied to some types of a union is reported.
+///
+/// The type arguments of a generic function, class or alias are checked against the
+/// types their parameters extend, whether they are written out or inferred from a call.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, CompilerOptions};
+/// # use surn::compiler::infer::checker::check_types;
+/// // var count: int = 0;
+/// // count = "1";
+/// # let source = "var count: int = 0;\ncount = \"1\";\n";
+/// # let body = Parser::new(CompilerOptions::default())
+/// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+/// check_types(&body); // [`count` is declared as `int`, but was given a value of type `string`.]
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub fn check_types(body: &AstBody) -> Vec<TypeError> {
+    let mut declarations = Declarations {
+        generics: HashMap::new(),
+        parents: HashMap::new(),
+    };
+    visit::walk_body(&mut declarations, body);
+    let mut checker = TypeChecker {
+        aliases: type_aliases(body),
+        functions: collect_signatures(body),
+        generics: declarations.generics,
+        parents: declarations.parents,
+        scopes: vec![HashMap::new()],
+        span: 0..0,
+        errors: Vec::new(),
+    };
+    for node in body.get_program() {
+        checker.span = node.start()..node.end();
+        visit::walk_node(&mut checker, node);
+    }
+    checker.errors
+}
+
+/// Collects the type parameters of every class and alias, and what each class extends.
+struct Declarations {
+    generics: HashMap<String, Vec<TypeParam>>,
+    parents: HashMap<String, Vec<String>>,
+}
+
+impl Visitor for Declarations {
+    fn visit_statement(&mut self, stmt: &Statement) {
+        if let Statement::TypeDef(def) = stmt {
+            let params = def.params.clone().unwrap_or_default();
+            self.generics.insert(def.name.clone(), params);
+        }
+        visit::walk_statement(self, stmt);
+    }
+
+    fn visit_class(&mut self, class: &Class) {
+        let parents = class
+            .extends
+            .iter()
+            .chain(class.implements.iter().flatten())
+            .cloned()
+            .collect();
+        self.parents.insert(class.name.clone(), parents);
+        self.generics
+            .insert(class.name.clone(), class.generics.clone());
+        visit::walk_class(self, class);
+    }
+}
+
+struct TypeChecker {
+    aliases: HashMap<String, TypeKind>,
+    functions: HashMap<String, FunctionSignature>,
+    /// The type parameters of the classes and aliases.
+    generics: HashMap<String, Vec<TypeParam>>,
+    /// The classes each class extends or implements.
+    parents: HashMap<String, Vec<String>>,
+    scopes: Vec<HashMap<String, Binding>>,
+    /// The span of the innermost declaration or call being checked.
+    span: Range<usize>,
+    errors: Vec<TypeError>,
+}
+
+impl TypeChecker {
+    fn lookup(&self, name: &str) -> Option<&Binding> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    /// The binding a name was declared with, before any `is` test narrowed it.
+    fn lookup_declared(&self, name: &str) -> Option<&Binding> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).filter(|binding| !binding.narrowed))
+    }
+
+    fn infer(&self, expr: &Expression) -> Option<TypeKind> {
+        infer_expression(
+            expr,
+            &|name| self.lookup(name).map(|binding| binding.ty.clone()),
+            &self.functions,
+        )
+    }
+
+    fn declare(&mut self, name: &str, binding: Binding) {
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .insert(name.to_string(), binding);
+    }
+
+    /// The type of a value given where `expected` is declared, if it doesn't fit.
+    fn mismatch(&self, expected: &TypeKind, value: &Expression) -> Option<TypeKind> {
+        if matches!(value, Expression::Object(_)) {
+            return None;
+        }
+        self.infer(value)
+            .filter(|found| !assignable(&self.aliases, expected, found))
+    }
+
+    fn error(&mut self, message: String, declaration: Option<Range<usize>>) {
+        self.errors.push(TypeError {
+            message,
+            span: self.span.clone(),
+            declaration,
+        });
+    }
+
+    fn check_operation(&mut self, operation: &Operation) {
+        let op = match &operation.op {
+            AnyOperation::BinOp(op) => op.clone(),
+            AnyOperation::AssignmentOp(AssignmentOp::Eq) => {
+                return self.check_assignment(operation)
+            }
+            AnyOperation::AssignmentOp(op) => match compound(op) {
+                Some(op) => op,
+                None => return,
+            },
+            _ => return,
+        };
+        let (left, right) = match (self.infer(&operation.left), self.infer(&operation.right)) {
+            (Some(left), Some(right)) => (left, right),
+            _ => return,
+        };
+        // every pair of types the operands can have, a union has more than one.
+        let (lefts, rights) = (self.members(&left), self.members(&right));
+        let mut pairs = 0;
+        let mut allowed = 0;
+        for l in lefts.iter() {
+            for r in rights.iter() {
+                pairs += 1;
+                allowed += match (l, r) {
+                    (TypeKind::BuiltIn(l), TypeKind::BuiltIn(r)) => {
+                        operands_allowed(&op, l, r) as usize
+                    }
+                    _ => 1,
+                };
+            }
+        }
+        if allowed == pairs {
+            return;
+        }
+        let message = match (allowed, lefts.len() > 1) {
+            (0, _) => format!(
+                "`{}` can't be applied to `{}` and `{}`.",
+                operation.op, left, right
+            ),
+            (_, true) => format!(
+                "`{}` can't be applied to every type of `{}`, narrow it with `is` first.",
+                operation.op, left
+            ),
+            (_, false) => format!(
+                "`{}` can't be applied to every type of `{}`, narrow it with `is` first.",
+                operation.op, right
+            ),
+        };
+        let outer = match operation.span.is_empty() {
+            true => self.span.clone(),
+            false => std::mem::replace(&mut self.span, operation.span.clone()),
+        };
+        self.error(message, None);
+        self.span = outer;
+    }
+
+    fn members(&self, ty: &TypeKind) -> Vec<TypeKind> {
+        narrowing::members(&self.aliases, ty)
+    }
+
+    fn passes(&self, member: &TypeKind, tested: &TypeKind) -> bool {
+        narrowing::passes(&self.aliases, member, tested)
+    }
+
+    /// The binding of a variable where a test holds, `None` when the variable isn't a name
+    /// with a known type.
+    fn narrow(&self, test: &Test) -> Option<(String, Binding)> {
+        let binding = self.lookup(test.name)?;
+        let binding = Binding {
+            ty: narrowing::narrow(&self.aliases, &binding.ty, test)?,
+            declaration: None,
+            narrowed: true,
+        };
+        Some((test.name.to_string(), binding))
+    }
+
+    /// The bindings of the variables a condition narrows where it is `holds`.
+    fn narrow_all(&self, condition: &Expression, holds: bool) -> Vec<(String, Binding)> {
+        tests(condition, holds)
+            .iter()
+            .filter_map(|test| self.narrow(test))
+            .collect()
+    }
+
+    /// Reports a test of a variable that is never true, eg: `x is bool` of an `int | string`.
+    fn check_type_test(&mut self, test: &TypeTest) {
+        let name = match test.value.as_ref() {
+            Expression::Literal(literal) if literal.ty.is_none() => &literal.value,
+            _ => return,
+        };
+        let Some(found) = self.lookup(name).map(|binding| binding.ty.clone()) else {
+            return;
+        };
+        let members = self.members(&found);
+        let possible = members.iter().any(|member| {
+            self.passes(member, &test.ty) || assignable(&self.aliases, member, &test.ty)
+        });
+        if !possible {
+            let outer = std::mem::replace(&mut self.span, test.span.clone());
+            self.error(
+                format!("`{}` is never a `{}`, it is `{}`.", name, test.ty, found),
+                None,
+            );
+            self.span = outer;
+        }
+    }
+
+    /// Whether a class extends or implements another, through any of its parents.
+    fn extends(&self, class: &str, ancestor: &str) -> bool {
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut next = vec![class];
+        while let Some(class) = next.pop() {
+            if class == ancestor {
+                return true;
+            }
+            if seen.insert(class) {
+                next.extend(
+                    self.parents
+                        .get(class)
+                        .into_iter()
+                        .flatten()
+                        .map(String::as_str),
+                );
+            }
+        }
+        false
+    }
+
+    /// Whether a type can be given to a type parameter that extends `bound`.
+    /// A class bound is only satisfied by the classes that extend or implement it.
+    fn satisfies(&self, bound: &TypeKind, found: &TypeKind) -> bool {
+        let class = match resolve_alias(&self.aliases, bound) {
+            TypeKind::Reference(bound) if self.parents.contains_key(&bound.name) => &bound.name,
+            bound => return assignable(&self.aliases, bound, found),
+        };
+        match resolve_alias(&self.aliases, found) {
+            TypeKind::Reference(found) => {
+                !self.parents.contains_key(&found.name) || self.extends(&found.name, class)
+            }
+            TypeKind::Union(union) => union.types.iter().all(|ty| self.satisfies(bound, ty)),
+            TypeKind::Nullable(inner) => self.satisfies(bound, inner),
+            TypeKind::BuiltIn(BuiltInType::Any | BuiltInType::Null) => true,
+            _ => false,
+        }
+    }
+
+    /// Checks the type arguments written out for a generic declaration.
+    fn check_type_arguments<'a>(
+        &mut self,
+        name: &str,
+        params: &[TypeParam],
+        arguments: impl IntoIterator<Item = &'a TypeKind>,
+        declaration: Option<Range<usize>>,
+    ) {
+        for (param, argument) in params.iter().zip(arguments) {
+            let (Some(generic), Some(bound)) = (&param.name, param.bound()) else {
+                continue;
+            };
+            if !self.satisfies(bound, argument) {
+                self.error(
+                    format!(
+                        "`{}` of `{}` extends `{}`, but was given `{}`.",
+                        generic, name, bound, argument
+                    ),
+                    declaration.clone(),
+                );
+            }
+        }
+    }
+
+    /// Checks the type arguments a call to a generic function infers from its arguments,
+    /// eg: `string` for `T` in `max("a", "b")`.
+    fn check_inferred_arguments(&mut self, call: &Call, signature: &FunctionSignature) {
+        for (input, argument) in signature.arguments(&call.arguments) {
+            let Some(TypeKind::Reference(reference)) = &input.ty else {
+                continue;
+            };
+            let Some(param) = signature
+                .generics
+                .iter()
+                .find(|g| g.name.as_ref() == Some(&reference.name))
+            else {
+                continue;
+            };
+            let (Some(bound), Some(found)) = (param.bound(), self.infer(argument)) else {
+                continue;
+            };
+            if !self.satisfies(bound, &found) {
+                self.error(
+                    format!(
+                        "`{}` of `{}` extends `{}`, but was given a value of type `{}`.",
+                        reference.name, call.name, bound, found
+                    ),
+                    Some(signature.span.clone()),
+                );
+            }
+        }
+    }
+
+    /// Checks the type arguments of the classes and aliases a type annotation names.
+    fn check_annotation(&mut self, ty: &TypeKind) {
+        match ty {
+            TypeKind::Reference(reference) => {
+                let arguments = reference.params.as_deref().unwrap_or_default();
+                if let Some(params) = self.generics.get(&reference.name).cloned() {
+                    let arguments = arguments.iter().map(|argument| &argument.kind);
+                    self.check_type_arguments(&reference.name, &params, arguments, None);
+                }
+                for argument in arguments {
+                    self.check_annotation(&argument.kind);
+                }
+            }
+            TypeKind::Union(union) => {
+                for ty in union.types.iter() {
+                    self.check_annotation(ty);
+                }
+            }
+            TypeKind::BuiltIn(BuiltInType::Array(inner)) | TypeKind::Nullable(inner) => {
+                self.check_annotation(inner)
+            }
+            TypeKind::BuiltIn(BuiltInType::Map(key, value)) => {
+                self.check_annotation(key);
+                self.check_annotation(value);
+            }
+            TypeKind::Function { params, ret } => {
+                for param in params.iter() {
+                    self.check_annotation(param);
+                }
+                self.check_annotation(ret);
+            }
+            TypeKind::Tuple(types) => {
+                for ty in types.iter() {
+                    self.check_annotation(ty);
+                }
+            }
+            TypeKind::Object(shape) => {
+                for property in shape.properties.iter() {
+                    self.check_annotation(&property.ty);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Visits a node with the variables an `is` test narrowed in scope.
+    fn narrowed(&mut self, bindings: Vec<(String, Binding)>, visit: impl FnOnce(&mut Self)) {
+        self.scopes.push(bindings.into_iter().collect());
+        visit(self);
+        self.scopes.pop();
+    }
+
+    /// Checks a value assigned to a name that was declared with a type.
+    fn check_assignment(&mut self, operation: &Operation) {
+        let name = match operation.left.as_ref() {
+            Expression::Literal(literal) if literal.ty.is_none() => &literal.value,
+            _ => return,
+        };
+        let binding = match self.lookup_declared(name) {
+            Some(binding) if binding.declaration.is_some() => binding.clone(),
+            _ => return,
+        };
+        if let Some(found) = self.mismatch(&binding.ty, &operation.right) {
+            self.error(
+                format!(
+                    "`{}` is declared as `{}`, but was given a value of type `{}`.",
+                    name, binding.ty, found
+                ),
+                binding.declaration,
+            );
+        }
+    }
+}
+
+/// The operator a compound assignment applies, eg: `+` for `+=`.
+fn compound(op: &AssignmentOp) -> Option<BinOp> {
+    match op {
+        AssignmentOp::Add => Some(BinOp::Plus),
+        AssignmentOp::Sub => Some(BinOp::Minus),
+        AssignmentOp::Mul => Some(BinOp::Star),
+        AssignmentOp::Div => Some(BinOp::Slash),
+        AssignmentOp::Rem => Some(BinOp::Percent),
+        AssignmentOp::BitAnd => Some(BinOp::And),
+        AssignmentOp::BitOr => Some(BinOp::Or),
+        AssignmentOp::BitXor => Some(BinOp::Caret),
+        AssignmentOp::BitSh1 => Some(BinOp::Shl),
+        AssignmentOp::BitShr => Some(BinOp::Shr),
+        AssignmentOp::BitUshr => Some(BinOp::UShr),
+        _ => None,
+    }
+}
+
+/// Whether a binary operator can be applied to operands of the given types.
+/// Only numbers, strings and booleans are checked, anything else is accepted.
+fn operands_allowed(op: &BinOp, left: &BuiltInType, right: &BuiltInType) -> bool {
+    let is_integer = |ty: &BuiltInType| {
+        matches!(
+            ty,
+            BuiltInType::Strict(_)
+                | BuiltInType::Byte
+                | BuiltInType::Short
+                | BuiltInType::Int
+                | BuiltInType::Long
+        )
+    };
+    let is_number =
+        |ty: &BuiltInType| is_integer(ty) || matches!(ty, BuiltInType::Float | BuiltInType::Double);
+    let is_scalar =
+        |ty: &BuiltInType| is_number(ty) || matches!(ty, BuiltInType::String | BuiltInType::Bool);
+    if !is_scalar(left) || !is_scalar(right) {
+        return true;
+    }
+    let both = |check: &dyn Fn(&BuiltInType) -> bool| check(left) && check(right);
+    match op {
+        // php adds strings as numbers, so `+` never concatenates.
+        BinOp::Plus | BinOp::Minus | BinOp::Star | BinOp::Slash | BinOp::Percent => {
+            both(&is_number)
+        }
+        BinOp::And | BinOp::Or | BinOp::Caret => {
+            both(&is_integer) || both(&|ty| matches!(ty, BuiltInType::Bool))
+        }
+        BinOp::Shl | BinOp::Shr | BinOp::UShr => both(&is_integer),
+        BinOp::Not | BinOp::Flip => true,
+    }
+}
+
+impl Visitor for TypeChecker {
+    fn visit_expression(&mut self, expr: &Expression) {
+        match expr {
+            Expression::Operation(operation) => {
+                self.check_operation(operation);
+                let holds = match &operation.op {
+                    AnyOperation::LogicalOp(LogicalOp::And) => true,
+                    AnyOperation::LogicalOp(LogicalOp::Or) => false,
+                    _ => return visit::walk_expression(self, expr),
+                };
+                // the right of `x is int && ..` only runs when `x` is an `int`.
+                self.visit_expression(&operation.left);
+                let bindings = self.narrow_all(&operation.left, holds);
+                self.narrowed(bindings, |checker| {
+                    checker.visit_expression(&operation.right)
+                });
+            }
+            Expression::Is(test) => {
+                self.check_type_test(test);
+                visit::walk_expression(self, expr);
+            }
+            Expression::New(call) => {
+                if let Some(params) = self.generics.get(call.name.as_str()).cloned() {
+                    let outer = match call.span.is_empty() {
+                        true => self.span.clone(),
+                        false => std::mem::replace(&mut self.span, call.span.clone()),
+                    };
+                    self.check_type_arguments(call.name.as_str(), &params, &call.generics, None);
+                    self.span = outer;
+                }
+                visit::walk_expression(self, expr);
+            }
+            _ => visit::walk_expression(self, expr),
+        }
+    }
+
+    fn visit_statement(&mut self, stmt: &Statement) {
+        let i = match stmt {
+            Statement::If(i) => i,
+            // what an `if` in a block narrows after it ends with the block.
+            Statement::Block(_) => {
+                let depth = self.scopes.len();
+                self.scopes.push(HashMap::new());
+                visit::walk_statement(self, stmt);
+                self.scopes.truncate(depth);
+                return;
+            }
+            _ => return visit::walk_statement(self, stmt),
+        };
+        self.visit_expression(&i.condition);
+        let bindings = self.narrow_all(&i.condition, true);
+        self.narrowed(bindings, |checker| checker.visit_statement(&i.body));
+        let bindings = self.narrow_all(&i.condition, false);
+        match &i.otherwise {
+            Some(otherwise) => {
+                self.narrowed(bindings, |checker| checker.visit_statement(otherwise))
+            }
+            // the code after `if (x is null) { return; }` only runs when `x` isn't `null`.
+            None if exits(&i.body) => self.scopes.push(bindings.into_iter().collect()),
+            None => {}
+        }
+    }
+
+    fn visit_variable(&mut self, var: &Variable) {
+        let outer = std::mem::replace(&mut self.span, var.span.clone());
+        if let Some(ty) = &var.ty {
+            self.check_annotation(ty);
+        }
+        visit::walk_variable(self, var);
+        if let (Some(expected), Some(value)) = (&var.ty, &var.assignment) {
+            if let Some(found) = self.mismatch(expected, value) {
+                self.error(
+                    format!(
+                        "`{}` is declared as `{}`, but was given a value of type `{}`.",
+                        var.name, expected, found
+                    ),
+                    None,
+                );
+            }
+        }
+        self.span = outer;
+
+        let ty = var
+            .type_of()
+            .cloned()
+            .or_else(|| var.assignment.as_ref().and_then(|value| self.infer(value)));
+        if let Some(ty) = ty {
+            let declaration = var.ty.as_ref().map(|_| var.span.clone());
+            let binding = Binding {
+                ty,
+                declaration,
+                narrowed: false,
+            };
+            self.declare(&var.name, binding);
+        }
+    }
+
+    fn visit_class_property(&mut self, property: &ClassProperty) {
+        if let (Some(expected), Some(value)) = (&property.ty, &property.assignment) {
+            if let Some(found) = self.mismatch(expected, value) {
+                self.error(
+                    format!(
+                        "`{}` is declared as `{}`, but was given a value of type `{}`.",
+                        property.name, expected, found
+                    ),
+                    None,
+                );
+            }
+        }
+        visit::walk_class_property(self, property);
+    }
+
+    fn visit_class_constant(&mut self, constant: &Variable) {
+        let outer = std::mem::replace(&mut self.span, constant.span.clone());
+        if let (Some(expected), Some(value)) = (&constant.ty, &constant.assignment) {
+            if let Some(found) = self.mismatch(expected, value) {
+                self.error(
+                    format!(
+                        "`{}` is declared as `{}`, but was given a value of type `{}`.",
+                        constant.name, expected, found
+                    ),
+                    None,
+                );
+            }
+        }
+        visit::walk_variable(self, constant);
+        self.span = outer;
+    }
+
+    fn visit_function(&mut self, func: &Function) {
+        let outer = std::mem::replace(&mut self.span, func.span.clone());
+        for ty in func.inputs.iter().filter_map(|input| input.ty.as_ref()) {
+            self.check_annotation(ty);
+        }
+        if let Some(ty) = &func.outputs {
+            self.check_annotation(ty);
+        }
+        self.span = outer;
+
+        self.scopes.push(HashMap::new());
+        for input in func.inputs.iter() {
+            if let Some(ty) = input.body_type() {
+                let declaration = match input.span.is_empty() {
+                    true => func.span.clone(),
+                    false => input.span.clone(),
+                };
+                let binding = Binding {
+                    ty,
+                    declaration: Some(declaration),
+                    narrowed: false,
+                };
+                self.declare(&input.name, binding);
+            }
+        }
+        visit::walk_function(self, func);
+        self.scopes.pop();
+    }
+
+    fn visit_call(&mut self, call: &Call) {
+        let outer = std::mem::replace(&mut self.span, call.span.clone());
+        if let Some(signature) = self.functions.get(call.name.as_str()).cloned() {
+            for (input, argument) in signature.arguments(&call.arguments) {
+                let expected = match &input.ty {
+                    Some(expected) => expected,
+                    None => continue,
+                };
+                if let Some(found) = self.mismatch(expected, argument) {
+                    self.error(
+                        format!(
+                            "`{}` of `{}` is `{}`, but was given a value of type `{}`.",
+                            input.name, call.name, expected, found
+                        ),
+                        Some(signature.span.clone()),
+                    );
+                }
+            }
+            match call.generics.is_empty() {
+                true => self.check_inferred_arguments(call, &signature),
+                false => self.check_type_arguments(
+                    call.name.as_str(),
+                    &signature.generics,
+                    &call.generics,
+                    Some(signature.span.clone()),
+                ),
+            }
+        }
+        visit::walk_call(self, call);
+        self.span = outer;
+    }
+}
@@ -0,0 +1,212 @@
+use std::{collections::HashMap, ops::Range};
+
+use crate::compiler::ast::{
+    types::{BuiltInType, TypeKind},
+    visit::{self, Visitor},
+    AstBody, Call, Class, ClassProperty, Expression, Function, Variable,
+};
+
+/// A use of a generic declaration with the wrong amount of type arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArityError {
+    /// The function or class that was given type arguments.
+    pub name: String,
+    /// The amount of type parameters the declaration has.
+    pub expected: usize,
+    /// The amount of type arguments that were given.
+    pub found: usize,
+    /// The span of the call, or of the top level node when the use has no span of its own.
+    pub span: Range<usize>,
+}
+
+impl ArityError {
+    pub fn message(&self) -> String {
+        let plural = |n: usize| if n == 1 { "" } else { "s" };
+        format!(
+            "`{}` expects {} type argument{} but {} {} given.",
+            self.name,
+            self.expected,
+            plural(self.expected),
+            self.found,
+            if self.found == 1 { "was" } else { "were" }
+        )
+    }
+}
+
+/// Checks the type arguments of every call, `new` and type annotation against the
+/// type parameters of the function or class it names.
+///
+/// Type arguments can be left out entirely, they are inferred from the call instead.
+/// Names that aren't declared in the body are not checked.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, CompilerOptions};
+/// # use surn::compiler::infer::generics::check_generics;
+/// // fn first<T>(items: any): T {}
+/// // var x = first<int, string>(items);
+/// # let source = "fn first<T>(items: any): T {}\nvar x = first<int, string>(items);\n";
+/// # let body = Parser::new(CompilerOptions::default())
+/// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+/// check_generics(&body); // [`first` expects 1 type argument but 2 were given.]
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub fn check_generics(body: &AstBody) -> Vec<ArityError> {
+    let mut collector = DeclarationCollector {
+        functions: HashMap::new(),
+        classes: HashMap::new(),
+    };
+    visit::walk_body(&mut collector, body);
+
+    let mut checker = ArityChecker {
+        functions: collector.functions,
+        classes: collector.classes,
+        span: 0..0,
+        errors: Vec::new(),
+    };
+    for node in body.get_program() {
+        checker.span = node.start()..node.end();
+        visit::walk_node(&mut checker, node);
+    }
+    checker.errors
+}
+
+/// Collects the amount of type parameters of every named function and class.
+struct DeclarationCollector {
+    functions: HashMap<String, usize>,
+    classes: HashMap<String, usize>,
+}
+
+impl Visitor for DeclarationCollector {
+    fn visit_function(&mut self, func: &Function) {
+        if let Some(name) = &func.name {
+            self.functions.insert(name.clone(), func.generics.len());
+        }
+        visit::walk_function(self, func);
+    }
+
+    fn visit_class(&mut self, class: &Class) {
+        self.classes
+            .insert(class.name.clone(), class.generics.len());
+        visit::walk_class(self, class);
+    }
+}
+
+struct ArityChecker {
+    functions: HashMap<String, usize>,
+    classes: HashMap<String, usize>,
+    /// The span of the top level node being checked.
+    span: Range<usize>,
+    errors: Vec<ArityError>,
+}
+
+impl ArityChecker {
+    fn check(&mut self, name: &str, expected: Option<usize>, found: usize, span: Range<usize>) {
+        match expected {
+            Some(expected) if found > 0 && found != expected => self.errors.push(ArityError {
+                name: name.to_string(),
+                expected,
+                found,
+                span,
+            }),
+            _ => {}
+        }
+    }
+
+    fn check_type(&mut self, ty: &TypeKind) {
+        match ty {
+            TypeKind::Reference(reference) => {
+                let params = reference.params.as_deref().unwrap_or_default();
+                let expected = self.classes.get(&reference.name).copied();
+                self.check(&reference.name, expected, params.len(), self.span.clone());
+                for param in params {
+                    self.check_type(&param.kind);
+                }
+            }
+            TypeKind::Union(union) => {
+                for ty in union.types.iter() {
+                    self.check_type(ty);
+                }
+            }
+            TypeKind::BuiltIn(BuiltInType::Array(inner)) | TypeKind::Nullable(inner) => {
+                self.check_type(inner)
+            }
+            TypeKind::BuiltIn(BuiltInType::Map(key, value)) => {
+                self.check_type(key);
+                self.check_type(value);
+            }
+            TypeKind::Function { params, ret } => {
+                for param in params.iter() {
+                    self.check_type(param);
+                }
+                self.check_type(ret);
+            }
+            TypeKind::Tuple(types) => {
+                for ty in types.iter() {
+                    self.check_type(ty);
+                }
+            }
+            TypeKind::Object(shape) => {
+                for property in shape.properties.iter() {
+                    self.check_type(&property.ty);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Visitor for ArityChecker {
+    fn visit_expression(&mut self, expr: &Expression) {
+        if let Expression::New(call) = expr {
+            let expected = self.classes.get(call.name.as_str()).copied();
+            let span = match call.span.is_empty() {
+                true => self.span.clone(),
+                false => call.span.clone(),
+            };
+            self.check(&call.name, expected, call.generics.len(), span);
+            for ty in call.generics.iter() {
+                self.check_type(ty);
+            }
+        }
+        visit::walk_expression(self, expr);
+    }
+
+    fn visit_call(&mut self, call: &Call) {
+        let expected = self.functions.get(call.name.as_str()).copied();
+        let span = if call.span.is_empty() {
+            self.span.clone()
+        } else {
+            call.span.clone()
+        };
+        self.check(&call.name, expected, call.generics.len(), span);
+        for ty in call.generics.iter() {
+            self.check_type(ty);
+        }
+        visit::walk_call(self, call);
+    }
+
+    fn visit_variable(&mut self, var: &Variable) {
+        if let Some(ty) = &var.ty {
+            self.check_type(ty);
+        }
+        visit::walk_variable(self, var);
+    }
+
+    fn visit_function(&mut self, func: &Function) {
+        for ty in func.inputs.iter().filter_map(|input| input.ty.as_ref()) {
+            self.check_type(ty);
+        }
+        if let Some(ty) = &func.outputs {
+            self.check_type(ty);
+        }
+        visit::walk_function(self, func);
+    }
+
+    fn visit_class_property(&mut self, property: &ClassProperty) {
+        if let Some(ty) = &property.ty {
+            self.check_type(ty);
+        }
+        visit::walk_class_property(self, property);
+    }
+}
@@ -0,0 +1,386 @@
+use std::{collections::HashMap, ops::Range};
+
+pub mod awaits;
+pub mod checker;
+pub mod generics;
+mod narrowing;
+pub mod nullability;
+pub mod purity;
+pub mod shapes;
+
+use crate::compiler::ast::{
+    ops::AnyOperation,
+    types::{BuiltInType, ObjectShape, ShapeProperty, TypeKind, TypeParam},
+    visit::{self, Visitor},
+    AstBody, Class, ClassAllowedStatement, Expression, Function, FunctionInput, MemberLookup,
+    NodeKind, Statement, Variable,
+};
+
+/// The signature of a declared function.
+/// This is what call sites are checked and inferred against.
+#[derive(Debug, Clone)]
+pub struct FunctionSignature {
+    pub inputs: Vec<FunctionInput>,
+    pub outputs: Option<TypeKind>,
+    /// The type parameters, with the types they extend.
+    pub generics: Vec<TypeParam>,
+    /// The range of the `fn` keyword and the name of the declaration.
+    pub span: Range<usize>,
+}
+
+impl FunctionSignature {
+    /// The arguments of a call with the input each is given to, the arguments after
+    /// the last input are given to it when it is variadic.
+    /// A spread gives an unknown amount of values, so the arguments from it on are left out.
+    pub fn arguments<'a>(
+        &'a self,
+        arguments: &'a [Expression],
+    ) -> impl Iterator<Item = (&'a FunctionInput, &'a Expression)> {
+        let rest = self.inputs.last().filter(|input| input.variadic);
+        arguments
+            .iter()
+            .take_while(|argument| !matches!(argument, Expression::Spread(_)))
+            .enumerate()
+            .filter_map(move |(i, argument)| Some((self.inputs.get(i).or(rest)?, argument)))
+    }
+}
+
+/// Local type inference over a single `AstBody`.
+///
+/// Every declaration is given a type, either the one it was annotated with
+/// or the one derived from its initializer. The results are keyed by the
+/// span of the declaration.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{infer::TypeInference, parser::Parser, CompilerOptions};
+/// // var x = 5;
+/// # let body = Parser::new(CompilerOptions::default())
+/// #     .try_parse_script("main.surn".to_string(), "var x = 5;".to_string())?;
+/// let inference = TypeInference::run(&body);
+/// inference.variable_type(&(0..10)); // Some(int)
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct TypeInference {
+    variables: HashMap<Range<usize>, TypeKind>,
+    functions: HashMap<String, FunctionSignature>,
+}
+
+impl TypeInference {
+    pub fn run(body: &AstBody) -> Self {
+        let mut inferrer = Inferrer {
+            result: TypeInference {
+                variables: HashMap::new(),
+                functions: collect_signatures(body),
+            },
+            scopes: vec![HashMap::new()],
+        };
+        visit::walk_body(&mut inferrer, body);
+        inferrer.result
+    }
+
+    /// Gets the type of the declaration at the given span.
+    pub fn variable_type(&self, span: &Range<usize>) -> Option<&TypeKind> {
+        self.variables.get(span)
+    }
+
+    /// Gets the signature of a function declared with the given name.
+    pub fn function(&self, name: &str) -> Option<&FunctionSignature> {
+        self.functions.get(name)
+    }
+}
+
+/// Stores the inferred type of every variable declared without an annotation on
+/// the variable, see `Variable::inferred`.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, CompilerOptions};
+/// # use surn::compiler::ast::{NodeKind, Statement};
+/// # use surn::compiler::infer::infer_variables;
+/// // var x = 5;
+/// # let source = "var x = 5;\n";
+/// # let mut body = Parser::new(CompilerOptions::default())
+/// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+/// infer_variables(&mut body);
+/// # let NodeKind::Statement(Statement::Var(var)) = &body.get_program()[0].inner else { unreachable!() };
+/// &var.inferred; // Some(int)
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub fn infer_variables(body: &mut AstBody) {
+    let inference = TypeInference::run(body);
+    for node in body.get_program_mut() {
+        match &mut node.inner {
+            NodeKind::Statement(stmt) => assign_statement(stmt, &inference),
+            NodeKind::Expression(expr) => assign_expression(expr, &inference),
+        }
+    }
+}
+
+fn assign_statement(stmt: &mut Statement, inference: &TypeInference) {
+    match stmt {
+        Statement::Var(var) | Statement::Const(var) if var.ty.is_none() => {
+            var.inferred = inference.variable_type(&var.span).cloned();
+        }
+        Statement::Static(s) => assign_statement(&mut s.statement, inference),
+        Statement::Function(func) => assign_statement(&mut func.body, inference),
+        Statement::Class(class) => {
+            for method in class.body.methods.iter_mut() {
+                assign_statement(&mut method.body, inference);
+            }
+            for other in class.body.other.iter_mut() {
+                assign_class_statement(other, inference);
+            }
+        }
+        Statement::Block(exprs) => {
+            for expr in exprs.iter_mut() {
+                assign_expression(expr, inference);
+            }
+        }
+        Statement::Namespace(namespace) => {
+            if let Some(body) = &mut namespace.body {
+                assign_statement(body, inference);
+            }
+        }
+        Statement::Try(t) => {
+            assign_statement(&mut t.body, inference);
+            for catch in t.catches.iter_mut() {
+                assign_statement(&mut catch.body, inference);
+            }
+            if let Some(finally) = &mut t.finally {
+                assign_statement(finally, inference);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn assign_class_statement(stmt: &mut ClassAllowedStatement, inference: &TypeInference) {
+    match stmt {
+        ClassAllowedStatement::Method(method) => assign_statement(&mut method.body, inference),
+        ClassAllowedStatement::Static(inner) => assign_class_statement(inner, inference),
+        _ => {}
+    }
+}
+
+fn assign_expression(expr: &mut Expression, inference: &TypeInference) {
+    if let Expression::Statement(stmt) = expr {
+        assign_statement(stmt, inference);
+    }
+}
+
+/// The signatures of all named functions declared in a body.
+pub fn collect_signatures(body: &AstBody) -> HashMap<String, FunctionSignature> {
+    let mut collector = SignatureCollector {
+        functions: HashMap::new(),
+    };
+    visit::walk_body(&mut collector, body);
+    collector.functions
+}
+
+/// Collects the signatures of all named functions, methods are excluded
+/// because they can not be called without a callee.
+struct SignatureCollector {
+    functions: HashMap<String, FunctionSignature>,
+}
+
+impl Visitor for SignatureCollector {
+    fn visit_function(&mut self, func: &Function) {
+        if let Some(name) = &func.name {
+            self.functions.insert(
+                name.clone(),
+                FunctionSignature {
+                    inputs: func.inputs.clone(),
+                    outputs: func.outputs.clone(),
+                    generics: func.generics.clone(),
+                    span: func.span.clone(),
+                },
+            );
+        }
+        visit::walk_function(self, func);
+    }
+
+    fn visit_class(&mut self, _class: &Class) {}
+}
+
+struct Inferrer {
+    result: TypeInference,
+    scopes: Vec<HashMap<String, TypeKind>>,
+}
+
+impl Inferrer {
+    fn lookup(&self, name: &str) -> Option<TypeKind> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .cloned()
+    }
+}
+
+impl Visitor for Inferrer {
+    fn visit_statement(&mut self, stmt: &Statement) {
+        visit::walk_statement(self, stmt);
+        if let Statement::Destructure(destructure) = stmt {
+            let ty = destructure.ty.clone().or_else(|| {
+                infer_expression(
+                    &destructure.assignment,
+                    &|name| self.lookup(name),
+                    &self.result.functions,
+                )
+            });
+            if let Some(ty) = ty {
+                let scope = self.scopes.last_mut().unwrap();
+                scope.extend(tuple_bindings(&destructure.names, &ty));
+            }
+        }
+    }
+
+    fn visit_variable(&mut self, var: &Variable) {
+        visit::walk_variable(self, var);
+        let ty = match var.type_of() {
+            Some(ty) => Some(ty.clone()),
+            None => var.assignment.as_ref().and_then(|expr| {
+                infer_expression(expr, &|name| self.lookup(name), &self.result.functions)
+            }),
+        };
+
+        if let Some(ty) = ty {
+            self.scopes
+                .last_mut()
+                .unwrap()
+                .insert(var.name.to_string(), ty.clone());
+            self.result.variables.insert(var.span.clone(), ty);
+        }
+    }
+
+    fn visit_function(&mut self, func: &Function) {
+        let mut scope = HashMap::new();
+        for input in func.inputs.iter() {
+            if let Some(ty) = input.body_type() {
+                scope.insert(input.name.clone(), ty);
+            }
+        }
+        self.scopes.push(scope);
+        visit::walk_function(self, func);
+        self.scopes.pop();
+    }
+}
+
+/// The type of every name a tuple is destructured into, names without a value are left out.
+pub fn tuple_bindings(names: &[String], ty: &TypeKind) -> Vec<(String, TypeKind)> {
+    match ty {
+        TypeKind::Tuple(types) => names.iter().cloned().zip(types.iter().cloned()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Infers the type of a single expression.
+/// `lookup` resolves the type of a name that is in scope.
+pub fn infer_expression(
+    expr: &Expression,
+    lookup: &dyn Fn(&str) -> Option<TypeKind>,
+    functions: &HashMap<String, FunctionSignature>,
+) -> Option<TypeKind> {
+    match expr {
+        Expression::Literal(literal) => match &literal.ty {
+            Some(ty) => Some(ty.clone()),
+            None => lookup(&literal.value),
+        },
+        Expression::Call(call) => functions
+            .get(call.name.as_str())
+            .and_then(|signature| signature.outputs.clone()),
+        Expression::New(call) => Some(TypeKind::reference(call.name.clone(), None)),
+        // the element type is the union of the types of the values, eg: `(int | string)[]`.
+        Expression::Array(array) => {
+            let mut elements: Vec<TypeKind> = Vec::new();
+            for value in array.values.iter() {
+                let Some(ty) = infer_expression(value, lookup, functions) else {
+                    elements = vec![TypeKind::BuiltIn(BuiltInType::Any)];
+                    break;
+                };
+                let types = match ty {
+                    TypeKind::Union(union) => union.types,
+                    ty => vec![ty],
+                };
+                for ty in types {
+                    if !elements.iter().any(|e| e.to_string() == ty.to_string()) {
+                        elements.push(ty);
+                    }
+                }
+            }
+            let element = match elements.len() {
+                0 => TypeKind::BuiltIn(BuiltInType::Any),
+                1 => elements.pop().unwrap(),
+                _ => TypeKind::union(elements),
+            };
+            Some(TypeKind::BuiltIn(BuiltInType::Array(Box::new(element))))
+        }
+        Expression::Tuple(values) => Some(TypeKind::Tuple(
+            values
+                .iter()
+                .map(|value| {
+                    infer_expression(value, lookup, functions)
+                        .unwrap_or(TypeKind::BuiltIn(BuiltInType::Any))
+                })
+                .collect(),
+        )),
+        Expression::Object(object) => object.ty.clone().or_else(|| {
+            Some(TypeKind::Object(ObjectShape::new(
+                object
+                    .properties
+                    .iter()
+                    .map(|p| ShapeProperty {
+                        name: p.name.clone(),
+                        ty: infer_expression(&p.value, lookup, functions)
+                            .unwrap_or(TypeKind::BuiltIn(BuiltInType::Any)),
+                    })
+                    .collect(),
+            )))
+        }),
+        // the values of an array or a map are of its element type, eg: `int` for `items[0]`.
+        Expression::Member(member) if matches!(member.lookup, MemberLookup::Index) => {
+            match lookup(&member.origin.value()?)? {
+                TypeKind::BuiltIn(BuiltInType::Array(element) | BuiltInType::Map(_, element)) => {
+                    Some(*element)
+                }
+                _ => None,
+            }
+        }
+        Expression::Await(inner) => infer_expression(inner, lookup, functions),
+        Expression::Is(_) => Some(TypeKind::BuiltIn(BuiltInType::Bool)),
+        // a spread is not a value on its own.
+        Expression::Spread(_) => None,
+        Expression::Operation(op) => match &op.op {
+            AnyOperation::ComparisonOp(_) | AnyOperation::LogicalOp(_) => {
+                Some(TypeKind::BuiltIn(BuiltInType::Bool))
+            }
+            AnyOperation::AssignmentOp(_) => infer_expression(&op.right, lookup, functions),
+            AnyOperation::BinOp(_) => {
+                let left = infer_expression(&op.left, lookup, functions)?;
+                let right = infer_expression(&op.right, lookup, functions)?;
+                infer_binary(&left, &right)
+            }
+            AnyOperation::UnaryOp(_) => infer_expression(&op.right, lookup, functions),
+        },
+        _ => None,
+    }
+}
+
+fn infer_binary(left: &TypeKind, right: &TypeKind) -> Option<TypeKind> {
+    match (left, right) {
+        (TypeKind::BuiltIn(BuiltInType::Int), TypeKind::BuiltIn(BuiltInType::Int)) => {
+            Some(left.clone())
+        }
+        (TypeKind::BuiltIn(BuiltInType::Float), TypeKind::BuiltIn(BuiltInType::Int))
+        | (TypeKind::BuiltIn(BuiltInType::Int), TypeKind::BuiltIn(BuiltInType::Float))
+        | (TypeKind::BuiltIn(BuiltInType::Float), TypeKind::BuiltIn(BuiltInType::Float)) => {
+            Some(TypeKind::BuiltIn(BuiltInType::Float))
+        }
+        (TypeKind::BuiltIn(BuiltInType::Bool), TypeKind::BuiltIn(BuiltInType::Bool)) => {
+            Some(left.clone())
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,150 @@
+//! What a condition tells about the variables it tests, eg: `x` is an `int` in the body
+//! of `if (x is int)` and isn't `null` after `if (x == null) { return 0; }`.
+//! Both `check_types` and `check_nulls` narrow the variables of a condition this way.
+use std::collections::HashMap;
+
+use crate::compiler::ast::{
+    ops::{AnyOperation, ComparisonOp, LogicalOp},
+    types::{BuiltInType, TypeKind, TypeUnion},
+    Expression, Statement,
+};
+
+use super::shapes::{assignable, resolve_alias};
+
+/// A test of a variable, eg: `x is int` or `x != null`.
+#[derive(Debug, Clone)]
+pub(super) struct Test<'a> {
+    pub name: &'a str,
+    /// The type tested for, `null` for a comparison with `null`.
+    pub ty: TypeKind,
+    /// Whether the variable passes the test, or fails it.
+    pub passed: bool,
+}
+
+/// The tests a condition makes that hold where it is `holds`, eg: both tests of
+/// `x is int && y != null` where it is true, none of them where it is false.
+pub(super) fn tests(condition: &Expression, holds: bool) -> Vec<Test<'_>> {
+    let Expression::Operation(operation) = condition else {
+        return match condition {
+            Expression::Is(test) => variable(&test.value)
+                .map(|name| Test {
+                    name,
+                    ty: test.ty.clone(),
+                    passed: holds,
+                })
+                .into_iter()
+                .collect(),
+            _ => Vec::new(),
+        };
+    };
+    let (left, right) = (operation.left.as_ref(), operation.right.as_ref());
+    match &operation.op {
+        // both sides hold where an `&&` is true, and where an `||` is false.
+        AnyOperation::LogicalOp(LogicalOp::And) if holds => {
+            let mut found = tests(left, true);
+            found.extend(tests(right, true));
+            found
+        }
+        AnyOperation::LogicalOp(LogicalOp::Or) if !holds => {
+            let mut found = tests(left, false);
+            found.extend(tests(right, false));
+            found
+        }
+        AnyOperation::ComparisonOp(op @ (ComparisonOp::Eq | ComparisonOp::NotEq)) => {
+            let name = match (variable(left), variable(right)) {
+                (Some(name), _) if is_null(right) => name,
+                (_, Some(name)) if is_null(left) => name,
+                _ => return Vec::new(),
+            };
+            vec![Test {
+                name,
+                ty: TypeKind::BuiltIn(BuiltInType::Null),
+                passed: holds == matches!(op, ComparisonOp::Eq),
+            }]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// The types a value of a type can have, the members of a union, or of a nullable
+/// type and `null`, or the type itself.
+pub(super) fn members(aliases: &HashMap<String, TypeKind>, ty: &TypeKind) -> Vec<TypeKind> {
+    match resolve_alias(aliases, ty) {
+        TypeKind::Union(union) => union
+            .types
+            .iter()
+            .flat_map(|t| members(aliases, t))
+            .collect(),
+        TypeKind::Nullable(inner) => {
+            let mut found = members(aliases, inner);
+            found.push(TypeKind::BuiltIn(BuiltInType::Null));
+            found
+        }
+        ty => vec![ty.clone()],
+    }
+}
+
+/// Whether a value of type `member` passes an `is` test of `tested`.
+pub(super) fn passes(
+    aliases: &HashMap<String, TypeKind>,
+    member: &TypeKind,
+    tested: &TypeKind,
+) -> bool {
+    match member {
+        // anything accepts a `null`, only the types that are nullable pass it.
+        TypeKind::BuiltIn(BuiltInType::Null) => matches!(
+            resolve_alias(aliases, tested),
+            TypeKind::BuiltIn(BuiltInType::Null | BuiltInType::Any) | TypeKind::Nullable(_)
+        ),
+        member => assignable(aliases, tested, member),
+    }
+}
+
+/// The type a variable of type `ty` has where a test holds, the tested type when it
+/// passes and the types that don't pass when it fails.
+/// `None` when no type is left, eg: an `int` that isn't an `int`.
+pub(super) fn narrow(
+    aliases: &HashMap<String, TypeKind>,
+    ty: &TypeKind,
+    test: &Test,
+) -> Option<TypeKind> {
+    if test.passed {
+        return Some(test.ty.clone());
+    }
+    let mut left: Vec<TypeKind> = members(aliases, ty)
+        .into_iter()
+        .filter(|member| !passes(aliases, member, &test.ty))
+        .collect();
+    match left.len() {
+        0 => None,
+        1 => left.pop(),
+        _ => Some(TypeKind::Union(Box::new(TypeUnion::new(left)))),
+    }
+}
+
+/// Whether a statement never finishes, so the code after it only runs when the
+/// condition of an `if` it is the body of was false, eg: `{ return 0; }`.
+pub(super) fn exits(stmt: &Statement) -> bool {
+    match stmt {
+        Statement::Return(_) | Statement::Throw(_) => true,
+        Statement::Block(exprs) => exprs.iter().rev().any(|expr| match expr {
+            Expression::Statement(stmt) => exits(stmt),
+            _ => false,
+        }),
+        Statement::If(i) => exits(&i.body) && i.otherwise.as_deref().is_some_and(exits),
+        _ => false,
+    }
+}
+
+/// The name of a variable, eg: `x`.
+fn variable(expr: &Expression) -> Option<&str> {
+    match expr {
+        Expression::Literal(literal) if literal.ty.is_none() => Some(&literal.value),
+        _ => None,
+    }
+}
+
+fn is_null(expr: &Expression) -> bool {
+    matches!(expr, Expression::Literal(literal)
+        if matches!(literal.ty, Some(TypeKind::BuiltIn(BuiltInType::Null))))
+}
@@ -0,0 +1,335 @@
+use std::{collections::HashMap, ops::Range};
+
+use crate::compiler::ast::{
+    ops::{AnyOperation, ComparisonOp, LogicalOp, UnaryOp},
+    types::{BuiltInType, TypeKind},
+    visit::{self, Visitor},
+    AstBody, Call, Class, Expression, Function, MemberLookup, Statement, Variable,
+};
+
+use super::{
+    infer_expression,
+    narrowing::{self, exits, tests},
+    shapes::type_aliases,
+    FunctionSignature,
+};
+
+/// A value that may be `null` used where a non-nullable type is expected.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NullError {
+    pub message: String,
+    /// The span of the declaration, call or return, or of the top level node when it has
+    /// no span of its own.
+    pub span: Range<usize>,
+}
+
+/// Checks that values which may be `null` only end up in nullable types.
+///
+/// This covers declarations with a type annotation, arguments to declared functions,
+/// return values, and the members and operators used on a value. Values whose type
+/// can't be inferred are not checked.
+///
+/// A variable is narrowed by the conditions it is tested in, eg: `x != null` or `x is int`,
+/// like `check_types` narrows it, and after an `if` that returns when it is `null`.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, CompilerOptions};
+/// # use surn::compiler::infer::nullability::check_nulls;
+/// // var name: string? = null;
+/// // var upper: string = name;
+/// # let source = "var name: string? = null;\nvar upper: string = name;\n";
+/// # let body = Parser::new(CompilerOptions::default())
+/// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+/// check_nulls(&body); // [`upper` is not nullable but was given a value of type `string?`.]
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub fn check_nulls(body: &AstBody) -> Vec<NullError> {
+    let mut collector = SignatureCollector {
+        functions: HashMap::new(),
+    };
+    visit::walk_body(&mut collector, body);
+
+    let mut checker = NullChecker {
+        aliases: type_aliases(body),
+        functions: collector.functions,
+        scopes: vec![HashMap::new()],
+        returns: Vec::new(),
+        span: 0..0,
+        errors: Vec::new(),
+    };
+    for node in body.get_program() {
+        checker.span = node.start()..node.end();
+        visit::walk_node(&mut checker, node);
+    }
+    checker.errors
+}
+
+/// Collects the signatures of all named functions, with type parameters erased to `any`
+/// as they may be given `null`.
+struct SignatureCollector {
+    functions: HashMap<String, FunctionSignature>,
+}
+
+impl Visitor for SignatureCollector {
+    fn visit_function(&mut self, func: &Function) {
+        if let Some(name) = &func.name {
+            let erase = |ty: &TypeKind| match ty {
+                TypeKind::Reference(r)
+                    if func
+                        .generics
+                        .iter()
+                        .any(|g| g.name.as_ref() == Some(&r.name)) =>
+                {
+                    TypeKind::BuiltIn(BuiltInType::Any)
+                }
+                ty => ty.clone(),
+            };
+            let mut inputs = func.inputs.clone();
+            for input in inputs.iter_mut() {
+                input.ty = input.ty.as_ref().map(erase);
+            }
+            self.functions.insert(
+                name.clone(),
+                FunctionSignature {
+                    inputs,
+                    outputs: func.outputs.as_ref().map(erase),
+                    generics: func.generics.clone(),
+                    span: func.span.clone(),
+                },
+            );
+        }
+        visit::walk_function(self, func);
+    }
+
+    fn visit_class(&mut self, _class: &Class) {}
+}
+
+struct NullChecker {
+    aliases: HashMap<String, TypeKind>,
+    functions: HashMap<String, FunctionSignature>,
+    scopes: Vec<HashMap<String, TypeKind>>,
+    /// The return type of every function being checked, the innermost last.
+    returns: Vec<Option<TypeKind>>,
+    /// The span of the top level node being checked.
+    span: Range<usize>,
+    errors: Vec<NullError>,
+}
+
+impl NullChecker {
+    fn lookup(&self, name: &str) -> Option<TypeKind> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .cloned()
+    }
+
+    /// The type of the value if it may be `null`.
+    fn nullable_type(&self, expr: &Expression) -> Option<TypeKind> {
+        infer_expression(expr, &|name| self.lookup(name), &self.functions)
+            .filter(|ty| ty.may_be_null())
+    }
+
+    /// The types of the variables a condition narrows where it is `holds`.
+    fn narrow(&self, condition: &Expression, holds: bool) -> HashMap<String, TypeKind> {
+        tests(condition, holds)
+            .iter()
+            .filter_map(|test| {
+                let ty = self.lookup(test.name)?;
+                let narrowed = narrowing::narrow(&self.aliases, &ty, test)?;
+                Some((test.name.to_string(), narrowed))
+            })
+            .collect()
+    }
+
+    /// Visits a node with the variables a condition narrowed in scope.
+    fn narrowed(&mut self, scope: HashMap<String, TypeKind>, visit: impl FnOnce(&mut Self)) {
+        self.scopes.push(scope);
+        visit(self);
+        self.scopes.pop();
+    }
+
+    /// Reports members accessed on, and operators applied to, values that may be `null`.
+    fn check_access(&mut self, expr: &Expression) {
+        let message = match expr {
+            Expression::Member(member) if !matches!(member.lookup, MemberLookup::Static) => {
+                let Some(name) = member.origin.text() else {
+                    return;
+                };
+                let Some(found) = self.lookup(name).filter(|ty| ty.may_be_null()) else {
+                    return;
+                };
+                format!(
+                    "A member of `{}` can't be accessed, it is of type `{}` and may be `null`.",
+                    name, found
+                )
+            }
+            Expression::MethodCall(call) => match self.nullable_type(&call.callee) {
+                Some(found) => format!(
+                    "`{}` can't be called on a value of type `{}`, it may be `null`.",
+                    call.name, found
+                ),
+                None => return,
+            },
+            Expression::Operation(operation) => {
+                let operands = match &operation.op {
+                    AnyOperation::BinOp(_)
+                    | AnyOperation::ComparisonOp(
+                        ComparisonOp::GreaterThan
+                        | ComparisonOp::GreaterThanOrEqual
+                        | ComparisonOp::LessThan
+                        | ComparisonOp::LessThanOrEqual,
+                    ) => vec![&operation.left, &operation.right],
+                    AnyOperation::UnaryOp(UnaryOp::Neg | UnaryOp::Pos) => vec![&operation.right],
+                    _ => return,
+                };
+                let Some(found) = operands.into_iter().find_map(|e| self.nullable_type(e)) else {
+                    return;
+                };
+                format!(
+                    "`{}` can't be applied to a value of type `{}`, it may be `null`.",
+                    operation.op, found
+                )
+            }
+            _ => return,
+        };
+        self.errors.push(NullError {
+            message,
+            span: self.span_or_node(&expr.span().unwrap_or_default()),
+        });
+    }
+
+    fn span_or_node(&self, span: &Range<usize>) -> Range<usize> {
+        if span.is_empty() {
+            self.span.clone()
+        } else {
+            span.clone()
+        }
+    }
+}
+
+impl Visitor for NullChecker {
+    fn visit_expression(&mut self, expr: &Expression) {
+        self.check_access(expr);
+        let Expression::Operation(operation) = expr else {
+            return visit::walk_expression(self, expr);
+        };
+        let holds = match &operation.op {
+            AnyOperation::LogicalOp(LogicalOp::And) => true,
+            AnyOperation::LogicalOp(LogicalOp::Or) => false,
+            _ => return visit::walk_expression(self, expr),
+        };
+        // the right of `x != null && ..` only runs when `x` isn't `null`.
+        self.visit_expression(&operation.left);
+        let scope = self.narrow(&operation.left, holds);
+        self.narrowed(scope, |checker| checker.visit_expression(&operation.right));
+    }
+
+    fn visit_statement(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::If(i) => {
+                self.visit_expression(&i.condition);
+                let scope = self.narrow(&i.condition, true);
+                self.narrowed(scope, |checker| checker.visit_statement(&i.body));
+                let scope = self.narrow(&i.condition, false);
+                match &i.otherwise {
+                    Some(otherwise) => {
+                        self.narrowed(scope, |checker| checker.visit_statement(otherwise))
+                    }
+                    // the code after `if (x == null) { return 0; }` only runs when `x` isn't `null`.
+                    None if exits(&i.body) => self.scopes.push(scope),
+                    None => {}
+                }
+                return;
+            }
+            // what an `if` in a block narrows after it ends with the block.
+            Statement::Block(_) => {
+                let depth = self.scopes.len();
+                self.scopes.push(HashMap::new());
+                visit::walk_statement(self, stmt);
+                self.scopes.truncate(depth);
+                return;
+            }
+            _ => {}
+        }
+        if let Statement::Return(ret) = stmt {
+            let expected = self.returns.last().cloned().flatten();
+            let found = ret.expression.as_ref().and_then(|e| self.nullable_type(e));
+            if let (Some(expected), Some(found)) = (expected, found) {
+                if !expected.accepts_null() {
+                    self.errors.push(NullError {
+                        message: format!(
+                            "The function returns `{}` which is not nullable, but a value of type `{}` was returned.",
+                            expected, found
+                        ),
+                        span: self.span_or_node(&ret.span),
+                    });
+                }
+            }
+        }
+        visit::walk_statement(self, stmt);
+    }
+
+    fn visit_variable(&mut self, var: &Variable) {
+        visit::walk_variable(self, var);
+        let found = var.assignment.as_ref().and_then(|e| self.nullable_type(e));
+        if let (Some(expected), Some(found)) = (&var.ty, &found) {
+            if !expected.accepts_null() {
+                self.errors.push(NullError {
+                    message: format!(
+                        "`{}` is not nullable but was given a value of type `{}`.",
+                        var.name, found
+                    ),
+                    span: self.span_or_node(&var.span),
+                });
+            }
+        }
+
+        let ty = var.ty.clone().or_else(|| {
+            var.assignment
+                .as_ref()
+                .and_then(|e| infer_expression(e, &|name| self.lookup(name), &self.functions))
+        });
+        if let Some(ty) = ty {
+            self.scopes
+                .last_mut()
+                .unwrap()
+                .insert(var.name.to_string(), ty);
+        }
+    }
+
+    fn visit_function(&mut self, func: &Function) {
+        let mut scope = HashMap::new();
+        for input in func.inputs.iter() {
+            if let Some(ty) = input.body_type() {
+                scope.insert(input.name.clone(), ty);
+            }
+        }
+        self.scopes.push(scope);
+        self.returns.push(func.outputs.clone());
+        visit::walk_function(self, func);
+        self.returns.pop();
+        self.scopes.pop();
+    }
+
+    fn visit_call(&mut self, call: &Call) {
+        if let Some(signature) = self.functions.get(call.name.as_str()) {
+            for (input, argument) in signature.arguments(&call.arguments) {
+                let found = self.nullable_type(argument);
+                if let (Some(expected), Some(found)) = (&input.ty, found) {
+                    if !expected.accepts_null() {
+                        self.errors.push(NullError {
+                            message: format!(
+                                "`{}` of `{}` is not nullable but was given a value of type `{}`.",
+                                input.name, call.name, found
+                            ),
+                            span: self.span_or_node(&call.span),
+                        });
+                    }
+                }
+            }
+        }
+        visit::walk_call(self, call);
+    }
+}
@@ -0,0 +1,450 @@
+//! Which functions have side effects.
+//!
+//! A function is impure when its body does I/O, reads a superglobal, assigns to a
+//! name it didn't declare, contains a `php!` block, divides by what may be zero,
+//! or calls a function that is impure or can throw.
+//! Calls that can't be resolved, like method calls, are impure too, purity is only
+//! claimed for what can be proven.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::compiler::{
+    ast::{
+        ops::{AnyOperation, AssignmentOp, BinOp},
+        visit::{self, Visitor},
+        AstBody, Call, Class, ClassAllowedStatement, Expression, Function, MemberLookup, NodeKind,
+        Operation, Purity, Statement, Variable,
+    },
+    stdlib,
+};
+
+/// Builtins that read or write outside of the program.
+pub const IO_FUNCTIONS: &[&str] = &[
+    "echo",
+    "print",
+    "printf",
+    "print_r",
+    "var_dump",
+    "header",
+    "setcookie",
+    "session_start",
+    "fopen",
+    "fread",
+    "fwrite",
+    "fclose",
+    "file_get_contents",
+    "file_put_contents",
+    "unlink",
+    "mkdir",
+    "exec",
+    "shell_exec",
+    "mail",
+    "error_log",
+    "rand",
+    "mt_rand",
+    "random_int",
+    "time",
+    "microtime",
+    "date",
+    "exit",
+    "die",
+];
+
+/// Builtins that only compute their result from their arguments.
+pub const PURE_BUILTINS: &[&str] = &[
+    "abs",
+    "ceil",
+    "floor",
+    "round",
+    "sqrt",
+    "pow",
+    "min",
+    "max",
+    "intval",
+    "floatval",
+    "strval",
+    "boolval",
+    "is_null",
+    "is_int",
+    "is_string",
+    "is_array",
+    "strlen",
+    "strtolower",
+    "strtoupper",
+    "trim",
+    "str_replace",
+    "str_contains",
+    "implode",
+    "count",
+    "in_array",
+    "array_keys",
+    "array_values",
+    "array_merge",
+    "array_slice",
+];
+
+/// Builtins that compute their result from their arguments, but throw for some of them,
+/// eg: `explode` with an empty separator. A call to one is kept even when its result isn't used.
+pub const THROWING_BUILTINS: &[&str] = &["substr", "str_repeat", "sprintf", "explode", "intdiv"];
+
+/// The superglobals of php, they can be written with or without the `$`.
+pub const SUPERGLOBALS: &[&str] = &[
+    "GLOBALS", "_GET", "_POST", "_SERVER", "_COOKIE", "_SESSION", "_FILES", "_ENV", "_REQUEST",
+];
+
+/// Infers the purity of every function and method of the body and stores it on them.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, CompilerOptions};
+/// # use surn::compiler::infer::purity::infer_purity;
+/// // fn add(a: int, b: int): int { return a + b; }
+/// // fn log(message: string) { echo(message); }
+/// # let source = "fn add(a: int, b: int): int { return a + b; }\nfn log(message: string) { echo(message); }\n";
+/// # let mut body = Parser::new(CompilerOptions::default())
+/// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+/// infer_purity(&mut body);
+/// // add: pure
+/// // log: impure, calls `echo`, which does I/O
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub fn infer_purity(body: &mut AstBody) {
+    let mut collector = EffectCollector {
+        class: None,
+        functions: HashMap::new(),
+    };
+    visit::walk_body(&mut collector, body);
+    let purities = resolve(collector.functions);
+    for node in body.get_program_mut().iter_mut() {
+        match &mut node.inner {
+            NodeKind::Statement(stmt) => assign_statement(stmt, None, &purities),
+            NodeKind::Expression(expr) => assign_expression(expr, &purities),
+        }
+    }
+}
+
+/// The purity of every named function of the body, as stored by `infer_purity`.
+/// Methods are left out, they can't be called without a callee.
+pub fn function_purities(body: &AstBody) -> HashMap<String, Purity> {
+    struct Collector(HashMap<String, Purity>);
+
+    impl Visitor for Collector {
+        fn visit_function(&mut self, func: &Function) {
+            if let Some(name) = &func.name {
+                self.0.insert(name.clone(), func.purity.clone());
+            }
+            visit::walk_function(self, func);
+        }
+
+        fn visit_class(&mut self, _class: &Class) {}
+    }
+
+    let mut collector = Collector(HashMap::new());
+    visit::walk_body(&mut collector, body);
+    collector.0
+}
+
+/// Whether a function that isn't declared in the body is known to be pure,
+/// a php builtin or a function of the standard library that doesn't do I/O.
+fn is_pure_builtin(name: &str) -> bool {
+    PURE_BUILTINS.contains(&name) || stdlib::find(name).is_some_and(|b| b.effect().is_none())
+}
+
+/// Whether an operation throws for some of its operands, a `/` or `%` by what may be zero.
+fn can_throw(op: &Operation) -> bool {
+    let divides = matches!(
+        op.op,
+        AnyOperation::BinOp(BinOp::Slash | BinOp::Percent)
+            | AnyOperation::AssignmentOp(AssignmentOp::Div)
+    );
+    let nonzero = match op.right.as_ref() {
+        Expression::Literal(literal) if literal.ty.is_some() => {
+            literal.value.parse::<f64>().is_ok_and(|n| n != 0.0)
+        }
+        _ => false,
+    };
+    divides && !nonzero
+}
+
+/// Whether evaluating an expression has no side effect, given the purity of the functions it may call.
+pub fn is_pure_expression(expr: &Expression, purities: &HashMap<String, Purity>) -> bool {
+    match expr {
+        Expression::Literal(literal) => !is_superglobal(&literal.value),
+        Expression::Call(call) => {
+            let callee = purities
+                .get(call.name.as_str())
+                .is_some_and(Purity::is_pure)
+                || is_pure_builtin(call.name.as_str());
+            callee
+                && call
+                    .arguments
+                    .iter()
+                    .all(|a| is_pure_expression(a, purities))
+        }
+        Expression::Array(array) => array.values.iter().all(|v| is_pure_expression(v, purities)),
+        Expression::Tuple(values) => values.iter().all(|v| is_pure_expression(v, purities)),
+        Expression::Object(object) => object
+            .properties
+            .iter()
+            .all(|p| is_pure_expression(&p.value, purities)),
+        Expression::Operation(op) => {
+            !matches!(op.op, AnyOperation::AssignmentOp(_))
+                && !can_throw(op)
+                && is_pure_expression(&op.left, purities)
+                && is_pure_expression(&op.right, purities)
+        }
+        Expression::EndOfLine => true,
+        _ => false,
+    }
+}
+
+fn is_superglobal(name: &str) -> bool {
+    SUPERGLOBALS.contains(&name.trim_start_matches('$'))
+}
+
+/// What the body of a function does by itself, before the functions it calls are known.
+struct Effects {
+    /// The first side effect of the body itself.
+    reason: Option<String>,
+    /// The declared functions it calls.
+    calls: Vec<String>,
+}
+
+/// The key of a function, methods are prefixed with their class, eg: `User::save`.
+fn key(class: Option<&str>, func: &Function) -> Option<String> {
+    let name = func.name.as_ref()?;
+    Some(match class {
+        Some(class) => format!("{}::{}", class, name),
+        None => name.clone(),
+    })
+}
+
+/// Collects the effects of every function, including the ones nested in other functions.
+struct EffectCollector {
+    class: Option<String>,
+    functions: HashMap<String, Effects>,
+}
+
+impl Visitor for EffectCollector {
+    fn visit_function(&mut self, func: &Function) {
+        if let Some(key) = key(self.class.as_deref(), func) {
+            let mut body = BodyEffects {
+                locals: func.inputs.iter().map(|i| i.name.clone()).collect(),
+                reason: None,
+                calls: Vec::new(),
+            };
+            body.visit_statement(&func.body);
+            self.functions.insert(
+                key,
+                Effects {
+                    reason: body.reason,
+                    calls: body.calls,
+                },
+            );
+        }
+        // functions declared in the body are not part of the class.
+        let class = self.class.take();
+        visit::walk_function(self, func);
+        self.class = class;
+    }
+
+    fn visit_class(&mut self, class: &Class) {
+        let outer = self.class.replace(class.name.clone());
+        visit::walk_class(self, class);
+        self.class = outer;
+    }
+}
+
+struct BodyEffects {
+    /// The parameters and the names declared in the body.
+    locals: HashSet<String>,
+    reason: Option<String>,
+    calls: Vec<String>,
+}
+
+impl BodyEffects {
+    fn impure(&mut self, reason: String) {
+        self.reason.get_or_insert(reason);
+    }
+}
+
+impl Visitor for BodyEffects {
+    fn visit_statement(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Destructure(destructure) => {
+                self.locals.extend(destructure.names.iter().cloned());
+            }
+            Statement::Static(_) => self.impure("has a static variable".to_string()),
+            Statement::Throw(_) => self.impure("throws an error".to_string()),
+            Statement::Try(t) => {
+                self.locals
+                    .extend(t.catches.iter().map(|catch| catch.name.clone()));
+            }
+            Statement::MacroInvocation(invocation) if invocation.arms.is_empty() => {
+                self.impure(format!("contains a `{}!` block", invocation.name));
+            }
+            Statement::MacroInvocation(invocation) => {
+                for arm in invocation.arms.iter() {
+                    visit::walk_body(self, &arm.body);
+                }
+            }
+            _ => {}
+        }
+        visit::walk_statement(self, stmt);
+    }
+
+    fn visit_variable(&mut self, var: &Variable) {
+        self.locals.insert(var.name.to_string());
+        visit::walk_variable(self, var);
+    }
+
+    fn visit_function(&mut self, _func: &Function) {
+        // a nested function only has an effect when it is called, which can't be followed.
+    }
+
+    fn visit_expression(&mut self, expr: &Expression) {
+        match expr {
+            Expression::Literal(literal) if is_superglobal(&literal.value) => {
+                self.impure(format!(
+                    "reads the superglobal `${}`",
+                    literal.value.trim_start_matches('$')
+                ));
+            }
+            Expression::MethodCall(_) => self.impure("calls a method".to_string()),
+            Expression::New(call) => self.impure(format!("creates a `{}`", call.name)),
+            Expression::Await(_) => self.impure("awaits a value".to_string()),
+            Expression::Operation(op) if can_throw(op) => {
+                self.impure("divides by a value that may be zero".to_string());
+            }
+            Expression::Operation(op) if matches!(op.op, AnyOperation::AssignmentOp(_)) => {
+                match op.left.as_ref() {
+                    Expression::Literal(name) if !self.locals.contains(&name.value) => {
+                        self.impure(format!("assigns to `{}`, which is not local", name.value));
+                    }
+                    Expression::Member(member) => {
+                        let local = matches!(member.lookup, MemberLookup::Index)
+                            && member
+                                .origin
+                                .value()
+                                .is_some_and(|origin| self.locals.contains(&origin));
+                        if !local {
+                            self.impure("assigns to a member".to_string());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+        visit::walk_expression(self, expr);
+    }
+
+    fn visit_call(&mut self, call: &Call) {
+        let name = call.name.as_str();
+        let effect = match stdlib::find(name) {
+            _ if IO_FUNCTIONS.contains(&name) => Some("does I/O"),
+            _ if THROWING_BUILTINS.contains(&name) => Some("can throw"),
+            Some(builtin) => builtin.effect(),
+            None => None,
+        };
+        if let Some(effect) = effect {
+            self.impure(format!("calls `{}`, which {}", name, effect));
+        } else if self.locals.contains(name) {
+            self.impure(format!("calls `{}`, which is not known", name));
+        } else if !is_pure_builtin(name) {
+            self.calls.push(call.name.to_string());
+        }
+        visit::walk_call(self, call);
+    }
+}
+
+/// Spreads impurity from callees to callers until nothing changes.
+/// Calls to functions that are not declared in the body are impure,
+/// recursive functions are pure unless something else makes them impure.
+fn resolve(functions: HashMap<String, Effects>) -> HashMap<String, Purity> {
+    let mut purities: HashMap<String, Purity> = functions
+        .iter()
+        .map(|(name, effects)| {
+            let purity = match &effects.reason {
+                Some(reason) => Purity::Impure(reason.clone()),
+                None => Purity::Pure,
+            };
+            (name.clone(), purity)
+        })
+        .collect();
+
+    loop {
+        let mut changed = false;
+        for (name, effects) in functions.iter() {
+            if !purities[name].is_pure() {
+                continue;
+            }
+            let reason = effects
+                .calls
+                .iter()
+                .find_map(|callee| match purities.get(callee) {
+                    None => Some(format!("calls `{}`, which is not known", callee)),
+                    Some(Purity::Pure) => None,
+                    Some(_) => Some(format!("calls `{}`, which is impure", callee)),
+                });
+            if let Some(reason) = reason {
+                purities.insert(name.clone(), Purity::Impure(reason));
+                changed = true;
+            }
+        }
+        if !changed {
+            return purities;
+        }
+    }
+}
+
+fn assign_function(func: &mut Function, class: Option<&str>, purities: &HashMap<String, Purity>) {
+    if let Some(purity) = key(class, func).and_then(|key| purities.get(&key)) {
+        func.purity = purity.clone();
+    }
+    assign_statement(&mut func.body, None, purities);
+}
+
+fn assign_statement(stmt: &mut Statement, class: Option<&str>, purities: &HashMap<String, Purity>) {
+    match stmt {
+        Statement::Function(func) => assign_function(func, class, purities),
+        Statement::Class(class) => {
+            for method in class.body.methods.iter_mut() {
+                assign_function(method, Some(&class.name), purities);
+            }
+            for other in class.body.other.iter_mut() {
+                assign_class_statement(other, &class.name, purities);
+            }
+        }
+        Statement::Static(s) => assign_statement(&mut s.statement, class, purities),
+        Statement::Namespace(namespace) => {
+            if let Some(body) = &mut namespace.body {
+                assign_statement(body, class, purities);
+            }
+        }
+        Statement::Block(exprs) => {
+            for expr in exprs.iter_mut() {
+                assign_expression(expr, purities);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn assign_class_statement(
+    stmt: &mut ClassAllowedStatement,
+    class: &str,
+    purities: &HashMap<String, Purity>,
+) {
+    match stmt {
+        ClassAllowedStatement::Method(method) => assign_function(method, Some(class), purities),
+        ClassAllowedStatement::Static(inner) => assign_class_statement(inner, class, purities),
+        _ => {}
+    }
+}
+
+fn assign_expression(expr: &mut Expression, purities: &HashMap<String, Purity>) {
+    if let Expression::Statement(stmt) = expr {
+        assign_statement(stmt, None, purities);
+    }
+}
@@ -0,0 +1,364 @@
+use std::{collections::HashMap, ops::Range};
+
+use crate::compiler::ast::{
+    types::{BuiltInType, TypeKind},
+    visit::{self, Visitor},
+    AstBody, Call, ClassProperty, Expression, Function, Object, Statement, Variable,
+};
+
+use super::{collect_signatures, infer_expression, FunctionSignature};
+
+/// A property of an object literal that doesn't match the shape the object was declared with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShapeError {
+    pub message: String,
+    /// The span of the property, or of the object when a property is missing.
+    pub span: Range<usize>,
+}
+
+/// Checks object literals against the object shape they are declared with.
+///
+/// Literals are checked when they are given to a declaration with a type annotation,
+/// a parameter of a declared function or a return value. Every property is reported
+/// on its own, values whose type can't be inferred are not checked.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, CompilerOptions};
+/// # use surn::compiler::infer::shapes::check_shapes;
+/// // type Point = { x: int, y: int };
+/// // var p: Point = { x: 1, y: "2" };
+/// # let source = "type Point = { x: int, y: int };\nvar p: Point = { x: 1, y: \"2\" };\n";
+/// # let body = Parser::new(CompilerOptions::default())
+/// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+/// check_shapes(&body); // [`y` of `Point` is `int`, but was given a value of type `string`.]
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub fn check_shapes(body: &AstBody) -> Vec<ShapeError> {
+    let mut checker = ShapeChecker {
+        aliases: type_aliases(body),
+        functions: collect_signatures(body),
+        scopes: vec![HashMap::new()],
+        returns: Vec::new(),
+        span: 0..0,
+        errors: Vec::new(),
+    };
+    for node in body.get_program() {
+        checker.span = node.start()..node.end();
+        visit::walk_node(&mut checker, node);
+    }
+    checker.errors
+}
+
+/// The type aliases declared in a body, by name.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, CompilerOptions};
+/// # use surn::compiler::infer::shapes::type_aliases;
+/// // type Point = { x: int, y: int };
+/// # let source = "type Point = { x: int, y: int };\n";
+/// # let body = Parser::new(CompilerOptions::default())
+/// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+/// &type_aliases(&body)["Point"]; // { x: int, y: int }
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub fn type_aliases(body: &AstBody) -> HashMap<String, TypeKind> {
+    let mut collector = AliasCollector {
+        aliases: HashMap::new(),
+    };
+    visit::walk_body(&mut collector, body);
+    collector.aliases
+}
+
+struct AliasCollector {
+    aliases: HashMap<String, TypeKind>,
+}
+
+impl Visitor for AliasCollector {
+    fn visit_statement(&mut self, stmt: &Statement) {
+        if let Statement::TypeDef(def) = stmt {
+            self.aliases.insert(def.name.clone(), def.kind.clone());
+        }
+        visit::walk_statement(self, stmt);
+    }
+}
+
+struct ShapeChecker {
+    aliases: HashMap<String, TypeKind>,
+    functions: HashMap<String, FunctionSignature>,
+    scopes: Vec<HashMap<String, TypeKind>>,
+    /// The return type of every function being checked, the innermost last.
+    returns: Vec<Option<TypeKind>>,
+    /// The span of the top level node being checked.
+    span: Range<usize>,
+    errors: Vec<ShapeError>,
+}
+
+impl ShapeChecker {
+    fn lookup(&self, name: &str) -> Option<TypeKind> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .cloned()
+    }
+
+    fn infer(&self, expr: &Expression) -> Option<TypeKind> {
+        infer_expression(expr, &|name| self.lookup(name), &self.functions)
+    }
+
+    fn resolve<'a>(&'a self, ty: &'a TypeKind) -> &'a TypeKind {
+        resolve_alias(&self.aliases, ty)
+    }
+
+    fn error(&mut self, message: String, span: &Range<usize>) {
+        let span = if span.is_empty() {
+            self.span.clone()
+        } else {
+            span.clone()
+        };
+        self.errors.push(ShapeError { message, span });
+    }
+
+    /// Checks a value that is given where `expected` is declared, if it is an object literal.
+    fn check_value(&mut self, expected: &TypeKind, value: &Expression) {
+        let object = match value {
+            Expression::Object(object) => object,
+            _ => return,
+        };
+        let shape = match self.resolve(expected) {
+            TypeKind::Nullable(inner) => self.resolve(inner),
+            ty => ty,
+        };
+        let shape = match shape {
+            TypeKind::Object(shape) => shape.clone(),
+            TypeKind::BuiltIn(BuiltInType::Map(_, value)) => {
+                let value = value.as_ref().clone();
+                return self.check_map(expected, &value, object);
+            }
+            _ => return,
+        };
+
+        for property in object.properties.iter() {
+            let ty = match shape.property(&property.name) {
+                Some(ty) => ty,
+                None => {
+                    self.error(
+                        format!("`{}` has no property `{}`.", expected, property.name),
+                        &property.span,
+                    );
+                    continue;
+                }
+            };
+            // nested literals are reported property by property.
+            if matches!(property.value, Expression::Object(_)) {
+                self.check_value(ty, &property.value);
+                continue;
+            }
+            if let Some(found) = self.infer(&property.value) {
+                if !self.assignable(ty, &found) {
+                    self.error(
+                        format!(
+                            "`{}` of `{}` is `{}`, but was given a value of type `{}`.",
+                            property.name, expected, ty, found
+                        ),
+                        &property.span,
+                    );
+                }
+            }
+        }
+        for property in shape.properties.iter() {
+            if !object.properties.iter().any(|p| p.name == property.name) {
+                self.error(
+                    format!(
+                        "`{}` is missing the property `{}` of type `{}`.",
+                        expected, property.name, property.ty
+                    ),
+                    &object.span,
+                );
+            }
+        }
+    }
+
+    /// Checks the properties of an object literal given where a map is declared,
+    /// every property is a value of the map.
+    fn check_map(&mut self, expected: &TypeKind, value: &TypeKind, object: &Object) {
+        for property in object.properties.iter() {
+            if matches!(property.value, Expression::Object(_)) {
+                self.check_value(value, &property.value);
+                continue;
+            }
+            if let Some(found) = self.infer(&property.value) {
+                if !self.assignable(value, &found) {
+                    self.error(
+                        format!(
+                            "`{}` of `{}` is `{}`, but was given a value of type `{}`.",
+                            property.name, expected, value, found
+                        ),
+                        &property.span,
+                    );
+                }
+            }
+        }
+    }
+
+    fn assignable(&self, expected: &TypeKind, found: &TypeKind) -> bool {
+        assignable(&self.aliases, expected, found)
+    }
+}
+
+/// Follows aliases until the type is not a reference to one.
+pub(super) fn resolve_alias<'a>(
+    aliases: &'a HashMap<String, TypeKind>,
+    mut ty: &'a TypeKind,
+) -> &'a TypeKind {
+    // an alias of itself would never resolve.
+    for _ in 0..=aliases.len() {
+        match ty {
+            TypeKind::Reference(reference) => match aliases.get(&reference.name) {
+                Some(alias) => ty = alias,
+                None => break,
+            },
+            _ => break,
+        }
+    }
+    ty
+}
+
+/// Whether a value of type `found` can be given where `expected` is declared.
+/// Classes and other types that aren't known well enough are always accepted,
+/// `null` is left to the null checks.
+pub(crate) fn assignable(
+    aliases: &HashMap<String, TypeKind>,
+    expected: &TypeKind,
+    found: &TypeKind,
+) -> bool {
+    match (
+        resolve_alias(aliases, expected),
+        resolve_alias(aliases, found),
+    ) {
+        (TypeKind::BuiltIn(BuiltInType::Any), _) | (_, TypeKind::BuiltIn(BuiltInType::Any)) => true,
+        (_, TypeKind::Nullable(_)) | (_, TypeKind::BuiltIn(BuiltInType::Null)) => true,
+        (TypeKind::Nullable(inner), found) => assignable(aliases, inner, found),
+        // every type a union can have has to fit, one type at a time.
+        (expected, TypeKind::Union(union)) => union
+            .types
+            .iter()
+            .all(|ty| assignable(aliases, expected, ty)),
+        (TypeKind::Union(union), found) => {
+            union.types.iter().any(|ty| assignable(aliases, ty, found))
+        }
+        (
+            TypeKind::BuiltIn(BuiltInType::Array(expected)),
+            TypeKind::BuiltIn(BuiltInType::Array(found)),
+        ) => assignable(aliases, expected, found),
+        (
+            TypeKind::BuiltIn(BuiltInType::Map(key, value)),
+            TypeKind::BuiltIn(BuiltInType::Map(found_key, found_value)),
+        ) => assignable(aliases, key, found_key) && assignable(aliases, value, found_value),
+        // the properties of an object are the string keys of a map.
+        (TypeKind::BuiltIn(BuiltInType::Map(key, value)), TypeKind::Object(found)) => {
+            assignable(aliases, key, &TypeKind::BuiltIn(BuiltInType::String))
+                && found
+                    .properties
+                    .iter()
+                    .all(|p| assignable(aliases, value, &p.ty))
+        }
+        (TypeKind::BuiltIn(expected), TypeKind::BuiltIn(found)) => {
+            built_in_assignable(expected, found)
+        }
+        (TypeKind::Object(expected), TypeKind::Object(found)) => {
+            expected.properties.iter().all(|p| {
+                found
+                    .property(&p.name)
+                    .is_some_and(|ty| assignable(aliases, &p.ty, ty))
+            })
+        }
+        (TypeKind::Object(_), TypeKind::BuiltIn(_))
+        | (TypeKind::BuiltIn(_), TypeKind::Object(_)) => false,
+        _ => true,
+    }
+}
+
+fn built_in_assignable(expected: &BuiltInType, found: &BuiltInType) -> bool {
+    let is_integer = |ty: &BuiltInType| {
+        matches!(
+            ty,
+            BuiltInType::Strict(_)
+                | BuiltInType::Byte
+                | BuiltInType::Short
+                | BuiltInType::Int
+                | BuiltInType::Long
+        )
+    };
+    let is_float = |ty: &BuiltInType| matches!(ty, BuiltInType::Float | BuiltInType::Double);
+    match (expected, found) {
+        (expected, found) if is_integer(expected) => is_integer(found),
+        // integers are widened to floats.
+        (expected, found) if is_float(expected) => is_float(found) || is_integer(found),
+        (expected, found) => expected.to_string() == found.to_string(),
+    }
+}
+
+impl Visitor for ShapeChecker {
+    fn visit_statement(&mut self, stmt: &Statement) {
+        if let Statement::Return(ret) = stmt {
+            if let (Some(Some(expected)), Some(value)) = (self.returns.last(), &ret.expression) {
+                let expected = expected.clone();
+                self.check_value(&expected, value);
+            }
+        }
+        visit::walk_statement(self, stmt);
+    }
+
+    fn visit_variable(&mut self, var: &Variable) {
+        visit::walk_variable(self, var);
+        if let (Some(expected), Some(value)) = (&var.ty, &var.assignment) {
+            self.check_value(expected, value);
+        }
+
+        let ty = var
+            .ty
+            .clone()
+            .or_else(|| var.assignment.as_ref().and_then(|e| self.infer(e)));
+        if let Some(ty) = ty {
+            self.scopes
+                .last_mut()
+                .unwrap()
+                .insert(var.name.to_string(), ty);
+        }
+    }
+
+    fn visit_class_property(&mut self, property: &ClassProperty) {
+        if let (Some(expected), Some(value)) = (&property.ty, &property.assignment) {
+            self.check_value(expected, value);
+        }
+        visit::walk_class_property(self, property);
+    }
+
+    fn visit_function(&mut self, func: &Function) {
+        let mut scope = HashMap::new();
+        for input in func.inputs.iter() {
+            if let Some(ty) = input.body_type() {
+                scope.insert(input.name.clone(), ty);
+            }
+        }
+        self.scopes.push(scope);
+        self.returns.push(func.outputs.clone());
+        visit::walk_function(self, func);
+        self.returns.pop();
+        self.scopes.pop();
+    }
+
+    fn visit_call(&mut self, call: &Call) {
+        if let Some(signature) = self.functions.get(call.name.as_str()).cloned() {
+            for (input, argument) in signature.arguments(&call.arguments) {
+                if let Some(expected) = &input.ty {
+                    self.check_value(expected, argument);
+                }
+            }
+        }
+        visit::walk_call(self, call);
+    }
+}
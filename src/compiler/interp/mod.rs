@@ -0,0 +1,791 @@
+//! Runs surn without generating it to another language, so the meaning of the language
+//! can be tested on its own, see `Interpreter`.
+//!
+//! Operations have the meaning the constant evaluator gives them, see `eval::binary`,
+//! which is the meaning of the php they are generated to.
+use std::{collections::HashMap, fmt, ops::Range};
+
+use crate::compiler::{
+    ast::{
+        ops::{AnyOperation, AssignmentOp, BinOp, LogicalOp, UnaryOp},
+        types::{BuiltInType, TypeKind},
+        AstBody, Expression, Function, MemberListNode, MemberLookup, Node, NodeKind, Statement,
+    },
+    eval::{binary, unary, Chain, ConstEvaluator, ConstValue},
+    lexer::escape::escape,
+};
+
+/// How deep calls can nest before the interpreter gives up, eg: on endless recursion.
+pub const MAX_CALL_DEPTH: usize = 64;
+
+/// A value of a running program.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    String(String),
+    Null,
+    Array(Vec<Value>),
+    Tuple(Vec<Value>),
+    /// An object literal, its properties in the order they were given.
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// The value as a constant, `None` for values that aren't constants, eg: objects.
+    pub fn to_const(&self) -> Option<ConstValue> {
+        let value = match self {
+            Value::Int(value) => ConstValue::Int(*value),
+            Value::Float(value) => ConstValue::Float(*value),
+            Value::Bool(value) => ConstValue::Bool(*value),
+            Value::String(value) => ConstValue::String(value.clone()),
+            Value::Null => ConstValue::Null,
+            Value::Array(values) => ConstValue::Array(
+                values
+                    .iter()
+                    .map(Value::to_const)
+                    .collect::<Option<Vec<ConstValue>>>()?,
+            ),
+            Value::Tuple(_) | Value::Object(_) => return None,
+        };
+        Some(value)
+    }
+
+    /// Whether the value is of a type, eg: for `x is int`.
+    /// Classes and type aliases aren't known to the interpreter, no value is one of them.
+    pub fn is(&self, ty: &TypeKind) -> bool {
+        match (ty, self) {
+            (TypeKind::BuiltIn(BuiltInType::Any), _) => true,
+            (TypeKind::Nullable(inner), value) => *value == Value::Null || value.is(inner),
+            (TypeKind::Union(union), value) => union.types.iter().any(|ty| value.is(ty)),
+            (TypeKind::BuiltIn(built_in), value) => match (built_in, value) {
+                (BuiltInType::Null, Value::Null)
+                | (BuiltInType::Bool, Value::Bool(_))
+                | (BuiltInType::String, Value::String(_))
+                | (BuiltInType::Float | BuiltInType::Double, Value::Float(_))
+                | (
+                    BuiltInType::Strict(_)
+                    | BuiltInType::Byte
+                    | BuiltInType::Short
+                    | BuiltInType::Int
+                    | BuiltInType::Long,
+                    Value::Int(_),
+                ) => true,
+                (BuiltInType::Array(element), Value::Array(values)) => {
+                    values.iter().all(|value| value.is(element))
+                }
+                // the keys of an object are strings.
+                (BuiltInType::Map(key, element), Value::Object(properties)) => {
+                    let string_keys = matches!(
+                        key.as_ref(),
+                        TypeKind::BuiltIn(BuiltInType::String | BuiltInType::Any)
+                    );
+                    (properties.is_empty() || string_keys)
+                        && properties.iter().all(|(_, value)| value.is(element))
+                }
+                _ => false,
+            },
+            (TypeKind::Tuple(types), Value::Tuple(values)) => {
+                types.len() == values.len() && values.iter().zip(types).all(|(v, ty)| v.is(ty))
+            }
+            (TypeKind::Object(shape), value @ Value::Object(_)) => {
+                shape.properties.iter().all(|property| {
+                    value
+                        .property(&property.name)
+                        .is_some_and(|value| value.is(&property.ty))
+                })
+            }
+            _ => false,
+        }
+    }
+
+    /// The property of an object.
+    pub fn property(&self, name: &str) -> Option<&Value> {
+        match self {
+            Value::Object(properties) => properties
+                .iter()
+                .find(|(property, _)| property == name)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+}
+
+impl From<ConstValue> for Value {
+    fn from(value: ConstValue) -> Self {
+        match value {
+            ConstValue::Int(value) => Value::Int(value),
+            ConstValue::Float(value) => Value::Float(value),
+            ConstValue::Bool(value) => Value::Bool(value),
+            ConstValue::String(value) => Value::String(value),
+            ConstValue::Null => Value::Null,
+            ConstValue::Array(values) => {
+                Value::Array(values.into_iter().map(Value::from).collect())
+            }
+        }
+    }
+}
+
+/// Writes the value as it is written in surn source.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let list = |values: &[Value]| {
+            values
+                .iter()
+                .map(|value| value.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+        match self {
+            Value::Int(value) => write!(f, "{}", value),
+            // debug keeps the `.0` of whole numbers, so the value is read back as a float.
+            Value::Float(value) => write!(f, "{:?}", value),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::String(value) => write!(f, "\"{}\"", escape(value)),
+            Value::Null => write!(f, "null"),
+            Value::Array(values) => write!(f, "[{}]", list(values)),
+            Value::Tuple(values) => write!(f, "({})", list(values)),
+            Value::Object(properties) => {
+                let properties = properties
+                    .iter()
+                    .map(|(name, value)| format!("{}: {}", name, value))
+                    .collect::<Vec<String>>();
+                write!(f, "{{ {} }}", properties.join(", "))
+            }
+        }
+    }
+}
+
+/// Why a program stopped, with the span of the expression it stopped at when it is known.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterpError {
+    pub message: String,
+    pub span: Option<Range<usize>>,
+}
+
+impl fmt::Display for InterpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// How the execution of a statement ends early.
+enum Unwind {
+    Return(Value),
+    Throw(Value),
+    Error(InterpError),
+}
+
+impl Unwind {
+    fn error(message: impl Into<String>) -> Self {
+        Unwind::Error(InterpError {
+            message: message.into(),
+            span: None,
+        })
+    }
+
+    /// Places an error at a span, unless an expression inside of it was blamed already.
+    fn at(self, span: Option<Range<usize>>) -> Self {
+        match self {
+            Unwind::Error(mut error) if error.span.is_none() => {
+                error.span = span;
+                Unwind::Error(error)
+            }
+            unwind => unwind,
+        }
+    }
+}
+
+impl From<String> for Unwind {
+    fn from(message: String) -> Self {
+        Unwind::error(message)
+    }
+}
+
+type Exec<T> = Result<T, Unwind>;
+
+/// Runs a subset of surn: literals, operations, variables, functions, arrays, tuples,
+/// objects, `return`, `throw` and `try`, and the builtins that don't do I/O, except
+/// for `print` and `println`, which write to `output`.
+/// Classes can be declared but not constructed, a `catch` catches whatever is thrown.
+///
+/// Functions see the variables of the top level, the way `lower::globals` generates them.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{interp::Interpreter, parser::Parser, CompilerOptions};
+/// // fn double(x: int): int { return x * 2; }
+/// // var y = double(21);
+/// # let source = "fn double(x: int): int { return x * 2; }\nvar y = double(21);\n";
+/// # let body = Parser::new(CompilerOptions::default())
+/// #     .try_parse_script("main.surn".to_string(), source.to_string())
+/// #     .unwrap();
+/// let mut interpreter = Interpreter::new();
+/// interpreter.run(&body)?;
+/// interpreter.get("y"); // Some(Value::Int(42))
+/// # Ok::<(), surn::compiler::interp::InterpError>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct Interpreter {
+    globals: HashMap<String, Value>,
+    functions: HashMap<String, Function>,
+    /// The variables of the functions being called, the innermost is last.
+    frames: Vec<HashMap<String, Value>>,
+    /// What `print` and `println` wrote.
+    output: String,
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs a program, its functions can be called before they are declared.
+    /// Returns the value of the last top level expression.
+    pub fn run(&mut self, body: &AstBody) -> Result<Option<Value>, InterpError> {
+        for node in body.get_program() {
+            if let NodeKind::Statement(Statement::Function(func)) = &node.inner {
+                self.declare(func);
+            }
+        }
+        let mut last = None;
+        for node in body.get_program() {
+            last = self.run_node(node)?;
+        }
+        Ok(last)
+    }
+
+    /// Runs a single top level node, returning its value when it is an expression.
+    /// A top level `return` ends the node with its value.
+    pub fn run_node(&mut self, node: &Node) -> Result<Option<Value>, InterpError> {
+        let span = Some(node.start()..node.end());
+        let result = match &node.inner {
+            NodeKind::Expression(Expression::EndOfLine) => Ok(None),
+            NodeKind::Expression(expr) => self.expression(expr).map(Some),
+            NodeKind::Statement(stmt) => self.statement(stmt).map(|_| None),
+        };
+        match result.map_err(|unwind| unwind.at(span.clone())) {
+            Ok(value) => Ok(value),
+            Err(Unwind::Return(value)) => Ok(Some(value)),
+            Err(Unwind::Throw(value)) => Err(InterpError {
+                message: format!("Uncaught {}.", value),
+                span,
+            }),
+            Err(Unwind::Error(error)) => Err(error),
+        }
+    }
+
+    /// Evaluates an expression in the top level.
+    pub fn evaluate(&mut self, expr: &Expression) -> Result<Value, InterpError> {
+        match self
+            .expression(expr)
+            .map_err(|unwind| unwind.at(expr.span()))
+        {
+            Ok(value) | Err(Unwind::Return(value)) => Ok(value),
+            Err(Unwind::Throw(value)) => Err(InterpError {
+                message: format!("Uncaught {}.", value),
+                span: expr.span(),
+            }),
+            Err(Unwind::Error(error)) => Err(error),
+        }
+    }
+
+    /// Calls a function that was declared, or a builtin.
+    pub fn call(&mut self, name: &str, arguments: Vec<Value>) -> Result<Value, InterpError> {
+        match self.call_function(name, arguments) {
+            Ok(value) => Ok(value),
+            Err(Unwind::Throw(value)) => Err(InterpError {
+                message: format!("Uncaught {}.", value),
+                span: None,
+            }),
+            Err(Unwind::Error(error)) => Err(error),
+            Err(Unwind::Return(value)) => Ok(value),
+        }
+    }
+
+    /// The value of a variable of the top level.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.globals.get(name)
+    }
+
+    pub fn define(&mut self, name: &str, value: Value) {
+        self.globals.insert(name.to_string(), value);
+    }
+
+    /// What `print` and `println` wrote so far.
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+
+    fn declare(&mut self, func: &Function) {
+        if let Some(name) = &func.name {
+            self.functions.insert(name.clone(), func.clone());
+        }
+    }
+
+    fn statement(&mut self, stmt: &Statement) -> Exec<()> {
+        match stmt {
+            Statement::Var(var) | Statement::Const(var) => {
+                let value = match &var.assignment {
+                    Some(value) => self.expression(value)?,
+                    None => Value::Null,
+                };
+                self.declare_variable(&var.name, value);
+            }
+            Statement::Destructure(destructure) => {
+                let values = match self.expression(&destructure.assignment)? {
+                    Value::Tuple(values) | Value::Array(values) => values,
+                    value => return Err(format!("`{}` can't be destructured.", value).into()),
+                };
+                if values.len() < destructure.names.len() {
+                    return Err(format!(
+                        "{} values can't be destructured into {} names.",
+                        values.len(),
+                        destructure.names.len()
+                    )
+                    .into());
+                }
+                for (name, value) in destructure.names.iter().zip(values) {
+                    self.declare_variable(name, value);
+                }
+            }
+            Statement::Static(s) => self.statement(&s.statement)?,
+            Statement::Function(func) => self.declare(func),
+            Statement::Block(exprs) => {
+                for expr in exprs {
+                    self.expression(expr).map_err(|u| u.at(expr.span()))?;
+                }
+            }
+            Statement::Return(ret) => {
+                let value = match &ret.expression {
+                    Some(expr) => self.expression(expr)?,
+                    None => Value::Null,
+                };
+                return Err(Unwind::Return(value));
+            }
+            Statement::Throw(expr) => return Err(Unwind::Throw(self.expression(expr)?)),
+            Statement::If(i) => match self.expression(&i.condition)? {
+                Value::Bool(true) => self.statement(&i.body)?,
+                Value::Bool(false) => {
+                    if let Some(otherwise) = &i.otherwise {
+                        self.statement(otherwise)?;
+                    }
+                }
+                value => {
+                    return Err(
+                        format!("`{}` is not a `bool`, an if only tests a `bool`.", value).into(),
+                    )
+                }
+            },
+            Statement::Try(t) => {
+                let mut result = self.statement(&t.body);
+                if let (Err(Unwind::Throw(value)), Some(catch)) = (&result, t.catches.first()) {
+                    let value = value.clone();
+                    self.declare_variable(&catch.name, value);
+                    result = self.statement(&catch.body);
+                }
+                if let Some(finally) = &t.finally {
+                    self.statement(finally)?;
+                }
+                result?;
+            }
+            Statement::Namespace(namespace) => {
+                if let Some(body) = &namespace.body {
+                    self.statement(body)?;
+                }
+            }
+            // declarations that don't run, classes are only known to the checker.
+            Statement::Class(_)
+            | Statement::Enum(_)
+            | Statement::Import(_)
+            | Statement::TypeDef(_)
+            | Statement::MacroInvocation(_) => {}
+        }
+        Ok(())
+    }
+
+    fn expression(&mut self, expr: &Expression) -> Exec<Value> {
+        let value = match expr {
+            Expression::Literal(literal) if literal.ty.is_none() => self
+                .variable(&literal.value)
+                .cloned()
+                .ok_or_else(|| Unwind::error(format!("`{}` is not defined.", literal.value)))?,
+            Expression::Literal(_) => Value::from(ConstEvaluator::new().evaluate(expr)?),
+            Expression::Array(array) => Value::Array(self.list(&array.values)?),
+            Expression::Tuple(values) => Value::Tuple(self.list(values)?),
+            Expression::Object(object) => {
+                let mut properties = Vec::new();
+                for property in object.properties.iter() {
+                    properties.push((property.name.clone(), self.expression(&property.value)?));
+                }
+                Value::Object(properties)
+            }
+            Expression::Operation(operation) => match &operation.op {
+                AnyOperation::UnaryOp(op) => self.unary(op, &operation.right)?,
+                AnyOperation::AssignmentOp(op) => {
+                    self.assign(op, &operation.left, &operation.right)?
+                }
+                _ => self.chain(&Chain::group(expr)?)?,
+            },
+            Expression::Call(call) => {
+                let arguments = self.list(&call.arguments)?;
+                self.call_function(&call.name, arguments)?
+            }
+            Expression::Member(member) => self.member(member)?,
+            Expression::Await(inner) => self.expression(inner)?,
+            Expression::Is(test) => Value::Bool(self.expression(&test.value)?.is(&test.ty)),
+            Expression::Statement(stmt) => {
+                self.statement(stmt)?;
+                Value::Null
+            }
+            Expression::EndOfLine => Value::Null,
+            Expression::Spread(_) => {
+                return Err(Unwind::error(
+                    "A spread can only be given to an array or a call.",
+                ))
+            }
+            Expression::MethodCall(_) | Expression::New(_) => {
+                return Err(Unwind::error("Classes can't be interpreted yet."))
+            }
+        };
+        Ok(value)
+    }
+
+    /// The values of the elements of an array or the arguments of a call,
+    /// spread arrays give each of their values.
+    fn list(&mut self, exprs: &[Expression]) -> Exec<Vec<Value>> {
+        let mut values = Vec::new();
+        for expr in exprs {
+            match expr {
+                Expression::Spread(inner) => match self.expression(inner)? {
+                    Value::Array(spread) => values.extend(spread),
+                    value => return Err(format!("`{}` can't be spread.", value).into()),
+                },
+                expr => values.push(self.expression(expr)?),
+            }
+        }
+        Ok(values)
+    }
+
+    /// Evaluates a chain of binary operations, `&&`, `||` and `??` only evaluate
+    /// their right side when it is needed.
+    fn chain(&mut self, chain: &Chain) -> Exec<Value> {
+        let (op, left, right) = match chain {
+            Chain::Operand(expr) => return self.expression(expr),
+            Chain::Operation(op, left, right) => (*op, left, right),
+        };
+        let left = self.chain(left)?;
+        match (op, &left) {
+            (AnyOperation::LogicalOp(LogicalOp::And), Value::Bool(false))
+            | (AnyOperation::LogicalOp(LogicalOp::Or), Value::Bool(true)) => return Ok(left),
+            (AnyOperation::LogicalOp(LogicalOp::Coalasce), value) if *value != Value::Null => {
+                return Ok(left)
+            }
+            _ => {}
+        }
+        let right = self.chain(right)?;
+        operate(op, left, right)
+    }
+
+    fn unary(&mut self, op: &UnaryOp, operand: &Expression) -> Exec<Value> {
+        let step = match op {
+            UnaryOp::IncP | UnaryOp::Inc => BinOp::Plus,
+            UnaryOp::DecP | UnaryOp::Dec => BinOp::Minus,
+            op => {
+                let value = self.expression(operand)?;
+                let value = constant(op, &value)?;
+                return Ok(Value::from(unary(op, value)?));
+            }
+        };
+        let before = self.expression(operand)?;
+        let after = operate(&AnyOperation::BinOp(step), before.clone(), Value::Int(1))?;
+        self.store(operand, after.clone())?;
+        match op {
+            UnaryOp::Inc | UnaryOp::Dec => Ok(before),
+            _ => Ok(after),
+        }
+    }
+
+    fn assign(
+        &mut self,
+        op: &AssignmentOp,
+        target: &Expression,
+        value: &Expression,
+    ) -> Exec<Value> {
+        let mut value = self.expression(value)?;
+        if *op != AssignmentOp::Eq {
+            let op = compound(op)
+                .ok_or_else(|| Unwind::error("The assignment can't be interpreted."))?;
+            value = operate(&op, self.expression(target)?, value)?;
+        }
+        self.store(target, value.clone())?;
+        Ok(value)
+    }
+
+    /// Stores a value in a variable, or in an element or property of one.
+    fn store(&mut self, target: &Expression, value: Value) -> Exec<()> {
+        match target {
+            Expression::Literal(literal) if literal.ty.is_none() => {
+                self.assign_variable(&literal.value, value);
+                Ok(())
+            }
+            Expression::Member(member) if !matches!(member.lookup, MemberLookup::Static) => {
+                let origin = member
+                    .origin
+                    .value()
+                    .ok_or_else(|| Unwind::error("Only a variable can be assigned to."))?;
+                let key = self.key(member)?;
+                let mut container = self
+                    .variable(&origin)
+                    .cloned()
+                    .ok_or_else(|| Unwind::error(format!("`{}` is not defined.", origin)))?;
+                match (&mut container, key) {
+                    (Value::Array(values), Value::Int(index)) => {
+                        match usize::try_from(index).ok().filter(|i| *i <= values.len()) {
+                            Some(i) if i == values.len() => values.push(value),
+                            Some(i) => values[i] = value,
+                            None => return Err(format!("{} is out of bounds.", index).into()),
+                        }
+                    }
+                    (Value::Object(properties), Value::String(name)) => {
+                        match properties
+                            .iter_mut()
+                            .find(|(property, _)| *property == name)
+                        {
+                            Some((_, property)) => *property = value,
+                            None => properties.push((name, value)),
+                        }
+                    }
+                    (container, key) => {
+                        return Err(
+                            format!("`{}` of `{}` can't be assigned.", key, container).into()
+                        )
+                    }
+                }
+                self.assign_variable(&origin, container);
+                Ok(())
+            }
+            _ => Err(Unwind::error("Only a variable can be assigned to.")),
+        }
+    }
+
+    fn member(&mut self, member: &MemberListNode) -> Exec<Value> {
+        if matches!(member.lookup, MemberLookup::Static) {
+            return Err(Unwind::error("Classes can't be interpreted yet."));
+        }
+        let origin = member
+            .origin
+            .value()
+            .ok_or_else(|| Unwind::error("Only the members of a variable can be interpreted."))?;
+        let key = self.key(member)?;
+        let container = self
+            .variable(&origin)
+            .ok_or_else(|| Unwind::error(format!("`{}` is not defined.", origin)))?;
+        let found = match (container, &key) {
+            (Value::Array(values) | Value::Tuple(values), Value::Int(index)) => {
+                usize::try_from(*index).ok().and_then(|i| values.get(i))
+            }
+            (Value::Object(_), Value::String(name)) => container.property(name),
+            _ => None,
+        };
+        found
+            .cloned()
+            .ok_or_else(|| Unwind::error(format!("`{}` has no member {}.", origin, key)))
+    }
+
+    /// The index of an element, or the name of a property as a string.
+    fn key(&mut self, member: &MemberListNode) -> Exec<Value> {
+        match (&member.lookup, member.name.as_ref()) {
+            (MemberLookup::Index, index) => self.expression(index),
+            (_, Expression::Literal(name)) => Ok(Value::String(name.value.clone())),
+            _ => Err(Unwind::error("The member can't be interpreted.")),
+        }
+    }
+
+    /// A variable of the function being called, or of the top level.
+    fn variable(&self, name: &str) -> Option<&Value> {
+        self.frames
+            .last()
+            .and_then(|frame| frame.get(name))
+            .or_else(|| self.globals.get(name))
+    }
+
+    fn declare_variable(&mut self, name: &str, value: Value) {
+        match self.frames.last_mut() {
+            Some(frame) => frame.insert(name.to_string(), value),
+            None => self.globals.insert(name.to_string(), value),
+        };
+    }
+
+    /// Assigns the variable that is seen by that name, a new one is declared
+    /// in the function being called.
+    fn assign_variable(&mut self, name: &str, value: Value) {
+        let local = self
+            .frames
+            .last()
+            .is_some_and(|frame| frame.contains_key(name));
+        if !local && self.globals.contains_key(name) {
+            self.globals.insert(name.to_string(), value);
+        } else {
+            self.declare_variable(name, value);
+        }
+    }
+
+    fn call_function(&mut self, name: &str, arguments: Vec<Value>) -> Exec<Value> {
+        let Some(func) = self.functions.get(name).cloned() else {
+            return self.builtin(name, arguments);
+        };
+        if self.frames.len() >= MAX_CALL_DEPTH {
+            return Err(format!("Calls nest deeper than {}.", MAX_CALL_DEPTH).into());
+        }
+
+        let mut frame = HashMap::new();
+        let mut arguments = arguments.into_iter();
+        for input in func.inputs.iter() {
+            let value = match input.variadic {
+                true => Value::Array(arguments.by_ref().collect()),
+                false => arguments.next().ok_or_else(|| {
+                    Unwind::error(format!("`{}` expects a value for `{}`.", name, input.name))
+                })?,
+            };
+            frame.insert(input.name.clone(), value);
+        }
+        if arguments.next().is_some() {
+            return Err(format!("`{}` expects {} arguments.", name, func.inputs.len()).into());
+        }
+
+        self.frames.push(frame);
+        let result = self.statement(&func.body);
+        self.frames.pop();
+        match result {
+            Ok(()) => Ok(Value::Null),
+            Err(Unwind::Return(value)) => Ok(value),
+            Err(unwind) => Err(unwind),
+        }
+    }
+
+    /// The builtins of `stdlib` that can be run, see `Interpreter`.
+    fn builtin(&mut self, name: &str, arguments: Vec<Value>) -> Exec<Value> {
+        let text = |value: &Value| match value {
+            Value::String(value) => value.clone(),
+            value => value.to_string(),
+        };
+        let float = |value: &Value| match value {
+            Value::Int(value) => Ok(*value as f64),
+            Value::Float(value) => Ok(*value),
+            value => Err(Unwind::error(format!("`{}` is not a number.", value))),
+        };
+        let string = |index: usize| match arguments.get(index) {
+            Some(Value::String(value)) => Ok(value.as_str()),
+            _ => Err(Unwind::error(format!(
+                "`{}` expects a string as argument {}.",
+                name,
+                index + 1
+            ))),
+        };
+        let number = |index: usize| {
+            arguments
+                .get(index)
+                .ok_or_else(|| {
+                    Unwind::error(format!("`{}` expects {} arguments.", name, index + 1))
+                })
+                .and_then(float)
+        };
+        let array = |index: usize| match arguments.get(index) {
+            Some(Value::Array(values)) => Ok(values),
+            _ => Err(Unwind::error(format!(
+                "`{}` expects an array as argument {}.",
+                name,
+                index + 1
+            ))),
+        };
+
+        let value = match name {
+            "print" | "println" => {
+                self.output
+                    .push_str(&arguments.first().map(text).unwrap_or_default());
+                if name == "println" {
+                    self.output.push('\n');
+                }
+                Value::Null
+            }
+            "len" => match arguments.first() {
+                Some(Value::String(value)) => Value::Int(value.chars().count() as i64),
+                Some(Value::Array(values)) => Value::Int(values.len() as i64),
+                _ => return Err(Unwind::error("`len` expects a string or an array.")),
+            },
+            "to_upper" => Value::String(string(0)?.to_uppercase()),
+            "to_lower" => Value::String(string(0)?.to_lowercase()),
+            "trim" => Value::String(string(0)?.trim().to_string()),
+            "contains" => Value::Bool(string(0)?.contains(string(1)?)),
+            "starts_with" => Value::Bool(string(0)?.starts_with(string(1)?)),
+            "ends_with" => Value::Bool(string(0)?.ends_with(string(1)?)),
+            "replace" => Value::String(string(0)?.replace(string(1)?, string(2)?)),
+            "split" => Value::Array(
+                string(0)?
+                    .split(string(1)?)
+                    .map(|part| Value::String(part.to_string()))
+                    .collect(),
+            ),
+            "join" => Value::String(
+                array(0)?
+                    .iter()
+                    .map(text)
+                    .collect::<Vec<String>>()
+                    .join(string(1)?),
+            ),
+            "reverse" => Value::Array(array(0)?.iter().rev().cloned().collect()),
+            "keys" => Value::Array((0..array(0)?.len() as i64).map(Value::Int).collect()),
+            "abs" => Value::Float(number(0)?.abs()),
+            "floor" => Value::Float(number(0)?.floor()),
+            "ceil" => Value::Float(number(0)?.ceil()),
+            "round" => Value::Float(number(0)?.round()),
+            "sqrt" => Value::Float(number(0)?.sqrt()),
+            "pow" => Value::Float(number(0)?.powf(number(1)?)),
+            "min" => Value::Float(number(0)?.min(number(1)?)),
+            "max" => Value::Float(number(0)?.max(number(1)?)),
+            name => return Err(format!("`{}` is not a function that can be run.", name).into()),
+        };
+        Ok(value)
+    }
+}
+
+/// Applies a binary operation, on the values that are constants.
+fn operate(op: &AnyOperation, left: Value, right: Value) -> Exec<Value> {
+    let mismatch = || format!("`{}` can't be applied to `{}` and `{}`.", op, left, right);
+    let (Some(l), Some(r)) = (left.to_const(), right.to_const()) else {
+        return Err(mismatch().into());
+    };
+    Ok(Value::from(binary(op, l, r)?))
+}
+
+fn constant(op: &UnaryOp, value: &Value) -> Exec<ConstValue> {
+    value.to_const().ok_or_else(|| {
+        Unwind::error(format!(
+            "`{}` can't be applied to `{}`.",
+            AnyOperation::UnaryOp(op.clone()),
+            value
+        ))
+    })
+}
+
+/// The operation of a compound assignment, eg: `+` for `+=`.
+fn compound(op: &AssignmentOp) -> Option<AnyOperation> {
+    let op = match op {
+        AssignmentOp::Add => AnyOperation::BinOp(BinOp::Plus),
+        AssignmentOp::Sub => AnyOperation::BinOp(BinOp::Minus),
+        AssignmentOp::Mul => AnyOperation::BinOp(BinOp::Star),
+        AssignmentOp::Div => AnyOperation::BinOp(BinOp::Slash),
+        AssignmentOp::Rem => AnyOperation::BinOp(BinOp::Percent),
+        AssignmentOp::BitAnd => AnyOperation::BinOp(BinOp::And),
+        AssignmentOp::BitOr => AnyOperation::BinOp(BinOp::Or),
+        AssignmentOp::BitXor => AnyOperation::BinOp(BinOp::Caret),
+        AssignmentOp::BitSh1 => AnyOperation::BinOp(BinOp::Shl),
+        AssignmentOp::BitShr => AnyOperation::BinOp(BinOp::Shr),
+        AssignmentOp::BoolAnd => AnyOperation::LogicalOp(LogicalOp::And),
+        AssignmentOp::BoolOr => AnyOperation::LogicalOp(LogicalOp::Or),
+        _ => return None,
+    };
+    Some(op)
+}
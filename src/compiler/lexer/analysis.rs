@@ -1,11 +1,18 @@
-use crate::util::{StreamBuffer, TokenStream};
-
-use super::{token::Token, tokenizer::tokenize};
+use crate::{
+    compiler::parser::fix_its,
+    report::{codes, diagnostic::Diagnostic, Suggestion},
+    util::{StreamBuffer, TokenStream},
+};
+
+use super::{
+    escape::{unescape, EscapeError},
+    token::{Token, TokenType},
+    tokenizer::tokenize,
+};
 
 pub struct Analyzer {
     pub stream: TokenStream,
-    // Todo: Properly add errors.
-    errors: Vec<String>,
+    errors: Vec<Diagnostic>,
 }
 
 impl Analyzer {
@@ -18,11 +25,11 @@ impl Analyzer {
 
     pub fn next(&mut self) -> bool {
         // Does a check on identifiers
-        if let Err(error) = self.check_identifiers() {
+        if let Some(error) = self.check_identifiers() {
             self.errors.push(error);
         }
 
-        if let Err(error) = self.check_captures() {
+        if let Some(error) = self.check_escapes() {
             self.errors.push(error);
         }
 
@@ -31,9 +38,9 @@ impl Analyzer {
     }
 
     /// Does a full analysis of identifiers (if they are present).
-    fn check_identifiers(&mut self) -> Result<(), String> {
+    fn check_identifiers(&mut self) -> Option<Diagnostic> {
         if !self.stream.second().is_some() {
-            return Ok(());
+            return None;
         }
 
         if let Some(token) = self.stream.first() {
@@ -41,69 +48,197 @@ impl Analyzer {
             if token.kind().is_identifier() && second.kind().is_identifier() {
                 // Identifiers can NOT be next to eachother!
                 self.stream.peek_inc(1);
-                return Err(
-                    format!("Error! Identifiers can never be next to each-other in this context!\n -> The indentifier \"{}\" at {}:{} is next to identifier \"{}\" at {}:{}", token.value().unwrap(), token.1.start, token.1.end, second.value().unwrap(), second.1.start, second.1.end)
+                return Some(
+                    Diagnostic::error(
+                        "Identifiers can never be next to each-other in this context!".to_string(),
+                    )
+                    .with_code(codes::UNEXPECTED_TOKEN)
+                    .with_span(
+                        token.range(),
+                        format!("The identifier \"{}\"", token.value().unwrap()),
+                    )
+                    .with_span(
+                        second.range(),
+                        format!("is next to the identifier \"{}\"", second.value().unwrap()),
+                    ),
                 );
             }
         }
-        Ok(())
+        None
     }
 
-    /// Checks for captures. A capture being anything that is followed by a `(` and ended with a `)`.
-    fn check_captures(&mut self) -> Result<(), String> {
-        let token = self.stream.first().unwrap();
-
-        if token.kind().is_left_parenthesis() {
-            let mut contents = self.stream.clone();
-            let mut needs = 0;
-            contents.peek();
-
-            while !contents.is_eof() {
-                if let Some(ctk) = contents.peek() {
-                    if ctk.kind().is_right_parenthesis() {
-                        if needs == 0 {
-                            return Ok(());
-                        } else {
-                            needs -= 1;
-                        }
-                    } else if ctk.kind().is_left_parenthesis() {
-                        needs += 1;
-                    }
-                }
-            }
-            return Err(format!(
-                "Error! Parenthesis at {}:{} is never closed!",
-                token.1.start, token.1.end
-            ));
+    /// Checks the escape sequences of strings.
+    fn check_escapes(&mut self) -> Option<Diagnostic> {
+        let token = self.stream.first()?;
+        if !token.kind().is_string() {
+            return None;
         }
+        let error = unescape(&token.value()?).err()?;
+        Some(escape_diagnostic(&token, error))
+    }
+}
+
+/// The diagnostic of an invalid escape sequence in a string token.
+pub fn escape_diagnostic(token: &Token, error: EscapeError) -> Diagnostic {
+    // the raw value starts after the opening quote.
+    let start = token.range().start + 1;
+    Diagnostic::error(error.message)
+        .with_code(codes::INVALID_ESCAPE)
+        .with_span(
+            start + error.range.start..start + error.range.end,
+            "This escape sequence is invalid.".to_string(),
+        )
+        .with_note("Write `\\\\` for a backslash.".to_string())
+}
+
+/// The problems the analyzer finds in a source, see `analyze`.
+pub fn analyze_source(source: &str) -> Result<(), Vec<Diagnostic>> {
+    analyze(tokenize(source))
+}
 
-        Ok(())
+/// The problems the analyzer finds in the tokens, in the order they are in the source.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::lexer::{analysis::analyze, tokenizer::tokenize};
+/// # use surn::util::source::SourceBuffer;
+/// # let buffer = SourceBuffer::new("fn f(a;".to_string());
+/// if let Err(found) = analyze(tokenize("fn f(a;")) {
+///     found[0].clone().into_report("main.surn".to_string(), buffer).print();
+/// }
+/// ```
+pub fn analyze(tokens: Vec<Token>) -> Result<(), Vec<Diagnostic>> {
+    let mut diagnostics = diagnose(tokens);
+    if diagnostics.is_empty() {
+        return Ok(());
     }
+    diagnostics.sort_by_key(|d| d.spans.first().map_or(0, |label| label.span.start));
+    Err(diagnostics)
 }
 
-pub fn analyze_source(source: &str) -> Result<(), String> {
-    let tokens = tokenize(source);
+/// Every problem the analyzer finds in the tokens.
+pub fn diagnose(tokens: Vec<Token>) -> Vec<Diagnostic> {
+    let mut found = unbalanced(&tokens);
+    found.extend(misspelled_keywords(&tokens));
+    found.extend(unknown_characters(&tokens));
     let mut analyzer = Analyzer::new(tokens);
     while !analyzer.stream.is_eof() {
         analyzer.next();
     }
+    analyzer.errors.extend(found);
+    analyzer.errors
+}
 
-    if !analyzer.errors.is_empty() {
-        return Err(analyzer.errors.join("\n"));
+/// Notices of statements that start with a typo of a keyword, eg: `clas User {}`,
+/// with the keyword as a fix-it. See `fix_its::closest_keyword`.
+pub fn misspelled_keywords(tokens: &[Token]) -> Vec<Diagnostic> {
+    let tokens: Vec<&Token> = tokens
+        .iter()
+        .filter(|t| !t.kind().is_whitespace() && !t.kind().is_comment())
+        .collect();
+    let mut notices = Vec::new();
+    for start in 0..tokens.len() {
+        let starts_statement = start == 0 || {
+            let before = tokens[start - 1].kind();
+            before.is_statement_end() || before.is_left_brace() || before.is_right_brace()
+        };
+        if !starts_statement {
+            continue;
+        }
+        if let Some((token, keyword)) = fix_its::misspelled_keyword(&tokens[start..]) {
+            let name = token.text().unwrap_or_default();
+            notices.push(
+                Diagnostic::notice(format!("`{}` is not a keyword.", name))
+                    .with_code(codes::MISSPELLED_KEYWORD)
+                    .with_span(token.range(), format!("Did you mean `{}`?", keyword))
+                    .with_suggestion(Suggestion::new(
+                        token.range(),
+                        keyword.clone(),
+                        format!("Replace it with `{}`.", keyword),
+                    )),
+            );
+        }
     }
+    notices
+}
 
-    Ok(())
+/// The chars outside of strings and comments that don't start any token, eg: an emoji.
+/// Letters of any script start a name, so only symbols and marks are reported.
+pub fn unknown_characters(tokens: &[Token]) -> Vec<Diagnostic> {
+    tokens
+        .iter()
+        .filter(|token| token.kind().is_unknown())
+        .map(|token| {
+            let text = token.text().unwrap_or_default();
+            let point = text.chars().next().map_or(0, u32::from);
+            Diagnostic::error(format!("`{}` can't be used outside of a string.", text))
+                .with_code(codes::UNEXPECTED_CHARACTER)
+                .with_span(
+                    token.range(),
+                    format!("U+{:04X} isn't part of any token.", point),
+                )
+        })
+        .collect()
 }
 
-pub fn analyze(tokens: Vec<Token>) -> Result<(), String> {
-    let mut analyzer = Analyzer::new(tokens);
-    while !analyzer.stream.is_eof() {
-        analyzer.next();
+/// The opening and closing character of a delimiter, and whether the token opens it.
+fn delimiter(token: &Token) -> Option<(char, char, bool)> {
+    match token.kind() {
+        TokenType::LeftParenthesis => Some(('(', ')', true)),
+        TokenType::RightParenthesis => Some(('(', ')', false)),
+        TokenType::LeftBracket => Some(('[', ']', true)),
+        TokenType::RightBracket => Some(('[', ']', false)),
+        TokenType::LeftBrace => Some(('{', '}', true)),
+        TokenType::RightBrace => Some(('{', '}', false)),
+        _ => None,
     }
+}
 
-    if !analyzer.errors.is_empty() {
-        return Err(analyzer.errors.join("\n"));
+/// The parentheses, brackets and braces that are never closed, or closed by the
+/// wrong delimiter. A closing delimiter closes the last one of its kind that is
+/// open, the ones opened after it are never closed.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::lexer::{analysis::unbalanced, tokenizer::tokenize};
+/// // fn main() { call(1; }
+/// # let tokens = tokenize("fn main() { call(1; }");
+/// &unbalanced(&tokens)[0].message; // This `(` is never closed.
+/// ```
+pub fn unbalanced(tokens: &[Token]) -> Vec<Diagnostic> {
+    let mut errors = Vec::new();
+    let mut open: Vec<(&Token, char, char)> = Vec::new();
+    for token in tokens {
+        let Some((opening, closing, opens)) = delimiter(token) else {
+            continue;
+        };
+        if opens {
+            open.push((token, opening, closing));
+            continue;
+        }
+        match open.iter().rposition(|(_, _, close)| *close == closing) {
+            Some(index) => {
+                for (unclosed, opening, closing) in open.split_off(index).into_iter().skip(1) {
+                    errors.push(unclosed_diagnostic(unclosed, opening, closing));
+                }
+            }
+            None => errors.push(
+                Diagnostic::error(format!("This `{}` closes nothing.", closing))
+                    .with_code(codes::UNEXPECTED_TOKEN)
+                    .with_span(token.range(), format!("No `{}` is open here.", opening)),
+            ),
+        }
     }
+    for (unclosed, opening, closing) in open {
+        errors.push(unclosed_diagnostic(unclosed, opening, closing));
+    }
+    errors.sort_by_key(|error| error.spans[0].span.start);
+    errors
+}
 
-    Ok(())
+fn unclosed_diagnostic(token: &Token, opening: char, closing: char) -> Diagnostic {
+    Diagnostic::error(format!("This `{}` is never closed.", opening))
+        .with_code(codes::UNCLOSED_DELIMITER)
+        .with_span(token.range(), "Opened here.".to_string())
+        .with_note(format!("Add a `{}` where what it opens ends.", closing))
 }
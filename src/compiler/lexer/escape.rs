@@ -0,0 +1,109 @@
+//! Escape sequences of string literals.
+//!
+//! A string token keeps its value as written, its raw value, the value the escape
+//! sequences stand for, its cooked value, is decoded from it when it is asked for. Backends escape the cooked value again
+//! in the way the target language reads it.
+//!
+//! The escapes are `\n`, `\t`, `\r`, `\0`, `\\`, `\"`, `\'`, `` \` `` and `\u{...}`,
+//! which takes the code point of a character in hex, eg: `\u{1F600}`.
+use std::ops::Range;
+
+/// An escape sequence that can't be decoded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EscapeError {
+    pub message: String,
+    /// The range of the escape sequence, in chars from the start of the raw value.
+    pub range: Range<usize>,
+}
+
+/// Decodes the escape sequences of the raw value of a string, without its quotes.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::lexer::escape::unescape;
+/// unescape(r"a\u{41}"); // Ok("aA")
+/// unescape(r"\q"); // Err(`\q` is not an escape sequence.)
+/// ```
+pub fn unescape(raw: &str) -> Result<String, EscapeError> {
+    let chars: Vec<char> = raw.chars().collect();
+    let mut cooked = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] != '\\' {
+            cooked.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        let start = i;
+        let error = |message: String, end: usize| EscapeError {
+            message,
+            range: start..end,
+        };
+        let Some(&c) = chars.get(i + 1) else {
+            return Err(error(
+                "A `\\` at the end of a string escapes nothing.".to_string(),
+                i + 1,
+            ));
+        };
+        i += 2;
+        match c {
+            'n' => cooked.push('\n'),
+            't' => cooked.push('\t'),
+            'r' => cooked.push('\r'),
+            '0' => cooked.push('\0'),
+            '\\' | '"' | '\'' | '`' => cooked.push(c),
+            'u' => {
+                if chars.get(i) != Some(&'{') {
+                    return Err(error(
+                        "Expected a `{` to follow `\\u`, eg: `\\u{1F600}`.".to_string(),
+                        i,
+                    ));
+                }
+                let Some(close) = chars[i..].iter().position(|c| *c == '}') else {
+                    return Err(error(
+                        "The code point of a `\\u{...}` escape is never closed.".to_string(),
+                        chars.len(),
+                    ));
+                };
+                let digits: String = chars[i + 1..i + close].iter().collect();
+                i += close + 1;
+                let code = match digits.len() {
+                    1..=6 => u32::from_str_radix(&digits, 16).ok(),
+                    _ => None,
+                };
+                match code.and_then(char::from_u32) {
+                    Some(c) => cooked.push(c),
+                    None => {
+                        return Err(error(
+                            format!("`{}` is not the code point of a character.", digits),
+                            i,
+                        ))
+                    }
+                }
+            }
+            c => {
+                return Err(error(format!("`\\{}` is not an escape sequence.", c), i));
+            }
+        }
+    }
+    Ok(cooked)
+}
+
+/// Escapes a value to be written between double quotes in surn,
+/// the opposite of `unescape`.
+pub fn escape(value: &str) -> String {
+    let mut escaped = String::new();
+    for c in value.chars() {
+        match c {
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            '\0' => escaped.push_str("\\0"),
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            c if c.is_control() => escaped.push_str(&format!("\\u{{{:X}}}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -1,6 +1,7 @@
 pub const MAX_KEYWORD_LENGTH: usize = 10;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum KeyWord {
     /// Namespace
     Namespace,
@@ -52,9 +53,56 @@ pub enum KeyWord {
     Drop,
     /// `use` - Use statement.
     Use,
+    /// `try` - Try statement.
+    Try,
+    /// `catch` - Catch clause of a try statement.
+    Catch,
+    /// `finally` - Finally clause of a try statement.
+    Finally,
+    /// `throw` - Throw statement.
+    Throw,
+    /// `async` - Asynchronous function.
+    Async,
+    /// `await` - Await expression.
+    Await,
 }
 
 impl KeyWord {
+    /// Every keyword, in the order they are declared.
+    pub const ALL: [KeyWord; 31] = [
+        KeyWord::Namespace,
+        KeyWord::Const,
+        KeyWord::Var,
+        KeyWord::Class,
+        KeyWord::Enum,
+        KeyWord::Interface,
+        KeyWord::Extends,
+        KeyWord::Implements,
+        KeyWord::Type,
+        KeyWord::Function,
+        KeyWord::If,
+        KeyWord::Else,
+        KeyWord::Public,
+        KeyWord::Private,
+        KeyWord::Protected,
+        KeyWord::Static,
+        KeyWord::Return,
+        KeyWord::Break,
+        KeyWord::Continue,
+        KeyWord::For,
+        KeyWord::While,
+        KeyWord::Do,
+        KeyWord::New,
+        KeyWord::Drop,
+        KeyWord::Use,
+        KeyWord::Try,
+        KeyWord::Catch,
+        KeyWord::Finally,
+        KeyWord::Throw,
+        KeyWord::Async,
+        KeyWord::Await,
+    ];
+
     pub fn from_string(v: &String) -> Option<Self> {
         match v.as_str() {
             "namespace" => Some(KeyWord::Namespace),
@@ -82,6 +130,12 @@ impl KeyWord {
             "extends" => Some(KeyWord::Extends),
             "enum" => Some(KeyWord::Enum),
             "implements" => Some(KeyWord::Implements),
+            "try" => Some(KeyWord::Try),
+            "catch" => Some(KeyWord::Catch),
+            "finally" => Some(KeyWord::Finally),
+            "throw" => Some(KeyWord::Throw),
+            "async" => Some(KeyWord::Async),
+            "await" => Some(KeyWord::Await),
             _ => None,
         }
     }
@@ -113,6 +167,12 @@ impl KeyWord {
             KeyWord::Extends => "extends".to_string(),
             KeyWord::Enum => "enum".to_string(),
             KeyWord::Implements => "implements".to_string(),
+            KeyWord::Try => "try".to_string(),
+            KeyWord::Catch => "catch".to_string(),
+            KeyWord::Finally => "finally".to_string(),
+            KeyWord::Throw => "throw".to_string(),
+            KeyWord::Async => "async".to_string(),
+            KeyWord::Await => "await".to_string(),
         }
     }
 
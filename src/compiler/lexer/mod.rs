@@ -1,5 +1,7 @@
 pub mod analysis;
+pub mod escape;
 pub mod keyword;
 pub mod pos;
 pub mod token;
 pub mod tokenizer;
+pub mod trivia;
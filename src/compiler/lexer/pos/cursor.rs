@@ -4,6 +4,7 @@ pub const END_OF_FILE: char = '\0';
 
 /// A struct that handles a stream of chars
 pub struct Cursor<'a> {
+    input: &'a str,
     ilen: usize,
     chars: Chars<'a>,
     prev: char,
@@ -14,6 +15,7 @@ pub struct Cursor<'a> {
 impl<'a> Cursor<'a> {
     pub fn new(input: &'a str) -> Cursor<'a> {
         Cursor {
+            input,
             ilen: input.len(),
             chars: input.chars(),
             prev: END_OF_FILE,
@@ -96,28 +98,34 @@ impl<'a> Cursor<'a> {
         }
     }
 
-    /// Shows how many chars have been consumed by the cursor.
+    /// Shows how many bytes have been consumed by the cursor.
     pub fn eaten(&self) -> usize {
         self.ilen - self.chars.as_str().len()
     }
 
-    pub fn eat_while(&mut self, mut pred: impl FnMut(char) -> bool) -> String {
-        let mut segment = String::new();
-        while !self.is_eof() && pred(self.first()) == true {
-            segment.push(self.peek().unwrap_or(END_OF_FILE));
+    /// The input from the given byte offset to where the cursor is, see `eaten`.
+    pub fn slice_from(&self, start: usize) -> &'a str {
+        &self.input[start..self.eaten()]
+    }
+
+    /// Eats chars while the predicate is true, the chars are returned as a slice of the input.
+    pub fn eat_while(&mut self, mut pred: impl FnMut(char) -> bool) -> &'a str {
+        let start = self.eaten();
+        while !self.is_eof() && pred(self.first()) {
+            self.peek();
         }
-        segment
+        self.slice_from(start)
     }
 
     pub fn eat_while_cursor(
         &mut self,
         mut pred: impl FnMut(&mut Cursor<'a>, char) -> bool,
-    ) -> String {
-        let mut segment = String::new();
-        while !self.is_eof() && pred(self, self.first()) == true {
-            segment.push(self.peek().unwrap_or(END_OF_FILE));
+    ) -> &'a str {
+        let start = self.eaten();
+        while !self.is_eof() && pred(self, self.first()) {
+            self.peek();
         }
-        segment
+        self.slice_from(start)
     }
 }
 
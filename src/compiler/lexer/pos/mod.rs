@@ -3,7 +3,10 @@ pub mod region;
 
 pub use self::region::Region;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// A line and column in the source, both start at 1.
+/// The default position, line 0, is used for nodes that were not parsed from a source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Position {
     pub line: usize,
     pub column: usize,
@@ -51,3 +54,40 @@ impl Position {
         }
     }
 }
+
+/// The offsets every line of a source starts at, so offsets can be resolved
+/// to positions without scanning the source again.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::lexer::pos::LineIndex;
+/// let index = LineIndex::new("var a = 1;\nvar b = 2;");
+/// index.position(15); // Position { line: 2, column: 5 }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+    /// The char offset of the start of every line.
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(source: &str) -> Self {
+        let mut starts = vec![0];
+        for (i, c) in source.chars().enumerate() {
+            if c == '\n' {
+                starts.push(i + 1);
+            }
+        }
+        Self { starts }
+    }
+
+    /// The position of a char offset, offsets past the end are on the last line.
+    pub fn position(&self, offset: usize) -> Position {
+        let line = self.starts.partition_point(|start| *start <= offset);
+        Position::new(line, offset - self.starts[line - 1] + 1)
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.starts.len()
+    }
+}
@@ -1,7 +1,15 @@
-use crate::compiler::lexer::keyword::KeyWord;
-use std::ops::Range;
+use crate::{
+    compiler::lexer::{
+        escape::unescape,
+        keyword::KeyWord,
+        pos::{Position, Region},
+    },
+    util::intern::Symbol,
+};
+use std::{fmt, ops::Range, sync::Arc};
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum TokenType {
     /// Any token that signifies a variable is to be created.
     ///
@@ -76,6 +84,11 @@ pub enum TokenType {
     /// For example:
     /// - `..`
     Range,
+    /// The spread token, which collects or expands the values of an array.
+    /// For example:
+    /// - `fn sum(...numbers: int)`
+    /// - `sum(...values)`
+    Spread,
     /// The keywords `true` and `false` are used to represent boolean values.
     /// For example:
     /// - `var test: bool = true;`
@@ -106,9 +119,95 @@ pub enum TokenType {
     Comma,
     /// The `\` character that can signal the start of a string literal.
     Backslash,
+    /// The raw body of a macro invocation, including the `!` that follows the macro name.
+    /// The value is everything between the braces, or `None` if the body is never closed.
+    ///
+    /// For example:
+    /// - `! { echo "hi"; }` in `php! { echo "hi"; }`
+    MacroBody,
+    /// An attribute placed before a declaration.
+    /// The value is everything between the brackets, or `None` if the attribute is never closed.
+    ///
+    /// For example:
+    /// - `#[Service]`
+    /// - `#[Singleton(CacheContract)]`
+    Attribute,
+    /// A char that doesn't start any token and isn't ascii, eg: an emoji outside of a string.
+    Unknown,
 }
 
 impl TokenType {
+    /// Every kind of token, each keyword is a kind of its own.
+    pub fn all() -> Vec<TokenType> {
+        let mut kinds = vec![
+            TokenType::Constant,
+            TokenType::Variable,
+            TokenType::Colon,
+            TokenType::Comment,
+        ];
+        kinds.extend(KeyWord::ALL.iter().cloned().map(TokenType::KeyWord));
+        kinds.extend([
+            TokenType::Identifier,
+            TokenType::Number,
+            TokenType::StringLiteral,
+            TokenType::Operator,
+            TokenType::Accessor,
+            TokenType::Range,
+            TokenType::Spread,
+            TokenType::Boolean,
+            TokenType::Whitespace,
+            TokenType::StatementEnd,
+            TokenType::LineBreak,
+            TokenType::LeftBracket,
+            TokenType::RightBracket,
+            TokenType::LeftParenthesis,
+            TokenType::RightParenthesis,
+            TokenType::LeftBrace,
+            TokenType::RightBrace,
+            TokenType::Comma,
+            TokenType::Backslash,
+            TokenType::MacroBody,
+            TokenType::Attribute,
+            TokenType::Unknown,
+        ]);
+        kinds
+    }
+
+    /// A stable name for the kind, eg: `left_brace`, or `keyword.fn` for a keyword.
+    /// Unlike `to_string`, every kind has a name of its own.
+    pub fn name(&self) -> String {
+        let name = match self {
+            TokenType::KeyWord(keyword) => return format!("keyword.{}", keyword.to_string()),
+            TokenType::Constant => "constant",
+            TokenType::Variable => "variable",
+            TokenType::Colon => "colon",
+            TokenType::Comment => "comment",
+            TokenType::Identifier => "identifier",
+            TokenType::Number => "number",
+            TokenType::StringLiteral => "string",
+            TokenType::Operator => "operator",
+            TokenType::Accessor => "accessor",
+            TokenType::Range => "range",
+            TokenType::Spread => "spread",
+            TokenType::Boolean => "boolean",
+            TokenType::Whitespace => "whitespace",
+            TokenType::StatementEnd => "statement_end",
+            TokenType::LineBreak => "line_break",
+            TokenType::LeftBracket => "left_bracket",
+            TokenType::RightBracket => "right_bracket",
+            TokenType::LeftParenthesis => "left_parenthesis",
+            TokenType::RightParenthesis => "right_parenthesis",
+            TokenType::LeftBrace => "left_brace",
+            TokenType::RightBrace => "right_brace",
+            TokenType::Comma => "comma",
+            TokenType::Backslash => "backslash",
+            TokenType::MacroBody => "macro_body",
+            TokenType::Attribute => "attribute",
+            TokenType::Unknown => "unknown",
+        };
+        name.to_string()
+    }
+
     pub fn is_colon(&self) -> bool {
         match self {
             TokenType::Colon => true,
@@ -270,6 +369,10 @@ impl TokenType {
         }
     }
 
+    pub fn is_spread(&self) -> bool {
+        matches!(self, TokenType::Spread)
+    }
+
     pub fn is_backslash(&self) -> bool {
         match self {
             TokenType::Backslash => true,
@@ -277,6 +380,18 @@ impl TokenType {
         }
     }
 
+    pub fn is_macro_body(&self) -> bool {
+        matches!(self, TokenType::MacroBody)
+    }
+
+    pub fn is_attribute(&self) -> bool {
+        matches!(self, TokenType::Attribute)
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, TokenType::Unknown)
+    }
+
     /// This will panic if the token type is not a keyword.
     pub fn as_keyword(&self) -> KeyWord {
         match self {
@@ -311,24 +426,178 @@ impl ToString for TokenType {
             TokenType::Whitespace => "Whitespace".to_string(),
             TokenType::Accessor => "Accessor".to_string(),
             TokenType::Range => "Range".to_string(),
+            TokenType::Spread => "Spread".to_string(),
             TokenType::Backslash => "Backslash".to_string(),
+            TokenType::MacroBody => "Macro Body".to_string(),
+            TokenType::Attribute => "Attribute".to_string(),
+            TokenType::Unknown => "Unknown".to_string(),
+        }
+    }
+}
+
+/// The value of a token, a range of the source it was read from.
+/// Every token of a source shares it, so a value is only copied when it is asked for.
+#[derive(Clone)]
+pub struct TokenValue {
+    source: Arc<str>,
+    /// The range of the value in the source, in bytes.
+    bytes: Range<usize>,
+}
+
+impl TokenValue {
+    /// A value that isn't read from a source, eg: a name put together by the parser.
+    pub fn new(value: &str) -> Self {
+        Self {
+            source: Arc::from(value),
+            bytes: 0..value.len(),
         }
     }
+
+    pub fn from_source(source: &Arc<str>, bytes: Range<usize>) -> Self {
+        Self {
+            source: source.clone(),
+            bytes,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source[self.bytes.clone()]
+    }
+}
+
+impl PartialEq for TokenValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
 }
 
+impl Eq for TokenValue {}
+
+impl fmt::Debug for TokenValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl From<&str> for TokenValue {
+    fn from(value: &str) -> Self {
+        TokenValue::new(value)
+    }
+}
+
+impl From<String> for TokenValue {
+    fn from(value: String) -> Self {
+        TokenValue::new(&value)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for TokenValue {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// A token of the source, its kind, range, value and the position it starts at.
+/// The value of a string is its raw value, see `cooked` for the value of its escapes.
 #[derive(Debug, Clone)]
-pub struct Token(pub TokenType, pub Range<usize>, pub Option<String>);
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Token(
+    pub TokenType,
+    pub Range<usize>,
+    pub Option<TokenValue>,
+    pub Position,
+);
 
 impl Token {
     pub fn kind(&self) -> TokenType {
         self.0.clone()
     }
 
+    /// A copy of the value, see `text` to read it without copying it.
     pub fn value(&self) -> Option<String> {
-        self.2.clone()
+        self.text().map(str::to_string)
+    }
+
+    pub fn text(&self) -> Option<&str> {
+        self.2.as_ref().map(TokenValue::as_str)
+    }
+
+    /// The value interned, without copying it first, see `util::intern`.
+    pub fn symbol(&self) -> Option<Symbol> {
+        self.text().map(Symbol::intern)
     }
 
     pub fn range(&self) -> Range<usize> {
         self.1.clone()
     }
+
+    /// The line and column the token starts at.
+    pub fn position(&self) -> Position {
+        self.3
+    }
+
+    /// The lines and columns the token covers, the end is the column after its last char.
+    /// A token that spans lines ends where its value does, after the quote of a string.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::compiler::lexer::tokenizer::tokenize;
+    /// // var s = "a
+    /// // bc";
+    /// # let tokens = tokenize("var s = \"a\nbc\";");
+    /// tokens[6].region(); // 1:9 to 2:4
+    /// ```
+    pub fn region(&self) -> Region {
+        let start = self.3;
+        let text = self.text().unwrap_or_default();
+        let end = match text.rfind('\n') {
+            None => Position::new(start.line, start.column + self.1.len()),
+            Some(last) => {
+                let closing = match self.0 {
+                    TokenType::StringLiteral => 1,
+                    _ => 0,
+                };
+                Position::new(
+                    start.line + text.matches('\n').count(),
+                    text[last + 1..].chars().count() + closing + 1,
+                )
+            }
+        };
+        Region::new(start, end, None)
+    }
+
+    /// The value of a string with its escape sequences decoded, see `escape`.
+    /// `None` for other tokens, and for strings with an invalid escape.
+    pub fn cooked(&self) -> Option<String> {
+        match self.0 {
+            TokenType::StringLiteral => unescape(self.text()?).ok(),
+            _ => None,
+        }
+    }
+
+    /// Whether both tokens are the same, wherever they are in the source.
+    pub fn same_kind_and_value(&self, other: &Token) -> bool {
+        self.0 == other.0 && self.2 == other.2
+    }
+}
+
+/// Whether both lists have the same tokens, ignoring whitespace and line breaks.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::lexer::{token::same_tokens, tokenizer::tokenize};
+/// same_tokens(&tokenize("var x = 1;"), &tokenize("var x=1;")); // true
+/// ```
+pub fn same_tokens(a: &[Token], b: &[Token]) -> bool {
+    let significant = |t: &&Token| !t.0.is_whitespace() && !t.0.is_line_break();
+    let mut a = a.iter().filter(significant);
+    let mut b = b.iter().filter(significant);
+    loop {
+        match (a.next(), b.next()) {
+            (Some(a), Some(b)) if a.same_kind_and_value(b) => continue,
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
 }
@@ -1,20 +1,28 @@
+use std::{ops::Range, sync::Arc};
+
 use super::{
     keyword::{KeyWord, MAX_KEYWORD_LENGTH},
-    pos::cursor::{Cursor, END_OF_FILE},
-    token::{Token, TokenType},
+    pos::{
+        cursor::{Cursor, END_OF_FILE},
+        LineIndex, Position,
+    },
+    token::{Token, TokenType, TokenValue},
 };
 
+/// A token as it is eaten, its value is a slice of the input.
+type RawToken<'a> = (TokenType, Range<usize>, Option<&'a str>);
+
 macro_rules! token {
     ($start: expr, $end: expr, $t: expr, $v: expr) => {
-        Some(Token($t, $start..$end, $v))
+        Some(($t, $start..$end, $v))
     };
     ($start: expr, $end: expr, $t: expr) => {
-        Some(Token($t, $start..$end, None))
+        Some(($t, $start..$end, None))
     };
 }
 
-impl Cursor<'_> {
-    fn eat(&mut self) -> Option<Token> {
+impl<'a> Cursor<'a> {
+    fn eat(&mut self) -> Option<RawToken<'a>> {
         let start_pos = self.get_pos();
 
         if let Some(spaces) = self.eat_whitespace() {
@@ -30,6 +38,14 @@ impl Cursor<'_> {
             return token!(start_pos, self.get_pos(), TokenType::Comment, Some(comment));
         }
 
+        if let Some(body) = self.eat_macro_body() {
+            return token!(start_pos, self.get_pos(), TokenType::MacroBody, body);
+        }
+
+        if let Some(attribute) = self.eat_attribute() {
+            return token!(start_pos, self.get_pos(), TokenType::Attribute, attribute);
+        }
+
         if let Some(operator) = self.eat_operator() {
             return token!(
                 start_pos,
@@ -66,7 +82,8 @@ impl Cursor<'_> {
         }
 
         if let Some(string) = self.eat_string() {
-            self.peek(); // what?
+            // the closing quote.
+            self.peek();
             return token!(
                 start_pos,
                 self.get_pos(),
@@ -85,11 +102,23 @@ impl Cursor<'_> {
             return token!(start_pos, self.get_pos(), token_type);
         }
 
+        // an ascii char that starts no token is skipped, any other one, eg: an emoji
+        // outside of a string, is a token the analyzer reports.
+        let start = self.eaten();
+        let unknown = !self.first().is_ascii();
         self.peek();
+        if unknown {
+            return token!(
+                start_pos,
+                self.get_pos(),
+                TokenType::Unknown,
+                Some(self.slice_from(start))
+            );
+        }
         return None;
     }
 
-    fn eat_comment(&mut self) -> Option<String> {
+    fn eat_comment(&mut self) -> Option<&'a str> {
         return match self.first() {
             '/' => {
                 // check the next character
@@ -118,9 +147,90 @@ impl Cursor<'_> {
         };
     }
 
+    /// Eats the body of a macro invocation, eg: `! { ... }` in `php! { ... }`
+    /// The body is kept as written, the braces only have to be balanced outside of
+    /// strings and comments. A body that is never closed eats the rest of the input.
+    fn eat_macro_body(&mut self) -> Option<Option<&'a str>> {
+        if self.first() != '!' {
+            return None;
+        }
+        let rest = self.chars().as_str();
+        let chars: Vec<char> = rest.chars().collect();
+        let open = chars
+            .iter()
+            .skip(1)
+            .position(|c| !c.is_whitespace())
+            .map(|i| i + 1)?;
+        if chars[open] != '{' {
+            return None;
+        }
+
+        let mut depth = 0;
+        let mut quote: Option<char> = None;
+        let mut line_comment = false;
+        let mut i = open;
+        while i < chars.len() {
+            let c = chars[i];
+            match quote {
+                Some(_) if c == '\\' => i += 1,
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None if line_comment => line_comment = c != '\n',
+                None => match c {
+                    '"' | '\'' => quote = Some(c),
+                    '#' => line_comment = true,
+                    '/' if chars.get(i + 1) == Some(&'/') => line_comment = true,
+                    '{' => depth += 1,
+                    '}' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            self.peek_inc(i);
+                            return Some(Some(
+                                &rest[byte_offset(rest, open + 1)..byte_offset(rest, i)],
+                            ));
+                        }
+                    }
+                    _ => {}
+                },
+            }
+            i += 1;
+        }
+
+        self.peek_inc(chars.len());
+        Some(None)
+    }
+
+    /// Eats an attribute, eg: `#[Service]`
+    /// An attribute that is never closed eats the rest of the line.
+    fn eat_attribute(&mut self) -> Option<Option<&'a str>> {
+        if self.first() != '#' || self.second() != '[' {
+            return None;
+        }
+        let rest = self.chars().as_str();
+        let chars: Vec<char> = rest.chars().take_while(|c| *c != '\n').collect();
+        let mut depth = 0;
+        for (i, c) in chars.iter().enumerate() {
+            match c {
+                '[' => depth += 1,
+                ']' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.peek_inc(i);
+                        return Some(Some(
+                            rest[byte_offset(rest, 2)..byte_offset(rest, i)].trim(),
+                        ));
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.peek_inc(chars.len() - 1);
+        Some(None)
+    }
+
     /// This may be misleading,
     /// because it eats ALL whitespace until a char is not whitespace
-    fn eat_whitespace(&mut self) -> Option<String> {
+    fn eat_whitespace(&mut self) -> Option<&'a str> {
         let segment = self.eat_while(|c| c.is_whitespace());
         return if segment.is_empty() {
             None
@@ -129,17 +239,18 @@ impl Cursor<'_> {
         };
     }
 
-    fn eat_identifier(&mut self) -> Option<String> {
+    fn eat_identifier(&mut self) -> Option<&'a str> {
         match self.first() {
-            // 'A'..='z' can't be used here as it includes a plethora of reserved characters that are used elsewhere
-            '_' | 'a'..='z' | 'A'..='Z' => Some(
-                self.eat_while(|c: char| !c.is_whitespace() && (c.is_alphanumeric() || c == '_')),
-            ),
+            // a letter of any script starts an identifier, eg: `café` or `名前`, as php allows them.
+            // the combining accents of a decomposed letter, eg: `e\u{301}`, are part of it.
+            c if c == '_' || c.is_alphabetic() => Some(self.eat_while(|c: char| {
+                c.is_alphanumeric() || c == '_' || ('\u{0300}'..='\u{036F}').contains(&c)
+            })),
             _ => None,
         }
     }
 
-    fn eat_number(&mut self) -> Option<String> {
+    fn eat_number(&mut self) -> Option<&'a str> {
         match self.first() {
             // there is an issue with leading floats where they are parsed as accessors right now.
             // we should leave this to the parser.
@@ -159,7 +270,9 @@ impl Cursor<'_> {
             segment.push(next_char);
 
             if let Some(keyword) = KeyWord::from_string(&segment) {
-                if self.nth_char(i + 1).is_whitespace() {
+                // a keyword can end a statement, eg: `return;`.
+                let next = self.nth_char(i + 1);
+                if next.is_whitespace() || next == ';' {
                     self.peek_inc(i);
                     return Some(keyword.to_string());
                 } else {
@@ -171,16 +284,18 @@ impl Cursor<'_> {
         return None;
     }
 
-    fn eat_operator(&mut self) -> Option<String> {
+    fn eat_operator(&mut self) -> Option<&'a str> {
+        let start = self.eaten();
         match self.first() {
-            '+' | '-' | '*' | '/' | '%' | '=' | '<' | '>' | '&' | '|' | '^' | '~' => {
+            '+' | '-' | '*' | '/' | '%' | '=' | '!' | '<' | '>' | '&' | '|' | '^' | '~' | '?' => {
                 self.peek();
-                Some(self.get_prev().to_string())
+                Some(self.slice_from(start))
             }
             'o' => {
                 if self.nth_char(1) == 'r' {
                     self.peek_inc(2);
-                    Some("or".to_string())
+                    // the char after the operator is eaten along with it.
+                    Some(&self.slice_from(start)[..2])
                 } else {
                     None
                 }
@@ -188,7 +303,7 @@ impl Cursor<'_> {
             'a' => {
                 if self.nth_char(1) == 'n' && self.nth_char(2) == 'd' {
                     self.peek_inc(3);
-                    return Some("and".to_string());
+                    Some(&self.slice_from(start)[..3])
                 } else {
                     return None;
                 }
@@ -197,7 +312,8 @@ impl Cursor<'_> {
         }
     }
 
-    fn eat_boolean(&mut self) -> Option<String> {
+    fn eat_boolean(&mut self) -> Option<&'a str> {
+        let start = self.eaten();
         // TODO: it may not be the best practice to use this vector
         for value in ["true", "false"].iter() {
             let mut segment = String::new();
@@ -216,41 +332,51 @@ impl Cursor<'_> {
 
                 if segment == value.to_string() {
                     self.peek_inc(i);
-                    return Some(segment);
+                    return Some(self.slice_from(start));
                 }
             }
         }
         return None;
     }
 
-    fn eat_string(&mut self) -> Option<String> {
+    /// Eats a string up to its closing quote, a quote after a `\` doesn't close it.
+    fn eat_string(&mut self) -> Option<&'a str> {
         if self.first() != '"' && self.first() != '\'' && self.first() != '`' {
             return None;
         } else {
-            let first = self.peek().unwrap();
-            return Some(self.eat_while(|c| c != first));
+            // the quote can be the last char, which `peek` doesn't return.
+            let first = self.first();
+            self.peek();
+            let mut escaped = false;
+            Some(self.eat_while(|c| {
+                let closes = c == first && !escaped;
+                escaped = c == '\\' && !escaped;
+                !closes
+            }))
         }
     }
 
-    fn eat_value_reserved(&mut self) -> Option<(TokenType, String)> {
+    fn eat_value_reserved(&mut self) -> Option<(TokenType, &'a str)> {
+        let start = self.eaten();
         match self.first() {
             ':' => {
                 if self.second() == ':' {
                     self.peek_inc(1);
-                    return Some((TokenType::Accessor, "::".to_string()));
+                    Some((TokenType::Accessor, self.slice_from(start)))
                 } else {
                     self.peek();
-                    return Some((TokenType::Colon, ":".to_string()));
+                    Some((TokenType::Colon, self.slice_from(start)))
                 }
             }
             '.' => {
-                if self.second() == '.' {
-                    self.peek_inc(2);
-                    return Some((TokenType::Range, "..".to_string()));
-                } else {
-                    self.peek();
-                    return Some((TokenType::Accessor, ".".to_string()));
-                }
+                // the amount of dots after the first one.
+                let (kind, rest) = match (self.second(), self.nth_char(2)) {
+                    ('.', '.') => (TokenType::Spread, 2),
+                    ('.', _) => (TokenType::Range, 1),
+                    _ => (TokenType::Accessor, 0),
+                };
+                self.peek_inc(rest);
+                Some((kind, self.slice_from(start)))
             }
             _ => None,
         }
@@ -272,16 +398,35 @@ impl Cursor<'_> {
     }
 }
 
-pub fn tokenize<'a>(input: &'a str) -> Vec<Token> {
+/// The byte offset of the `n`th char of a str.
+fn byte_offset(input: &str, n: usize) -> usize {
+    input
+        .char_indices()
+        .nth(n)
+        .map_or(input.len(), |(offset, _)| offset)
+}
+
+/// Tokenizes a source, the values of the tokens are ranges of one copy of it, see `TokenValue`.
+pub fn tokenize(input: &str) -> Vec<Token> {
+    let source: Arc<str> = Arc::from(input);
     let mut cursor = Cursor::new(input);
     let mut tokens: Vec<Token> = Vec::new();
 
     while !cursor.is_eof() {
-        let token = cursor.eat();
-        if let Some(token) = token {
-            tokens.push(token);
+        if let Some((kind, range, value)) = cursor.eat() {
+            // the values are slices of the input, so their offsets are the same in the copy.
+            let value = value.map(|value| {
+                let start = value.as_ptr() as usize - input.as_ptr() as usize;
+                TokenValue::from_source(&source, start..start + value.len())
+            });
+            tokens.push(Token(kind, range, value, Position::default()));
         }
     }
 
+    let lines = LineIndex::new(input);
+    for token in tokens.iter_mut() {
+        token.3 = lines.position(token.1.start);
+    }
+
     return tokens;
 }
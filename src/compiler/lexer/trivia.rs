@@ -0,0 +1,113 @@
+use std::ops::Range;
+
+use super::token::Token;
+
+/// A comment that was found in the source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    pub range: Range<usize>,
+    /// The comment as written, including its `//` or `/* */`.
+    pub text: String,
+    /// Whether or not the comment is the first thing on its line.
+    /// Comments that aren't trail the code before them, eg: `var x = 5; // five`
+    pub own_line: bool,
+}
+
+/// Everything in the source that the parser discards but a formatter needs to keep.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::lexer::{tokenizer::tokenize, trivia::Trivia};
+/// let source = "// the answer\nvar x = 42;\n";
+/// let tokens = tokenize(source);
+/// let trivia = Trivia::collect(source, &tokens);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Trivia {
+    /// Every comment, in source order.
+    pub comments: Vec<Comment>,
+    /// The offsets of whitespace that contains at least one empty line.
+    pub blank_lines: Vec<usize>,
+}
+
+impl Trivia {
+    pub fn collect(source: &str, tokens: &[Token]) -> Self {
+        let chars: Vec<char> = source.chars().collect();
+        let mut trivia = Trivia::default();
+
+        for token in tokens.iter() {
+            let range = token.range();
+            if token.kind().is_comment() {
+                // the tokenizer doesn't always include the end of the comment in its value.
+                let end = range.end.max(range.start + 1).min(chars.len());
+                let text: String = chars[range.start..end].iter().collect();
+                let line_start = chars[..range.start]
+                    .iter()
+                    .rposition(|c| *c == '\n')
+                    .map_or(0, |i| i + 1);
+                trivia.comments.push(Comment {
+                    range: range.start..end,
+                    text: text.trim_end().to_string(),
+                    own_line: chars[line_start..range.start]
+                        .iter()
+                        .all(|c| c.is_whitespace()),
+                });
+            } else if token.kind().is_whitespace()
+                && token.value().unwrap_or_default().matches('\n').count() > 1
+            {
+                trivia.blank_lines.push(range.start);
+            }
+        }
+
+        trivia
+    }
+
+    /// Whether or not there is an empty line between the two offsets.
+    pub fn has_blank_line(&self, range: Range<usize>) -> bool {
+        self.blank_lines.iter().any(|offset| range.contains(offset))
+    }
+}
+
+/// The whitespace and comments the parser skipped between two tokens.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Skipped {
+    /// The range that was skipped, empty when there was nothing to skip.
+    pub range: Range<usize>,
+    /// How many line breaks were skipped, including the ones inside block comments.
+    pub newlines: usize,
+    /// The range of every comment that was skipped.
+    pub comments: Vec<Range<usize>>,
+}
+
+impl Skipped {
+    /// Nothing skipped at the offset.
+    pub fn at(offset: usize) -> Self {
+        Self {
+            range: offset..offset,
+            ..Self::default()
+        }
+    }
+
+    /// Adds a whitespace or comment token to what was skipped.
+    pub fn push(&mut self, token: &Token) {
+        self.range.end = token.range().end;
+        self.newlines += token.value().unwrap_or_default().matches('\n').count();
+        if token.kind().is_comment() {
+            self.comments.push(token.range());
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.range.is_empty()
+    }
+
+    /// Whether the next token is on a line of its own.
+    pub fn has_newline(&self) -> bool {
+        self.newlines > 0
+    }
+
+    /// Whether an empty line was skipped.
+    pub fn has_blank_line(&self) -> bool {
+        self.newlines > 1
+    }
+}
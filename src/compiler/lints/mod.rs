@@ -0,0 +1,137 @@
+use std::{collections::HashMap, ops::Range};
+
+use crate::report::{diagnostic::Diagnostic, ReportKind};
+
+pub mod unused;
+
+/// The type parameters of a declaration are erased by the target.
+pub const ERASED_GENERICS: &str = "erased_generics";
+/// An integer type is narrowed to a smaller one on the target.
+pub const NARROWED_INTEGERS: &str = "narrowed_integers";
+/// `await` is dropped and the code runs synchronously on the target.
+pub const FLATTENED_ASYNC: &str = "flattened_async";
+/// A variable declared in a function is never used.
+pub const UNUSED_VARIABLE: &str = "unused_variable";
+
+/// Every lint the compiler has.
+pub const KNOWN_LINTS: &[&str] = &[
+    ERASED_GENERICS,
+    NARROWED_INTEGERS,
+    FLATTENED_ASYNC,
+    UNUSED_VARIABLE,
+];
+
+/// What is done with a diagnostic of a lint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintLevel {
+    /// The diagnostic is not reported.
+    Allow,
+    /// The diagnostic is reported, compiling goes on.
+    Warn,
+    /// The diagnostic is reported as an error.
+    Deny,
+}
+
+impl LintLevel {
+    /// The level an attribute sets, eg: `allow` in `#[allow(unused_variable)]`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "allow" => Some(LintLevel::Allow),
+            "warn" => Some(LintLevel::Warn),
+            "deny" => Some(LintLevel::Deny),
+            _ => None,
+        }
+    }
+}
+
+/// The level of a lint in the statement an attribute is placed on.
+/// For example:
+/// - `#[allow(unused_variable)] fn f() { var x = 1; }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintScope {
+    pub lint: String,
+    pub level: LintLevel,
+    /// The range of the statement, attributes included.
+    pub span: Range<usize>,
+}
+
+/// The level of every lint, lints that aren't configured are reported as warnings.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::lints::{LintLevel, Lints, ERASED_GENERICS, FLATTENED_ASYNC};
+/// let mut lints = Lints::new();
+/// lints.set(ERASED_GENERICS, LintLevel::Allow);
+/// lints.level(ERASED_GENERICS); // LintLevel::Allow
+/// lints.level(FLATTENED_ASYNC); // LintLevel::Warn
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Lints {
+    levels: HashMap<String, LintLevel>,
+}
+
+impl Lints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&mut self, lint: &str, level: LintLevel) {
+        self.levels.insert(lint.to_string(), level);
+    }
+
+    pub fn level(&self, lint: &str) -> LintLevel {
+        self.levels.get(lint).copied().unwrap_or(LintLevel::Warn)
+    }
+
+    /// The level of a lint at an offset, the innermost attribute around it wins over the options.
+    pub fn level_at(&self, lint: &str, offset: usize, scopes: &[LintScope]) -> LintLevel {
+        scopes
+            .iter()
+            .filter(|scope| scope.lint == lint && scope.span.contains(&offset))
+            .min_by_key(|scope| scope.span.len())
+            .map_or_else(|| self.level(lint), |scope| scope.level)
+    }
+
+    /// The configured lints that don't exist, eg: a typo in a lint name.
+    pub fn unknown(&self) -> Vec<&str> {
+        let mut unknown: Vec<&str> = self
+            .levels
+            .keys()
+            .map(|lint| lint.as_str())
+            .filter(|lint| !KNOWN_LINTS.contains(lint))
+            .collect();
+        unknown.sort();
+        unknown
+    }
+
+    /// The diagnostic at the level of its lint, `None` when the lint is allowed.
+    /// A denied lint makes the diagnostic an error, diagnostics without a lint are kept as they are.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::compiler::{parser::Parser, CompilerOptions};
+    /// # use surn::compiler::lints::{unused::check_unused_variables, LintLevel, UNUSED_VARIABLE};
+    /// # let mut options = CompilerOptions::default();
+    /// # let body = Parser::new(CompilerOptions::default())
+    /// #     .try_parse_script("main.surn".to_string(), "fn f() { var x = 1; return 2; }".to_string())?;
+    /// # let diagnostic = check_unused_variables(&body).remove(0);
+    /// options.lints.set(UNUSED_VARIABLE, LintLevel::Deny);
+    /// let diagnostic = options.lints.apply(diagnostic, body.lint_scopes()).unwrap();
+    /// diagnostic.is_error(); // true
+    /// # Ok::<(), surn::report::Report>(())
+    /// ```
+    pub fn apply(&self, mut diagnostic: Diagnostic, scopes: &[LintScope]) -> Option<Diagnostic> {
+        let Some(lint) = diagnostic.lint else {
+            return Some(diagnostic);
+        };
+        let offset = diagnostic.spans.first().map_or(0, |label| label.span.start);
+        match self.level_at(lint, offset, scopes) {
+            LintLevel::Allow => None,
+            LintLevel::Warn => Some(diagnostic),
+            LintLevel::Deny => {
+                diagnostic.severity = ReportKind::Error;
+                Some(diagnostic)
+            }
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::{collections::HashSet, ops::Range};
+
+use crate::{
+    compiler::ast::{
+        visit::{self, Visitor},
+        AstBody, Call, Expression, Function, Statement, Variable,
+    },
+    report::{codes, diagnostic::Diagnostic},
+};
+
+use super::UNUSED_VARIABLE;
+
+/// Reports the variables declared in a function that are never used.
+/// Variables at the top level are left out, other files can use them,
+/// and so are names that start with `_`.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, CompilerOptions};
+/// # use surn::compiler::lints::unused::check_unused_variables;
+/// // fn f() { var x = 1; return 2; }
+/// # let source = "fn f() { var x = 1; return 2; }\n";
+/// # let body = Parser::new(CompilerOptions::default())
+/// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+/// check_unused_variables(&body); // `x` is never used.
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub fn check_unused_variables(body: &AstBody) -> Vec<Diagnostic> {
+    let mut checker = UnusedChecker {
+        diagnostics: Vec::new(),
+    };
+    visit::walk_body(&mut checker, body);
+    checker.diagnostics
+}
+
+struct UnusedChecker {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Visitor for UnusedChecker {
+    fn visit_function(&mut self, func: &Function) {
+        let mut uses = Uses::default();
+        uses.visit_statement(&func.body);
+        for (name, span) in uses.declared {
+            let used = uses.used.contains(&name)
+                || uses
+                    .macros
+                    .iter()
+                    .any(|body| body.contains(&format!("${}", name)));
+            if used || name.starts_with('_') {
+                continue;
+            }
+            self.diagnostics.push(
+                Diagnostic::warning(format!("`{}` is never used.", name))
+                    .with_code(codes::UNUSED_VARIABLE)
+                    .with_lint(UNUSED_VARIABLE)
+                    .with_span(span, format!("`{}` is declared here.", name))
+                    .with_note(format!(
+                        "Prefix the name with `_` if this is intended, eg: `_{}`.",
+                        name
+                    )),
+            );
+        }
+        visit::walk_function(self, func);
+    }
+}
+
+/// The variables a function declares and the names it uses.
+/// Functions declared in it only count for their uses, they are checked on their own.
+#[derive(Default)]
+struct Uses {
+    declared: Vec<(String, Range<usize>)>,
+    used: HashSet<String>,
+    /// The bodies of `php!` blocks, they can use a variable as `$name`.
+    macros: Vec<String>,
+    /// How many functions deep the visitor is.
+    nested: usize,
+}
+
+impl Visitor for Uses {
+    fn visit_statement(&mut self, stmt: &Statement) {
+        if let Statement::MacroInvocation(invocation) = stmt {
+            self.macros.push(invocation.body.clone());
+            for arm in invocation.arms.iter() {
+                visit::walk_body(self, &arm.body);
+            }
+        }
+        visit::walk_statement(self, stmt);
+    }
+
+    fn visit_variable(&mut self, var: &Variable) {
+        if self.nested == 0 {
+            self.declared.push((var.name.to_string(), var.span.clone()));
+        }
+        visit::walk_variable(self, var);
+    }
+
+    fn visit_function(&mut self, func: &Function) {
+        self.nested += 1;
+        visit::walk_function(self, func);
+        self.nested -= 1;
+    }
+
+    fn visit_expression(&mut self, expr: &Expression) {
+        match expr {
+            Expression::Literal(literal) if literal.ty.is_none() => {
+                self.used.insert(literal.value.clone());
+            }
+            Expression::Member(member) => {
+                if let Some(origin) = member.origin.value() {
+                    self.used.insert(origin);
+                }
+            }
+            _ => {}
+        }
+        visit::walk_expression(self, expr);
+    }
+
+    fn visit_call(&mut self, call: &Call) {
+        self.used.insert(call.name.to_string());
+        visit::walk_call(self, call);
+    }
+}
@@ -0,0 +1,350 @@
+use std::collections::HashSet;
+
+use crate::compiler::{
+    ast::{
+        build::ClassBuilder,
+        expr,
+        visit::{self, Visitor},
+        AstBody, Class, ClassAllowedStatement, Expression, Function, MemberLookup, Node, NodeKind,
+        Statement, Variable, Visibility,
+    },
+    names::NameGenerator,
+};
+
+/// Moves the top level statics, and the top level variables a function uses, into a class
+/// of static properties, functions can't see the variables of the top level in php.
+/// Every use of them is rewritten to the property, eg: `GLOBALS::count`.
+///
+/// The class takes the place of the first declaration it was given. A declaration with a
+/// value that isn't a literal gives the property its value where it was declared.
+/// A variable that is the origin of a member, eg: `items.length`, is left where it is.
+/// The class is named `class`, or numbered when the source already uses the name, eg: `GLOBALS_2`.
+///
+/// Returns the names that were moved, in the order they were declared.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, CompilerOptions};
+/// # use surn::compiler::{lower::globals::lower_globals, names::NameGenerator};
+/// // static var count: int = 0;
+/// // fn bump() { count = count + 1; }
+/// # let source = "static var count: int = 0;\nfn bump() { count = count + 1; }\n";
+/// # let mut body = Parser::new(CompilerOptions::default())
+/// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+/// let mut names = NameGenerator::for_body(&body);
+/// lower_globals(&mut body, "GLOBALS", &mut names);
+/// // class GLOBALS { pub static count: int = 0; }
+/// // fn bump() { GLOBALS::count = GLOBALS::count + 1; }
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub fn lower_globals(body: &mut AstBody, class: &str, names: &mut NameGenerator) -> Vec<String> {
+    let mut finder = UseFinder {
+        locals: Vec::new(),
+        used: HashSet::new(),
+        origins: HashSet::new(),
+    };
+    visit::walk_body(&mut finder, body);
+
+    let mut declarations: Vec<(usize, Variable)> = Vec::new();
+    for (index, node) in body.get_program().into_iter().enumerate() {
+        let var = match &node.inner {
+            NodeKind::Statement(Statement::Static(s)) => match s.statement.as_ref() {
+                Statement::Var(var) => var,
+                _ => continue,
+            },
+            NodeKind::Statement(Statement::Var(var)) if finder.used.contains(&*var.name) => var,
+            _ => continue,
+        };
+        if !finder.origins.contains(&*var.name) {
+            declarations.push((index, var.clone()));
+        }
+    }
+    let Some((first, _)) = declarations.first().cloned() else {
+        return Vec::new();
+    };
+    let class = names.unique(class);
+    let class = class.as_str();
+
+    let mut moved: Vec<String> = Vec::new();
+    let mut globals = ClassBuilder::new(class);
+    let mut replaced: Vec<(usize, NodeKind)> = Vec::new();
+    for (index, var) in declarations {
+        let name = var.name.to_string();
+        let value = var.assignment.clone();
+        let literal = value.as_ref().is_none_or(is_literal);
+        let declared = moved.contains(&name);
+        if !declared {
+            let default = value.clone().filter(|_| literal);
+            globals = globals.static_property(&name, Visibility::Public, var.ty.clone(), default);
+            moved.push(name.clone());
+        }
+        let kind = match value {
+            Some(value) if declared || !literal => {
+                NodeKind::Expression(expr::assign(expr::static_member(class, &name), value))
+            }
+            _ => NodeKind::Expression(Expression::EndOfLine),
+        };
+        replaced.push((index, kind));
+    }
+
+    let mut rewriter = Rewriter {
+        class,
+        names: moved.iter().cloned().collect(),
+        locals: Vec::new(),
+    };
+    let mut program = body.get_program_mut();
+    for (index, kind) in replaced {
+        program[index].inner = kind;
+    }
+    for node in program.iter_mut() {
+        match &mut node.inner {
+            NodeKind::Statement(stmt) => rewriter.statement(stmt),
+            NodeKind::Expression(expr) => rewriter.expression(expr),
+        }
+    }
+
+    let at = body.get_program()[first];
+    let node = Node::new(
+        NodeKind::Statement(Statement::Class(globals.build())),
+        at.start.clone(),
+        at.end.clone(),
+    )
+    .at(at.position);
+    body.insert_node(first, node);
+    moved
+}
+
+/// Whether a value can be the default of a property, php only allows constant expressions.
+fn is_literal(value: &Expression) -> bool {
+    match value {
+        Expression::Literal(literal) => literal.ty.is_some(),
+        Expression::Array(array) => array.values.iter().all(is_literal),
+        _ => false,
+    }
+}
+
+/// The names a function declares, its own variables in php, whichever block they are in.
+fn locals(func: &Function) -> HashSet<String> {
+    struct Locals(HashSet<String>);
+    impl Visitor for Locals {
+        fn visit_statement(&mut self, stmt: &Statement) {
+            match stmt {
+                Statement::Destructure(destructure) => {
+                    self.0.extend(destructure.names.iter().cloned())
+                }
+                Statement::Try(t) => {
+                    self.0.extend(t.catches.iter().map(|c| c.name.clone()));
+                }
+                _ => {}
+            }
+            visit::walk_statement(self, stmt);
+        }
+
+        fn visit_variable(&mut self, var: &Variable) {
+            self.0.insert(var.name.to_string());
+            visit::walk_variable(self, var);
+        }
+
+        // a nested function or class has variables of its own.
+        fn visit_function(&mut self, _: &Function) {}
+
+        fn visit_class(&mut self, _: &Class) {}
+    }
+    let mut found = Locals(func.inputs.iter().map(|i| i.name.clone()).collect());
+    visit::walk_function(&mut found, func);
+    found.0
+}
+
+/// Finds the names functions use that they don't declare.
+struct UseFinder {
+    /// The names declared by the functions being visited, the innermost is last.
+    locals: Vec<HashSet<String>>,
+    used: HashSet<String>,
+    /// The names used as the origin of a member, eg: `items` in `items.length`.
+    origins: HashSet<String>,
+}
+
+impl Visitor for UseFinder {
+    fn visit_function(&mut self, func: &Function) {
+        self.locals.push(locals(func));
+        visit::walk_function(self, func);
+        self.locals.pop();
+    }
+
+    fn visit_expression(&mut self, expr: &Expression) {
+        match expr {
+            Expression::Literal(literal) if literal.ty.is_none() => {
+                if self
+                    .locals
+                    .last()
+                    .is_some_and(|locals| !locals.contains(&literal.value))
+                {
+                    self.used.insert(literal.value.clone());
+                }
+            }
+            Expression::Member(member) => {
+                self.origins.extend(member.origin.value());
+                if matches!(member.lookup, MemberLookup::Index) {
+                    self.visit_expression(&member.name);
+                }
+            }
+            _ => visit::walk_expression(self, expr),
+        }
+    }
+}
+
+/// Rewrites every use of a moved name to the property it was moved to.
+struct Rewriter<'a> {
+    class: &'a str,
+    names: HashSet<String>,
+    /// The names declared by the functions being rewritten, the innermost is last.
+    locals: Vec<HashSet<String>>,
+}
+
+impl Rewriter<'_> {
+    fn statement(&mut self, stmt: &mut Statement) {
+        match stmt {
+            Statement::Var(var) | Statement::Const(var) => {
+                if let Some(assignment) = &mut var.assignment {
+                    self.expression(assignment);
+                }
+            }
+            Statement::Destructure(destructure) => self.expression(&mut destructure.assignment),
+            Statement::Static(s) => self.statement(&mut s.statement),
+            Statement::Function(func) => self.function(func),
+            Statement::Class(class) => {
+                for method in class.body.methods.iter_mut() {
+                    self.function(method);
+                }
+                for other in class.body.other.iter_mut() {
+                    self.class_statement(other);
+                }
+                for property in class.body.properties.iter_mut() {
+                    for accessor in property.accessors.iter_mut() {
+                        if let Some(body) = &mut accessor.body {
+                            self.function(body);
+                        }
+                    }
+                }
+            }
+            Statement::Block(exprs) => {
+                for expr in exprs.iter_mut() {
+                    self.expression(expr);
+                }
+            }
+            Statement::Return(ret) => {
+                if let Some(expr) = &mut ret.expression {
+                    self.expression(expr);
+                }
+            }
+            Statement::Namespace(namespace) => {
+                if let Some(body) = &mut namespace.body {
+                    self.statement(body);
+                }
+            }
+            Statement::If(i) => {
+                self.expression(&mut i.condition);
+                self.statement(&mut i.body);
+                if let Some(otherwise) = &mut i.otherwise {
+                    self.statement(otherwise);
+                }
+            }
+            Statement::Try(t) => {
+                self.statement(&mut t.body);
+                for catch in t.catches.iter_mut() {
+                    self.statement(&mut catch.body);
+                }
+                if let Some(finally) = &mut t.finally {
+                    self.statement(finally);
+                }
+            }
+            Statement::Throw(expr) => self.expression(expr),
+            Statement::Enum(_)
+            | Statement::Import(_)
+            | Statement::TypeDef(_)
+            | Statement::MacroInvocation(_) => {}
+        }
+    }
+
+    fn class_statement(&mut self, stmt: &mut ClassAllowedStatement) {
+        match stmt {
+            ClassAllowedStatement::Method(method) => self.function(method),
+            ClassAllowedStatement::Property(property) => {
+                for accessor in property.accessors.iter_mut() {
+                    if let Some(body) = &mut accessor.body {
+                        self.function(body);
+                    }
+                }
+            }
+            ClassAllowedStatement::Static(inner) => self.class_statement(inner),
+            ClassAllowedStatement::Constant(_)
+            | ClassAllowedStatement::Macro(_)
+            | ClassAllowedStatement::Import(_) => {}
+        }
+    }
+
+    fn function(&mut self, func: &mut Function) {
+        self.locals.push(locals(func));
+        self.statement(&mut func.body);
+        self.locals.pop();
+    }
+
+    fn expression(&mut self, expr: &mut Expression) {
+        match expr {
+            Expression::Literal(literal) if literal.ty.is_none() => {
+                let local = self
+                    .locals
+                    .last()
+                    .is_some_and(|locals| locals.contains(&literal.value));
+                if !local && self.names.contains(&literal.value) {
+                    *expr = expr::static_member(self.class, &literal.value);
+                }
+            }
+            Expression::Call(call) => {
+                for arg in call.arguments.iter_mut() {
+                    self.expression(arg);
+                }
+            }
+            Expression::MethodCall(call) => {
+                self.expression(&mut call.callee);
+                for arg in call.arguments.iter_mut() {
+                    self.expression(arg);
+                }
+            }
+            Expression::New(call) => {
+                for arg in call.arguments.iter_mut() {
+                    self.expression(arg);
+                }
+            }
+            Expression::Array(array) => {
+                for value in array.values.iter_mut() {
+                    self.expression(value);
+                }
+            }
+            Expression::Tuple(values) => {
+                for value in values.iter_mut() {
+                    self.expression(value);
+                }
+            }
+            Expression::Object(object) => {
+                for property in object.properties.iter_mut() {
+                    self.expression(&mut property.value);
+                }
+            }
+            Expression::Operation(op) => {
+                self.expression(&mut op.left);
+                self.expression(&mut op.right);
+            }
+            Expression::Await(inner) | Expression::Spread(inner) => self.expression(inner),
+            Expression::Is(test) => self.expression(&mut test.value),
+            Expression::Statement(stmt) => self.statement(stmt),
+            // the name of a property isn't a variable, an index is.
+            Expression::Member(member) => {
+                if matches!(member.lookup, MemberLookup::Index) {
+                    self.expression(&mut member.name);
+                }
+            }
+            Expression::Literal(_) | Expression::EndOfLine => {}
+        }
+    }
+}
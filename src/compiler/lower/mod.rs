@@ -0,0 +1,11 @@
+//! Passes that rewrite the body into something every generator can emit,
+//! eg: top level statics, which only exist in a function in php.
+use super::{ast::AstBody, names::NameGenerator, CompilerOptions};
+
+pub mod globals;
+
+/// Runs every lowering over the body, this is done before it is optimized.
+pub fn lower(body: &mut AstBody, options: &CompilerOptions) {
+    let mut names = NameGenerator::for_body(body);
+    globals::lower_globals(body, &options.globals_class, &mut names);
+}
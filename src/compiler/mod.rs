@@ -1,12 +1,46 @@
 pub mod ast;
-pub mod error;
+pub mod eval;
+pub mod infer;
+pub mod interp;
 pub mod lexer;
+pub mod lints;
+pub mod lower;
+pub mod names;
+pub mod optimize;
 pub mod parser;
+pub mod resolve;
+pub mod stdlib;
+pub mod symbols;
+
+use std::{fmt, path::PathBuf};
+
+use self::{
+    lints::{LintLevel, Lints},
+    optimize::profile::Profile,
+};
+use crate::{
+    report::{Report, ReportKind},
+    transpiler::frameworks::Framework,
+};
 
 pub const CURRENT_VERSION: &'static str = "0.0.1-alpha.rc.1";
 pub const NIGHTLY_VERSION: &'static str = "0.0.1-alpha.rc.1";
 pub const BETA_VERSION: &'static str = "0.0.1-alpha.rc.1";
 
+/// The largest source compiled by default, in bytes.
+///
+/// Parsing takes time quadratic in the number of tokens, because rules that
+/// backtrack copy the rest of the token stream. On a typical machine a 16 KiB
+/// file parses in under a second, 64 KiB in about 8 seconds and 256 KiB in well
+/// over a minute, so anything larger than this is most likely generated code.
+pub const DEFAULT_MAX_FILE_SIZE: usize = 128 * 1024;
+/// The most tokens compiled from a single source by default,
+/// a 128 KiB file of ordinary code has about 80 thousand.
+pub const DEFAULT_MAX_TOKENS: usize = 100_000;
+/// The class top level statics are moved into by default, see `lower::globals`.
+pub const DEFAULT_GLOBALS_CLASS: &str = "GLOBALS";
+
+#[derive(Debug, Clone)]
 pub struct CompilerOptions {
     /// The version of the compiler to compile with, by default,
     /// this is the most recent version.
@@ -33,13 +67,63 @@ pub struct CompilerOptions {
     /// Whether or not to stop compiling after the ast is complete.
     /// This is useful for debugging / testing.
     pub ast_only: bool,
-    // / The target php version to compile for.
-    // pub target_php_version: &'static str,
+    /// The php version the generated code has to run on, `None` for the newest.
+    pub target: Option<PhpTarget>,
+    /// Whether or not the generated php starts with `declare(strict_types=1);`.
+    pub strict_types: bool,
     pub detect_bleeding_declarations: bool,
+    /// Whether or not to write a source map sidecar next to every generated file.
+    /// See `transpiler::sourcemap`.
+    pub source_maps: bool,
+    /// Whether or not to write a `preload.php` for opcache next to the generated
+    /// classes when a directory is transpiled. See `transpiler::preload`.
+    pub preload: bool,
+    /// The framework to write glue code for when a directory is transpiled.
+    /// See `transpiler::frameworks`.
+    pub framework: Option<Framework>,
+    /// Whether or not to specialize functions with `any` parameters for the
+    /// concrete types they are called with, see `optimize::specialize`.
+    /// This requires `optimize`.
+    pub specialize: bool,
+    /// The functions that profiling marked as hot paths.
+    /// Only these are specialized, every other function keeps its erased signature.
+    pub hot_functions: Vec<String>,
+    /// Call counts of the generated php, the functions it finds hot are
+    /// optimized along with `hot_functions`. See `optimize::profile`.
+    pub profile: Option<Profile>,
+    /// The level of every lint, eg: whether the places the target language
+    /// can't keep the meaning of the source are reported. See `lints`.
+    pub lints: Lints,
+    /// Whether or not to record every rule the parser enters and exits,
+    /// see `parser::trace`. This is useful for debugging the grammar.
+    pub trace_parse: bool,
+    /// The largest source to compile in bytes, `None` for no limit.
+    /// See `DEFAULT_MAX_FILE_SIZE`.
+    pub max_file_size: Option<usize>,
+    /// The most tokens to parse from a single source, `None` for no limit.
+    pub max_tokens: Option<usize>,
+    /// Directories of declarations the sources can use, eg: the `.d.surn` files
+    /// `surnc declare` writes. They are parsed into the contexts before the first script.
+    pub include_paths: Vec<PathBuf>,
+    /// The most scripts to parse at once, `None` for one per core.
+    /// Scripts are parsed one at a time without the `threads` feature.
+    /// See `Parser::parse_scripts`.
+    pub jobs: Option<usize>,
+    /// The least severe kind of report that fails a compile, errors always do.
+    /// Eg: `ReportKind::Warning` makes `surnc build` exit with 1 on a warning,
+    /// see `CompilerOptions::fails`.
+    pub fail_on: ReportKind,
+    /// The class the top level statics, and the top level variables functions use,
+    /// are moved into before code is generated, see `lower::globals`.
+    pub globals_class: String,
+    /// Whether or not the values given to runtime types are checked when the generated
+    /// code runs, eg: `type Positive(x) = x > 0;`. The values are checked where they are
+    /// declared and where they are passed to a function.
+    pub runtime_checks: bool,
 }
 
-impl CompilerOptions {
-    pub fn default() -> Self {
+impl Default for CompilerOptions {
+    fn default() -> Self {
         Self {
             version: NIGHTLY_VERSION,
             semantic_checks: true,
@@ -47,19 +131,232 @@ impl CompilerOptions {
             dump_ast: false,
             post_semantic_checks: true,
             ast_only: false,
+            target: None,
+            strict_types: false,
             detect_bleeding_declarations: false,
+            source_maps: false,
+            preload: false,
+            framework: None,
+            specialize: false,
+            hot_functions: Vec::new(),
+            profile: None,
+            lints: Lints::new(),
+            trace_parse: false,
+            max_file_size: Some(DEFAULT_MAX_FILE_SIZE),
+            max_tokens: Some(DEFAULT_MAX_TOKENS),
+            include_paths: Vec::new(),
+            jobs: None,
+            fail_on: ReportKind::Error,
+            globals_class: DEFAULT_GLOBALS_CLASS.to_string(),
+            runtime_checks: false,
         }
     }
+}
 
-    pub fn dev() -> Self {
-        Self {
-            version: CURRENT_VERSION,
-            semantic_checks: true,
-            optimize: true,
-            dump_ast: true,
-            post_semantic_checks: false,
-            ast_only: false,
-            detect_bleeding_declarations: false,
+impl CompilerOptions {
+    /// A builder that starts from the default options and checks them once they're built.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::compiler::{CompilerOptions, PhpTarget};
+    /// let options = CompilerOptions::builder()
+    ///     .optimize(true)
+    ///     .strict_types(true)
+    ///     .target(PhpTarget::Php81)
+    ///     .build()?;
+    /// # Ok::<(), surn::report::Report>(())
+    /// ```
+    pub fn builder() -> CompilerOptionsBuilder {
+        CompilerOptionsBuilder::from(Self::default())
+    }
+
+    /// Whether a report of the kind fails the compile, see `fail_on`.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::{compiler::CompilerOptions, report::ReportKind};
+    /// let options = CompilerOptions::builder().fail_on(ReportKind::Warning).build()?;
+    /// options.fails(ReportKind::Warning); // true
+    /// options.fails(ReportKind::Notice); // false
+    /// # Ok::<(), surn::report::Report>(())
+    /// ```
+    pub fn fails(&self, kind: ReportKind) -> bool {
+        kind <= self.fail_on
+    }
+
+    /// Why the options can't be compiled with, if they can't.
+    pub fn validate(&self) -> Result<(), Report> {
+        let invalid = |message: String| {
+            Err(Report::new()
+                .set_name("compiler options".to_string())
+                .set_message(message))
+        };
+        if self.ast_only {
+            let emitting = [
+                ("target", self.target.is_some()),
+                ("strict_types", self.strict_types),
+                ("source_maps", self.source_maps),
+                ("preload", self.preload),
+                ("framework", self.framework.is_some()),
+            ];
+            if let Some((name, _)) = emitting.iter().find(|(_, set)| *set) {
+                return invalid(format!(
+                    "`ast_only` stops before any code is generated, so `{}` can't be set with it.",
+                    name
+                ));
+            }
+        }
+        if self.specialize && !self.optimize {
+            return invalid("`specialize` is an optimization, it requires `optimize`.".to_string());
+        }
+        if self.max_file_size == Some(0) || self.max_tokens == Some(0) {
+            return invalid(
+                "A limit of 0 refuses every source, use `None` for no limit instead.".to_string(),
+            );
+        }
+        if self.jobs == Some(0) {
+            return invalid(
+                "`jobs` has to be at least 1, use `None` for one per core.".to_string(),
+            );
+        }
+        let mut chars = self.globals_class.chars();
+        let is_name = chars.next().is_some_and(|c| c == '_' || c.is_alphabetic())
+            && chars.all(|c| c == '_' || c.is_alphanumeric());
+        if !is_name {
+            return invalid(format!(
+                "`globals_class` has to be the name of a class, `{}` isn't one.",
+                self.globals_class
+            ));
+        }
+        if let Some(lint) = self.lints.unknown().first() {
+            return invalid(format!(
+                "`{}` is not a lint, the lints are: {}.",
+                lint,
+                lints::KNOWN_LINTS.join(", ")
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The php versions code can be generated for, the generated code uses enums
+/// and `match` so 8.1 is the oldest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PhpTarget {
+    Php81,
+    Php82,
+}
+
+impl PhpTarget {
+    /// The target of a version, eg: `8.1`.
+    pub fn from_version(version: &str) -> Option<Self> {
+        match version {
+            "8.1" => Some(PhpTarget::Php81),
+            "8.2" => Some(PhpTarget::Php82),
+            _ => None,
         }
     }
 }
+
+impl fmt::Display for PhpTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let version = match self {
+            PhpTarget::Php81 => "8.1",
+            PhpTarget::Php82 => "8.2",
+        };
+        write!(f, "{}", version)
+    }
+}
+
+/// Declares a setter on the builder for every option.
+macro_rules! option_setters {
+    ($($name: ident: $ty: ty;)*) => {
+        impl CompilerOptionsBuilder {
+            $(pub fn $name(mut self, $name: $ty) -> Self {
+                self.options.$name = $name;
+                self
+            })*
+        }
+    };
+}
+
+/// Builds `CompilerOptions`, see `CompilerOptions::builder`.
+/// A builder made from other options, eg: `CompilerOptionsBuilder::from(options)`,
+/// starts from them instead of the defaults.
+#[derive(Debug, Clone)]
+pub struct CompilerOptionsBuilder {
+    options: CompilerOptions,
+}
+
+impl From<CompilerOptions> for CompilerOptionsBuilder {
+    fn from(options: CompilerOptions) -> Self {
+        Self { options }
+    }
+}
+
+option_setters! {
+    version: &'static str;
+    semantic_checks: bool;
+    optimize: bool;
+    dump_ast: bool;
+    post_semantic_checks: bool;
+    ast_only: bool;
+    strict_types: bool;
+    detect_bleeding_declarations: bool;
+    source_maps: bool;
+    preload: bool;
+    specialize: bool;
+    hot_functions: Vec<String>;
+    lints: Lints;
+    trace_parse: bool;
+    max_file_size: Option<usize>;
+    max_tokens: Option<usize>;
+    include_paths: Vec<PathBuf>;
+    jobs: Option<usize>;
+    fail_on: ReportKind;
+    globals_class: String;
+    runtime_checks: bool;
+}
+
+impl CompilerOptionsBuilder {
+    /// The options used while working on the compiler, the ast is dumped and the
+    /// checks after parsing are skipped.
+    ///
+    /// **Example:**
+    /// ```rust ignore
+    /// let options = CompilerOptions::builder().dev().build()?;
+    /// ```
+    pub fn dev(mut self) -> Self {
+        self.options.version = CURRENT_VERSION;
+        self.options.dump_ast = true;
+        self.options.post_semantic_checks = false;
+        self
+    }
+
+    pub fn target(mut self, target: PhpTarget) -> Self {
+        self.options.target = Some(target);
+        self
+    }
+
+    pub fn framework(mut self, framework: Framework) -> Self {
+        self.options.framework = Some(framework);
+        self
+    }
+
+    pub fn profile(mut self, profile: Profile) -> Self {
+        self.options.profile = Some(profile);
+        self
+    }
+
+    /// Sets the level of a single lint, see `Lints::set`.
+    pub fn lint(mut self, lint: &str, level: LintLevel) -> Self {
+        self.options.lints.set(lint, level);
+        self
+    }
+
+    /// The options, or why they can't be compiled with.
+    pub fn build(self) -> Result<CompilerOptions, Report> {
+        self.options.validate()?;
+        Ok(self.options)
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::HashSet;
+
+use crate::compiler::ast::{
+    visit::{self, Visitor},
+    AstBody, Call, Class, ClassProperty, Expression, Function, Statement, Variable,
+};
+
+/// The prefix of every name the compiler makes up.
+pub const TMP_PREFIX: &str = "__surn_tmp_";
+
+/// Hands out fresh names for symbols synthesized by lowering passes,
+/// eg: the temporaries of a desugared expression or the `GLOBALS` class.
+///
+/// Names are numbered per context in the order they are asked for, so building
+/// the same source twice gives the same names. A name the source already uses
+/// is skipped.
+///
+/// **Example:**
+/// ```rust ignore
+/// // a body parsed in context 3
+/// let mut names = NameGenerator::for_body(&body);
+/// names.fresh(); // __surn_tmp_3_1
+/// names.fresh(); // __surn_tmp_3_2, or __surn_tmp_3_3 if the source declares __surn_tmp_3_2
+/// names.unique("GLOBALS"); // GLOBALS, or GLOBALS_2 if the source declares GLOBALS
+/// ```
+#[derive(Debug, Clone)]
+pub struct NameGenerator {
+    context: u64,
+    next: u64,
+    taken: HashSet<String>,
+}
+
+impl NameGenerator {
+    pub fn new(context: u64) -> Self {
+        Self {
+            context,
+            next: 0,
+            taken: HashSet::new(),
+        }
+    }
+
+    /// A generator for the context of a body that avoids every symbol used in it.
+    pub fn for_body(body: &AstBody) -> Self {
+        let mut generator = Self::new(body.context());
+        generator.taken = symbols(body);
+        generator
+    }
+
+    /// Marks a name as taken so it is never handed out.
+    pub fn reserve(&mut self, name: &str) {
+        self.taken.insert(name.to_string());
+    }
+
+    pub fn is_taken(&self, name: &str) -> bool {
+        self.taken.contains(name)
+    }
+
+    /// The name itself when it is free, otherwise the name numbered from 2, eg: `GLOBALS_2`.
+    /// The name returned is taken from then on.
+    pub fn unique(&mut self, name: &str) -> String {
+        let mut candidate = name.to_string();
+        let mut n = 1;
+        while !self.taken.insert(candidate.clone()) {
+            n += 1;
+            candidate = format!("{}_{}", name, n);
+        }
+        candidate
+    }
+
+    pub fn fresh(&mut self) -> String {
+        loop {
+            self.next += 1;
+            let name = format!("{}{}_{}", TMP_PREFIX, self.context, self.next);
+            if self.taken.insert(name.clone()) {
+                return name;
+            }
+        }
+    }
+}
+
+/// Every name declared or referenced in a body.
+pub fn symbols(body: &AstBody) -> HashSet<String> {
+    let mut collector = SymbolCollector {
+        names: HashSet::new(),
+    };
+    visit::walk_body(&mut collector, body);
+    collector.names
+}
+
+struct SymbolCollector {
+    names: HashSet<String>,
+}
+
+impl Visitor for SymbolCollector {
+    fn visit_statement(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Destructure(destructure) => {
+                self.names.extend(destructure.names.iter().cloned())
+            }
+            Statement::TypeDef(def) => {
+                self.names.insert(def.name.clone());
+            }
+            Statement::Enum(e) => {
+                self.names.insert(e.name.clone());
+            }
+            _ => {}
+        }
+        visit::walk_statement(self, stmt);
+    }
+
+    fn visit_expression(&mut self, expr: &Expression) {
+        match expr {
+            // literals without a type are names of variables and consts.
+            Expression::Literal(literal) if literal.ty.is_none() => {
+                self.names.insert(literal.value.clone());
+            }
+            Expression::MethodCall(call) => {
+                self.names.insert(call.name.clone());
+            }
+            _ => {}
+        }
+        visit::walk_expression(self, expr);
+    }
+
+    fn visit_variable(&mut self, var: &Variable) {
+        self.names.insert(var.name.to_string());
+        visit::walk_variable(self, var);
+    }
+
+    fn visit_function(&mut self, func: &Function) {
+        self.names.extend(func.name.iter().cloned());
+        self.names
+            .extend(func.inputs.iter().map(|input| input.name.clone()));
+        visit::walk_function(self, func);
+    }
+
+    fn visit_class(&mut self, class: &Class) {
+        self.names.insert(class.name.clone());
+        visit::walk_class(self, class);
+    }
+
+    fn visit_class_property(&mut self, property: &ClassProperty) {
+        self.names.insert(property.name.clone());
+        visit::walk_class_property(self, property);
+    }
+
+    fn visit_call(&mut self, call: &Call) {
+        self.names.insert(call.name.to_string());
+        visit::walk_call(self, call);
+    }
+}
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use crate::compiler::{
+    ast::{AstBody, ClassAllowedStatement, Expression, NodeKind, Purity, Statement},
+    infer::purity::{function_purities, infer_purity, is_pure_expression},
+};
+
+/// Removes the calls to pure functions whose result is never used.
+///
+/// A call that is a statement of its own only matters for its side effects, so a call
+/// to a pure function with pure arguments can be dropped. Purity is inferred again first,
+/// earlier passes may have added functions.
+///
+/// Returns the name of the function of every call that was removed.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, CompilerOptions};
+/// # use surn::compiler::optimize::dead_calls::remove_dead_calls;
+/// // fn add(a: int, b: int): int { return a + b; }
+/// // fn main() { add(1, 2); echo("done"); }
+/// # let source = "fn add(a: int, b: int): int { return a + b; }\nfn main() { add(1, 2); echo(\"done\"); }\n";
+/// # let mut body = Parser::new(CompilerOptions::default())
+/// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+/// remove_dead_calls(&mut body);
+/// // fn main() { echo("done"); }
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub fn remove_dead_calls(body: &mut AstBody) -> Vec<String> {
+    infer_purity(body);
+    let purities = function_purities(body);
+    let mut removed = Vec::new();
+    for node in body.get_program_mut().iter_mut() {
+        match &mut node.inner {
+            NodeKind::Statement(stmt) => statement(stmt, &purities, &mut removed),
+            NodeKind::Expression(expr) => expression(expr, &purities, &mut removed),
+        }
+    }
+    removed
+}
+
+fn statement(stmt: &mut Statement, purities: &HashMap<String, Purity>, removed: &mut Vec<String>) {
+    match stmt {
+        Statement::Function(func) => statement(&mut func.body, purities, removed),
+        Statement::Class(class) => {
+            for method in class.body.methods.iter_mut() {
+                statement(&mut method.body, purities, removed);
+            }
+            for other in class.body.other.iter_mut() {
+                let mut other = other;
+                while let ClassAllowedStatement::Static(inner) = other {
+                    other = inner;
+                }
+                if let ClassAllowedStatement::Method(method) = other {
+                    statement(&mut method.body, purities, removed);
+                }
+            }
+        }
+        Statement::Static(s) => statement(&mut s.statement, purities, removed),
+        Statement::Namespace(namespace) => {
+            if let Some(body) = &mut namespace.body {
+                statement(body, purities, removed);
+            }
+        }
+        Statement::Try(t) => {
+            statement(&mut t.body, purities, removed);
+            for catch in t.catches.iter_mut() {
+                statement(&mut catch.body, purities, removed);
+            }
+            if let Some(finally) = &mut t.finally {
+                statement(finally, purities, removed);
+            }
+        }
+        Statement::Block(exprs) => {
+            // a call statement is the call followed by the end of the line.
+            let mut i = 0;
+            while i < exprs.len() {
+                let dead = match (&exprs[i], exprs.get(i + 1)) {
+                    (Expression::Call(call), Some(Expression::EndOfLine))
+                        if is_pure_expression(&exprs[i], purities) =>
+                    {
+                        Some(call.name.to_string())
+                    }
+                    _ => None,
+                };
+                match dead {
+                    Some(name) => {
+                        exprs.drain(i..i + 2);
+                        removed.push(name);
+                    }
+                    None => {
+                        expression(&mut exprs[i], purities, removed);
+                        i += 1;
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn expression(
+    expr: &mut Expression,
+    purities: &HashMap<String, Purity>,
+    removed: &mut Vec<String>,
+) {
+    if let Expression::Statement(stmt) = expr {
+        statement(stmt, purities, removed);
+    }
+}
@@ -0,0 +1,82 @@
+use super::{ast::AstBody, eval::fold_consts, names::NameGenerator, CompilerOptions};
+
+use self::profile::Profile;
+
+pub mod dead_calls;
+pub mod profile;
+pub mod specialize;
+
+/// What the passes did to a body.
+#[derive(Debug, Clone, Default)]
+pub struct OptimizeStats {
+    /// The functions that were treated as hot paths, the most called first.
+    pub hot: Vec<String>,
+    /// Every specialized copy, with the name of the function it was made from.
+    pub specialized: Vec<(String, String)>,
+    /// The function of every unused call to a pure function that was removed.
+    pub removed_calls: Vec<String>,
+}
+
+impl OptimizeStats {
+    /// Lists every hot function along with how it was optimized.
+    /// Call counts are taken from the profile when one is given.
+    ///
+    /// **Example:**
+    /// ```text
+    /// Hot functions:
+    ///     add (1200 calls): specialized as add__int_int
+    ///     log (300 calls): not optimized
+    /// ```
+    pub fn report(&self, profile: Option<&Profile>) -> String {
+        if self.hot.is_empty() {
+            return "No hot functions.".to_string();
+        }
+
+        let mut report = String::from("Hot functions:");
+        for name in self.hot.iter() {
+            report.push_str(&format!("\n    {}", name));
+            if let Some(profile) = profile {
+                report.push_str(&format!(" ({} calls)", profile.calls(name)));
+            }
+            let copies = self
+                .specialized
+                .iter()
+                .filter(|(generic, _)| generic == name)
+                .map(|(_, copy)| copy.as_str())
+                .collect::<Vec<&str>>();
+            if copies.is_empty() {
+                report.push_str(": not optimized");
+            } else {
+                report.push_str(&format!(": specialized as {}", copies.join(", ")));
+            }
+        }
+        report
+    }
+}
+
+/// Runs every pass enabled by the options over the body.
+/// This is done after parsing and before code generation.
+pub fn optimize(body: &mut AstBody, options: &CompilerOptions) -> OptimizeStats {
+    let mut stats = OptimizeStats::default();
+    if !options.optimize {
+        return stats;
+    }
+
+    // functions named in the options come first, then the profile from the most called.
+    stats.hot = options.hot_functions.clone();
+    if let Some(profile) = &options.profile {
+        for name in profile.hot_functions() {
+            if !stats.hot.contains(&name) {
+                stats.hot.push(name);
+            }
+        }
+    }
+
+    fold_consts(body);
+    if options.specialize {
+        let mut names = NameGenerator::for_body(body);
+        stats.specialized = specialize::specialize(body, &stats.hot, &mut names);
+    }
+    stats.removed_calls = dead_calls::remove_dead_calls(body);
+    stats
+}
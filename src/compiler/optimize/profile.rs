@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+#[cfg(feature = "fs")]
+use std::{fs, path::Path};
+
+/// The share of all recorded calls a function needs to be considered hot.
+pub const HOT_CALL_SHARE: f64 = 0.01;
+
+/// How often each function was called while profiling the generated php.
+///
+/// Two formats are understood:
+/// - A plain list with one function per line, eg: `add 1200` or `add,1200`
+/// - Callgrind files, which is what xdebug writes, eg: `cachegrind.out.1234`
+///
+/// Names are matched against surn functions without their php namespace,
+/// eg: `\App\add` is counted for `add`.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use std::path::Path;
+/// # use surn::compiler::optimize::profile::Profile;
+/// let profile = Profile::load(Path::new("cachegrind.out.1234"))?;
+/// profile.calls("add"); // 1200
+/// profile.hot_functions(); // ["add"]
+/// # Ok::<(), String>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    calls: HashMap<String, u64>,
+}
+
+impl Profile {
+    #[cfg(feature = "fs")]
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read profile {}: {}", path.display(), e))?;
+        Profile::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let is_callgrind = contents
+            .lines()
+            .any(|l| l.starts_with("events:") || l.starts_with("cfn="));
+        if is_callgrind {
+            Ok(Profile::parse_callgrind(contents))
+        } else {
+            Profile::parse_list(contents)
+        }
+    }
+
+    fn parse_list(contents: &str) -> Result<Self, String> {
+        let mut profile = Profile::default();
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, count) = line.rsplit_once([' ', '\t', ',']).ok_or(format!(
+                "Expected a function and a call count on line {}",
+                i + 1
+            ))?;
+            let count = count
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| format!("Invalid call count on line {}: {}", i + 1, count))?;
+            profile.record(name.trim(), count);
+        }
+        Ok(profile)
+    }
+
+    /// Every `calls=` line counts towards the callee named by the `cfn=` line before it.
+    /// Callgrind can compress names, `cfn=(3) add` defines `(3)` which is later used alone.
+    fn parse_callgrind(contents: &str) -> Self {
+        let mut profile = Profile::default();
+        let mut compressed: HashMap<String, String> = HashMap::new();
+        let mut callee: Option<String> = None;
+
+        for line in contents.lines() {
+            if let Some(name) = line
+                .strip_prefix("cfn=")
+                .or_else(|| line.strip_prefix("fn="))
+            {
+                let name = name.trim();
+                let name = match name.strip_prefix('(').and_then(|n| n.split_once(')')) {
+                    Some((id, rest)) if rest.trim().is_empty() => {
+                        compressed.get(id).cloned().unwrap_or_default()
+                    }
+                    Some((id, rest)) => {
+                        compressed.insert(id.to_string(), rest.trim().to_string());
+                        rest.trim().to_string()
+                    }
+                    None => name.to_string(),
+                };
+                callee = line.starts_with("cfn=").then_some(name);
+            } else if let Some(calls) = line.strip_prefix("calls=") {
+                let count = calls
+                    .split_whitespace()
+                    .next()
+                    .and_then(|c| c.parse::<u64>().ok());
+                if let (Some(name), Some(count)) = (&callee, count) {
+                    profile.record(name, count);
+                }
+                callee = None;
+            }
+        }
+        profile
+    }
+
+    fn record(&mut self, name: &str, count: u64) {
+        let name = name.rsplit('\\').next().unwrap_or(name);
+        *self.calls.entry(name.to_string()).or_insert(0) += count;
+    }
+
+    /// The amount of times the function was called, 0 if it was never seen.
+    pub fn calls(&self, name: &str) -> u64 {
+        self.calls.get(name).copied().unwrap_or(0)
+    }
+
+    pub fn total_calls(&self) -> u64 {
+        self.calls.values().sum()
+    }
+
+    /// The functions that took at least `HOT_CALL_SHARE` of all calls,
+    /// the most called first.
+    pub fn hot_functions(&self) -> Vec<String> {
+        let threshold = (self.total_calls() as f64 * HOT_CALL_SHARE).ceil() as u64;
+        let mut hot = self
+            .calls
+            .iter()
+            .filter(|(_, count)| **count >= threshold.max(1))
+            .collect::<Vec<(&String, &u64)>>();
+        hot.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        hot.into_iter().map(|(name, _)| name.clone()).collect()
+    }
+}
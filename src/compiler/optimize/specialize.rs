@@ -0,0 +1,442 @@
+use std::collections::HashMap;
+
+use crate::compiler::{
+    ast::{
+        types::{BuiltInType, ObjectShape, ShapeProperty, TypeKind},
+        AstBody, Call, Expression, Function, Node, NodeKind, Statement,
+    },
+    infer::{infer_expression, tuple_bindings, FunctionSignature},
+    names::NameGenerator,
+};
+
+/// Specializes hot functions for the concrete types they are called with.
+///
+/// Parameters typed `any` are erased, in php they become `mixed` and every call pays for it,
+/// and so are the type parameters of a generic function, which become their bounds.
+/// When every `any` argument of a call to a hot function has a known type, and every type
+/// parameter is given or can be told from the arguments, a copy of the function with those
+/// types is emitted right after it and the call is redirected to the copy.
+/// The original function is kept for the calls that can't be specialized.
+/// A copy is named after the function and the types, unless the source already uses that name.
+///
+/// Returns every specialized copy that was added, with the name of the function it was made from.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{
+/// #     names::NameGenerator, optimize::specialize::specialize, parser::Parser, CompilerOptions,
+/// # };
+/// // fn add(a: any, b: any): any { return a + b; }
+/// // var x = add(1, 2);
+/// # let source = "fn add(a: any, b: any): any {\n    return a + b;\n}\nvar x = add(1, 2);\n";
+/// # let mut body = Parser::new(CompilerOptions::default())
+/// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+/// let mut names = NameGenerator::for_body(&body);
+/// specialize(&mut body, &["add".to_string()], &mut names);
+/// // fn add(a: any, b: any): any { return a + b; }
+/// // fn add__int_int(a: int, b: int): any { return a + b; }
+/// // var x = add__int_int(1, 2);
+///
+/// // fn first<T>(items: T[]): T { return items[0]; }
+/// // var f = first([1, 2]);
+/// // fn first__int(items: int[]): int { return items[0]; }
+/// // var f = first__int([1, 2]);
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub fn specialize(
+    body: &mut AstBody,
+    hot: &[String],
+    names: &mut NameGenerator,
+) -> Vec<(String, String)> {
+    let mut specializer = Specializer {
+        names,
+        copies: HashMap::new(),
+        generics: HashMap::new(),
+        signatures: HashMap::new(),
+        scopes: vec![HashMap::new()],
+        created: Vec::new(),
+    };
+    for node in body.get_program() {
+        if let NodeKind::Statement(Statement::Function(func)) = &node.inner {
+            let name = match &func.name {
+                Some(name) => name.clone(),
+                None => continue,
+            };
+            specializer.signatures.insert(
+                name.clone(),
+                FunctionSignature {
+                    inputs: func.inputs.clone(),
+                    outputs: func.outputs.clone(),
+                    generics: func.generics.clone(),
+                    span: func.span.clone(),
+                },
+            );
+            let erased = func.inputs.iter().any(|i| is_any(i.ty.as_ref()))
+                || func.generics.iter().any(|g| g.name.is_some());
+            if hot.contains(&name) && erased {
+                specializer.generics.insert(name, func.clone());
+            }
+        }
+    }
+
+    for node in body.get_program_mut().iter_mut() {
+        match &mut node.inner {
+            NodeKind::Statement(stmt) => specializer.statement(stmt),
+            NodeKind::Expression(expr) => specializer.expression(expr),
+        }
+    }
+
+    let added = specializer
+        .created
+        .iter()
+        .map(|(generic, func)| (generic.clone(), func.name.clone().unwrap_or_default()))
+        .collect();
+    for (generic, func) in specializer.created {
+        let program = body.get_program();
+        let index = program
+            .iter()
+            .rposition(|node| match &node.inner {
+                NodeKind::Statement(Statement::Function(f)) => {
+                    f.name.as_deref() == Some(generic.as_str())
+                        || f.name
+                            .as_deref()
+                            .is_some_and(|n| n.starts_with(&format!("{}__", generic)))
+                }
+                _ => false,
+            })
+            .unwrap();
+        // the copy points back at the original so errors and source maps do too.
+        let node = Node::new(
+            NodeKind::Statement(Statement::Function(func)),
+            program[index].start.clone(),
+            program[index].end.clone(),
+        );
+        body.insert_node(index + 1, node);
+    }
+    added
+}
+
+struct Specializer<'a> {
+    names: &'a mut NameGenerator,
+    /// The name of the copy made for every function and types, eg: `add__int_int`.
+    copies: HashMap<String, String>,
+    /// The hot functions that have erased parameters or type parameters.
+    generics: HashMap<String, Function>,
+    signatures: HashMap<String, FunctionSignature>,
+    scopes: Vec<HashMap<String, TypeKind>>,
+    /// The specialized copies, with the name of the function they were made from.
+    created: Vec<(String, Function)>,
+}
+
+impl Specializer<'_> {
+    fn lookup(&self, name: &str) -> Option<TypeKind> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .cloned()
+    }
+
+    fn statement(&mut self, stmt: &mut Statement) {
+        match stmt {
+            Statement::Var(var) | Statement::Const(var) => {
+                if let Some(assignment) = &mut var.assignment {
+                    self.expression(assignment);
+                }
+                let ty = var.ty.clone().or_else(|| {
+                    var.assignment.as_ref().and_then(|expr| {
+                        infer_expression(expr, &|name| self.lookup(name), &self.signatures)
+                    })
+                });
+                if let Some(ty) = ty {
+                    self.scopes
+                        .last_mut()
+                        .unwrap()
+                        .insert(var.name.to_string(), ty);
+                }
+            }
+            Statement::Destructure(destructure) => {
+                self.expression(&mut destructure.assignment);
+                let ty = destructure.ty.clone().or_else(|| {
+                    infer_expression(
+                        &destructure.assignment,
+                        &|name| self.lookup(name),
+                        &self.signatures,
+                    )
+                });
+                if let Some(ty) = ty {
+                    let scope = self.scopes.last_mut().unwrap();
+                    scope.extend(tuple_bindings(&destructure.names, &ty));
+                }
+            }
+            Statement::Static(s) => self.statement(&mut s.statement),
+            Statement::Function(func) => self.function(func),
+            Statement::Class(class) => {
+                for method in class.body.methods.iter_mut() {
+                    self.function(method);
+                }
+            }
+            Statement::Block(exprs) => {
+                self.scopes.push(HashMap::new());
+                for expr in exprs.iter_mut() {
+                    self.expression(expr);
+                }
+                self.scopes.pop();
+            }
+            Statement::Return(ret) => {
+                if let Some(expr) = &mut ret.expression {
+                    self.expression(expr);
+                }
+            }
+            Statement::Namespace(namespace) => {
+                if let Some(body) = &mut namespace.body {
+                    self.statement(body);
+                }
+            }
+            Statement::If(i) => {
+                self.expression(&mut i.condition);
+                self.statement(&mut i.body);
+                if let Some(otherwise) = &mut i.otherwise {
+                    self.statement(otherwise);
+                }
+            }
+            Statement::Try(t) => {
+                self.statement(&mut t.body);
+                for catch in t.catches.iter_mut() {
+                    self.statement(&mut catch.body);
+                }
+                if let Some(finally) = &mut t.finally {
+                    self.statement(finally);
+                }
+            }
+            Statement::Throw(expr) => self.expression(expr),
+            Statement::Enum(_)
+            | Statement::Import(_)
+            | Statement::TypeDef(_)
+            | Statement::MacroInvocation(_) => {}
+        }
+    }
+
+    fn function(&mut self, func: &mut Function) {
+        let scope = func
+            .inputs
+            .iter()
+            .filter_map(|input| Some((input.name.clone(), input.ty.clone()?)))
+            .collect::<HashMap<String, TypeKind>>();
+        self.scopes.push(scope);
+        self.statement(&mut func.body);
+        self.scopes.pop();
+    }
+
+    fn expression(&mut self, expr: &mut Expression) {
+        match expr {
+            Expression::Call(call) => {
+                for arg in call.arguments.iter_mut() {
+                    self.expression(arg);
+                }
+                self.call(call);
+            }
+            Expression::MethodCall(call) => {
+                self.expression(&mut call.callee);
+                for arg in call.arguments.iter_mut() {
+                    self.expression(arg);
+                }
+            }
+            Expression::New(call) => {
+                for arg in call.arguments.iter_mut() {
+                    self.expression(arg);
+                }
+            }
+            Expression::Array(array) => {
+                for value in array.values.iter_mut() {
+                    self.expression(value);
+                }
+            }
+            Expression::Tuple(values) => {
+                for value in values.iter_mut() {
+                    self.expression(value);
+                }
+            }
+            Expression::Object(object) => {
+                for property in object.properties.iter_mut() {
+                    self.expression(&mut property.value);
+                }
+            }
+            Expression::Operation(op) => {
+                self.expression(&mut op.left);
+                self.expression(&mut op.right);
+            }
+            Expression::Await(inner) | Expression::Spread(inner) => self.expression(inner),
+            Expression::Is(test) => self.expression(&mut test.value),
+            Expression::Statement(stmt) => self.statement(stmt),
+            Expression::Member(member) => self.expression(&mut member.name),
+            Expression::Literal(_) | Expression::EndOfLine => {}
+        }
+    }
+
+    /// Redirects the call to a specialized copy when every erased argument has a known type
+    /// and every type parameter is bound.
+    fn call(&mut self, call: &mut Call) {
+        let generic = match self.generics.get(call.name.as_str()) {
+            Some(generic) if generic.inputs.len() == call.arguments.len() => generic.clone(),
+            _ => return,
+        };
+        let params = generic
+            .generics
+            .iter()
+            .filter_map(|g| g.name.clone())
+            .collect::<Vec<String>>();
+
+        // the type arguments given, eg: `first<int>(items)`, bind the parameters in order.
+        let mut bindings: HashMap<String, TypeKind> = HashMap::new();
+        if !call.generics.is_empty() {
+            if call.generics.len() != params.len() {
+                return;
+            }
+            bindings.extend(params.iter().cloned().zip(call.generics.iter().cloned()));
+        }
+
+        let mut specialized = generic.clone();
+        let mut erased: Vec<TypeKind> = Vec::new();
+        for (input, arg) in specialized.inputs.iter_mut().zip(call.arguments.iter()) {
+            let Some(ty) = &input.ty else {
+                continue;
+            };
+            let known = infer_expression(arg, &|name| self.lookup(name), &self.signatures)
+                .filter(|ty| !is_any(Some(ty)));
+            if is_any(Some(ty)) {
+                let Some(found) = known else {
+                    return;
+                };
+                input.ty = Some(found.clone());
+                erased.push(found);
+            } else if let Some(found) = known {
+                if !bind(ty, &found, &params, &mut bindings) {
+                    return;
+                }
+            }
+        }
+        if params.iter().any(|param| !bindings.contains_key(param)) {
+            return;
+        }
+
+        // the copy has no type parameters left, every use of them is the type they are bound to.
+        for input in specialized.inputs.iter_mut() {
+            input.ty = input.ty.as_ref().map(|ty| substitute(ty, &bindings));
+        }
+        specialized.outputs = specialized
+            .outputs
+            .as_ref()
+            .map(|ty| substitute(ty, &bindings));
+        specialized.generics.clear();
+        let types = params
+            .iter()
+            .map(|param| bindings[param].clone())
+            .chain(erased)
+            .collect::<Vec<TypeKind>>();
+
+        let key = format!(
+            "{}__{}",
+            call.name,
+            types.iter().map(mangle).collect::<Vec<String>>().join("_")
+        );
+        let name = match self.copies.get(&key) {
+            Some(name) => name.clone(),
+            None => {
+                let name = self.names.unique(&key);
+                self.copies.insert(key, name.clone());
+                specialized.name = Some(name.clone());
+                self.created.push((call.name.to_string(), specialized));
+                name
+            }
+        };
+        call.name = name.into();
+        call.generics.clear();
+    }
+}
+
+/// Binds the type parameters a parameter type names to the parts of an argument type,
+/// eg: `T` to `int` for `T[]` and `int[]`. Returns false when a parameter is bound twice
+/// to different types.
+fn bind(
+    ty: &TypeKind,
+    found: &TypeKind,
+    params: &[String],
+    bindings: &mut HashMap<String, TypeKind>,
+) -> bool {
+    match (ty, found) {
+        (TypeKind::Reference(reference), found) if params.contains(&reference.name) => {
+            match bindings.get(&reference.name) {
+                Some(bound) => bound.to_string() == found.to_string(),
+                None => {
+                    bindings.insert(reference.name.clone(), found.clone());
+                    true
+                }
+            }
+        }
+        (
+            TypeKind::BuiltIn(BuiltInType::Array(inner)),
+            TypeKind::BuiltIn(BuiltInType::Array(found)),
+        ) => bind(inner, found, params, bindings),
+        (
+            TypeKind::BuiltIn(BuiltInType::Map(key, value)),
+            TypeKind::BuiltIn(BuiltInType::Map(found_key, found_value)),
+        ) => bind(key, found_key, params, bindings) && bind(value, found_value, params, bindings),
+        (TypeKind::Nullable(inner), TypeKind::Nullable(found)) => {
+            bind(inner, found, params, bindings)
+        }
+        (TypeKind::Nullable(inner), found) => bind(inner, found, params, bindings),
+        (TypeKind::Tuple(types), TypeKind::Tuple(found)) if types.len() == found.len() => types
+            .iter()
+            .zip(found.iter())
+            .all(|(ty, found)| bind(ty, found, params, bindings)),
+        _ => true,
+    }
+}
+
+/// Replaces every type parameter with the type it is bound to.
+fn substitute(ty: &TypeKind, bindings: &HashMap<String, TypeKind>) -> TypeKind {
+    let sub = |ty: &TypeKind| substitute(ty, bindings);
+    match ty {
+        TypeKind::Reference(reference) if reference.params.is_none() => bindings
+            .get(&reference.name)
+            .cloned()
+            .unwrap_or_else(|| ty.clone()),
+        TypeKind::Union(union) => TypeKind::union(union.types.iter().map(sub).collect()),
+        TypeKind::BuiltIn(BuiltInType::Array(inner)) => {
+            TypeKind::BuiltIn(BuiltInType::Array(Box::new(sub(inner))))
+        }
+        TypeKind::BuiltIn(BuiltInType::Map(key, value)) => {
+            TypeKind::BuiltIn(BuiltInType::Map(Box::new(sub(key)), Box::new(sub(value))))
+        }
+        TypeKind::Nullable(inner) => TypeKind::nullable(sub(inner)),
+        TypeKind::Function { params, ret } => {
+            TypeKind::function(params.iter().map(sub).collect(), sub(ret))
+        }
+        TypeKind::Tuple(types) => TypeKind::Tuple(types.iter().map(sub).collect()),
+        TypeKind::Object(shape) => TypeKind::Object(ObjectShape::new(
+            shape
+                .properties
+                .iter()
+                .map(|p| ShapeProperty {
+                    name: p.name.clone(),
+                    ty: sub(&p.ty),
+                })
+                .collect(),
+        )),
+        other => other.clone(),
+    }
+}
+
+fn is_any(ty: Option<&TypeKind>) -> bool {
+    matches!(ty, Some(TypeKind::BuiltIn(BuiltInType::Any)))
+}
+
+/// Turns a type into something that can be part of a function name, eg: `int[]` -> `int_array`
+fn mangle(ty: &TypeKind) -> String {
+    ty.to_string()
+        .replace("[]", "_array")
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect()
+}
@@ -1,7 +1,27 @@
-use std::{collections::HashMap, fs::OpenOptions, io::Read, path::PathBuf};
+use std::{
+    collections::HashMap,
+    fmt,
+    ops::Range,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
 
-use crate::compiler::ast::AstBody;
+use crate::{
+    compiler::{
+        ast::{types::TypeStore, AstBody, NodeKind, Statement},
+        stdlib,
+        symbols::{Symbol, SymbolKind, SymbolTable},
+    },
+    report::diagnostic::Diagnostic,
+    util::{
+        provider::{FileLoader, OsProvider},
+        source::SourceBuffer,
+    },
+};
 
+/// Where a statement is being parsed, the parser keeps the flags it is in,
+/// see `AstGenerator::context_flag`.
 #[derive(Debug, Clone, PartialEq)]
 #[repr(u8)]
 pub enum ContextFlag {
@@ -12,67 +32,82 @@ pub enum ContextFlag {
     InIf,
     InElse,
     EndOfFile,
+    /// Outside of any class or function, eg: a statement of a namespace block.
+    TopLevel,
 }
 
 /// Contains a map to the actual source for any context.
-/// The contents of the source file are not kept in memory, but rather
-/// their locations.
+/// The contents of a source file are read the first time they're needed and kept
+/// until the file changes, see `FileLoader::modified`.
 /// Each map entry will contain a id for the given session.
 pub struct SourceMap {
     internal: HashMap<u64, SourceOrigin>,
     current: u64,
+    /// Reads the files of the origins added with `add_path`.
+    loader: Arc<dyn FileLoader>,
 }
 
 impl SourceMap {
-    /// Creates a new source map.
+    /// Creates a new source map that reads files from the disk.
     pub fn new() -> Self {
+        Self::with_loader(Arc::new(OsProvider))
+    }
+
+    /// A source map that reads files with the given loader, eg: from memory.
+    pub fn with_loader(loader: Arc<dyn FileLoader>) -> Self {
         Self {
             internal: HashMap::new(),
             current: 0,
+            loader,
         }
     }
 
+    /// Adds a source, returns whether it wasn't added before.
+    /// A virtual source is told apart by its name, a file by its path.
     pub fn add(&mut self, source: SourceOrigin) -> bool {
-        let search = match source.is_virtual() {
-            true => source.path.as_ref().unwrap().to_str().unwrap().to_string(),
-            false => source.name.to_string(),
-        };
-
-        if self.get_by_path_or_name(search).is_none() {
-            self.current += 1;
-            self.internal.insert(self.current, source.clone());
-            return true;
-        } else {
+        if self.find(&source).is_some() {
             return false;
         }
+        self.current += 1;
+        self.internal.insert(self.current, source);
+        true
+    }
+
+    /// Adds a file read with the loader of the map, returns the id of the file
+    /// whether it was added before or not.
+    pub fn add_path(&mut self, path: PathBuf) -> u64 {
+        let source = SourceOrigin::with_loader(path, self.loader.clone());
+        if let Some(id) = self.find(&source) {
+            return id;
+        }
+        self.add(source);
+        self.current
     }
 
     pub fn get(&self, session_id: u64) -> Option<&SourceOrigin> {
         self.internal.get(&session_id)
     }
 
-    fn get_by_path_or_name(&self, search: String) -> Option<&SourceOrigin> {
-        for (_id, origin) in self.internal.iter() {
-            if origin.is_virtual() {
-                // check the name
-                if origin.name.clone() == search {
-                    return Some(origin);
-                }
-            } else {
-                // check the path
-                if origin.path.as_ref().unwrap().to_str().unwrap().to_string() == search {
-                    return Some(origin);
-                }
-            }
-        }
+    /// The contents of a source, read again if the file changed since the last time.
+    pub fn contents(&self, session_id: u64) -> Option<String> {
+        self.get(session_id)?.get_contents()
+    }
 
-        return None;
+    fn find(&self, source: &SourceOrigin) -> Option<u64> {
+        self.internal
+            .iter()
+            .find(|(_, origin)| match source.is_virtual() {
+                true => origin.is_virtual() && origin.name == source.name,
+                false => !origin.is_virtual() && origin.path == source.path,
+            })
+            .map(|(id, _)| *id)
     }
 }
 
 /// A struct used to help identify the origin of a source.
 /// This is used to help return errors to the user.
-#[derive(Debug, Clone)]
+/// A clone shares the contents read so far with the origin it was cloned from.
+#[derive(Clone)]
 pub struct SourceOrigin {
     /// Optional, the path to the file. (if virtual, add a name to the source.)
     pub path: Option<PathBuf>,
@@ -82,22 +117,30 @@ pub struct SourceOrigin {
     contents: Option<String>,
     /// Whether or not the source is virtual.
     is_virtual: bool,
+    /// Reads the file of the path.
+    loader: Arc<dyn FileLoader>,
+    /// The contents of the file the last time it was read.
+    cache: Arc<Mutex<Option<Cached>>>,
 }
 
 impl SourceOrigin {
-    /// Creates a new origin with the given path.
+    /// Creates a new origin with the given path, the file is read from the disk.
     pub fn new(path: PathBuf) -> Self {
+        Self::with_loader(path, Arc::new(OsProvider))
+    }
+
+    /// Creates a new origin with the given path, the file is read with the loader.
+    pub fn with_loader(path: PathBuf, loader: Arc<dyn FileLoader>) -> Self {
         Self {
-            path: Some(path.clone()),
             name: path
-                .clone()
                 .file_name()
-                .unwrap()
-                .to_str()
-                .unwrap()
-                .to_string(),
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.display().to_string()),
+            path: Some(path),
             contents: None,
             is_virtual: false,
+            loader,
+            cache: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -108,28 +151,34 @@ impl SourceOrigin {
             name,
             contents: Some(contents),
             is_virtual: true,
+            loader: Arc::new(OsProvider),
+            cache: Arc::new(Mutex::new(None)),
         }
     }
 
+    /// The contents of the source, a file is only read again once it changed.
+    /// `None` when the file can't be read.
     pub fn get_contents(&self) -> Option<String> {
         if self.is_virtual {
-            let string = self.contents.as_ref().unwrap();
-            Some(string.clone())
-        } else {
-            // read the file.
-            // the path is assumed to be absolute.
-            let file = OpenOptions::new()
-                .read(true)
-                .open(self.path.as_ref().unwrap());
-
-            if let Ok(mut f) = file {
-                let mut string = String::new();
-                f.read_to_string(&mut string).unwrap();
-                return Some(string);
-            }
-
-            return None;
+            return self.contents.clone();
         }
+        let path = self.path.as_ref()?;
+        let modified = self.loader.modified(path);
+        let mut cache = self.cache.lock().unwrap();
+        if let Some(cached) = cache.as_ref().filter(|c| c.modified == modified) {
+            return Some(cached.contents.clone());
+        }
+        let contents = self.loader.load(path).ok()?;
+        *cache = Some(Cached {
+            contents: contents.clone(),
+            modified,
+        });
+        Some(contents)
+    }
+
+    /// Drops the contents read so far, the file is read again the next time.
+    pub fn invalidate(&self) {
+        *self.cache.lock().unwrap() = None;
     }
 
     pub fn is_virtual(&self) -> bool {
@@ -137,34 +186,82 @@ impl SourceOrigin {
     }
 }
 
+/// The contents of a source the last time it was read.
+#[derive(Clone)]
+struct Cached {
+    contents: String,
+    modified: Option<SystemTime>,
+}
+
+impl fmt::Debug for Cached {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Cached")
+            .field("len", &self.contents.len())
+            .field("modified", &self.modified)
+            .finish()
+    }
+}
+
+impl fmt::Debug for SourceOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SourceOrigin")
+            .field("path", &self.path)
+            .field("name", &self.name)
+            .field("contents", &self.contents)
+            .field("is_virtual", &self.is_virtual)
+            .field("cache", &self.cache)
+            .finish()
+    }
+}
+
 /// A context store is all contexts for the current run.
 /// This **will** include standard libraries and user defined libraries.
 /// This is used to resolve symbols.
+///
+/// The contexts are shared, so a clone of the store is cheap and can be read from
+/// other threads while scripts are parsed, see `Parser::parse_scripts`.
+///
+/// A context is given its id with `reserve` before its source is parsed, added with
+/// `insert` once it is, and given its body and symbols with `set_body` when the source parsed.
+/// Contexts that are no longer needed, eg: a script that was parsed again, are dropped
+/// with `remove_context` or `remove_replaced`.
+#[derive(Clone)]
 pub struct ContextStore {
-    contexts: HashMap<u64, Context>,
+    contexts: HashMap<u64, Arc<Context>>,
     /// Ids that provide the location of the global variables.
     globals: Vec<u64>,
+    /// The ids of the contexts that declare a name at their top level, in order,
+    /// see `lookup_global`.
+    declarations: HashMap<String, Vec<u64>>,
     id: u64,
 }
 
 impl ContextStore {
+    /// A store with the standard library registered, see `stdlib`.
     pub fn new() -> Self {
-        Self {
+        let mut store = Self {
             contexts: HashMap::new(),
             globals: Vec::new(),
+            declarations: HashMap::new(),
             id: 0,
-        }
+        };
+        let mut stdlib = stdlib::context();
+        store.add_context(&mut stdlib);
+        store.globals.push(stdlib.origin);
+        store
     }
 
     pub fn add_context(&mut self, context: &mut Context) {
         self.id += 1;
         context.origin = self.id;
-        self.contexts.insert(self.id, context.clone());
+        self.contexts.insert(self.id, Arc::new(context.clone()));
+        self.declare_globals(self.id);
     }
 
     pub fn new_context(&mut self, source: SourceOrigin) -> &Context {
         self.id += 1;
-        self.contexts.insert(self.id, Context::new(source, self.id));
+        self.contexts
+            .insert(self.id, Arc::new(Context::new(source, self.id)));
         return self.contexts.get(&self.id).unwrap();
     }
 
@@ -172,31 +269,277 @@ impl ContextStore {
         self.id + 1
     }
 
+    /// Ids for contexts that are made elsewhere, eg: on another thread, and added with `insert`.
+    /// The ids are given in order, so they don't depend on which context is made first.
+    pub fn reserve(&mut self, count: usize) -> Range<u64> {
+        let start = self.id + 1;
+        self.id += count as u64;
+        start..self.id + 1
+    }
+
+    /// Adds a context with the id it was made with, see `reserve`.
+    pub fn insert(&mut self, context: Context) {
+        debug_assert!(
+            context.origin <= self.id,
+            "the id of the context is reserved"
+        );
+        let id = context.origin;
+        self.contexts.insert(id, Arc::new(context));
+        self.forget_globals(id);
+        self.declare_globals(id);
+    }
+
     pub fn get_globals(&self) -> &Vec<u64> {
         &self.globals
     }
+
+    pub fn get(&self, id: u64) -> Option<&Context> {
+        self.contexts.get(&id).map(Arc::as_ref)
+    }
+
+    /// Every context, in the order of their ids.
+    pub fn contexts(&self) -> Vec<&Context> {
+        let mut contexts: Vec<&Context> = self.contexts.values().map(Arc::as_ref).collect();
+        contexts.sort_by_key(|context| context.origin);
+        contexts
+    }
+
+    /// A symbol declared at the top level of a global context, eg: `println`,
+    /// along with the context it is declared in.
+    pub fn resolve_global(&self, name: &str) -> Option<(&Context, &Symbol)> {
+        self.globals
+            .iter()
+            .filter_map(|id| self.get(*id))
+            .find_map(|context| {
+                let symbols = &context.symbols.global().symbols;
+                symbols
+                    .iter()
+                    .find(|s| s.name == name)
+                    .map(|symbol| (context, symbol))
+            })
+    }
+
+    /// A variable, constant or function declared at the top level of any context,
+    /// outside of a namespace, along with the context it is declared in.
+    /// The global contexts are looked in first, then the others in the order they were added.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::compiler::{parser::Parser, CompilerOptions};
+    /// # let mut parser = Parser::new(CompilerOptions::default());
+    /// // config.surn: const DEBUG = true;
+    /// # parser.try_parse_script("config.surn".to_string(), "const DEBUG = true;".to_string())?;
+    /// # let store = parser.contexts();
+    /// let (context, symbol) = store.lookup_global("DEBUG").unwrap();
+    /// &context.source.name; // config.surn
+    /// # Ok::<(), surn::report::Report>(())
+    /// ```
+    pub fn lookup_global(&self, name: &str) -> Option<(&Context, &Symbol)> {
+        let ids = self.declarations.get(name)?;
+        let globals = ids.iter().filter(|id| self.globals.contains(id));
+        let others = ids.iter().filter(|id| !self.globals.contains(id));
+        globals
+            .chain(others)
+            .filter_map(|id| self.get(*id))
+            .find_map(|context| {
+                context
+                    .symbols
+                    .global()
+                    .symbols
+                    .iter()
+                    .find(|s| s.name == name && is_global_kind(s.kind))
+                    .map(|symbol| (context, symbol))
+            })
+    }
+
+    /// The context of a source, by the name it was parsed with.
+    pub fn find(&self, name: &str) -> Option<&Context> {
+        self.contexts
+            .values()
+            .map(Arc::as_ref)
+            .find(|c| c.source.name == name)
+    }
+
+    /// Drops a context from the store, it is no longer a global context either.
+    /// The context is returned, its id isn't given out again.
+    pub fn remove_context(&mut self, id: u64) -> Option<Context> {
+        let context = self.contexts.remove(&id)?;
+        self.globals.retain(|global| *global != id);
+        self.forget_globals(id);
+        Some(Arc::unwrap_or_clone(context))
+    }
+
+    /// Drops the virtual contexts that have the name of a newer one, eg: a script that was
+    /// parsed again, so `find` gives the newest. Global contexts are kept.
+    /// Returns the ids of the contexts that were dropped.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::compiler::parser::context::{Context, ContextStore, SourceOrigin};
+    /// # let mut store = ContextStore::new();
+    /// # let repl = |code: &str| SourceOrigin::new_virtual("repl".to_string(), code.to_string());
+    /// # let first_repl = Context::new(repl("var x = 1;"), 1);
+    /// # let second_repl = Context::new(repl("var x = 2;"), 2);
+    /// store.insert(first_repl);
+    /// store.insert(second_repl);
+    /// store.remove_replaced(); // [the id of `first_repl`]
+    /// ```
+    pub fn remove_replaced(&mut self) -> Vec<u64> {
+        let mut newest: HashMap<&str, u64> = HashMap::new();
+        for context in self.contexts.values() {
+            let id = newest.entry(context.source.name.as_str()).or_default();
+            *id = (*id).max(context.origin);
+        }
+        let mut replaced: Vec<u64> = self
+            .contexts
+            .values()
+            .filter(|context| context.source.is_virtual())
+            .filter(|context| !self.globals.contains(&context.origin))
+            .filter(|context| newest[context.source.name.as_str()] != context.origin)
+            .map(|context| context.origin)
+            .collect();
+        replaced.sort();
+        for id in replaced.iter() {
+            self.contexts.remove(id);
+            self.forget_globals(*id);
+        }
+        replaced
+    }
+
+    /// Keeps the parsed body of a context along with its symbols and types.
+    pub(crate) fn set_body(&mut self, id: u64, body: AstBody) {
+        if let Some(context) = self.contexts.get_mut(&id).map(Arc::make_mut) {
+            context.symbols = SymbolTable::build(&body);
+            context.types = TypeStore::build(&body).0;
+            context.body = body;
+        }
+        self.forget_globals(id);
+        self.declare_globals(id);
+    }
+
+    /// Keeps the names a context declares at its top level, see `lookup_global`.
+    fn declare_globals(&mut self, id: u64) {
+        let Some(context) = self.get(id) else {
+            return;
+        };
+        for name in global_names(&context.body) {
+            let ids = self.declarations.entry(name).or_default();
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    }
+
+    fn forget_globals(&mut self, id: u64) {
+        self.declarations.retain(|_, ids| {
+            ids.retain(|declared| *declared != id);
+            !ids.is_empty()
+        });
+    }
+}
+
+/// Whether a kind of symbol can be looked up with `ContextStore::lookup_global`.
+fn is_global_kind(kind: SymbolKind) -> bool {
+    matches!(
+        kind,
+        SymbolKind::Variable | SymbolKind::Constant | SymbolKind::Function
+    )
+}
+
+/// The names of the variables, constants and functions declared at the top level of a body,
+/// a declaration in a namespace isn't global, nor is one after a `namespace` statement.
+fn global_names(body: &AstBody) -> Vec<String> {
+    fn names(stmt: &Statement, found: &mut Vec<String>) {
+        match stmt {
+            Statement::Var(var) | Statement::Const(var) => found.push(var.name.to_string()),
+            Statement::Function(func) => found.extend(func.name.clone()),
+            Statement::Static(s) => names(&s.statement, found),
+            _ => {}
+        }
+    }
+    let mut found = Vec::new();
+    for node in body.get_program() {
+        match &node.inner {
+            NodeKind::Statement(Statement::Namespace(namespace)) if namespace.body.is_none() => {
+                break
+            }
+            NodeKind::Statement(stmt) => names(stmt, &mut found),
+            _ => {}
+        }
+    }
+    found
 }
 
 #[derive(Debug, Clone)]
 pub struct Context {
     pub source: SourceOrigin,
+    /// The contents of the source, read once and shared by every report about it.
+    pub buffer: Arc<SourceBuffer>,
     pub body: AstBody,
+    /// The symbols declared in the body, these are kept once the body is parsed.
+    pub symbols: SymbolTable,
+    /// The types defined in the body, the first definition of a name is kept.
+    pub types: TypeStore,
     pub(crate) origin: u64,
     local_id: u64,
+    /// The diagnostics the passes over the source found, see `Context::push_diagnostic`.
+    diagnostics: Vec<Diagnostic>,
 }
 
 impl Context {
     pub fn new(source: SourceOrigin, id: u64) -> Self {
+        let buffer = Arc::new(SourceBuffer::new(source.get_contents().unwrap_or_default()));
+        Self::with_buffer(source, buffer, id)
+    }
+
+    /// A context for a source that was already read, eg: the arms of a `target!`.
+    pub fn with_buffer(source: SourceOrigin, buffer: Arc<SourceBuffer>, id: u64) -> Self {
         Self {
             source,
-            body: AstBody::new(),
+            buffer,
+            body: AstBody::for_context(id),
+            symbols: SymbolTable::new(),
+            types: TypeStore::new(),
             origin: id,
             local_id: 0,
+            diagnostics: Vec::new(),
         }
     }
 
+    /// The id of the context in its store, see `ContextStore::get`.
+    pub fn id(&self) -> u64 {
+        self.origin
+    }
+
     pub fn get_next_local_id(&mut self) -> u64 {
         self.local_id += 1;
         return self.local_id;
     }
+
+    /// Keeps a warning or notice any pass found, they are reported once the source is parsed.
+    /// An error doesn't stop the pass that found it, the parse fails once it is done.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::compiler::parser::context::{Context, SourceOrigin};
+    /// # use surn::report::diagnostic::Diagnostic;
+    /// # let mut context = Context::new(
+    /// #     SourceOrigin::new_virtual("main.surn".to_string(), "fn f(x: int) { var x = 1; }".to_string()),
+    /// #     1,
+    /// # );
+    /// context.push_diagnostic(Diagnostic::warning("`x` shadows a parameter.".to_string()));
+    /// ```
+    pub fn push_diagnostic(&mut self, diagnostic: Diagnostic) {
+        self.diagnostics.push(diagnostic);
+    }
+
+    /// The diagnostics pushed so far, in the order they were pushed.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Takes the diagnostics pushed so far, leaving none behind.
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
 }
@@ -0,0 +1,120 @@
+//! Machine-applicable fixes for the reports of the parser.
+//! They are worked out from the code of a report and the tokens around it,
+//! so the rules that raise the reports don't have to.
+
+use crate::{
+    compiler::lexer::{keyword::KeyWord, token::Token},
+    report::{codes, Report, Suggestion},
+};
+
+/// Adds the fixes that apply to a parse report.
+/// - a statement that starts with a misspelled keyword gets the keyword, eg: `retrun 1;`.
+/// - otherwise a missing `;` or `,` is inserted after the token before the report.
+pub fn suggest(report: Report, tokens: &[Token]) -> Report {
+    let Some(at) = report.snippets.first().map(|s| s.range().start) else {
+        return report;
+    };
+    let tokens: Vec<&Token> = tokens
+        .iter()
+        .filter(|t| !t.kind().is_whitespace() && !t.kind().is_comment())
+        .collect();
+    let before = tokens.iter().rposition(|t| t.range().start < at);
+
+    // the first token of the statement the report is in.
+    let start = tokens[..before.map_or(0, |b| b + 1)]
+        .iter()
+        .rposition(|t| {
+            t.kind().is_statement_end() || t.kind().is_left_brace() || t.kind().is_right_brace()
+        })
+        .map_or(0, |end| end + 1);
+    // a misspelled keyword reads as a name, which is missing the `;` of an expression statement.
+    if let Some((token, keyword)) = misspelled_keyword(&tokens[start..]) {
+        return report.add_suggestion(Suggestion::new(
+            token.range(),
+            keyword.clone(),
+            format!("Did you mean `{}`?", keyword),
+        ));
+    }
+
+    let missing = match report.code {
+        codes::MISSING_SEMICOLON => Some((";", "Add a `;` to end the statement.")),
+        codes::MISSING_COMMA => Some((",", "Add a `,` to separate the items.")),
+        _ => None,
+    };
+    match (missing, before) {
+        (Some((text, message)), Some(before)) => {
+            let offset = tokens[before].range().end;
+            report.add_suggestion(Suggestion::insert(offset, text, message.to_string()))
+        }
+        _ => report,
+    }
+}
+
+/// The keyword an identifier at the start of a statement is a typo of.
+/// Only identifiers followed by another name or a value are checked, those can't
+/// start an expression statement.
+pub fn misspelled_keyword<'a>(statement: &[&'a Token]) -> Option<(&'a Token, String)> {
+    let (first, next) = (statement.first()?, statement.get(1)?);
+    let follows = next.kind().is_identifier()
+        || next.kind().is_keyword()
+        || next.kind().is_number()
+        || next.kind().is_string();
+    if !first.kind().is_identifier() || !follows {
+        return None;
+    }
+    closest_keyword(first.text()?).map(|keyword| (*first, keyword))
+}
+
+/// Words other languages use for a keyword, eg: `function` for `fn`.
+const FOREIGN_KEYWORDS: &[(&str, KeyWord)] = &[("function", KeyWord::Function)];
+
+/// The keyword a name is most likely a typo of, one edit away for short keywords
+/// and two for keywords of 6 characters or more. Keywords shorter than 4 characters
+/// are left out, most short names are a typo of one of them.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::parser::fix_its::closest_keyword;
+/// closest_keyword("clas"); // Some("class")
+/// closest_keyword("fuction"); // Some("fn"), it is `function` in php.
+/// ```
+pub fn closest_keyword(name: &str) -> Option<String> {
+    let keywords = KeyWord::ALL.iter().map(|k| (k.to_string(), k));
+    let foreign = FOREIGN_KEYWORDS
+        .iter()
+        .map(|(word, k)| (word.to_string(), k));
+    keywords
+        .chain(foreign)
+        .filter(|(word, _)| word.len() >= 4)
+        .map(|(word, keyword)| (edit_distance(name, &word), word, keyword.to_string()))
+        .filter(|(distance, word, keyword)| {
+            *distance <= if word.len() < 6 { 1 } else { 2 } && keyword != name
+        })
+        .min_by_key(|(distance, _, _)| *distance)
+        .map(|(_, _, keyword)| keyword)
+}
+
+/// The edits needed to turn one word into the other, swapping two neighbouring
+/// characters counts as a single edit.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    rows[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
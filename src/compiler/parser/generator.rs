@@ -1,58 +1,150 @@
 // Home of the Surn Parser.
-use std::{ops::Range, process};
+use std::ops::Range;
 
 use crate::compiler::{
     ast::{
-        ops::AnyOperation, Array, AstBody, Call, Class, ClassAllowedStatement, ClassBody,
-        ClassProperty, Expression, Function, FunctionInput, Literal, MemberListNode, MemberLookup,
-        Namespace, NewCall, Object, ObjectProperty, Operation, Path, Return, Statement, Static,
-        Variable, Visibility,
+        ops::AnyOperation, Accessor, AccessorKind, Array, AstBody, Attribute, Call, Catch, Class,
+        ClassAllowedStatement, ClassBody, ClassProperty, CompilerMacro, Destructure, Expression,
+        Function, FunctionInput, If, Literal, MemberListNode, MemberLookup, Namespace, NewCall,
+        Object, ObjectProperty, Operation, Path, Purity, Return, Statement, Static, TargetArm, Try,
+        TypeTest, Variable, Visibility, KNOWN_MACROS,
     },
     ast::{
-        types::{BuiltInType, TypeDefinition, TypeKind, TypeParam, TypeReference, TypeUnion},
+        types::{
+            BuiltInType, ObjectShape, RuntimeType, ShapeProperty, TypeDefinition, TypeKind,
+            TypeParam, TypeReference,
+        },
         Node,
     },
     lexer::{
+        escape::unescape,
         keyword::KeyWord,
+        pos::{LineIndex, Position},
         token::{Token, TokenType},
+        tokenizer::tokenize,
+        trivia::Skipped,
     },
+    lints::{LintLevel, LintScope},
 };
 
-use super::context::{Context, SourceOrigin};
-use crate::report::Report;
-use crate::util::{source::SourceBuffer, StreamBuffer, TokenStream};
+use super::{
+    context::{Context, ContextFlag, SourceOrigin},
+    trace::{Outcome, ParseTrace},
+};
+use crate::report::{codes, Report};
+use crate::util::{StreamBuffer, TokenStream};
 
+/// Creates a parser report at the given location and returns it as an error
+/// from the current parse function.
 macro_rules! create_report {
-    ($ctx: expr, $location: expr, $message: expr) => {
-        Report::new()
-            .set_source(SourceBuffer::new(
-                $ctx.source.clone().get_contents().unwrap(),
-            ))
+    ($ctx: expr, $code: expr, $location: expr, $message: expr) => {
+        return Err(Report::new()
+            .set_code($code)
+            .set_source($ctx.buffer.clone())
             .set_name($ctx.source.clone().name)
             .set_message("Occurred while parsing".to_string())
-            .make_snippet($location, $message, None)
-            .print();
-        dbg!("At line.");
-        process::exit(1);
+            .make_snippet($location, $message, None))
     };
-    ($ctx: expr, $location: expr, $message: expr, $inline: expr) => {
-        Report::new()
-            .set_source(SourceBuffer::new(
-                $ctx.source.clone().get_contents().unwrap(),
-            ))
+    ($ctx: expr, $code: expr, $location: expr, $message: expr, $inline: expr) => {
+        return Err(Report::new()
+            .set_code($code)
+            .set_source($ctx.buffer.clone())
             .set_name($ctx.source.clone().name)
             .set_message("Occurred while parsing".to_string())
-            .make_snippet($location, $message, Some($inline))
-            .print();
-        dbg!("At line.");
-        process::exit(1);
+            .make_snippet($location, $message, Some($inline)))
+    };
+}
+
+/// Reports that something else was expected at the next token, or that the source
+/// ended before it, see `AstGenerator::unexpected_eof`.
+/// A missing `;` or `,` is reported as missing, its fix-it applies at the end too.
+macro_rules! expected {
+    ($gen: expr, $code: expr, $message: expr $(, $inline: expr)?) => {
+        if $gen.at_eof() && !matches!($code, codes::MISSING_SEMICOLON | codes::MISSING_COMMA) {
+            let message: String = $message;
+            return Err($gen.unexpected_eof(&message));
+        } else {
+            create_report!($gen.context, $code, $gen.current_range(), $message $(, $inline)?)
+        }
+    };
+}
+
+/// Declares a `parse_*` function for every rule that calls the rule through
+/// `AstGenerator::traced`, so its entry and exit are recorded in the parse trace.
+macro_rules! traced_rules {
+    ($($parse: ident => $rule: ident($($arg: ident: $ty: ty),*): $out: ty;)*) => {
+        impl AstGenerator {
+            $(fn $parse(&mut self, $($arg: $ty),*) -> ParseResult<$out> {
+                let name = stringify!($parse).trim_start_matches("parse_");
+                self.traced(name, |generator| generator.$rule($($arg),*))
+            })*
+        }
     };
 }
 
+/// The result of a parse function.
+/// - `Ok(Some(_))` the rule matched.
+/// - `Ok(None)` the rule did not match, and nothing was reported.
+/// - `Err(_)` the rule matched, but the source is invalid.
+pub type ParseResult<T> = Result<Option<T>, Report>;
+
+const STATIC_IN_FUNCTION: &str = "`static` can only be declared in a class or at the top level.";
+
 pub struct AstGenerator {
     pub(crate) body: AstBody,
     pub(crate) tokens: TokenStream,
     pub(crate) context: Context,
+    /// The rules entered and exited so far, when tracing is enabled.
+    pub(crate) trace: Option<ParseTrace>,
+    /// The trivia skipped last, see `skip_trivia`.
+    skipped: Skipped,
+    /// The classes and functions the parser is in, the innermost last.
+    flags: Vec<ContextFlag>,
+}
+
+traced_rules! {
+    parse_statement => statement_rule(): Statement;
+    parse_namespace => namespace_rule(): Namespace;
+    parse_type_definition => type_definition_rule(): TypeDefinition;
+    parse_import => import_rule(): Path;
+    parse_macro_invocation => macro_invocation_rule(): CompilerMacro;
+    parse_static => static_rule(): Statement;
+    parse_destructure => destructure_rule(): Destructure;
+    parse_variable => variable_rule(): (Variable, bool);
+    parse_function => function_rule(): Function;
+    parse_function_inputs => function_inputs_rule(): (Vec<FunctionInput>, Option<TypeKind>);
+    parse_class => class_rule(): Class;
+    parse_class_extension => class_extension_rule(): String;
+    parse_class_implementation => class_implementation_rule(): Vec<String>;
+    parse_class_property => class_property_rule(visibility: Visibility): ClassProperty;
+    parse_class_allowed_statement => class_allowed_statement_rule(): ClassAllowedStatement;
+    parse_class_constant => class_constant_rule(visibility: Option<Visibility>): Variable;
+    parse_accessor => accessor_rule(): Accessor;
+    parse_class_body => class_body_rule(): ClassBody;
+    parse_if => if_rule(): If;
+    parse_try => try_rule(): Try;
+    parse_catch => catch_rule(): Catch;
+    parse_throw => throw_rule(): Expression;
+    parse_return => return_rule(): Return;
+    parse_block => block_rule(): Vec<Expression>;
+    parse_visibility => visibility_rule(): Visibility;
+    parse_type_kind => type_kind_rule(): TypeKind;
+    parse_single_type => single_type_rule(): TypeKind;
+    parse_parenthesized_type => parenthesized_type_rule(): TypeKind;
+    parse_object_type => object_type_rule(): TypeKind;
+    parse_type_generics => type_generics_rule(): Vec<TypeParam>;
+    parse_expression => expression_rule(): Expression;
+    parse_operand => operand_rule(): Expression;
+    parse_call_expression => call_expression_rule(): Call;
+    parse_member_expression => member_expression_rule(): MemberListNode;
+    parse_new_expression => new_expression_rule(): NewCall;
+    parse_tuple_expression => tuple_expression_rule(): Vec<Expression>;
+    parse_array_expression => array_expression_rule(): Array;
+    parse_object_expression => object_expression_rule(): Object;
+    parse_literal_expression => literal_expression_rule(): Literal;
+    parse_function_call_inputs => function_call_inputs_rule(): Vec<Expression>;
+    parse_call_arguments => call_arguments_rule(): Vec<Expression>;
+    parse_argument => argument_rule(): Expression;
 }
 
 /// Parses the given token stream into an AST.
@@ -60,114 +152,224 @@ pub struct AstGenerator {
 /// AST is **not** optimized during this stage, however it is validated.
 impl AstGenerator {
     pub fn new(source: SourceOrigin, id: u64) -> Self {
+        Self::with_context(Context::new(source, id))
+    }
+
+    /// A generator for a part of the source of this one, sharing its buffer,
+    /// in the same classes and functions.
+    fn nested(&self) -> Self {
+        let mut nested = Self::with_context(Context::with_buffer(
+            self.context.source.clone(),
+            self.context.buffer.clone(),
+            self.context.origin,
+        ));
+        nested.flags = self.flags.clone();
+        nested
+    }
+
+    fn with_context(context: Context) -> Self {
         AstGenerator {
-            body: AstBody::new(),
+            body: AstBody::for_context(context.origin),
             tokens: TokenStream::new(Vec::new()),
-            context: Context::new(source, id),
+            context,
+            trace: None,
+            skipped: Skipped::default(),
+            flags: Vec::new(),
         }
     }
 
-    pub fn begin_parse(&mut self, tokens: TokenStream) -> AstBody {
+    /// Parses the given tokens, stopping at the first error.
+    pub fn begin_parse(&mut self, tokens: TokenStream) -> Result<AstBody, Report> {
         self.tokens = tokens;
 
         while !self.tokens.is_eof() {
             self.skip_whitespace();
-            self.parse();
+            self.parse()?;
         }
 
-        return self.body.clone();
+        Ok(self.body.clone())
     }
 
-    fn parse(&mut self) {
+    fn parse(&mut self) -> Result<(), Report> {
         // attempt to parse a statement
-        let start = {
+        let (start, position) = {
             if let Some(token) = self.tokens.first() {
-                token.range()
+                (token.range(), token.position())
             } else {
-                Range { start: 0, end: 0 }
+                (Range { start: 0, end: 0 }, Position::default())
             }
         };
 
-        if let Some(stmt) = self.parse_statement() {
-            self.body.push_node(Node::new(
-                stmt.into(),
-                start,
-                self.tokens.prev().unwrap().range(),
-            ));
-            return;
+        if let Some(stmt) = self.parse_statement()? {
+            self.body.push_node(
+                Node::new(stmt.into(), start, self.tokens.prev().unwrap().range()).at(position),
+            );
+            return Ok(());
         }
 
-        if let Some(left) = self.parse_expression() {
-            self.body.push_node(Node::new(
-                left.into(),
-                start,
-                self.tokens.prev().unwrap().range(),
-            ));
-            return;
+        if let Some(left) = self.parse_expression()? {
+            self.expression_statement_end(&left)?;
+            self.body.push_node(
+                Node::new(left.into(), start, self.tokens.prev().unwrap().range()).at(position),
+            );
+            return Ok(());
         }
 
         if self
             .tokens
             .first()
-            .unwrap_or(Token(TokenType::Whitespace, 0..1, None))
+            .unwrap_or(Token(
+                TokenType::Whitespace,
+                0..1,
+                None,
+                Position::default(),
+            ))
             .kind()
             .is_whitespace()
         {
             self.tokens.peek();
-            return;
+            return Ok(());
         }
 
         // we don't know what this is!
         // the only body we can have is a statement or an expression
-        create_report!(
-            self.context,
-            self.tokens.first().unwrap().range(),
+        expected!(
+            self,
+            codes::UNEXPECTED_TOKEN,
             "Unable to proceed parsing. This token was unexpected at this time.".to_string(),
-            format!(
-                "Unexpected token: {}",
-                self.tokens.peek().unwrap().kind().to_string()
-            )
+            format!("Unexpected token: {}", self.current_kind())
         );
     }
 
     /// A statement can be a variable declaration, function declaration, class declaration, etc.
-    fn parse_statement(&mut self) -> Option<Statement> {
-        if let Some(namespace) = self.parse_namespace() {
-            return Some(Statement::Namespace(namespace));
+    fn statement_rule(&mut self) -> ParseResult<Statement> {
+        if let Some(invocation) = self.parse_macro_invocation()? {
+            return Ok(Some(Statement::MacroInvocation(invocation)));
+        }
+
+        let start = self.tokens.first().map_or(0, |t| t.range().start);
+        let (levels, attributes): (Vec<Attribute>, Vec<Attribute>) = self
+            .parse_attributes()?
+            .into_iter()
+            .partition(|a| LintLevel::from_name(&a.name).is_some());
+        if !levels.is_empty() {
+            // the lint attributes apply to whole statement, the other attributes included.
+            let statement = match attributes.is_empty() {
+                true => self.statement_rule()?,
+                false => Some(Statement::Class(self.attributed_class(attributes)?)),
+            };
+            let Some(statement) = statement else {
+                create_report!(
+                    self.context,
+                    codes::EXPECTED_STATEMENT,
+                    self.tokens.first().map_or(0..0, |t| t.range()),
+                    "Lint attributes can only be placed on statements.".to_string(),
+                    "Expected a statement here.".to_string()
+                );
+            };
+            let end = self.tokens.prev().map_or(start, |t| t.range().end);
+            for attribute in levels {
+                let level = LintLevel::from_name(&attribute.name).unwrap();
+                for lint in attribute.arguments {
+                    self.body.push_lint_scope(LintScope {
+                        lint,
+                        level,
+                        span: start..end,
+                    });
+                }
+            }
+            return Ok(Some(statement));
+        }
+        if !attributes.is_empty() {
+            return Ok(Some(Statement::Class(self.attributed_class(attributes)?)));
+        }
+
+        if let Some(path) = self.parse_import()? {
+            return Ok(Some(Statement::Import(path)));
+        }
+
+        if let Some(namespace) = self.parse_namespace()? {
+            return Ok(Some(Statement::Namespace(namespace)));
+        }
+
+        if let Some(definition) = self.parse_type_definition()? {
+            return Ok(Some(Statement::TypeDef(definition)));
         }
 
         // Try to parse a static statement (this is obsolete in global context, but can exist)
-        // this is transpiled to a GLOBALS class.
-        if let Some(stmt) = self.parse_static() {
-            return Some(stmt);
+        // this is transpiled to a GLOBALS class, see `lower::globals`.
+        if let Some(stmt) = self.parse_static()? {
+            return Ok(Some(stmt));
+        }
+
+        if let Some(destructure) = self.try_parse(|generator| generator.parse_destructure())? {
+            return Ok(Some(Statement::Destructure(destructure)));
         }
 
         // try to parse a mutable or constant variable.
-        if let Some((var, constant)) = self.parse_variable() {
+        if let Some((var, constant)) = self.parse_variable()? {
             if constant {
-                return Some(Statement::Const(var));
+                return Ok(Some(Statement::Const(var)));
             } else {
-                return Some(Statement::Var(var));
+                return Ok(Some(Statement::Var(var)));
             }
         }
 
         // try to parse a function declaration
-        if let Some(func) = self.parse_function() {
-            return Some(Statement::Function(func));
+        if let Some(func) = self.parse_function()? {
+            return Ok(Some(Statement::Function(func)));
+        }
+
+        if let Some(class) = self.parse_class()? {
+            return Ok(Some(Statement::Class(class)));
+        }
+
+        if let Some(i) = self.parse_if()? {
+            return Ok(Some(Statement::If(i)));
+        }
+
+        if let Some(t) = self.parse_try()? {
+            return Ok(Some(Statement::Try(t)));
+        }
+
+        if let Some(expr) = self.parse_throw()? {
+            return Ok(Some(Statement::Throw(expr)));
+        }
+
+        if let Some(ret) = self.parse_return()? {
+            return Ok(Some(Statement::Return(ret)));
         }
 
-        if let Some(class) = self.parse_class() {
-            return Some(Statement::Class(class));
+        // there are no loops yet, so `break` and `continue` are never in one.
+        if let Some(keyword) = self.tokens.first_if(|t| {
+            t.kind().is_keyword()
+                && matches!(t.kind().as_keyword(), KeyWord::Break | KeyWord::Continue)
+        }) {
+            if !self.flags.contains(&ContextFlag::InLoop) {
+                return self.misplaced(
+                    &keyword,
+                    format!(
+                        "`{}` can only be used in a loop.",
+                        keyword.kind().as_keyword().to_string()
+                    ),
+                );
+            }
         }
 
-        return None;
+        Ok(None)
     }
 
-    fn parse_namespace(&mut self) -> Option<Namespace> {
-        if let Some(_) = self
+    fn namespace_rule(&mut self) -> ParseResult<Namespace> {
+        if let Some(keyword) = self
             .tokens
             .peek_if(|t| t.kind().is_keyword() && (t.kind().as_keyword() == KeyWord::Namespace))
         {
+            if self.context_flag() != ContextFlag::TopLevel {
+                return self.misplaced(
+                    &keyword,
+                    "A namespace can only be declared at the top level.".to_string(),
+                );
+            }
             let mut path: Vec<String> = Vec::new();
             self.skip_whitespace();
             if let Some(name) = self.tokens.peek_if(|t| t.kind().is_identifier()) {
@@ -178,9 +380,9 @@ impl AstGenerator {
                         if let Some(ident) = self.tokens.peek_if(|t| t.kind().is_identifier()) {
                             path.push(ident.value().unwrap());
                         } else {
-                            create_report!(
-                                self.context,
-                                self.tokens.first().unwrap().range(),
+                            expected!(
+                                self,
+                                codes::EXPECTED_NAME,
                                 "Expected identifier after backslash.".to_string()
                             );
                         }
@@ -189,57 +391,416 @@ impl AstGenerator {
                         .find_after(|t| t.kind().is_left_brace(), |t| t.kind().is_whitespace())
                     {
                         self.tokens.peek_inc(amt);
-                        if let Some(block) = self.parse_block() {
+                        if let Some(block) = self.parse_block()? {
                             if let Some(_) = self.tokens.peek_if(|t| t.kind().is_statement_end()) {
-                                return Some(Namespace {
+                                return Ok(Some(Namespace {
                                     path: Path::from(name.value().unwrap(), path),
                                     body: Some(Box::new(Statement::Block(block))),
-                                });
+                                }));
                             } else {
-                                create_report!(
-                                    self.context,
-                                    self.tokens.first().unwrap().range(),
+                                expected!(
+                                    self,
+                                    codes::MISSING_SEMICOLON,
                                     "Expected statement end after namespace statement.".to_string()
                                 );
                             }
                         } else {
-                            create_report!(
-                                self.context,
-                                self.tokens.first().unwrap().range(),
+                            expected!(
+                                self,
+                                codes::EXPECTED_STATEMENT,
                                 "Expected block after namespace with opening brace.".to_string()
                             );
                         }
                     } else if let Some(_) = self.tokens.peek_if(|t| t.kind().is_statement_end()) {
-                        return Some(Namespace {
+                        return Ok(Some(Namespace {
                             path: Path::from(name.value().unwrap(), path),
                             body: None,
-                        });
+                        }));
                     } else {
-                        create_report!(
-                            self.context,
-                            self.tokens.first().unwrap().range(),
+                        expected!(
+                            self,
+                            codes::UNEXPECTED_TOKEN,
                             "Unable to parse namespace path.".to_string(),
-                            format!(
-                                "Unexpected token: {}",
-                                self.tokens.peek().unwrap().kind().to_string()
-                            )
+                            format!("Unexpected token: {}", self.current_kind())
+                        );
+                    }
+                }
+            } else {
+                expected!(
+                    self,
+                    codes::EXPECTED_NAME,
+                    "Expected a namespace name.".to_string()
+                );
+            }
+        }
+        Ok(None)
+    }
+
+    /// Parses a type alias.
+    ///
+    /// For example:
+    /// - `type Id = int;`
+    /// - `type Point = { x: int, y: int };`
+    fn type_definition_rule(&mut self) -> ParseResult<TypeDefinition> {
+        if self
+            .tokens
+            .peek_if(|t| t.kind().is_keyword() && t.kind().as_keyword() == KeyWord::Type)
+            .is_none()
+        {
+            return Ok(None);
+        }
+
+        self.skip_whitespace();
+        let name = match self.tokens.peek_if(|t| t.kind().is_identifier()) {
+            Some(name) => name.value().unwrap(),
+            None => expected!(
+                self,
+                codes::EXPECTED_TYPE,
+                "Expected the name of a type.".to_string(),
+                "A name is expected here.".to_string()
+            ),
+        };
+        let params = Some(self.parse_generic_params()?).filter(|params| !params.is_empty());
+        let checked = self.runtime_type_value()?;
+        self.skip_whitespace();
+        if self
+            .tokens
+            .peek_if(|t| t.kind().is_operator() && t.text() == Some("="))
+            .is_none()
+        {
+            expected!(
+                self,
+                codes::EXPECTED_TYPE,
+                format!("Expected `=` and the type `{}` is an alias of.", name),
+                "`=` is expected here.".to_string()
+            );
+        }
+        self.skip_whitespace_err("Expected a type to follow `=`.")?;
+        let kind = match checked {
+            // the value of a runtime type is checked by an expression, not a type.
+            Some(value) => match self.parse_expression()? {
+                Some(body) => TypeKind::RuntimeType(RuntimeType::new(Some(vec![value]), body)),
+                None => expected!(
+                    self,
+                    codes::EXPECTED_EXPRESSION,
+                    format!("Expected the expression that checks a value of `{}`.", name),
+                    "An expression is expected here.".to_string()
+                ),
+            },
+            None => match self.parse_type_kind()? {
+                Some(kind) => kind,
+                None => expected!(
+                    self,
+                    codes::EXPECTED_TYPE,
+                    "Expected a type to follow `=`.".to_string(),
+                    "A type is expected here.".to_string()
+                ),
+            },
+        };
+        self.skip_whitespace_err("A semicolon was expected but none was found.")?;
+        if self
+            .tokens
+            .peek_if(|t| t.kind().is_statement_end())
+            .is_none()
+        {
+            expected!(
+                self,
+                codes::MISSING_SEMICOLON,
+                "A semicolon was expected but none was found.".to_string(),
+                "A semicolon is expected here.".to_string()
+            );
+        }
+        Ok(Some(TypeDefinition::new(name, params, kind)))
+    }
+
+    /// Parses the value a runtime type checks, if the type has one.
+    /// The value is bound by its type, `any` when it has none.
+    ///
+    /// For example:
+    /// - `(x)` in `type AnyNumber(x) = is_float(x) || is_int(x);`
+    /// - `(x: int)` in `type Positive(x: int) = x > 0;`
+    fn runtime_type_value(&mut self) -> ParseResult<TypeParam> {
+        self.skip_whitespace();
+        if self
+            .tokens
+            .peek_if(|t| t.kind().is_left_parenthesis())
+            .is_none()
+        {
+            return Ok(None);
+        }
+        self.skip_whitespace_err("Expected the value a runtime type checks.")?;
+        let Some(name) = self.tokens.peek_if(|t| t.kind().is_identifier()) else {
+            expected!(
+                self,
+                codes::EXPECTED_NAME,
+                "Expected a name for the value a runtime type checks.".to_string(),
+                "A name is expected here, eg: `type Positive(x) = x > 0;`".to_string()
+            );
+        };
+        self.skip_whitespace_err("Expected the value of a runtime type to be closed.")?;
+        let mut ty = TypeKind::BuiltIn(BuiltInType::Any);
+        if self.tokens.peek_if(|t| t.kind().is_colon()).is_some() {
+            self.skip_whitespace();
+            ty = match self.parse_type_kind()? {
+                Some(ty) => ty,
+                None => expected!(
+                    self,
+                    codes::EXPECTED_TYPE,
+                    "Expected the type of the value a runtime type checks.".to_string(),
+                    "A type is expected here.".to_string()
+                ),
+            };
+            self.skip_whitespace_err("Expected the value of a runtime type to be closed.")?;
+        }
+        if self
+            .tokens
+            .peek_if(|t| t.kind().is_right_parenthesis())
+            .is_none()
+        {
+            expected!(
+                self,
+                codes::INVALID_SYNTAX,
+                "Expected a `)` to close the value of a runtime type, it checks a single value."
+                    .to_string(),
+                "A `)` is expected here.".to_string()
+            );
+        }
+        Ok(Some(TypeParam::named(name.value().unwrap(), ty)))
+    }
+
+    /// Parses an import statement (if plausible).
+    ///
+    /// For example:
+    /// - `use foo::bar;`
+    /// - `use foo::{bar, baz};`
+    /// - `use foo::bar as baz;`
+    fn import_rule(&mut self) -> ParseResult<Path> {
+        let Some(keyword) = self
+            .tokens
+            .peek_if(|t| t.kind().is_keyword() && (t.kind().as_keyword() == KeyWord::Use))
+        else {
+            return Ok(None);
+        };
+        if self.flags.contains(&ContextFlag::InFunction) {
+            return self.misplaced(&keyword, "`use` can't be used in a function.".to_string());
+        }
+
+        self.skip_whitespace();
+        let start = self
+            .tokens
+            .first()
+            .map(|t| t.range().start)
+            .unwrap_or_default();
+        let mut path = self.parse_import_path()?;
+        path.span = start..self.tokens.prev().map(|t| t.range().end).unwrap_or(start);
+        self.skip_whitespace();
+        if self
+            .tokens
+            .peek_if(|t| t.kind().is_statement_end())
+            .is_none()
+        {
+            expected!(
+                self,
+                codes::MISSING_SEMICOLON,
+                "Expected statement end after import statement.".to_string()
+            );
+        }
+        Ok(Some(path))
+    }
+
+    /// Parses the path of an import, including any groups and aliases.
+    fn parse_import_path(&mut self) -> Result<Path, Report> {
+        let mut path = match self.tokens.peek_if(|t| t.kind().is_identifier()) {
+            Some(name) => Path::new(name.symbol().unwrap()),
+            None => expected!(
+                self,
+                codes::EXPECTED_NAME,
+                "Expected a module name in import path.".to_string()
+            ),
+        };
+
+        if self
+            .tokens
+            .peek_if(|t| t.kind().is_accessor() && t.text() == Some("::"))
+            .is_some()
+        {
+            if self.tokens.peek_if(|t| t.kind().is_left_brace()).is_some() {
+                // a group import, eg: `foo::{bar, baz}`
+                loop {
+                    self.skip_whitespace();
+                    path.parts.push(self.parse_import_path()?);
+                    self.skip_whitespace();
+                    if self.tokens.peek_if(|t| t.kind().is_comma()).is_some() {
+                        continue;
+                    } else if self.tokens.peek_if(|t| t.kind().is_right_brace()).is_some() {
+                        break;
+                    } else {
+                        expected!(
+                            self,
+                            codes::MISSING_COMMA,
+                            "Expected a comma or closing brace in import group.".to_string()
                         );
                     }
                 }
             } else {
+                path.parts.push(self.parse_import_path()?);
+            }
+            return Ok(path);
+        }
+
+        if let Some((amt, _)) = self.tokens.find_after(
+            |t| t.kind().is_identifier() && t.text() == Some("as"),
+            |t| t.kind().is_whitespace(),
+        ) {
+            self.tokens.peek_inc(amt + 1);
+            self.skip_whitespace();
+            match self.tokens.peek_if(|t| t.kind().is_identifier()) {
+                Some(alias) => path.alias = alias.value(),
+                None => expected!(
+                    self,
+                    codes::INVALID_SYNTAX,
+                    "Expected an alias after `as` in import path.".to_string()
+                ),
+            }
+        }
+        Ok(path)
+    }
+
+    /// Parses a macro invocation, the body is captured as is by the lexer.
+    /// - `php! { echo "hello"; }`
+    fn macro_invocation_rule(&mut self) -> ParseResult<CompilerMacro> {
+        let is_invocation = self
+            .tokens
+            .first()
+            .is_some_and(|t| t.kind().is_identifier())
+            && self
+                .tokens
+                .second()
+                .is_some_and(|t| t.kind().is_macro_body());
+        if !is_invocation {
+            return Ok(None);
+        }
+
+        let name = self.tokens.peek().unwrap();
+        let body = self.tokens.peek().unwrap();
+        let name_value = name.value().unwrap();
+        if !KNOWN_MACROS.contains(&name_value.as_str()) {
+            create_report!(
+                self.context,
+                codes::UNKNOWN_MACRO,
+                name.range(),
+                format!(
+                    "Unknown macro `{}!`, the known macros are: {}",
+                    name_value,
+                    KNOWN_MACROS
+                        .iter()
+                        .map(|m| format!("`{}!`", m))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                ),
+                "Unknown macro.".to_string()
+            );
+        }
+        match body.value() {
+            Some(raw) => {
+                let mut invocation = CompilerMacro::new(name_value, raw.clone());
+                if invocation.name == "target" {
+                    // the token ends at the closing brace, right after the body.
+                    let offset = body.range().end - raw.chars().count();
+                    invocation.arms = self.parse_target_arms(&raw, offset)?;
+                }
+                Ok(Some(invocation))
+            }
+            None => create_report!(
+                self.context,
+                codes::UNCLOSED_DELIMITER,
+                name.range().start..body.range().end,
+                "The body of this macro is never closed.".to_string(),
+                "Expected a closing brace for this macro.".to_string()
+            ),
+        }
+    }
+
+    /// Parses the arms of a `target!` macro, each arm is parsed as its own program.
+    /// - `php | js => { ... },`
+    /// - `_ => { ... }`
+    fn parse_target_arms(&mut self, raw: &str, offset: usize) -> Result<Vec<TargetArm>, Report> {
+        // the tokens are moved to where the body is in the source, so reports point at it.
+        let lines = LineIndex::new(&self.context.source.get_contents().unwrap_or_default());
+        let mut tokens = TokenStream::new(
+            tokenize(raw)
+                .into_iter()
+                .filter(|t| !t.kind().is_whitespace() && !t.kind().is_comment())
+                .map(|t| {
+                    let range = t.1.start + offset..t.1.end + offset;
+                    Token(t.0, range.clone(), t.2, lines.position(range.start))
+                })
+                .collect(),
+        );
+        let end = offset + raw.chars().count();
+
+        let mut arms: Vec<TargetArm> = Vec::new();
+        while !tokens.is_eof() {
+            let mut targets: Vec<String> = Vec::new();
+            loop {
+                match tokens.peek() {
+                    Some(t) if t.kind().is_identifier() => targets.push(t.value().unwrap()),
+                    other => create_report!(
+                        self.context,
+                        codes::EXPECTED_NAME,
+                        other.map_or(end..end, |t| t.range()),
+                        "Expected the name of a language in a `target!` arm.".to_string(),
+                        "Expected a language, eg: `php`".to_string()
+                    ),
+                }
+                if tokens
+                    .peek_if(|t| t.kind().is_operator() && t.text() == Some("|"))
+                    .is_none()
+                {
+                    break;
+                }
+            }
+
+            let arrow = matches!(
+                (tokens.first(), tokens.second()),
+                (Some(a), Some(b)) if a.text() == Some("=")
+                    && b.text() == Some(">")
+            );
+            if !arrow || tokens.nth_if(2, |t| t.kind().is_left_brace()).is_none() {
                 create_report!(
                     self.context,
-                    self.tokens.first().unwrap().range(),
-                    "Expected a namespace name.".to_string()
+                    codes::INVALID_SYNTAX,
+                    tokens.first().map_or(end..end, |t| t.range()),
+                    "Expected `=> {` to follow the languages of a `target!` arm.".to_string(),
+                    "Expected `=> {` here.".to_string()
                 );
             }
+            tokens.peek_inc(2);
+
+            // the arm ends at its matching brace.
+            let open = tokens.first().unwrap();
+            let Some(arm) = tokens.balanced() else {
+                create_report!(
+                    self.context,
+                    codes::UNCLOSED_DELIMITER,
+                    open.range(),
+                    "This `target!` arm is never closed.".to_string(),
+                    "Expected a closing brace for this arm.".to_string()
+                );
+            };
+
+            let mut generator = self.nested();
+            let body = generator.begin_parse(arm)?;
+            arms.push(TargetArm { targets, body });
+            tokens.peek_if(|t| t.kind().is_comma());
         }
-        return None;
+        Ok(arms)
     }
 
     /// Parses a static statement (if plausible).
-    /// A static statement can only be declared in classes and will be checked after initial parsing.
-    fn parse_static(&mut self) -> Option<Statement> {
+    /// A static statement can only be declared in classes and at the top level, not in a function.
+    fn static_rule(&mut self) -> ParseResult<Statement> {
+        let in_function = self.flags.contains(&ContextFlag::InFunction);
         // We actually can't parse visibility here, because a static statement may not exist, however,
         // we will parse it later, if visibility is present.
         if let Some(_) = self
@@ -247,49 +808,155 @@ impl AstGenerator {
             .first_if(|t| t.kind().is_keyword() && t.kind().as_keyword().is_visibility())
         {
             // We have a keyword however we need to make sure we have a static keyword next.
-            if let Some(_) = self
+            if let Some(keyword) = self
                 .tokens
                 .second_if(|t| t.kind().is_keyword() && (t.kind().as_keyword() == KeyWord::Static))
             {
-                let visibility = self.parse_visibility().unwrap();
+                if in_function {
+                    return self.misplaced(&keyword, STATIC_IN_FUNCTION.to_string());
+                }
+                let visibility = self.parse_visibility()?.unwrap();
                 self.tokens.peek();
                 self.skip_whitespace();
                 // We have a static keyword, so we can parse the rest of the statement.
-                if let Some(stmt) = self.parse_statement() {
-                    return Some(Statement::Static(Static::new(visibility, stmt)));
+                if let Some(stmt) = self.parse_statement()? {
+                    return Ok(Some(Statement::Static(Static::new(visibility, stmt))));
                 } else {
-                    create_report!(
-                        self.context,
-                        self.tokens.first().unwrap().range(),
+                    expected!(
+                        self,
+                        codes::EXPECTED_STATEMENT,
                         format!("Expected a statement after a static keyword, but found none."),
                         format!("A statement was expected here.")
                     );
                 }
             } else {
-                return None;
+                return Ok(None);
             }
         }
 
         // check if we have a static keyword!
-        if let Some(_) = self
+        if let Some(keyword) = self
             .tokens
             .first_if(|t| t.kind().is_keyword() && (t.kind().as_keyword() == KeyWord::Static))
         {
+            if in_function {
+                return self.misplaced(&keyword, STATIC_IN_FUNCTION.to_string());
+            }
             self.tokens.peek();
             self.skip_whitespace();
             // We have a static keyword, so we can parse the rest of the statement.
-            if let Some(stmt) = self.parse_statement() {
-                return Some(Statement::Static(Static::new(Visibility::Private, stmt)));
+            if let Some(stmt) = self.parse_statement()? {
+                Ok(Some(Statement::Static(Static::new(
+                    Visibility::Private,
+                    stmt,
+                ))))
             } else {
-                create_report!(
-                    self.context,
-                    self.tokens.first().unwrap().range(),
+                expected!(
+                    self,
+                    codes::EXPECTED_STATEMENT,
                     format!("Expected a statement after a static keyword, but found none."),
                     format!("A statement was expected here.")
                 );
             }
         } else {
-            return None;
+            Ok(None)
+        }
+    }
+
+    /// Parses a declaration that destructures a tuple (if plausible)
+    ///
+    /// For example:
+    /// - `var (a, b) = pair;`
+    /// - `var (a, b): (int, string) = pair;`
+    fn destructure_rule(&mut self) -> ParseResult<Destructure> {
+        let keyword = match self
+            .tokens
+            .peek_if(|t| t.kind().is_keyword() && t.kind().as_keyword() == KeyWord::Var)
+        {
+            Some(keyword) => keyword,
+            None => return Ok(None),
+        };
+        self.skip_whitespace();
+        if self
+            .tokens
+            .peek_if(|t| t.kind().is_left_parenthesis())
+            .is_none()
+        {
+            return Ok(None);
+        }
+
+        let mut names: Vec<String> = Vec::new();
+        loop {
+            self.skip_whitespace_err("Expected the names of a destructuring to be closed.")?;
+            match self.tokens.peek_if(|t| t.kind().is_identifier()) {
+                Some(name) => names.push(name.value().unwrap()),
+                None => expected!(
+                    self,
+                    codes::EXPECTED_NAME,
+                    "Expected a name to destructure a tuple value into.".to_string(),
+                    "A name is expected here.".to_string()
+                ),
+            }
+            self.skip_whitespace_err("Expected the names of a destructuring to be closed.")?;
+            if self
+                .tokens
+                .peek_if(|t| t.kind().is_right_parenthesis())
+                .is_some()
+            {
+                break;
+            }
+            if self.tokens.peek_if(|t| t.kind().is_comma()).is_none() {
+                expected!(
+                    self,
+                    codes::MISSING_COMMA,
+                    "Expected a `,` or `)` after a name.".to_string(),
+                    "A `,` or `)` is expected here.".to_string()
+                );
+            }
+        }
+
+        let mut ty: Option<TypeKind> = None;
+        if self.tokens.peek_if(|t| t.kind().is_colon()).is_some() {
+            self.skip_whitespace();
+            ty = self.parse_type_kind()?;
+        }
+        self.skip_whitespace_err("Expected a tuple to destructure.")?;
+        if self
+            .tokens
+            .peek_if(|t| t.kind().is_operator() && t.text() == Some("="))
+            .is_none()
+        {
+            expected!(
+                self,
+                codes::INVALID_SYNTAX,
+                "Expected a tuple to destructure.".to_string(),
+                "`=` is expected here.".to_string()
+            );
+        }
+        self.skip_whitespace_err("Expected a tuple to destructure.")?;
+        let assignment = match self.parse_expression()? {
+            Some(expr) => expr,
+            None => expected!(
+                self,
+                codes::INVALID_SYNTAX,
+                "Expected a tuple to destructure.".to_string(),
+                "An expression is expected here.".to_string()
+            ),
+        };
+        self.skip_whitespace_err("A semicolon was expected but none was found.")?;
+        match self.tokens.peek_if(|t| t.kind().is_statement_end()) {
+            Some(end) => Ok(Some(Destructure {
+                names,
+                ty,
+                assignment,
+                span: keyword.range().start..end.range().end,
+            })),
+            None => expected!(
+                self,
+                codes::MISSING_SEMICOLON,
+                "Expected a semicolon to follow a variable declaration.".to_string(),
+                "A semicolon is expected here.".to_string()
+            ),
         }
     }
 
@@ -298,9 +965,9 @@ impl AstGenerator {
     /// For example:
     /// - `var x = 5`
     /// - `const x = 5`
-    fn parse_variable(&mut self) -> Option<(Variable, bool)> {
+    fn variable_rule(&mut self) -> ParseResult<(Variable, bool)> {
         // check for visibility
-        let visibility = self.parse_visibility().unwrap_or(Visibility::Private);
+        let visibility = self.parse_visibility()?.unwrap_or(Visibility::Private);
         let decl_keyword = self.tokens.peek_if(|t| {
             if t.kind().is_keyword() {
                 return (t.kind().as_keyword() == KeyWord::Const)
@@ -312,7 +979,7 @@ impl AstGenerator {
 
         if let Some(keyword) = decl_keyword {
             let is_constant = keyword.kind().as_keyword() == KeyWord::Const;
-            self.skip_whitespace_err("A variable name was expected but none was found.");
+            self.skip_whitespace_err("A variable name was expected but none was found.")?;
 
             // check if the next token is an indentifier
             if let Some(identifier) = self.tokens.peek_if(|t| t.kind().is_identifier()) {
@@ -324,12 +991,13 @@ impl AstGenerator {
                 // the type checking and just parse the variable
                 if let Some(_) = self.tokens.peek_if(|t| t.kind().is_colon()) {
                     // now parse a type statement.
-                    if let Some(type_smt) = self.parse_type_kind() {
+                    self.skip_whitespace();
+                    if let Some(type_smt) = self.parse_type_kind()? {
                         type_node = Some(type_smt);
                     } else {
-                        create_report!(
-                            self.context,
-                            self.tokens.first().unwrap().range(),
+                        expected!(
+                            self,
+                            codes::EXPECTED_TYPE,
                             "Expected type statement to follow a variable declaration with a colon.".to_string(),
                             "A type statement is expected here.".to_string()
                         );
@@ -339,7 +1007,7 @@ impl AstGenerator {
                 }
 
                 // we now need an assignment operator
-                self.skip_whitespace_err("An operator was expected but none was found.");
+                self.skip_whitespace_err("An operator was expected but none was found.")?;
 
                 // check for an "equals" operator
                 if let Some(_) = self
@@ -348,34 +1016,33 @@ impl AstGenerator {
                 {
                     // we have an equals operator!
                     // we need to parse an expression
-                    self.skip_whitespace_err("An expression was expected but none was found.");
-                    if let Some(expr) = self.parse_expression() {
+                    self.skip_whitespace_err("An expression was expected but none was found.")?;
+                    if let Some(expr) = self.parse_expression()? {
                         // we have an expression!
                         // we need to parse a semicolon
-                        self.skip_whitespace_err("A semicolon was expected but none was found.");
-                        if let Some(_) = self.tokens.peek_if(|t| t.kind().is_statement_end()) {
-                            return Some((
-                                Variable::new(
-                                    identifier.value().unwrap(),
-                                    type_node,
-                                    visibility,
-                                    Some(expr),
-                                ),
-                                is_constant,
-                            ));
+                        self.skip_whitespace_err("A semicolon was expected but none was found.")?;
+                        if let Some(end) = self.tokens.peek_if(|t| t.kind().is_statement_end()) {
+                            let mut var = Variable::new(
+                                identifier.symbol().unwrap(),
+                                type_node,
+                                visibility,
+                                Some(expr),
+                            );
+                            var.span = keyword.range().start..end.range().end;
+                            Ok(Some((var, is_constant)))
                         } else {
-                            create_report!(
-                                self.context,
-                                self.tokens.first().unwrap().range(),
+                            expected!(
+                                self,
+                                codes::MISSING_SEMICOLON,
                                 "Expected a semicolon to follow a variable declaration."
                                     .to_string(),
                                 "A semicolon is expected here.".to_string()
                             );
                         }
                     } else {
-                        create_report!(
-                            self.context,
-                            self.tokens.first().unwrap().range(),
+                        expected!(
+                            self,
+                            codes::EXPECTED_EXPRESSION,
                             "Expected an expression to follow a variable declaration.".to_string(),
                             "An expression is expected here.".to_string()
                         );
@@ -383,19 +1050,23 @@ impl AstGenerator {
                 } else {
                     // variables **can** be uninitialized
                     // we need to check if the next token is an end of statement
-                    if let Some(_) = self.tokens.peek_if(|t| t.kind().is_statement_end()) {
+                    if let Some(end) = self.tokens.peek_if(|t| t.kind().is_statement_end()) {
                         // we have an end of statement!
                         // we can return a variable declaration
-                        return Some((
-                            Variable::new(identifier.value().unwrap(), type_node, visibility, None),
-                            is_constant,
-                        ));
+                        let mut var = Variable::new(
+                            identifier.symbol().unwrap(),
+                            type_node,
+                            visibility,
+                            None,
+                        );
+                        var.span = keyword.range().start..end.range().end;
+                        Ok(Some((var, is_constant)))
                     } else {
                         // we don't have an end of statement!
                         // we need to report an error
-                        create_report!(
-                            self.context,
-                            self.tokens.first().unwrap().range(),
+                        expected!(
+                            self,
+                            codes::MISSING_SEMICOLON,
                             "Expected an end of statement to follow an uninitialized declaration."
                                 .to_string(),
                             "A semi-colon is expected here.".to_string()
@@ -403,18 +1074,15 @@ impl AstGenerator {
                     }
                 }
             } else {
-                create_report!(
-                    self.context,
-                    self.tokens.first().unwrap().range(),
+                expected!(
+                    self,
+                    codes::EXPECTED_NAME,
                     "A name must follow a variable declaration".to_string(),
-                    format!(
-                        "Unexpected token: \"{}\"",
-                        self.tokens.first().unwrap().kind().to_string()
-                    )
+                    format!("Unexpected token: \"{}\"", self.current_kind())
                 );
             }
         } else {
-            return None;
+            Ok(None)
         }
     }
 
@@ -424,127 +1092,251 @@ impl AstGenerator {
     /// - `function foo() {}`
     /// - `function foo(x, y) {}`
     /// - `function foo(x, y): int {}`
-    fn parse_function(&mut self) -> Option<Function> {
-        if let Some(_) = self
+    /// - `function foo<T>(x: T): T {}`
+    /// - `async function foo() {}`
+    fn function_rule(&mut self) -> ParseResult<Function> {
+        // the start of the `async` keyword, if the function has one.
+        let mut async_start: Option<usize> = None;
+        if let Some(keyword) = self
+            .tokens
+            .first_if(|t| t.kind().is_keyword() && t.kind().as_keyword() == KeyWord::Async)
+        {
+            // `async` only starts a function when `fn` follows it.
+            match self.tokens.find_after_nth(
+                1,
+                |t| t.kind().is_keyword() && t.kind().as_keyword() == KeyWord::Function,
+                |t| t.kind().is_whitespace(),
+            ) {
+                Some((inc, _)) => {
+                    self.tokens.peek_inc(inc);
+                    async_start = Some(keyword.range().start);
+                }
+                None => expected!(
+                    self,
+                    codes::EXPECTED_STATEMENT,
+                    "Expected a function declaration to follow an async keyword.".to_string(),
+                    "Only functions can be async.".to_string()
+                ),
+            }
+        }
+        if let Some(keyword) = self
             .tokens
             .peek_if(|t| t.kind().is_keyword() && (t.kind().as_keyword() == KeyWord::Function))
         {
-            let _ = self.parse_visibility().unwrap_or(Visibility::Private);
+            let _ = self.parse_visibility()?.unwrap_or(Visibility::Private);
             let mut name: Option<String> = None;
-            self.skip_whitespace_err("A function input list was expected but none was found.");
+            let mut span = async_start.unwrap_or(keyword.range().start)..keyword.range().end;
+            self.skip_whitespace_err("A function input list was expected but none was found.")?;
             if let Some(n) = self.tokens.peek_if(|t| t.kind().is_identifier()) {
                 // we have a function name.
                 // we need to parse the input list
                 name = n.value();
+                span.end = n.range().end;
             }
+            let generics = self.parse_generic_params()?;
 
             // we need to parse the input list
-            self.skip_whitespace_err("A function input list was expected but none was found.");
-            if let Some((inputs, outputs)) = self.parse_function_inputs() {
+            self.skip_whitespace_err("A function input list was expected but none was found.")?;
+            if let Some((inputs, outputs)) = self.parse_function_inputs()? {
                 // we need a block now.
-                self.skip_whitespace_err("A block was expected but none was found.");
-                if let Some(block) = self.parse_block() {
-                    return Some(Function {
+                self.skip_whitespace_err("A block was expected but none was found.")?;
+                let open = self
+                    .tokens
+                    .first()
+                    .map(|t| t.range().start)
+                    .unwrap_or_default();
+                if let Some(block) =
+                    self.in_context(ContextFlag::InFunction, |g| g.parse_block())?
+                {
+                    return Ok(Some(Function {
                         name,
+                        generics,
                         inputs,
                         outputs,
                         body: Box::new(Statement::Block(block)),
                         visibility: Visibility::Public,
                         node_id: 0,
-                    });
+                        span,
+                        body_span: open..self.tokens.prev().unwrap().range().end,
+                        purity: Purity::Unknown,
+                        is_async: async_start.is_some(),
+                        attributes: Vec::new(),
+                    }));
                 } else {
-                    create_report!(
-                        self.context,
-                        self.tokens.first().unwrap().range(),
+                    expected!(
+                        self,
+                        codes::EXPECTED_STATEMENT,
                         "Expected a block to follow a function declaration.".to_string(),
                         "A block is expected here.".to_string()
                     );
                 }
             } else {
-                create_report!(
-                    self.context,
-                    self.tokens.first().unwrap().range(),
+                expected!(
+                    self,
+                    codes::EXPECTED_STATEMENT,
                     "Expected a function input list to follow a function declaration.".to_string(),
                     "A function input list is expected here.".to_string()
                 );
             }
         }
-        return None;
+        Ok(None)
     }
 
-    fn parse_function_inputs(&mut self) -> Option<(Vec<FunctionInput>, Option<TypeKind>)> {
-        if let Some(_) = self.tokens.peek_if(|t| t.kind().is_left_parenthesis()) {
-            let mut inputs: Vec<FunctionInput> = Vec::new();
-            while !self.tokens.is_eof() {
-                self.skip_whitespace_err("Function declaration arguments must be closed.");
-                if let Some(_) = self.tokens.peek_if(|t| t.kind().is_right_parenthesis()) {
-                    // we can't actually return here because we still need to parse the function body
-                    // as well as the return type
-                    break;
-                } else if let Some(param_name) = self.tokens.peek_if(|t| t.kind().is_identifier()) {
-                    // we have an identifier!
-                    // we need to check if a colon follows, if so, we need to parse a type, otherwise we can skip
+    /// Parses the type parameters of a declaration, if it has any.
+    /// A parameter can be bound by the type it extends, it is `any` when it isn't.
+    /// For example:
+    /// - `<T>` in `fn first<T>(items: T[]): T {}`
+    /// - `<K, V>` in `class Map<K, V> {}`
+    /// - `<T extends Comparable>` in `type Sorted<T extends Comparable> = T[];`
+    fn parse_generic_params(&mut self) -> Result<Vec<TypeParam>, Report> {
+        let mut params: Vec<TypeParam> = Vec::new();
+        if self
+            .tokens
+            .peek_if(|t| t.kind().is_operator() && t.text() == Some("<"))
+            .is_none()
+        {
+            return Ok(params);
+        }
+
+        loop {
+            self.skip_whitespace_err("Expected a type parameter to follow a `<`.")?;
+            match self.tokens.peek_if(|t| t.kind().is_identifier()) {
+                Some(name) => {
+                    let value = name.value().unwrap();
+                    if params.iter().any(|p| p.name.as_ref() == Some(&value)) {
+                        create_report!(
+                            self.context,
+                            codes::DUPLICATE_DECLARATION,
+                            name.range(),
+                            format!("The type parameter `{}` is declared twice.", value),
+                            "Declared again here.".to_string()
+                        );
+                    }
+                    let bound = self.generic_bound()?;
+                    params.push(TypeParam::named(
+                        value,
+                        bound.unwrap_or(TypeKind::BuiltIn(BuiltInType::Any)),
+                    ));
+                }
+                None => expected!(
+                    self,
+                    codes::EXPECTED_TYPE,
+                    "Expected the name of a type parameter.".to_string(),
+                    "A type parameter is expected here.".to_string()
+                ),
+            }
+
+            self.skip_whitespace_err("The type parameter list must be closed with `>`.")?;
+            if self.tokens.peek_if(|t| t.kind().is_comma()).is_some() {
+                continue;
+            } else if self
+                .tokens
+                .peek_if(|t| t.kind().is_operator() && t.text() == Some(">"))
+                .is_some()
+            {
+                return Ok(params);
+            } else {
+                expected!(
+                    self,
+                    codes::MISSING_COMMA,
+                    "Expected a comma or `>` in a type parameter list.".to_string(),
+                    "A comma or `>` is expected here.".to_string()
+                );
+            }
+        }
+    }
+
+    fn function_inputs_rule(&mut self) -> ParseResult<(Vec<FunctionInput>, Option<TypeKind>)> {
+        if let Some(_) = self.tokens.peek_if(|t| t.kind().is_left_parenthesis()) {
+            let mut inputs: Vec<FunctionInput> = Vec::new();
+            while !self.tokens.is_eof() {
+                self.skip_whitespace_err("Function declaration arguments must be closed.")?;
+                if let Some(_) = self.tokens.peek_if(|t| t.kind().is_right_parenthesis()) {
+                    // we can't actually return here because we still need to parse the function body
+                    // as well as the return type
+                    break;
+                } else if inputs.last().is_some_and(|input| input.variadic) {
+                    expected!(
+                        self,
+                        codes::MISPLACED_VARIADIC,
+                        "Only the last parameter of a function can be variadic.".to_string(),
+                        "No parameter can follow a variadic one.".to_string()
+                    );
+                }
+
+                // a variadic parameter collects the rest of the arguments, eg: `...names: string`.
+                let spread = self.tokens.peek_if(|t| t.kind().is_spread());
+                let variadic = spread.is_some();
+                if let Some(param_name) = self.tokens.peek_if(|t| t.kind().is_identifier()) {
+                    let start = spread.unwrap_or(param_name.clone()).range().start;
+                    // we have an identifier!
+                    // we need to check if a colon follows, if so, we need to parse a type, otherwise we can skip
                     // the type checking and just parse the variable
                     self.skip_whitespace_err(
                         "Expected a type statement after a function argument declaration.",
-                    );
+                    )?;
                     if let Some(_) = self.tokens.peek_if(|t| t.kind().is_colon()) {
                         // now parse a type statement.
                         self.skip_whitespace();
-                        if let Some(type_smt) = self.parse_type_kind() {
+                        if let Some(type_smt) = self.parse_type_kind()? {
                             // we have a type!
+                            let span = self.span_from(start);
                             // we need to parse a comma
-                            self.skip_whitespace_err("A comma was expected but none was found.");
+                            self.skip_whitespace_err("A comma was expected but none was found.")?;
                             if let Some(_) = self.tokens.peek_if(|t| t.kind().is_comma()) {
                                 // we have a comma!
                                 // we need to parse another argument
-                                inputs.push(FunctionInput::new(
-                                    param_name.value().unwrap_or("".to_string()),
-                                    Some(type_smt),
-                                ));
+                                inputs.push(FunctionInput {
+                                    name: param_name.value().unwrap_or("".to_string()),
+                                    ty: Some(type_smt),
+                                    variadic,
+                                    span,
+                                });
                             } else {
                                 // we don't have a comma!
                                 // we should check if a right parentises follows now
                                 if let Some(_) =
                                     self.tokens.peek_if(|t| t.kind().is_right_parenthesis())
                                 {
-                                    inputs.push(FunctionInput::new(
-                                        param_name.value().unwrap(),
-                                        Some(type_smt),
-                                    ));
+                                    inputs.push(FunctionInput {
+                                        name: param_name.value().unwrap(),
+                                        ty: Some(type_smt),
+                                        variadic,
+                                        span,
+                                    });
                                     break;
                                 } else {
                                     // we don't have a right parenthesis!
                                     // we need to report an error
-                                    create_report!(
-                                        self.context,
-                                        self.tokens.first().unwrap().range(),
+                                    expected!(
+                                        self,
+                                        codes::UNCLOSED_DELIMITER,
                                         "Expected a right parenthesis to follow a function argument declaration.".to_string(),
                                         "A right parenthesis is expected here.".to_string()
                                     );
                                 }
                             }
                         } else {
-                            create_report!(
-                                self.context,
-                                self.tokens.first().unwrap().range(),
+                            expected!(
+                                self,
+                                codes::EXPECTED_TYPE,
                                 "Expected a type statement to follow a function declaration argument.".to_string(),
                                 "A type statement is expected here.".to_string()
                             );
                         }
                     } else {
-                        create_report!(
-                            self.context,
-                            self.tokens.first().unwrap().range(),
+                        expected!(
+                            self,
+                            codes::EXPECTED_TYPE,
                             "Expected a type statement to follow a function declaration argument."
                                 .to_string(),
                             "A type statement is expected here.".to_string()
                         );
                     }
                 } else {
-                    create_report!(
-                        self.context,
-                        self.tokens.first().unwrap().range(),
+                    expected!(
+                        self,
+                        codes::EXPECTED_NAME,
                         "Expected a function parameter name but none was found.".to_string(),
                         "A name is expected here.".to_string()
                     );
@@ -559,13 +1351,13 @@ impl AstGenerator {
                 // we need to parse a type statement
                 self.skip_whitespace_err(
                     "Expected a return type statement after a function declaration.",
-                );
-                if let Some(type_smt) = self.parse_type_kind() {
+                )?;
+                if let Some(type_smt) = self.parse_type_kind()? {
                     returns = Some(type_smt);
                 } else {
-                    create_report!(
-                        self.context,
-                        self.tokens.first().unwrap().range(),
+                    expected!(
+                        self,
+                        codes::EXPECTED_TYPE,
                         "Expected a return type statement to follow a function declaration."
                             .to_string(),
                         "A return type is expected here.".to_string()
@@ -573,71 +1365,147 @@ impl AstGenerator {
                 }
             }
 
-            return Some((inputs, returns));
+            return Ok(Some((inputs, returns)));
+        }
+        Ok(None)
+    }
+
+    /// Parses the attributes before a declaration.
+    /// For example:
+    /// - `#[Service]`
+    /// - `#[Singleton(CacheContract)]`
+    fn parse_attributes(&mut self) -> Result<Vec<Attribute>, Report> {
+        let mut attributes: Vec<Attribute> = Vec::new();
+        while let Some(token) = self.tokens.peek_if(|t| t.kind().is_attribute()) {
+            let text = match token.value() {
+                Some(text) => text,
+                None => create_report!(
+                    self.context,
+                    codes::UNCLOSED_DELIMITER,
+                    token.range(),
+                    "This attribute is never closed.".to_string(),
+                    "Expected a `]` to close this attribute.".to_string()
+                ),
+            };
+            let (name, arguments) = match text.split_once('(') {
+                Some((name, rest)) if rest.ends_with(')') => (
+                    name.trim().to_string(),
+                    rest[..rest.len() - 1]
+                        .split(',')
+                        .map(|a| a.trim().to_string())
+                        .filter(|a| !a.is_empty())
+                        .collect(),
+                ),
+                Some(_) => create_report!(
+                    self.context,
+                    codes::UNCLOSED_DELIMITER,
+                    token.range(),
+                    "The arguments of this attribute are never closed.".to_string(),
+                    "Expected a `)` before the `]`.".to_string()
+                ),
+                None => (text.trim().to_string(), Vec::new()),
+            };
+            if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                create_report!(
+                    self.context,
+                    codes::EXPECTED_NAME,
+                    token.range(),
+                    format!("`{}` is not a valid attribute name.", name),
+                    "Expected a name, eg: `#[Service]`".to_string()
+                );
+            }
+            attributes.push(Attribute::new(name, arguments));
+            self.skip_whitespace();
+        }
+        Ok(attributes)
+    }
+
+    /// Parses the class the attributes are placed on.
+    fn attributed_class(&mut self, attributes: Vec<Attribute>) -> Result<Class, Report> {
+        match self.parse_class()? {
+            Some(mut class) => {
+                class.attributes = attributes;
+                Ok(class)
+            }
+            None => create_report!(
+                self.context,
+                codes::INVALID_SYNTAX,
+                self.tokens.first().map_or(0..0, |t| t.range()),
+                "Attributes can only be placed on classes.".to_string(),
+                "Expected a class here.".to_string()
+            ),
         }
-        return None;
     }
 
     /// Parses any class declaration.
-    fn parse_class(&mut self) -> Option<Class> {
-        if let Some(_) = self
+    fn class_rule(&mut self) -> ParseResult<Class> {
+        if let Some(keyword) = self
             .tokens
             .peek_if(|t| t.kind().is_keyword() && (t.kind().as_keyword() == KeyWord::Class))
         {
             self.skip_whitespace();
             if let Some(name) = self.tokens.peek_if(|t| t.kind().is_identifier()) {
+                let generics = self.parse_generic_params()?;
                 self.skip_whitespace();
-                let extends = self.parse_class_extension();
+                let extends = self.parse_class_extension()?;
                 self.skip_whitespace();
-                let implements: Option<Vec<String>> = self.parse_class_implementation();
-                let body: Option<ClassBody> = self.parse_class_body();
-                return Some(Class {
+                let implements: Option<Vec<String>> = self.parse_class_implementation()?;
+                let open = self
+                    .tokens
+                    .first()
+                    .map(|t| t.range().start)
+                    .unwrap_or_default();
+                let body: Option<ClassBody> =
+                    self.in_context(ContextFlag::InClass, |g| g.parse_class_body())?;
+                let body_span = match body {
+                    Some(_) => open..self.tokens.prev().unwrap().range().end,
+                    None => 0..0,
+                };
+                Ok(Some(Class {
                     name: name.value().unwrap(),
+                    attributes: Vec::new(),
+                    generics,
                     extends,
                     implements,
                     body: body.unwrap_or(ClassBody::new()),
                     node_id: self.context.get_next_local_id(),
-                });
+                    span: keyword.range().start..name.range().end,
+                    body_span,
+                }))
             } else {
-                create_report!(
-                    self.context,
-                    self.tokens.first().unwrap().range(),
+                expected!(
+                    self,
+                    codes::EXPECTED_NAME,
                     "Expected a class name but none was found.".to_string(),
-                    format!(
-                        "Unexpected token: {}",
-                        self.tokens.first().unwrap().kind().to_string()
-                    )
+                    format!("Unexpected token: {}", self.current_kind())
                 );
             }
         } else {
-            return None;
+            Ok(None)
         }
     }
 
-    fn parse_class_extension(&mut self) -> Option<String> {
+    fn class_extension_rule(&mut self) -> ParseResult<String> {
         if let Some(_) = self
             .tokens
             .peek_if(|t| t.kind().is_keyword() && (t.kind().as_keyword() == KeyWord::Extends))
         {
             self.skip_whitespace();
             if let Some(path) = self.tokens.peek_if(|t| t.kind().is_identifier()) {
-                return Some(path.value().unwrap());
+                return Ok(Some(path.value().unwrap()));
             } else {
-                create_report!(
-                    self.context,
-                    self.tokens.first().unwrap().range(),
+                expected!(
+                    self,
+                    codes::EXPECTED_NAME,
                     "Expected a class name to extend but none was found.".to_string(),
-                    format!(
-                        "Unexpected token: {}",
-                        self.tokens.first().unwrap().kind().to_string()
-                    )
+                    format!("Unexpected token: {}", self.current_kind())
                 );
             }
         }
-        return None;
+        Ok(None)
     }
 
-    fn parse_class_implementation(&mut self) -> Option<Vec<String>> {
+    fn class_implementation_rule(&mut self) -> ParseResult<Vec<String>> {
         if let Some(_) = self
             .tokens
             .peek_if(|t| t.kind().is_keyword() && (t.kind().as_keyword() == KeyWord::Implements))
@@ -652,14 +1520,11 @@ impl AstGenerator {
                         if let Some(path) = self.tokens.peek_if(|t| t.kind().is_identifier()) {
                             paths.push(path.value().unwrap());
                         } else {
-                            create_report!(
-                                self.context,
-                                self.tokens.first().unwrap().range(),
+                            expected!(
+                                self,
+                                codes::EXPECTED_NAME,
                                 "Expected a class name to extend but none was found.".to_string(),
-                                format!(
-                                    "Unexpected token: {}",
-                                    self.tokens.first().unwrap().kind().to_string()
-                                )
+                                format!("Unexpected token: {}", self.current_kind())
                             );
                         }
                     } else {
@@ -668,55 +1533,58 @@ impl AstGenerator {
                 }
 
                 if !self.tokens.is_eof() {
-                    return Some(paths);
+                    return Ok(Some(paths));
                 } else {
-                    create_report!(
-                        self.context,
-                        self.tokens.first().unwrap().range(),
+                    expected!(
+                        self,
+                        codes::EXPECTED_NAME,
                         "Expected a class name or interface to implement but none was found."
                             .to_string(),
-                        format!(
-                            "Unexpected token: {}",
-                            self.tokens.first().unwrap().kind().to_string()
-                        )
+                        format!("Unexpected token: {}", self.current_kind())
                     );
                 }
             } else {
-                create_report!(
-                    self.context,
-                    self.tokens.first().unwrap().range(),
+                expected!(
+                    self,
+                    codes::EXPECTED_NAME,
                     "Expected a class name to implement but none was found.".to_string(),
-                    format!(
-                        "Unexpected token: {}",
-                        self.tokens.first().unwrap().kind().to_string()
-                    )
+                    format!("Unexpected token: {}", self.current_kind())
                 );
             }
         }
-        return None;
+        Ok(None)
     }
 
     /// This function will attempt to parse a class property, however
     /// it will not parse it if it is not a property.
-    fn parse_class_property(&mut self, visibility: Visibility) -> Option<ClassProperty> {
+    fn class_property_rule(&mut self, visibility: Visibility) -> ParseResult<ClassProperty> {
         if let Some(name) = self.tokens.peek_if(|t| t.kind().is_identifier()) {
             let mut type_node: Option<TypeKind> = None;
             // check if there's a type assigned to the property, if not, check for a statement end.
             if let Some(_) = self.tokens.peek_if(|t| t.kind().is_colon()) {
                 // type statement.
                 self.skip_whitespace();
-                if let Some(kind) = self.parse_type_kind() {
+                if let Some(kind) = self.parse_type_kind()? {
                     type_node = Some(kind);
                 } else {
-                    create_report!(
-                        self.context,
-                        self.tokens.first().unwrap().range(),
+                    expected!(
+                        self,
+                        codes::EXPECTED_TYPE,
                         "Expected a type statement to follow a property declaration.".to_string(),
                         "A type statement is expected here.".to_string()
                     );
                 }
             }
 
+            // a property with accessors has no initializer and isn't followed by a semicolon.
+            if let Some(accessors) = self.property_accessors()? {
+                let mut property =
+                    ClassProperty::new(name.value().unwrap(), visibility, type_node, None);
+                property.accessors = accessors;
+                property.span = self.span_from(name.range().start);
+                return Ok(Some(property));
+            }
+
             // check for an "equals" operator
             if let Some(_) = self
                 .tokens
@@ -724,30 +1592,32 @@ impl AstGenerator {
             {
                 // we have an equals operator!
                 // we need to parse an expression
-                self.skip_whitespace_err("An expression was expected but none was found.");
-                if let Some(expr) = self.parse_expression() {
+                self.skip_whitespace_err("An expression was expected but none was found.")?;
+                if let Some(expr) = self.parse_expression()? {
                     // we have an expression!
                     // we need to parse a semicolon
-                    self.skip_whitespace_err("A semicolon was expected but none was found.");
+                    self.skip_whitespace_err("A semicolon was expected but none was found.")?;
                     if let Some(_) = self.tokens.peek_if(|t| t.kind().is_statement_end()) {
-                        return Some(ClassProperty::new(
+                        let mut property = ClassProperty::new(
                             name.value().unwrap(),
                             visibility,
                             type_node.clone(),
                             Some(expr),
-                        ));
+                        );
+                        property.span = self.span_from(name.range().start);
+                        return Ok(Some(property));
                     } else {
-                        create_report!(
-                            self.context,
-                            self.tokens.first().unwrap().range(),
+                        expected!(
+                            self,
+                            codes::MISSING_SEMICOLON,
                             "Expected a semicolon to follow a variable declaration.".to_string(),
                             "A semicolon is expected here.".to_string()
                         );
                     }
                 } else {
-                    create_report!(
-                        self.context,
-                        self.tokens.first().unwrap().range(),
+                    expected!(
+                        self,
+                        codes::EXPECTED_EXPRESSION,
                         "Expected an expression to follow a variable declaration.".to_string(),
                         "An expression is expected here.".to_string()
                     );
@@ -758,18 +1628,20 @@ impl AstGenerator {
                 if let Some(_) = self.tokens.peek_if(|t| t.kind().is_statement_end()) {
                     // we have an end of statement!
                     // we can return a variable declaration
-                    return Some(ClassProperty::new(
+                    let mut property = ClassProperty::new(
                         name.value().unwrap(),
                         visibility,
                         type_node.clone(),
                         None,
-                    ));
+                    );
+                    property.span = self.span_from(name.range().start);
+                    return Ok(Some(property));
                 } else {
                     // we don't have an end of statement!
                     // we need to report an error
-                    create_report!(
-                        self.context,
-                        self.tokens.first().unwrap().range(),
+                    expected!(
+                        self,
+                        codes::MISSING_SEMICOLON,
                         "Expected an end of statement to follow an uninitialized declaration."
                             .to_string(),
                         "A semi-colon is expected here.".to_string()
@@ -777,64 +1649,271 @@ impl AstGenerator {
                 }
             }
         }
-        return None;
+        Ok(None)
+    }
+
+    /// Parses the accessors that follow a property, if it has any.
+    /// For example:
+    /// - `{ get; set; }` in `pub name: string { get; set; }`
+    fn property_accessors(&mut self) -> Result<Option<Vec<Accessor>>, Report> {
+        match self
+            .tokens
+            .find_after(|t| t.kind().is_left_brace(), |t| t.kind().is_whitespace())
+        {
+            Some((inc, _)) => self.tokens.peek_inc(inc + 1),
+            None => return Ok(None),
+        }
+        let mut accessors: Vec<Accessor> = Vec::new();
+        loop {
+            self.skip_whitespace_err("The accessors of a property must be closed with `}`.")?;
+            if self.tokens.peek_if(|t| t.kind().is_right_brace()).is_some() {
+                return Ok(Some(accessors));
+            }
+            let Some((token, kind)) = self.accessor_keyword() else {
+                expected!(
+                    self,
+                    codes::INVALID_ACCESSOR,
+                    "Expected `get;` or `set;` in the accessors of a property.".to_string(),
+                    "An accessor is expected here.".to_string()
+                );
+            };
+            self.tokens.peek();
+            if accessors.iter().any(|accessor| accessor.kind == kind) {
+                create_report!(
+                    self.context,
+                    codes::DUPLICATE_DECLARATION,
+                    token.range(),
+                    format!("The property already has a `{}` accessor.", kind),
+                    "Declared again here.".to_string()
+                );
+            }
+            self.skip_whitespace_err("A semicolon was expected but none was found.")?;
+            if self
+                .tokens
+                .peek_if(|t| t.kind().is_statement_end())
+                .is_none()
+            {
+                expected!(
+                    self,
+                    codes::MISSING_SEMICOLON,
+                    "Expected a semicolon to follow an accessor.".to_string(),
+                    "A semicolon is expected here.".to_string()
+                );
+            }
+            accessors.push(Accessor { kind, body: None });
+        }
+    }
+
+    /// The next token if it is `get` or `set`, without consuming it.
+    fn accessor_keyword(&self) -> Option<(Token, AccessorKind)> {
+        let token = self.tokens.first_if(|t| t.kind().is_identifier())?;
+        let kind = match token.text()? {
+            "get" => AccessorKind::Get,
+            "set" => AccessorKind::Set,
+            _ => return None,
+        };
+        Some((token, kind))
+    }
+
+    /// Parses an explicit accessor of a property, its name is the name of the property.
+    ///
+    /// For example:
+    /// - `get name(): string { return this.name; }`
+    /// - `set name(value: string) { this.name = value; }`
+    fn accessor_rule(&mut self) -> ParseResult<Accessor> {
+        let Some((keyword, kind)) = self.accessor_keyword() else {
+            return Ok(None);
+        };
+        // `get` is only an accessor when a name follows it, otherwise it names a property.
+        let Some((inc, name)) = self.tokens.find_after_nth(
+            1,
+            |t| t.kind().is_identifier(),
+            |t| t.kind().is_whitespace(),
+        ) else {
+            return Ok(None);
+        };
+        self.tokens.peek_inc(inc + 1);
+        let span = keyword.range().start..name.range().end;
+
+        self.skip_whitespace_err("A parameter list was expected but none was found.")?;
+        let Some((inputs, outputs)) = self.parse_function_inputs()? else {
+            expected!(
+                self,
+                codes::EXPECTED_STATEMENT,
+                "Expected a parameter list to follow an accessor.".to_string(),
+                "A parameter list is expected here.".to_string()
+            );
+        };
+        let parameters = match kind {
+            AccessorKind::Get => 0,
+            AccessorKind::Set => 1,
+        };
+        if inputs.len() != parameters {
+            let message = match kind {
+                AccessorKind::Get => "A getter takes no parameters.",
+                AccessorKind::Set => "A setter takes the value it is given as its only parameter.",
+            };
+            create_report!(
+                self.context,
+                codes::INVALID_ACCESSOR,
+                span,
+                message.to_string(),
+                format!("It takes {} parameters.", inputs.len())
+            );
+        }
+
+        self.skip_whitespace_err("A block was expected but none was found.")?;
+        let open = self
+            .tokens
+            .first()
+            .map(|t| t.range().start)
+            .unwrap_or_default();
+        let Some(block) = self.in_context(ContextFlag::InFunction, |g| g.parse_block())? else {
+            expected!(
+                self,
+                codes::EXPECTED_STATEMENT,
+                "Expected a block to follow an accessor.".to_string(),
+                "A block is expected here.".to_string()
+            );
+        };
+        let body = Function {
+            name: name.value(),
+            generics: Vec::new(),
+            inputs,
+            outputs,
+            body: Box::new(Statement::Block(block)),
+            visibility: Visibility::Public,
+            node_id: 0,
+            span,
+            body_span: open..self.tokens.prev().unwrap().range().end,
+            purity: Purity::Unknown,
+            is_async: false,
+            attributes: Vec::new(),
+        };
+        Ok(Some(Accessor {
+            kind,
+            body: Some(body),
+        }))
     }
 
-    fn parse_class_allowed_statement(&mut self) -> Option<ClassAllowedStatement> {
+    /// Gives explicit accessors to the properties they are named after.
+    fn attach_accessors(
+        &self,
+        body: &mut ClassBody,
+        accessors: Vec<Accessor>,
+    ) -> Result<(), Report> {
+        for accessor in accessors {
+            let Some(func) = &accessor.body else {
+                continue;
+            };
+            let name = func.name.clone().unwrap_or_default();
+            let span = func.span.clone();
+            let Some(property) = body.property_mut(&name) else {
+                create_report!(
+                    self.context,
+                    codes::INVALID_ACCESSOR,
+                    span,
+                    format!("The class has no property `{}` to {}.", name, accessor.kind),
+                    format!("Declare `{}` in the class.", name)
+                );
+            };
+            if property.accessor(accessor.kind).is_some() {
+                create_report!(
+                    self.context,
+                    codes::DUPLICATE_DECLARATION,
+                    span,
+                    format!("`{}` already has a `{}` accessor.", name, accessor.kind),
+                    "Declared again here.".to_string()
+                );
+            }
+            property.accessors.push(accessor);
+        }
+        Ok(())
+    }
+
+    fn class_allowed_statement_rule(&mut self) -> ParseResult<ClassAllowedStatement> {
         // check for visibility
-        let visibility = self.parse_visibility().unwrap_or(Visibility::Private);
+        let declared = self.parse_visibility()?;
+        if let Some(constant) = self.parse_class_constant(declared.clone())? {
+            return Ok(Some(ClassAllowedStatement::Constant(constant)));
+        }
+        let visibility = declared.unwrap_or(Visibility::Private);
         if let Some(_) = self
             .tokens
             .peek_if(|t| t.kind().is_keyword() && t.kind().as_keyword() == KeyWord::Static)
         {
             self.skip_whitespace();
             // the statement is static
-            if let Some(property) = self.parse_class_property(visibility.clone()) {
-                return Some(ClassAllowedStatement::new_static(
+            if let Some(property) = self.parse_class_property(visibility.clone())? {
+                Ok(Some(ClassAllowedStatement::new_static(
                     ClassAllowedStatement::Property(property),
-                ));
-            } else if let Some(mut func) = self.parse_function() {
+                )))
+            } else if let Some(mut func) = self.parse_function()? {
                 func.visibility = visibility;
-                return Some(ClassAllowedStatement::new_static(
+                Ok(Some(ClassAllowedStatement::new_static(
                     ClassAllowedStatement::Method(func),
-                ));
+                )))
             } else {
-                create_report!(
-                    self.context,
-                    self.tokens.first().unwrap().range(),
+                expected!(
+                    self,
+                    codes::EXPECTED_STATEMENT,
                     "Expected a property or function declaration but none was found.".to_string(),
-                    format!(
-                        "Unexpected token: {}",
-                        self.tokens.first().unwrap().kind().to_string()
-                    )
+                    format!("Unexpected token: {}", self.current_kind())
                 );
             }
         } else {
             // the statement is not static
             // Parse a property
-            self.skip_whitespace_err("Expected a class statement but none was found.");
-            if let Some(property) = self.parse_class_property(visibility.clone()) {
-                return Some(ClassAllowedStatement::Property(property));
-            } else if let Some(mut func) = self.parse_function() {
+            self.skip_whitespace_err("Expected a class statement but none was found.")?;
+            if let Some(property) = self.parse_class_property(visibility.clone())? {
+                Ok(Some(ClassAllowedStatement::Property(property)))
+            } else if let Some(mut func) = self.parse_function()? {
                 func.visibility = visibility;
-                return Some(ClassAllowedStatement::Method(func));
+                Ok(Some(ClassAllowedStatement::Method(func)))
             } else {
-                create_report!(
-                    self.context,
-                    self.tokens.first().unwrap().range(),
+                expected!(
+                    self,
+                    codes::EXPECTED_STATEMENT,
                     "Expected a property or function declaration but none was found.".to_string(),
-                    format!(
-                        "Unexpected token: {}",
-                        self.tokens.first().unwrap().kind().to_string()
-                    )
+                    format!("Unexpected token: {}", self.current_kind())
                 );
             }
         }
     }
 
-    fn parse_class_body(&mut self) -> Option<ClassBody> {
+    /// Parses a class constant, constants are public unless a visibility is given.
+    ///
+    /// For example:
+    /// - `const LIMIT = 10;`
+    /// - `priv const KEY: string = "users";`
+    fn class_constant_rule(&mut self, visibility: Option<Visibility>) -> ParseResult<Variable> {
+        if self
+            .tokens
+            .first_if(|t| t.kind().is_keyword() && t.kind().as_keyword() == KeyWord::Const)
+            .is_none()
+        {
+            return Ok(None);
+        }
+        let Some((mut constant, _)) = self.parse_variable()? else {
+            return Ok(None);
+        };
+        if constant.assignment.is_none() {
+            create_report!(
+                self.context,
+                codes::EXPECTED_EXPRESSION,
+                constant.span.clone(),
+                "Class constants must be initialized.".to_string(),
+                format!("`{}` is never given a value.", constant.name)
+            );
+        }
+        constant.visibility = visibility.unwrap_or(Visibility::Public);
+        Ok(Some(constant))
+    }
+
+    fn class_body_rule(&mut self) -> ParseResult<ClassBody> {
         if let Some(_) = self.tokens.peek_if(|t| t.kind().is_left_brace()) {
             let mut body = ClassBody::new();
+            let mut accessors: Vec<Accessor> = Vec::new();
             // opening a body.
             // we need to parse the body until we reach the end
             while !self.tokens.is_eof()
@@ -845,90 +1924,438 @@ impl AstGenerator {
             {
                 self.skip_whitespace_err(
                     "Expected a right brace to close the class body, found none.",
-                );
-                if let Some(_) = self.tokens.peek_if(|t| t.kind().is_right_brace()) {
+                )?;
+                let attributes = self.parse_attributes()?;
+                if !attributes.is_empty() {
+                    self.attributed_method(attributes, &mut body)?;
+                } else if self.tokens.peek_if(|t| t.kind().is_right_brace()).is_some() {
                     break;
-                } else if let Some(property) = self.parse_class_property(Visibility::Private) {
+                } else if let Some(invocation) = self.parse_macro_invocation()? {
+                    body.other.push(ClassAllowedStatement::Macro(invocation));
+                } else if let Some(accessor) = self.parse_accessor()? {
+                    accessors.push(accessor);
+                } else if let Some(property) = self.parse_class_property(Visibility::Private)? {
                     body.properties.push(property);
-                } else if let Some(method) = self.parse_function() {
+                } else if let Some(method) = self.parse_function()? {
                     body.methods.push(method);
-                } else if let Some(other) = self.parse_class_allowed_statement() {
+                } else if let Some(other) = self.parse_class_allowed_statement()? {
                     body.other.push(other);
                 } else {
-                    create_report!(
-                        self.context,
-                        self.tokens.first().unwrap().range(),
+                    expected!(
+                        self,
+                        codes::INVALID_SYNTAX,
                         "Classes must contain a property, method, import or macro.".to_string(),
                         format!(
                             "Unexpected token: \"{}\" inside class body.",
-                            self.tokens.first().unwrap().kind().to_string()
+                            self.current_kind()
                         )
                     );
                 }
             }
+            self.attach_accessors(&mut body, accessors)?;
 
-            return Some(body);
+            Ok(Some(body))
         } else {
-            return None;
+            Ok(None)
+        }
+    }
+
+    /// Parses the method the attributes are placed on and adds it to the class body.
+    /// For example:
+    /// - `#[Override] pub fn save() {}`
+    fn attributed_method(
+        &mut self,
+        attributes: Vec<Attribute>,
+        body: &mut ClassBody,
+    ) -> Result<(), Report> {
+        if let Some(mut method) = self.parse_function()? {
+            method.attributes = attributes;
+            body.methods.push(method);
+            return Ok(());
+        }
+        let start = self.tokens.first().map_or(0..0, |t| t.range());
+        match self.parse_class_allowed_statement()? {
+            Some(mut other) if other.method().is_some() => {
+                other.method_mut().unwrap().attributes = attributes;
+                body.other.push(other);
+                Ok(())
+            }
+            _ => create_report!(
+                self.context,
+                codes::INVALID_SYNTAX,
+                start,
+                "Attributes can only be placed on classes and methods.".to_string(),
+                "Expected a method here.".to_string()
+            ),
+        }
+    }
+
+    /// Parses a try statement, its catch clauses and its finally block.
+    ///
+    /// For example:
+    /// - `try { load(); } catch (e: IOError) { log(e); }`
+    /// - `try { load(); } catch (e) { log(e); } finally { close(); }`
+    fn try_rule(&mut self) -> ParseResult<Try> {
+        let Some(keyword) = self
+            .tokens
+            .peek_if(|t| t.kind().is_keyword() && t.kind().as_keyword() == KeyWord::Try)
+        else {
+            return Ok(None);
+        };
+        let body = self.required_block("Expected a block to follow a try keyword.")?;
+
+        let mut catches: Vec<Catch> = Vec::new();
+        while let Some(catch) = self.parse_catch()? {
+            catches.push(catch);
+        }
+
+        let mut finally: Option<Box<Statement>> = None;
+        if let Some((amt, _)) = self.tokens.find_after(
+            |t| t.kind().is_keyword() && t.kind().as_keyword() == KeyWord::Finally,
+            |t| t.kind().is_whitespace(),
+        ) {
+            self.tokens.peek_inc(amt + 1);
+            finally = Some(self.required_block("Expected a block to follow a finally keyword.")?);
+        }
+
+        if catches.is_empty() && finally.is_none() {
+            create_report!(
+                self.context,
+                codes::INVALID_SYNTAX,
+                keyword.range(),
+                "A try statement needs a catch clause or a finally block.".to_string(),
+                "This try catches nothing.".to_string()
+            );
+        }
+
+        Ok(Some(Try {
+            body,
+            catches,
+            finally,
+            span: keyword.range().start..self.tokens.prev().unwrap().range().end,
+        }))
+    }
+
+    /// Parses the type a type parameter extends, if it is followed by `extends`.
+    fn generic_bound(&mut self) -> Result<Option<TypeKind>, Report> {
+        let Some((amt, _)) = self.tokens.find_after(
+            |t| t.kind().is_keyword() && t.kind().as_keyword() == KeyWord::Extends,
+            |t| t.kind().is_whitespace(),
+        ) else {
+            return Ok(None);
+        };
+        self.tokens.peek_inc(amt + 1);
+        self.skip_whitespace_err("Expected the type a type parameter extends.")?;
+        match self.parse_type_kind()? {
+            Some(bound) => Ok(Some(bound)),
+            None => expected!(
+                self,
+                codes::EXPECTED_TYPE,
+                "Expected the type a type parameter extends.".to_string(),
+                "A type is expected here, eg: `T extends Comparable`.".to_string()
+            ),
+        }
+    }
+
+    /// Parses an if statement, with the `else if` and `else` that follow it.
+    ///
+    /// For example:
+    /// - `if (x is int) { log(x); }`
+    /// - `if (a) { start(); } else if (b) { wait(); } else { stop(); }`
+    fn if_rule(&mut self) -> ParseResult<If> {
+        let Some(keyword) = self
+            .tokens
+            .peek_if(|t| t.kind().is_keyword() && t.kind().as_keyword() == KeyWord::If)
+        else {
+            return Ok(None);
+        };
+        self.skip_whitespace_err("Expected the condition of an if statement.")?;
+        if self
+            .tokens
+            .peek_if(|t| t.kind().is_left_parenthesis())
+            .is_none()
+        {
+            expected!(
+                self,
+                codes::INVALID_SYNTAX,
+                "Expected a `(` to follow an if keyword.".to_string(),
+                "A `(` is expected here, eg: `if (x is int)`.".to_string()
+            );
+        }
+        self.skip_whitespace_err("Expected the condition of an if statement.")?;
+        let Some(condition) = self.parse_expression()? else {
+            expected!(
+                self,
+                codes::EXPECTED_EXPRESSION,
+                "Expected the condition of an if statement.".to_string(),
+                "An expression is expected here.".to_string()
+            );
+        };
+        self.skip_whitespace_err("Expected the condition of an if statement to be closed.")?;
+        if self
+            .tokens
+            .peek_if(|t| t.kind().is_right_parenthesis())
+            .is_none()
+        {
+            expected!(
+                self,
+                codes::INVALID_SYNTAX,
+                "Expected a `)` to close the condition of an if statement.".to_string(),
+                "A `)` is expected here.".to_string()
+            );
+        }
+        let body = self.required_block("Expected a block to follow the condition of an if.")?;
+
+        let mut otherwise: Option<Box<Statement>> = None;
+        if let Some((amt, _)) = self.tokens.find_after(
+            |t| t.kind().is_keyword() && t.kind().as_keyword() == KeyWord::Else,
+            |t| t.kind().is_whitespace(),
+        ) {
+            self.tokens.peek_inc(amt + 1);
+            self.skip_whitespace_err("Expected a block or an if to follow an else keyword.")?;
+            otherwise = match self.parse_if()? {
+                Some(i) => Some(Box::new(Statement::If(i))),
+                None => Some(self.required_block("Expected a block to follow an else keyword.")?),
+            };
+        }
+
+        Ok(Some(If {
+            condition,
+            body,
+            otherwise,
+            span: keyword.range().start..self.tokens.prev().unwrap().range().end,
+        }))
+    }
+
+    /// Parses a catch clause, the type of the error is optional.
+    ///
+    /// For example:
+    /// - `catch (e: IOError) { log(e); }`
+    /// - `catch (e) { log(e); }`
+    fn catch_rule(&mut self) -> ParseResult<Catch> {
+        let Some((amt, _)) = self.tokens.find_after(
+            |t| t.kind().is_keyword() && t.kind().as_keyword() == KeyWord::Catch,
+            |t| t.kind().is_whitespace(),
+        ) else {
+            return Ok(None);
+        };
+        self.tokens.peek_inc(amt + 1);
+        self.skip_whitespace_err("Expected the error of a catch clause.")?;
+        if self
+            .tokens
+            .peek_if(|t| t.kind().is_left_parenthesis())
+            .is_none()
+        {
+            expected!(
+                self,
+                codes::INVALID_SYNTAX,
+                "Expected a `(` to follow a catch keyword.".to_string(),
+                "A `(` is expected here, eg: `catch (e: Error)`.".to_string()
+            );
+        }
+        self.skip_whitespace_err("Expected the error of a catch clause.")?;
+        let Some(name) = self.tokens.peek_if(|t| t.kind().is_identifier()) else {
+            expected!(
+                self,
+                codes::EXPECTED_NAME,
+                "Expected a name for the error of a catch clause.".to_string(),
+                "A name is expected here.".to_string()
+            );
+        };
+        self.skip_whitespace_err("Expected the error of a catch clause to be closed.")?;
+        let mut ty: Option<TypeKind> = None;
+        if self.tokens.peek_if(|t| t.kind().is_colon()).is_some() {
+            self.skip_whitespace();
+            ty = self.parse_type_kind()?;
+            if ty.is_none() {
+                expected!(
+                    self,
+                    codes::EXPECTED_TYPE,
+                    "Expected the type of error to catch.".to_string(),
+                    "A type is expected here.".to_string()
+                );
+            }
+            self.skip_whitespace_err("Expected the error of a catch clause to be closed.")?;
+        }
+        if self
+            .tokens
+            .peek_if(|t| t.kind().is_right_parenthesis())
+            .is_none()
+        {
+            expected!(
+                self,
+                codes::INVALID_SYNTAX,
+                "Expected a `)` to close the error of a catch clause.".to_string(),
+                "A `)` is expected here.".to_string()
+            );
+        }
+        let body = self.required_block("Expected a block to follow a catch clause.")?;
+        Ok(Some(Catch {
+            name: name.value().unwrap(),
+            ty,
+            body,
+        }))
+    }
+
+    /// Parses a throw statement
+    ///
+    /// For example:
+    /// - `throw new Error("failed");`
+    /// - `throw error;`
+    fn throw_rule(&mut self) -> ParseResult<Expression> {
+        if self
+            .tokens
+            .peek_if(|t| t.kind().is_keyword() && t.kind().as_keyword() == KeyWord::Throw)
+            .is_none()
+        {
+            return Ok(None);
+        }
+        self.skip_whitespace_err("Expected an error to follow a throw keyword.")?;
+        let Some(expr) = self.parse_expression()? else {
+            expected!(
+                self,
+                codes::EXPECTED_EXPRESSION,
+                "Expected an error to follow a throw keyword.".to_string(),
+                "An expression is expected here.".to_string()
+            );
+        };
+        self.skip_whitespace_err("A semicolon was expected but none was found.")?;
+        if self
+            .tokens
+            .peek_if(|t| t.kind().is_statement_end())
+            .is_none()
+        {
+            expected!(
+                self,
+                codes::MISSING_SEMICOLON,
+                "Expected a semicolon to follow a throw statement.".to_string(),
+                "A semicolon is expected here.".to_string()
+            );
+        }
+        Ok(Some(expr))
+    }
+
+    /// Requires the semicolon that ends an expression statement, eg: `save(user);`,
+    /// the same way in a block as at the top level. Returns whether one was eaten,
+    /// statements end themselves.
+    fn expression_statement_end(&mut self, expr: &Expression) -> Result<bool, Report> {
+        if matches!(expr, Expression::Statement(_)) {
+            return Ok(false);
+        }
+        self.skip_whitespace();
+        if self
+            .tokens
+            .peek_if(|t| t.kind().is_statement_end())
+            .is_none()
+        {
+            expected!(
+                self,
+                codes::MISSING_SEMICOLON,
+                "Expected a semicolon to follow an expression statement.".to_string(),
+                "A semicolon is expected here.".to_string()
+            );
+        }
+        Ok(true)
+    }
+
+    /// Parses a return statement, the value is optional.
+    ///
+    /// For example:
+    /// - `return total + 1;`
+    /// - `return;`
+    fn return_rule(&mut self) -> ParseResult<Return> {
+        let Some(keyword) = self
+            .tokens
+            .peek_if(|t| t.kind().is_keyword() && t.kind().as_keyword() == KeyWord::Return)
+        else {
+            return Ok(None);
+        };
+        if !self.flags.contains(&ContextFlag::InFunction) {
+            return self.misplaced(
+                &keyword,
+                "`return` can only be used in a function.".to_string(),
+            );
+        }
+        self.skip_whitespace_err("A semicolon was expected but none was found.")?;
+        let expression = match self.tokens.first_if(|t| t.kind().is_statement_end()) {
+            Some(_) => None,
+            None => match self.parse_expression()? {
+                Some(expr) => Some(expr),
+                None => expected!(
+                    self,
+                    codes::EXPECTED_EXPRESSION,
+                    "Expected a value or a semicolon to follow a return keyword.".to_string(),
+                    "An expression is expected here.".to_string()
+                ),
+            },
+        };
+        self.skip_whitespace_err("A semicolon was expected but none was found.")?;
+        if self
+            .tokens
+            .peek_if(|t| t.kind().is_statement_end())
+            .is_none()
+        {
+            expected!(
+                self,
+                codes::MISSING_SEMICOLON,
+                "Expected a semicolon to follow a return statement.".to_string(),
+                "A semicolon is expected here.".to_string()
+            );
+        }
+        let mut ret = Return::new(expression);
+        ret.span = self.span_from(keyword.range().start);
+        Ok(Some(ret))
+    }
+
+    /// Parses the block that must follow a `try`, `catch` or `finally`, reporting `err` if there is none.
+    fn required_block(&mut self, err: &'static str) -> Result<Box<Statement>, Report> {
+        self.skip_whitespace_err(err)?;
+        match self.parse_block()? {
+            Some(block) => Ok(Box::new(Statement::Block(block))),
+            None => expected!(
+                self,
+                codes::EXPECTED_STATEMENT,
+                err.to_string(),
+                "A block is expected here.".to_string()
+            ),
         }
     }
 
     /// Parses any block statement
     /// A block statement is a statement that is surrounded by curly braces
     /// However, this does not include class bodies, as they have special properties.
-    fn parse_block(&mut self) -> Option<Vec<Expression>> {
+    fn block_rule(&mut self) -> ParseResult<Vec<Expression>> {
         // we're expecting the next token to be a brace
         if let Some(_) = self.tokens.peek_if(|t| t.kind().is_left_brace()) {
             // we have a brace!
             // we need to parse the statements inside the block
             let mut expressions: Vec<Expression> = Vec::new();
             while !self.tokens.is_eof() {
-                self.skip_whitespace_err("Expected a statement to follow a block.");
-                if let Some(expr) = self.parse_expression() {
+                self.skip_whitespace_err("Expected a statement to follow a block.")?;
+                if let Some(expr) = self.parse_expression()? {
+                    let ends = self.expression_statement_end(&expr)?;
                     expressions.push(expr);
+                    if ends {
+                        expressions.push(Expression::EndOfLine);
+                    }
                 } else if let Some(_) = self.tokens.peek_if(|t| t.kind().is_right_brace()) {
                     // we have a right brace!
                     // this is the end of the block.
                     break;
                 } else if let Some(_) = self.tokens.peek_if(|t| t.kind().is_statement_end()) {
                     expressions.push(Expression::EndOfLine);
-                } else if let Some(_) = self
-                    .tokens
-                    .peek_if(|t| t.kind().is_keyword() && t.kind().as_keyword() == KeyWord::Return)
-                {
-                    // we have a return statement!
-                    // we need to parse the return statement
-                    self.skip_whitespace();
-                    if let Some(expr) = self.parse_expression() {
-                        expressions.push(Expression::Statement(Box::new(Statement::Return(
-                            Return::new(Some(expr)),
-                        ))));
-                    }
-                    if let Some(_) = self.tokens.peek_if(|t| t.kind().is_statement_end()) {
-                        // end of statement! however, we dont care because this is a block and we don't
-                        // have the context of the block.
-                        continue;
-                    } else {
-                        create_report!(
-                            self.context,
-                            self.tokens.first().unwrap().range(),
-                            "Expected an expression to follow a return statement.".to_string(),
-                            "Expected an expression here.".to_string()
-                        );
-                    }
                 } else {
-                    println!("{:?}", self.tokens.first().unwrap());
-                    create_report!(
-                        self.context,
-                        self.tokens.first().unwrap().range(),
+                    expected!(
+                        self,
+                        codes::EXPECTED_STATEMENT,
                         "Expected a statement to follow a block.".to_string(),
                         "A statement is expected here.".to_string()
                     );
                 }
             }
-            return Some(expressions);
+            Ok(Some(expressions))
         } else {
-            return None;
+            Ok(None)
         }
     }
 
@@ -937,18 +2364,18 @@ impl AstGenerator {
     /// > This is an alias for `parse_statement` as it will only parse visibility and static statements.
     /// EG: `public`
     /// EG: `private static`
-    fn parse_visibility(&mut self) -> Option<Visibility> {
+    fn visibility_rule(&mut self) -> ParseResult<Visibility> {
         if let Some(modifier) = self
             .tokens
             .peek_if(|t| t.kind().is_keyword() && t.kind().as_keyword().is_visibility())
         {
             let visibility = Visibility::from_keyword(modifier.kind().as_keyword());
 
-            self.skip_whitespace_err("A statement or static keyword was expected after a visibility modifier but none was found.");
+            self.skip_whitespace_err("A statement or static keyword was expected after a visibility modifier but none was found.")?;
 
-            return Some(visibility);
+            Ok(Some(visibility))
         } else {
-            return None;
+            Ok(None)
         }
     }
 
@@ -960,97 +2387,267 @@ impl AstGenerator {
     //     {
     //         let visibility = Visibility::from_keyword(modifier.kind().as_keyword());
 
-    //         return Some(visibility);
+    //         return Ok(Some(visibility));
     //     } else {
-    //         return None;
+    //         return Ok(None);
     //     }
     // }
 
-    /// Parses a type kind.
-    /// For example:
-    /// - `int`
-    /// - `string`
-    /// - `bool`
-    fn parse_type_kind(&mut self) -> Option<TypeKind> {
-        if let Some(initial) = self.tokens.peek_if(|t| t.kind().is_identifier()) {
-            let name = initial.value().unwrap();
-            // The first token is an identifier! This is good, this is a type kind already, however!,
-            // we need to check if the next token is a union, if it's not, we can return the type kind.
+    /// Parses a type kind.
+    /// For example:
+    /// - `int`
+    /// - `string?`
+    /// - `Box<int> | null`
+    fn type_kind_rule(&mut self) -> ParseResult<TypeKind> {
+        let first = match self.parse_single_type()? {
+            Some(ty) => ty,
+            None => return Ok(None),
+        };
+        self.skip_whitespace();
+        if self
+            .tokens
+            .first_if(|t| t.kind().is_operator() && t.text() == Some("|"))
+            .is_none()
+        {
+            return Ok(Some(first));
+        }
+
+        // this is a union type!
+        let mut types: Vec<TypeKind> = vec![first];
+        while self
+            .tokens
+            .peek_if(|t| t.kind().is_operator() && t.text() == Some("|"))
+            .is_some()
+        {
+            self.skip_whitespace_err("Expected a type reference to follow a union type.")?;
+            match self.parse_single_type()? {
+                Some(ty) => types.push(ty),
+                None => expected!(
+                    self,
+                    codes::EXPECTED_TYPE,
+                    "Expected a type reference to follow a union type.".to_string(),
+                    "A type reference is expected here.".to_string()
+                ),
+            }
+            self.skip_whitespace();
+        }
+        Ok(Some(TypeKind::union(types)))
+    }
+
+    /// Parses a type that is not a union, eg: `int`, `Box<T>`, `string?`, `(int) => bool`,
+    /// `(int, int)`, `{ x: int }`, `int[]` or `map<string, int>`
+    fn single_type_rule(&mut self) -> ParseResult<TypeKind> {
+        let ty = if self
+            .tokens
+            .first_if(|t| t.kind().is_left_parenthesis())
+            .is_some()
+        {
+            match self.parse_parenthesized_type()? {
+                Some(ty) => ty,
+                None => return Ok(None),
+            }
+        } else if self.tokens.first_if(|t| t.kind().is_left_brace()).is_some() {
+            match self.parse_object_type()? {
+                Some(ty) => ty,
+                None => return Ok(None),
+            }
+        } else {
+            let initial = match self.tokens.peek_if(|t| t.kind().is_identifier()) {
+                Some(initial) => initial,
+                None => return Ok(None),
+            };
+            let name = initial.value().unwrap();
+            match BuiltInType::from_string(name.clone()) {
+                Some(BuiltInType::Map(key, value)) => match self.parse_type_generics()? {
+                    None => TypeKind::BuiltIn(BuiltInType::Map(key, value)),
+                    Some(params) if params.len() == 2 => {
+                        let mut params = params.into_iter().map(|p| Box::new(p.kind));
+                        let key = params.next().unwrap();
+                        TypeKind::BuiltIn(BuiltInType::Map(key, params.next().unwrap()))
+                    }
+                    Some(_) => create_report!(
+                        self.context,
+                        codes::EXPECTED_TYPE,
+                        self.span_from(initial.range().start),
+                        "A map takes the type of its keys and of its values.".to_string(),
+                        "Expected two types here, eg: `map<string, int>`.".to_string()
+                    ),
+                },
+                Some(ty) => TypeKind::BuiltIn(ty),
+                None => TypeKind::Reference(TypeReference::new(name, self.parse_type_generics()?)),
+            }
+        };
+        Ok(Some(self.type_suffixes(ty)))
+    }
+
+    /// Applies the `?` and `[]` that follow a type, eg: `int[]?` is a nullable array of `int`.
+    fn type_suffixes(&mut self, mut ty: TypeKind) -> TypeKind {
+        loop {
+            if self
+                .tokens
+                .peek_if(|t| t.kind().is_operator() && t.text() == Some("?"))
+                .is_some()
+            {
+                ty = TypeKind::nullable(ty);
+            } else if self.is_array_suffix() {
+                self.tokens.peek_inc(2);
+                ty = TypeKind::BuiltIn(BuiltInType::Array(Box::new(ty)));
+            } else {
+                return ty;
+            }
+        }
+    }
+
+    /// Whether the next tokens are `[]`, which makes the type before them an array.
+    fn is_array_suffix(&mut self) -> bool {
+        self.tokens
+            .first_if(|t| t.kind().is_left_bracket())
+            .is_some()
+            && self
+                .tokens
+                .second_if(|t| t.kind().is_right_bracket())
+                .is_some()
+    }
+
+    /// Parses a type in parentheses, which is either a function type or a tuple type.
+    ///
+    /// For example:
+    /// - `(int, string) => bool`
+    /// - `(int, string)`
+    fn parenthesized_type_rule(&mut self) -> ParseResult<TypeKind> {
+        let open = self.tokens.peek().unwrap().range();
+        let mut types: Vec<TypeKind> = Vec::new();
+        loop {
+            self.skip_whitespace_err("Expected the parentheses of a type to be closed.")?;
+            if self
+                .tokens
+                .peek_if(|t| t.kind().is_right_parenthesis())
+                .is_some()
+            {
+                break;
+            }
+            match self.parse_type_kind()? {
+                Some(ty) => types.push(ty),
+                None => expected!(
+                    self,
+                    codes::EXPECTED_TYPE,
+                    "Expected a type in parentheses.".to_string(),
+                    "A type, `,` or `)` is expected here.".to_string()
+                ),
+            }
+            self.skip_whitespace();
+            if self.tokens.peek_if(|t| t.kind().is_comma()).is_none()
+                && self
+                    .tokens
+                    .first_if(|t| t.kind().is_right_parenthesis())
+                    .is_none()
+            {
+                expected!(
+                    self,
+                    codes::MISSING_COMMA,
+                    "Expected a `,` or `)` after a type.".to_string(),
+                    "A `,` or `)` is expected here.".to_string()
+                );
+            }
+        }
+
+        // `=>` is tokenized as two operators.
+        self.skip_whitespace();
+        let is_arrow = self
+            .tokens
+            .first_if(|t| t.kind().is_operator() && t.text() == Some("="))
+            .is_some()
+            && self
+                .tokens
+                .second_if(|t| t.kind().is_operator() && t.text() == Some(">"))
+                .is_some();
+        if !is_arrow {
+            // the parentheses group the type an array is of, eg: `(int | string)[]`.
+            if types.len() == 1 && self.is_array_suffix() {
+                return Ok(types.pop());
+            }
+            if types.len() < 2 {
+                create_report!(
+                    self.context,
+                    codes::EXPECTED_TYPE,
+                    open.start..self.current_range().start,
+                    "A tuple type needs at least two types.".to_string(),
+                    "Expected `=>` and a return type if this is a function type.".to_string()
+                );
+            }
+            return Ok(Some(TypeKind::Tuple(types)));
+        }
+        self.tokens.peek();
+        self.tokens.peek();
+
+        self.skip_whitespace_err("Expected the return type of a function type.")?;
+        match self.parse_single_type()? {
+            Some(ret) => Ok(Some(TypeKind::function(types, ret))),
+            None => expected!(
+                self,
+                codes::EXPECTED_TYPE,
+                "Expected the return type of a function type.".to_string(),
+                "A type is expected here.".to_string()
+            ),
+        }
+    }
+
+    /// Parses the shape of an object, eg: `{ x: int, y: int }`
+    fn object_type_rule(&mut self) -> ParseResult<TypeKind> {
+        self.tokens.peek();
+        let mut properties: Vec<ShapeProperty> = Vec::new();
+        loop {
+            self.skip_whitespace_err("Expected the shape of an object to be closed.")?;
+            if self.tokens.peek_if(|t| t.kind().is_right_brace()).is_some() {
+                break;
+            }
+            let name = match self.tokens.peek_if(|t| t.kind().is_identifier()) {
+                Some(name) => name.value().unwrap(),
+                None => expected!(
+                    self,
+                    codes::EXPECTED_NAME,
+                    "Expected the name of a property.".to_string(),
+                    "A property name or `}` is expected here.".to_string()
+                ),
+            };
             self.skip_whitespace();
-            if let Some(_) = self
-                .tokens
-                .peek_if(|t| t.kind().is_operator() && t.value().unwrap().as_str() == "|")
+            if self.tokens.peek_if(|t| t.kind().is_colon()).is_none() {
+                expected!(
+                    self,
+                    codes::EXPECTED_TYPE,
+                    format!("Expected the type of the property `{}`.", name),
+                    "`:` is expected here.".to_string()
+                );
+            }
+            self.skip_whitespace();
+            match self.parse_type_kind()? {
+                Some(ty) => properties.push(ShapeProperty { name, ty }),
+                None => expected!(
+                    self,
+                    codes::EXPECTED_TYPE,
+                    format!("Expected the type of the property `{}`.", name),
+                    "A type is expected here.".to_string()
+                ),
+            }
+            self.skip_whitespace();
+            if self.tokens.peek_if(|t| t.kind().is_comma()).is_none()
+                && self
+                    .tokens
+                    .first_if(|t| t.kind().is_right_brace())
+                    .is_none()
             {
-                // this is a union type!
-                let mut union_type = TypeUnion::empty();
-                while !self.tokens.is_eof() {
-                    // we need to recursively parse in a union type, this can be exhausting!
-                    // because of this, we will only be parsing type references here.
-                    self.skip_whitespace_err("Expected a type reference to follow a union type.");
-                    if let Some(_) = self
-                        .tokens
-                        .peek_if(|t| t.kind().is_operator() && t.value().unwrap().as_str() == "|")
-                    {
-                        // we have another pipe, meaning another type to the type union, lets parse the next token.
-                        self.skip_whitespace_err(
-                            "Expected a type reference to follow a union type.",
-                        );
-                        if let Some(name) = self.tokens.peek_if(|t| t.kind().is_identifier()) {
-                            // we have a type reference!
-                            union_type
-                                .types
-                                .push(TypeKind::Reference(TypeReference::new(
-                                    name.value().unwrap(),
-                                    self.parse_type_generics(),
-                                )));
-                        } else {
-                            create_report!(
-                                self.context,
-                                self.tokens.first().unwrap().range(),
-                                "Expected a type reference to follow a union type.".to_string(),
-                                "A type reference is expected here.".to_string()
-                            );
-                        }
-                    } else if let Some(_) =
-                        self.tokens.first_if(|t| t.value().unwrap().as_str() == "=")
-                    {
-                        // we have an equals sign, meaning this union is completed.
-                        break;
-                    } else {
-                        create_report!(
-                            self.context,
-                            self.tokens.first().unwrap().range(),
-                            "Expected a type reference to follow a union type.".to_string(),
-                            "A type reference is expected here.".to_string()
-                        );
-                    }
-                }
-
-                // check to see if all types are actually references in the union.
-                // basically checking if the reference is a builtin.
-                for type_kind in union_type.types.iter_mut() {
-                    if let TypeKind::Reference(ref reference) = type_kind {
-                        if let Some(built_in) = BuiltInType::from_string(reference.name.clone()) {
-                            *type_kind = TypeKind::BuiltIn(built_in);
-                        }
-                    }
-                }
-                return Some(TypeKind::Union(Box::new(union_type)));
-            } else {
-                if let Some(ty) = BuiltInType::from_string(name.clone()) {
-                    return Some(TypeKind::BuiltIn(ty));
-                } else {
-                    return Some(TypeKind::Reference(TypeReference::new(
-                        name.clone(),
-                        self.parse_type_generics(),
-                    )));
-                }
+                expected!(
+                    self,
+                    codes::MISSING_COMMA,
+                    "Expected a `,` or `}` after a property.".to_string(),
+                    "A `,` or `}` is expected here.".to_string()
+                );
             }
         }
-        return None;
+        Ok(Some(TypeKind::Object(ObjectShape::new(properties))))
     }
 
-    fn parse_type_generics(&mut self) -> Option<Vec<TypeParam>> {
+    fn type_generics_rule(&mut self) -> ParseResult<Vec<TypeParam>> {
         if let Some(_) = self
             .tokens
             .peek_if(|t| t.kind().is_operator() && t.value().unwrap() == "<")
@@ -1059,8 +2656,8 @@ impl AstGenerator {
             while !self.tokens.is_eof() {
                 self.skip_whitespace_err(
                     "Expected a type paramater to follow a typed parameter list.",
-                );
-                if let Some(kind) = self.parse_type_kind() {
+                )?;
+                if let Some(kind) = self.parse_type_kind()? {
                     generics.push(TypeParam::new(kind));
                 } else if let Some(_) = self
                     .tokens
@@ -1068,22 +2665,22 @@ impl AstGenerator {
                 {
                     // check if the generics list is empty, if so throw an error
                     if generics.is_empty() {
-                        create_report!(
-                            self.context,
-                            self.tokens.first().unwrap().range(),
+                        expected!(
+                            self,
+                            codes::EXPECTED_TYPE,
                             "Expected a type paramater to follow a typed parameter list."
                                 .to_string(),
                             "A type paramater is expected here.".to_string()
                         );
                     } else {
-                        return Some(generics);
+                        return Ok(Some(generics));
                     }
                 } else if let Some(_) = self.tokens.peek_if(|t| t.kind().is_comma()) {
                     continue;
                 } else {
-                    create_report!(
-                        self.context,
-                        self.tokens.first().unwrap().range(),
+                    expected!(
+                        self,
+                        codes::EXPECTED_TYPE,
                         "Expected a type paramater to follow a typed parameter list.".to_string(),
                         "A type paramater is expected here.".to_string()
                     );
@@ -1091,7 +2688,7 @@ impl AstGenerator {
             }
         }
 
-        return None;
+        Ok(None)
     }
 
     /// Parses an expression.
@@ -1100,7 +2697,112 @@ impl AstGenerator {
     /// - `x`
     /// - `x + 5`
     /// - `x + 5 * y`
-    fn parse_expression(&mut self) -> Option<Expression> {
+    fn expression_rule(&mut self) -> ParseResult<Expression> {
+        // check left
+        let start = self.current_range().start;
+        if let Some(left) = self.parse_operand()? {
+            let left = self.type_test(left, start)?;
+            self.skip_whitespace();
+            // check whitespace
+            if let Some(ops) = self.tokens.peek_if(|t| t.kind().is_operator()) {
+                let operator = self.joined_operator(ops.value().unwrap());
+                self.skip_whitespace();
+                if let Some(op) = AnyOperation::from_string(operator) {
+                    // we have an operation!
+                    self.skip_whitespace();
+                    if let Some(right) = self.parse_expression()? {
+                        let mut instruction = Operation::new(left, op, right);
+                        instruction.span = self.span_from(start);
+                        Ok(Some(Expression::Operation(instruction)))
+                    } else {
+                        expected!(
+                            self,
+                            codes::EXPECTED_EXPRESSION,
+                            "Expected an expression to follow an operation.".to_string(),
+                            "An expression is expected here.".to_string()
+                        );
+                    }
+                } else {
+                    create_report!(
+                        self.context,
+                        codes::UNEXPECTED_TOKEN,
+                        ops.range(),
+                        "Unknown operator: {}".to_string(),
+                        ops.value().unwrap()
+                    );
+                }
+            } else {
+                Ok(Some(left))
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Parses the type a value is tested against, when the value is followed by `is` or
+    /// `instanceof`, otherwise the value is returned as it is.
+    ///
+    /// For example:
+    /// - `x is int`
+    /// - `user instanceof Admin`
+    fn type_test(&mut self, value: Expression, start: usize) -> Result<Expression, Report> {
+        let Some((amt, _)) = self.tokens.find_after(
+            |t| t.kind().is_identifier() && matches!(t.text(), Some("is" | "instanceof")),
+            |t| t.kind().is_whitespace(),
+        ) else {
+            return Ok(value);
+        };
+        self.tokens.peek_inc(amt + 1);
+        self.skip_whitespace_err("Expected the type a value is tested against.")?;
+        let Some(ty) = self.parse_single_type()? else {
+            expected!(
+                self,
+                codes::EXPECTED_TYPE,
+                "Expected the type a value is tested against.".to_string(),
+                "A type is expected here, eg: `x is int`.".to_string()
+            );
+        };
+        Ok(Expression::Is(TypeTest {
+            value: Box::new(value),
+            ty,
+            span: self.span_from(start),
+        }))
+    }
+
+    /// The operator that starts with `first`, an operator of more than one character is
+    /// lexed a character at a time, eg: `||` is `|` followed by `|`.
+    fn joined_operator(&mut self, mut first: String) -> String {
+        while let Some(next) = self.tokens.peek_if(|t| {
+            t.kind().is_operator()
+                && AnyOperation::from_string(format!("{}{}", first, t.text().unwrap_or_default()))
+                    .is_some()
+        }) {
+            first.push_str(&next.value().unwrap());
+        }
+        first
+    }
+
+    /// Parses the operand of an operation, `await` binds to the operand alone.
+    /// For example:
+    /// - `a` and `await load()` in `a + await load()`
+    fn operand_rule(&mut self) -> ParseResult<Expression> {
+        if self
+            .tokens
+            .peek_if(|t| t.kind().is_keyword() && t.kind().as_keyword() == KeyWord::Await)
+            .is_some()
+        {
+            self.skip_whitespace_err("Expected an expression to follow an await keyword.")?;
+            match self.parse_operand()? {
+                Some(operand) => return Ok(Some(Expression::Await(Box::new(operand)))),
+                None => expected!(
+                    self,
+                    codes::EXPECTED_EXPRESSION,
+                    "Expected an expression to follow an await keyword.".to_string(),
+                    "An expression is expected here.".to_string()
+                ),
+            }
+        }
+
         // We're storing this operand in a variable so we can return it later.
         // We will be using this to parse operations.
         let mut left: Option<Expression> = None;
@@ -1108,138 +2810,125 @@ impl AstGenerator {
         // parse a statement expression
         // this needs to be before object parsing because
         // object expressions will assume a block check has already taken place.
-        if let Some(statement_expr) = self.parse_statement() {
+        if let Some(statement_expr) = self.parse_statement()? {
             left = Some(Expression::Statement(Box::new(statement_expr)));
         }
 
         // parse a call expression
-        if let Some(call_expr) = self.parse_call_expression() {
+        if let Some(call_expr) = self.parse_call_expression()? {
             left = Some(Expression::Call(call_expr));
         }
 
         // parse a member expression
-        if let Some(member_expr) = self.parse_member_expression() {
+        if let Some(member_expr) = self.parse_member_expression()? {
             left = Some(Expression::Member(member_expr));
         }
 
         // parse a new expression
-        if let Some(new_expr) = self.parse_new_expression() {
+        if let Some(new_expr) = self.parse_new_expression()? {
             left = Some(Expression::New(new_expr));
         }
 
         // parse an array
-        if let Some(array_expr) = self.parse_array_expression() {
+        if let Some(array_expr) = self.parse_array_expression()? {
             left = Some(Expression::Array(array_expr));
         }
 
-        if let Some(object_expr) = self.parse_object_expression() {
+        if let Some(values) = self.parse_tuple_expression()? {
+            left = Some(Expression::Tuple(values));
+        }
+
+        if let Some(object_expr) = self.parse_object_expression()? {
             left = Some(Expression::Object(object_expr));
         }
 
-        if let Some(literal_expr) = self.parse_literal_expression() {
+        if let Some(literal_expr) = self.parse_literal_expression()? {
             left = Some(Expression::Literal(literal_expr));
         }
 
-        // check left
-        if let Some(left) = left {
-            self.skip_whitespace();
-            // check whitespace
-            if let Some(ops) = self.tokens.peek_if(|t| t.kind().is_operator()) {
-                self.skip_whitespace();
-                if let Some(op) = AnyOperation::from_string(ops.value().unwrap()) {
-                    // we have an operation!
-                    self.skip_whitespace();
-                    if let Some(right) = self.parse_expression() {
-                        let instruction = Operation::new(left, op, right);
-                        return Some(Expression::Operation(instruction));
-                    } else {
-                        create_report!(
-                            self.context,
-                            self.tokens.first().unwrap().range(),
-                            "Expected an expression to follow an operation.".to_string(),
-                            "An expression is expected here.".to_string()
-                        );
-                    }
-                } else {
-                    create_report!(
-                        self.context,
-                        ops.range(),
-                        "Unknown operator: {}".to_string(),
-                        ops.value().unwrap()
-                    );
-                }
-            } else {
-                return Some(left);
-            }
-        } else {
-            return None;
-        }
+        Ok(left)
     }
 
-    fn parse_call_expression(&mut self) -> Option<Call> {
+    fn call_expression_rule(&mut self) -> ParseResult<Call> {
         // parse a call expression
         if let Some(identifier) = self.tokens.first_if(|t| t.kind().is_identifier()) {
+            if let Some(generics) = self.parse_call_generics() {
+                // a generic call, eg: `map<int, string>(items, f)`
+                let args = self.parse_call_arguments()?.unwrap();
+                let mut call = Call::new(identifier.symbol().unwrap(), args);
+                call.generics = generics;
+                call.span = identifier.range().start..self.tokens.prev().unwrap().range().end;
+                return Ok(Some(call));
+            }
             // we have an identifier, we need to try to parse function arguments now.
-            if let Some(args) = self.parse_function_call_inputs() {
+            if let Some(args) = self.parse_function_call_inputs()? {
                 // This is definitely a function call.
-                return Some(Call::new(identifier.value().unwrap(), args));
+                let mut call = Call::new(identifier.symbol().unwrap(), args);
+                call.span = identifier.range().start..self.tokens.prev().unwrap().range().end;
+                return Ok(Some(call));
             } else {
                 // This probably isn't a function call.
-                return None;
+                return Ok(None);
             }
         }
 
-        return None;
+        Ok(None)
     }
 
-    fn parse_member_expression(&mut self) -> Option<MemberListNode> {
+    fn member_expression_rule(&mut self) -> ParseResult<MemberListNode> {
         // parse a member expression
-        if let Some(identifier) = self.tokens.first_if(|t| t.kind().is_identifier()) {
+        // the origin of a static member can be qualified, eg: `models\User::find()`
+        let len = self.qualified_name_len(0);
+        if len > 0 {
             // we have an identifier, we need to try to parse member expressions now.
             // we need to verify that this is a member expression
             // we need to check if the next token is a period
 
-            if let Some(accessor) = self.tokens.second_if(|t| t.kind().is_accessor()) {
+            if let Some(accessor) = self.tokens.nth_if(len, |t| t.kind().is_accessor()) {
                 let access_kind = match accessor.value().unwrap().as_str() {
                     "." => MemberLookup::Dynamic,
                     "::" => MemberLookup::Static,
                     _ => unreachable!(),
                 };
+                if len > 1 && !matches!(access_kind, MemberLookup::Static) {
+                    return Ok(None);
+                }
 
-                self.tokens.peek_inc(2);
+                let identifier = self.eat_qualified_name(len);
+                self.tokens.peek();
                 // we have a period, we need to parse a member expression
                 // we need to parse a member expression
-                if let Some(member_expr) = self.parse_expression() {
+                if let Some(member_expr) = self.parse_expression()? {
                     // we have a member expression, we need to create a member list node
-                    println!("Parsed a member node!!");
-                    return Some(MemberListNode::new(
+                    return Ok(Some(MemberListNode::new(
                         member_expr,
                         identifier.clone(),
                         access_kind,
-                    ));
+                    )));
                 } else {
                     // we don't have a member expression, we need to report an error
-                    create_report!(
-                        self.context,
-                        self.tokens.first().unwrap().range(),
+                    expected!(
+                        self,
+                        codes::EXPECTED_EXPRESSION,
                         "Expected an expression to follow a property member.".to_string(),
                         "An expression was expected here.".to_string()
                     );
                 }
             } else {
                 // we don't have a period, this is probably not a member expression
-                return None;
+                return Ok(None);
             }
         }
 
-        return None;
+        Ok(None)
     }
 
-    fn parse_new_expression(&mut self) -> Option<NewCall> {
-        if let Some(_) = self
+    fn new_expression_rule(&mut self) -> ParseResult<NewCall> {
+        if let Some(keyword) = self
             .tokens
             .first_if(|t| t.kind().is_keyword() && t.kind().as_keyword().is_new())
         {
+            let start = keyword.range().start;
             // we have a new keyword, we need to parse a name.
             if let Some((inc, name)) = self.tokens.find_after_nth(
                 1,
@@ -1247,15 +2936,39 @@ impl AstGenerator {
                 |t| t.kind().is_whitespace(),
             ) {
                 self.tokens.peek_inc(inc);
+                // a class qualified with its namespace, eg: `new models\User()`
+                let len = self.qualified_name_len(0);
+                let name = match len {
+                    1 => name.value().unwrap(),
+                    _ => {
+                        // the namespaces and their backslashes, the name itself is left.
+                        let qualified = self.eat_qualified_name(len - 1).value().unwrap();
+                        format!(
+                            "{}{}",
+                            qualified,
+                            self.tokens.first().unwrap().value().unwrap()
+                        )
+                    }
+                };
+                if let Some(generics) = self.parse_call_generics() {
+                    // a generic class, eg: `new Box<int>(5)`
+                    let mut call =
+                        NewCall::new(name.clone(), self.parse_call_arguments()?.unwrap());
+                    call.generics = generics;
+                    call.span = self.span_from(start);
+                    return Ok(Some(call));
+                }
                 // we have a name, we need to parse a function call inputs.
-                if let Some(args) = self.parse_function_call_inputs() {
+                if let Some(args) = self.parse_function_call_inputs()? {
                     // we have a function call inputs, we need to create a new call.
-                    return Some(NewCall::new(name.value().unwrap(), args));
+                    let mut call = NewCall::new(name, args);
+                    call.span = self.span_from(start);
+                    return Ok(Some(call));
                 } else {
                     // we don't have a function call inputs, we need to report an error.
-                    create_report!(
-                        self.context,
-                        self.tokens.first().unwrap().range(),
+                    expected!(
+                        self,
+                        codes::INVALID_SYNTAX,
                         "Expected a function call inputs to follow a new expression.".to_string(),
                         "Function inputs expected here.".to_string()
                     );
@@ -1264,38 +2977,89 @@ impl AstGenerator {
                 // we don't have a name, we need to report an error.
                 create_report!(
                     self.context,
+                    codes::EXPECTED_NAME,
                     self.tokens.second().unwrap().range(),
                     "Expected a name to follow a new expression.".to_string(),
                     "A name was expected here.".to_string()
                 );
             }
         }
-        return None;
+        Ok(None)
+    }
+
+    /// Parses a tuple literal, eg: `(1, "a")`
+    fn tuple_expression_rule(&mut self) -> ParseResult<Vec<Expression>> {
+        let open = match self.tokens.peek_if(|t| t.kind().is_left_parenthesis()) {
+            Some(open) => open.range(),
+            None => return Ok(None),
+        };
+        let mut values: Vec<Expression> = Vec::new();
+        loop {
+            self.skip_whitespace_err("Tuples must be closed.")?;
+            if let Some(close) = self.tokens.peek_if(|t| t.kind().is_right_parenthesis()) {
+                if values.len() < 2 {
+                    create_report!(
+                        self.context,
+                        codes::INVALID_SYNTAX,
+                        open.start..close.range().end,
+                        "A tuple needs at least two values.".to_string(),
+                        "Parentheses only group values of a tuple.".to_string()
+                    );
+                }
+                return Ok(Some(values));
+            }
+            match self.parse_expression()? {
+                Some(value) => values.push(value),
+                None => expected!(
+                    self,
+                    codes::INVALID_SYNTAX,
+                    "Expected a value in a tuple.".to_string(),
+                    "A value, `,` or `)` is expected here.".to_string()
+                ),
+            }
+            self.skip_whitespace_err("Tuples must be closed.")?;
+            if self.tokens.peek_if(|t| t.kind().is_comma()).is_none()
+                && self
+                    .tokens
+                    .first_if(|t| t.kind().is_right_parenthesis())
+                    .is_none()
+            {
+                expected!(
+                    self,
+                    codes::MISSING_COMMA,
+                    "A comma is required to seperate tuple values.".to_string(),
+                    "A comma is expected here.".to_string()
+                );
+            }
+        }
     }
 
-    fn parse_array_expression(&mut self) -> Option<Array> {
-        if let Some(_) = self.tokens.peek_if(|t| t.kind().is_left_bracket()) {
+    fn array_expression_rule(&mut self) -> ParseResult<Array> {
+        if let Some(open) = self.tokens.peek_if(|t| t.kind().is_left_bracket()) {
+            let start = open.range().start;
             // inside array
             let mut elements: Vec<Expression> = Vec::new();
             while !self.tokens.is_eof() {
-                self.skip_whitespace_err("Array's must be closed.");
-                if let Some(element) = self.parse_expression() {
+                self.skip_whitespace_err("Array's must be closed.")?;
+                if let Some(element) = self.parse_argument()? {
                     // we have an expression, we need to parse a comma
-                    self.skip_whitespace_err("Array's must be closed.");
+                    self.skip_whitespace_err("Array's must be closed.")?;
                     if let Some(_) = self.tokens.peek_if(|t| t.kind().is_comma()) {
                         elements.push(element);
                     } else {
                         // ok, check if the next token is a right bracket, if so, we're done.
                         // otherwise error
-                        self.skip_whitespace_err("Array's must be closed.");
+                        self.skip_whitespace_err("Array's must be closed.")?;
                         if let Some(_) = self.tokens.peek_if(|t| t.kind().is_right_bracket()) {
                             // we have a right bracket, we can return the inputs
                             elements.push(element);
-                            return Some(Array::new(elements, None));
+                            let mut array = Array::new(elements, None);
+                            array.span = self.span_from(start);
+                            return Ok(Some(array));
                         } else {
-                            create_report!(
-                                self.context,
-                                self.tokens.first().unwrap().range(),
+                            expected!(
+                                self,
+                                codes::MISSING_COMMA,
                                 "A comma is required to seperate array elements.".to_string(),
                                 "A comma is expected here.".to_string()
                             );
@@ -1303,40 +3067,43 @@ impl AstGenerator {
                     }
                 } else if let Some(_) = self.tokens.peek_if(|t| t.kind().is_right_bracket()) {
                     // end of array
-                    return Some(Array::new(elements, None));
+                    let mut array = Array::new(elements, None);
+                    array.span = self.span_from(start);
+                    return Ok(Some(array));
                 } else {
                     // we don't have an expression, we need to report an error.
-                    create_report!(
-                        self.context,
-                        self.tokens.first().unwrap().range(),
+                    expected!(
+                        self,
+                        codes::EXPECTED_EXPRESSION,
                         "Expected an expression to follow an array element.".to_string(),
-                        format!(
-                            "Unexpected Token: {}",
-                            self.tokens.first().unwrap().kind().to_string()
-                        )
+                        format!("Unexpected Token: {}", self.current_kind())
                     );
                 }
             }
         }
-        return None;
+        Ok(None)
     }
 
-    fn parse_object_expression(&mut self) -> Option<Object> {
-        if let Some(_) = self.tokens.peek_if(|t| t.kind().is_left_brace()) {
+    fn object_expression_rule(&mut self) -> ParseResult<Object> {
+        if let Some(open) = self.tokens.peek_if(|t| t.kind().is_left_brace()) {
             // this is definitely an object body.
             let mut object: Object = Object::empty();
+            object.span = open.range();
 
             while !self.tokens.is_eof() {
                 // purge whitespace.
-                self.skip_whitespace_err("Object body must be closed.");
+                self.skip_whitespace_err("Object body must be closed.")?;
                 if let Some(property) = self.tokens.peek_if(|t| t.kind().is_identifier()) {
                     // the property name was found, now we need to parse a colon.
                     if let Some(_) = self.tokens.peek_if(|t| t.kind().is_colon()) {
                         // we have a colon, we need to parse an expression.
-                        self.skip_whitespace_err("Object body must be closed.");
-                        if let Some(expression) = self.parse_expression() {
+                        self.skip_whitespace_err("Object body must be closed.")?;
+                        if let Some(expression) = self.parse_expression()? {
                             // we have an expression, we need to add the property to the object.
-                            let prop = ObjectProperty::new(property.value().unwrap(), expression);
+                            let mut prop =
+                                ObjectProperty::new(property.value().unwrap(), expression);
+                            prop.span =
+                                property.range().start..self.tokens.prev().unwrap().range().end;
 
                             // check if we have a comma, if so, we need to parse another property.
                             // otherwise we need to check if we have a right brace, if so, we're done.
@@ -1345,17 +3112,19 @@ impl AstGenerator {
                                 object.properties.push(prop);
                             } else {
                                 // check for a right brace, if so, we're done.
-                                self.skip_whitespace_err("Object body must be closed.");
-                                if let Some(_) = self.tokens.peek_if(|t| t.kind().is_right_brace())
+                                self.skip_whitespace_err("Object body must be closed.")?;
+                                if let Some(close) =
+                                    self.tokens.peek_if(|t| t.kind().is_right_brace())
                                 {
                                     // we have a right brace, we're done.
+                                    object.span.end = close.range().end;
                                     object.properties.push(prop);
-                                    return Some(object);
+                                    return Ok(Some(object));
                                 } else {
                                     // we don't have a right brace, we need to report an error.
-                                    create_report!(
-                                        self.context,
-                                        self.tokens.first().unwrap().range(),
+                                    expected!(
+                                        self,
+                                        codes::UNCLOSED_DELIMITER,
                                         "Expected a right brace to close an object body."
                                             .to_string(),
                                         "A right brace was expected here.".to_string()
@@ -1364,43 +3133,41 @@ impl AstGenerator {
                             }
                         } else {
                             // we don't have an expression, we need to report an error.
-                            create_report!(
-                                self.context,
-                                self.tokens.first().unwrap().range(),
+                            expected!(
+                                self,
+                                codes::EXPECTED_EXPRESSION,
                                 "Expected an expression to follow a property.".to_string(),
                                 "An expression was expected here.".to_string()
                             );
                         }
                     } else {
                         // we don't have a colon, we need to report an error.
-                        create_report!(
-                            self.context,
-                            self.tokens.first().unwrap().range(),
+                        expected!(
+                            self,
+                            codes::INVALID_SYNTAX,
                             "Expected a colon to follow a property name.".to_string(),
-                            format!(
-                                "Unexpected Token: {}",
-                                self.tokens.first().unwrap().kind().to_string()
-                            )
+                            format!("Unexpected Token: {}", self.current_kind())
                         );
                     }
-                } else if let Some(_) = self.tokens.peek_if(|t| t.kind().is_right_brace()) {
+                } else if let Some(close) = self.tokens.peek_if(|t| t.kind().is_right_brace()) {
                     // end of object
-                    return Some(object);
+                    object.span.end = close.range().end;
+                    return Ok(Some(object));
                 } else {
                     // we don't have an object property, we need to report an error.
-                    create_report!(
-                        self.context,
-                        self.tokens.first().unwrap().range(),
+                    expected!(
+                        self,
+                        codes::INVALID_SYNTAX,
                         "Expected an object property to follow an object element.".to_string(),
                         "An object property was expected here.".to_string()
                     );
                 }
             }
         }
-        return None;
+        Ok(None)
     }
 
-    fn parse_literal_expression(&mut self) -> Option<Literal> {
+    fn literal_expression_rule(&mut self) -> ParseResult<Literal> {
         // we have a literal, we need to parse a value.
         // a literal is either a string, number, boolean or null
         // either way we need to check if the next token is a identifier.
@@ -1410,27 +3177,99 @@ impl AstGenerator {
                 || t.kind().is_string()
                 || t.kind().is_boolean()
         }) {
-            return Some(Literal::new(v.value().unwrap(), None));
+            // identifiers are left untyped, they are resolved later on.
+            let ty = match v.kind() {
+                TokenType::Identifier if v.text() == Some("null") => {
+                    Some(TypeKind::BuiltIn(BuiltInType::Null))
+                }
+                TokenType::Number => {
+                    if v.value().unwrap().contains('.') {
+                        Some(TypeKind::BuiltIn(BuiltInType::Float))
+                    } else {
+                        Some(TypeKind::BuiltIn(BuiltInType::Int))
+                    }
+                }
+                TokenType::StringLiteral => Some(TypeKind::BuiltIn(BuiltInType::String)),
+                TokenType::Boolean => Some(TypeKind::BuiltIn(BuiltInType::Bool)),
+                _ => None,
+            };
+            if v.kind().is_string() {
+                // strings hold their cooked value, backends escape it for their target.
+                if let Some(cooked) = v.cooked() {
+                    let mut literal = Literal::new(cooked, ty);
+                    literal.span = v.range();
+                    return Ok(Some(literal));
+                }
+                if let Err(error) = unescape(&v.value().unwrap()) {
+                    let start = v.range().start + 1;
+                    create_report!(
+                        self.context,
+                        codes::INVALID_ESCAPE,
+                        start + error.range.start..start + error.range.end,
+                        error.message,
+                        "This escape sequence is invalid.".to_string()
+                    );
+                }
+            }
+            let mut literal = Literal::new(v.value().unwrap(), ty);
+            literal.span = v.range();
+            Ok(Some(literal))
         } else {
-            return None;
+            Ok(None)
         }
     }
 
+    /// Parses the type arguments that follow the name of a call, eg: `<int>` in `first<int>(items)`.
+    /// A `<` is also the start of a comparison, so the stream is only advanced when
+    /// the type arguments are directly followed by the arguments of the call.
+    /// On success the stream is left at the opening parenthesis.
+    fn parse_call_generics(&mut self) -> Option<Vec<TypeKind>> {
+        self.tokens
+            .second_if(|t| t.kind().is_operator() && t.text() == Some("<"))?;
+        let generics = self.try_parse(|generator| {
+            generator.tokens.peek();
+            let params = generator.parse_type_generics()?;
+            match generator
+                .tokens
+                .first_if(|t| t.kind().is_left_parenthesis())
+            {
+                Some(_) => Ok(params),
+                None => Ok(None),
+            }
+        });
+        let params = generics.ok().flatten()?;
+        Some(params.into_iter().map(|p| p.kind).collect())
+    }
+
     /// parses function inputs (aka arguments)
-    fn parse_function_call_inputs(&mut self) -> Option<Vec<Expression>> {
+    fn function_call_inputs_rule(&mut self) -> ParseResult<Vec<Expression>> {
         // parse a function input
         // we need to check for a parenthesis
         if let Some(_) = self.tokens.second_if(|t| t.kind().is_left_parenthesis()) {
             // ok we have a parenthesis!
             // lets peek to the next token now.
-            self.tokens.peek_inc(2);
+            self.tokens.peek();
+            return self.parse_call_arguments();
+        }
+
+        Ok(None)
+    }
+
+    /// Parses the arguments of a call, starting at the opening parenthesis.
+    fn call_arguments_rule(&mut self) -> ParseResult<Vec<Expression>> {
+        if self
+            .tokens
+            .first_if(|t| t.kind().is_left_parenthesis())
+            .is_some()
+        {
+            self.tokens.peek();
             // we're inside a parenthesis, we need to parse an expression now.
             let mut inputs: Vec<Expression> = Vec::new();
             while !self.tokens.is_eof() {
                 // we need to parse an expression
-                self.skip_whitespace_err("Function arguments must be closed.");
+                self.skip_whitespace_err("Function arguments must be closed.")?;
 
-                if let Some(expr) = self.parse_expression() {
+                if let Some(expr) = self.parse_argument()? {
                     // we have an expression, we need to parse a comma
                     if let Some(_) = self.tokens.peek_if(|t| t.kind().is_comma()) {
                         inputs.push(expr);
@@ -1440,11 +3279,11 @@ impl AstGenerator {
                         if let Some(_) = self.tokens.peek_if(|t| t.kind().is_right_parenthesis()) {
                             // we have a right parenthesis, we can return the inputs
                             inputs.push(expr);
-                            return Some(inputs);
+                            return Ok(Some(inputs));
                         } else {
-                            create_report!(
-                                self.context,
-                                self.tokens.first().unwrap().range(),
+                            expected!(
+                                self,
+                                codes::MISSING_COMMA,
                                 "Expected a comma to follow a function input.".to_string(),
                                 "A comma is expected here.".to_string()
                             );
@@ -1452,53 +3291,251 @@ impl AstGenerator {
                     }
                 } else if let Some(_) = self.tokens.peek_if(|t| t.kind().is_right_parenthesis()) {
                     // we have a right parenthesis, we can return the inputs
-                    return Some(inputs);
+                    return Ok(Some(inputs));
                 } else {
                     // we don't have an expression, we need to report an error
-                    create_report!(
-                        self.context,
-                        self.tokens.first().unwrap().range(),
+                    expected!(
+                        self,
+                        codes::EXPECTED_EXPRESSION,
                         "Expected an expression to follow a function input.".to_string(),
                         "An expression is expected here.".to_string()
                     );
                 }
             }
 
-            create_report!(
-                self.context,
-                self.tokens.first().unwrap().range(),
+            expected!(
+                self,
+                codes::EXPECTED_EXPRESSION,
                 "Expected an expression to follow a function input.".to_string(),
                 "An expression is expected here.".to_string()
             );
         }
 
-        return None;
+        Ok(None)
     }
 
-    fn skip_whitespace_err(&mut self, err: &'static str) {
-        let start = self.tokens.first().unwrap().range().start;
-        match self
+    /// Parses an argument of a call or an element of an array, its values can be spread.
+    /// For example:
+    /// - `...names` in `greet(...names)`
+    fn argument_rule(&mut self) -> ParseResult<Expression> {
+        if self.tokens.peek_if(|t| t.kind().is_spread()).is_none() {
+            return self.parse_expression();
+        }
+        match self.parse_expression()? {
+            Some(spread) => Ok(Some(Expression::Spread(Box::new(spread)))),
+            None => expected!(
+                self,
+                codes::EXPECTED_EXPRESSION,
+                "Expected an expression to spread.".to_string(),
+                "An expression is expected here.".to_string()
+            ),
+        }
+    }
+
+    /// Runs a rule, recording its entry and exit when tracing is enabled.
+    fn traced<T>(
+        &mut self,
+        rule: &'static str,
+        parse: impl FnOnce(&mut Self) -> ParseResult<T>,
+    ) -> ParseResult<T> {
+        if self.trace.is_none() {
+            return parse(self);
+        }
+        let (cursor, token) = (self.tokens.eaten(), self.tokens.first());
+        self.trace.as_mut().unwrap().enter(rule, cursor, token);
+        let result = parse(self);
+        let outcome = match &result {
+            Ok(Some(_)) => Outcome::Matched,
+            Ok(None) => Outcome::Rejected,
+            Err(report) => Outcome::Failed(
+                report
+                    .snippets
+                    .first()
+                    .map_or(report.message.clone(), |s| s.message.clone()),
+            ),
+        };
+        let (cursor, token) = (self.tokens.eaten(), self.tokens.first());
+        self.trace
+            .as_mut()
+            .unwrap()
+            .exit(rule, cursor, token, outcome);
+        result
+    }
+
+    /// Parses a rule in a context, eg: the body of a function in `ContextFlag::InFunction`.
+    fn in_context<T>(&mut self, flag: ContextFlag, rule: impl FnOnce(&mut Self) -> T) -> T {
+        self.flags.push(flag);
+        let result = rule(self);
+        self.flags.pop();
+        result
+    }
+
+    /// The innermost class or function the parser is in, `ContextFlag::TopLevel` outside of both.
+    pub fn context_flag(&self) -> ContextFlag {
+        self.flags.last().cloned().unwrap_or(ContextFlag::TopLevel)
+    }
+
+    /// Reports a statement where the context doesn't allow it, at its keyword.
+    fn misplaced<T>(&self, keyword: &Token, message: String) -> Result<T, Report> {
+        let place = match self.context_flag() {
+            ContextFlag::InFunction => "in a function",
+            ContextFlag::InClass => "in a class",
+            _ => "at the top level",
+        };
+        create_report!(
+            self.context,
+            codes::MISPLACED_STATEMENT,
+            keyword.range(),
+            message,
+            format!("This is {}.", place)
+        )
+    }
+
+    /// Runs a rule that may consume tokens before it knows whether it matches,
+    /// the stream is rewound to where the rule started unless it matched.
+    ///
+    /// **Example:**
+    /// ```text
+    /// // `var (a, b) = pair;` and `var a = 1;` both start with `var`.
+    /// let destructure = self.try_parse(|generator| generator.parse_destructure())?;
+    /// ```
+    fn try_parse<T>(&mut self, rule: impl FnOnce(&mut Self) -> ParseResult<T>) -> ParseResult<T> {
+        let (checkpoint, skipped) = (self.tokens.checkpoint(), self.skipped.clone());
+        let result = rule(self);
+        if !matches!(result, Ok(Some(_))) {
+            self.tokens.rewind(checkpoint);
+            self.skipped = skipped;
+        }
+        result
+    }
+
+    /// The whitespace and comments skipped last.
+    pub fn skipped(&self) -> &Skipped {
+        &self.skipped
+    }
+
+    /// Skips the whitespace and comments before the next token and keeps what
+    /// was skipped, every other way of skipping trivia goes through this.
+    fn skip_trivia(&mut self) -> &Skipped {
+        let start = self.tokens.first().map_or(0, |t| t.range().start);
+        let mut skipped = Skipped::at(start);
+        while let Some(token) = self
             .tokens
-            .peek_until(|t| !t.kind().is_whitespace() && !t.kind().is_comment())
+            .peek_if(|t| t.kind().is_whitespace() || t.kind().is_comment())
         {
-            None => {
-                create_report!(
-                    self.context,
-                    start..self.context.source.get_contents().unwrap().len(),
-                    err.to_string()
-                );
-            }
-            _ => (),
+            skipped.push(&token);
         }
+        self.skipped = skipped;
+        &self.skipped
     }
 
-    fn skip_whitespace(&mut self) {
-        self.tokens.peek_until(|t| {
-            if t.kind().is_whitespace() || t.kind().is_comment() {
+    /// Skips trivia, reporting `err` if the source ends before another token.
+    fn skip_whitespace_err(&mut self, err: &'static str) -> Result<(), Report> {
+        self.skip_trivia();
+        if self.tokens.is_eof() {
+            return Err(self.unexpected_eof(err));
+        }
+        Ok(())
+    }
+
+    /// Whether only whitespace and comments are left.
+    fn at_eof(&self) -> bool {
+        let mut n = 0;
+        while let Some(token) = self.tokens.nth(n) {
+            if !token.kind().is_whitespace() && !token.kind().is_comment() {
                 return false;
-            } else {
-                return true;
             }
-        });
+            n += 1;
+        }
+        true
+    }
+
+    /// The report of a source that ended where `expected` was, it points at the last
+    /// character of the source that isn't whitespace instead of at a token.
+    ///
+    /// **Example:**
+    /// ```text
+    /// // class Foo
+    /// self.unexpected_eof("Expected a class body."); // error[E0002]: Unexpected end of file.
+    /// ```
+    pub(crate) fn unexpected_eof(&self, expected: &str) -> Report {
+        let source = &self.context.buffer.source;
+        let end = source.trim_end().chars().count();
+        Report::new()
+            .set_code(codes::UNEXPECTED_EOF)
+            .set_source(self.context.buffer.clone())
+            .set_name(self.context.source.name.clone())
+            .set_message("Unexpected end of file.".to_string())
+            .make_snippet(
+                end.saturating_sub(1)..end,
+                expected.to_string(),
+                Some("The file ends after this.".to_string()),
+            )
+    }
+
+    /// The number of tokens of a name from the `nth` token, with the namespaces it is
+    /// qualified with, eg: 3 for `models\User`. `0` when there is no name.
+    fn qualified_name_len(&self, nth: usize) -> usize {
+        if self
+            .tokens
+            .nth_if(nth, |t| t.kind().is_identifier())
+            .is_none()
+        {
+            return 0;
+        }
+        let mut len = 1;
+        while self
+            .tokens
+            .nth_if(nth + len, |t| t.kind().is_backslash())
+            .is_some()
+            && self
+                .tokens
+                .nth_if(nth + len + 1, |t| t.kind().is_identifier())
+                .is_some()
+        {
+            len += 2;
+        }
+        len
+    }
+
+    /// Consumes a name of `len` tokens, see `qualified_name_len`, as one identifier
+    /// whose value is the whole name.
+    fn eat_qualified_name(&mut self, len: usize) -> Token {
+        let mut name = self.tokens.peek().unwrap();
+        for _ in 1..len {
+            let token = self.tokens.peek().unwrap();
+            let part = match token.kind().is_backslash() {
+                true => "\\".to_string(),
+                false => token.value().unwrap_or_default(),
+            };
+            name.2 = Some(format!("{}{}", name.text().unwrap_or_default(), part).into());
+            name.1.end = token.range().end;
+        }
+        name
+    }
+
+    /// The range from `start` to the end of the last token that was eaten.
+    fn span_from(&self, start: usize) -> Range<usize> {
+        start..self.tokens.prev().map_or(start, |token| token.range().end)
+    }
+
+    /// The range of the next token, errors point at it.
+    /// At the end of the source, this is the range of the last token.
+    fn current_range(&self) -> Range<usize> {
+        self.tokens
+            .first()
+            .or_else(|| self.tokens.prev())
+            .map_or(0..0, |token| token.range())
+    }
+
+    /// The kind of the next token, for messages about what was found instead.
+    fn current_kind(&self) -> String {
+        self.tokens
+            .first()
+            .map_or("end of file".to_string(), |token| token.kind().to_string())
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.skip_trivia();
     }
 }
@@ -1,18 +1,49 @@
-use crate::util::TokenStream;
+use std::path::PathBuf;
+#[cfg(feature = "process")]
+use std::process;
+#[cfg(feature = "threads")]
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use crate::{
+    project::config::Config,
+    report::{codes, diagnostic::Diagnostic, Report},
+    util::{
+        provider::{collect_sources, OsProvider, SourceProvider},
+        TokenStream,
+    },
+};
 
 use self::{
-    context::{ContextStore, SourceOrigin},
+    context::{Context, ContextStore, SourceOrigin},
     generator::AstGenerator,
+    trace::ParseTrace,
 };
 
 use super::{
-    ast::AstBody,
-    lexer::{analysis::analyze, token::Token, tokenizer::tokenize},
+    ast::{types::TypeStore, AstBody},
+    eval::check_consts,
+    infer::{
+        awaits::check_awaits, checker::check_types, generics::check_generics, infer_variables,
+        nullability::check_nulls, purity::infer_purity, shapes::check_shapes,
+    },
+    lexer::{analysis::analyze, tokenizer::tokenize},
+    lints::unused::check_unused_variables,
+    resolve::{
+        cycles::find_cycles, inheritance::check_inheritance, members::check_members, resolve,
+    },
     CompilerOptions,
 };
 
 pub mod context;
+pub mod fix_its;
 pub mod generator;
+pub mod trace;
 
 /// The parser struct.
 /// This contains the context of the AST as well as information
@@ -20,6 +51,12 @@ pub mod generator;
 pub struct Parser {
     options: CompilerOptions,
     contexts: ContextStore,
+    /// The trace of the last script parsed, when `trace_parse` is set.
+    trace: Option<ParseTrace>,
+    /// The warnings and notices of the last script parsed.
+    warnings: Vec<Diagnostic>,
+    /// Whether or not the declarations of the include paths were parsed.
+    included: bool,
 }
 
 impl Parser {
@@ -27,33 +64,418 @@ impl Parser {
         Parser {
             options,
             contexts: ContextStore::new(),
+            trace: None,
+            warnings: Vec::new(),
+            included: false,
         }
     }
 
+    /// A parser with the compiler options of a project, see `project::config::Config`.
+    pub fn with_config(config: &Config) -> Self {
+        Self::new(config.options.clone())
+    }
+
+    /// Parses a single script, printing the report and exiting the process
+    /// if the script is invalid.
+    #[cfg(feature = "process")]
     pub fn parse_script(&mut self, name: String, source: String) -> AstBody {
-        // create a source origin for the script
-        let source_origin = SourceOrigin::new_virtual(name, source.clone());
-        // because we're going to be parsing a single script, we can use a new astgenerator.
-        let mut ast_generator = AstGenerator::new(source_origin, self.contexts.next_context_id());
-        // add the generators context to our parser.
-        self.contexts.add_context(&mut ast_generator.context);
+        match self.try_parse_script(name, source) {
+            Ok(ast) => ast,
+            Err(report) => {
+                report.print();
+                process::exit(1);
+            }
+        }
+    }
+
+    /// Parses a single script, returning the report of the first error found.
+    pub fn try_parse_script(&mut self, name: String, source: String) -> Result<AstBody, Report> {
+        self.parse_includes_from(&OsProvider)?;
+
+        let id = self.contexts.reserve(1).start;
+        let parsed = parse_source(&self.options, &name, &source, id);
+        self.trace = parsed.trace;
+        self.warnings = parsed.warnings;
+        self.merge(parsed.context, &parsed.result);
+        parsed.result
+    }
+
+    /// Parses scripts that don't depend on each other on as many threads as
+    /// `CompilerOptions::jobs`, the scripts are given by name and source.
+    /// Without the `threads` feature they are parsed one after the other.
+    /// Every script gets the context id it would get if the scripts were parsed in order,
+    /// and the contexts are added in that order, so the store is the same on every run.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::compiler::{parser::Parser, CompilerOptions};
+    /// # let mut parser = Parser::new(CompilerOptions::default());
+    /// # let (a, b) = ("var x = 1;".to_string(), "var y = 2;".to_string());
+    /// let scripts = vec![("a.surn".to_string(), a), ("b.surn".to_string(), b)];
+    /// for script in parser.parse_scripts(scripts)? {
+    ///     if let Err(report) = script.result {
+    ///         report.print();
+    ///     }
+    /// }
+    /// # Ok::<(), surn::report::Report>(())
+    /// ```
+    pub fn parse_scripts(
+        &mut self,
+        scripts: Vec<(String, String)>,
+    ) -> Result<Vec<ParsedScript>, Report> {
+        self.parse_includes_from(&OsProvider)?;
+
+        let ids = self.contexts.reserve(scripts.len());
+        let parsed = parse_sources(&self.options, &scripts, ids.start);
+
+        self.warnings.clear();
+        let mut parsed_scripts = Vec::new();
+        for ((name, _), parsed) in scripts.into_iter().zip(parsed) {
+            self.trace = parsed.trace;
+            self.warnings.extend(parsed.warnings.iter().cloned());
+            self.merge(parsed.context, &parsed.result);
+            parsed_scripts.push(ParsedScript {
+                name,
+                result: parsed.result,
+                warnings: parsed.warnings,
+            });
+        }
+        Ok(parsed_scripts)
+    }
+
+    /// Adds the context of a parsed script to the store, with its body when it parsed.
+    /// The context of a script parsed before with the same name is dropped.
+    fn merge(&mut self, context: Option<Context>, result: &Result<AstBody, Report>) {
+        if let Some(context) = context {
+            let id = context.origin;
+            self.contexts.insert(context);
+            if let Ok(body) = result {
+                self.contexts.set_body(id, body.clone());
+            }
+            self.contexts.remove_replaced();
+        }
+    }
+
+    /// Parses every source under the include paths into its own context, reading them with
+    /// a provider, so the symbols they declare can be found. Declarations aren't checked.
+    /// The include paths are parsed once, scripts read them from the disk when they weren't.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::compiler::{parser::Parser, CompilerOptions};
+    /// # use surn::util::provider::MemoryProvider;
+    /// # let mut parser = Parser::new(CompilerOptions::default());
+    /// let files = MemoryProvider::new();
+    /// files.insert("lib/math.surn", "fn add(a: int, b: int): int { return a + b; }");
+    /// parser.parse_includes_from(&files)?;
+    /// # Ok::<(), surn::report::Report>(())
+    /// ```
+    pub fn parse_includes_from(&mut self, provider: &dyn SourceProvider) -> Result<(), Report> {
+        if self.included {
+            return Ok(());
+        }
+        self.included = true;
+        for root in self.options.include_paths.clone() {
+            let mut sources: Vec<PathBuf> = Vec::new();
+            collect_sources(provider, &root, &mut sources).map_err(|message| {
+                Report::new()
+                    .set_name(root.display().to_string())
+                    .set_message(message)
+            })?;
+            sources.sort();
+            for path in sources {
+                let name = path.display().to_string();
+                let source = provider.load(&path).map_err(|e| {
+                    Report::new()
+                        .set_name(name.clone())
+                        .set_message(format!("Could not read included file: {}", e))
+                })?;
+                let origin = SourceOrigin::new_virtual(name, source.clone());
+                let mut generator = AstGenerator::new(origin, self.contexts.next_context_id());
+                self.contexts.add_context(&mut generator.context);
+                let body = generator.begin_parse(TokenStream::new(tokenize(&source)))?;
+                self.contexts.set_body(generator.context.origin, body);
+            }
+        }
+        Ok(())
+    }
+
+    /// The rules the parser went through for the last script, see `CompilerOptions::trace_parse`.
+    /// The trace is kept when the script doesn't parse.
+    pub fn trace(&self) -> Option<&ParseTrace> {
+        self.trace.as_ref()
+    }
+
+    /// The warnings and notices the passes over the last script pushed to its context,
+    /// or of every script of the last `parse_scripts`, in order, see `Context::push_diagnostic`.
+    /// Lint warnings are at the level of their lint and only found when `post_semantic_checks` is set.
+    pub fn warnings(&self) -> &[Diagnostic] {
+        &self.warnings
+    }
+
+    /// The contexts of every script parsed so far.
+    pub fn contexts(&self) -> &ContextStore {
+        &self.contexts
+    }
+
+    /// Drops the context of a script, so its symbols are no longer resolved, eg: a script
+    /// of a REPL that was discarded. Global contexts, eg: the standard library, are kept.
+    pub fn remove_script(&mut self, name: &str) -> Option<Context> {
+        let id = self.contexts.find(name)?.origin;
+        if self.contexts.get_globals().contains(&id) {
+            return None;
+        }
+        self.contexts.remove_context(id)
+    }
+
+    /// Resolves the functions and classes every script parsed so far refers to,
+    /// across namespaces and include paths, see `resolve::resolve`.
+    /// Imports between the scripts that form a circle are reported last.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::compiler::{parser::Parser, CompilerOptions};
+    /// # let mut parser = Parser::new(CompilerOptions::default());
+    /// # let models = "namespace app\\models; class User {}".to_string();
+    /// # let http = "namespace app\\http; fn show() { return new models\\User(); }".to_string();
+    /// parser.try_parse_script("models.surn".to_string(), models)?;
+    /// parser.try_parse_script("http.surn".to_string(), http)?;
+    /// for report in parser.check_names() {
+    ///     report.print();
+    /// }
+    /// # Ok::<(), surn::report::Report>(())
+    /// ```
+    pub fn check_names(&self) -> Vec<Report> {
+        resolve(&self.contexts)
+            .into_iter()
+            .map(|error| {
+                let context = self.contexts.get(error.context).unwrap();
+                Diagnostic::from(error)
+                    .into_report(context.source.name.clone(), context.buffer.clone())
+            })
+            .chain(
+                find_cycles(&self.contexts)
+                    .into_iter()
+                    .map(|cycle| cycle.into_report(&self.contexts)),
+            )
+            .collect()
+    }
+}
+
+/// A script parsed along with others, see `Parser::parse_scripts`.
+#[derive(Debug)]
+pub struct ParsedScript {
+    pub name: String,
+    pub result: Result<AstBody, Report>,
+    /// The warnings and notices of the script, see `Parser::warnings`.
+    pub warnings: Vec<Diagnostic>,
+}
+
+/// A script parsed on its own, before its context is added to the store.
+struct Parsed {
+    /// `None` when the source is refused before it is read.
+    context: Option<Context>,
+    result: Result<AstBody, Report>,
+    warnings: Vec<Diagnostic>,
+    trace: Option<ParseTrace>,
+}
+
+/// Parses scripts into contexts with ids from the first one on, in order, on as many
+/// threads as `CompilerOptions::jobs`.
+#[cfg(feature = "threads")]
+fn parse_sources(
+    options: &CompilerOptions,
+    scripts: &[(String, String)],
+    first: u64,
+) -> Vec<Parsed> {
+    let jobs = options
+        .jobs
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, usize::from))
+        .clamp(1, scripts.len().max(1));
+    let next = AtomicUsize::new(0);
+    let slots: Vec<Mutex<Option<Parsed>>> = scripts.iter().map(|_| Mutex::new(None)).collect();
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some((name, source)) = scripts.get(i) else {
+                    break;
+                };
+                let parsed = parse_source(options, name, source, first + i as u64);
+                *slots[i].lock().unwrap() = Some(parsed);
+            });
+        }
+    });
+    slots
+        .into_iter()
+        .map(|slot| slot.into_inner().unwrap().expect("every script is parsed"))
+        .collect()
+}
+
+/// Parses scripts into contexts with ids from the first one on, one after the other.
+#[cfg(not(feature = "threads"))]
+fn parse_sources(
+    options: &CompilerOptions,
+    scripts: &[(String, String)],
+    first: u64,
+) -> Vec<Parsed> {
+    scripts
+        .iter()
+        .enumerate()
+        .map(|(i, (name, source))| parse_source(options, name, source, first + i as u64))
+        .collect()
+}
+
+/// Parses a script into a context with the given id, without a store,
+/// so scripts can be parsed on different threads.
+fn parse_source(options: &CompilerOptions, name: &str, source: &str, id: u64) -> Parsed {
+    let mut parsed = Parsed {
+        context: None,
+        result: Ok(AstBody::new()),
+        warnings: Vec::new(),
+        trace: None,
+    };
+    if let Some(max) = options.max_file_size.filter(|max| source.len() > *max) {
+        parsed.result = Err(too_large(
+            name.to_string(),
+            format!("is {} bytes, the limit is {}", source.len(), max),
+            "max_file_size",
+        ));
+        return parsed;
+    }
 
-        // lets tokenize the source code.
-        let tokens = tokenize(source.as_str());
+    // create a source origin for the script
+    let source_origin = SourceOrigin::new_virtual(name.to_string(), source.to_string());
+    // because we're going to be parsing a single script, we can use a new astgenerator.
+    let mut ast_generator = AstGenerator::new(source_origin, id);
+    parsed.result = parse_tokens(options, &mut ast_generator, source);
+    // the warnings and notices of every pass are reported once the source is parsed.
+    parsed.warnings = ast_generator.context.take_diagnostics();
+    parsed.trace = ast_generator.trace.take();
+    parsed.context = Some(ast_generator.context);
+    parsed
+}
+
+fn parse_tokens(
+    options: &CompilerOptions,
+    ast_generator: &mut AstGenerator,
+    source: &str,
+) -> Result<AstBody, Report> {
+    // lets tokenize the source code.
+    let tokens = tokenize(source);
+    if let Some(max) = options.max_tokens.filter(|max| tokens.len() > *max) {
+        return Err(too_large(
+            ast_generator.context.source.name.clone(),
+            format!("has {} tokens, the limit is {}", tokens.len(), max),
+            "max_tokens",
+        ));
+    }
+
+    if options.semantic_checks {
+        // do semantic checks
+        // the first error is reported, a delimiter that is never closed is reported
+        // where it is opened instead of where the parser runs out of tokens.
+        if let Err(found) = analyze(tokens.clone()) {
+            let (errors, notices): (Vec<Diagnostic>, Vec<Diagnostic>) =
+                found.into_iter().partition(Diagnostic::is_error);
+            for notice in notices {
+                ast_generator.context.push_diagnostic(notice);
+            }
+            if let Some(first) = errors.into_iter().next() {
+                let context = &ast_generator.context;
+                return Err(first.into_report(context.source.name.clone(), context.buffer.clone()));
+            }
+        }
+    }
 
-        // do our options with compiler options
-        self.do_options(&tokens);
+    if options.trace_parse {
+        ast_generator.trace = Some(ParseTrace::new());
+    }
 
-        // time to parse.
-        let ast = ast_generator.begin_parse(TokenStream::new(tokens)); // parse the tokens.
+    // time to parse.
+    let ast = ast_generator.begin_parse(TokenStream::new(tokens)); // parse the tokens.
+                                                                   // the tokens were handed to the generator, the fixes are worked out from a fresh copy.
+    let mut ast = ast.map_err(|report| fix_its::suggest(report, &tokenize(source)))?;
+    infer_variables(&mut ast);
 
-        return ast;
+    // a type is only defined once, whichever checks are on.
+    if let Some(duplicate) = TypeStore::build(&ast).1.into_iter().next() {
+        return Err(Diagnostic::from(duplicate).into_report(
+            ast_generator.context.source.name.clone(),
+            ast_generator.context.buffer.clone(),
+        ));
     }
 
-    pub(crate) fn do_options(&self, tokens: &Vec<Token>) {
-        if self.options.semantic_checks == true {
-            // do semantic checks
-            analyze(tokens.clone());
+    if options.post_semantic_checks {
+        // every mismatched property of an object is a span of the same diagnostic.
+        let shapes = || {
+            check_shapes(&ast)
+                .into_iter()
+                .map(Diagnostic::from)
+                .reduce(|mut all, diagnostic| {
+                    all.spans.extend(diagnostic.spans);
+                    all
+                })
+        };
+        let first = check_generics(&ast)
+            .into_iter()
+            .map(Diagnostic::from)
+            .next()
+            .or_else(|| check_nulls(&ast).into_iter().map(Diagnostic::from).next())
+            .or_else(|| check_consts(&ast).into_iter().map(Diagnostic::from).next())
+            .or_else(|| check_awaits(&ast).into_iter().map(Diagnostic::from).next())
+            .or_else(shapes)
+            .or_else(|| check_types(&ast).into_iter().map(Diagnostic::from).next())
+            .or_else(|| {
+                check_inheritance(&ast)
+                    .into_iter()
+                    .map(Diagnostic::from)
+                    .next()
+            })
+            .or_else(|| check_members(&ast).into_iter().map(Diagnostic::from).next());
+        if let Some(diagnostic) = first {
+            return Err(diagnostic.into_report(
+                ast_generator.context.source.name.clone(),
+                ast_generator.context.buffer.clone(),
+            ));
         }
+
+        for diagnostic in check_unused_variables(&ast) {
+            match options.lints.apply(diagnostic, ast.lint_scopes()) {
+                Some(denied) if denied.is_error() => {
+                    return Err(denied.into_report(
+                        ast_generator.context.source.name.clone(),
+                        ast_generator.context.buffer.clone(),
+                    ));
+                }
+                Some(warning) => ast_generator.context.push_diagnostic(warning),
+                None => {}
+            }
+        }
+    }
+
+    // an error a pass pushed fails the parse, the warnings and notices are kept.
+    let context = &mut ast_generator.context;
+    let (errors, kept): (Vec<Diagnostic>, Vec<Diagnostic>) = context
+        .take_diagnostics()
+        .into_iter()
+        .partition(Diagnostic::is_error);
+    for diagnostic in kept {
+        context.push_diagnostic(diagnostic);
+    }
+    if let Some(first) = errors.into_iter().next() {
+        return Err(first.into_report(context.source.name.clone(), context.buffer.clone()));
     }
+
+    infer_purity(&mut ast);
+    Ok(ast)
+}
+
+/// Reports a source over one of the size limits of the compiler options.
+/// The report has no snippet, printing one would read the whole source.
+fn too_large(name: String, size: String, option: &str) -> Report {
+    Report::new().set_code(codes::SOURCE_TOO_LARGE).set_name(name).set_message(format!(
+        "The file is too large to compile, it {}. Raise `{}` in the compiler options to compile it anyway.",
+        size, option
+    ))
 }
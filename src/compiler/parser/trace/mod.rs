@@ -0,0 +1,245 @@
+use std::fmt;
+
+use crate::{
+    compiler::lexer::token::{Token, TokenType},
+    transpiler::sourcemap::escape_json,
+};
+
+/// How a parse rule ended.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// The rule matched the tokens it consumed.
+    Matched,
+    /// The rule did not match and gave the tokens back.
+    Rejected,
+    /// The rule matched, but the source is invalid. Holds the reported message.
+    Failed(String),
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::Matched => write!(f, "matched"),
+            Outcome::Rejected => write!(f, "rejected"),
+            Outcome::Failed(message) => write!(f, "failed, {}", message),
+        }
+    }
+}
+
+/// The entry or exit of a parse rule.
+#[derive(Debug, Clone)]
+pub struct TraceEvent {
+    /// The name of the rule, eg: `statement` for `parse_statement`.
+    pub rule: &'static str,
+    /// How many rules were entered and not exited before this one.
+    pub depth: usize,
+    /// The index of the next token in the stream.
+    pub cursor: usize,
+    /// The next token in the stream, `None` at the end of the source.
+    pub token: Option<Token>,
+    /// `None` when the rule is entered.
+    pub outcome: Option<Outcome>,
+}
+
+/// Every rule the parser entered and exited, in order.
+/// Recorded when `CompilerOptions::trace_parse` is set, see `Parser::trace`.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, CompilerOptions};
+/// let options = CompilerOptions::builder().dev().trace_parse(true).build()?;
+/// let mut parser = Parser::new(options);
+/// parser.try_parse_script("main.surn".to_string(), "var x = ;".to_string());
+/// println!("{}", parser.trace().unwrap().explain("variable"));
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ParseTrace {
+    pub events: Vec<TraceEvent>,
+    depth: usize,
+}
+
+/// A single run of a rule, built from its entry and exit.
+struct RuleRun<'a> {
+    enter: &'a TraceEvent,
+    exit: Option<&'a TraceEvent>,
+    children: Vec<RuleRun<'a>>,
+}
+
+impl ParseTrace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn enter(&mut self, rule: &'static str, cursor: usize, token: Option<Token>) {
+        self.events.push(TraceEvent {
+            rule,
+            depth: self.depth,
+            cursor,
+            token,
+            outcome: None,
+        });
+        self.depth += 1;
+    }
+
+    pub fn exit(
+        &mut self,
+        rule: &'static str,
+        cursor: usize,
+        token: Option<Token>,
+        outcome: Outcome,
+    ) {
+        self.depth = self.depth.saturating_sub(1);
+        self.events.push(TraceEvent {
+            rule,
+            depth: self.depth,
+            cursor,
+            token,
+            outcome: Some(outcome),
+        });
+    }
+
+    /// Serializes every event as a JSON array.
+    ///
+    /// ```json
+    /// [{"rule":"statement","depth":0,"cursor":0,"token":"var","start":0,"end":3,"event":"enter"},
+    ///  {"rule":"statement","depth":0,"cursor":8,"token":null,"event":"exit","outcome":"matched"}]
+    /// ```
+    pub fn to_json(&self) -> String {
+        let events = self
+            .events
+            .iter()
+            .map(|e| {
+                let token = match &e.token {
+                    Some(token) => format!(
+                        "{},\"start\":{},\"end\":{}",
+                        escape_json(&describe(token)),
+                        token.range().start,
+                        token.range().end
+                    ),
+                    None => "null".to_string(),
+                };
+                let event = match &e.outcome {
+                    None => "\"event\":\"enter\"".to_string(),
+                    Some(Outcome::Failed(message)) => format!(
+                        "\"event\":\"exit\",\"outcome\":\"failed\",\"message\":{}",
+                        escape_json(message)
+                    ),
+                    Some(outcome) => format!("\"event\":\"exit\",\"outcome\":\"{}\"", outcome),
+                };
+                format!(
+                    "{{\"rule\":\"{}\",\"depth\":{},\"cursor\":{},\"token\":{},{}}}",
+                    e.rule, e.depth, e.cursor, token, event
+                )
+            })
+            .collect::<Vec<String>>();
+        format!("[{}]", events.join(",\n"))
+    }
+
+    /// Renders every rule as a tree, one line per run.
+    ///
+    /// **Example:**
+    /// ```text
+    /// statement matched at `var`, tokens 0..8
+    ///     macro_invocation rejected at `var`
+    ///     variable matched at `var`, tokens 0..8
+    ///         expression matched at `1`, tokens 6..7
+    /// ```
+    pub fn render(&self) -> String {
+        let mut rendered = String::new();
+        for run in self.runs().iter() {
+            render_run(run, 0, &mut rendered);
+        }
+        rendered
+    }
+
+    /// Renders every run of a rule that didn't match along with the rules it tried,
+    /// which shows the token each of them stopped at.
+    pub fn explain(&self, rule: &str) -> String {
+        fn collect<'a>(run: &'a RuleRun<'a>, rule: &str, found: &mut Vec<&'a RuleRun<'a>>) {
+            let matched = matches!(
+                run.exit.and_then(|e| e.outcome.as_ref()),
+                Some(Outcome::Matched)
+            );
+            if run.enter.rule == rule && !matched {
+                found.push(run);
+            }
+            for child in run.children.iter() {
+                collect(child, rule, found);
+            }
+        }
+
+        let runs = self.runs();
+        let mut found = Vec::new();
+        for run in runs.iter() {
+            collect(run, rule, &mut found);
+        }
+        if found.is_empty() {
+            return format!("`{}` never rejected the input.", rule);
+        }
+        let mut rendered = String::new();
+        for run in found {
+            render_run(run, 0, &mut rendered);
+        }
+        rendered
+    }
+
+    fn runs(&self) -> Vec<RuleRun<'_>> {
+        let mut stack: Vec<RuleRun> = Vec::new();
+        let mut roots: Vec<RuleRun> = Vec::new();
+        for event in self.events.iter() {
+            if event.outcome.is_none() {
+                stack.push(RuleRun {
+                    enter: event,
+                    exit: None,
+                    children: Vec::new(),
+                });
+                continue;
+            }
+            if let Some(mut run) = stack.pop() {
+                run.exit = Some(event);
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(run),
+                    None => roots.push(run),
+                }
+            }
+        }
+        // rules that never exited, eg: when the parser panicked.
+        while let Some(run) = stack.pop() {
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(run),
+                None => roots.push(run),
+            }
+        }
+        roots
+    }
+}
+
+fn describe(token: &Token) -> String {
+    match token.kind() {
+        TokenType::KeyWord(keyword) => keyword.to_string(),
+        TokenType::Whitespace | TokenType::LineBreak => token.kind().to_string().to_lowercase(),
+        kind => token.value().unwrap_or_else(|| kind.to_string()),
+    }
+}
+
+fn render_run(run: &RuleRun, depth: usize, rendered: &mut String) {
+    let at = match &run.enter.token {
+        Some(token) => format!("`{}`", describe(token)),
+        None => "the end".to_string(),
+    };
+    let line = match run.exit.and_then(|e| e.outcome.as_ref().map(|o| (e, o))) {
+        Some((exit, Outcome::Matched)) => format!(
+            "{} matched at {}, tokens {}..{}",
+            run.enter.rule, at, run.enter.cursor, exit.cursor
+        ),
+        Some((_, outcome)) => format!("{} {} at {}", run.enter.rule, outcome, at),
+        None => format!("{} never exited, entered at {}", run.enter.rule, at),
+    };
+    rendered.push_str(&"    ".repeat(depth));
+    rendered.push_str(&line);
+    rendered.push('\n');
+    for child in run.children.iter() {
+        render_run(child, depth + 1, rendered);
+    }
+}
@@ -0,0 +1,246 @@
+//! Imports that lead back to the file they are in, eg: `a.surn` imports a class of
+//! `b.surn`, which imports a function of `a.surn`.
+use std::{
+    collections::{BTreeSet, HashSet},
+    ops::Range,
+};
+
+use crate::{
+    compiler::{
+        ast::{
+            visit::{self, Visitor},
+            Path, Statement,
+        },
+        parser::context::{Context, ContextStore},
+    },
+    report::{codes, Report, Snippet},
+};
+
+use super::{sources, NamespaceTree};
+
+/// An import of the declarations of another file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportSite {
+    /// The context the import is in.
+    pub context: u64,
+    /// The name of the source the import is in.
+    pub source: String,
+    /// The path as it is written, eg: `app::models::User`.
+    pub path: String,
+    pub span: Range<usize>,
+    /// The context the import leads to.
+    pub target: u64,
+}
+
+/// Files that import each other in a circle, every import is in the file
+/// the one before it leads to and the last one leads back to the first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportCycle {
+    pub imports: Vec<ImportSite>,
+}
+
+impl ImportCycle {
+    /// The files of the cycle, the first one is repeated at the end.
+    pub fn files(&self) -> Vec<&str> {
+        let mut files: Vec<&str> = self
+            .imports
+            .iter()
+            .map(|site| site.source.as_str())
+            .collect();
+        files.push(&self.imports[0].source);
+        files
+    }
+
+    pub fn message(&self) -> String {
+        format!("Circular import: {}.", self.files().join(" -> "))
+    }
+
+    /// A report with a snippet at every import of the cycle, in the file it is in.
+    pub fn into_report(self, store: &ContextStore) -> Report {
+        let files: Vec<String> = self.files().into_iter().map(str::to_string).collect();
+        let first = store.get(self.imports[0].context).unwrap();
+        let mut report = Report::new()
+            .set_code(codes::CIRCULAR_IMPORT)
+            .set_name(first.source.name.clone())
+            .set_source(first.buffer.clone())
+            .set_message(self.message());
+        for (i, site) in self.imports.into_iter().enumerate() {
+            let context = store.get(site.context).unwrap();
+            let message = format!("`{}` is imported from `{}` here.", site.path, files[i + 1]);
+            let mut snippet = Snippet::new(context.buffer.clone(), message, site.span);
+            // the first file is the one the report is named after.
+            if i > 0 {
+                snippet = snippet.set_name(context.source.name.clone());
+            }
+            report = report.add_snippet(snippet);
+        }
+        report.add_note("Move what both files need to a file neither of them imports.".to_string())
+    }
+}
+
+/// Finds the cycles in the imports between the contexts of a store, every cycle is found once.
+/// An import leads to the files that declare what it names, or what is declared
+/// in the namespace it names.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, resolve::cycles::find_cycles, CompilerOptions};
+/// # let mut parser = Parser::new(CompilerOptions::default());
+/// // a.surn: use app::b::second;    b.surn: use app::a::first;
+/// # parser.try_parse_script("a.surn".to_string(), "use app::b::second;".to_string())?;
+/// # parser.try_parse_script("b.surn".to_string(), "use app::a::first;".to_string())?;
+/// find_cycles(parser.contexts())[0].message(); // Circular import: a.surn -> b.surn -> a.surn.
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub fn find_cycles(store: &ContextStore) -> Vec<ImportCycle> {
+    let tree = NamespaceTree::build(store);
+    let contexts = sources(store);
+    let graph = ImportGraph {
+        edges: contexts
+            .iter()
+            .map(|context| (context.origin, imports(context, &tree)))
+            .collect(),
+    };
+
+    let mut search = CycleSearch {
+        graph: &graph,
+        path: Vec::new(),
+        sites: Vec::new(),
+        done: HashSet::new(),
+        found: BTreeSet::new(),
+        cycles: Vec::new(),
+    };
+    for context in contexts.iter() {
+        if !search.done.contains(&context.origin) {
+            search.path.push(context.origin);
+            search.visit(context.origin);
+            search.path.pop();
+        }
+    }
+    search.cycles
+}
+
+/// The imports of a context that lead to other contexts, in the order they are written.
+pub(super) fn imports(context: &Context, tree: &NamespaceTree) -> Vec<ImportSite> {
+    let mut collector = ImportCollector { paths: Vec::new() };
+    visit::walk_body(&mut collector, &context.body);
+
+    let mut sites = Vec::new();
+    for path in collector.paths {
+        let mut targets: Vec<u64> = Vec::new();
+        for leaf in path.leaves() {
+            let segments: Vec<String> = leaf.iter().map(|part| part.name.to_string()).collect();
+            let declarations = tree.root.lookup(&segments).iter();
+            let namespace = tree.root.get(&segments).into_iter();
+            let declared = namespace.flat_map(|node| node.declarations.values().flatten());
+            for declaration in declarations.chain(declared) {
+                if declaration.context != context.origin && !targets.contains(&declaration.context)
+                {
+                    targets.push(declaration.context);
+                }
+            }
+        }
+        for target in targets {
+            sites.push(ImportSite {
+                context: context.origin,
+                source: context.source.name.clone(),
+                path: written(&path),
+                span: path.span.clone(),
+                target,
+            });
+        }
+    }
+    sites
+}
+
+/// A path the way it is written in an import, eg: `app::{a, b}`.
+fn written(path: &Path) -> String {
+    let parts = match path.parts.len() {
+        0 => String::new(),
+        1 => format!("::{}", written(&path.parts[0])),
+        _ => format!(
+            "::{{{}}}",
+            path.parts
+                .iter()
+                .map(written)
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+    };
+    match &path.alias {
+        Some(alias) => format!("{}{} as {}", path.name, parts, alias),
+        None => format!("{}{}", path.name, parts),
+    }
+}
+
+struct ImportCollector {
+    paths: Vec<Path>,
+}
+
+impl Visitor for ImportCollector {
+    fn visit_statement(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Import(path) => self.paths.push(path.clone()),
+            _ => visit::walk_statement(self, stmt),
+        }
+    }
+}
+
+struct ImportGraph {
+    /// The imports of every context, by the id of the context.
+    edges: Vec<(u64, Vec<ImportSite>)>,
+}
+
+impl ImportGraph {
+    fn imports(&self, context: u64) -> &[ImportSite] {
+        self.edges
+            .iter()
+            .find(|(id, _)| *id == context)
+            .map(|(_, sites)| sites.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+/// A depth first search, a cycle is an import back to a context on the path.
+struct CycleSearch<'a> {
+    graph: &'a ImportGraph,
+    /// The contexts from where the search started to the one being visited.
+    path: Vec<u64>,
+    /// The imports between the contexts of the path.
+    sites: Vec<ImportSite>,
+    done: HashSet<u64>,
+    /// The contexts of every cycle found, starting with the smallest id.
+    found: BTreeSet<Vec<u64>>,
+    cycles: Vec<ImportCycle>,
+}
+
+impl CycleSearch<'_> {
+    fn visit(&mut self, context: u64) {
+        for site in self.graph.imports(context) {
+            if let Some(start) = self.path.iter().position(|id| *id == site.target) {
+                let mut imports = self.sites[start..].to_vec();
+                imports.push(site.clone());
+                self.found(imports);
+            } else if !self.done.contains(&site.target) {
+                self.path.push(site.target);
+                self.sites.push(site.clone());
+                self.visit(site.target);
+                self.sites.pop();
+                self.path.pop();
+            }
+        }
+        self.done.insert(context);
+    }
+
+    /// Keeps a cycle unless it was found from another one of its contexts.
+    fn found(&mut self, mut imports: Vec<ImportSite>) {
+        let smallest = (0..imports.len())
+            .min_by_key(|i| imports[*i].context)
+            .unwrap_or_default();
+        imports.rotate_left(smallest);
+        let contexts = imports.iter().map(|site| site.context).collect();
+        if self.found.insert(contexts) {
+            self.cycles.push(ImportCycle { imports });
+        }
+    }
+}
@@ -0,0 +1,314 @@
+//! The classes a class extends and implements, eg: `class User extends Model implements Jsonable`.
+//!
+//! A method of a class overrides the method with the same name of the classes it extends
+//! and implements, so it has to take the same parameters and return what they return.
+//! Classes that aren't declared in the body aren't known, so nothing is checked against them.
+use std::{
+    collections::{HashMap, VecDeque},
+    ops::Range,
+};
+
+use crate::compiler::{
+    ast::{
+        types::TypeKind,
+        visit::{self, Visitor},
+        AstBody, Class, Function,
+    },
+    infer::shapes::{assignable, type_aliases},
+};
+
+/// The attribute that marks a method as overriding one, eg: `#[Override] pub fn save() {}`.
+pub const OVERRIDE_ATTRIBUTE: &str = "Override";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InheritanceErrorKind {
+    /// A class extends or implements itself, through the classes it names.
+    Cycle,
+    /// A class doesn't declare a method of a class it implements.
+    Unimplemented,
+    /// A method doesn't take the parameters or return what the method it overrides does.
+    Incompatible,
+    /// A method is marked `#[Override]` but there is nothing for it to override.
+    NothingOverridden,
+}
+
+/// A class that can't extend or implement the classes it names the way it does.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InheritanceError {
+    pub kind: InheritanceErrorKind,
+    pub message: String,
+    /// The span of the method, or of the class for the errors about the class itself.
+    pub span: Range<usize>,
+}
+
+/// Checks the classes every class of a body extends and implements.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, CompilerOptions};
+/// # use surn::compiler::resolve::inheritance::check_inheritance;
+/// // class Jsonable { pub fn json(): string {} }
+/// // class User implements Jsonable {}
+/// # let source = "class Jsonable { pub fn json(): string {} }\nclass User implements Jsonable {}\n";
+/// # let body = Parser::new(CompilerOptions::default())
+/// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+/// check_inheritance(&body); // [`User` implements `Jsonable` but doesn't declare `json`.]
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub fn check_inheritance(body: &AstBody) -> Vec<InheritanceError> {
+    let mut collector = ClassCollector {
+        classes: Vec::new(),
+    };
+    visit::walk_body(&mut collector, body);
+
+    let checker = InheritanceChecker {
+        aliases: type_aliases(body),
+        classes: collector
+            .classes
+            .iter()
+            .map(|class| (class.name.as_str(), class))
+            .collect(),
+    };
+    let mut errors = Vec::new();
+    let mut cycles: Vec<&str> = Vec::new();
+    for class in collector.classes.iter() {
+        if cycles.contains(&class.name.as_str()) {
+            continue;
+        }
+        if let Some(cycle) = checker.cycle(&class.name) {
+            errors.push(InheritanceError {
+                kind: InheritanceErrorKind::Cycle,
+                message: format!(
+                    "`{}` inherits from itself: {}.",
+                    class.name,
+                    cycle.join(" -> ")
+                ),
+                span: class.span.clone(),
+            });
+            cycles.extend(cycle);
+            continue;
+        }
+        checker.check(class, &mut errors);
+    }
+    errors
+}
+
+struct ClassCollector {
+    classes: Vec<Class>,
+}
+
+impl Visitor for ClassCollector {
+    fn visit_class(&mut self, class: &Class) {
+        self.classes.push(class.clone());
+        visit::walk_class(self, class);
+    }
+}
+
+/// Where a method was looked up.
+enum Lookup<'a> {
+    /// The methods with the name and the classes that declare them.
+    Found(Vec<(&'a str, &'a Function)>),
+    /// Every class that was looked at is declared, and none of them has the method.
+    Missing,
+    /// A class that was looked at isn't declared, it may have the method.
+    Unknown,
+}
+
+struct InheritanceChecker<'a> {
+    aliases: HashMap<String, TypeKind>,
+    classes: HashMap<&'a str, &'a Class>,
+}
+
+impl<'a> InheritanceChecker<'a> {
+    /// The classes a class extends and implements, in the order they are written.
+    fn parents(class: &Class) -> impl Iterator<Item = &String> {
+        class
+            .extends
+            .iter()
+            .chain(class.implements.iter().flatten())
+    }
+
+    /// The classes from `name` back to itself, if it inherits from itself.
+    fn cycle(&self, name: &str) -> Option<Vec<&'a str>> {
+        let start = *self.classes.get(name)?;
+        let mut path = vec![start.name.as_str()];
+        let mut visited = Vec::new();
+        self.find_cycle(start, &mut path, &mut visited)
+            .then_some(path)
+    }
+
+    fn find_cycle(
+        &self,
+        class: &'a Class,
+        path: &mut Vec<&'a str>,
+        visited: &mut Vec<&'a str>,
+    ) -> bool {
+        for parent in Self::parents(class) {
+            if parent == path[0] {
+                path.push(path[0]);
+                return true;
+            }
+            let Some(next) = self.classes.get(parent.as_str()).copied() else {
+                continue;
+            };
+            if visited.contains(&next.name.as_str()) {
+                continue;
+            }
+            visited.push(next.name.as_str());
+            path.push(next.name.as_str());
+            if self.find_cycle(next, path, visited) {
+                return true;
+            }
+            path.pop();
+        }
+        false
+    }
+
+    /// The methods with the name of the classes a class extends and implements,
+    /// and of the classes they extend and implement.
+    fn inherited(&self, class: &'a Class, name: &str) -> Lookup<'a> {
+        let mut found = Vec::new();
+        let mut unknown = false;
+        let mut queue: VecDeque<&String> = Self::parents(class).collect();
+        let mut visited: Vec<&str> = Vec::new();
+        while let Some(parent) = queue.pop_front() {
+            if visited.contains(&parent.as_str()) {
+                continue;
+            }
+            visited.push(parent.as_str());
+            let Some(parent) = self.classes.get(parent.as_str()).copied() else {
+                unknown = true;
+                continue;
+            };
+            match methods(parent).find(|method| method.name.as_deref() == Some(name)) {
+                Some(method) => found.push((parent.name.as_str(), method)),
+                // a method is only checked against the closest one it overrides.
+                None => queue.extend(Self::parents(parent)),
+            }
+        }
+        match (found.is_empty(), unknown) {
+            (false, _) => Lookup::Found(found),
+            (true, true) => Lookup::Unknown,
+            (true, false) => Lookup::Missing,
+        }
+    }
+
+    /// Whether a class or a class it extends declares a method, `None` when it extends
+    /// a class that isn't declared.
+    fn declares(&self, class: &Class, name: &str) -> Option<bool> {
+        let mut class = class;
+        for _ in 0..=self.classes.len() {
+            if methods(class).any(|method| method.name.as_deref() == Some(name)) {
+                return Some(true);
+            }
+            match &class.extends {
+                Some(parent) => class = self.classes.get(parent.as_str())?,
+                None => return Some(false),
+            }
+        }
+        Some(false)
+    }
+
+    fn check(&self, class: &'a Class, errors: &mut Vec<InheritanceError>) {
+        for method in methods(class) {
+            let Some(name) = method.name.as_deref() else {
+                continue;
+            };
+            // constructors take what the class needs.
+            if name == "construct" {
+                continue;
+            }
+            let marked = method
+                .attributes
+                .iter()
+                .any(|attribute| attribute.name == OVERRIDE_ATTRIBUTE);
+            match self.inherited(class, name) {
+                Lookup::Found(overridden) => {
+                    for (parent, base) in overridden {
+                        if let Some(reason) = self.incompatible(method, base) {
+                            errors.push(InheritanceError {
+                                kind: InheritanceErrorKind::Incompatible,
+                                message: format!(
+                                    "`{}` of `{}` doesn't match `{}` of `{}`, {}.",
+                                    name, class.name, name, parent, reason
+                                ),
+                                span: method.span.clone(),
+                            });
+                        }
+                    }
+                }
+                Lookup::Missing if marked => errors.push(InheritanceError {
+                    kind: InheritanceErrorKind::NothingOverridden,
+                    message: format!(
+                        "`{}` of `{}` is marked `#[{}]` but no class `{}` extends or implements declares `{}`.",
+                        name, class.name, OVERRIDE_ATTRIBUTE, class.name, name
+                    ),
+                    span: method.span.clone(),
+                }),
+                _ => {}
+            }
+        }
+
+        for interface in class.implements.iter().flatten() {
+            let Some(interface) = self.classes.get(interface.as_str()) else {
+                continue;
+            };
+            for method in methods(interface) {
+                let Some(name) = method.name.as_deref() else {
+                    continue;
+                };
+                if self.declares(class, name) == Some(false) {
+                    errors.push(InheritanceError {
+                        kind: InheritanceErrorKind::Unimplemented,
+                        message: format!(
+                            "`{}` implements `{}` but doesn't declare `{}`.",
+                            class.name, interface.name, name
+                        ),
+                        span: class.span.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Why a method can't override another one, if it can't.
+    /// A parameter can accept more than the one it overrides, and the result can be narrower.
+    fn incompatible(&self, method: &Function, base: &Function) -> Option<String> {
+        if method.inputs.len() != base.inputs.len() {
+            return Some(format!(
+                "it takes {} parameters instead of {}",
+                method.inputs.len(),
+                base.inputs.len()
+            ));
+        }
+        for (input, overridden) in method.inputs.iter().zip(base.inputs.iter()) {
+            if input.variadic != overridden.variadic {
+                let is = if input.variadic { "is" } else { "isn't" };
+                return Some(format!("`{}` {} variadic", input.name, is));
+            }
+            if let (Some(ty), Some(base_ty)) = (&input.ty, &overridden.ty) {
+                if !assignable(&self.aliases, ty, base_ty) {
+                    return Some(format!(
+                        "`{}` is `{}` instead of `{}`",
+                        input.name, ty, base_ty
+                    ));
+                }
+            }
+        }
+        match (&method.outputs, &base.outputs) {
+            (Some(ty), Some(expected)) if !assignable(&self.aliases, expected, ty) => {
+                Some(format!("it returns `{}` instead of `{}`", ty, expected))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The methods a class declares, static or not.
+fn methods(class: &Class) -> impl Iterator<Item = &Function> {
+    class
+        .body
+        .methods
+        .iter()
+        .chain(class.body.other.iter().filter_map(|other| other.method()))
+}
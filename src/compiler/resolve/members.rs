@@ -0,0 +1,386 @@
+//! Accesses of the members of classes, eg: `user.name`, `User::create()` or `this.save()`.
+//!
+//! A member is looked up on the class the value it is accessed on was declared with,
+//! then on the classes it extends. Values whose class isn't known are not checked.
+use std::{collections::HashMap, ops::Range};
+
+use crate::compiler::ast::{
+    types::TypeKind,
+    visit::{self, Visitor},
+    AstBody, Class, ClassAllowedStatement, Expression, Function, MemberListNode, MemberLookup,
+    Variable, Visibility,
+};
+
+/// Why a member access isn't allowed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MemberErrorKind {
+    /// Neither the class nor the classes it extends declare the member.
+    Unknown,
+    /// The member is private or protected and accessed from a class that can't see it,
+    /// with the class that declares it.
+    Inaccessible(Visibility, String),
+    /// An instance member accessed with `::`.
+    NotStatic,
+    /// A static member accessed with `.`.
+    Static,
+}
+
+/// A member access that doesn't resolve to a member the access is allowed to use.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemberError {
+    /// The class the member is looked up on.
+    pub class: String,
+    pub member: String,
+    /// Whether the member is called, eg: `user.save()`.
+    pub is_call: bool,
+    /// The span of the access, from the value to the name of the member.
+    pub span: Range<usize>,
+    pub kind: MemberErrorKind,
+}
+
+impl MemberError {
+    pub fn message(&self) -> String {
+        let (class, member) = (&self.class, &self.member);
+        match &self.kind {
+            MemberErrorKind::Unknown if self.is_call => {
+                format!("`{}` has no method `{}`.", class, member)
+            }
+            MemberErrorKind::Unknown => format!("`{}` has no property `{}`.", class, member),
+            MemberErrorKind::Inaccessible(Visibility::Protected, declared) => format!(
+                "`{}` of `{}` is protected, only `{}` and the classes that extend it can access it.",
+                member, class, declared
+            ),
+            MemberErrorKind::Inaccessible(_, declared) => format!(
+                "`{}` of `{}` is private, only `{}` can access it.",
+                member, class, declared
+            ),
+            MemberErrorKind::NotStatic => format!(
+                "`{}` of `{}` is not static, access it on an instance with `.`.",
+                member, class
+            ),
+            MemberErrorKind::Static => format!(
+                "`{}` of `{}` is static, access it with `{}::{}`.",
+                member, class, class, member
+            ),
+        }
+    }
+}
+
+/// Checks every member access of a body against the classes it declares.
+///
+/// The class of a value is the one `this`, `self`, `static` or `parent` stand for,
+/// the class named before `::`, or the type a variable or parameter was declared with.
+/// Members of classes that extend a class which isn't declared in the body may be
+/// declared by it, so they are only reported when they are found and can't be accessed.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, CompilerOptions};
+/// # use surn::compiler::resolve::members::check_members;
+/// // class User { priv fn secret() {} }
+/// // fn main(user: User) { user.secret(); }
+/// # let source = "class User { priv fn secret() {} }\nfn main(user: User) { user.secret(); }\n";
+/// # let body = Parser::new(CompilerOptions::default())
+/// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+/// check_members(&body); // [`secret` of `User` is private, only `User` can access it.]
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub fn check_members(body: &AstBody) -> Vec<MemberError> {
+    let mut collector = ClassCollector {
+        classes: HashMap::new(),
+    };
+    visit::walk_body(&mut collector, body);
+
+    let mut checker = MemberChecker {
+        classes: collector.classes,
+        current: Vec::new(),
+        scopes: vec![HashMap::new()],
+        errors: Vec::new(),
+    };
+    visit::walk_body(&mut checker, body);
+    checker.errors
+}
+
+#[derive(Debug, Clone)]
+struct Member {
+    visibility: Visibility,
+    is_static: bool,
+}
+
+#[derive(Debug, Default)]
+struct ClassMembers {
+    extends: Option<String>,
+    properties: HashMap<String, Member>,
+    methods: HashMap<String, Member>,
+}
+
+/// Where a member was found.
+enum Lookup<'a> {
+    /// The member and the class that declares it.
+    Found(&'a str, &'a Member),
+    /// The class and every class it extends are declared, and none of them has the member.
+    Missing,
+    /// The class extends a class that isn't declared, which may have the member.
+    Unknown,
+}
+
+struct ClassCollector {
+    classes: HashMap<String, ClassMembers>,
+}
+
+impl ClassCollector {
+    fn add(members: &mut ClassMembers, stmt: &ClassAllowedStatement, is_static: bool) {
+        match stmt {
+            ClassAllowedStatement::Property(property) => {
+                let member = Member {
+                    visibility: property.visibility.clone(),
+                    is_static,
+                };
+                members.properties.insert(property.name.clone(), member);
+            }
+            ClassAllowedStatement::Method(method) => {
+                if let Some(name) = &method.name {
+                    let member = Member {
+                        visibility: method.visibility.clone(),
+                        is_static,
+                    };
+                    members.methods.insert(name.clone(), member);
+                }
+            }
+            // constants are accessed on the class, eg: `User::LIMIT`.
+            ClassAllowedStatement::Constant(constant) => {
+                let member = Member {
+                    visibility: constant.visibility.clone(),
+                    is_static: true,
+                };
+                members.properties.insert(constant.name.to_string(), member);
+            }
+            ClassAllowedStatement::Static(inner) => Self::add(members, inner, true),
+            ClassAllowedStatement::Macro(_) | ClassAllowedStatement::Import(_) => {}
+        }
+    }
+}
+
+impl Visitor for ClassCollector {
+    fn visit_class(&mut self, class: &Class) {
+        let mut members = ClassMembers {
+            extends: class.extends.clone(),
+            ..Default::default()
+        };
+        for property in class.body.properties.iter() {
+            let member = Member {
+                visibility: property.visibility.clone(),
+                is_static: false,
+            };
+            members.properties.insert(property.name.clone(), member);
+        }
+        for method in class.body.methods.iter().filter(|m| m.name.is_some()) {
+            let member = Member {
+                visibility: method.visibility.clone(),
+                is_static: false,
+            };
+            members.methods.insert(method.name.clone().unwrap(), member);
+        }
+        for other in class.body.other.iter() {
+            Self::add(&mut members, other, false);
+        }
+        self.classes.insert(class.name.clone(), members);
+        visit::walk_class(self, class);
+    }
+}
+
+/// How the class of an access was named.
+enum Origin {
+    /// A value of the class, eg: `this` or a variable.
+    Instance,
+    /// The class itself, eg: `User::`.
+    Class,
+    /// `self`, `static` or `parent`, which can call the instance methods of the class.
+    Scope,
+}
+
+struct MemberChecker {
+    classes: HashMap<String, ClassMembers>,
+    /// The classes being checked, the innermost last.
+    current: Vec<String>,
+    scopes: Vec<HashMap<String, String>>,
+    errors: Vec<MemberError>,
+}
+
+impl MemberChecker {
+    fn lookup(&self, class: &str, name: &str, is_call: bool) -> Lookup<'_> {
+        let mut class = class;
+        // a class that extends itself would never end.
+        for _ in 0..=self.classes.len() {
+            let Some((declared, members)) = self.classes.get_key_value(class) else {
+                return Lookup::Unknown;
+            };
+            let member = match is_call {
+                // a property can hold a function.
+                true => members
+                    .methods
+                    .get(name)
+                    .or_else(|| members.properties.get(name)),
+                false => members.properties.get(name),
+            };
+            if let Some(member) = member {
+                return Lookup::Found(declared, member);
+            }
+            match &members.extends {
+                Some(parent) => class = parent,
+                None => return Lookup::Missing,
+            }
+        }
+        Lookup::Unknown
+    }
+
+    /// Whether `class` is `ancestor` or extends it.
+    fn extends(&self, class: &str, ancestor: &str) -> bool {
+        let mut class = class;
+        for _ in 0..=self.classes.len() {
+            if class == ancestor {
+                return true;
+            }
+            match self.classes.get(class).and_then(|c| c.extends.as_deref()) {
+                Some(parent) => class = parent,
+                None => return false,
+            }
+        }
+        false
+    }
+
+    /// The class an access is made on, and how it was named.
+    fn origin(&self, member: &MemberListNode) -> Option<(String, Origin)> {
+        let name = member.origin.value()?;
+        let current = self.current.last();
+        match (name.as_str(), &member.lookup) {
+            ("this", MemberLookup::Dynamic) => Some((current?.clone(), Origin::Instance)),
+            ("self" | "static", MemberLookup::Static) => Some((current?.clone(), Origin::Scope)),
+            ("parent", MemberLookup::Static) => {
+                let parent = self.classes.get(current?)?.extends.clone()?;
+                Some((parent, Origin::Scope))
+            }
+            (name, MemberLookup::Static) => {
+                // the class can be qualified, eg: `models\User`.
+                let class = name.rsplit('\\').next().unwrap_or(name);
+                Some((class.to_string(), Origin::Class))
+            }
+            (name, MemberLookup::Dynamic) => {
+                let class = self.scopes.iter().rev().find_map(|scope| scope.get(name))?;
+                Some((class.clone(), Origin::Instance))
+            }
+            (_, MemberLookup::Index) => None,
+        }
+    }
+
+    fn check(&mut self, access: &MemberListNode) {
+        let Some((class, origin)) = self.origin(access) else {
+            return;
+        };
+        if !self.classes.contains_key(&class) {
+            return;
+        }
+        let (member, is_call, end) = match access.name.as_ref() {
+            Expression::Call(call) => (call.name.to_string(), true, Some(call.span.end)),
+            Expression::Literal(literal) if literal.ty.is_none() => {
+                (literal.value.clone(), false, None)
+            }
+            // a chain, eg: `user.profile.name`, where the name of the first member is the origin.
+            Expression::Member(inner) => (inner.origin.value().unwrap_or_default(), false, None),
+            _ => return,
+        };
+        let origin_span = access.origin.range();
+        let end = end.unwrap_or_else(|| {
+            let accessor = match access.lookup {
+                MemberLookup::Static => "::".len(),
+                _ => ".".len(),
+            };
+            origin_span.end + accessor + member.chars().count()
+        });
+
+        let kind = match self.lookup(&class, &member, is_call) {
+            Lookup::Unknown => return,
+            Lookup::Missing => Some(MemberErrorKind::Unknown),
+            Lookup::Found(declared, found) => {
+                let current = self.current.last().map(String::as_str);
+                let visible = match found.visibility {
+                    Visibility::Private => current == Some(declared),
+                    Visibility::Protected => current.is_some_and(|c| self.extends(c, declared)),
+                    Visibility::Public | Visibility::Module => true,
+                };
+                match origin {
+                    _ if !visible => Some(MemberErrorKind::Inaccessible(
+                        found.visibility.clone(),
+                        declared.to_string(),
+                    )),
+                    Origin::Instance if found.is_static => Some(MemberErrorKind::Static),
+                    Origin::Class if !found.is_static => Some(MemberErrorKind::NotStatic),
+                    Origin::Scope if !found.is_static && !is_call => {
+                        Some(MemberErrorKind::NotStatic)
+                    }
+                    _ => None,
+                }
+            }
+        };
+        if let Some(kind) = kind {
+            self.errors.push(MemberError {
+                class,
+                member,
+                is_call,
+                span: origin_span.start..end,
+                kind,
+            });
+        }
+    }
+
+    fn declare(&mut self, name: &str, ty: Option<&TypeKind>) {
+        if let Some(TypeKind::Reference(reference)) = ty {
+            self.scopes
+                .last_mut()
+                .unwrap()
+                .insert(name.to_string(), reference.name.clone());
+        }
+    }
+
+    /// Visits what a member access contains, its name is only checked as part of the access.
+    fn walk_member(&mut self, name: &Expression) {
+        match name {
+            Expression::Call(call) => visit::walk_call(self, call),
+            Expression::Member(inner) => self.walk_member(&inner.name),
+            Expression::Literal(_) => {}
+            name => self.visit_expression(name),
+        }
+    }
+}
+
+impl Visitor for MemberChecker {
+    fn visit_expression(&mut self, expr: &Expression) {
+        match expr {
+            Expression::Member(member) => {
+                self.check(member);
+                self.walk_member(&member.name);
+            }
+            _ => visit::walk_expression(self, expr),
+        }
+    }
+
+    fn visit_variable(&mut self, var: &Variable) {
+        visit::walk_variable(self, var);
+        self.declare(&var.name, var.type_of());
+    }
+
+    fn visit_function(&mut self, func: &Function) {
+        self.scopes.push(HashMap::new());
+        for input in func.inputs.iter() {
+            self.declare(&input.name, input.body_type().as_ref());
+        }
+        visit::walk_function(self, func);
+        self.scopes.pop();
+    }
+
+    fn visit_class(&mut self, class: &Class) {
+        self.current.push(class.name.clone());
+        visit::walk_class(self, class);
+        self.current.pop();
+    }
+}
@@ -0,0 +1,521 @@
+//! Name resolution across contexts, through the namespaces they declare.
+//!
+//! Every context of a `ContextStore` adds its top level declarations to one tree of
+//! namespaces, then every reference to a function or a class is resolved against it:
+//! - `User` is an import of the file, a declaration of its namespace or a global one.
+//! - `models\User` is relative to the namespace of the file, or to the global namespace.
+//!
+//! The standard library is global, so its functions resolve when nothing else does.
+//! Files that import each other in a circle are found by `cycles`. Within a file,
+//! `inheritance` checks the classes a class extends and `members` the members accessed.
+//! The type aliases of a file are looked up through its imports by `types`.
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::Range,
+};
+
+use crate::compiler::{
+    ast::{
+        visit::{self, Visitor},
+        Call, Class, Expression, Function, MemberLookup, Path, Statement,
+    },
+    parser::context::{Context, ContextStore},
+    symbols::SymbolKind,
+};
+
+pub mod cycles;
+pub mod inheritance;
+pub mod members;
+pub mod types;
+
+/// A top level declaration, along with the context it is declared in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Declaration {
+    pub context: u64,
+    /// The name qualified with its namespace, eg: `app\models\User`.
+    pub path: String,
+    pub kind: SymbolKind,
+    pub span: Range<usize>,
+}
+
+/// A namespace, with the namespaces in it and what it declares.
+#[derive(Debug, Clone, Default)]
+pub struct NamespaceNode {
+    pub children: BTreeMap<String, NamespaceNode>,
+    pub declarations: BTreeMap<String, Vec<Declaration>>,
+}
+
+impl NamespaceNode {
+    /// The namespace at the given path, relative to this one.
+    pub fn get(&self, path: &[String]) -> Option<&NamespaceNode> {
+        match path.split_first() {
+            Some((first, rest)) => self.children.get(first)?.get(rest),
+            None => Some(self),
+        }
+    }
+
+    /// The declarations at the given path, eg: `["app", "models", "User"]`.
+    pub fn lookup(&self, path: &[String]) -> &[Declaration] {
+        let declarations = path
+            .split_last()
+            .and_then(|(name, namespace)| self.get(namespace)?.declarations.get(name));
+        declarations.map(Vec::as_slice).unwrap_or_default()
+    }
+
+    fn get_or_insert(&mut self, path: &[String]) -> &mut NamespaceNode {
+        match path.split_first() {
+            Some((first, rest)) => self
+                .children
+                .entry(first.clone())
+                .or_default()
+                .get_or_insert(rest),
+            None => self,
+        }
+    }
+}
+
+/// The namespaces of every context of a store, from the global namespace down.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, resolve::NamespaceTree, CompilerOptions};
+/// # let mut parser = Parser::new(CompilerOptions::default());
+/// // models.surn: namespace app\models; class User {}
+/// # parser.try_parse_script(
+/// #     "models.surn".to_string(),
+/// #     "namespace app\\models; class User {}".to_string(),
+/// # )?;
+/// let tree = NamespaceTree::build(parser.contexts());
+/// tree.root.lookup(&["app".into(), "models".into(), "User".into()]); // [class User]
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct NamespaceTree {
+    pub root: NamespaceNode,
+}
+
+impl NamespaceTree {
+    /// A source parsed more than once only counts with its last context,
+    /// the global contexts are left out, see `ContextStore::lookup_global`.
+    pub fn build(store: &ContextStore) -> Self {
+        let mut tree = NamespaceTree::default();
+        for context in sources(store) {
+            tree.add(context);
+        }
+        tree
+    }
+
+    /// Adds the top level declarations of a context.
+    pub fn add(&mut self, context: &Context) {
+        let mut collector = DeclarationCollector {
+            context,
+            namespace: Vec::new(),
+            depth: 0,
+            declarations: Vec::new(),
+        };
+        visit::walk_body(&mut collector, &context.body);
+        for (namespace, declaration) in collector.declarations {
+            let name = declaration.path.rsplit('\\').next().unwrap_or_default();
+            self.root
+                .get_or_insert(&namespace)
+                .declarations
+                .entry(name.to_string())
+                .or_default()
+                .push(declaration);
+        }
+    }
+}
+
+/// Why a reference could not be resolved.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NameErrorKind {
+    /// Nothing is declared with the name.
+    Unresolved,
+    /// More than one declaration has the name, eg: `app\models\User (models.surn)`.
+    Ambiguous(Vec<String>),
+}
+
+/// A reference to a function or a class that doesn't resolve to a single declaration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NameError {
+    /// The context the reference is in.
+    pub context: u64,
+    /// The name as it is written, eg: `models\User`.
+    pub name: String,
+    /// The namespace the reference is in, empty for the global namespace.
+    pub namespace: String,
+    /// The span of the reference, imports and `new` don't have one.
+    pub span: Option<Range<usize>>,
+    pub kind: NameErrorKind,
+}
+
+impl NameError {
+    pub fn message(&self) -> String {
+        match &self.kind {
+            NameErrorKind::Unresolved if self.namespace.is_empty() => {
+                format!("`{}` is not declared.", self.name)
+            }
+            NameErrorKind::Unresolved => format!(
+                "`{}` is not declared in `{}` or the global namespace.",
+                self.name, self.namespace
+            ),
+            NameErrorKind::Ambiguous(candidates) => format!(
+                "`{}` is ambiguous, it could be: {}.",
+                self.name,
+                candidates.join(", ")
+            ),
+        }
+    }
+}
+
+/// Resolves the references of every context of a store, see `NamespaceTree`.
+/// The errors are in the order of the contexts, then of the source.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, resolve::resolve, CompilerOptions};
+/// # let mut parser = Parser::new(CompilerOptions::default());
+/// // http.surn: namespace app\http; fn show() { return new models\Post(); }
+/// # parser.try_parse_script(
+/// #     "http.surn".to_string(),
+/// #     "namespace app\\http; fn show() { return new models\\Post(); }".to_string(),
+/// # )?;
+/// resolve(parser.contexts()); // [`models\Post` is not declared in `app\http` or the global namespace.]
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub fn resolve(store: &ContextStore) -> Vec<NameError> {
+    let tree = NamespaceTree::build(store);
+    let mut errors = Vec::new();
+    for context in sources(store) {
+        let mut resolver = Resolver {
+            store,
+            tree: &tree,
+            context,
+            namespace: Vec::new(),
+            imports: HashMap::new(),
+            errors: Vec::new(),
+        };
+        visit::walk_body(&mut resolver, &context.body);
+        errors.extend(resolver.errors);
+    }
+    errors
+}
+
+/// The contexts of the sources of a store, the last one of each source, in order.
+fn sources(store: &ContextStore) -> Vec<&Context> {
+    let mut latest: BTreeMap<&str, &Context> = BTreeMap::new();
+    for context in store.contexts() {
+        if !store.get_globals().contains(&context.origin) {
+            latest.insert(&context.source.name, context);
+        }
+    }
+    let mut contexts: Vec<&Context> = latest.into_values().collect();
+    contexts.sort_by_key(|context| context.origin);
+    contexts
+}
+
+/// Splits a name into its namespaces and itself, eg: `models\User`.
+fn segments(name: &str) -> Vec<String> {
+    name.split('\\').map(str::to_string).collect()
+}
+
+/// What a reference can resolve to, functions and classes don't share names.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Reference {
+    Function,
+    Class,
+}
+
+impl Reference {
+    fn accepts(&self, kind: SymbolKind) -> bool {
+        match self {
+            Reference::Function => kind == SymbolKind::Function,
+            Reference::Class => matches!(
+                kind,
+                SymbolKind::Class | SymbolKind::Enum | SymbolKind::Type
+            ),
+        }
+    }
+}
+
+struct DeclarationCollector<'a> {
+    context: &'a Context,
+    namespace: Vec<String>,
+    /// How many functions and classes deep the walk is, only the top level is declared.
+    depth: usize,
+    declarations: Vec<(Vec<String>, Declaration)>,
+}
+
+impl DeclarationCollector<'_> {
+    fn declare(&mut self, name: &str, kind: SymbolKind) {
+        if self.depth > 0 {
+            return;
+        }
+        let span = self
+            .context
+            .symbols
+            .global()
+            .symbols
+            .iter()
+            .find(|symbol| symbol.name == name && symbol.kind == kind)
+            .map(|symbol| symbol.span.clone())
+            .unwrap_or_default();
+        let mut path = self.namespace.clone();
+        path.push(name.to_string());
+        self.declarations.push((
+            self.namespace.clone(),
+            Declaration {
+                context: self.context.origin,
+                path: path.join("\\"),
+                kind,
+                span,
+            },
+        ));
+    }
+}
+
+impl Visitor for DeclarationCollector<'_> {
+    fn visit_statement(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Namespace(namespace) => match &namespace.body {
+                Some(body) => {
+                    let outer =
+                        std::mem::replace(&mut self.namespace, path_segments(&namespace.path));
+                    self.visit_statement(body);
+                    self.namespace = outer;
+                }
+                None => self.namespace = path_segments(&namespace.path),
+            },
+            Statement::Enum(e) => self.declare(&e.name, SymbolKind::Enum),
+            Statement::TypeDef(def) => self.declare(&def.name, SymbolKind::Type),
+            _ => visit::walk_statement(self, stmt),
+        }
+    }
+
+    fn visit_function(&mut self, func: &Function) {
+        if let Some(name) = &func.name {
+            self.declare(name, SymbolKind::Function);
+        }
+        self.depth += 1;
+        visit::walk_function(self, func);
+        self.depth -= 1;
+    }
+
+    fn visit_class(&mut self, class: &Class) {
+        self.declare(&class.name, SymbolKind::Class);
+    }
+
+    fn visit_expression(&mut self, expr: &Expression) {
+        // only statements declare anything, closures are expressions.
+        if let Expression::Statement(stmt) = expr {
+            self.visit_statement(stmt);
+        }
+    }
+}
+
+/// The segments of a namespace path, eg: `app\models`.
+fn path_segments(path: &Path) -> Vec<String> {
+    path.leaves()
+        .first()
+        .map(|leaf| leaf.iter().map(|part| part.name.to_string()).collect())
+        .unwrap_or_default()
+}
+
+struct Resolver<'a> {
+    store: &'a ContextStore,
+    tree: &'a NamespaceTree,
+    context: &'a Context,
+    namespace: Vec<String>,
+    /// The paths of the imports of the current namespace, by the name they are bound to.
+    imports: HashMap<String, Vec<String>>,
+    errors: Vec<NameError>,
+}
+
+impl Resolver<'_> {
+    fn error(&mut self, name: &str, span: Option<Range<usize>>, kind: NameErrorKind) {
+        self.errors.push(NameError {
+            context: self.context.origin,
+            name: name.to_string(),
+            namespace: self.namespace.join("\\"),
+            span,
+            kind,
+        });
+    }
+
+    /// The declarations at a path that are of the right kind.
+    fn declarations(&self, path: &[String], reference: Reference) -> Vec<&Declaration> {
+        self.tree
+            .root
+            .lookup(path)
+            .iter()
+            .filter(|declaration| reference.accepts(declaration.kind))
+            .collect()
+    }
+
+    fn describe(&self, declaration: &Declaration) -> String {
+        match self.store.get(declaration.context) {
+            Some(context) => format!("{} ({})", declaration.path, context.source.name),
+            None => declaration.path.clone(),
+        }
+    }
+
+    /// Resolves a reference written as `name`, eg: `User` or `models\User`.
+    fn resolve(&mut self, name: &str, span: Option<Range<usize>>, reference: Reference) {
+        let segments = segments(name);
+        let relative: Vec<String> = self
+            .namespace
+            .iter()
+            .chain(segments.iter())
+            .cloned()
+            .collect();
+        let imported = self.imports.get(&segments[0]).map(|import| {
+            let mut path = import.clone();
+            path.extend(segments[1..].iter().cloned());
+            path
+        });
+
+        let candidates = match &imported {
+            Some(path) => vec![path.clone()],
+            None => vec![relative.clone(), segments.clone()],
+        };
+        let found = candidates
+            .iter()
+            .map(|path| self.declarations(path, reference))
+            .find(|declarations| !declarations.is_empty());
+        let mut found = match found {
+            Some(found) => found,
+            None => {
+                let global = segments.len() == 1
+                    && self
+                        .store
+                        .lookup_global(name)
+                        .is_some_and(|(_, symbol)| reference.accepts(symbol.kind));
+                if !global {
+                    self.error(name, span, NameErrorKind::Unresolved);
+                }
+                return;
+            }
+        };
+        // an import hides a declaration of the namespace with the same name.
+        if imported.is_some() && segments.len() == 1 {
+            found.extend(self.declarations(&relative, reference));
+        }
+
+        let first = found[0].context;
+        if found.iter().any(|declaration| declaration.context != first) {
+            let candidates = found
+                .iter()
+                .map(|declaration| self.describe(declaration))
+                .collect();
+            self.error(name, span, NameErrorKind::Ambiguous(candidates));
+        }
+    }
+
+    fn import(&mut self, path: &Path) {
+        for leaf in path.leaves() {
+            let segments: Vec<String> = leaf.iter().map(|part| part.name.to_string()).collect();
+            let name = segments.join("\\");
+            let binding = leaf.last().unwrap().binding().to_string();
+
+            // an import can name a namespace as well as a declaration.
+            let declarations = self.tree.root.lookup(&segments);
+            if declarations.is_empty() && self.tree.root.get(&segments).is_none() {
+                // imports are absolute, the namespace of the file plays no part.
+                self.errors.push(NameError {
+                    context: self.context.origin,
+                    name: name.clone(),
+                    namespace: String::new(),
+                    span: None,
+                    kind: NameErrorKind::Unresolved,
+                });
+            }
+            if let Some(other) = self
+                .imports
+                .get(&binding)
+                .filter(|other| **other != segments)
+            {
+                let candidates = vec![other.join("\\"), name.clone()];
+                self.error(&binding, None, NameErrorKind::Ambiguous(candidates));
+            }
+            self.imports.insert(binding, segments);
+        }
+    }
+
+    /// Whether a call is to a variable or a function declared in a function.
+    fn is_local(&self, call: &Call) -> bool {
+        let global = &self.context.symbols.global().symbols;
+        self.context
+            .symbols
+            .lookup(&call.name, call.span.start)
+            .is_some_and(|symbol| {
+                !global
+                    .iter()
+                    .any(|g| g.span == symbol.span && g.name == symbol.name)
+            })
+    }
+}
+
+impl Visitor for Resolver<'_> {
+    fn visit_statement(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Namespace(namespace) => {
+                let segments = path_segments(&namespace.path);
+                let imports = std::mem::take(&mut self.imports);
+                match &namespace.body {
+                    Some(body) => {
+                        let outer = std::mem::replace(&mut self.namespace, segments);
+                        self.visit_statement(body);
+                        self.namespace = outer;
+                        self.imports = imports;
+                    }
+                    None => self.namespace = segments,
+                }
+            }
+            Statement::Import(path) => self.import(path),
+            _ => visit::walk_statement(self, stmt),
+        }
+    }
+
+    fn visit_class(&mut self, class: &Class) {
+        // the classes it extends and implements don't have a span of their own.
+        for parent in class
+            .extends
+            .iter()
+            .chain(class.implements.iter().flatten())
+        {
+            self.resolve(parent, None, Reference::Class);
+        }
+        visit::walk_class(self, class);
+    }
+
+    fn visit_call(&mut self, call: &Call) {
+        if !self.is_local(call) {
+            self.resolve(&call.name, Some(call.span.clone()), Reference::Function);
+        }
+        visit::walk_call(self, call);
+    }
+
+    fn visit_expression(&mut self, expr: &Expression) {
+        match expr {
+            Expression::New(call) => {
+                self.resolve(&call.name, None, Reference::Class);
+                visit::walk_expression(self, expr);
+            }
+            Expression::Member(member) => {
+                if let MemberLookup::Static = member.lookup {
+                    let origin = member.origin.value().unwrap_or_default();
+                    if !matches!(origin.as_str(), "self" | "static" | "parent") {
+                        self.resolve(&origin, Some(member.origin.range()), Reference::Class);
+                    }
+                }
+                match &*member.name {
+                    // a method, its name is looked up on the class.
+                    Expression::Call(call) => visit::walk_call(self, call),
+                    Expression::Literal(_) => {}
+                    name => self.visit_expression(name),
+                }
+            }
+            _ => visit::walk_expression(self, expr),
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! Type aliases across contexts, a file sees the types it defines, then the types of the
+//! files it imports, then the global ones.
+use crate::compiler::{
+    ast::types::TypeDefinition,
+    parser::context::{Context, ContextStore},
+};
+
+use super::{cycles, NamespaceTree};
+
+/// Finds the definition of a type for a context, see `TypeStore`.
+/// The namespaces are built once, so one resolver answers for every context of a store.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, resolve::types::TypeResolver, CompilerOptions};
+/// # let mut parser = Parser::new(CompilerOptions::default());
+/// // ids.surn: namespace app\ids; type Id = int;
+/// // main.surn: use app::ids::Id; var id: Id = 1;
+/// # parser.try_parse_script("ids.surn".to_string(), "namespace app\\ids; type Id = int;".to_string())?;
+/// # parser.try_parse_script("main.surn".to_string(), "use app::ids::Id; var id: Id = 1;".to_string())?;
+/// # let main = parser.contexts().find("main.surn").unwrap().id();
+/// let resolver = TypeResolver::new(parser.contexts());
+/// let (context, definition) = resolver.resolve(main, "Id").unwrap();
+/// &context.source.name; // ids.surn
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub struct TypeResolver<'a> {
+    store: &'a ContextStore,
+    tree: NamespaceTree,
+}
+
+impl<'a> TypeResolver<'a> {
+    pub fn new(store: &'a ContextStore) -> Self {
+        Self {
+            store,
+            tree: NamespaceTree::build(store),
+        }
+    }
+
+    /// The definition of a type, along with the context it is defined in.
+    /// The context itself is looked in first, then the contexts it imports in the order
+    /// of their imports, then the global contexts.
+    pub fn resolve(&self, context: u64, name: &str) -> Option<(&'a Context, &'a TypeDefinition)> {
+        let context = self.store.get(context)?;
+        let imported = cycles::imports(context, &self.tree)
+            .into_iter()
+            .map(|site| site.target);
+        let globals = self.store.get_globals().iter().copied();
+        std::iter::once(context.origin)
+            .chain(imported)
+            .chain(globals)
+            .filter_map(|id| self.store.get(id))
+            .find_map(|context| {
+                context
+                    .types
+                    .get(name)
+                    .map(|definition| (context, definition))
+            })
+    }
+}
@@ -0,0 +1,151 @@
+//! The standard library, functions every script can call without declaring them.
+//!
+//! The functions are declared in a context of their own that every `ContextStore`
+//! registers first, so their calls resolve like calls to any other declaration.
+//! A function declared in the script with the same name is called instead.
+//! How a call is generated is up to each language, see `transpiler::langs::intrinsics`.
+use crate::compiler::{
+    ast::{
+        build::{BodyBuilder, FunctionBuilder},
+        types::{BuiltInType, TypeKind},
+        AstBody, Purity,
+    },
+    parser::context::{Context, SourceOrigin},
+    symbols::SymbolTable,
+};
+
+/// The name of the context the standard library is declared in.
+pub const STDLIB_NAME: &str = "std";
+
+/// A function of the standard library.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Builtin {
+    pub name: &'static str,
+    /// The parameters, by name and type.
+    pub inputs: &'static [(&'static str, &'static str)],
+    /// The return type, `None` when nothing is returned.
+    pub output: Option<&'static str>,
+    /// Whether the function reads or writes outside of the program.
+    pub io: bool,
+    /// Whether the function throws for some of its arguments, eg: `split` with an empty separator.
+    pub throws: bool,
+    /// Whether the function changes one of its arguments, eg: `push`.
+    pub mutates: bool,
+}
+
+impl Builtin {
+    /// What a call does besides computing its result, if anything, eg: `does I/O`.
+    pub fn effect(&self) -> Option<&'static str> {
+        match (self.io, self.throws, self.mutates) {
+            (true, _, _) => Some("does I/O"),
+            (_, true, _) => Some("can throw"),
+            (_, _, true) => Some("changes its arguments"),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! builtins {
+    ($($name: literal ($($input: ident: $ty: literal),*) $(-> $output: literal)? $(, $effect: ident)?;)*) => {
+        /// Every function of the standard library.
+        pub const BUILTINS: &[Builtin] = &[
+            $(Builtin {
+                name: $name,
+                inputs: &[$((stringify!($input), $ty)),*],
+                output: builtins!(@output $($output)?),
+                io: builtins!(@is io $($effect)?),
+                throws: builtins!(@is throws $($effect)?),
+                mutates: builtins!(@is mutates $($effect)?),
+            },)*
+        ];
+    };
+    (@output $output: literal) => { Some($output) };
+    (@output) => { None };
+    (@is io io) => { true };
+    (@is throws throws) => { true };
+    (@is mutates mutates) => { true };
+    (@is $effect: ident $($other: ident)?) => { false };
+}
+
+builtins! {
+    // io
+    "println"(value: "any"), io;
+    "print"(value: "any"), io;
+    "read_line"() -> "string", io;
+    "read_file"(path: "string") -> "string", io;
+    "write_file"(path: "string", contents: "string"), io;
+    "random"(min: "int", max: "int") -> "int", io;
+    // strings
+    "to_upper"(value: "string") -> "string";
+    "to_lower"(value: "string") -> "string";
+    "trim"(value: "string") -> "string";
+    "contains"(value: "string", needle: "string") -> "bool";
+    "starts_with"(value: "string", prefix: "string") -> "bool";
+    "ends_with"(value: "string", suffix: "string") -> "bool";
+    "replace"(value: "string", search: "string", replacement: "string") -> "string";
+    "split"(value: "string", separator: "string") -> "string[]", throws;
+    "join"(values: "string[]", separator: "string") -> "string";
+    // strings and arrays
+    "len"(value: "any") -> "int";
+    // arrays
+    "push"(values: "any[]", value: "any"), mutates;
+    "reverse"(values: "any[]") -> "any[]";
+    "keys"(values: "any[]") -> "any[]";
+    // math
+    "abs"(value: "float") -> "float";
+    "floor"(value: "float") -> "float";
+    "ceil"(value: "float") -> "float";
+    "round"(value: "float") -> "float";
+    "sqrt"(value: "float") -> "float";
+    "pow"(base: "float", exponent: "float") -> "float";
+    "min"(a: "float", b: "float") -> "float";
+    "max"(a: "float", b: "float") -> "float";
+}
+
+/// The function of the standard library with the given name.
+pub fn find(name: &str) -> Option<&'static Builtin> {
+    BUILTINS.iter().find(|builtin| builtin.name == name)
+}
+
+/// The type of a parameter or return type of a builtin, eg: `string[]`.
+fn ty(name: &str) -> TypeKind {
+    match name.strip_suffix("[]") {
+        Some(inner) => TypeKind::BuiltIn(BuiltInType::Array(Box::new(ty(inner)))),
+        None => TypeKind::BuiltIn(
+            BuiltInType::from_string(name.to_string()).expect("builtins use built in types"),
+        ),
+    }
+}
+
+/// The declarations of the standard library, functions with empty bodies
+/// whose purity is already known.
+pub fn body() -> AstBody {
+    let mut body = BodyBuilder::new();
+    for builtin in BUILTINS {
+        let mut function = FunctionBuilder::new(builtin.name);
+        for (name, input) in builtin.inputs {
+            function = function.input(name, ty(input));
+        }
+        if let Some(output) = builtin.output {
+            function = function.returns(ty(output));
+        }
+        let mut function = function.build();
+        function.purity = match builtin.effect() {
+            Some(effect) => Purity::Impure(effect.to_string()),
+            None => Purity::Pure,
+        };
+        body = body.function(function);
+    }
+    body.build()
+}
+
+/// The context the standard library is declared in, see `ContextStore::new`.
+pub fn context() -> Context {
+    let mut context = Context::new(
+        SourceOrigin::new_virtual(STDLIB_NAME.to_string(), String::new()),
+        0,
+    );
+    context.body = body();
+    context.symbols = SymbolTable::build(&context.body);
+    context
+}
@@ -0,0 +1,409 @@
+use std::{collections::HashSet, ops::Range};
+
+use crate::compiler::ast::{
+    types::{BuiltInType, TypeKind},
+    AstBody, Class, ClassAllowedStatement, Expression, Function, NodeKind, Statement, Visibility,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Variable,
+    Constant,
+    Parameter,
+    Function,
+    Class,
+    Property,
+    Method,
+    Enum,
+    Type,
+}
+
+/// A declared name.
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// The span of the declaration. Parameters point at their function and
+    /// properties at their class, they don't have a span of their own.
+    pub span: Range<usize>,
+    pub visibility: Visibility,
+    /// The declared type, functions and methods have a function type.
+    pub ty: Option<TypeKind>,
+}
+
+impl Symbol {
+    /// Whether the symbol can only be used after it is declared.
+    /// Functions, classes and types can be used anywhere in their scope.
+    pub fn is_ordered(&self) -> bool {
+        matches!(
+            self.kind,
+            SymbolKind::Variable | SymbolKind::Constant | SymbolKind::Parameter
+        )
+    }
+}
+
+/// The names declared in the body of a function or class, or at the top level.
+#[derive(Debug, Clone)]
+pub struct Scope {
+    /// The name of the function or class the scope is the body of, the top level has none.
+    pub owner: Option<String>,
+    /// The span of the body, the top level spans the whole source.
+    pub span: Range<usize>,
+    /// The index of the enclosing scope.
+    pub parent: Option<usize>,
+    pub symbols: Vec<Symbol>,
+}
+
+/// Every scope of a body and the symbols declared in them.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, CompilerOptions};
+/// # use surn::compiler::symbols::SymbolTable;
+/// // var x = 1;
+/// // fn double(x: int): int { return x * 2; }
+/// # let source = "var x = 1;\nfn double(x: int): int { return x * 2; }\n";
+/// # let body = Parser::new(CompilerOptions::default())
+/// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+/// let table = SymbolTable::build(&body);
+/// table.lookup("x", 40); // the parameter `x` of `double`
+/// table.lookup("x", 0);  // the variable `x`
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct SymbolTable {
+    scopes: Vec<Scope>,
+}
+
+impl Default for SymbolTable {
+    fn default() -> Self {
+        Self {
+            scopes: vec![Scope {
+                owner: None,
+                span: 0..usize::MAX,
+                parent: None,
+                symbols: Vec::new(),
+            }],
+        }
+    }
+}
+
+impl SymbolTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn build(body: &AstBody) -> Self {
+        let mut table = Self::new();
+        for node in body.get_program() {
+            if let NodeKind::Statement(stmt) = &node.inner {
+                table.statement(0, stmt, node.start()..node.end(), Visibility::Module);
+            }
+        }
+        table
+    }
+
+    /// The top level scope.
+    pub fn global(&self) -> &Scope {
+        &self.scopes[0]
+    }
+
+    pub fn scopes(&self) -> &[Scope] {
+        &self.scopes
+    }
+
+    /// The index of the innermost scope that contains the offset.
+    pub fn scope_at(&self, offset: usize) -> usize {
+        let mut current = 0;
+        // children are always added after their parent.
+        for (index, scope) in self.scopes.iter().enumerate().skip(1) {
+            if scope.span.contains(&offset) && self.encloses(current, index) {
+                current = index;
+            }
+        }
+        current
+    }
+
+    /// Every symbol that can be used at the offset, the innermost first.
+    /// A symbol that is shadowed by an inner one is left out.
+    pub fn visible_at(&self, offset: usize) -> Vec<&Symbol> {
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut visible: Vec<&Symbol> = Vec::new();
+        let mut scope = Some(self.scope_at(offset));
+        while let Some(index) = scope {
+            let scope_symbols = self.scopes[index]
+                .symbols
+                .iter()
+                .filter(|s| !s.is_ordered() || s.span.start <= offset)
+                .filter(|s| !matches!(s.kind, SymbolKind::Property | SymbolKind::Method));
+            for symbol in scope_symbols.rev() {
+                if seen.insert(symbol.name.as_str()) {
+                    visible.push(symbol);
+                }
+            }
+            scope = self.scopes[index].parent;
+        }
+        visible
+    }
+
+    /// The symbol a name refers to at the offset.
+    pub fn lookup(&self, name: &str, offset: usize) -> Option<&Symbol> {
+        self.visible_at(offset)
+            .into_iter()
+            .find(|symbol| symbol.name == name)
+    }
+
+    /// The members of a class, by the name of the class.
+    pub fn members(&self, class: &str) -> Vec<&Symbol> {
+        self.scopes
+            .iter()
+            .filter(|scope| scope.owner.as_deref() == Some(class))
+            .flat_map(|scope| scope.symbols.iter())
+            .filter(|s| matches!(s.kind, SymbolKind::Property | SymbolKind::Method))
+            .collect()
+    }
+
+    /// Every declaration of a name, in any scope.
+    pub fn declarations(&self, name: &str) -> Vec<&Symbol> {
+        self.scopes
+            .iter()
+            .flat_map(|scope| scope.symbols.iter())
+            .filter(|symbol| symbol.name == name)
+            .collect()
+    }
+
+    fn encloses(&self, outer: usize, mut inner: usize) -> bool {
+        while let Some(parent) = self.scopes[inner].parent {
+            if parent == outer {
+                return true;
+            }
+            inner = parent;
+        }
+        false
+    }
+
+    fn declare(&mut self, scope: usize, symbol: Symbol) {
+        self.scopes[scope].symbols.push(symbol);
+    }
+
+    fn open(&mut self, parent: usize, owner: Option<String>, span: Range<usize>) -> usize {
+        self.scopes.push(Scope {
+            owner,
+            span,
+            parent: Some(parent),
+            symbols: Vec::new(),
+        });
+        self.scopes.len() - 1
+    }
+
+    /// `span` is the span of the top level node the statement is in.
+    fn statement(
+        &mut self,
+        scope: usize,
+        stmt: &Statement,
+        span: Range<usize>,
+        visibility: Visibility,
+    ) {
+        match stmt {
+            Statement::Var(var) | Statement::Const(var) => {
+                let kind = match stmt {
+                    Statement::Const(_) => SymbolKind::Constant,
+                    _ => SymbolKind::Variable,
+                };
+                self.declare(
+                    scope,
+                    Symbol {
+                        name: var.name.to_string(),
+                        kind,
+                        span: var.span.clone(),
+                        visibility: var.visibility.clone(),
+                        ty: var.ty.clone(),
+                    },
+                );
+            }
+            Statement::Destructure(destructure) => {
+                for name in destructure.names.iter() {
+                    self.declare(
+                        scope,
+                        Symbol {
+                            name: name.clone(),
+                            kind: SymbolKind::Variable,
+                            span: destructure.span.clone(),
+                            visibility: Visibility::Module,
+                            ty: None,
+                        },
+                    );
+                }
+            }
+            Statement::Static(s) => self.statement(scope, &s.statement, span, s.visibility.clone()),
+            Statement::Function(func) => self.function(scope, func, SymbolKind::Function),
+            Statement::Class(class) => self.class(scope, class),
+            Statement::Enum(e) => self.declare(
+                scope,
+                Symbol {
+                    name: e.name.clone(),
+                    kind: SymbolKind::Enum,
+                    span,
+                    visibility,
+                    ty: None,
+                },
+            ),
+            Statement::TypeDef(def) => self.declare(
+                scope,
+                Symbol {
+                    name: def.name.clone(),
+                    kind: SymbolKind::Type,
+                    span,
+                    visibility,
+                    ty: Some(def.kind.clone()),
+                },
+            ),
+            Statement::Block(exprs) => {
+                for expr in exprs.iter() {
+                    if let Expression::Statement(stmt) = expr {
+                        self.statement(scope, stmt, span.clone(), Visibility::Module);
+                    }
+                }
+            }
+            Statement::Namespace(namespace) => {
+                if let Some(body) = &namespace.body {
+                    self.statement(scope, body, span, visibility);
+                }
+            }
+            Statement::If(i) => {
+                self.statement(scope, &i.body, span.clone(), Visibility::Module);
+                if let Some(otherwise) = &i.otherwise {
+                    self.statement(scope, otherwise, span, Visibility::Module);
+                }
+            }
+            Statement::Try(t) => {
+                self.statement(scope, &t.body, span.clone(), Visibility::Module);
+                for catch in t.catches.iter() {
+                    self.statement(scope, &catch.body, span.clone(), Visibility::Module);
+                }
+                if let Some(finally) = &t.finally {
+                    self.statement(scope, finally, span, Visibility::Module);
+                }
+            }
+            Statement::Return(_)
+            | Statement::Throw(_)
+            | Statement::Import(_)
+            | Statement::MacroInvocation(_) => {}
+        }
+    }
+
+    fn function(&mut self, scope: usize, func: &Function, kind: SymbolKind) {
+        let any = || TypeKind::BuiltIn(BuiltInType::Any);
+        if let Some(name) = &func.name {
+            let params = func
+                .inputs
+                .iter()
+                .map(|input| input.ty.clone().unwrap_or_else(any))
+                .collect();
+            self.declare(
+                scope,
+                Symbol {
+                    name: name.clone(),
+                    kind,
+                    span: func.span.clone(),
+                    visibility: func.visibility.clone(),
+                    ty: Some(TypeKind::function(
+                        params,
+                        func.outputs.clone().unwrap_or_else(any),
+                    )),
+                },
+            );
+        }
+
+        let body = self.open(scope, func.name.clone(), func.body_span.clone());
+        for input in func.inputs.iter() {
+            self.declare(
+                body,
+                Symbol {
+                    name: input.name.clone(),
+                    kind: SymbolKind::Parameter,
+                    span: or_span(&input.span, &func.span),
+                    visibility: Visibility::Module,
+                    ty: input.ty.clone(),
+                },
+            );
+        }
+        self.statement(body, &func.body, func.body_span.clone(), Visibility::Module);
+    }
+
+    fn class(&mut self, scope: usize, class: &Class) {
+        self.declare(
+            scope,
+            Symbol {
+                name: class.name.clone(),
+                kind: SymbolKind::Class,
+                span: class.span.clone(),
+                visibility: Visibility::Public,
+                ty: None,
+            },
+        );
+
+        let body = self.open(scope, Some(class.name.clone()), class.body_span.clone());
+        for property in class.body.properties.iter() {
+            self.declare(
+                body,
+                Symbol {
+                    name: property.name.clone(),
+                    kind: SymbolKind::Property,
+                    span: or_span(&property.span, &class.span),
+                    visibility: property.visibility.clone(),
+                    ty: property.ty.clone(),
+                },
+            );
+        }
+        for method in class.body.methods.iter() {
+            self.function(body, method, SymbolKind::Method);
+        }
+        for other in class.body.other.iter() {
+            self.member(body, other, &class.span);
+        }
+    }
+
+    /// `span` is the span of the class the member is in.
+    fn member(&mut self, scope: usize, member: &ClassAllowedStatement, span: &Range<usize>) {
+        match member {
+            ClassAllowedStatement::Property(property) => {
+                self.declare(
+                    scope,
+                    Symbol {
+                        name: property.name.clone(),
+                        kind: SymbolKind::Property,
+                        span: or_span(&property.span, span),
+                        visibility: property.visibility.clone(),
+                        ty: property.ty.clone(),
+                    },
+                );
+            }
+            ClassAllowedStatement::Method(method) => {
+                self.function(scope, method, SymbolKind::Method)
+            }
+            ClassAllowedStatement::Constant(constant) => {
+                self.declare(
+                    scope,
+                    Symbol {
+                        name: constant.name.to_string(),
+                        kind: SymbolKind::Constant,
+                        span: constant.span.clone(),
+                        visibility: constant.visibility.clone(),
+                        ty: constant.ty.clone(),
+                    },
+                );
+            }
+            ClassAllowedStatement::Static(inner) => self.member(scope, inner, span),
+            ClassAllowedStatement::Macro(_) | ClassAllowedStatement::Import(_) => {}
+        }
+    }
+}
+
+/// The span of a declaration, or the one of what it is declared in when it wasn't parsed.
+fn or_span(span: &Range<usize>, outer: &Range<usize>) -> Range<usize> {
+    match span.is_empty() {
+        true => outer.clone(),
+        false => span.clone(),
+    }
+}
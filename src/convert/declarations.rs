@@ -0,0 +1,556 @@
+use std::collections::HashMap;
+#[cfg(feature = "fs")]
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::compiler::ast::types::{BuiltInType, TypeKind, TypeParam};
+
+use super::php::{
+    parse_declarations, PhpClassKind, PhpConst, PhpFile, PhpFunction, PhpParam, PhpProperty,
+};
+
+/// The extension of declaration files, eg: `Invoice.d.surn`
+pub const DECLARATION_EXTENSION: &str = "d.surn";
+
+/// The types a docblock gives, keyed by parameter name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DocTypes {
+    pub params: HashMap<String, String>,
+    pub ret: Option<String>,
+    pub var: Option<String>,
+}
+
+impl DocTypes {
+    /// Reads the `@param`, `@return` and `@var` tags of a docblock.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::convert::declarations::DocTypes;
+    /// let doc = DocTypes::parse("/** @param callable(int): bool $filter */");
+    /// &doc.params["filter"]; // callable(int): bool
+    /// ```
+    pub fn parse(doc: &str) -> Self {
+        let mut types = DocTypes::default();
+        let doc = doc.trim_start_matches("/**").trim_end_matches("*/");
+        for line in doc.lines() {
+            let line = line.trim().trim_start_matches('*').trim();
+            let (tag, rest) = match line.split_once(char::is_whitespace) {
+                Some((tag, rest)) => (tag, rest.trim()),
+                None => continue,
+            };
+            let (ty, rest) = split_doc_type(rest);
+            match tag {
+                "@param" | "@phpstan-param" | "@psalm-param" => {
+                    let name = rest.split_whitespace().next().unwrap_or_default();
+                    if let Some(name) = name.trim_start_matches("...").strip_prefix('$') {
+                        types.params.insert(name.to_string(), ty);
+                    }
+                }
+                "@return" | "@phpstan-return" | "@psalm-return" => types.ret = Some(ty),
+                "@var" => types.var = Some(ty),
+                _ => {}
+            }
+        }
+        types
+    }
+}
+
+/// Splits the type off the front of a docblock tag, types can contain spaces inside
+/// brackets and after the `:` of a callable, eg: `array<int, string> $map`
+fn split_doc_type(text: &str) -> (String, &str) {
+    let mut depth = 0;
+    let mut after_colon = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            '<' | '(' | '{' | '[' => depth += 1,
+            '>' | ')' | '}' | ']' => depth -= 1,
+            ':' if depth == 0 => after_colon = true,
+            c if c.is_whitespace() && depth == 0 && !after_colon => {
+                return (text[..i].to_string(), text[i..].trim_start());
+            }
+            c if !c.is_whitespace() => after_colon = false,
+            _ => {}
+        }
+    }
+    (text.to_string(), "")
+}
+
+/// Converts a php type, as hinted or as written in a docblock, to a surn type.
+/// Types surn can't express become `any`, and `void` has no surn type at all.
+/// `self` and `static` become the name of the class they're used in.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::convert::declarations::surn_type;
+/// surn_type("?\\App\\User", None); // Some(User?)
+/// surn_type("callable(int, string): bool", None); // Some((int, string) => bool)
+/// surn_type("void", None); // None
+/// ```
+pub fn surn_type(php: &str, class: Option<&str>) -> Option<TypeKind> {
+    let php = php.trim();
+    if php.eq_ignore_ascii_case("void") || php.eq_ignore_ascii_case("never") {
+        return None;
+    }
+    Some(TypeParser { class }.union(php))
+}
+
+struct TypeParser<'a> {
+    class: Option<&'a str>,
+}
+
+impl TypeParser<'_> {
+    fn union(&self, php: &str) -> TypeKind {
+        let parts = split_top_level(php, '|');
+        let has_null = parts.iter().any(|p| p.eq_ignore_ascii_case("null"));
+        let types = parts
+            .iter()
+            .filter(|p| !p.eq_ignore_ascii_case("null"))
+            .map(|p| self.single(p))
+            .collect::<Vec<TypeKind>>();
+        if types
+            .iter()
+            .any(|t| matches!(t, TypeKind::BuiltIn(BuiltInType::Any)))
+        {
+            return TypeKind::BuiltIn(BuiltInType::Any);
+        }
+        match (types.len(), has_null) {
+            (0, _) => TypeKind::BuiltIn(BuiltInType::Null),
+            (1, true) => nullable(types.into_iter().next().unwrap()),
+            (1, false) => types.into_iter().next().unwrap(),
+            (_, true) => TypeKind::union(
+                types
+                    .into_iter()
+                    .chain([TypeKind::BuiltIn(BuiltInType::Null)])
+                    .collect(),
+            ),
+            (_, false) => TypeKind::union(types),
+        }
+    }
+
+    fn single(&self, php: &str) -> TypeKind {
+        let php = php.trim();
+        if let Some(inner) = php.strip_prefix('?') {
+            return nullable(self.single(inner));
+        }
+        if php.starts_with('(') && php.ends_with(')') {
+            return self.union(&php[1..php.len() - 1]);
+        }
+        if php.ends_with("[]") {
+            return TypeKind::built_in("array".to_string());
+        }
+        if php.contains('&') {
+            // intersections have no surn type.
+            return TypeKind::BuiltIn(BuiltInType::Any);
+        }
+
+        let (name, args) = match php.find(['<', '(']) {
+            Some(i) => (&php[..i], Some(&php[i..])),
+            None => (php, None),
+        };
+        let name = name.trim_start_matches('\\');
+        let name = name.rsplit('\\').next().unwrap_or(name);
+        match (name.to_lowercase().as_str(), args) {
+            ("callable" | "closure", Some(args)) if args.starts_with('(') => self.callable(args),
+            ("int" | "integer" | "positive-int" | "negative-int" | "non-negative-int", _) => {
+                TypeKind::built_in("int".to_string())
+            }
+            ("float" | "double", _) => TypeKind::built_in("float".to_string()),
+            ("bool" | "boolean" | "true" | "false", _) => TypeKind::built_in("bool".to_string()),
+            ("string" | "non-empty-string" | "class-string" | "numeric-string", _) => {
+                TypeKind::built_in("string".to_string())
+            }
+            ("array" | "list" | "non-empty-array" | "non-empty-list" | "iterable", _) => {
+                TypeKind::built_in("array".to_string())
+            }
+            ("self" | "static" | "$this", _) => match self.class {
+                Some(class) => TypeKind::reference(class.to_string(), None),
+                None => TypeKind::BuiltIn(BuiltInType::Any),
+            },
+            ("mixed" | "object" | "callable" | "closure" | "resource" | "scalar", _) => {
+                TypeKind::BuiltIn(BuiltInType::Any)
+            }
+            (_, Some(args)) if args.starts_with('<') && args.ends_with('>') => {
+                let params = split_top_level(&args[1..args.len() - 1], ',')
+                    .iter()
+                    .map(|arg| TypeParam::new(self.union(arg)))
+                    .collect::<Vec<TypeParam>>();
+                TypeKind::reference(name.to_string(), Some(params))
+            }
+            (_, None)
+                if !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_') =>
+            {
+                TypeKind::reference(name.to_string(), None)
+            }
+            _ => TypeKind::BuiltIn(BuiltInType::Any),
+        }
+    }
+
+    /// `(int, string): bool` of a `callable(int, string): bool`
+    fn callable(&self, args: &str) -> TypeKind {
+        let close = match matching_paren(args) {
+            Some(close) => close,
+            None => return TypeKind::BuiltIn(BuiltInType::Any),
+        };
+        let params = split_top_level(&args[1..close], ',')
+            .iter()
+            .filter(|p| !p.is_empty())
+            // docblock parameters may be named, eg: `callable(int $id): bool`
+            .map(|p| self.union(p.split_whitespace().next().unwrap_or_default()))
+            .collect::<Vec<TypeKind>>();
+        let ret = args[close + 1..]
+            .trim()
+            .strip_prefix(':')
+            .and_then(|ret| surn_type(ret, self.class))
+            .unwrap_or(TypeKind::BuiltIn(BuiltInType::Any));
+        TypeKind::function(params, ret)
+    }
+}
+
+/// `T?`, but a function type is unioned with `null` instead, as `(int) => bool?` would
+/// make its return type nullable.
+fn nullable(ty: TypeKind) -> TypeKind {
+    match ty {
+        TypeKind::BuiltIn(BuiltInType::Any) => ty,
+        TypeKind::Function { .. } => {
+            TypeKind::union(vec![ty, TypeKind::BuiltIn(BuiltInType::Null)])
+        }
+        ty => TypeKind::nullable(ty),
+    }
+}
+
+fn matching_paren(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Splits on a separator that isn't nested in brackets.
+fn split_top_level(text: &str, separator: char) -> Vec<String> {
+    let mut parts: Vec<String> = Vec::new();
+    let mut depth = 0;
+    let mut current = String::new();
+    for c in text.chars() {
+        match c {
+            '<' | '(' | '{' | '[' => depth += 1,
+            '>' | ')' | '}' | ']' => depth -= 1,
+            c if c == separator && depth == 0 => {
+                parts.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    parts.push(current.trim().to_string());
+    parts
+}
+
+/// Generates the surn declarations of a php file, the signatures of its functions
+/// and classes with empty bodies. Docblock types are preferred over type hints,
+/// as they're usually more precise, eg: `callable(int): bool` over `callable`.
+///
+/// Interfaces, traits and enums are declared as classes.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::convert::declarations::php_to_declarations;
+/// // <?php
+/// // /** @param callable(int): bool $filter */
+/// // function count_where(array $items, callable $filter): int { ... }
+/// # let source = "<?php\n/** @param callable(int): bool $filter */\nfunction count_where(array $items, callable $filter): int { return 0; }\n";
+/// php_to_declarations(source); // fn count_where(items: array, filter: (int) => bool): int {\n}
+/// ```
+pub fn php_to_declarations(source: &str) -> String {
+    declarations(&parse_declarations(source))
+}
+
+pub fn declarations(file: &PhpFile) -> String {
+    let mut output = String::from("// Generated by surn from php, only the signatures are kept.\n");
+    if let Some(namespace) = &file.namespace {
+        output.push_str(&format!("namespace {};\n", namespace));
+    }
+    for import in file.uses.iter() {
+        let path = import.path.replace('\\', "::");
+        match &import.alias {
+            Some(alias) => output.push_str(&format!("use {} as {};\n", path, alias)),
+            None => output.push_str(&format!("use {};\n", path)),
+        }
+    }
+    if !file.consts.is_empty() {
+        output.push('\n');
+    }
+    for constant in file.consts.iter() {
+        output.push_str(&self::constant(constant, ""));
+    }
+
+    for func in file.functions.iter() {
+        output.push('\n');
+        output.push_str(&function(func, None, ""));
+    }
+
+    for class in file.classes.iter() {
+        output.push('\n');
+        match class.kind {
+            PhpClassKind::Interface => output.push_str("// interface\n"),
+            PhpClassKind::Trait => output.push_str("// trait\n"),
+            PhpClassKind::Enum => output.push_str("// enum\n"),
+            PhpClassKind::Class => {}
+        }
+        let short = |name: &String| name.rsplit('\\').next().unwrap_or(name).to_string();
+        let mut header = format!("class {}", class.name);
+        // interfaces extend other interfaces, which surn only has as implements.
+        let (extends, implements) = match class.kind {
+            PhpClassKind::Interface => (Vec::new(), class.extends.clone()),
+            _ => (class.extends.clone(), class.implements.clone()),
+        };
+        if let Some(parent) = extends.first() {
+            header.push_str(&format!(" extends {}", short(parent)));
+        }
+        if !implements.is_empty() {
+            let names = implements.iter().map(short).collect::<Vec<String>>();
+            header.push_str(&format!(" implements {}", names.join(", ")));
+        }
+        output.push_str(&header);
+        output.push_str(" {\n");
+
+        for constant in class.consts.iter() {
+            output.push_str(&self::constant(constant, "    "));
+        }
+        let promoted = class
+            .methods
+            .iter()
+            .filter(|m| m.name == "__construct")
+            .flat_map(|m| m.params.iter().filter(|p| p.promoted.is_some()));
+        for param in promoted {
+            let ty = param_type(param, None, Some(&class.name));
+            let visibility = visibility(param.promoted.iter().map(|p| p.as_str()));
+            output.push_str(&format!("    {}{}: {};\n", visibility, param.name, ty));
+        }
+        for property in class.properties.iter() {
+            output.push_str(&format!("    {};\n", self::property(property, &class.name)));
+        }
+        for method in class.methods.iter() {
+            output.push_str(&function(method, Some(&class.name), "    "));
+        }
+        output.push_str("}\n");
+    }
+    output
+}
+
+/// A constant with its value, values surn can't express are left out with a `TODO` comment,
+/// eg: `priv const LIMIT = 10;`
+fn constant(constant: &PhpConst, indent: &str) -> String {
+    let value = match const_value(&constant.value) {
+        Some(value) => value,
+        None => {
+            return format!(
+                "{}// TODO: the value of `{}` can't be converted to surn.\n",
+                indent, constant.name
+            )
+        }
+    };
+    // class constants are public unless they say otherwise.
+    let visibility = match visibility(constant.modifiers.iter().map(|m| m.as_str())) {
+        "pub " => "",
+        visibility => visibility,
+    };
+    format!(
+        "{}{}const {} = {};\n",
+        indent, visibility, constant.name, value
+    )
+}
+
+/// Numbers, strings without escapes or interpolation, `true`, `false`, `null`
+/// and other constants, eg: `self::MAX`
+fn const_value(php: &str) -> Option<String> {
+    let quoted = |quote: char| {
+        php.strip_prefix(quote)
+            .and_then(|s| s.strip_suffix(quote))
+            .filter(|s| !s.contains(['\\', '"', '$', '{']))
+    };
+    if let Some(string) = quoted('\'').or_else(|| quoted('"')) {
+        return Some(format!("\"{}\"", string));
+    }
+    let number = php.strip_prefix('-').unwrap_or(php);
+    if number.starts_with(|c: char| c.is_ascii_digit())
+        && number.chars().all(|c| c.is_ascii_digit() || c == '.')
+    {
+        return Some(php.to_string());
+    }
+    let lower = php.to_lowercase();
+    if ["true", "false", "null"].contains(&lower.as_str()) {
+        return Some(lower);
+    }
+    let is_name = |name: &str| {
+        name.starts_with(|c: char| c.is_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+    };
+    match php.split_once("::") {
+        Some((class, name)) if is_name(class) && is_name(name) => Some(php.to_string()),
+        _ => None,
+    }
+}
+
+/// A property without its default value, eg: `pub static currency: string?`
+pub(super) fn property(property: &PhpProperty, class: &str) -> String {
+    let doc = property
+        .doc
+        .as_deref()
+        .map(DocTypes::parse)
+        .unwrap_or_default();
+    let ty = doc
+        .var
+        .as_deref()
+        .or(property.ty.as_deref())
+        .and_then(|ty| surn_type(ty, Some(class)))
+        .unwrap_or(TypeKind::BuiltIn(BuiltInType::Any));
+    let modifiers = property.modifiers.iter().map(|m| m.as_str());
+    let is_static = property.modifiers.iter().any(|m| m == "static");
+    format!(
+        "{}{}{}: {}",
+        visibility(modifiers),
+        if is_static { "static " } else { "" },
+        property.name,
+        ty
+    )
+}
+
+pub(super) fn visibility<'a>(modifiers: impl Iterator<Item = &'a str>) -> &'static str {
+    let mut visibility = "pub ";
+    for modifier in modifiers {
+        match modifier {
+            "private" => visibility = "priv ",
+            "protected" => visibility = "prot ",
+            _ => {}
+        }
+    }
+    visibility
+}
+
+pub(super) fn param_type(
+    param: &PhpParam,
+    doc: Option<&DocTypes>,
+    class: Option<&str>,
+) -> TypeKind {
+    let doc = doc.and_then(|doc| doc.params.get(&param.name));
+    let ty = doc
+        .map(|ty| ty.as_str())
+        .or(param.ty.as_deref())
+        .and_then(|ty| surn_type(ty, class))
+        .unwrap_or(TypeKind::BuiltIn(BuiltInType::Any));
+    // a variadic parameter receives its arguments as an array.
+    if param.variadic {
+        TypeKind::built_in("array".to_string())
+    } else {
+        ty
+    }
+}
+
+fn function(func: &PhpFunction, class: Option<&str>, indent: &str) -> String {
+    format!("{}{} {{\n{}}}\n", indent, signature(func, class), indent)
+}
+
+/// The signature of a function, with the modifiers of a method, eg: `pub static fn find(id: int): Invoice`
+pub(super) fn signature(func: &PhpFunction, class: Option<&str>) -> String {
+    let doc = func.doc.as_deref().map(DocTypes::parse).unwrap_or_default();
+    let params = func
+        .params
+        .iter()
+        .map(|param| format!("{}: {}", param.name, param_type(param, Some(&doc), class)))
+        .collect::<Vec<String>>();
+    let ret = doc
+        .ret
+        .as_deref()
+        .or(func.ret.as_deref())
+        .and_then(|ty| surn_type(ty, class))
+        .map(|ty| format!(": {}", ty))
+        .unwrap_or_default();
+
+    let mut modifiers = String::new();
+    if class.is_some() {
+        modifiers.push_str(visibility(func.modifiers.iter().map(|m| m.as_str())));
+        if func.modifiers.iter().any(|m| m == "static") {
+            modifiers.push_str("static ");
+        }
+    }
+    format!(
+        "{}fn {}({}){}",
+        modifiers,
+        func.name,
+        params.join(", "),
+        ret
+    )
+}
+
+/// Writes a `.d.surn` declaration file for every `.php` file under the path,
+/// to the same relative location under the output root.
+/// A single file is written next to itself when no output root is given.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use std::path::Path;
+/// # use surn::convert::declarations::write_declarations;
+/// // legacy/Billing/Invoice.php -> types/Billing/Invoice.d.surn
+/// write_declarations(Path::new("legacy"), Some(Path::new("types")))?;
+/// # Ok::<(), String>(())
+/// ```
+#[cfg(feature = "fs")]
+pub fn write_declarations(path: &Path, output_root: Option<&Path>) -> Result<Vec<PathBuf>, String> {
+    let (root, sources) = if path.is_dir() {
+        let mut sources: Vec<PathBuf> = Vec::new();
+        collect_php(path, &mut sources)?;
+        sources.sort();
+        (path.to_path_buf(), sources)
+    } else {
+        let parent = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        (parent, vec![path.to_path_buf()])
+    };
+
+    let mut written: Vec<PathBuf> = Vec::new();
+    for source in sources {
+        let contents = fs::read_to_string(&source)
+            .map_err(|e| format!("Could not read {}: {}", source.display(), e))?;
+        let relative = source.strip_prefix(&root).unwrap().to_path_buf();
+        let output = output_root
+            .unwrap_or(&root)
+            .join(relative)
+            .with_extension(DECLARATION_EXTENSION);
+        if let Some(parent) = output.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Could not create {}: {}", parent.display(), e))?;
+        }
+        fs::write(&output, php_to_declarations(&contents))
+            .map_err(|e| format!("Could not write {}: {}", output.display(), e))?;
+        written.push(output);
+    }
+    Ok(written)
+}
+
+#[cfg(feature = "fs")]
+fn collect_php(dir: &Path, sources: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Could not read directory {}: {}", dir.display(), e))?;
+    for entry in entries {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.is_dir() {
+            collect_php(&path, sources)?;
+        } else if path.extension().is_some_and(|e| e == "php") {
+            sources.push(path);
+        }
+    }
+    Ok(())
+}
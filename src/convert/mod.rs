@@ -0,0 +1,4 @@
+//! Conversions from php to surn, for projects that are migrating to surn.
+pub mod declarations;
+pub mod php;
+pub mod php_to_surn;
@@ -0,0 +1,657 @@
+//! A lightweight reader for the declarations of a php file.
+//! Only signatures are read, function bodies and initializers are skipped over.
+use std::ops::Range;
+
+/// A php token, whitespace and regular comments are dropped.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PhpToken {
+    /// A name, keyword or qualified name, eg: `class` or `\App\User`
+    Name(String),
+    /// A variable without the `$`.
+    Variable(String),
+    /// A `/** */` comment, with its delimiters.
+    DocComment(String),
+    /// A string or number literal as written.
+    Literal(String),
+    /// `...`
+    Ellipsis,
+    Punct(char),
+}
+
+/// Splits php source into tokens, anything outside of `<?php ?>` tags is skipped.
+pub fn tokenize(source: &str) -> Vec<PhpToken> {
+    tokenize_with_spans(source)
+        .into_iter()
+        .map(|(token, _)| token)
+        .collect()
+}
+
+/// Splits php source into tokens, along with the byte range each token was read from.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::convert::php::tokenize_with_spans;
+/// tokenize_with_spans("<?php $a = 'b';"); // [(Variable("a"), 6..8), (Punct('='), 9..10), ...]
+/// ```
+pub fn tokenize_with_spans(source: &str) -> Vec<(PhpToken, Range<usize>)> {
+    let chars = source.chars().collect::<Vec<char>>();
+    let offsets = source
+        .char_indices()
+        .map(|(offset, _)| offset)
+        .chain([source.len()])
+        .collect::<Vec<usize>>();
+    let mut tokens: Vec<PhpToken> = Vec::new();
+    let mut spans: Vec<Range<usize>> = Vec::new();
+    let starts_with = |i: usize, s: &str| {
+        s.chars()
+            .enumerate()
+            .all(|(j, c)| chars.get(i + j) == Some(&c))
+    };
+    let mut i = 0;
+    // a file without an open tag is read as php from the start.
+    let mut in_php = !source.contains("<?php");
+
+    while i < chars.len() {
+        if !in_php {
+            match (i..chars.len()).find(|&j| starts_with(j, "<?php")) {
+                Some(j) => {
+                    in_php = true;
+                    i = j + 5;
+                }
+                None => break,
+            }
+            continue;
+        }
+
+        let c = chars[i];
+        let (start, count) = (i, tokens.len());
+        if c.is_whitespace() {
+            i += 1;
+        } else if starts_with(i, "?>") {
+            in_php = false;
+            i += 2;
+        } else if starts_with(i, "/**") {
+            let end = find_from(&chars, i + 3, "*/").map_or(chars.len(), |e| e + 2);
+            tokens.push(PhpToken::DocComment(chars[i..end].iter().collect()));
+            i = end;
+        } else if starts_with(i, "/*") {
+            i = find_from(&chars, i + 2, "*/").map_or(chars.len(), |e| e + 2);
+        } else if starts_with(i, "#[") {
+            // attributes can't be expressed in declarations.
+            i = skip_balanced(&chars, i + 1, '[', ']');
+        } else if starts_with(i, "//") || c == '#' {
+            while i < chars.len() && chars[i] != '\n' && !starts_with(i, "?>") {
+                i += 1;
+            }
+        } else if c == '\'' || c == '"' || c == '`' {
+            let start = i;
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                i += if chars[i] == '\\' { 2 } else { 1 };
+            }
+            i = (i + 1).min(chars.len());
+            tokens.push(PhpToken::Literal(chars[start..i].iter().collect()));
+        } else if starts_with(i, "<<<") {
+            // heredoc and nowdoc strings end at the line that starts with their label.
+            let label = chars[i + 3..]
+                .iter()
+                .take_while(|c| **c != '\n')
+                .filter(|c| is_name_char(**c))
+                .collect::<String>();
+            let mut line = i;
+            i = loop {
+                line = match find_from(&chars, line + 1, "\n") {
+                    Some(line) => line,
+                    None => break chars.len(),
+                };
+                let indent = chars[line + 1..]
+                    .iter()
+                    .take_while(|c| **c == ' ' || **c == '\t')
+                    .count();
+                if starts_with(line + 1 + indent, &label) {
+                    break line + 1 + indent + label.len();
+                }
+            };
+            tokens.push(PhpToken::Literal("''".to_string()));
+        } else if c == '$' && chars.get(i + 1).is_some_and(|c| is_name_char(*c)) {
+            let start = i + 1;
+            i = start;
+            while i < chars.len() && is_name_char(chars[i]) {
+                i += 1;
+            }
+            tokens.push(PhpToken::Variable(chars[start..i].iter().collect()));
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '.' || chars[i] == '_')
+            {
+                i += 1;
+            }
+            tokens.push(PhpToken::Literal(chars[start..i].iter().collect()));
+        } else if is_name_char(c) || c == '\\' {
+            let start = i;
+            while i < chars.len() && (is_name_char(chars[i]) || chars[i] == '\\') {
+                i += 1;
+            }
+            tokens.push(PhpToken::Name(chars[start..i].iter().collect()));
+        } else if starts_with(i, "...") {
+            tokens.push(PhpToken::Ellipsis);
+            i += 3;
+        } else {
+            tokens.push(PhpToken::Punct(c));
+            i += 1;
+        }
+        if tokens.len() > count {
+            spans.push(offsets[start]..offsets[i]);
+        }
+    }
+    tokens.into_iter().zip(spans).collect()
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn find_from(chars: &[char], from: usize, needle: &str) -> Option<usize> {
+    let needle = needle.chars().collect::<Vec<char>>();
+    (from..chars.len()).find(|&i| chars[i..].starts_with(&needle))
+}
+
+/// The index after the bracket that closes the one at `start`.
+fn skip_balanced(chars: &[char], start: usize, open: char, close: char) -> usize {
+    let mut depth = 0;
+    for (i, c) in chars.iter().enumerate().skip(start) {
+        if *c == open {
+            depth += 1;
+        } else if *c == close {
+            depth -= 1;
+            if depth == 0 {
+                return i + 1;
+            }
+        }
+    }
+    chars.len()
+}
+
+/// The declarations of a php file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PhpFile {
+    pub namespace: Option<String>,
+    pub uses: Vec<PhpUse>,
+    pub consts: Vec<PhpConst>,
+    pub functions: Vec<PhpFunction>,
+    pub classes: Vec<PhpClass>,
+}
+
+/// A class imported with `use`, eg: `use App\Models\User as Model;`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhpUse {
+    /// The qualified name without its leading `\`.
+    pub path: String,
+    pub alias: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhpConst {
+    pub name: String,
+    /// The value as written, eg: `self::MAX*2`
+    pub value: String,
+    /// The visibility of a class constant, eg: `private`
+    pub modifiers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PhpClassKind {
+    Class,
+    Interface,
+    Trait,
+    Enum,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhpClass {
+    pub kind: PhpClassKind,
+    pub name: String,
+    pub extends: Vec<String>,
+    pub implements: Vec<String>,
+    pub doc: Option<String>,
+    pub consts: Vec<PhpConst>,
+    pub properties: Vec<PhpProperty>,
+    pub methods: Vec<PhpFunction>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhpProperty {
+    pub name: String,
+    /// The type hint as written, eg: `?string`
+    pub ty: Option<String>,
+    pub doc: Option<String>,
+    pub modifiers: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhpFunction {
+    pub name: String,
+    pub params: Vec<PhpParam>,
+    /// The return type hint as written.
+    pub ret: Option<String>,
+    pub doc: Option<String>,
+    /// `public`, `static`, `abstract` and the like, methods only.
+    pub modifiers: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhpParam {
+    pub name: String,
+    /// The type hint as written.
+    pub ty: Option<String>,
+    /// The default value as written, eg: `[]`
+    pub default: Option<String>,
+    pub variadic: bool,
+    /// The visibility of a promoted constructor parameter, eg: `private`
+    pub promoted: Option<String>,
+}
+
+const MODIFIERS: [&str; 9] = [
+    "public",
+    "protected",
+    "private",
+    "static",
+    "abstract",
+    "final",
+    "readonly",
+    "var",
+    "const",
+];
+
+/// Reads the namespace, imports, constants, functions and classes of a php file.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::convert::php::parse_declarations;
+/// let file = parse_declarations("<?php\nfunction add(int $a, int $b): int { return $a + $b; }");
+/// &file.functions[0].ret; // Some("int")
+/// ```
+pub fn parse_declarations(source: &str) -> PhpFile {
+    let mut reader = Reader::new(tokenize(source));
+    let mut file = PhpFile::default();
+    let mut doc: Option<String> = None;
+
+    while let Some(token) = reader.next() {
+        match token {
+            PhpToken::DocComment(comment) => {
+                doc = Some(comment);
+                continue;
+            }
+            PhpToken::Name(name) => match name.to_lowercase().as_str() {
+                "namespace" => {
+                    file.namespace = reader.name();
+                    // a braced namespace is read like the rest of the file.
+                    reader.eat(&PhpToken::Punct('{'));
+                }
+                "function" if matches!(reader.peek(), Some(PhpToken::Name(_))) => {
+                    if let Some(func) = reader.function(doc.take(), Vec::new()) {
+                        file.functions.push(func);
+                    }
+                }
+                "class" | "interface" | "trait" | "enum" => {
+                    if let Some(class) = reader.class(&name.to_lowercase(), doc.take()) {
+                        file.classes.push(class);
+                    }
+                }
+                "use" => file.uses.extend(reader.uses()),
+                "const" => file.consts.extend(reader.consts(Vec::new())),
+                _ => {}
+            },
+            PhpToken::Punct('{') => reader.skip_block(),
+            _ => {}
+        }
+        // a doc comment only belongs to the declaration right after it.
+        doc = None;
+    }
+    file
+}
+
+pub(super) struct Reader {
+    pub(super) tokens: Vec<PhpToken>,
+    pub(super) pos: usize,
+}
+
+impl Reader {
+    pub(super) fn new(tokens: Vec<PhpToken>) -> Self {
+        Reader { tokens, pos: 0 }
+    }
+
+    pub(super) fn next(&mut self) -> Option<PhpToken> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    pub(super) fn peek(&self) -> Option<&PhpToken> {
+        self.tokens.get(self.pos)
+    }
+
+    pub(super) fn eat(&mut self, token: &PhpToken) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub(super) fn name(&mut self) -> Option<String> {
+        match self.peek() {
+            Some(PhpToken::Name(name)) => {
+                let name = name.clone();
+                self.pos += 1;
+                Some(name)
+            }
+            _ => None,
+        }
+    }
+
+    fn names(&mut self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        while let Some(name) = self.name() {
+            names.push(name);
+            if !self.eat(&PhpToken::Punct(',')) {
+                break;
+            }
+        }
+        names
+    }
+
+    /// Skips to the end of the statement, or over the block it opens.
+    fn skip_statement(&mut self) {
+        while let Some(token) = self.next() {
+            match token {
+                PhpToken::Punct(';') => return,
+                PhpToken::Punct('{') => return self.skip_block(),
+                _ => {}
+            }
+        }
+    }
+
+    /// Skips to the `}` that closes a block which was just opened.
+    fn skip_block(&mut self) {
+        let mut depth = 1;
+        while let Some(token) = self.next() {
+            match token {
+                PhpToken::Punct('{') => depth += 1,
+                PhpToken::Punct('}') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Reads the imports of a `use` statement after its keyword.
+    /// Functions, constants and group imports are skipped, surn can't import them.
+    fn uses(&mut self) -> Vec<PhpUse> {
+        let mut uses: Vec<PhpUse> = Vec::new();
+        let is_keyword = |token: Option<&PhpToken>, keywords: &[&str]| matches!(token, Some(PhpToken::Name(n)) if keywords.contains(&n.to_lowercase().as_str()));
+        if is_keyword(self.peek(), &["function", "const"]) {
+            self.skip_statement();
+            return uses;
+        }
+        while let Some(path) = self.name() {
+            // `use App\{User, Post};`
+            if self.peek() == Some(&PhpToken::Punct('{')) {
+                break;
+            }
+            let alias = if is_keyword(self.peek(), &["as"]) {
+                self.pos += 1;
+                self.name()
+            } else {
+                None
+            };
+            uses.push(PhpUse {
+                path: path.trim_start_matches('\\').to_string(),
+                alias,
+            });
+            if !self.eat(&PhpToken::Punct(',')) {
+                break;
+            }
+        }
+        self.skip_statement();
+        uses
+    }
+
+    /// Reads the constants of a `const` statement after its keyword, eg: `const A = 1, B = 2;`
+    fn consts(&mut self, modifiers: Vec<String>) -> Vec<PhpConst> {
+        let mut consts: Vec<PhpConst> = Vec::new();
+        loop {
+            // the type of a typed constant, eg: `const string NAME = 'a';`
+            if matches!(self.tokens.get(self.pos + 1), Some(PhpToken::Name(_))) {
+                self.pos += 1;
+            }
+            let name = match self.name() {
+                Some(name) if self.eat(&PhpToken::Punct('=')) => name,
+                _ => break,
+            };
+            consts.push(PhpConst {
+                name,
+                value: self.expression(),
+                modifiers: modifiers.clone(),
+            });
+            if !self.eat(&PhpToken::Punct(',')) {
+                break;
+            }
+        }
+        self.skip_statement();
+        consts
+    }
+
+    /// Reads a type hint, eg: `?int`, `int|string` or `\App\User`
+    pub(super) fn type_hint(&mut self) -> Option<String> {
+        let mut hint = String::new();
+        loop {
+            match self.peek() {
+                Some(PhpToken::Name(name)) => hint.push_str(name),
+                Some(PhpToken::Punct(c)) if "?|()".contains(*c) => hint.push(*c),
+                // `&` is an intersection unless it makes the parameter a reference.
+                Some(PhpToken::Punct('&'))
+                    if matches!(self.tokens.get(self.pos + 1), Some(PhpToken::Name(_))) =>
+                {
+                    hint.push('&')
+                }
+                _ => break,
+            }
+            self.pos += 1;
+        }
+        (!hint.is_empty()).then_some(hint)
+    }
+
+    /// Reads everything up to a `,` or closing bracket at the current depth, as written.
+    fn expression(&mut self) -> String {
+        let mut depth = 0;
+        let mut parts: Vec<String> = Vec::new();
+        while let Some(token) = self.peek() {
+            match token {
+                PhpToken::Punct('(' | '[' | '{') => depth += 1,
+                PhpToken::Punct(')' | ']' | '}') if depth == 0 => break,
+                PhpToken::Punct(')' | ']' | '}') => depth -= 1,
+                PhpToken::Punct(',' | ';') if depth == 0 => break,
+                _ => {}
+            }
+            parts.push(match token {
+                PhpToken::Name(s) | PhpToken::Literal(s) => s.clone(),
+                PhpToken::Variable(s) => format!("${}", s),
+                PhpToken::DocComment(_) => String::new(),
+                PhpToken::Ellipsis => "...".to_string(),
+                PhpToken::Punct(c) => c.to_string(),
+            });
+            self.pos += 1;
+        }
+        parts.join("")
+    }
+
+    /// Reads a function after its `function` keyword, and skips its body.
+    fn function(&mut self, doc: Option<String>, modifiers: Vec<String>) -> Option<PhpFunction> {
+        let func = self.signature(doc, modifiers)?;
+        // the body, or the `;` of an abstract method.
+        self.skip_statement();
+        Some(func)
+    }
+
+    /// Reads the signature of a function after its `function` keyword, up to its body.
+    pub(super) fn signature(
+        &mut self,
+        doc: Option<String>,
+        modifiers: Vec<String>,
+    ) -> Option<PhpFunction> {
+        self.eat(&PhpToken::Punct('&'));
+        let name = self.name()?;
+        if !self.eat(&PhpToken::Punct('(')) {
+            return None;
+        }
+
+        let mut params: Vec<PhpParam> = Vec::new();
+        loop {
+            if self.eat(&PhpToken::Punct(')')) || self.peek().is_none() {
+                break;
+            }
+            let mut promoted: Option<String> = None;
+            while let Some(PhpToken::Name(name)) = self.peek() {
+                match name.as_str() {
+                    "public" | "protected" | "private" => promoted = Some(name.clone()),
+                    "readonly" => {}
+                    _ => break,
+                }
+                self.pos += 1;
+            }
+            let ty = self.type_hint();
+            self.eat(&PhpToken::Punct('&'));
+            let variadic = self.eat(&PhpToken::Ellipsis);
+            let name = match self.next() {
+                Some(PhpToken::Variable(name)) => name,
+                _ => return None,
+            };
+            let default = self.eat(&PhpToken::Punct('=')).then(|| self.expression());
+            params.push(PhpParam {
+                name,
+                ty,
+                default,
+                variadic,
+                promoted,
+            });
+            self.eat(&PhpToken::Punct(','));
+        }
+
+        let ret = if self.eat(&PhpToken::Punct(':')) {
+            self.type_hint()
+        } else {
+            None
+        };
+        Some(PhpFunction {
+            name,
+            params,
+            ret,
+            doc,
+            modifiers,
+        })
+    }
+
+    /// Reads a class, interface, trait or enum after its keyword, with all of its members.
+    fn class(&mut self, keyword: &str, doc: Option<String>) -> Option<PhpClass> {
+        let name = self.name()?;
+        let kind = match keyword {
+            "interface" => PhpClassKind::Interface,
+            "trait" => PhpClassKind::Trait,
+            "enum" => PhpClassKind::Enum,
+            _ => PhpClassKind::Class,
+        };
+        let mut class = PhpClass {
+            kind,
+            name,
+            extends: Vec::new(),
+            implements: Vec::new(),
+            doc,
+            consts: Vec::new(),
+            properties: Vec::new(),
+            methods: Vec::new(),
+        };
+
+        // the backing type of an enum, eg: `enum Suit: string`
+        if self.eat(&PhpToken::Punct(':')) {
+            self.type_hint();
+        }
+        loop {
+            match self.peek() {
+                Some(PhpToken::Name(n)) if n.eq_ignore_ascii_case("extends") => {
+                    self.pos += 1;
+                    class.extends = self.names();
+                }
+                Some(PhpToken::Name(n)) if n.eq_ignore_ascii_case("implements") => {
+                    self.pos += 1;
+                    class.implements = self.names();
+                }
+                _ => break,
+            }
+        }
+        if !self.eat(&PhpToken::Punct('{')) {
+            return None;
+        }
+
+        let mut doc: Option<String> = None;
+        let mut modifiers: Vec<String> = Vec::new();
+        while let Some(token) = self.next() {
+            match token {
+                PhpToken::Punct('}') => break,
+                PhpToken::DocComment(comment) => doc = Some(comment),
+                PhpToken::Name(name) if name.eq_ignore_ascii_case("function") => {
+                    let modifiers = std::mem::take(&mut modifiers);
+                    if let Some(method) = self.function(doc.take(), modifiers) {
+                        class.methods.push(method);
+                    }
+                }
+                PhpToken::Name(name) if MODIFIERS.contains(&name.to_lowercase().as_str()) => {
+                    if name.eq_ignore_ascii_case("const") {
+                        let modifiers = std::mem::take(&mut modifiers);
+                        class.consts.extend(self.consts(modifiers));
+                        doc = None;
+                    } else {
+                        modifiers.push(name.to_lowercase());
+                    }
+                }
+                PhpToken::Name(name)
+                    if name.eq_ignore_ascii_case("use") || name.eq_ignore_ascii_case("case") =>
+                {
+                    self.skip_statement();
+                    doc = None;
+                }
+                token => {
+                    // a property, with or without a type hint.
+                    self.pos -= 1;
+                    let ty = self.type_hint();
+                    if let Some(PhpToken::Variable(name)) = self.peek().cloned() {
+                        self.pos += 1;
+                        class.properties.push(PhpProperty {
+                            name,
+                            ty,
+                            doc: doc.take(),
+                            modifiers: std::mem::take(&mut modifiers),
+                        });
+                        // the default value is not part of the declaration.
+                        self.expression();
+                        self.eat(&PhpToken::Punct(';'));
+                    } else if ty.is_none() {
+                        self.pos += 1;
+                        if token == PhpToken::Punct('{') {
+                            self.skip_block();
+                        }
+                    }
+                    modifiers.clear();
+                    doc = None;
+                }
+            }
+        }
+        Some(class)
+    }
+}
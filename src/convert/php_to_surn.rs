@@ -0,0 +1,647 @@
+//! An experimental translation of simple php files to surn source.
+//!
+//! Classes, functions, properties and simple statements are translated. Anything surn
+//! can't express yet is kept as it was written in a `php!` block, marked with a `TODO`
+//! comment, so the converted file still compiles to the same php.
+//! Comments other than docblocks are not carried over.
+use std::{collections::HashSet, ops::Range};
+
+use super::{
+    declarations::{param_type, property, signature, visibility},
+    php::{tokenize_with_spans, PhpProperty, PhpToken, Reader},
+};
+
+const INDENT: &str = "    ";
+
+/// Names that are keywords in surn or that mean something surn can't express.
+const RESERVED: [&str; 22] = [
+    "function",
+    "fn",
+    "match",
+    "instanceof",
+    "clone",
+    "static",
+    "self",
+    "var",
+    "const",
+    "class",
+    "enum",
+    "type",
+    "pub",
+    "priv",
+    "prot",
+    "use",
+    "return",
+    "await",
+    "yield",
+    "throw",
+    "list",
+    "print",
+];
+
+/// Statements that end with the block they open, rather than with a `;`.
+const BLOCK_STATEMENTS: [&str; 15] = [
+    "if",
+    "for",
+    "foreach",
+    "while",
+    "switch",
+    "try",
+    "function",
+    "class",
+    "interface",
+    "trait",
+    "enum",
+    "abstract",
+    "final",
+    "readonly",
+    "namespace",
+];
+
+/// Translates a php file to surn source.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::convert::php_to_surn::php_to_surn;
+/// // <?php
+/// // function greet(string $name): string {
+/// //     $greeting = 'Hello ';
+/// //     echo $greeting;
+/// //     return $name;
+/// // }
+/// # let source = "<?php\nfunction greet(string $name): string {\n    $greeting = 'Hello ';\n    echo $greeting;\n    return $name;\n}\n";
+/// php_to_surn(source);
+/// // fn greet(name: string): string {
+/// //     var greeting = "Hello ";
+/// //     // TODO: `echo` can't be converted to surn yet.
+/// //     php! {
+/// //         echo $greeting;
+/// //     }
+/// //     return name;
+/// // }
+/// ```
+pub fn php_to_surn(source: &str) -> String {
+    let (tokens, spans) = tokenize_with_spans(source).into_iter().unzip();
+    let mut converter = Converter {
+        source,
+        reader: Reader::new(tokens),
+        spans,
+        output: String::new(),
+    };
+    converter.file();
+    converter.output
+}
+
+struct Converter<'a> {
+    source: &'a str,
+    reader: Reader,
+    spans: Vec<Range<usize>>,
+    output: String,
+}
+
+impl Converter<'_> {
+    fn line(&mut self, depth: usize, code: &str) {
+        self.output.push_str(&INDENT.repeat(depth));
+        self.output.push_str(code);
+        self.output.push('\n');
+    }
+
+    /// Separates declarations with an empty line.
+    fn gap(&mut self) {
+        if !self.output.is_empty() && !self.output.ends_with("{\n") {
+            self.output.push('\n');
+        }
+    }
+
+    fn peek_punct(&self, offset: usize, c: char) -> bool {
+        self.reader.tokens.get(self.reader.pos + offset) == Some(&PhpToken::Punct(c))
+    }
+
+    fn file(&mut self) {
+        let mut locals: HashSet<String> = HashSet::new();
+        let mut doc: Option<String> = None;
+        while let Some(token) = self.reader.peek().cloned() {
+            let start = self.reader.pos;
+            match token {
+                PhpToken::DocComment(comment) => {
+                    self.reader.pos += 1;
+                    doc = Some(comment);
+                    continue;
+                }
+                // the end of a braced namespace.
+                PhpToken::Punct('}') => self.reader.pos += 1,
+                PhpToken::Name(name) => match name.to_lowercase().as_str() {
+                    "namespace" => {
+                        self.reader.pos += 1;
+                        let namespace = self.reader.name();
+                        match namespace {
+                            Some(namespace)
+                                if self.reader.eat(&PhpToken::Punct(';'))
+                                    || self.reader.eat(&PhpToken::Punct('{')) =>
+                            {
+                                let namespace = namespace.trim_start_matches('\\');
+                                self.line(0, &format!("namespace {};", namespace));
+                            }
+                            _ => self.fallback(start, 0),
+                        }
+                    }
+                    "use" => self.import(start),
+                    "function"
+                        if matches!(self.reader.tokens.get(start + 1), Some(PhpToken::Name(_))) =>
+                    {
+                        self.reader.pos += 1;
+                        self.gap();
+                        self.function(start, doc.take(), Vec::new(), None, 0);
+                    }
+                    "class" => {
+                        self.reader.pos += 1;
+                        self.gap();
+                        self.class(start, 0);
+                    }
+                    _ => self.top_level_statement(&mut locals),
+                },
+                _ => self.top_level_statement(&mut locals),
+            }
+            doc = None;
+        }
+    }
+
+    fn top_level_statement(&mut self, locals: &mut HashSet<String>) {
+        if self.output.ends_with("}\n") {
+            self.output.push('\n');
+        }
+        self.statement(locals, 0, true);
+    }
+
+    /// `use App\Models\User as Model;` becomes `use App::Models::User as Model;`
+    fn import(&mut self, start: usize) {
+        self.reader.pos += 1;
+        let mut imports: Vec<String> = Vec::new();
+        loop {
+            // `use function` and `use const` have no surn equivalent.
+            let path = match self.reader.name() {
+                Some(path) if !RESERVED.contains(&path.to_lowercase().as_str()) => path,
+                _ => return self.fallback(start, 0),
+            };
+            let mut import = path.trim_start_matches('\\').replace('\\', "::");
+            if matches!(self.reader.peek(), Some(PhpToken::Name(n)) if n.eq_ignore_ascii_case("as"))
+            {
+                self.reader.pos += 1;
+                match self.reader.name() {
+                    Some(alias) => import.push_str(&format!(" as {}", alias)),
+                    None => return self.fallback(start, 0),
+                }
+            }
+            imports.push(import);
+            if self.reader.eat(&PhpToken::Punct(';')) {
+                break;
+            }
+            if !self.reader.eat(&PhpToken::Punct(',')) {
+                return self.fallback(start, 0);
+            }
+        }
+        for import in imports {
+            self.line(0, &format!("use {};", import));
+        }
+    }
+
+    /// Converts a class after its `class` keyword, with all of its members.
+    fn class(&mut self, start: usize, depth: usize) {
+        let name = match self.reader.name() {
+            Some(name) => name,
+            None => return self.fallback(start, depth),
+        };
+        let mut header = format!("class {}", name);
+        loop {
+            let keyword = match self.reader.peek() {
+                Some(PhpToken::Name(n)) if n.eq_ignore_ascii_case("extends") => "extends",
+                Some(PhpToken::Name(n)) if n.eq_ignore_ascii_case("implements") => "implements",
+                _ => break,
+            };
+            self.reader.pos += 1;
+            let mut names: Vec<String> = Vec::new();
+            while let Some(name) = self.reader.name() {
+                names.push(name);
+                if !self.reader.eat(&PhpToken::Punct(',')) {
+                    break;
+                }
+            }
+            // TODO: qualified names need to be imported with a `use` before surn can resolve them.
+            if names.is_empty() || names.iter().any(|n| n.contains('\\')) {
+                return self.fallback(start, depth);
+            }
+            header.push_str(&format!(" {} {}", keyword, names.join(", ")));
+        }
+        if !self.reader.eat(&PhpToken::Punct('{')) {
+            return self.fallback(start, depth);
+        }
+        self.line(depth, &format!("{} {{", header));
+
+        loop {
+            let start = self.reader.pos;
+            let mut doc: Option<String> = None;
+            let mut modifiers: Vec<String> = Vec::new();
+            loop {
+                match self.reader.peek() {
+                    Some(PhpToken::DocComment(comment)) => doc = Some(comment.clone()),
+                    Some(PhpToken::Name(n))
+                        if [
+                            "public",
+                            "protected",
+                            "private",
+                            "static",
+                            "readonly",
+                            "var",
+                        ]
+                        .contains(&n.to_lowercase().as_str()) =>
+                    {
+                        modifiers.push(n.to_lowercase())
+                    }
+                    _ => break,
+                }
+                self.reader.pos += 1;
+            }
+
+            match self.reader.peek() {
+                None => break,
+                Some(PhpToken::Punct('}')) => {
+                    self.reader.pos += 1;
+                    break;
+                }
+                Some(PhpToken::Name(n)) if n.eq_ignore_ascii_case("function") => {
+                    self.reader.pos += 1;
+                    self.gap();
+                    self.function(start, doc, modifiers, Some(&name), depth + 1);
+                }
+                Some(PhpToken::Name(_) | PhpToken::Punct('?') | PhpToken::Variable(_)) => {
+                    if !self.property(doc, modifiers, &name, depth + 1) {
+                        self.reader.pos = start;
+                        self.fallback(start, depth + 1);
+                    }
+                }
+                // TODO: constants, trait uses and abstract methods.
+                Some(_) => self.fallback(start, depth + 1),
+            }
+        }
+        self.line(depth, "}");
+    }
+
+    /// Converts a property with an optional default value, `false` when the default
+    /// can't be converted or more than one property is declared.
+    fn property(
+        &mut self,
+        doc: Option<String>,
+        modifiers: Vec<String>,
+        class: &str,
+        depth: usize,
+    ) -> bool {
+        let ty = self.reader.type_hint();
+        let name = match self.reader.next() {
+            Some(PhpToken::Variable(name)) => name,
+            _ => return false,
+        };
+        let default = if self.reader.eat(&PhpToken::Punct('=')) {
+            match self.expression() {
+                Some(default) => Some(default),
+                None => return false,
+            }
+        } else {
+            None
+        };
+        if !self.reader.eat(&PhpToken::Punct(';')) {
+            return false;
+        }
+
+        let property = property(
+            &PhpProperty {
+                name,
+                ty,
+                doc,
+                modifiers,
+            },
+            class,
+        );
+        match default {
+            Some(default) => self.line(depth, &format!("{} = {};", property, default)),
+            None => self.line(depth, &format!("{};", property)),
+        }
+        true
+    }
+
+    /// Converts a function after its `function` keyword, promoted constructor parameters
+    /// become properties that are assigned at the start of the constructor.
+    fn function(
+        &mut self,
+        start: usize,
+        doc: Option<String>,
+        modifiers: Vec<String>,
+        class: Option<&str>,
+        depth: usize,
+    ) {
+        let func = match self.reader.signature(doc, modifiers) {
+            // surn has no default values or variadic parameters yet.
+            Some(func)
+                if func
+                    .params
+                    .iter()
+                    .all(|p| p.default.is_none() && !p.variadic) =>
+            {
+                func
+            }
+            _ => return self.fallback(start, depth),
+        };
+        // abstract and interface methods have no body.
+        if func.modifiers.iter().any(|m| m == "abstract") || !self.reader.eat(&PhpToken::Punct('{'))
+        {
+            return self.fallback(start, depth);
+        }
+
+        let promoted = func
+            .params
+            .iter()
+            .filter(|p| p.promoted.is_some())
+            .collect::<Vec<_>>();
+        for param in promoted.iter() {
+            let visibility = visibility(param.promoted.iter().map(|p| p.as_str()));
+            let ty = param_type(param, None, class);
+            self.line(depth, &format!("{}{}: {};", visibility, param.name, ty));
+        }
+        if !promoted.is_empty() {
+            self.output.push('\n');
+        }
+
+        self.line(depth, &format!("{} {{", signature(&func, class)));
+        for param in promoted {
+            self.line(depth + 1, &format!("this.{0} = {0};", param.name));
+        }
+        let mut locals = func
+            .params
+            .iter()
+            .map(|p| p.name.clone())
+            .collect::<HashSet<String>>();
+        loop {
+            match self.reader.peek() {
+                None => break,
+                Some(PhpToken::Punct('}')) => {
+                    self.reader.pos += 1;
+                    break;
+                }
+                Some(PhpToken::DocComment(_)) => self.reader.pos += 1,
+                Some(_) => self.statement(&mut locals, depth + 1, false),
+            }
+        }
+        self.line(depth, "}");
+    }
+
+    /// Converts a statement, or keeps it as php when it can't be converted.
+    fn statement(&mut self, locals: &mut HashSet<String>, depth: usize, top_level: bool) {
+        let start = self.reader.pos;
+        match self.simple_statement(locals, top_level) {
+            Some(code) => self.line(depth, &code),
+            None => self.fallback(start, depth),
+        }
+    }
+
+    /// Returns, assignments and calls, the first assignment to a variable declares it.
+    fn simple_statement(
+        &mut self,
+        locals: &mut HashSet<String>,
+        top_level: bool,
+    ) -> Option<String> {
+        let code = match self.reader.peek()?.clone() {
+            // surn reads a bare `return;` as an expression.
+            PhpToken::Name(n) if n.eq_ignore_ascii_case("return") && !top_level => {
+                self.reader.pos += 1;
+                format!("return {};", self.expression()?)
+            }
+            PhpToken::Variable(name)
+                if name != "this" && self.peek_punct(1, '=') && !self.peek_punct(2, '=') =>
+            {
+                self.reader.pos += 2;
+                let value = self.expression()?;
+                if locals.insert(name.clone()) {
+                    format!("var {} = {};", name, value)
+                } else if !top_level {
+                    format!("{} = {};", name, value)
+                } else {
+                    // TODO: surn only allows declarations at the top level.
+                    return None;
+                }
+            }
+            _ if !top_level => {
+                let target = self.expression()?;
+                if self.reader.eat(&PhpToken::Punct('=')) {
+                    format!("{} = {};", target, self.expression()?)
+                } else {
+                    format!("{};", target)
+                }
+            }
+            _ => return None,
+        };
+        self.reader.eat(&PhpToken::Punct(';')).then_some(code)
+    }
+
+    /// Operands joined by the binary operators surn has.
+    fn expression(&mut self) -> Option<String> {
+        let mut code = self.operand()?;
+        while let Some(op) = self.binary_operator() {
+            code = format!("{} {} {}", code, op, self.operand()?);
+        }
+        Some(code)
+    }
+
+    fn binary_operator(&mut self) -> Option<&'static str> {
+        let c = match self.reader.peek() {
+            Some(PhpToken::Punct(c)) => *c,
+            _ => return None,
+        };
+        let next = match self.reader.tokens.get(self.reader.pos + 1) {
+            Some(PhpToken::Punct(next)) => Some(*next),
+            _ => None,
+        };
+        // `!=` is the only operator of two characters surn has.
+        if c == '!' && next == Some('=') && !self.peek_punct(2, '=') {
+            self.reader.pos += 2;
+            return Some("!=");
+        }
+        // compound assignments, increments, `&&`, `<<` and the like.
+        if next == Some('=') || next == Some(c) || (c == '<' && next == Some('>')) {
+            return None;
+        }
+        let op = match c {
+            '+' => "+",
+            '-' if next != Some('>') => "-",
+            '*' => "*",
+            '/' => "/",
+            '%' => "%",
+            '^' => "^",
+            '&' => "&",
+            '|' => "|",
+            '<' => "<",
+            '>' => ">",
+            _ => return None,
+        };
+        self.reader.pos += 1;
+        Some(op)
+    }
+
+    /// A variable, literal, call, `new` or array, followed by property and method lookups.
+    fn operand(&mut self) -> Option<String> {
+        let pos = self.reader.pos;
+        let mut code = match self.reader.next()? {
+            PhpToken::Variable(name) => name,
+            PhpToken::Literal(_) => self.literal(&self.source[self.spans[pos].clone()])?,
+            PhpToken::Punct('[') => {
+                let mut values: Vec<String> = Vec::new();
+                while !self.reader.eat(&PhpToken::Punct(']')) {
+                    values.push(self.expression()?);
+                    if !self.reader.eat(&PhpToken::Punct(',')) && !self.peek_punct(0, ']') {
+                        return None;
+                    }
+                }
+                format!("[{}]", values.join(", "))
+            }
+            PhpToken::Name(n) if n.eq_ignore_ascii_case("new") => {
+                let class = self.reader.name().filter(|n| is_plain_name(n))?;
+                return Some(format!("new {}({})", class, self.arguments()?));
+            }
+            PhpToken::Name(name) if is_plain_name(&name) => {
+                if self.peek_punct(0, ':') && self.peek_punct(1, ':') {
+                    self.reader.pos += 2;
+                    let member = self.reader.name()?;
+                    if !self.peek_punct(0, '(') {
+                        return Some(format!("{}::{}", name, member));
+                    }
+                    return Some(format!("{}::{}({})", name, member, self.arguments()?));
+                }
+                if !self.peek_punct(0, '(') {
+                    // any other constant would be read as a variable.
+                    let constant =
+                        ["true", "false", "null"].contains(&name.to_lowercase().as_str());
+                    return constant.then(|| name.to_lowercase());
+                }
+                // surn can't look anything up on the result of a call.
+                return Some(format!("{}({})", name, self.arguments()?));
+            }
+            _ => return None,
+        };
+
+        while self.peek_punct(0, '-') && self.peek_punct(1, '>') {
+            self.reader.pos += 2;
+            code = format!("{}.{}", code, self.reader.name()?);
+            if self.peek_punct(0, '(') {
+                code = format!("{}({})", code, self.arguments()?);
+                break;
+            }
+        }
+        Some(code)
+    }
+
+    fn arguments(&mut self) -> Option<String> {
+        if !self.reader.eat(&PhpToken::Punct('(')) {
+            return None;
+        }
+        let mut arguments: Vec<String> = Vec::new();
+        while !self.reader.eat(&PhpToken::Punct(')')) {
+            arguments.push(self.expression()?);
+            if !self.reader.eat(&PhpToken::Punct(',')) && !self.peek_punct(0, ')') {
+                return None;
+            }
+        }
+        Some(arguments.join(", "))
+    }
+
+    /// Numbers and strings without escapes or interpolation.
+    fn literal(&self, literal: &str) -> Option<String> {
+        let quoted = |quote: char| {
+            literal
+                .strip_prefix(quote)
+                .and_then(|s| s.strip_suffix(quote))
+                .filter(|s| !s.contains(['\\', '"', '$', '{']))
+        };
+        if let Some(string) = quoted('\'').or_else(|| quoted('"')) {
+            return Some(format!("\"{}\"", string));
+        }
+        let is_number = literal.starts_with(|c: char| c.is_ascii_digit())
+            && literal.chars().all(|c| c.is_ascii_digit() || c == '.');
+        is_number.then(|| literal.to_string())
+    }
+
+    /// Keeps the statement starting at `start` as php.
+    fn fallback(&mut self, start: usize, depth: usize) {
+        self.reader.pos = start;
+        let end = self.statement_end();
+        self.reader.pos = end;
+        if end <= start {
+            // an unbalanced `)` or `]`, which can't start anything.
+            self.reader.pos += 1;
+            return;
+        }
+
+        let reason = match &self.reader.tokens[start] {
+            PhpToken::Name(name) => format!("`{}` can't be converted to surn yet", name),
+            _ => "this statement can't be converted to surn yet".to_string(),
+        };
+        let span = self.spans[start].start..self.spans[end - 1].end;
+        let line_start = self.source[..span.start]
+            .rfind('\n')
+            .map_or(0, |line| line + 1);
+        // the statement is reindented relative to its first line.
+        let column = self.source[line_start..span.start].chars().count();
+
+        self.line(depth, &format!("// TODO: {}.", reason));
+        self.line(depth, "php! {");
+        for (i, line) in self.source[span].lines().enumerate() {
+            let indent = line.chars().take_while(|c| c.is_whitespace()).count();
+            let strip = if i == 0 { 0 } else { indent.min(column) };
+            let line = &line[line
+                .char_indices()
+                .nth(strip)
+                .map_or(line.len(), |(i, _)| i)..];
+            if line.trim().is_empty() {
+                self.output.push('\n');
+            } else {
+                self.line(depth + 1, line.trim_end());
+            }
+        }
+        self.line(depth, "}");
+    }
+
+    /// The index after the last token of the statement at the current position.
+    fn statement_end(&self) -> usize {
+        let tokens = &self.reader.tokens;
+        let start = self.reader.pos;
+        let is_block = match tokens.get(start) {
+            Some(PhpToken::Name(n)) => BLOCK_STATEMENTS.contains(&n.to_lowercase().as_str()),
+            _ => false,
+        };
+        let mut depth = 0;
+        for i in start..tokens.len() {
+            match &tokens[i] {
+                PhpToken::Punct('(' | '[' | '{') => depth += 1,
+                PhpToken::Punct(')' | ']' | '}') if depth == 0 => return i,
+                PhpToken::Punct('}') => {
+                    depth -= 1;
+                    // `else`, `catch` and `finally` are part of the statement they follow.
+                    let continues = matches!(
+                        tokens.get(i + 1),
+                        Some(PhpToken::Name(n)) if ["else", "elseif", "catch", "finally"]
+                            .contains(&n.to_lowercase().as_str())
+                    );
+                    if depth == 0 && is_block && !continues {
+                        return i + 1;
+                    }
+                }
+                PhpToken::Punct(')' | ']') => depth -= 1,
+                PhpToken::Punct(';') if depth == 0 => return i + 1,
+                _ => {}
+            }
+        }
+        tokens.len()
+    }
+}
+
+/// A name surn can use as is, qualified names need an import first.
+fn is_plain_name(name: &str) -> bool {
+    !name.contains('\\') && !RESERVED.contains(&name.to_lowercase().as_str())
+}
@@ -0,0 +1,132 @@
+use std::{collections::HashSet, ops::Range};
+
+use crate::compiler::{
+    ast::{AstBody, NodeKind, Path, Statement},
+    lexer::tokenizer::tokenize,
+};
+
+use super::{apply_edits, full_line, CodeAction, TextEdit};
+
+/// The LSP kind of the organize imports action.
+pub const ORGANIZE_IMPORTS_KIND: &str = "source.organizeImports";
+
+/// Organizes the top level `use` statements of the given source.
+/// - Imports that are never referenced are removed.
+/// - Imports from the same module are merged into a group, eg: `use foo::{bar, baz};`
+/// - The imports are sorted by module, then by name.
+///
+/// The organized imports replace the first `use` statement, every other one is removed.
+/// Returns no edits if the imports are already organized.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, CompilerOptions};
+/// # use surn::ide::imports::organize_imports;
+/// // use std::io;
+/// // use app::Zed;
+/// // use app::alpha;
+/// // var x = alpha(Zed);
+/// # let source = "use std::io;\nuse app::Zed;\nuse app::alpha;\nvar x = alpha(Zed);\n";
+/// # let body = Parser::new(CompilerOptions::default())
+/// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+/// let edits = organize_imports(source, &body);
+/// // use app::{alpha, Zed};
+/// // var x = alpha(Zed);
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub fn organize_imports(source: &str, body: &AstBody) -> Vec<TextEdit> {
+    let imports = body
+        .get_program()
+        .iter()
+        .filter_map(|node| match &node.inner {
+            NodeKind::Statement(Statement::Import(path)) => Some((node.start()..node.end(), path)),
+            _ => None,
+        })
+        .collect::<Vec<(Range<usize>, &Path)>>();
+    if imports.is_empty() {
+        return Vec::new();
+    }
+
+    // anything referenced outside of the imports themselves counts as a usage.
+    let used = tokenize(source)
+        .into_iter()
+        .filter(|t| t.kind().is_identifier())
+        .filter(|t| {
+            !imports
+                .iter()
+                .any(|(range, _)| range.contains(&t.range().start))
+        })
+        .filter_map(|t| t.value())
+        .collect::<HashSet<String>>();
+
+    let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+    for (_, path) in imports.iter() {
+        for leaf in path.leaves() {
+            let (item, prefix) = leaf.split_last().unwrap();
+            if !used.contains(item.binding()) {
+                continue;
+            }
+
+            let module = prefix
+                .iter()
+                .map(|p| p.name.as_str())
+                .collect::<Vec<&str>>()
+                .join("::");
+            let item = match &item.alias {
+                Some(alias) => format!("{} as {}", item.name, alias),
+                None => item.name.to_string(),
+            };
+            match groups.iter_mut().find(|(m, _)| *m == module) {
+                Some((_, items)) if !items.contains(&item) => items.push(item),
+                Some(_) => {}
+                None => groups.push((module, vec![item])),
+            }
+        }
+    }
+
+    groups.sort_by_key(|(module, _)| module.to_lowercase());
+    let lines = groups
+        .into_iter()
+        .map(|(module, mut items)| {
+            items.sort_by_key(|item| item.to_lowercase());
+            match (module.is_empty(), items.len()) {
+                (true, _) => items
+                    .iter()
+                    .map(|item| format!("use {};", item))
+                    .collect::<Vec<String>>()
+                    .join("\n"),
+                (false, 1) => format!("use {}::{};", module, items[0]),
+                (false, _) => format!("use {}::{{{}}};", module, items.join(", ")),
+            }
+        })
+        .collect::<Vec<String>>();
+
+    let chars: Vec<char> = source.chars().collect();
+    let mut edits: Vec<TextEdit> = Vec::new();
+    for (i, (range, _)) in imports.iter().enumerate() {
+        if i == 0 && !lines.is_empty() {
+            edits.push(TextEdit::new(range.clone(), lines.join("\n")));
+        } else {
+            edits.push(TextEdit::delete(full_line(&chars, range)));
+        }
+    }
+
+    if apply_edits(source, &edits) == source {
+        Vec::new()
+    } else {
+        edits
+    }
+}
+
+/// Organize imports as a code action, if there is anything to organize.
+pub fn organize_imports_action(source: &str, body: &AstBody) -> Option<CodeAction> {
+    let edits = organize_imports(source, body);
+    if edits.is_empty() {
+        return None;
+    }
+    Some(CodeAction {
+        title: "Organize imports".to_string(),
+        kind: ORGANIZE_IMPORTS_KIND.to_string(),
+        edits,
+    })
+}
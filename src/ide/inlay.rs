@@ -0,0 +1,142 @@
+use crate::compiler::{
+    ast::{
+        visit::{self, Visitor},
+        AstBody, Call, Expression, Variable,
+    },
+    infer::TypeInference,
+    lexer::{token::Token, tokenizer::tokenize},
+};
+
+use super::tokens_within;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InlayHintKind {
+    /// The inferred type of a declaration, eg: `var x: int = 5;`
+    Type,
+    /// The name of the parameter an argument is passed to, eg: `foo(a: 5)`
+    Parameter,
+}
+
+/// A piece of text that is displayed inline by an editor but is not part of the source.
+#[derive(Debug, Clone)]
+pub struct InlayHint {
+    /// The offset in the source the hint is displayed at.
+    pub offset: usize,
+    /// The text of the hint.
+    pub label: String,
+    pub kind: InlayHintKind,
+}
+
+/// Produces the inlay hints for the given source.
+/// - Declarations without a type annotation get their inferred type after the name.
+/// - Arguments of calls to known functions get the name of the parameter they are passed to.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, CompilerOptions};
+/// # use surn::ide::inlay::inlay_hints;
+/// // var x = 5;
+/// // foo(x, "bar");
+/// # let source = "var x = 5;\nfoo(x, \"bar\");\nfn foo(a: int, b: string) {}\n";
+/// # let body = Parser::new(CompilerOptions::default())
+/// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+/// let hints = inlay_hints(source, &body);
+/// // [": int" at 5, "a:" at 15, "b:" at 18]
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub fn inlay_hints(source: &str, body: &AstBody) -> Vec<InlayHint> {
+    let mut collector = HintCollector {
+        tokens: tokenize(source),
+        inference: TypeInference::run(body),
+        hints: Vec::new(),
+    };
+    visit::walk_body(&mut collector, body);
+
+    let mut hints = collector.hints;
+    hints.sort_by_key(|h| h.offset);
+    hints
+}
+
+struct HintCollector {
+    tokens: Vec<Token>,
+    inference: TypeInference,
+    hints: Vec<InlayHint>,
+}
+
+impl Visitor for HintCollector {
+    fn visit_variable(&mut self, var: &Variable) {
+        if var.ty.is_none() {
+            if let Some(ty) = self.inference.variable_type(&var.span) {
+                // the hint goes right after the name of the declaration.
+                if let Some(name) = tokens_within(&self.tokens, &var.span)
+                    .into_iter()
+                    .find(|t| t.kind().is_identifier())
+                {
+                    self.hints.push(InlayHint {
+                        offset: name.range().end,
+                        label: format!(": {}", ty),
+                        kind: InlayHintKind::Type,
+                    });
+                }
+            }
+        }
+        visit::walk_variable(self, var);
+    }
+
+    fn visit_call(&mut self, call: &Call) {
+        if let Some(signature) = self.inference.function(call.name.as_str()) {
+            let starts = argument_starts(&tokens_within(&self.tokens, &call.span));
+            for ((start, arg), input) in starts
+                .into_iter()
+                .zip(call.arguments.iter())
+                .zip(signature.inputs.iter())
+            {
+                // passing `a` to a parameter named `a` doesn't need a hint.
+                if let Expression::Literal(literal) = arg {
+                    if literal.ty.is_none() && literal.value == input.name {
+                        continue;
+                    }
+                }
+
+                self.hints.push(InlayHint {
+                    offset: start,
+                    label: format!("{}:", input.name),
+                    kind: InlayHintKind::Parameter,
+                });
+            }
+        }
+        visit::walk_call(self, call);
+    }
+}
+
+/// Finds the offset each argument starts at, given the tokens of a call.
+fn argument_starts(tokens: &[Token]) -> Vec<usize> {
+    let mut starts: Vec<usize> = Vec::new();
+    let mut depth = 0;
+    let mut expecting = false;
+
+    for token in tokens.iter() {
+        let kind = token.kind();
+        if kind.is_whitespace() || kind.is_comment() {
+            continue;
+        }
+
+        if depth == 1 && expecting && !kind.is_right_parenthesis() {
+            starts.push(token.range().start);
+            expecting = false;
+        }
+
+        if kind.is_left_parenthesis() || kind.is_left_bracket() || kind.is_left_brace() {
+            depth += 1;
+            if depth == 1 {
+                expecting = true;
+            }
+        } else if kind.is_right_parenthesis() || kind.is_right_bracket() || kind.is_right_brace() {
+            depth -= 1;
+        } else if depth == 1 && kind.is_comma() {
+            expecting = true;
+        }
+    }
+
+    starts
+}
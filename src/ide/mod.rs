@@ -0,0 +1,97 @@
+//! Editor tooling built on top of the compiler.
+//! Everything in here works on a parsed `AstBody` and the source it came from,
+//! and returns plain data that can be mapped onto LSP requests.
+use std::ops::Range;
+
+use crate::compiler::{ast::AstBody, lexer::token::Token};
+
+pub mod imports;
+pub mod inlay;
+pub mod on_type;
+pub mod project;
+pub mod quick_fix;
+pub mod refactor;
+
+/// A change to the source, replacing the given range with new text.
+/// Ranges are offsets into the source, the same as token ranges.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub new_text: String,
+}
+
+impl TextEdit {
+    pub fn new(range: Range<usize>, new_text: String) -> Self {
+        Self { range, new_text }
+    }
+
+    pub fn insert(offset: usize, new_text: String) -> Self {
+        Self::new(offset..offset, new_text)
+    }
+
+    pub fn delete(range: Range<usize>) -> Self {
+        Self::new(range, String::new())
+    }
+}
+
+/// An automated change offered to the user, eg: an LSP code action.
+#[derive(Debug, Clone)]
+pub struct CodeAction {
+    pub title: String,
+    /// The LSP kind of the action, eg: `source.organizeImports`
+    pub kind: String,
+    pub edits: Vec<TextEdit>,
+}
+
+/// Every code action available for the given source.
+/// The range is the selection of the editor, actions that need a target only apply to it.
+pub fn code_actions(source: &str, body: &AstBody, range: Range<usize>) -> Vec<CodeAction> {
+    let mut actions = quick_fix::binding_fixes(body);
+    actions.extend(imports::organize_imports_action(source, body));
+    actions.extend(refactor::inline_variable_action(source, body, range));
+    actions
+}
+
+/// Applies the given edits to the source.
+/// Edits must not overlap, but they can be given in any order.
+pub fn apply_edits(source: &str, edits: &[TextEdit]) -> String {
+    let mut chars: Vec<char> = source.chars().collect();
+    let mut sorted = edits.to_vec();
+    sorted.sort_by_key(|e| e.range.start);
+
+    for edit in sorted.iter().rev() {
+        chars.splice(edit.range.clone(), edit.new_text.chars());
+    }
+    chars.into_iter().collect()
+}
+
+/// Returns the tokens that are fully contained within the given range.
+pub(crate) fn tokens_within(tokens: &[Token], range: &Range<usize>) -> Vec<Token> {
+    tokens
+        .iter()
+        .filter(|t| t.range().start >= range.start && t.range().end <= range.end)
+        .cloned()
+        .collect()
+}
+
+/// Extends the range over its indentation and line break when nothing else is on its line.
+pub(crate) fn full_line(chars: &[char], range: &Range<usize>) -> Range<usize> {
+    let mut start = range.start;
+    while start > 0 && (chars[start - 1] == ' ' || chars[start - 1] == '\t') {
+        start -= 1;
+    }
+    let mut end = range.end;
+    while end < chars.len() && (chars[end] == ' ' || chars[end] == '\t' || chars[end] == '\r') {
+        end += 1;
+    }
+
+    let own_line =
+        (start == 0 || chars[start - 1] == '\n') && (end == chars.len() || chars[end] == '\n');
+    if !own_line {
+        return range.clone();
+    }
+    if end < chars.len() {
+        end += 1;
+    }
+    start..end
+}
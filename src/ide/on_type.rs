@@ -0,0 +1,314 @@
+use std::ops::Range;
+
+use crate::{
+    compiler::lexer::{
+        keyword::KeyWord,
+        token::{Token, TokenType},
+        tokenizer::tokenize,
+    },
+    transpiler::format::{BraceType, FormatOptions},
+};
+
+use super::TextEdit;
+
+/// Formats the statement or block that was just completed by typing a character.
+/// `offset` is the offset right after the typed character, the same as an LSP
+/// `textDocument/onTypeFormatting` position.
+///
+/// - `;` re-indents the completed statement and normalizes the spacing inside of it.
+/// - `}` re-indents the completed block and places its opening brace according
+///   to the brace style for the kind of block (function, class, if, etc).
+///
+/// Any other character produces no edits.
+pub fn on_type_formatting(
+    source: &str,
+    offset: usize,
+    typed: char,
+    options: &FormatOptions,
+) -> Vec<TextEdit> {
+    if offset == 0 {
+        return Vec::new();
+    }
+
+    let tokens = tokenize(source);
+    let chars: Vec<char> = source.chars().collect();
+    let index = match tokens.iter().position(|t| t.range().start == offset - 1) {
+        Some(index) => index,
+        None => return Vec::new(),
+    };
+
+    match (typed, tokens[index].kind()) {
+        (';', TokenType::StatementEnd) => format_statement(&chars, &tokens, index, options),
+        ('}', TokenType::RightBrace) => format_block(&chars, &tokens, index, options),
+        _ => Vec::new(),
+    }
+}
+
+fn format_statement(
+    chars: &[char],
+    tokens: &[Token],
+    end: usize,
+    options: &FormatOptions,
+) -> Vec<TextEdit> {
+    // walk back to the end of the previous statement or the start of the block.
+    let mut start = end;
+    while start > 0 {
+        let kind = tokens[start - 1].kind();
+        if kind.is_statement_end() || kind.is_left_brace() || kind.is_right_brace() {
+            break;
+        }
+        start -= 1;
+    }
+    while start < end && is_trivia(&tokens[start]) {
+        start += 1;
+    }
+
+    let mut edits: Vec<TextEdit> = Vec::new();
+    let line = line_start(chars, tokens[start].range().start);
+    if let Some(edit) = indent_line(chars, tokens, line, options) {
+        edits.push(edit);
+    }
+    edits.extend(spacing_edits(tokens, start, end));
+    edits
+}
+
+fn format_block(
+    chars: &[char],
+    tokens: &[Token],
+    close: usize,
+    options: &FormatOptions,
+) -> Vec<TextEdit> {
+    let open = match matching_open_brace(tokens, close) {
+        Some(open) => open,
+        None => return Vec::new(),
+    };
+
+    let mut edits: Vec<TextEdit> = Vec::new();
+    let brace_edit = brace_placement(chars, tokens, open, options);
+    if let Some(edit) = &brace_edit {
+        edits.push(edit.clone());
+    }
+
+    let first_line = line_start(chars, header_start(tokens, open));
+    let last_line = line_start(chars, tokens[close].range().start);
+    let mut line = first_line;
+    loop {
+        if let Some(edit) = indent_line(chars, tokens, line, options) {
+            // the brace placement already indents the line it touches.
+            let overlaps = brace_edit
+                .as_ref()
+                .is_some_and(|b| overlaps(&b.range, &edit.range));
+            if !overlaps {
+                edits.push(edit);
+            }
+        }
+
+        if line >= last_line {
+            break;
+        }
+        line = match chars[line..].iter().position(|c| *c == '\n') {
+            Some(newline) => line + newline + 1,
+            None => break,
+        };
+    }
+
+    edits.sort_by_key(|e| e.range.start);
+    edits
+}
+
+/// Places the opening brace of a block on the same line as its header (K&R)
+/// or on its own line (Allman), depending on the kind of block.
+fn brace_placement(
+    chars: &[char],
+    tokens: &[Token],
+    open: usize,
+    options: &FormatOptions,
+) -> Option<TextEdit> {
+    let style = brace_style(tokens, open, options)?;
+
+    // the last token of the header, eg: `)` in `fn foo() {`
+    let mut header_end = open;
+    while header_end > 0 && is_trivia(&tokens[header_end - 1]) {
+        header_end -= 1;
+    }
+    if header_end == 0 {
+        return None;
+    }
+    let range = tokens[header_end - 1].range().end..tokens[open].range().start;
+
+    let new_text = if style.opens_on_new_line() {
+        let level = brace_level(tokens, tokens[open].range().start);
+        format!("{}{}", options.new_line, options.indent(level))
+    } else {
+        " ".to_string()
+    };
+
+    let current: String = chars[range.clone()].iter().collect();
+    if current == new_text {
+        None
+    } else {
+        Some(TextEdit::new(range, new_text))
+    }
+}
+
+/// Finds the brace style that applies to the block opened at `open`, based on
+/// the first keyword of its header. Blocks without a keyword (eg: objects) are left alone.
+fn brace_style(tokens: &[Token], open: usize, options: &FormatOptions) -> Option<BraceType> {
+    let start = header_start(tokens, open);
+    let keyword = tokens[..open]
+        .iter()
+        .filter(|t| t.range().start >= start)
+        .find(|t| t.kind().is_keyword())?
+        .kind()
+        .as_keyword();
+
+    let style = match keyword {
+        KeyWord::Function => &options.function_brace,
+        KeyWord::Class | KeyWord::Interface | KeyWord::Enum | KeyWord::Namespace => {
+            &options.class_brace
+        }
+        KeyWord::If => &options.if_brace,
+        KeyWord::Else => &options.else_brace,
+        KeyWord::While | KeyWord::Do => &options.while_brace,
+        KeyWord::For => &options.for_brace,
+        KeyWord::Try | KeyWord::Catch | KeyWord::Finally => &options.try_brace,
+        _ => return None,
+    };
+    Some(style.clone())
+}
+
+/// The offset of the first token of the header of the block opened at `open`.
+fn header_start(tokens: &[Token], open: usize) -> usize {
+    let mut start = open;
+    while start > 0 {
+        let kind = tokens[start - 1].kind();
+        if kind.is_statement_end() || kind.is_left_brace() || kind.is_right_brace() {
+            break;
+        }
+        start -= 1;
+    }
+    while start < open && is_trivia(&tokens[start]) {
+        start += 1;
+    }
+    tokens[start].range().start
+}
+
+fn matching_open_brace(tokens: &[Token], close: usize) -> Option<usize> {
+    let mut depth = 0;
+    for i in (0..close).rev() {
+        let kind = tokens[i].kind();
+        if kind.is_right_brace() {
+            depth += 1;
+        } else if kind.is_left_brace() {
+            if depth == 0 {
+                return Some(i);
+            }
+            depth -= 1;
+        }
+    }
+    None
+}
+
+/// Normalizes the spacing between the tokens of a single line statement.
+/// - No space before `;`, `,` and closing delimiters, or after opening delimiters.
+/// - One space after a `,`.
+/// - Runs of spaces are collapsed to one.
+fn spacing_edits(tokens: &[Token], start: usize, end: usize) -> Vec<TextEdit> {
+    let mut edits: Vec<TextEdit> = Vec::new();
+    for i in start..end {
+        let token = &tokens[i];
+        let next = &tokens[i + 1];
+
+        if token.kind().is_comma() && !next.kind().is_whitespace() && !is_closing(next) {
+            edits.push(TextEdit::insert(token.range().end, " ".to_string()));
+            continue;
+        }
+
+        if !token.kind().is_whitespace() || i == start {
+            continue;
+        }
+        let value = token.value().unwrap_or_default();
+        if value.contains('\n') {
+            continue;
+        }
+
+        let prev = &tokens[i - 1];
+        let desired = if is_closing(next)
+            || next.kind().is_statement_end()
+            || next.kind().is_comma()
+            || prev.kind().is_left_parenthesis()
+            || prev.kind().is_left_bracket()
+        {
+            ""
+        } else {
+            " "
+        };
+
+        if value != desired {
+            edits.push(TextEdit::new(token.range(), desired.to_string()));
+        }
+    }
+    edits
+}
+
+/// Re-indents the line starting at `line` to the depth of the braces around it.
+fn indent_line(
+    chars: &[char],
+    tokens: &[Token],
+    line: usize,
+    options: &FormatOptions,
+) -> Option<TextEdit> {
+    let mut content = line;
+    while content < chars.len() && (chars[content] == ' ' || chars[content] == '\t') {
+        content += 1;
+    }
+    // blank lines are left alone.
+    if content >= chars.len() || chars[content] == '\n' || chars[content] == '\r' {
+        return None;
+    }
+
+    let mut level = brace_level(tokens, content);
+    if chars[content] == '}' {
+        level = level.saturating_sub(1);
+    }
+
+    let desired = options.indent(level);
+    let current: String = chars[line..content].iter().collect();
+    if current == desired {
+        None
+    } else {
+        Some(TextEdit::new(line..content, desired))
+    }
+}
+
+/// The amount of braces that are open before the given offset.
+fn brace_level(tokens: &[Token], offset: usize) -> usize {
+    let mut level: usize = 0;
+    for token in tokens.iter().take_while(|t| t.range().start < offset) {
+        if token.kind().is_left_brace() {
+            level += 1;
+        } else if token.kind().is_right_brace() {
+            level = level.saturating_sub(1);
+        }
+    }
+    level
+}
+
+fn line_start(chars: &[char], offset: usize) -> usize {
+    chars[..offset]
+        .iter()
+        .rposition(|c| *c == '\n')
+        .map_or(0, |i| i + 1)
+}
+
+fn is_trivia(token: &Token) -> bool {
+    token.kind().is_whitespace() || token.kind().is_comment()
+}
+
+fn is_closing(token: &Token) -> bool {
+    token.kind().is_right_parenthesis() || token.kind().is_right_bracket()
+}
+
+fn overlaps(a: &Range<usize>, b: &Range<usize>) -> bool {
+    a.start < b.end && b.start < a.end
+}
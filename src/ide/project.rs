@@ -0,0 +1,248 @@
+use std::{collections::HashMap, ops::Range};
+
+use crate::{
+    compiler::{
+        ast::{
+            visit::{self, Visitor},
+            Function, Purity, Visibility,
+        },
+        lexer::{token::Token, tokenizer::tokenize},
+        parser::{context::Context, Parser},
+        symbols::{Symbol, SymbolKind},
+        CompilerOptions,
+    },
+    report::Report,
+};
+
+/// A span in one of the files of a project.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    pub file: String,
+    pub span: Range<usize>,
+}
+
+/// The declaration a name refers to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Definition {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// The span of the declaration, the same as the span of its symbol.
+    pub location: Location,
+}
+
+/// The files open in an editor, parsed into a context each.
+/// Names are resolved with the symbol table of their file, public symbols
+/// declared at the top level of another file are found too, then the standard library.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::{compiler::CompilerOptions, ide::project::Project};
+/// let mut project = Project::new(CompilerOptions::default());
+/// project.open("main.surn", "var x = 1;\nvar y = x;\n")?;
+/// let definition = project.definition_at("main.surn", 19).unwrap(); // `var x = 1;`
+/// project.references(&definition); // the `x` in `var y = x;`
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub struct Project {
+    parser: Parser,
+    /// The context id of every open file, by name.
+    files: HashMap<String, u64>,
+}
+
+impl Project {
+    pub fn new(options: CompilerOptions) -> Self {
+        Self {
+            parser: Parser::new(options),
+            files: HashMap::new(),
+        }
+    }
+
+    /// Parses a file, replacing the previous version of it.
+    /// The previous version is kept if the file doesn't parse.
+    pub fn open(&mut self, file: &str, source: &str) -> Result<(), Report> {
+        let id = self.parser.contexts().next_context_id();
+        self.parser
+            .try_parse_script(file.to_string(), source.to_string())?;
+        self.files.insert(file.to_string(), id);
+        Ok(())
+    }
+
+    pub fn close(&mut self, file: &str) {
+        self.files.remove(file);
+    }
+
+    /// The names of the open files, sorted.
+    pub fn files(&self) -> Vec<&str> {
+        let mut files: Vec<&str> = self.files.keys().map(|f| f.as_str()).collect();
+        files.sort();
+        files
+    }
+
+    /// The declaration of the name at the offset, if the offset is on a name.
+    pub fn definition_at(&self, file: &str, offset: usize) -> Option<Definition> {
+        let (_, source) = self.context(file)?;
+        let token = names(&source)
+            .into_iter()
+            .find(|t| t.range().contains(&offset))?;
+        self.resolve(file, &token)
+    }
+
+    /// Every use of a declaration in the open files, the declaration itself is left out.
+    /// Uses are ordered by file, then by offset.
+    pub fn references(&self, definition: &Definition) -> Vec<Location> {
+        let mut references = Vec::new();
+        for file in self.files() {
+            let (_, source) = self.context(file).unwrap();
+            for token in names(&source) {
+                if token.text() != Some(definition.name.as_str()) {
+                    continue;
+                }
+                let location = Location {
+                    file: file.to_string(),
+                    span: token.range(),
+                };
+                let declared = location.file == definition.location.file
+                    && definition.location.span.contains(&location.span.start);
+                if !declared && self.resolve(file, &token).as_ref() == Some(definition) {
+                    references.push(location);
+                }
+            }
+        }
+        references
+    }
+
+    /// What the name at the offset refers to, as shown when hovering it.
+    /// Functions and methods also say whether calling them has side effects.
+    ///
+    /// **Example:**
+    /// ```text
+    /// function `add`: fn(int, int): int
+    /// pure
+    /// ```
+    pub fn hover(&self, file: &str, offset: usize) -> Option<String> {
+        let definition = self.definition_at(file, offset)?;
+        let (context, _) = self.context(&definition.location.file)?;
+        let symbol = context
+            .symbols
+            .scopes()
+            .iter()
+            .flat_map(|scope| scope.symbols.iter())
+            .find(|s| {
+                s.name == definition.name
+                    && s.kind == definition.kind
+                    && s.span == definition.location.span
+            })?;
+        let kind = format!("{:?}", symbol.kind).to_lowercase();
+        let mut hover = match &symbol.ty {
+            Some(ty) => format!("{} `{}`: {}", kind, symbol.name, ty),
+            None => format!("{} `{}`", kind, symbol.name),
+        };
+        if matches!(symbol.kind, SymbolKind::Function | SymbolKind::Method) {
+            let mut finder = FunctionFinder {
+                span: symbol.span.clone(),
+                purity: None,
+            };
+            visit::walk_body(&mut finder, &context.body);
+            if let Some(purity) = finder.purity {
+                hover.push_str(&format!("\n{}", purity));
+            }
+        }
+        Some(hover)
+    }
+
+    /// The context of an open file, or of a global context like the standard library.
+    fn context(&self, file: &str) -> Option<(&Context, String)> {
+        let contexts = self.parser.contexts();
+        let context = match self.files.get(file) {
+            Some(id) => contexts.get(*id)?,
+            None => contexts
+                .get_globals()
+                .iter()
+                .filter_map(|id| contexts.get(*id))
+                .find(|context| context.source.name == file)?,
+        };
+        let source = context.source.get_contents()?;
+        Some((context, source))
+    }
+
+    /// The declaration a name token refers to, looked up in its own file first.
+    fn resolve(&self, file: &str, token: &Token) -> Option<Definition> {
+        let name = token.value()?;
+        let (context, _) = self.context(file)?;
+        let offset = token.range().start;
+        // a parameter is declared before the body it belongs to, in the closest one after it.
+        let parameter = context
+            .symbols
+            .scopes()
+            .iter()
+            .filter(|scope| scope.span.start > offset)
+            .min_by_key(|scope| scope.span.start)
+            .and_then(|scope| {
+                scope.symbols.iter().find(|s| {
+                    s.kind == SymbolKind::Parameter && s.name == name && s.span.start <= offset
+                })
+            });
+        if let Some(symbol) = parameter.or_else(|| context.symbols.lookup(&name, offset)) {
+            return Some(definition(file, symbol));
+        }
+        self.files()
+            .into_iter()
+            .filter(|f| *f != file)
+            .find_map(|other| {
+                let (context, _) = self.context(other)?;
+                context
+                    .symbols
+                    .global()
+                    .symbols
+                    .iter()
+                    .find(|s| s.name == name && matches!(s.visibility, Visibility::Public))
+                    .map(|symbol| definition(other, symbol))
+            })
+            .or_else(|| {
+                let (context, symbol) = self.parser.contexts().lookup_global(&name)?;
+                Some(definition(&context.source.name, symbol))
+            })
+    }
+}
+
+/// Finds the purity of the function declared at a span.
+struct FunctionFinder {
+    span: Range<usize>,
+    purity: Option<Purity>,
+}
+
+impl Visitor for FunctionFinder {
+    fn visit_function(&mut self, func: &Function) {
+        if func.span == self.span {
+            self.purity = Some(func.purity.clone());
+        }
+        visit::walk_function(self, func);
+    }
+}
+
+fn definition(file: &str, symbol: &Symbol) -> Definition {
+    Definition {
+        name: symbol.name.clone(),
+        kind: symbol.kind,
+        location: Location {
+            file: file.to_string(),
+            span: symbol.span.clone(),
+        },
+    }
+}
+
+/// The identifiers of a source that are names, members after an accessor are left out.
+fn names(source: &str) -> Vec<Token> {
+    let tokens: Vec<Token> = tokenize(source)
+        .into_iter()
+        .filter(|t| !t.kind().is_whitespace() && !t.kind().is_line_break())
+        .collect();
+    tokens
+        .iter()
+        .enumerate()
+        .filter(|(i, t)| {
+            t.kind().is_identifier() && (*i == 0 || !tokens[i - 1].kind().is_accessor())
+        })
+        .map(|(_, t)| t.clone())
+        .collect()
+}
@@ -0,0 +1,145 @@
+use std::ops::Range;
+
+use crate::compiler::ast::{
+    ops::{AnyOperation, UnaryOp},
+    visit::{self, Visitor},
+    AstBody, Expression, Function, Statement,
+};
+
+use super::{CodeAction, TextEdit};
+
+/// The LSP kind of quick fixes.
+pub const QUICK_FIX_KIND: &str = "quickfix";
+
+/// Suggests changing declarations based on how they are used:
+/// - a `var` that is never reassigned can be a `const`.
+/// - a `const` that is reassigned has to be a `var`.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, CompilerOptions};
+/// # use surn::ide::quick_fix::binding_fixes;
+/// // fn main() {
+/// //     var x = 5;
+/// //     const y = 1;
+/// //     y = 2;
+/// // }
+/// # let source = "fn main() {\n    var x = 5;\n    const y = 1;\n    y = 2;\n}\n";
+/// # let body = Parser::new(CompilerOptions::default())
+/// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+/// let actions = binding_fixes(&body);
+/// // ["Change `var x` to `const`", "Change `const y` to `var`"]
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub fn binding_fixes(body: &AstBody) -> Vec<CodeAction> {
+    let mut collector = BindingCollector {
+        bindings: Vec::new(),
+        scopes: vec![Vec::new()],
+    };
+    visit::walk_body(&mut collector, body);
+
+    collector
+        .bindings
+        .into_iter()
+        .filter_map(|binding| {
+            let (from, to) = match (binding.constant, binding.reassigned) {
+                _ if binding.parameter => return None,
+                (false, false) if binding.initialized => ("var", "const"),
+                (true, true) => ("const", "var"),
+                _ => return None,
+            };
+            // declaration spans start at the keyword.
+            let keyword = binding.span.start..binding.span.start + from.len();
+            Some(CodeAction {
+                title: format!("Change `{} {}` to `{}`", from, binding.name, to),
+                kind: QUICK_FIX_KIND.to_string(),
+                edits: vec![TextEdit::new(keyword, to.to_string())],
+            })
+        })
+        .collect()
+}
+
+struct Binding {
+    name: String,
+    span: Range<usize>,
+    constant: bool,
+    initialized: bool,
+    reassigned: bool,
+    /// Parameters shadow outer bindings but are never suggested.
+    parameter: bool,
+}
+
+struct BindingCollector {
+    bindings: Vec<Binding>,
+    /// The indexes of the bindings declared in each scope.
+    scopes: Vec<Vec<usize>>,
+}
+
+impl BindingCollector {
+    fn reassign(&mut self, name: &str) {
+        let found = self
+            .scopes
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.iter().rev())
+            .find(|i| self.bindings[**i].name == name)
+            .copied();
+        if let Some(i) = found {
+            self.bindings[i].reassigned = true;
+        }
+    }
+}
+
+impl Visitor for BindingCollector {
+    fn visit_statement(&mut self, stmt: &Statement) {
+        visit::walk_statement(self, stmt);
+        if let Statement::Var(var) | Statement::Const(var) = stmt {
+            self.bindings.push(Binding {
+                name: var.name.to_string(),
+                span: var.span.clone(),
+                constant: matches!(stmt, Statement::Const(_)),
+                initialized: !var.is_uninit(),
+                reassigned: false,
+                parameter: false,
+            });
+            let index = self.bindings.len() - 1;
+            self.scopes.last_mut().unwrap().push(index);
+        }
+    }
+
+    fn visit_expression(&mut self, expr: &Expression) {
+        if let Expression::Operation(op) = expr {
+            let target = match &op.op {
+                AnyOperation::AssignmentOp(_) => Some(&op.left),
+                AnyOperation::UnaryOp(
+                    UnaryOp::Inc | UnaryOp::IncP | UnaryOp::Dec | UnaryOp::DecP,
+                ) => Some(&op.right),
+                _ => None,
+            };
+            if let Some(Expression::Literal(literal)) = target.map(|t| t.as_ref()) {
+                if literal.ty.is_none() {
+                    self.reassign(&literal.value);
+                }
+            }
+        }
+        visit::walk_expression(self, expr);
+    }
+
+    fn visit_function(&mut self, func: &Function) {
+        let mut scope = Vec::new();
+        for input in func.inputs.iter() {
+            self.bindings.push(Binding {
+                name: input.name.clone(),
+                span: input.span.clone(),
+                constant: false,
+                initialized: true,
+                reassigned: false,
+                parameter: true,
+            });
+            scope.push(self.bindings.len() - 1);
+        }
+        self.scopes.push(scope);
+        visit::walk_function(self, func);
+        self.scopes.pop();
+    }
+}
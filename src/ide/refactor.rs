@@ -0,0 +1,601 @@
+use std::ops::Range;
+
+use crate::{
+    compiler::{
+        ast::{
+            ops::{AnyOperation, UnaryOp},
+            types::{BuiltInType, TypeKind},
+            visit::{self, Visitor},
+            AstBody, Expression, Function, NodeKind, Statement, Variable,
+        },
+        infer::TypeInference,
+        lexer::{token::Token, tokenizer::tokenize},
+        parser::{context::Context, Parser},
+        CompilerOptions,
+    },
+    report::Report,
+    transpiler::format::{printer::Printer, FormatOptions},
+    util::source::SourceBuffer,
+};
+
+use super::{full_line, tokens_within, CodeAction, TextEdit};
+
+/// The LSP kind of inline refactorings.
+pub const REFACTOR_INLINE_KIND: &str = "refactor.inline";
+
+/// The result of an extract function refactoring.
+#[derive(Debug, Clone)]
+pub struct Extraction {
+    /// The function that was synthesized from the selection.
+    pub function: Function,
+    /// The edits that replace the selection with a call and insert the function.
+    pub edits: Vec<TextEdit>,
+}
+
+/// Extracts the selected range of the context into a new function.
+/// - Bindings used in the selection but declared before it become the inputs.
+/// - A declaration in the selection that is used after it becomes the output,
+///   only one is allowed as there is a single return value.
+/// - A selection without any statements is an expression, and is returned as is.
+///
+/// The function is inserted after the top level node the selection is in.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{
+/// #     parser::{context::{Context, SourceOrigin}, Parser},
+/// #     CompilerOptions,
+/// # };
+/// # use surn::ide::refactor::extract_function;
+/// // fn main(a: int) {
+/// //     var b = a * 2;
+/// //     var c = b + 1;
+/// // }
+/// # let source = "fn main(a: int) {\n    var b = a * 2;\n    var c = b + 1;\n}\n";
+/// # let mut context = Context::new(
+/// #     SourceOrigin::new_virtual("main.surn".to_string(), source.to_string()),
+/// #     1,
+/// # );
+/// # context.body = Parser::new(CompilerOptions::default())
+/// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+/// let extraction = extract_function(&context, 22..36, "double")?;
+/// // fn main(a: int) {
+/// //     var b = double(a);
+/// //     var c = b + 1;
+/// // }
+/// //
+/// // fn double(a: int): int {
+/// //     var b = a * 2;
+/// //     return b;
+/// // }
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub fn extract_function(
+    context: &Context,
+    span: Range<usize>,
+    new_name: &str,
+) -> Result<Extraction, Report> {
+    let source = context.source.get_contents().unwrap_or_default();
+    let error = |message: &str| {
+        Report::new()
+            .set_source(SourceBuffer::new(source.clone()))
+            .set_name(context.source.name.clone())
+            .set_message("Could not extract a function".to_string())
+            .make_snippet(span.clone(), message.to_string(), None)
+    };
+
+    let tokens = tokenize(&source);
+    let span = trim(&tokens, &span).ok_or_else(|| error("Nothing is selected."))?;
+    let selected = tokens_within(&tokens, &span);
+    let chars: Vec<char> = source.chars().collect();
+    let text: String = chars[span.clone()].iter().collect();
+
+    let enclosing = context
+        .body
+        .get_program()
+        .into_iter()
+        .find(|node| node.start() <= span.start && span.end <= node.end())
+        .ok_or_else(|| error("The selection must be within a single declaration."))?;
+    let scope = enclosing.start()..enclosing.end();
+
+    let mut collector = BindingCollector {
+        scope: scope.clone(),
+        bindings: Vec::new(),
+    };
+    visit::walk_node(&mut collector, enclosing);
+    let inference = TypeInference::run(&context.body);
+    let type_of = |binding: &Binding| -> TypeKind {
+        binding
+            .ty
+            .clone()
+            .or_else(|| inference.variable_type(&binding.span).cloned())
+            .unwrap_or(TypeKind::BuiltIn(BuiltInType::Any))
+    };
+
+    // inputs are bindings from before the selection, in the order they are first used.
+    let mut inputs: Vec<&Binding> = Vec::new();
+    for name in references(&selected) {
+        let declared = collector
+            .bindings
+            .iter()
+            .rev()
+            .find(|b| b.name == name && b.span.start < span.start);
+        let shadowed = collector
+            .bindings
+            .iter()
+            .any(|b| b.name == name && span.contains(&b.span.start));
+        if let Some(binding) = declared {
+            if !shadowed && !inputs.iter().any(|i| i.name == name) {
+                inputs.push(binding);
+            }
+        }
+    }
+
+    let after = tokens
+        .iter()
+        .filter(|t| t.range().start >= span.end && t.range().end <= scope.end)
+        .cloned()
+        .collect::<Vec<Token>>();
+    let used_after = references(&after);
+    let outputs = collector
+        .bindings
+        .iter()
+        .filter(|b| span.contains(&b.span.start) && used_after.contains(&b.name))
+        .collect::<Vec<&Binding>>();
+    if outputs.len() > 1 {
+        return Err(error(
+            "More than one declaration in the selection is used after it.",
+        ));
+    }
+    let output = outputs.first().copied();
+
+    let is_expression = !selected.iter().any(|t| t.kind().is_statement_end());
+    let parameters = inputs
+        .iter()
+        .map(|i| format!("{}: {}", i.name, type_of(i)))
+        .collect::<Vec<String>>();
+    let arguments = inputs
+        .iter()
+        .map(|i| i.name.clone())
+        .collect::<Vec<String>>()
+        .join(", ");
+    let call = format!("{}({})", new_name, arguments);
+
+    let (signature, body, replacement) = match output {
+        Some(output) => (
+            format!(": {}", type_of(output)),
+            format!("{}\nreturn {};", text, output.name),
+            format!("{} {} = {};", output.keyword, output.name, call),
+        ),
+        None if is_expression => {
+            // the type of the expression is only known once it is a declaration.
+            let probe = format!("var __extracted = {};", text);
+            let ty = Parser::new(CompilerOptions::default())
+                .try_parse_script(context.source.name.clone(), probe)
+                .ok()
+                .and_then(|body| {
+                    let inference = TypeInference::run(&body);
+                    body.get_program()
+                        .first()
+                        .and_then(|node| match &node.inner {
+                            NodeKind::Statement(Statement::Var(var)) => {
+                                inference.variable_type(&var.span).cloned()
+                            }
+                            _ => None,
+                        })
+                });
+            (
+                ty.map(|ty| format!(": {}", ty)).unwrap_or_default(),
+                format!("return {};", text),
+                call,
+            )
+        }
+        None => (String::new(), text, format!("{};", call)),
+    };
+
+    let declaration = format!(
+        "fn {}({}){} {{\n{}\n}}\n",
+        new_name,
+        parameters.join(", "),
+        signature,
+        body
+    );
+    let mut parser = Parser::new(CompilerOptions::default());
+    let synthesized = parser
+        .try_parse_script(context.source.name.clone(), declaration.clone())
+        .map_err(|_| error("The selection is not a complete expression or statement list."))?;
+    let function = synthesized
+        .get_program()
+        .first()
+        .and_then(|node| match &node.inner {
+            NodeKind::Statement(Statement::Function(function)) => Some(function.clone()),
+            _ => None,
+        })
+        .ok_or_else(|| error("The selection is not a complete expression or statement list."))?;
+
+    let options = FormatOptions::default();
+    let printed = Printer::new(&declaration, &options).print(&synthesized);
+    Ok(Extraction {
+        function,
+        edits: vec![
+            TextEdit::new(span, replacement),
+            TextEdit::insert(scope.end, format!("\n\n{}", printed.trim_end())),
+        ],
+    })
+}
+
+/// Replaces every use of a variable with its initializer and removes the declaration.
+/// The offset can be on the declaration or on any of its uses.
+///
+/// The variable has to be assigned exactly once, when it is declared. The initializer
+/// is only moved when that can't change what the program does:
+/// - None of the bindings it reads are written between the declaration and the last use.
+/// - If it has side effects, eg: a call, it is used once and no other call happens before that use.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, CompilerOptions};
+/// # use surn::ide::refactor::inline_variable;
+/// // var a = b + 1;
+/// // var c = a * 2;
+/// # let source = "var a = b + 1;\nvar c = a * 2;\n";
+/// # let body = Parser::new(CompilerOptions::default())
+/// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+/// let edits = inline_variable(source, &body, 4)?;
+/// // var c = (b + 1) * 2;
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub fn inline_variable(
+    source: &str,
+    body: &AstBody,
+    offset: usize,
+) -> Result<Vec<TextEdit>, Report> {
+    let error = |range: Range<usize>, message: String| {
+        Report::new()
+            .set_source(SourceBuffer::new(source.to_string()))
+            .set_message("Could not inline the variable".to_string())
+            .make_snippet(range, message, None)
+    };
+
+    let tokens = tokenize(source);
+    let chars: Vec<char> = source.chars().collect();
+    let name = identifier_at(&tokens, offset)
+        .ok_or_else(|| error(offset..offset, "There is no variable here.".to_string()))?;
+
+    // functions can't see the variables of the top level, and the other way around.
+    let regions = match body
+        .nodes_in_range(offset..offset)
+        .next()
+        .map(|(_, node)| node)
+    {
+        Some(node) if matches!(node.inner, NodeKind::Statement(Statement::Function(_))) => {
+            let range = node.start()..node.end();
+            vec![range]
+        }
+        _ => body
+            .get_program()
+            .iter()
+            .filter(|node| {
+                !matches!(
+                    node.inner,
+                    NodeKind::Statement(Statement::Function(_) | Statement::Class(_))
+                )
+            })
+            .map(|node| node.start()..node.end())
+            .collect(),
+    };
+    let within = |start: usize, end: usize| {
+        tokens
+            .iter()
+            .filter(|t| t.range().start >= start && t.range().end <= end)
+            .filter(|t| regions.iter().any(|r| r.contains(&t.range().start)))
+            .cloned()
+            .collect::<Vec<Token>>()
+    };
+
+    let mut collector = BindingCollector {
+        scope: 0..0,
+        bindings: Vec::new(),
+    };
+    visit::walk_body(&mut collector, body);
+    let declarations = collector
+        .bindings
+        .into_iter()
+        .filter(|b| b.name == name && b.initializer.is_some())
+        .filter(|b| regions.iter().any(|r| r.contains(&b.span.start)))
+        .collect::<Vec<Binding>>();
+    let index = declarations
+        .iter()
+        .rposition(|b| b.span.start <= offset)
+        .ok_or_else(|| error(offset..offset, format!("`{}` is not declared here.", name)))?;
+    let declaration = &declarations[index];
+    let initializer = declaration.initializer.as_ref().unwrap();
+    // a later declaration of the same name shadows this one.
+    let limit = declarations
+        .get(index + 1)
+        .map_or(usize::MAX, |next| next.span.start);
+
+    let following = within(declaration.span.end, limit);
+    let uses = occurrences(&following, &name);
+    if writes(&chars, &following).iter().any(|(n, _)| *n == name) {
+        return Err(error(
+            declaration.span.clone(),
+            format!("`{}` is assigned more than once.", name),
+        ));
+    }
+
+    // the initializer is everything between the `=` and the `;`.
+    let declared = tokens_within(&tokens, &declaration.span);
+    let value = declared
+        .iter()
+        .skip_while(|t| !(t.kind().is_operator() && t.text() == Some("=")))
+        .skip(1)
+        .filter(|t| {
+            !t.kind().is_whitespace() && !t.kind().is_comment() && !t.kind().is_statement_end()
+        })
+        .collect::<Vec<&Token>>();
+    let value_range = match (value.first(), value.last()) {
+        (Some(first), Some(last)) => first.range().start..last.range().end,
+        _ => {
+            return Err(error(
+                declaration.span.clone(),
+                "Nothing to inline.".to_string(),
+            ))
+        }
+    };
+    let text: String = chars[value_range.clone()].iter().collect();
+
+    let last_use = uses.last().map_or(declaration.span.end, |u| u.end);
+    let before_last_use = within(declaration.span.end, last_use);
+    let dependencies = references(&tokens_within(&tokens, &value_range));
+    if let Some((dependency, at)) = writes(&chars, &before_last_use)
+        .into_iter()
+        .find(|(n, _)| dependencies.contains(n))
+    {
+        return Err(error(
+            at..at,
+            format!(
+                "`{}` is written to before the last use of `{}`.",
+                dependency, name
+            ),
+        ));
+    }
+    if !is_pure(initializer) {
+        if uses.len() > 1 {
+            return Err(error(
+                value_range,
+                format!("`{}` has side effects and is used more than once.", name),
+            ));
+        }
+        if has_call(&before_last_use) {
+            return Err(error(
+                value_range,
+                format!(
+                    "`{}` has side effects that would run after another call.",
+                    name
+                ),
+            ));
+        }
+    }
+
+    let replacement = match initializer {
+        Expression::Operation(_) => format!("({})", text),
+        _ => text,
+    };
+    let mut edits = uses
+        .into_iter()
+        .map(|range| TextEdit::new(range, replacement.clone()))
+        .collect::<Vec<TextEdit>>();
+    edits.push(TextEdit::delete(full_line(&chars, &declaration.span)));
+    Ok(edits)
+}
+
+/// Inline variable as a code action, if the variable at the start of the range can be inlined.
+pub fn inline_variable_action(
+    source: &str,
+    body: &AstBody,
+    range: Range<usize>,
+) -> Option<CodeAction> {
+    let edits = inline_variable(source, body, range.start).ok()?;
+    let name = identifier_at(&tokenize(source), range.start)?;
+    Some(CodeAction {
+        title: format!("Inline variable `{}`", name),
+        kind: REFACTOR_INLINE_KIND.to_string(),
+        edits,
+    })
+}
+
+/// Whether or not evaluating the expression can't have side effects.
+/// Calls are assumed to have side effects as their bodies aren't checked.
+fn is_pure(expr: &Expression) -> bool {
+    match expr {
+        Expression::Literal(_) | Expression::EndOfLine => true,
+        Expression::Array(array) => array.values.iter().all(is_pure),
+        Expression::Tuple(values) => values.iter().all(is_pure),
+        Expression::Object(object) => object.properties.iter().all(|p| is_pure(&p.value)),
+        Expression::Member(member) => is_pure(&member.name),
+        Expression::Spread(inner) => is_pure(inner),
+        Expression::Is(test) => is_pure(&test.value),
+        Expression::Operation(op) => match op.op {
+            AnyOperation::AssignmentOp(_) => false,
+            AnyOperation::UnaryOp(
+                UnaryOp::Inc | UnaryOp::IncP | UnaryOp::Dec | UnaryOp::DecP | UnaryOp::Delete,
+            ) => false,
+            _ => is_pure(&op.left) && is_pure(&op.right),
+        },
+        Expression::Call(_)
+        | Expression::MethodCall(_)
+        | Expression::New(_)
+        | Expression::Await(_)
+        | Expression::Statement(_) => false,
+    }
+}
+
+fn identifier_at(tokens: &[Token], offset: usize) -> Option<String> {
+    tokens
+        .iter()
+        .find(|t| t.kind().is_identifier() && t.range().start <= offset && offset <= t.range().end)
+        .and_then(|t| t.value())
+}
+
+/// The ranges the name is referenced at, excluding members and calls.
+fn occurrences(tokens: &[Token], name: &str) -> Vec<Range<usize>> {
+    let meaningful = meaningful(tokens);
+    meaningful
+        .iter()
+        .enumerate()
+        .filter(|(i, t)| {
+            t.kind().is_identifier()
+                && t.text() == Some(name)
+                && !(*i > 0 && meaningful[i - 1].kind().is_accessor())
+                && !meaningful
+                    .get(i + 1)
+                    .is_some_and(|t| t.kind().is_left_parenthesis())
+        })
+        .map(|(_, t)| t.range())
+        .collect()
+}
+
+/// The names that are assigned to, incremented or decremented, and where.
+/// Operators are single characters, so `+=` is a `+` directly followed by a `=`.
+fn writes(chars: &[char], tokens: &[Token]) -> Vec<(String, usize)> {
+    let meaningful = meaningful(tokens);
+    let op = |i: usize| {
+        meaningful
+            .get(i)
+            .filter(|t| t.kind().is_operator())
+            .and_then(|t| t.value())
+    };
+    let adjacent = |i: usize| meaningful[i].range().end == meaningful[i + 1].range().start;
+
+    let mut writes = Vec::new();
+    for (i, token) in meaningful.iter().enumerate() {
+        if !token.kind().is_identifier() || (i > 0 && meaningful[i - 1].kind().is_accessor()) {
+            continue;
+        }
+        let next = op(i + 1);
+        let after = op(i + 2);
+        let assigned = match (next.as_deref(), after.as_deref()) {
+            // `==`, `!=`, `<=` and `>=` are comparisons.
+            (Some("="), after) => {
+                let start = meaningful[i + 1].range().start;
+                let comparison = (after == Some("=") && adjacent(i + 1))
+                    || (start > 0 && "!<>=".contains(chars[start - 1]));
+                !comparison
+            }
+            (Some("+" | "-" | "*" | "/" | "%"), Some("=")) => adjacent(i + 1),
+            (Some("+"), Some("+")) | (Some("-"), Some("-")) => adjacent(i + 1),
+            _ => false,
+        };
+        let prefixed = i >= 2
+            && matches!(
+                (op(i - 2).as_deref(), op(i - 1).as_deref()),
+                (Some("+"), Some("+")) | (Some("-"), Some("-"))
+            )
+            && adjacent(i - 2)
+            && adjacent(i - 1);
+        if assigned || prefixed {
+            writes.push((token.value().unwrap_or_default(), token.range().start));
+        }
+    }
+    writes
+}
+
+fn has_call(tokens: &[Token]) -> bool {
+    let meaningful = meaningful(tokens);
+    meaningful.iter().enumerate().any(|(i, t)| {
+        t.kind().is_identifier()
+            && meaningful
+                .get(i + 1)
+                .is_some_and(|t| t.kind().is_left_parenthesis())
+    })
+}
+
+fn meaningful(tokens: &[Token]) -> Vec<&Token> {
+    tokens
+        .iter()
+        .filter(|t| !t.kind().is_whitespace() && !t.kind().is_comment())
+        .collect()
+}
+
+/// Shrinks the range so it starts and ends on a token that isn't whitespace.
+fn trim(tokens: &[Token], range: &Range<usize>) -> Option<Range<usize>> {
+    let inner = tokens_within(tokens, range)
+        .into_iter()
+        .filter(|t| !t.kind().is_whitespace())
+        .collect::<Vec<Token>>();
+    Some(inner.first()?.range().start..inner.last()?.range().end)
+}
+
+/// The names referenced by the tokens, excluding members and called functions.
+fn references(tokens: &[Token]) -> Vec<String> {
+    let meaningful = meaningful(tokens);
+    let mut names: Vec<String> = Vec::new();
+    for (i, token) in meaningful.iter().enumerate() {
+        if !token.kind().is_identifier() {
+            continue;
+        }
+        let member = i > 0 && meaningful[i - 1].kind().is_accessor();
+        let called = meaningful
+            .get(i + 1)
+            .is_some_and(|t| t.kind().is_left_parenthesis());
+        if let Some(name) = token.value() {
+            if !member && !called && !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    names
+}
+
+struct Binding {
+    name: String,
+    keyword: &'static str,
+    ty: Option<TypeKind>,
+    span: Range<usize>,
+    initializer: Option<Expression>,
+}
+
+/// Collects every declaration within the enclosing node.
+/// Parameters without a span of their own are placed at the start of the node.
+struct BindingCollector {
+    scope: Range<usize>,
+    bindings: Vec<Binding>,
+}
+
+impl Visitor for BindingCollector {
+    fn visit_statement(&mut self, stmt: &Statement) {
+        if let Statement::Var(var) | Statement::Const(var) = stmt {
+            self.push(var, matches!(stmt, Statement::Const(_)));
+        }
+        visit::walk_statement(self, stmt);
+    }
+
+    fn visit_function(&mut self, func: &Function) {
+        for input in func.inputs.iter() {
+            self.bindings.push(Binding {
+                name: input.name.clone(),
+                keyword: "var",
+                ty: input.ty.clone(),
+                span: match input.span.is_empty() {
+                    true => self.scope.start..self.scope.start,
+                    false => input.span.clone(),
+                },
+                initializer: None,
+            });
+        }
+        visit::walk_function(self, func);
+    }
+}
+
+impl BindingCollector {
+    fn push(&mut self, var: &Variable, constant: bool) {
+        self.bindings.push(Binding {
+            name: var.name.to_string(),
+            keyword: if constant { "const" } else { "var" },
+            ty: var.ty.clone(),
+            span: var.span.clone(),
+            initializer: var.assignment.clone(),
+        });
+    }
+}
@@ -1,4 +1,13 @@
+pub mod compile;
 pub mod compiler;
+pub mod convert;
+pub mod ide;
+pub mod project;
+pub mod repl;
 pub mod report;
 pub mod transpiler;
 pub mod util;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use compile::{compile_str, CompilationOutput};
@@ -0,0 +1,221 @@
+//! The settings of a project, mapped from the tables of its manifest.
+//! A table overrides the defaults key by key, see `Config::default`.
+//!
+//! ```toml
+//! [compiler]
+//! strict_types = true
+//! target = "8.1"
+//! fail_on = "warning"
+//!
+//! [format]
+//! braces = "k&r"
+//! indent_size = 2
+//!
+//! [lints]
+//! unused_variable = "deny"
+//! ```
+use std::collections::BTreeMap;
+
+use crate::{
+    compiler::{lints::LintLevel, CompilerOptions, CompilerOptionsBuilder, PhpTarget},
+    report::ReportKind,
+    transpiler::{
+        format::{AccessorStyle, BraceType, FormatOptions},
+        frameworks::Framework,
+    },
+};
+
+use super::{toml::Value, FormatProfile};
+
+/// The keys of a table in a manifest.
+pub type Table = BTreeMap<String, Value>;
+
+/// Everything the parser, the transpiler and the CLI are run with.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// The language to transpile to, eg: `php`.
+    pub lang: String,
+    pub options: CompilerOptions,
+    pub format: FormatOptions,
+}
+
+impl Default for Config {
+    /// The settings outside of a project, php in the psr-4 format.
+    fn default() -> Self {
+        Self {
+            lang: "php".to_string(),
+            options: CompilerOptions::default(),
+            format: FormatOptions::psr_4(),
+        }
+    }
+}
+
+/// Applies the `[compiler]` table to the options.
+pub(super) fn compiler_options(
+    table: &Table,
+    mut options: CompilerOptionsBuilder,
+) -> Result<CompilerOptionsBuilder, String> {
+    for (key, value) in table.iter() {
+        let key = format!("compiler.{}", key);
+        options = match key.as_str() {
+            "compiler.semantic_checks" => options.semantic_checks(boolean(value, &key)?),
+            "compiler.post_semantic_checks" => options.post_semantic_checks(boolean(value, &key)?),
+            "compiler.optimize" => options.optimize(boolean(value, &key)?),
+            "compiler.strict_types" => options.strict_types(boolean(value, &key)?),
+            "compiler.source_maps" => options.source_maps(boolean(value, &key)?),
+            "compiler.preload" => options.preload(boolean(value, &key)?),
+            "compiler.specialize" => options.specialize(boolean(value, &key)?),
+            "compiler.hot_functions" => options.hot_functions(strings(value, &key)?),
+            "compiler.max_file_size" => options.max_file_size(limit(value, &key)?),
+            "compiler.max_tokens" => options.max_tokens(limit(value, &key)?),
+            "compiler.jobs" => options.jobs(Some(size(value, &key)?)),
+            "compiler.globals_class" => options.globals_class(string(value, &key)?),
+            "compiler.runtime_checks" => options.runtime_checks(boolean(value, &key)?),
+            "compiler.target" => {
+                let version = string(value, &key)?;
+                options.target(
+                    PhpTarget::from_version(&version)
+                        .ok_or(format!("Unknown php version: {}", version))?,
+                )
+            }
+            "compiler.fail_on" => {
+                let kind = string(value, &key)?;
+                options.fail_on(ReportKind::from_name(&kind).ok_or(format!(
+                    "`{}` is not a kind of report, the kinds are: error, warning, notice.",
+                    kind
+                ))?)
+            }
+            "compiler.framework" => {
+                let name = string(value, &key)?;
+                options.framework(
+                    Framework::from_name(&name).ok_or(format!("Unknown framework: {}", name))?,
+                )
+            }
+            _ => return Err(format!("Unknown key: `{}`", key)),
+        };
+    }
+    Ok(options)
+}
+
+/// Applies the `[lints]` table to the options, every key is the name of a lint.
+pub(super) fn lint_levels(
+    table: &Table,
+    mut options: CompilerOptionsBuilder,
+) -> Result<CompilerOptionsBuilder, String> {
+    for (lint, value) in table.iter() {
+        let level = string(value, &format!("lints.{}", lint))?;
+        let level = LintLevel::from_name(&level).ok_or(format!(
+            "`{}` is not a lint level, the levels are: allow, warn, deny.",
+            level
+        ))?;
+        options = options.lint(lint, level);
+    }
+    Ok(options)
+}
+
+/// The format of the profile, with the `[format]` table applied to it.
+/// `braces` sets the style of every brace, the keys of single braces win over it.
+pub(super) fn format_options(
+    profile: FormatProfile,
+    table: &Table,
+) -> Result<FormatOptions, String> {
+    let mut format = profile.options();
+    if let Some(value) = table.get("braces") {
+        let style = brace(value, "format.braces")?;
+        for field in braces(&mut format) {
+            *field = style.clone();
+        }
+    }
+    for (key, value) in table.iter() {
+        let key = format!("format.{}", key);
+        match key.as_str() {
+            "format.braces" => {}
+            "format.tab_size" => format.tab_size = size(value, &key)?,
+            "format.indent_size" => format.indent_size = size(value, &key)?,
+            "format.snake_case_vars" => format.snake_case_vars = boolean(value, &key)?,
+            "format.indent_after_type" => format.indent_after_type = boolean(value, &key)?,
+            "format.class_brace" => format.class_brace = brace(value, &key)?,
+            "format.function_brace" => format.function_brace = brace(value, &key)?,
+            "format.if_brace" => format.if_brace = brace(value, &key)?,
+            "format.else_brace" => format.else_brace = brace(value, &key)?,
+            "format.while_brace" => format.while_brace = brace(value, &key)?,
+            "format.for_brace" => format.for_brace = brace(value, &key)?,
+            "format.match_brace" => format.match_brace = brace(value, &key)?,
+            "format.try_brace" => format.try_brace = brace(value, &key)?,
+            "format.accessors" => format.accessors = accessor_style(value, &key)?,
+            _ => return Err(format!("Unknown key: `{}`", key)),
+        }
+    }
+    Ok(format)
+}
+
+fn braces(format: &mut FormatOptions) -> [&mut BraceType; 8] {
+    [
+        &mut format.class_brace,
+        &mut format.function_brace,
+        &mut format.if_brace,
+        &mut format.else_brace,
+        &mut format.while_brace,
+        &mut format.for_brace,
+        &mut format.match_brace,
+        &mut format.try_brace,
+    ]
+}
+
+fn mismatch(value: &Value, key: &str, expected: &str) -> String {
+    format!("`{}` has to be {}, it is {}.", key, expected, value.kind())
+}
+
+pub(super) fn string(value: &Value, key: &str) -> Result<String, String> {
+    value
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| mismatch(value, key, "a string"))
+}
+
+pub(super) fn strings(value: &Value, key: &str) -> Result<Vec<String>, String> {
+    match value {
+        Value::Array(values) => values.iter().map(|value| string(value, key)).collect(),
+        value => Err(mismatch(value, key, "an array")),
+    }
+}
+
+fn boolean(value: &Value, key: &str) -> Result<bool, String> {
+    match value {
+        Value::Bool(value) => Ok(*value),
+        value => Err(mismatch(value, key, "a boolean")),
+    }
+}
+
+fn size(value: &Value, key: &str) -> Result<usize, String> {
+    match value {
+        Value::Integer(value) if *value >= 0 => Ok(*value as usize),
+        value => Err(mismatch(value, key, "a positive integer")),
+    }
+}
+
+/// A limit, `false` for no limit.
+fn limit(value: &Value, key: &str) -> Result<Option<usize>, String> {
+    match value {
+        Value::Bool(false) => Ok(None),
+        value => size(value, key)
+            .map(Some)
+            .map_err(|_| mismatch(value, key, "a positive integer or `false`")),
+    }
+}
+
+fn brace(value: &Value, key: &str) -> Result<BraceType, String> {
+    let name = string(value, key)?;
+    BraceType::from_name(&name).ok_or(format!(
+        "`{}` is not a brace style, the styles are: allman, k&r, allman-mix.",
+        name
+    ))
+}
+
+fn accessor_style(value: &Value, key: &str) -> Result<AccessorStyle, String> {
+    let name = string(value, key)?;
+    AccessorStyle::from_name(&name).ok_or(format!(
+        "`{}` is not an accessor style, the styles are: magic, methods.",
+        name
+    ))
+}
@@ -0,0 +1,361 @@
+//! Surn projects, a directory with a `surn.toml` manifest at its root.
+//!
+//! The manifest says what the project is called, which language it is transpiled to,
+//! how the generated code is laid out and where its sources are, so a project is built
+//! with `surnc build` instead of a list of flags.
+//!
+//! ```toml
+//! [package]
+//! name = "app"
+//!
+//! [build]
+//! lang = "php"
+//! format = "psr-4"
+//! sources = ["src"]
+//! output = "out"
+//! ```
+//!
+//! The `[compiler]`, `[format]` and `[lints]` tables set the options of the project,
+//! see `config`.
+#[cfg(feature = "fs")]
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "fs")]
+use crate::transpiler::scaffold::write_files;
+use crate::{
+    compiler::CompilerOptions,
+    transpiler::{
+        driver::{generate_tree, BatchReport},
+        format::FormatOptions,
+        Transpiler,
+    },
+    util::provider::{OsProvider, SourceProvider},
+};
+
+use self::{
+    config::{string, strings, Config, Table},
+    toml::Value,
+};
+
+pub mod config;
+pub mod toml;
+
+/// The name of the manifest at the root of a project.
+pub const MANIFEST_FILE: &str = "surn.toml";
+
+/// The layout of the generated code, see `FormatOptions`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FormatProfile {
+    /// Braces on their own line, the php standard.
+    Psr4,
+    /// Braces on the same line and snake case variables.
+    Rust,
+}
+
+impl FormatProfile {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "psr-4" | "psr4" => Some(FormatProfile::Psr4),
+            "rust" => Some(FormatProfile::Rust),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FormatProfile::Psr4 => "psr-4",
+            FormatProfile::Rust => "rust",
+        }
+    }
+
+    pub fn options(&self) -> FormatOptions {
+        match self {
+            FormatProfile::Psr4 => FormatOptions::psr_4(),
+            FormatProfile::Rust => FormatOptions::rust(),
+        }
+    }
+}
+
+/// The contents of a `surn.toml`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Manifest {
+    pub name: String,
+    /// The language the project is transpiled to, eg: `php`.
+    pub lang: String,
+    pub format: FormatProfile,
+    /// The directories of the sources, relative to the root of the project.
+    pub sources: Vec<PathBuf>,
+    /// The directory the sources are mirrored to, relative to the root of the project.
+    pub output: PathBuf,
+    /// The include paths of the compiler, relative to the root of the project.
+    pub include: Vec<PathBuf>,
+    /// The `[compiler]` table.
+    pub compiler: Table,
+    /// The `[format]` table, applied over the format profile.
+    pub formatting: Table,
+    /// The `[lints]` table.
+    pub lints: Table,
+}
+
+impl Manifest {
+    /// The manifest of a php project with its sources in `src`.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            lang: "php".to_string(),
+            format: FormatProfile::Psr4,
+            sources: vec![PathBuf::from("src")],
+            output: PathBuf::from("out"),
+            include: Vec::new(),
+            compiler: Table::new(),
+            formatting: Table::new(),
+            lints: Table::new(),
+        }
+    }
+
+    /// Parses a manifest, only `package.name` is required.
+    /// The settings are checked, so the manifest always makes a `Config`.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::project::Manifest;
+    /// let manifest = Manifest::parse("[package]\nname = \"app\"\n")?;
+    /// manifest.sources; // ["src"]
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut document = toml::parse(source)?;
+        let mut package = document.remove("package").unwrap_or_default();
+        let mut build = document.remove("build").unwrap_or_default();
+
+        let name = match package.remove("name") {
+            Some(value) => string(&value, "package.name")?,
+            None => return Err("`package.name` is missing.".to_string()),
+        };
+        let mut manifest = Manifest::new(&name);
+        if let Some(value) = build.remove("lang") {
+            manifest.lang = string(&value, "build.lang")?;
+        }
+        if let Some(value) = build.remove("format") {
+            let format = string(&value, "build.format")?;
+            manifest.format = FormatProfile::from_name(&format)
+                .ok_or(format!("Unknown format profile: {}", format))?;
+        }
+        if let Some(value) = build.remove("sources") {
+            manifest.sources = paths(&value, "build.sources")?;
+        }
+        if let Some(value) = build.remove("output") {
+            manifest.output = PathBuf::from(string(&value, "build.output")?);
+        }
+        if let Some(value) = build.remove("include") {
+            manifest.include = paths(&value, "build.include")?;
+        }
+        manifest.compiler = document.remove("compiler").unwrap_or_default();
+        manifest.formatting = document.remove("format").unwrap_or_default();
+        manifest.lints = document.remove("lints").unwrap_or_default();
+
+        // keys of the root table and whole tables that aren't known.
+        let root = document.remove("").unwrap_or_default();
+        let unknown = root
+            .into_keys()
+            .chain(document.into_keys().map(|table| format!("[{}]", table)))
+            .chain(package.into_keys().map(|key| format!("package.{}", key)))
+            .chain(build.into_keys().map(|key| format!("build.{}", key)))
+            .next();
+        if let Some(key) = unknown {
+            return Err(format!("Unknown key in {}: `{}`", MANIFEST_FILE, key));
+        }
+        manifest.config(Path::new(""))?;
+        Ok(manifest)
+    }
+
+    /// The settings of the manifest, include paths are resolved against the root.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use std::path::Path;
+    /// # use surn::project::Manifest;
+    /// // [compiler]
+    /// // strict_types = true
+    /// # let manifest = Manifest::parse("[package]\nname = \"app\"\n\n[compiler]\nstrict_types = true\n")?;
+    /// let config = manifest.config(Path::new("app"))?;
+    /// config.options.strict_types; // true
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn config(&self, root: &Path) -> Result<Config, String> {
+        let mut options = config::compiler_options(&self.compiler, CompilerOptions::builder())?;
+        options = config::lint_levels(&self.lints, options)?;
+        let options = options
+            .include_paths(self.include.iter().map(|path| root.join(path)).collect())
+            .build()
+            .map_err(|report| report.message)?;
+        Ok(Config {
+            lang: self.lang.clone(),
+            options,
+            format: config::format_options(self.format, &self.formatting)?,
+        })
+    }
+
+    /// Reads the manifest at the root of a project.
+    #[cfg(feature = "fs")]
+    pub fn load(root: &Path) -> Result<Self, String> {
+        let path = root.join(MANIFEST_FILE);
+        let source = fs::read_to_string(&path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        Self::parse(&source).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Writes the manifest the way `parse` reads it.
+    pub fn to_toml(&self) -> String {
+        let path = |path: &PathBuf| Value::String(path.to_string_lossy().replace('\\', "/"));
+        let mut toml = format!(
+            "[package]\nname = {}\n\n[build]\nlang = {}\nformat = {}\nsources = {}\noutput = {}\n",
+            toml::write_value(&Value::String(self.name.clone())),
+            toml::write_value(&Value::String(self.lang.clone())),
+            toml::write_value(&Value::String(self.format.name().to_string())),
+            toml::write_value(&Value::Array(self.sources.iter().map(path).collect())),
+            toml::write_value(&path(&self.output)),
+        );
+        if !self.include.is_empty() {
+            toml.push_str(&format!(
+                "include = {}\n",
+                toml::write_value(&Value::Array(self.include.iter().map(path).collect()))
+            ));
+        }
+        for (name, table) in [
+            ("compiler", &self.compiler),
+            ("format", &self.formatting),
+            ("lints", &self.lints),
+        ] {
+            if table.is_empty() {
+                continue;
+            }
+            toml.push_str(&format!("\n[{}]\n", name));
+            for (key, value) in table.iter() {
+                toml.push_str(&format!("{} = {}\n", key, toml::write_value(value)));
+            }
+        }
+        toml
+    }
+}
+
+fn paths(value: &Value, key: &str) -> Result<Vec<PathBuf>, String> {
+    Ok(strings(value, key)?
+        .into_iter()
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// The closest directory with a manifest, starting from the path itself.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use std::path::Path;
+/// # use surn::project::find_root;
+/// find_root(Path::new("app/src/main.surn")); // Some("app")
+/// ```
+#[cfg(feature = "fs")]
+pub fn find_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|dir| dir.join(MANIFEST_FILE).is_file())
+        .map(Path::to_path_buf)
+}
+
+/// A project on disk.
+#[derive(Debug, Clone)]
+pub struct Project {
+    pub root: PathBuf,
+    pub manifest: Manifest,
+}
+
+impl Project {
+    /// Loads the project whose manifest is in the given directory.
+    #[cfg(feature = "fs")]
+    pub fn load(root: &Path) -> Result<Self, String> {
+        Ok(Self {
+            root: root.to_path_buf(),
+            manifest: Manifest::load(root)?,
+        })
+    }
+
+    /// The settings of the project, see `Manifest::config`.
+    pub fn config(&self) -> Result<Config, String> {
+        self.manifest.config(&self.root)
+    }
+
+    /// Transpiles every source directory of the project to its output directory.
+    /// The config is usually the project's own, with the flags of the CLI applied to it.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use std::path::Path;
+    /// # use surn::{project::Project, transpiler::Transpiler};
+    /// # let mut transpiler = Transpiler::new();
+    /// # transpiler.register_defaults();
+    /// let project = Project::load(Path::new("app"))?;
+    /// let report = project.build(&transpiler, &project.config()?)?;
+    /// report.print_errors();
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn build(&self, transpiler: &Transpiler, config: &Config) -> Result<BatchReport, String> {
+        self.build_with(&OsProvider, transpiler, config)
+    }
+
+    /// Transpiles the project with its files read from and written to a provider,
+    /// eg: a project kept in memory, see `build`.
+    pub fn build_with(
+        &self,
+        provider: &dyn SourceProvider,
+        transpiler: &Transpiler,
+        config: &Config,
+    ) -> Result<BatchReport, String> {
+        let language = transpiler
+            .get(&config.lang)
+            .ok_or(format!("Unknown language: {}", config.lang))?;
+        let output = self.root.join(&self.manifest.output);
+
+        let mut report = BatchReport { files: Vec::new() };
+        for source in self.manifest.sources.iter() {
+            let source = self.root.join(source);
+            let batch = generate_tree(
+                provider,
+                language.generator.as_ref(),
+                &source,
+                &output,
+                &language.extension,
+                &config.options,
+                &config.format,
+            )?;
+            report.files.extend(batch.files);
+            language
+                .generator
+                .write_support(provider, &source, &output, &config.options)?;
+        }
+        Ok(report)
+    }
+}
+
+/// The files of a new project, with paths relative to the project root.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::project::project_files;
+/// project_files("app"); // [surn.toml, .gitignore, src/main.surn, tests/.gitkeep]
+/// ```
+pub fn project_files(name: &str) -> Vec<(PathBuf, String)> {
+    let source = "fn main(): string {\n    var greeting: string = \"Hello world!\";\n    return greeting;\n}\n";
+    vec![
+        (PathBuf::from(MANIFEST_FILE), Manifest::new(name).to_toml()),
+        (PathBuf::from(".gitignore"), "/out/\n".to_string()),
+        (PathBuf::from("src/main.surn"), source.to_string()),
+        (PathBuf::from("tests/.gitkeep"), String::new()),
+    ]
+}
+
+/// Creates a new project in the given directory, see `project_files`.
+/// Nothing is written when one of the files already exists.
+#[cfg(feature = "fs")]
+pub fn new_project(root: &Path, name: &str) -> Result<Vec<PathBuf>, String> {
+    write_files(root, project_files(name))
+}
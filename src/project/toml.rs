@@ -0,0 +1,194 @@
+//! The part of toml a `surn.toml` uses.
+//!
+//! Tables, `key = value` pairs and `#` comments, where a value is a string,
+//! an integer, a boolean or an array of them on a single line.
+//! Keys before the first table belong to the root table, named `""`.
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Bool(bool),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The name of the kind of value, for error messages.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Value::String(_) => "a string",
+            Value::Integer(_) => "an integer",
+            Value::Bool(_) => "a boolean",
+            Value::Array(_) => "an array",
+        }
+    }
+}
+
+/// The tables of a document and their keys, both sorted by name.
+pub type Document = BTreeMap<String, BTreeMap<String, Value>>;
+
+/// Parses a toml document, the error names the line it is on.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::project::toml::parse;
+/// let document = parse("[package]\nname = \"app\"\n")?;
+/// &document["package"]["name"]; // Value::String("app")
+/// # Ok::<(), String>(())
+/// ```
+pub fn parse(source: &str) -> Result<Document, String> {
+    let mut document = Document::new();
+    let mut table = String::new();
+    document.insert(table.clone(), BTreeMap::new());
+
+    for (i, line) in source.lines().enumerate() {
+        let error = |message: String| format!("line {}: {}", i + 1, message);
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[') {
+            let name = name
+                .strip_suffix(']')
+                .ok_or_else(|| error("A table name is never closed with `]`.".to_string()))?
+                .trim();
+            if !is_key(name) {
+                return Err(error(format!("`{}` is not a valid table name.", name)));
+            }
+            if document.contains_key(name) {
+                return Err(error(format!("The `{}` table is declared twice.", name)));
+            }
+            table = name.to_string();
+            document.insert(table.clone(), BTreeMap::new());
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| error(format!("Expected `key = value`, found `{}`.", line)))?;
+        let key = key.trim();
+        if !is_key(key) {
+            return Err(error(format!("`{}` is not a valid key.", key)));
+        }
+        let value = parse_value(value.trim()).map_err(error)?;
+        let keys = document.get_mut(&table).unwrap();
+        if keys.insert(key.to_string(), value).is_some() {
+            return Err(error(format!("`{}` is set twice.", key)));
+        }
+    }
+    Ok(document)
+}
+
+/// Writes a value the way `parse` reads it.
+pub fn write_value(value: &Value) -> String {
+    match value {
+        Value::String(value) => format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"")),
+        Value::Integer(value) => value.to_string(),
+        Value::Bool(value) => value.to_string(),
+        Value::Array(values) => format!(
+            "[{}]",
+            values
+                .iter()
+                .map(write_value)
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+    }
+}
+
+fn is_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+/// The line without its comment, a `#` in a string is kept.
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '\\' if in_string => {
+                escaped = !escaped;
+                continue;
+            }
+            '"' if !escaped => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => {}
+        }
+        escaped = false;
+    }
+    line
+}
+
+fn parse_value(value: &str) -> Result<Value, String> {
+    let (parsed, rest) = parse_prefix(value)?;
+    if !rest.trim().is_empty() {
+        return Err(format!("Unexpected `{}` after a value.", rest.trim()));
+    }
+    Ok(parsed)
+}
+
+/// Parses the value at the start of the input, and returns what follows it.
+fn parse_prefix(input: &str) -> Result<(Value, &str), String> {
+    let input = input.trim_start();
+    if let Some(rest) = input.strip_prefix('"') {
+        let mut value = String::new();
+        let mut chars = rest.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => return Ok((Value::String(value), &rest[i + 1..])),
+                '\\' => match chars.next().map(|(_, c)| c) {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some(c @ ('\\' | '"')) => value.push(c),
+                    Some(c) => return Err(format!("`\\{}` is not an escape sequence.", c)),
+                    None => break,
+                },
+                c => value.push(c),
+            }
+        }
+        return Err("A string is never closed with `\"`.".to_string());
+    }
+    if let Some(mut rest) = input.strip_prefix('[') {
+        let mut values: Vec<Value> = Vec::new();
+        loop {
+            rest = rest.trim_start();
+            if let Some(after) = rest.strip_prefix(']') {
+                return Ok((Value::Array(values), after));
+            }
+            let (value, after) = parse_prefix(rest)?;
+            values.push(value);
+            rest = after.trim_start();
+            if let Some(after) = rest.strip_prefix(',') {
+                rest = after;
+            } else if !rest.starts_with(']') {
+                return Err("Expected a `,` or `]` after a value in an array.".to_string());
+            }
+        }
+    }
+
+    let end = input
+        .find(|c: char| c == ',' || c == ']' || c.is_whitespace())
+        .unwrap_or(input.len());
+    let (word, rest) = input.split_at(end);
+    let value = match word {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        word => Value::Integer(
+            word.replace('_', "")
+                .parse()
+                .map_err(|_| format!("`{}` is not a value, strings need quotes.", word))?,
+        ),
+    };
+    Ok((value, rest))
+}
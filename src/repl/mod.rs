@@ -0,0 +1,192 @@
+//! Reads surn a line at a time, see `surnc repl`.
+//!
+//! Every line is parsed as a script of its own by the same parser, so what a line declares
+//! is found by the lines after it, see `ContextStore::lookup_global`. The lines are run
+//! by one `Interpreter`, so the variables and functions of a line are kept too.
+//!
+//! **Example:**
+//! ```rust no_run
+//! # use surn::{compiler::CompilerOptions, repl::Repl};
+//! let mut repl = Repl::new(CompilerOptions::default());
+//! repl.eval_line("var x = 1 + 2")?; // Some("x = 3")
+//! repl.eval_line("fn double(n: int): int { return n * 2; }")?; // None
+//! repl.eval_line("double(x)")?; // Some("6")
+//! repl.eval_line(":php")?;
+//! repl.eval_line("x * 2")?; // Some("$x * 2;")
+//! # Ok::<(), surn::report::Report>(())
+//! ```
+use std::{path::Path, sync::Arc};
+
+use crate::{
+    compiler::{
+        ast::{
+            ops::{AnyOperation, AssignmentOp},
+            AstBody, Expression, NodeKind, Statement,
+        },
+        interp::{Interpreter, Value},
+        parser::Parser,
+        CompilerOptions,
+    },
+    report::{Report, Snippet},
+    transpiler::{driver::generate_source, format::FormatOptions, Transpiler},
+    util::source::SourceBuffer,
+};
+
+/// What the REPL prints for a line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplMode {
+    /// The value of an expression, or of the variable a line declares or assigns.
+    Eval,
+    /// The php the line is transpiled to.
+    Php,
+}
+
+pub const HELP: &str = ":eval    Prints the values of the lines, the default.
+:php     Prints the php of the lines.
+:help    Shows this help.
+:quit    Stops reading lines, as does the end of the input.";
+
+pub struct Repl {
+    parser: Parser,
+    options: CompilerOptions,
+    mode: ReplMode,
+    interpreter: Interpreter,
+    transpiler: Transpiler,
+    /// The lines read so far, the script of a line is named after it, eg: `repl:3`.
+    lines: usize,
+}
+
+impl Repl {
+    pub fn new(options: CompilerOptions) -> Self {
+        let mut transpiler = Transpiler::new();
+        transpiler.register_defaults();
+        Repl {
+            parser: Parser::new(options.clone()),
+            options,
+            mode: ReplMode::Eval,
+            interpreter: Interpreter::new(),
+            transpiler,
+            lines: 0,
+        }
+    }
+
+    pub fn mode(&self) -> ReplMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: ReplMode) {
+        self.mode = mode;
+    }
+
+    /// The value a variable was last given.
+    pub fn value(&self, name: &str) -> Option<&Value> {
+        self.interpreter.get(name)
+    }
+
+    /// Reads a line, returning what is printed for it. A line that doesn't end a statement
+    /// is ended with a `;`. Lines starting with `:` are commands, see `HELP`.
+    /// A line that doesn't parse is forgotten, its declarations aren't kept.
+    pub fn eval_line(&mut self, line: &str) -> Result<Option<String>, Report> {
+        let line = line.trim();
+        if let Some(command) = line.strip_prefix(':') {
+            return self.command(command);
+        }
+        if line.is_empty() {
+            return Ok(None);
+        }
+
+        let mut source = line.to_string();
+        if !source.ends_with(';') && !source.ends_with('}') {
+            source.push(';');
+        }
+        self.lines += 1;
+        let name = format!("repl:{}", self.lines);
+        let body = match self.parser.try_parse_script(name.clone(), source.clone()) {
+            Ok(body) => body,
+            Err(report) => {
+                self.parser.remove_script(&name);
+                return Err(report);
+            }
+        };
+        match self.mode {
+            ReplMode::Eval => self.eval(&name, &source, &body),
+            ReplMode::Php => self.php(&name, &source, body),
+        }
+    }
+
+    fn command(&mut self, command: &str) -> Result<Option<String>, Report> {
+        match command.trim() {
+            "eval" => self.mode = ReplMode::Eval,
+            "php" => self.mode = ReplMode::Php,
+            "help" => return Ok(Some(HELP.to_string())),
+            command => {
+                return Err(Report::new()
+                    .set_name("repl".to_string())
+                    .set_message(format!("Unknown command `:{}`, see `:help`.", command)))
+            }
+        }
+        Ok(None)
+    }
+
+    fn eval(&mut self, name: &str, source: &str, body: &AstBody) -> Result<Option<String>, Report> {
+        let mut printed: Vec<String> = Vec::new();
+        for node in body.get_program() {
+            let value = self.interpreter.run_node(node).map_err(|error| {
+                let buffer = Arc::new(SourceBuffer::new(source.to_string()));
+                let span = error.span.unwrap_or(node.start()..node.end());
+                Report::new()
+                    .set_name(name.to_string())
+                    .set_source(buffer.clone())
+                    .set_message("The line can't be evaluated.".to_string())
+                    .add_snippet(Snippet::new(buffer, error.message, span))
+            })?;
+            // a declaration or an assignment prints the variable it gives a value.
+            let assigned = match &node.inner {
+                NodeKind::Statement(Statement::Var(var) | Statement::Const(var)) => {
+                    Some(&*var.name)
+                }
+                NodeKind::Statement(Statement::Static(s)) => match s.statement.as_ref() {
+                    Statement::Var(var) => Some(&*var.name),
+                    _ => None,
+                },
+                NodeKind::Expression(Expression::Operation(operation))
+                    if matches!(operation.op, AnyOperation::AssignmentOp(AssignmentOp::Eq)) =>
+                {
+                    match operation.left.as_ref() {
+                        Expression::Literal(target) if target.ty.is_none() => Some(&*target.value),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+            match (assigned, value) {
+                (Some(variable), _) => {
+                    if let Some(value) = self.interpreter.get(variable) {
+                        printed.push(format!("{} = {}", variable, value));
+                    }
+                }
+                (None, Some(value)) => printed.push(value.to_string()),
+                (None, None) => {}
+            }
+        }
+        Ok(Some(printed.join("\n")).filter(|printed| !printed.is_empty()))
+    }
+
+    fn php(&mut self, name: &str, source: &str, body: AstBody) -> Result<Option<String>, Report> {
+        let php = self
+            .transpiler
+            .get("php")
+            .expect("php is a default language");
+        let (code, _, _) = generate_source(
+            php.generator.as_ref(),
+            body,
+            Path::new(name),
+            source,
+            &self.options,
+            &FormatOptions::default(),
+        )?;
+        // a line is printed without the tag its file would start with.
+        let code = code.trim_start_matches("<?php").trim();
+        Ok(Some(code.to_string()).filter(|code| !code.is_empty()))
+    }
+}
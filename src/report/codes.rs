@@ -0,0 +1,459 @@
+//! The code of every report, see `Report::explain`.
+//!
+//! Codes are stable, a code is never reused for another error once it is released.
+//! `E00xx` are problems with the tokens themselves, `E01xx` syntax the parser expected,
+//! `E02xx` checks that run after parsing and `E03xx` limits of the compiler.
+
+/// A documented report code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorCode {
+    pub code: u64,
+    /// A short description, eg: `missing semicolon`.
+    pub title: &'static str,
+    pub explanation: &'static str,
+}
+
+pub const UNEXPECTED_TOKEN: u64 = 1;
+pub const UNEXPECTED_EOF: u64 = 2;
+pub const UNCLOSED_DELIMITER: u64 = 3;
+pub const INVALID_ESCAPE: u64 = 4;
+pub const MISSPELLED_KEYWORD: u64 = 5;
+pub const UNEXPECTED_CHARACTER: u64 = 6;
+pub const INVALID_SYNTAX: u64 = 100;
+pub const EXPECTED_EXPRESSION: u64 = 101;
+pub const MISSING_SEMICOLON: u64 = 102;
+pub const MISSING_COMMA: u64 = 103;
+pub const EXPECTED_TYPE: u64 = 104;
+pub const EXPECTED_NAME: u64 = 105;
+pub const EXPECTED_STATEMENT: u64 = 106;
+pub const UNKNOWN_MACRO: u64 = 107;
+pub const DUPLICATE_DECLARATION: u64 = 108;
+pub const INVALID_ACCESSOR: u64 = 109;
+pub const MISPLACED_VARIADIC: u64 = 110;
+pub const MISPLACED_STATEMENT: u64 = 111;
+pub const TYPE_ARGUMENT_COUNT: u64 = 201;
+pub const NULLABLE_VALUE: u64 = 202;
+pub const INVALID_CONST: u64 = 203;
+pub const SHAPE_MISMATCH: u64 = 204;
+pub const UNUSED_VARIABLE: u64 = 205;
+pub const AWAIT_OUTSIDE_ASYNC: u64 = 206;
+pub const UNRESOLVED_NAME: u64 = 207;
+pub const AMBIGUOUS_NAME: u64 = 208;
+pub const CIRCULAR_IMPORT: u64 = 209;
+pub const TYPE_MISMATCH: u64 = 210;
+pub const UNKNOWN_MEMBER: u64 = 211;
+pub const INACCESSIBLE_MEMBER: u64 = 212;
+pub const CIRCULAR_INHERITANCE: u64 = 213;
+pub const UNIMPLEMENTED_METHOD: u64 = 214;
+pub const INVALID_OVERRIDE: u64 = 215;
+pub const SOURCE_TOO_LARGE: u64 = 301;
+
+pub const CODES: &[ErrorCode] = &[
+    ErrorCode {
+        code: UNEXPECTED_TOKEN,
+        title: "unexpected token",
+        explanation: "A token was found where nothing that starts with it is allowed.
+
+    var x = 1 )
+
+Remove the token, or check the line before it for a statement that isn't finished.",
+    },
+    ErrorCode {
+        code: UNEXPECTED_EOF,
+        title: "unexpected end of file",
+        explanation: "The source ended in the middle of a statement.
+
+    fn main() {
+        var x =
+
+Finish the statement, an unclosed brace or string earlier in the file is the usual cause.",
+    },
+    ErrorCode {
+        code: UNCLOSED_DELIMITER,
+        title: "unclosed delimiter",
+        explanation: "A brace, bracket or parenthesis was opened but never closed.
+
+    var point = { x: 1, y: 2;
+
+Close it where the body it opens ends.",
+    },
+    ErrorCode {
+        code: INVALID_ESCAPE,
+        title: "invalid escape sequence",
+        explanation: "A string has a `\\` that isn't followed by an escape sequence.
+
+    var path = \"C:\\data\";
+
+The escapes are `\\n`, `\\t`, `\\r`, `\\0`, `\\\\`, `\\\"`, `\\'`, `` \\` `` and `\\u{...}`
+with the code point of a character in hex. Write `\\\\` for a backslash.",
+    },
+    ErrorCode {
+        code: MISSPELLED_KEYWORD,
+        title: "misspelled keyword",
+        explanation:
+            "A statement starts with a name that is one or two letters away from a keyword.
+
+    clas User {
+    }
+
+It is only a notice, the name can be meant. The report suggests the keyword,
+`function` suggests `fn`, the keyword functions are declared with.",
+    },
+    ErrorCode {
+        code: UNEXPECTED_CHARACTER,
+        title: "unexpected character",
+        explanation: "A character that isn't a letter, a digit or a symbol of the language
+was found outside of a string or a comment.
+
+    var mood = 😀;
+
+Names can have letters of any script, eg: `café` or `名前`. Anything else goes in a string.",
+    },
+    ErrorCode {
+        code: INVALID_SYNTAX,
+        title: "invalid syntax",
+        explanation: "The tokens don't form any statement or expression the language has.
+The message of the report names what was expected instead.",
+    },
+    ErrorCode {
+        code: EXPECTED_EXPRESSION,
+        title: "expected an expression",
+        explanation: "A value was expected, eg: after `=`, an operator or `return`.
+
+    var x = ;
+    var y = 1 + ;
+
+Give the value, or remove the operator that expects it.",
+    },
+    ErrorCode {
+        code: MISSING_SEMICOLON,
+        title: "missing semicolon",
+        explanation: "Every declaration, import and expression statement ends with a `;`.
+
+    var x = 1
+    var y = 2;
+
+Add a `;` at the end of the statement.",
+    },
+    ErrorCode {
+        code: MISSING_COMMA,
+        title: "missing comma",
+        explanation: "The items of a list are separated by commas, this covers arguments,
+parameters, array elements, tuples, object properties and import groups.
+
+    add(1 2);
+
+Add a `,` between the items.",
+    },
+    ErrorCode {
+        code: EXPECTED_TYPE,
+        title: "expected a type",
+        explanation: "A type was expected, eg: after the `:` of an annotation.
+
+    var x: = 1;
+    fn f(a:) {}
+
+Give the type, or remove the `:` to leave it to inference.",
+    },
+    ErrorCode {
+        code: EXPECTED_NAME,
+        title: "expected a name",
+        explanation: "A declaration, path or member is missing its name.
+
+    var = 1;
+    class extends Model {}
+
+Names start with a letter or `_`, and can't be a keyword.",
+    },
+    ErrorCode {
+        code: EXPECTED_STATEMENT,
+        title: "expected a statement",
+        explanation: "A statement or a body was expected, eg: after `static` or a function
+signature, or in the body of a class.
+
+    static;
+
+Give the statement or body.",
+    },
+    ErrorCode {
+        code: UNKNOWN_MACRO,
+        title: "unknown macro",
+        explanation: "A macro was invoked that the compiler doesn't know.
+The report lists the macros that exist, check the name for a typo.",
+    },
+    ErrorCode {
+        code: DUPLICATE_DECLARATION,
+        title: "duplicate declaration",
+        explanation: "The same name was declared twice where names have to be unique.
+
+    fn pair<T, T>(a: T, b: T) {}
+    type Id = int;
+    type Id = string;
+
+Rename or remove one of them.",
+    },
+    ErrorCode {
+        code: INVALID_ACCESSOR,
+        title: "invalid accessor",
+        explanation: "An accessor of a property is malformed or has no property to access.
+A getter takes no parameters and a setter takes the value it is given.
+
+    class User {
+        name: string;
+        set name() {}
+    }
+
+Accessors are named after a property the class declares.",
+    },
+    ErrorCode {
+        code: MISPLACED_VARIADIC,
+        title: "misplaced variadic parameter",
+        explanation: "A variadic parameter collects the rest of the arguments of a call,
+so no parameter can follow it.
+
+    fn log(...messages: string, level: int) {}
+
+Move the variadic parameter to the end of the parameters.",
+    },
+    ErrorCode {
+        code: MISPLACED_STATEMENT,
+        title: "misplaced statement",
+        explanation: "A statement was found where it can't be used.
+
+    return 1;
+
+`return` can only be used in a function and `break` or `continue` in a loop.
+`static`, `use` and `namespace` can't be used in a function, a namespace can
+only be declared at the top level.",
+    },
+    ErrorCode {
+        code: TYPE_ARGUMENT_COUNT,
+        title: "wrong number of type arguments",
+        explanation: "A generic function or class was given more or fewer type arguments
+than it has type parameters.
+
+    fn first<T>(items: any): T {}
+    var x = first<int, string>(items);
+
+Give one per parameter, or leave them all out to have them inferred.",
+    },
+    ErrorCode {
+        code: NULLABLE_VALUE,
+        title: "nullable value",
+        explanation: "A value that may be `null` was given to a type that can't be `null`.
+
+    var name: string? = null;
+    var upper: string = name;
+
+Make the type nullable with `?`, or check the value for `null` first:
+
+    if (name != null) {
+        var upper: string = name;
+    }",
+    },
+    ErrorCode {
+        code: INVALID_CONST,
+        title: "invalid constant",
+        explanation:
+            "A `const` was initialized with a value that can't be computed while compiling.
+
+    const NOW = time();
+
+Use a `var`, or a value made of literals and other constants.",
+    },
+    ErrorCode {
+        code: SHAPE_MISMATCH,
+        title: "object doesn't match its shape",
+        explanation: "A property of an object literal has a different type than the shape
+the object was declared with says.
+
+    type Point = { x: int, y: int };
+    var p: Point = { x: 1, y: \"2\" };
+
+Change the value, or the shape.",
+    },
+    ErrorCode {
+        code: UNUSED_VARIABLE,
+        title: "unused variable",
+        explanation: "A variable declared in a function is never used. This is a warning of the
+`unused_variable` lint.
+
+    fn total(items: int[]): int {
+        var count = 0;
+        return 1;
+    }
+
+Remove the variable, prefix its name with `_`, or allow the lint with
+`#[allow(unused_variable)]` on the function.",
+    },
+    ErrorCode {
+        code: AWAIT_OUTSIDE_ASYNC,
+        title: "await outside of an async function",
+        explanation: "`await` was used in a function that isn't declared `async`.
+
+    fn load(): string {
+        return await fetch(\"/\");
+    }
+
+Declare the function with `async fn`. The top level of a script can always `await`.",
+    },
+    ErrorCode {
+        code: UNRESOLVED_NAME,
+        title: "unresolved name",
+        explanation: "A function or class isn't declared in any file that was compiled.
+
+    namespace app\\http;
+    var post = new models\\Post();
+
+A name is looked up in the imports of the file, then in its namespace, then in the
+global namespace. Import the declaration with `use`, or qualify the name with its namespace.",
+    },
+    ErrorCode {
+        code: AMBIGUOUS_NAME,
+        title: "ambiguous name",
+        explanation: "A name resolves to declarations in more than one file.
+
+    // a.surn
+    namespace app;
+    class User {}
+
+    // b.surn
+    namespace app;
+    class User {}
+
+Rename one of them, or move it to a namespace of its own. An import with the same name
+as a declaration of the namespace is ambiguous as well.",
+    },
+    ErrorCode {
+        code: CIRCULAR_IMPORT,
+        title: "circular import",
+        explanation: "Files import each other in a circle, so none of them can be loaded first.
+
+    // a.surn
+    namespace app\\a;
+    use app::b::second;
+
+    // b.surn
+    namespace app\\b;
+    use app::a::first;
+
+The report points at every import of the circle. Move what the files need from
+each other to a file that none of them imports.",
+    },
+    ErrorCode {
+        code: TYPE_MISMATCH,
+        title: "mismatched types",
+        explanation: "A value was given where a different type is declared, or an operator
+was applied to values it can't be applied to.
+
+    fn pad(text: string, width: int): string {}
+    var count: int = 0;
+    count = \"1\";
+    pad(\"a\", \"2\");
+    var half = \"10\" / 2;
+
+Integers are widened to floats, other values have to be converted first.",
+    },
+    ErrorCode {
+        code: UNKNOWN_MEMBER,
+        title: "unknown member",
+        explanation: "A property or method is accessed on a class that doesn't declare it,
+and neither do the classes it extends.
+
+    class User {
+        pub name: string;
+    }
+    fn main(user: User) {
+        print(user.nmae);
+    }
+
+Check the spelling of the member, or declare it in the class.",
+    },
+    ErrorCode {
+        code: INACCESSIBLE_MEMBER,
+        title: "member can't be accessed",
+        explanation: "A member is accessed where it isn't allowed to be. A `priv` member can
+only be accessed by its class and a `prot` member by its class and the classes that
+extend it. Static members are accessed with `::` and the others on an instance with `.`.
+
+    class User {
+        priv fn secret() {}
+        pub static fn create(): User {}
+    }
+    fn main(user: User) {
+        user.secret();
+        user.create();
+    }
+
+Make the member `pub`, or access it the way it is declared.",
+    },
+    ErrorCode {
+        code: CIRCULAR_INHERITANCE,
+        title: "circular inheritance",
+        explanation: "A class extends or implements itself, through the classes it names.
+
+    class Model extends Record {}
+    class Record extends Model {}
+
+Remove one of the classes from the circle.",
+    },
+    ErrorCode {
+        code: UNIMPLEMENTED_METHOD,
+        title: "method not implemented",
+        explanation: "A class implements a class with a method that neither it nor the
+classes it extends declare.
+
+    class Jsonable {
+        pub fn json(): string {}
+    }
+    class User implements Jsonable {}
+
+Declare the method in the class.",
+    },
+    ErrorCode {
+        code: INVALID_OVERRIDE,
+        title: "invalid override",
+        explanation: "A method overrides a method of a class it extends or implements, but
+takes different parameters or returns something else. A parameter can accept more than
+the one it overrides and the result can be narrower, but the parameters have to be
+the same in number.
+
+    class Model {
+        pub fn find(id: int): Model {}
+    }
+    class User extends Model {
+        pub fn find(id: string): User {}
+    }
+
+A method marked `#[Override]` has to override one, this catches a method that was
+renamed in the class it extends.",
+    },
+    ErrorCode {
+        code: SOURCE_TOO_LARGE,
+        title: "source too large",
+        explanation: "The source is larger than `max_file_size` or has more tokens than
+`max_tokens`. Parsing time grows faster than the size of the source, so the limits
+refuse sources that would take minutes, which are most likely generated.
+
+Split the file, or raise the limit in the compiler options.",
+    },
+];
+
+/// The documentation of a code.
+pub fn lookup(code: u64) -> Option<&'static ErrorCode> {
+    CODES.iter().find(|c| c.code == code)
+}
+
+/// The code as it is printed, eg: `E0102`.
+pub fn format_code(code: u64) -> String {
+    format!("E{:04}", code)
+}
+
+/// Reads a printed code, the leading `E` can be left out.
+pub fn parse_code(code: &str) -> Option<u64> {
+    let digits = code.strip_prefix(['E', 'e']).unwrap_or(code);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
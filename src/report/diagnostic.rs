@@ -0,0 +1,306 @@
+use std::{fmt, ops::Range, sync::Arc};
+
+use crate::{
+    compiler::{
+        ast::types::DuplicateType,
+        eval::ConstError,
+        infer::{
+            awaits::AwaitError, checker::TypeError, generics::ArityError, nullability::NullError,
+            shapes::ShapeError,
+        },
+        resolve::{
+            inheritance::{InheritanceError, InheritanceErrorKind},
+            members::{MemberError, MemberErrorKind},
+            NameError, NameErrorKind,
+        },
+    },
+    transpiler::langs::LossyLowering,
+    util::source::SourceBuffer,
+};
+
+use super::{codes, Report, ReportKind, Suggestion};
+
+/// A span of the source a diagnostic points at, with what is wrong there.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Label {
+    pub span: Range<usize>,
+    pub message: String,
+}
+
+/// A problem found in a source, by any stage of the compiler.
+/// A diagnostic is plain data, it is rendered by turning it into a `Report`
+/// with the source it is about.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::parser::context::{Context, SourceOrigin};
+/// # use surn::report::{codes, diagnostic::Diagnostic};
+/// # let context = Context::new(
+/// #     SourceOrigin::new_virtual("main.surn".to_string(), "var x: string = null;".to_string()),
+/// #     1,
+/// # );
+/// let diagnostic = Diagnostic::error("`x` is not nullable.".to_string())
+///     .with_code(codes::NULLABLE_VALUE)
+///     .with_span(4..5, "Declared here.".to_string())
+///     .with_note("Use `string?` to allow `null`.".to_string());
+/// diagnostic.into_report("main.surn".to_string(), context.buffer.clone()).print();
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    /// See `codes`, 0 is a diagnostic without a code.
+    pub code: u64,
+    pub severity: ReportKind,
+    pub message: String,
+    /// The lint that reports the diagnostic, its level decides whether it is reported, see `Lints::apply`.
+    pub lint: Option<&'static str>,
+    pub spans: Vec<Label>,
+    pub suggestions: Vec<Suggestion>,
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: ReportKind, message: String) -> Self {
+        Self {
+            code: 0,
+            severity,
+            message,
+            lint: None,
+            spans: Vec::new(),
+            suggestions: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn error(message: String) -> Self {
+        Self::new(ReportKind::Error, message)
+    }
+
+    pub fn warning(message: String) -> Self {
+        Self::new(ReportKind::Warning, message)
+    }
+
+    pub fn notice(message: String) -> Self {
+        Self::new(ReportKind::Notice, message)
+    }
+
+    pub fn with_code(mut self, code: u64) -> Self {
+        self.code = code;
+        self
+    }
+
+    pub fn with_lint(mut self, lint: &'static str) -> Self {
+        self.lint = Some(lint);
+        self
+    }
+
+    pub fn with_span(mut self, span: Range<usize>, message: String) -> Self {
+        self.spans.push(Label { span, message });
+        self
+    }
+
+    pub fn with_suggestion(mut self, suggestion: Suggestion) -> Self {
+        self.suggestions.push(suggestion);
+        self
+    }
+
+    pub fn with_note(mut self, note: String) -> Self {
+        self.notes.push(note);
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == ReportKind::Error
+    }
+
+    /// The report of the diagnostic, every span is a snippet of it.
+    pub fn into_report(self, name: String, source: impl Into<Arc<SourceBuffer>>) -> Report {
+        let mut report = Report::new()
+            .set_code(self.code)
+            .set_kind(self.severity)
+            .set_name(name)
+            .set_source(source)
+            .set_message(self.message);
+        for label in self.spans {
+            report = report.make_snippet(label.span, label.message, None);
+        }
+        for note in self.notes {
+            report = report.add_note(note);
+        }
+        for suggestion in self.suggestions {
+            report = report.add_suggestion(suggestion);
+        }
+        report
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.code {
+            0 => write!(f, "{}! {}", self.severity, self.message)?,
+            code => write!(
+                f,
+                "{}[{}]! {}",
+                self.severity,
+                codes::format_code(code),
+                self.message
+            )?,
+        }
+        for label in self.spans.iter() {
+            write!(
+                f,
+                "\n -> {}..{}: {}",
+                label.span.start, label.span.end, label.message
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl From<Report> for Diagnostic {
+    fn from(report: Report) -> Self {
+        Diagnostic {
+            code: report.code,
+            severity: report.kind,
+            message: report.message.clone(),
+            lint: None,
+            spans: report
+                .snippets
+                .iter()
+                .map(|snippet| Label {
+                    span: snippet.range(),
+                    message: snippet.message.clone(),
+                })
+                .collect(),
+            suggestions: report.suggestions().into_iter().cloned().collect(),
+            notes: report.notes().into_iter().cloned().collect(),
+        }
+    }
+}
+
+impl From<ArityError> for Diagnostic {
+    fn from(error: ArityError) -> Self {
+        Diagnostic::error(error.message())
+            .with_code(codes::TYPE_ARGUMENT_COUNT)
+            .with_span(error.span.clone(), error.message())
+    }
+}
+
+impl From<DuplicateType> for Diagnostic {
+    fn from(error: DuplicateType) -> Self {
+        Diagnostic::error(error.message())
+            .with_code(codes::DUPLICATE_DECLARATION)
+            .with_span(error.second, "Defined again here.".to_string())
+            .with_span(error.first, "First defined here.".to_string())
+    }
+}
+
+impl From<NullError> for Diagnostic {
+    fn from(error: NullError) -> Self {
+        Diagnostic::error(error.message.clone())
+            .with_code(codes::NULLABLE_VALUE)
+            .with_span(error.span, error.message)
+    }
+}
+
+impl From<ConstError> for Diagnostic {
+    fn from(error: ConstError) -> Self {
+        Diagnostic::error(error.message.clone())
+            .with_code(codes::INVALID_CONST)
+            .with_span(error.span, error.message)
+    }
+}
+
+impl From<AwaitError> for Diagnostic {
+    fn from(error: AwaitError) -> Self {
+        Diagnostic::error(error.message())
+            .with_code(codes::AWAIT_OUTSIDE_ASYNC)
+            .with_span(
+                error.span.clone(),
+                "This function is not async.".to_string(),
+            )
+            .with_note("Declare it with `async fn`.".to_string())
+    }
+}
+
+impl From<NameError> for Diagnostic {
+    fn from(error: NameError) -> Self {
+        let (code, label) = match &error.kind {
+            NameErrorKind::Unresolved => (codes::UNRESOLVED_NAME, "Not found."),
+            NameErrorKind::Ambiguous(_) => (codes::AMBIGUOUS_NAME, "Declared more than once."),
+        };
+        let diagnostic = Diagnostic::error(error.message()).with_code(code);
+        match error.span {
+            Some(span) => diagnostic.with_span(span, label.to_string()),
+            None => diagnostic,
+        }
+    }
+}
+
+impl From<MemberError> for Diagnostic {
+    fn from(error: MemberError) -> Self {
+        let (code, label) = match &error.kind {
+            MemberErrorKind::Unknown => (codes::UNKNOWN_MEMBER, "Not found."),
+            MemberErrorKind::Inaccessible(..) => {
+                (codes::INACCESSIBLE_MEMBER, "Not accessible here.")
+            }
+            MemberErrorKind::NotStatic | MemberErrorKind::Static => {
+                (codes::INACCESSIBLE_MEMBER, "Accessed the wrong way.")
+            }
+        };
+        Diagnostic::error(error.message())
+            .with_code(code)
+            .with_span(error.span.clone(), label.to_string())
+    }
+}
+
+impl From<InheritanceError> for Diagnostic {
+    fn from(error: InheritanceError) -> Self {
+        let code = match error.kind {
+            InheritanceErrorKind::Cycle => codes::CIRCULAR_INHERITANCE,
+            InheritanceErrorKind::Unimplemented => codes::UNIMPLEMENTED_METHOD,
+            InheritanceErrorKind::Incompatible | InheritanceErrorKind::NothingOverridden => {
+                codes::INVALID_OVERRIDE
+            }
+        };
+        Diagnostic::error(error.message.clone())
+            .with_code(code)
+            .with_span(error.span, error.message)
+    }
+}
+
+impl From<ShapeError> for Diagnostic {
+    fn from(error: ShapeError) -> Self {
+        Diagnostic::error(error.message.clone())
+            .with_code(codes::SHAPE_MISMATCH)
+            .with_span(error.span, error.message)
+    }
+}
+
+/// The value and the declaration it doesn't fit are shown side by side.
+impl From<TypeError> for Diagnostic {
+    fn from(error: TypeError) -> Self {
+        let diagnostic = Diagnostic::error(error.message.clone())
+            .with_code(codes::TYPE_MISMATCH)
+            .with_span(error.span, error.message);
+        match error.declaration {
+            Some(span) => diagnostic.with_span(span, "Declared here.".to_string()),
+            None => diagnostic,
+        }
+    }
+}
+
+/// A lowering is a warning, the level of its lint decides whether it is reported at all.
+impl From<LossyLowering> for Diagnostic {
+    fn from(lowering: LossyLowering) -> Self {
+        Diagnostic::warning(lowering.message)
+            .with_lint(lowering.lint)
+            .with_span(
+                lowering.span,
+                format!("Reported by the `{}` lint.", lowering.lint),
+            )
+            .with_note(format!(
+                "Allow the `{}` lint to stop reporting this.",
+                lowering.lint
+            ))
+    }
+}
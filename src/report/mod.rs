@@ -1,7 +1,15 @@
-use std::{fmt, ops::Range};
+use std::{
+    env, fmt,
+    io::{self, IsTerminal},
+    ops::Range,
+    sync::Arc,
+};
 
 use crate::util::source::SourceBuffer;
 
+pub mod codes;
+pub mod diagnostic;
+
 pub(crate) fn repeat_char(c: char, n: usize) -> String {
     let mut s = String::new();
     for _ in 0..n {
@@ -10,7 +18,8 @@ pub(crate) fn repeat_char(c: char, n: usize) -> String {
     s
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// The kinds of report, from the most severe to the least.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(u8)]
 pub enum ReportKind {
     Error,
@@ -18,6 +27,18 @@ pub enum ReportKind {
     Notice,
 }
 
+impl ReportKind {
+    /// The kind of a name, eg: `warning`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "error" => Some(ReportKind::Error),
+            "warning" => Some(ReportKind::Warning),
+            "notice" => Some(ReportKind::Notice),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for ReportKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let s = match self {
@@ -34,28 +55,106 @@ pub struct Report {
     pub code: u64,
     pub message: String,
     pub name: String,
-    pub source: SourceBuffer,
+    /// Shared with the context the report is about, see `Context::buffer`.
+    pub source: Arc<SourceBuffer>,
     pub snippets: Vec<Snippet>,
+    /// Printed after the snippets in order, see `add_note` and `add_suggestion`.
+    pub help: Vec<Help>,
     pub kind: ReportKind,
 }
 
+/// A line printed after the snippets of a report.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Help {
+    /// Eg: why the code is invalid.
+    Note(String),
+    /// A fix that can be applied without asking, see `SourceBuffer::apply`.
+    Suggestion(Suggestion),
+}
+
+/// A machine-applicable fix for a report: the span is replaced with the replacement.
+/// An empty span inserts the replacement.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Suggestion {
+    pub span: Range<usize>,
+    pub replacement: String,
+    /// What the fix does, eg: "Add a `;` to end the statement."
+    pub message: String,
+}
+
+impl Suggestion {
+    pub fn new(span: Range<usize>, replacement: String, message: String) -> Self {
+        Self {
+            span,
+            replacement,
+            message,
+        }
+    }
+
+    pub fn insert(offset: usize, text: &str, message: String) -> Self {
+        Self::new(offset..offset, text.to_string(), message)
+    }
+}
+
 impl Report {
     pub fn new() -> Self {
         Report {
             name: String::from("unknown-name.surn"),
             code: 0,
             message: String::new(),
-            source: SourceBuffer::empty(),
+            source: Arc::new(SourceBuffer::empty()),
             snippets: Vec::new(),
+            help: Vec::new(),
             kind: ReportKind::Error,
         }
     }
 
+    /// The explanation of a code, eg: `E0102`, for `surnc --explain`.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::report::Report;
+    /// println!("{}", Report::explain("E0102").unwrap());
+    /// ```
+    pub fn explain(code: &str) -> Option<String> {
+        let documented = codes::lookup(codes::parse_code(code)?)?;
+        Some(format!(
+            "{}: {}\n\n{}\n",
+            codes::format_code(documented.code),
+            documented.title,
+            documented.explanation
+        ))
+    }
+
+    /// The line and column the first snippet of the report starts at, both counted from 1.
+    /// `None` when no snippet is of the source of the report.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::compiler::{parser::Parser, CompilerOptions};
+    /// // var x = 1
+    /// // var y = 2;
+    /// # let report = Parser::new(CompilerOptions::default())
+    /// #     .try_parse_script("main.surn".to_string(), "var x = 1\nvar y = 2;".to_string())
+    /// #     .unwrap_err();
+    /// report.location(); // Some((1, 10))
+    /// ```
+    pub fn location(&self) -> Option<(usize, usize)> {
+        let snippet = self.snippets.iter().find(|s| s.name.is_none())?;
+        self.source.line_col(snippet.range.start)
+    }
+
+    /// The code of the report, see `codes`. 0 is a report without a code.
     pub fn set_code(mut self, code: u64) -> Self {
         self.code = code;
         self
     }
 
+    pub fn set_kind(mut self, kind: ReportKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
     pub fn set_name(mut self, name: String) -> Self {
         self.name = name;
         self
@@ -66,8 +165,26 @@ impl Report {
         self
     }
 
-    pub fn set_source(mut self, source: SourceBuffer) -> Self {
-        self.source = source;
+    pub fn set_source(mut self, source: impl Into<Arc<SourceBuffer>>) -> Self {
+        self.source = source.into();
+        self
+    }
+
+    /// Prints the tabs of every snippet as `width` columns, eg: the width the
+    /// editor of the source shows them with.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::report::Report;
+    /// # let report = Report::new().set_message("`x` is not defined.".to_string());
+    /// report.set_tab_width(2).print();
+    /// ```
+    pub fn set_tab_width(mut self, width: usize) -> Self {
+        self.snippets = self
+            .snippets
+            .into_iter()
+            .map(|snippet| snippet.set_tab_width(width))
+            .collect();
         self
     }
 
@@ -92,54 +209,114 @@ impl Report {
         self
     }
 
+    /// Adds a snippet of the source of the report,
+    /// a snippet named after another source keeps its own, see `Snippet::set_name`.
     pub fn add_snippet(mut self, snippet: Snippet) -> Self {
-        self.snippets.push(snippet.set_source(self.source.clone()));
+        match snippet.name {
+            Some(_) => self.snippets.push(snippet),
+            None => self.snippets.push(snippet.set_source(self.source.clone())),
+        }
+        self
+    }
+
+    /// Prints the report, errors to stderr and everything else to stdout.
+    /// Colors are used when the stream is a terminal, see `ReportTheme::detect`.
+    pub fn add_note(mut self, note: String) -> Self {
+        self.help.push(Help::Note(note));
+        self
+    }
+
+    pub fn add_suggestion(mut self, suggestion: Suggestion) -> Self {
+        self.help.push(Help::Suggestion(suggestion));
         self
     }
 
+    pub fn notes(&self) -> Vec<&String> {
+        self.help
+            .iter()
+            .filter_map(|help| match help {
+                Help::Note(note) => Some(note),
+                Help::Suggestion(_) => None,
+            })
+            .collect()
+    }
+
+    pub fn suggestions(&self) -> Vec<&Suggestion> {
+        self.help
+            .iter()
+            .filter_map(|help| match help {
+                Help::Suggestion(suggestion) => Some(suggestion),
+                Help::Note(_) => None,
+            })
+            .collect()
+    }
+
     pub fn print(&self) {
-        let main_error = format!("{}! {}", self.kind, self.message);
-        let header = format!(
-            "{} [{}]",
-            repeat_char(Charset::defaults().dash, self.get_width() + 2),
-            self.name
-        );
-        let spacer = format!(
+        if self.kind == ReportKind::Error {
+            eprint!("{}", self.render(&ReportTheme::detect(&io::stderr())));
+        } else {
+            print!("{}", self.render(&ReportTheme::detect(&io::stdout())));
+        }
+    }
+
+    /// The printed report, styled with the theme.
+    pub fn render(&self, theme: &ReportTheme) -> String {
+        let style = theme.kind(self.kind);
+        let kind = match self.code {
+            0 => format!("{}!", self.kind),
+            code => format!("{}[{}]!", self.kind, codes::format_code(code)),
+        };
+        let main_error = format!("{} {}", style.bold().paint(kind), self.message);
+        let header = |name: &str| {
+            format!(
+                "{} [{}]",
+                theme
+                    .gutter
+                    .paint(repeat_char(Charset::defaults().dash, self.get_width() + 2)),
+                name
+            )
+        };
+        let spacer = theme.gutter.paint(format!(
             "{} |",
             repeat_char(Charset::defaults().space, self.get_width())
-        );
-        let spacer2 = format!(
-            "\n{} |\n",
-            repeat_char(Charset::defaults().space, self.get_width())
-        );
+        ));
+        let spacer2 = format!("\n{}\n", spacer);
         let snippets = self
             .snippets
             .iter()
-            .map(|s| s.get_print())
+            .map(|s| match &s.name {
+                Some(name) => format!(
+                    "{}\n{}\n{}",
+                    header(name),
+                    spacer,
+                    s.render(theme, self.kind)
+                ),
+                None => s.render(theme, self.kind),
+            })
             .collect::<Vec<String>>();
+        let help = self
+            .help
+            .iter()
+            .map(|help| match help {
+                Help::Note(note) => (theme.gutter.paint("note:"), note),
+                Help::Suggestion(s) => (theme.notice.bold().paint("help:"), &s.message),
+            })
+            .map(|(label, text)| format!("{} {} {}\n", spacer, label, text))
+            .collect::<String>();
         // todo: Add error snippets, see error.debug for an example of an error snippet.
         // todo: An error snippet essentially expands the error into possible solutions.
-        if self.kind == ReportKind::Error {
-            eprint!(
-                "{}\n{}\n{}\n{}\n",
-                main_error,
-                header,
-                spacer,
-                snippets.join(&spacer2)
-            );
-        } else {
-            print!(
-                "{}\n{}\n{}\n{}\n",
-                main_error,
-                header,
-                spacer,
-                snippets.join(&spacer2)
-            );
-        }
+        format!(
+            "{}\n{}\n{}\n{}\n{}",
+            main_error,
+            header(&self.name),
+            spacer,
+            snippets.join(&spacer2),
+            help
+        )
     }
 
     fn get_width(&self) -> usize {
-        let mut width = format!("{}", self.source.get_lines().len()).len();
+        let mut width = format!("{}", self.source.line_count()).len();
         if width < 3 {
             width = 3;
         }
@@ -157,19 +334,25 @@ impl Report {
 pub struct Snippet {
     pub(crate) message: String,
     pub(crate) inline: String,
-    source: SourceBuffer,
+    source: Arc<SourceBuffer>,
+    /// The name of the source, when it isn't the source of the report.
+    name: Option<String>,
     multiline: bool,
     range: Range<usize>,
+    /// How many columns a tab is printed as, the width of the source when `None`.
+    tab_width: Option<usize>,
 }
 
 impl Snippet {
-    pub fn new(source: SourceBuffer, message: String, range: Range<usize>) -> Self {
+    pub fn new(source: impl Into<Arc<SourceBuffer>>, message: String, range: Range<usize>) -> Self {
         Snippet {
             message: message,
             inline: String::new(),
-            source,
+            source: source.into(),
+            name: None,
             range: range,
             multiline: false,
+            tab_width: None,
         }
     }
 
@@ -177,9 +360,11 @@ impl Snippet {
         Snippet {
             message: String::new(),
             inline: String::new(),
-            source: SourceBuffer::empty(),
+            source: Arc::new(SourceBuffer::empty()),
+            name: None,
             range: (0 as usize)..(1 as usize),
             multiline: false,
+            tab_width: None,
         }
     }
 
@@ -193,8 +378,21 @@ impl Snippet {
         self
     }
 
-    pub fn set_source(mut self, source: SourceBuffer) -> Self {
-        self.source = source;
+    pub fn set_source(mut self, source: impl Into<Arc<SourceBuffer>>) -> Self {
+        self.source = source.into();
+        self
+    }
+
+    /// Names the source of the snippet, for a snippet of another source than its report's.
+    pub fn set_name(mut self, name: String) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// Prints tabs as `width` columns instead of the tab width of the source,
+    /// see `SourceBuffer::with_tab_width`.
+    pub fn set_tab_width(mut self, width: usize) -> Self {
+        self.tab_width = Some(width);
         self
     }
 
@@ -208,6 +406,10 @@ impl Snippet {
         self
     }
 
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
     /// Gets the line of code that is being reported on.
     /// If this is multi-line, then the line will be the first line of the snippet.
     pub fn get_line(&self) -> usize {
@@ -215,49 +417,71 @@ impl Snippet {
     }
 
     pub fn get_print(&self) -> String {
+        self.render(&ReportTheme::plain(), ReportKind::Error)
+    }
+
+    /// The printed snippet, the underline is styled for the kind of its report.
+    pub fn render(&self, theme: &ReportTheme, kind: ReportKind) -> String {
         // generating the padding
-        let source_code = format!(
-            "{}",
-            self.source
-                .get_line_at(self.range.start)
-                .expect(format!("Could not find line for index at: {}", self.range.start).as_str())
-                .trim()
-                .source()
-        );
+        let line = self
+            .source
+            .get_line_at(self.range.start)
+            .unwrap_or_else(|| panic!("Could not find line for index at: {}", self.range.start));
+        let line = match self.tab_width {
+            Some(width) => line.with_tab_width(width),
+            None => line,
+        };
+        let source_code = line.printed();
+        // the part of the range on its first line is underlined as wide as it is printed,
+        // the rest with a column per char.
+        let region = self.source.region_of(self.range.clone());
+        let start = region.start.column - 1;
+        let end = match region.end.line == region.start.line {
+            true => (region.end.column - 1).clamp(start, line.len().max(start)),
+            false => line.len().max(start),
+        };
+        let underlined =
+            line.width(start..end) + self.range.clone().count().saturating_sub(end - start);
         let inlined = format!("{}", self.inline);
-        let mut longest = format!("{}", self.source.get_lines().len()).len();
+        let mut longest = format!("{}", self.source.line_count()).len();
         if longest < 3 {
             longest = 3;
         }
         let line_num =
             SizedPadding::new(format!("{}", self.get_line()), Charset::defaults(), longest);
+        let space = theme.gutter.paint(format!(
+            "{} |",
+            repeat_char(Charset::defaults().space, longest)
+        ));
         let underline = format!(
-            "{} |{}",
-            repeat_char(Charset::defaults().space, longest),
-            format!(
+            "{}{}",
+            space,
+            theme.kind(kind).paint(format!(
                 "{}{} {}",
                 repeat_char(
                     Charset::defaults().space,
-                    self.source
-                        .get_line_at(self.range.start)
-                        .unwrap()
-                        .spaces_until(self.range.clone())
+                    line.spaces_until(self.range.clone())
                 ),
-                repeat_char(Charset::defaults().underline, self.range.clone().count()),
+                repeat_char(Charset::defaults().underline, underlined),
                 inlined
-            )
+            ))
         );
 
-        let space = format!("{} |", repeat_char(Charset::defaults().space, longest));
-
         let message = format!(
-            "{} | ---> {}",
-            SizedPadding::new("Err".into(), Charset::defaults(), longest),
+            "{} {}",
+            theme.gutter.paint(format!(
+                "{} | --->",
+                SizedPadding::new("Err".into(), Charset::defaults(), longest)
+            )),
             self.message
         );
         format!(
-            "{} | {}\n{}\n{}\n{}",
-            line_num, source_code, underline, space, message
+            "{} {}\n{}\n{}\n{}",
+            theme.gutter.paint(format!("{} |", line_num)),
+            source_code,
+            underline,
+            space,
+            message
         )
     }
 }
@@ -344,3 +568,113 @@ impl Charset {
         }
     }
 }
+
+/// An ANSI style, the plain style leaves text as it is.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Style {
+    /// The foreground color, eg: `31` for red.
+    pub color: Option<u8>,
+    pub bold: bool,
+    pub dim: bool,
+}
+
+impl Style {
+    pub const PLAIN: Style = Style::color(None);
+    pub const RED: Style = Style::color(Some(31));
+    pub const YELLOW: Style = Style::color(Some(33));
+    pub const CYAN: Style = Style::color(Some(36));
+    pub const DIM: Style = Style {
+        color: None,
+        bold: false,
+        dim: true,
+    };
+
+    const fn color(color: Option<u8>) -> Self {
+        Style {
+            color,
+            bold: false,
+            dim: false,
+        }
+    }
+
+    /// The same style in bold, the plain style stays plain.
+    pub fn bold(mut self) -> Self {
+        self.bold = self != Style::PLAIN;
+        self
+    }
+
+    pub fn paint<T: fmt::Display>(&self, text: T) -> String {
+        let mut codes = Vec::new();
+        if self.bold {
+            codes.push("1".to_string());
+        }
+        if self.dim {
+            codes.push("2".to_string());
+        }
+        if let Some(color) = self.color {
+            codes.push(color.to_string());
+        }
+        if codes.is_empty() {
+            return text.to_string();
+        }
+        format!("\x1b[{}m{}\x1b[0m", codes.join(";"), text)
+    }
+}
+
+/// The styles a report is printed with, one per kind of report and one for the gutter.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::report::{Report, ReportTheme};
+/// # let report = Report::new().set_message("`x` is not defined.".to_string());
+/// let theme = ReportTheme::detect(&std::io::stderr());
+/// eprint!("{}", report.render(&theme));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ReportTheme {
+    pub error: Style,
+    pub warning: Style,
+    pub notice: Style,
+    /// The line numbers and the `|` next to the source.
+    pub gutter: Style,
+}
+
+impl ReportTheme {
+    /// Red errors, yellow warnings, cyan notices and a dimmed gutter.
+    pub fn colored() -> Self {
+        ReportTheme {
+            error: Style::RED,
+            warning: Style::YELLOW,
+            notice: Style::CYAN,
+            gutter: Style::DIM,
+        }
+    }
+
+    pub fn plain() -> Self {
+        ReportTheme {
+            error: Style::PLAIN,
+            warning: Style::PLAIN,
+            notice: Style::PLAIN,
+            gutter: Style::PLAIN,
+        }
+    }
+
+    /// The colored theme when the stream is a terminal and `NO_COLOR` isn't set,
+    /// the plain theme otherwise.
+    pub fn detect<S: IsTerminal>(stream: &S) -> Self {
+        let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        if no_color || !stream.is_terminal() {
+            Self::plain()
+        } else {
+            Self::colored()
+        }
+    }
+
+    pub fn kind(&self, kind: ReportKind) -> Style {
+        match kind {
+            ReportKind::Error => self.error,
+            ReportKind::Warning => self.warning,
+            ReportKind::Notice => self.notice,
+        }
+    }
+}
@@ -0,0 +1,404 @@
+use std::{
+    fmt,
+    panic::{self, AssertUnwindSafe},
+};
+
+use crate::{
+    compiler::{
+        ast::types::TypeDefinition,
+        ast::{
+            ops::{AnyOperation, BinOp},
+            types::{
+                BuiltInType, ObjectShape, RuntimeType, ShapeProperty, TypeKind, TypeReference,
+            },
+            Array, AstBody, Call, Catch, Class, CompilerMacro, Destructure, Enum, Expression,
+            Function, FunctionInput, If, Literal, MemberListNode, MemberLookup, MethodCall,
+            Namespace, NewCall, Node, NodeKind, Object, ObjectProperty, Operation, Path, Purity,
+            Return, Statement, Static, Try, TypeTest, Variable, Visibility,
+        },
+        lexer::{
+            pos::Position,
+            token::{Token, TokenType},
+        },
+        CompilerOptions,
+    },
+    transpiler::{format::FormatOptions, langs::Generator},
+};
+
+/// How a backend generates a variant of the AST.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Coverage {
+    /// The variant is generated.
+    Generated,
+    /// The variant is generated, but loses some of its meaning. Holds what the backend does instead.
+    Lowered(String),
+    /// Nothing is generated for the variant.
+    Dropped,
+    /// The backend panics on the variant. Holds the panic message.
+    Panics(String),
+}
+
+impl fmt::Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Coverage::Generated => write!(f, "generated"),
+            Coverage::Lowered(message) => write!(f, "lowered, {}", message),
+            Coverage::Dropped => write!(f, "dropped"),
+            Coverage::Panics(message) => write!(f, "panics, {}", message),
+        }
+    }
+}
+
+/// The coverage of a single variant, eg: `Expression::Await`.
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    /// `Expression`, `Statement` or `TypeKind`.
+    pub category: &'static str,
+    pub variant: &'static str,
+    pub coverage: Coverage,
+}
+
+/// What a backend does with every variant of the AST.
+#[derive(Debug, Clone)]
+pub struct Audit {
+    pub entries: Vec<AuditEntry>,
+}
+
+impl Audit {
+    /// The variants the backend panics on.
+    pub fn panics(&self) -> Vec<&AuditEntry> {
+        self.entries
+            .iter()
+            .filter(|e| matches!(e.coverage, Coverage::Panics(_)))
+            .collect()
+    }
+
+    /// Lists the coverage of every variant by category.
+    ///
+    /// **Example:**
+    /// ```text
+    /// Expression:
+    ///     Await: lowered, `await` is dropped, php runs the script synchronously.
+    ///     Call: generated
+    /// ...
+    /// 32 of 35 variants are generated, 1 lowered, 2 dropped and 0 panic.
+    /// ```
+    pub fn report(&self) -> String {
+        let mut report = String::new();
+        let mut category = "";
+        for entry in self.entries.iter() {
+            if entry.category != category {
+                category = entry.category;
+                report.push_str(&format!("{}:\n", category));
+            }
+            report.push_str(&format!("    {}: {}\n", entry.variant, entry.coverage));
+        }
+        let count =
+            |f: fn(&Coverage) -> bool| self.entries.iter().filter(|e| f(&e.coverage)).count();
+        report.push_str(&format!(
+            "{} of {} variants are generated, {} lowered, {} dropped and {} panic.",
+            count(|c| *c == Coverage::Generated),
+            self.entries.len(),
+            count(|c| matches!(c, Coverage::Lowered(_))),
+            count(|c| *c == Coverage::Dropped),
+            self.panics().len()
+        ));
+        report
+    }
+}
+
+/// Generates a sample of every `Expression`, `Statement` and `TypeKind` variant
+/// with the backend and reports what it does with each.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::transpiler::{audit::audit, Transpiler};
+/// # let mut transpiler = Transpiler::new();
+/// # transpiler.register_defaults();
+/// let php = transpiler.get("php").unwrap();
+/// println!("{}", audit(php.generator.as_ref()).report());
+/// ```
+pub fn audit(generator: &dyn Generator) -> Audit {
+    let mut entries: Vec<AuditEntry> = Vec::new();
+    for expr in expression_samples() {
+        let variant = expression_variant(&expr);
+        let body = body_of(vec![NodeKind::Expression(expr)]);
+        entries.push(AuditEntry {
+            category: "Expression",
+            variant,
+            coverage: coverage(generator, body, None),
+        });
+    }
+    for stmt in statement_samples() {
+        let variant = statement_variant(&stmt);
+        let body = body_of(vec![NodeKind::Statement(stmt)]);
+        entries.push(AuditEntry {
+            category: "Statement",
+            variant,
+            coverage: coverage(generator, body, None),
+        });
+    }
+
+    // a type is dropped when the function it is given to is generated as if it had none.
+    let untyped = generate(generator, body_of(vec![typed_function(None)])).ok();
+    for ty in type_samples() {
+        let variant = type_variant(&ty);
+        let body = body_of(vec![typed_function(Some(ty))]);
+        entries.push(AuditEntry {
+            category: "TypeKind",
+            variant,
+            coverage: coverage(generator, body, untyped.as_deref()),
+        });
+    }
+    Audit { entries }
+}
+
+fn coverage(generator: &dyn Generator, body: AstBody, dropped_as: Option<&str>) -> Coverage {
+    let lowerings = generator.lossy_lowerings(&body);
+    match generate(generator, body) {
+        Err(message) => Coverage::Panics(message),
+        Ok(code) if code.trim().is_empty() || Some(code.as_str()) == dropped_as => {
+            Coverage::Dropped
+        }
+        Ok(_) => match lowerings.into_iter().next() {
+            Some(lowering) => Coverage::Lowered(lowering.message),
+            None => Coverage::Generated,
+        },
+    }
+}
+
+fn generate(generator: &dyn Generator, body: AstBody) -> Result<String, String> {
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        generator.generate_to_string(body, CompilerOptions::default(), &FormatOptions::default())
+    }))
+    .map_err(|error| {
+        error
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| error.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "without a message".to_string())
+    })
+}
+
+fn body_of(kinds: Vec<NodeKind>) -> AstBody {
+    let mut body = AstBody::new();
+    for kind in kinds {
+        body.push_node(Node::new(kind, 0..0, 0..0));
+    }
+    body
+}
+
+fn int(value: &str) -> Expression {
+    Expression::Literal(Literal::new(
+        value.to_string(),
+        Some(TypeKind::BuiltIn(BuiltInType::Int)),
+    ))
+}
+
+fn name(value: &str) -> Expression {
+    Expression::Literal(Literal::new(value.to_string(), None))
+}
+
+fn function(name: &str, inputs: Vec<FunctionInput>, body: Vec<Expression>) -> Function {
+    Function {
+        name: Some(name.to_string()),
+        generics: Vec::new(),
+        inputs,
+        body: Box::new(Statement::Block(body)),
+        outputs: None,
+        visibility: Visibility::Public,
+        node_id: 0,
+        span: 0..0,
+        body_span: 0..0,
+        purity: Purity::Unknown,
+        is_async: false,
+        attributes: Vec::new(),
+    }
+}
+
+fn typed_function(ty: Option<TypeKind>) -> NodeKind {
+    let input = FunctionInput::new("x".to_string(), ty);
+    NodeKind::Statement(Statement::Function(function("f", vec![input], Vec::new())))
+}
+
+/// The name of an `Expression` variant, every variant needs a sample in `expression_samples`.
+fn expression_variant(expr: &Expression) -> &'static str {
+    match expr {
+        Expression::Await(_) => "Await",
+        Expression::Spread(_) => "Spread",
+        Expression::Call(_) => "Call",
+        Expression::MethodCall(_) => "MethodCall",
+        Expression::New(_) => "New",
+        Expression::Array(_) => "Array",
+        Expression::Tuple(_) => "Tuple",
+        Expression::Object(_) => "Object",
+        Expression::Operation(_) => "Operation",
+        Expression::Statement(_) => "Statement",
+        Expression::Member(_) => "Member",
+        Expression::Literal(_) => "Literal",
+        Expression::Is(_) => "Is",
+        Expression::EndOfLine => "EndOfLine",
+    }
+}
+
+fn expression_samples() -> Vec<Expression> {
+    let call = || Call::new("load".to_string(), vec![int("1")]);
+    vec![
+        Expression::Await(Box::new(Expression::Call(call()))),
+        Expression::Spread(Box::new(name("loaders"))),
+        Expression::Call(call()),
+        Expression::MethodCall(MethodCall {
+            name: "load".to_string(),
+            arguments: vec![int("1")],
+            callee: Box::new(name("loader")),
+        }),
+        Expression::New(NewCall::new("Loader".to_string(), Vec::new())),
+        Expression::Array(Array::new(vec![int("1"), int("2")], None)),
+        Expression::Tuple(vec![int("1"), int("2")]),
+        Expression::Object(Object::new(
+            vec![ObjectProperty::new("x".to_string(), int("1"))],
+            None,
+        )),
+        Expression::Operation(Operation::new(
+            int("1"),
+            AnyOperation::BinOp(BinOp::Plus),
+            int("2"),
+        )),
+        Expression::Statement(Box::new(Statement::Return(Return::new(Some(int("1")))))),
+        Expression::Member(MemberListNode::new(
+            name("x"),
+            Token(
+                TokenType::Identifier,
+                0..0,
+                Some("point".into()),
+                Position::default(),
+            ),
+            MemberLookup::Dynamic,
+        )),
+        Expression::Literal(Literal::new(
+            "a".to_string(),
+            Some(TypeKind::BuiltIn(BuiltInType::String)),
+        )),
+        Expression::Is(TypeTest {
+            value: Box::new(name("x")),
+            ty: TypeKind::BuiltIn(BuiltInType::Int),
+            span: 0..0,
+        }),
+        Expression::EndOfLine,
+    ]
+}
+
+/// The name of a `Statement` variant, every variant needs a sample in `statement_samples`.
+fn statement_variant(stmt: &Statement) -> &'static str {
+    match stmt {
+        Statement::Var(_) => "Var",
+        Statement::Const(_) => "Const",
+        Statement::Destructure(_) => "Destructure",
+        Statement::Static(_) => "Static",
+        Statement::Function(_) => "Function",
+        Statement::Class(_) => "Class",
+        Statement::Enum(_) => "Enum",
+        Statement::Block(_) => "Block",
+        Statement::Return(_) => "Return",
+        Statement::If(_) => "If",
+        Statement::Try(_) => "Try",
+        Statement::Throw(_) => "Throw",
+        Statement::Import(_) => "Import",
+        Statement::Namespace(_) => "Namespace",
+        Statement::TypeDef(_) => "TypeDef",
+        Statement::MacroInvocation(_) => "MacroInvocation",
+    }
+}
+
+fn statement_samples() -> Vec<Statement> {
+    let var = || Variable::new("x".to_string(), None, Visibility::Module, Some(int("1")));
+    let mut class = Class::new();
+    class.name = "Point".to_string();
+    let mut e = Enum::new();
+    e.name = "Color".to_string();
+    vec![
+        Statement::Var(var()),
+        Statement::Const(var()),
+        Statement::Destructure(Destructure {
+            names: vec!["a".to_string(), "b".to_string()],
+            ty: None,
+            assignment: Expression::Tuple(vec![int("1"), int("2")]),
+            span: 0..0,
+        }),
+        Statement::Static(Static::new(Visibility::Public, Statement::Var(var()))),
+        Statement::Function(function(
+            "f",
+            Vec::new(),
+            vec![Expression::Statement(Box::new(Statement::Return(
+                Return::new(None),
+            )))],
+        )),
+        Statement::Class(class),
+        Statement::Enum(e),
+        Statement::Block(vec![Expression::Statement(Box::new(Statement::Var(var())))]),
+        Statement::Return(Return::new(Some(int("1")))),
+        Statement::If(If {
+            condition: name("ready"),
+            body: Box::new(Statement::Block(Vec::new())),
+            otherwise: Some(Box::new(Statement::Block(Vec::new()))),
+            span: 0..0,
+        }),
+        Statement::Try(Try {
+            body: Box::new(Statement::Block(Vec::new())),
+            catches: vec![Catch {
+                name: "e".to_string(),
+                ty: Some(TypeKind::Reference(TypeReference::new(
+                    "Error".to_string(),
+                    None,
+                ))),
+                body: Box::new(Statement::Block(Vec::new())),
+            }],
+            finally: Some(Box::new(Statement::Block(Vec::new()))),
+            span: 0..0,
+        }),
+        Statement::Throw(Expression::New(NewCall::new(
+            "Error".to_string(),
+            Vec::new(),
+        ))),
+        Statement::Import(Path::new("Loader".to_string())),
+        Statement::Namespace(Namespace::new(Path::new("App".to_string()))),
+        Statement::TypeDef(TypeDefinition::new(
+            "Id".to_string(),
+            None,
+            TypeKind::BuiltIn(BuiltInType::Int),
+        )),
+        Statement::MacroInvocation(CompilerMacro::new("php".to_string(), "echo 1;".to_string())),
+    ]
+}
+
+/// The name of a `TypeKind` variant, every variant needs a sample in `type_samples`.
+fn type_variant(ty: &TypeKind) -> &'static str {
+    match ty {
+        TypeKind::Union(_) => "Union",
+        TypeKind::Reference(_) => "Reference",
+        TypeKind::RuntimeType(_) => "RuntimeType",
+        TypeKind::BuiltIn(_) => "BuiltIn",
+        TypeKind::Nullable(_) => "Nullable",
+        TypeKind::Function { .. } => "Function",
+        TypeKind::Tuple(_) => "Tuple",
+        TypeKind::Object(_) => "Object",
+    }
+}
+
+fn type_samples() -> Vec<TypeKind> {
+    let int = || TypeKind::BuiltIn(BuiltInType::Int);
+    let string = || TypeKind::BuiltIn(BuiltInType::String);
+    vec![
+        TypeKind::union(vec![int(), string()]),
+        TypeKind::Reference(TypeReference::new("Point".to_string(), None)),
+        TypeKind::RuntimeType(RuntimeType::new(None, name("x"))),
+        int(),
+        TypeKind::nullable(int()),
+        TypeKind::function(vec![int()], string()),
+        TypeKind::Tuple(vec![int(), string()]),
+        TypeKind::Object(ObjectShape::new(vec![ShapeProperty {
+            name: "x".to_string(),
+            ty: int(),
+        }])),
+    ]
+}
@@ -1,14 +1,46 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
+
 use crate::{
     compiler::{
-        ast::{AstBody, Expression, Node, NodeKind, Statement, Variable},
+        ast::{
+            build::FunctionBuilder,
+            expr,
+            ops::{AnyOperation, AssignmentOp, BinOp, ComparisonOp, LogicalOp, UnaryOp},
+            types::{
+                BuiltInType, ObjectShape, RuntimeType, ShapeProperty, TypeKind, TypeParam,
+                TypeUnion,
+            },
+            visit::{self, Visitor},
+            Accessor, AccessorKind, AstBody, Class, ClassAllowedStatement, ClassProperty,
+            CompilerMacro, Enum, Expression, Function, If, MemberLookup, Node, NodeKind, Path,
+            Statement, Try, Variable, Visibility,
+        },
+        infer::{collect_signatures, infer_expression, shapes::type_aliases, FunctionSignature},
+        lints::{ERASED_GENERICS, FLATTENED_ASYNC, NARROWED_INTEGERS},
+        stdlib,
+        symbols::{SymbolKind, SymbolTable},
         CompilerOptions,
     },
     transpiler::{
-        format::FormatOptions,
-        langs::{ApiVersion, Generator, Language},
+        driver,
+        format::{AccessorStyle, FormatOptions},
+        frameworks::{laravel, Framework},
+        langs::{
+            intrinsics::{Argument, Intrinsics, Lowering},
+            ApiVersion, Generator, Language, LossyLowering,
+        },
+        preload,
+        sourcemap::{MappedWriter, SourceMap},
     },
+    util::provider::SourceProvider,
 };
 
+pub const EXTENSION: &str = "php";
+
 pub fn new() -> Language {
     Language {
         name: "php".to_string(),
@@ -16,18 +48,105 @@ pub fn new() -> Language {
         version: "8.x.x".to_string(),
         api: ApiVersion::V1,
         author: "Suruloon Studios".to_string(),
+        extension: EXTENSION.to_string(),
         generator: Box::new(PhpGenerator::new(AstBody::new())),
     }
 }
 
+/// The php of the standard library, see `compiler::stdlib`.
+///
+/// **Example:**
+/// ```text
+/// // len(name) where name is a string
+/// intrinsics().lower("len", &arguments); // Some(strlen($name))
+/// ```
+pub fn intrinsics() -> Intrinsics {
+    let mut intrinsics = Intrinsics::new();
+    intrinsics
+        // io
+        .template("println", "printf(\"%s\" . PHP_EOL, {0})")
+        .template("print", "print({0})")
+        .template("read_line", "rtrim(fgets(STDIN), \"\\n\")")
+        .template("read_file", "file_get_contents({0})")
+        .template("write_file", "file_put_contents({0}, {1})")
+        .template("random", "random_int({0}, {1})")
+        // strings
+        .template("to_upper", "strtoupper({0})")
+        .template("to_lower", "strtolower({0})")
+        .template("trim", "trim({0})")
+        .template("contains", "str_contains({0}, {1})")
+        .template("starts_with", "str_starts_with({0}, {1})")
+        .template("ends_with", "str_ends_with({0}, {1})")
+        .template("replace", "str_replace({1}, {2}, {0})")
+        .template("split", "explode({1}, {0})")
+        .template("join", "implode({1}, {0})")
+        // strings and arrays
+        .register("len", Lowering::Custom(php_len))
+        // arrays
+        .template("push", "array_push({0}, {1})")
+        .template("reverse", "array_reverse({0})")
+        .template("keys", "array_keys({0})")
+        // math
+        .template("abs", "abs({0})")
+        .template("floor", "floor({0})")
+        .template("ceil", "ceil({0})")
+        .template("round", "round({0})")
+        .template("sqrt", "sqrt({0})")
+        .template("pow", "pow({0}, {1})")
+        .template("min", "min({0}, {1})")
+        .template("max", "max({0}, {1})");
+    intrinsics
+}
+
+/// `strlen` for a string, `count` for anything else.
+fn php_len(arguments: &[Argument]) -> String {
+    let value = arguments
+        .iter()
+        .map(|argument| argument.code.as_str())
+        .collect::<Vec<&str>>()
+        .join(", ");
+    match arguments.first().and_then(|argument| argument.ty.as_ref()) {
+        Some(TypeKind::BuiltIn(BuiltInType::String)) => format!("strlen({})", value),
+        _ => format!("count({})", value),
+    }
+}
+
 pub struct PhpGenerator {
     formatting: FormatOptions,
+    /// The type aliases of the body being generated, php has no aliases so they're inlined.
+    aliases: HashMap<String, TypeKind>,
+    /// The functions the body declares, they are called instead of the standard library.
+    declared: HashSet<String>,
+    /// The calls generated as something else than a call, see `intrinsics`.
+    intrinsics: Intrinsics,
+    /// The signatures of the functions that can be called, to type the arguments of intrinsics.
+    signatures: HashMap<String, FunctionSignature>,
+    /// The types of the variables in scope, the innermost scope is last.
+    scopes: RefCell<Vec<HashMap<String, TypeKind>>>,
+    /// The accessors of the properties of every class, by class and property.
+    accessors: HashMap<String, HashMap<String, Vec<AccessorKind>>>,
+    /// The static properties of every class, they are read as `User::$count`.
+    statics: HashMap<String, HashSet<String>>,
+    /// The runtime types whose values are checked, none unless `runtime_checks` is set.
+    runtime_types: HashSet<String>,
 }
 
 impl PhpGenerator {
     pub fn new(body: AstBody) -> Self {
+        Self::with_format(FormatOptions::psr_4())
+    }
+
+    pub fn with_format(formatting: FormatOptions) -> Self {
         PhpGenerator {
-            formatting: FormatOptions::PSR_4(),
+            formatting,
+            aliases: HashMap::new(),
+            declared: HashSet::new(),
+            intrinsics: intrinsics(),
+            signatures: HashMap::new(),
+            scopes: RefCell::new(vec![HashMap::new()]),
+            accessors: HashMap::new(),
+            statics: HashMap::new(),
+            runtime_types: HashSet::new(),
         }
     }
 
@@ -35,53 +154,1681 @@ impl PhpGenerator {
         let kind = node.inner();
 
         match kind {
-            NodeKind::Expression(expr) => self.process_expression(expr),
-            NodeKind::Statement(stmt) => self.process_statement(stmt),
-            _ => "".to_string(),
+            NodeKind::Expression(expr) => {
+                let code = self.process_expression(expr);
+                if code.is_empty() {
+                    code
+                } else {
+                    format!("{};", code)
+                }
+            }
+            NodeKind::Statement(stmt) => self.process_statement(stmt, 0),
         }
     }
 
     fn process_expression(&self, expr: Expression) -> String {
         match expr {
-            _ => "".to_string(),
+            Expression::Literal(literal) => match literal.ty {
+                Some(TypeKind::BuiltIn(BuiltInType::String)) => php_string(&literal.value),
+                Some(_) => literal.value,
+                None => format!("${}", self.formatting.variable_name(&literal.value)),
+            },
+            Expression::Await(inner) => self.process_expression(*inner),
+            Expression::Spread(inner) => format!("...{}", self.process_expression(*inner)),
+            Expression::Call(call) => {
+                // a spread gives any amount of arguments.
+                let spread = call
+                    .arguments
+                    .iter()
+                    .any(|arg| matches!(arg, Expression::Spread(_)));
+                let arity = !spread
+                    && stdlib::find(call.name.as_str())
+                        .is_none_or(|builtin| builtin.inputs.len() == call.arguments.len());
+                if !arity
+                    || self.declared.contains(call.name.as_str())
+                    || self.intrinsics.get(call.name.as_str()).is_none()
+                {
+                    return format!("{}({})", call.name, self.process_arguments(call.arguments));
+                }
+                let arguments = call
+                    .arguments
+                    .iter()
+                    .map(|arg| Argument {
+                        code: self.process_expression(arg.clone()),
+                        ty: self.infer_type(arg),
+                    })
+                    .collect::<Vec<Argument>>();
+                self.intrinsics
+                    .lower(&call.name, &arguments)
+                    .unwrap_or_else(|| {
+                        format!("{}({})", call.name, self.process_arguments(call.arguments))
+                    })
+            }
+            Expression::MethodCall(call) => format!(
+                "{}->{}({})",
+                self.process_expression(*call.callee),
+                call.name,
+                self.process_arguments(call.arguments)
+            ),
+            Expression::New(call) => format!(
+                "new {}({})",
+                call.name,
+                self.process_arguments(call.arguments)
+            ),
+            Expression::Array(array) => format!("[{}]", self.process_arguments(array.values)),
+            Expression::Tuple(values) => format!("[{}]", self.process_arguments(values)),
+            Expression::Object(object) => {
+                let properties = object
+                    .properties
+                    .into_iter()
+                    .map(|p| format!("'{}' => {}", p.name, self.process_expression(p.value)))
+                    .collect::<Vec<String>>();
+                format!("[{}]", properties.join(", "))
+            }
+            Expression::Operation(op) => {
+                let right = self.process_expression(*op.right);
+                match &op.op {
+                    AnyOperation::UnaryOp(unary) => process_unary(unary, right),
+                    AnyOperation::ComparisonOp(ComparisonOp::Contains)
+                    | AnyOperation::ComparisonOp(ComparisonOp::In) => {
+                        format!("in_array({}, {})", self.process_expression(*op.left), right)
+                    }
+                    any => format!(
+                        "{} {} {}",
+                        self.process_expression(*op.left),
+                        operator(any),
+                        right
+                    ),
+                }
+            }
+            Expression::Member(member) => {
+                let origin = member.origin.value().unwrap_or_default();
+                if matches!(member.lookup, MemberLookup::Dynamic) {
+                    if let Some(code) = self.accessor_call(&origin, &member.name) {
+                        return code;
+                    }
+                }
+                let is_static_property = match member.name.as_ref() {
+                    Expression::Literal(property) if property.ty.is_none() => self
+                        .statics
+                        .get(&origin)
+                        .is_some_and(|statics| statics.contains(&property.value)),
+                    _ => false,
+                };
+                let name = self.process_expression(*member.name);
+                match member.lookup {
+                    MemberLookup::Static if is_static_property => format!("{}::{}", origin, name),
+                    MemberLookup::Static => format!("{}::{}", origin, name.trim_start_matches('$')),
+                    MemberLookup::Dynamic => format!(
+                        "${}->{}",
+                        self.formatting.variable_name(&origin),
+                        name.trim_start_matches('$')
+                    ),
+                    MemberLookup::Index => {
+                        format!("${}[{}]", self.formatting.variable_name(&origin), name)
+                    }
+                }
+            }
+            Expression::Is(test) => {
+                let value = self.process_expression(*test.value);
+                self.type_test(&value, &self.resolve_aliases(&test.ty))
+            }
+            Expression::Statement(stmt) => self.process_statement(*stmt, 0),
+            Expression::EndOfLine => "".to_string(),
         }
     }
 
-    fn process_statement(&self, stmt: Statement) -> String {
+    fn process_arguments(&self, arguments: Vec<Expression>) -> String {
+        arguments
+            .into_iter()
+            .map(|arg| self.process_expression(arg))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    fn process_statement(&self, stmt: Statement, depth: usize) -> String {
         match stmt {
             Statement::Var(var) => self.process_variable(var),
             Statement::Const(var) => self.process_const(var),
-            _ => "".to_string(),
+            Statement::Destructure(destructure) => {
+                let names = destructure
+                    .names
+                    .iter()
+                    .map(|name| format!("${}", self.formatting.variable_name(name)))
+                    .collect::<Vec<String>>();
+                format!(
+                    "[{}] = {};",
+                    names.join(", "),
+                    self.process_expression(destructure.assignment)
+                )
+            }
+            Statement::Static(s) => {
+                format!("static {}", self.process_statement(*s.statement, depth))
+            }
+            Statement::Function(func) => self.process_function(func, None, depth),
+            Statement::Class(class) => self.process_class(class, depth),
+            Statement::Enum(e) => self.process_enum(e, depth),
+            Statement::Block(exprs) => self.process_block(exprs, depth),
+            Statement::Import(path) => format!("use {};", process_path(&path)),
+            Statement::Namespace(namespace) => match namespace.body {
+                Some(body) => format!(
+                    "namespace {}{}{}{}{}}}",
+                    process_path(&namespace.path),
+                    self.formatting
+                        .open_brace(&self.formatting.class_brace, depth),
+                    self.formatting.new_line,
+                    self.process_body(*body, depth + 1),
+                    self.formatting.indent(depth)
+                ),
+                None => format!("namespace {};", process_path(&namespace.path)),
+            },
+            Statement::Return(ret) => match ret.expression {
+                Some(expr) => format!("return {};", self.process_expression(expr)),
+                None => "return;".to_string(),
+            },
+            Statement::If(i) => self.process_if(i, depth),
+            Statement::Try(t) => self.process_try(t, depth),
+            Statement::Throw(expr) => format!("throw {};", self.process_expression(expr)),
+            Statement::MacroInvocation(invocation) => self.process_macro(invocation, depth),
+            Statement::TypeDef(def) => match def.kind {
+                TypeKind::RuntimeType(ty) if self.runtime_types.contains(&def.name) => {
+                    self.process_runtime_type(&def.name, ty, depth)
+                }
+                _ => "".to_string(),
+            },
         }
     }
 
-    fn process_variable(&self, var: Variable) -> String {
+    /// An `else if` is generated as `elseif`.
+    fn process_if(&self, i: If, depth: usize) -> String {
+        let block = |body: Statement, brace| {
+            format!(
+                "{}{}{}{}}}",
+                self.formatting.open_brace(brace, depth),
+                self.formatting.new_line,
+                self.process_body(body, depth + 1),
+                self.formatting.indent(depth)
+            )
+        };
+        let mut code = format!(
+            "if ({}){}",
+            self.process_expression(i.condition),
+            block(*i.body, &self.formatting.if_brace)
+        );
+        let mut otherwise = i.otherwise;
+        while let Some(next) = otherwise.take() {
+            match *next {
+                Statement::If(i) => {
+                    code.push_str(&format!(
+                        " elseif ({}){}",
+                        self.process_expression(i.condition),
+                        block(*i.body, &self.formatting.if_brace)
+                    ));
+                    otherwise = i.otherwise;
+                }
+                body => code.push_str(&format!(
+                    " else{}",
+                    block(body, &self.formatting.else_brace)
+                )),
+            }
+        }
+        code
+    }
+
+    /// Tests whether a value is of a type, eg: `is_int($x)` or `$user instanceof Admin`.
+    /// A runtime type is tested with its expression, eg: `(fn($x) => $x > 0)($size)`.
+    fn type_test(&self, value: &str, ty: &TypeKind) -> String {
+        let call = |function: &str| format!("{}({})", function, value);
+        match ty {
+            TypeKind::BuiltIn(built_in) => match built_in {
+                BuiltInType::Strict(_)
+                | BuiltInType::Byte
+                | BuiltInType::Short
+                | BuiltInType::Int
+                | BuiltInType::Long => call("is_int"),
+                BuiltInType::Float | BuiltInType::Double => call("is_float"),
+                BuiltInType::Bool => call("is_bool"),
+                BuiltInType::String => call("is_string"),
+                BuiltInType::Array(_) | BuiltInType::Map(..) => call("is_array"),
+                BuiltInType::Null => call("is_null"),
+                BuiltInType::Any => "true".to_string(),
+            },
+            TypeKind::Nullable(inner) => {
+                format!("({} || {})", call("is_null"), self.type_test(value, inner))
+            }
+            TypeKind::Union(union) => {
+                let tests = union
+                    .types
+                    .iter()
+                    .map(|ty| self.type_test(value, ty))
+                    .collect::<Vec<String>>();
+                format!("({})", tests.join(" || "))
+            }
+            TypeKind::Function { .. } => call("is_callable"),
+            TypeKind::Tuple(_) | TypeKind::Object(_) => call("is_array"),
+            TypeKind::Reference(reference) => format!("{} instanceof {}", value, reference.name),
+            TypeKind::RuntimeType(runtime) => {
+                let param = runtime
+                    .params
+                    .iter()
+                    .flatten()
+                    .next()
+                    .and_then(|param| param.name.as_deref())
+                    .unwrap_or("value");
+                format!(
+                    "(fn(${}) => {})({})",
+                    self.formatting.variable_name(param),
+                    self.process_expression(*runtime.body.clone()),
+                    value
+                )
+            }
+        }
+    }
+
+    /// A catch clause without a type catches every error, as `\Throwable`.
+    fn process_try(&self, t: Try, depth: usize) -> String {
+        let block = |body: Statement| {
+            format!(
+                "{}{}{}{}}}",
+                self.formatting
+                    .open_brace(&self.formatting.try_brace, depth),
+                self.formatting.new_line,
+                self.process_body(body, depth + 1),
+                self.formatting.indent(depth)
+            )
+        };
+        let mut code = format!("try{}", block(*t.body));
+        for catch in t.catches {
+            let ty = match &catch.ty {
+                Some(ty) => process_type(&self.resolve_aliases(ty)),
+                None => "\\Throwable".to_string(),
+            };
+            code.push_str(&format!(
+                " catch ({} ${}){}",
+                ty,
+                self.formatting.variable_name(&catch.name),
+                block(*catch.body)
+            ));
+        }
+        if let Some(finally) = t.finally {
+            code.push_str(&format!(" finally{}", block(*finally)));
+        }
+        code
+    }
+
+    /// The function that checks the values of a runtime type, it throws a `TypeError` for
+    /// a value that isn't one and returns the value otherwise.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// // type Positive(x: int) = x > 0;
+    /// // function assert_Positive($x)
+    /// // {
+    /// //     if (!($x > 0)) {
+    /// //         throw new \TypeError("The value is not a `Positive`.");
+    /// //     }
+    /// //     return $x;
+    /// // }
+    /// ```
+    fn process_runtime_type(&self, name: &str, ty: RuntimeType, depth: usize) -> String {
+        let value = ty
+            .params
+            .and_then(|params| params.into_iter().next())
+            .and_then(|param| param.name)
+            .unwrap_or("value".to_string());
+        let value = format!("${}", self.formatting.variable_name(&value));
+        let indent = |depth: usize| self.formatting.indent(depth);
         format!(
-            "${} = {};",
-            var.name,
-            self.process_expression(var.assignment.unwrap())
+            "function {}({}){}{nl}{}if (!({})){}{nl}{}throw new \\TypeError({});{nl}{}}}{nl}{}return {};{nl}{}}}",
+            runtime_check(name),
+            value,
+            self.formatting
+                .open_brace(&self.formatting.function_brace, depth),
+            indent(depth + 1),
+            self.process_expression(*ty.body),
+            self.formatting
+                .open_brace(&self.formatting.if_brace, depth + 1),
+            indent(depth + 2),
+            php_string(&format!("The value is not a `{}`.", name)),
+            indent(depth + 1),
+            indent(depth + 1),
+            value,
+            indent(depth),
+            nl = self.formatting.new_line
         )
     }
 
+    /// The check of a value given where `ty` is declared, if `ty` has a runtime type whose
+    /// values are checked. Aliases are followed, a nullable type checks the value unless it's
+    /// `null` and an array type checks every item, eg: `array_map(fn($value) => assert_Positive($value), $xs)`.
+    /// The check returns the value, so it can take the place of it.
+    fn runtime_check(&self, ty: Option<&TypeKind>, value: &str) -> Option<String> {
+        self.check_value(ty?, value, self.aliases.len())
+    }
+
+    /// `depth` bounds how many aliases are followed, see `resolve_aliases`.
+    fn check_value(&self, ty: &TypeKind, value: &str, depth: usize) -> Option<String> {
+        let item = "$value";
+        match ty {
+            TypeKind::Reference(reference) if self.runtime_types.contains(&reference.name) => {
+                Some(format!("{}({})", runtime_check(&reference.name), value))
+            }
+            TypeKind::Reference(reference) if depth > 0 && reference.params.is_none() => {
+                let alias = self.aliases.get(&reference.name)?;
+                self.check_value(alias, value, depth - 1)
+            }
+            TypeKind::Nullable(inner) => {
+                let check = self.check_value(inner, item, depth)?;
+                Some(format!(
+                    "(fn({0}) => {0} === null ? null : {1})({2})",
+                    item, check, value
+                ))
+            }
+            TypeKind::BuiltIn(BuiltInType::Array(inner)) => {
+                let check = self.check_value(inner, item, depth)?;
+                Some(format!("array_map(fn({}) => {}, {})", item, check, value))
+            }
+            _ => None,
+        }
+    }
+
+    /// The type of an expression, from the variables in scope and the signatures.
+    fn infer_type(&self, expr: &Expression) -> Option<TypeKind> {
+        let scopes = self.scopes.borrow();
+        let lookup = |name: &str| {
+            scopes
+                .iter()
+                .rev()
+                .find_map(|scope| scope.get(name))
+                .cloned()
+        };
+        infer_expression(expr, &lookup, &self.signatures).map(|ty| self.resolve_aliases(&ty))
+    }
+
+    /// Generates a variable, a type php can't hint is kept in a `@var` docblock,
+    /// eg: `/** @var array{int, string} $pair */ $pair = [1, 'a'];`
+    fn process_variable(&self, var: Variable) -> String {
+        let ty = match var.type_of() {
+            Some(ty) => Some(self.resolve_aliases(ty)),
+            None => var
+                .assignment
+                .as_ref()
+                .and_then(|expr| self.infer_type(expr)),
+        };
+        let name = self.formatting.variable_name(var.name.as_str());
+        let docblock = match &ty {
+            Some(ty) if needs_docblock(ty) => {
+                format!("/** @var {} ${} */ ", docblock_type(ty), name)
+            }
+            _ => String::new(),
+        };
+        if let Some(ty) = ty {
+            let mut scopes = self.scopes.borrow_mut();
+            scopes.last_mut().unwrap().insert(var.name.to_string(), ty);
+        }
+        let value = match var.assignment {
+            Some(expr) => {
+                let value = self.process_expression(expr);
+                self.runtime_check(var.ty.as_ref(), &value).unwrap_or(value)
+            }
+            None => "null".to_string(),
+        };
+        format!("{}${} = {};", docblock, name, value)
+    }
+
     fn process_const(&self, var: Variable) -> String {
+        let value = match var.assignment {
+            Some(expr) => self.process_expression(expr),
+            None => "null".to_string(),
+        };
+        format!("const {} = {};", var.name, value)
+    }
+
+    /// Generates a function, `visibility` is only given for methods.
+    fn process_function(
+        &self,
+        mut func: Function,
+        visibility: Option<&str>,
+        depth: usize,
+    ) -> String {
+        // the arguments are checked before the body runs.
+        let checks = func
+            .inputs
+            .iter()
+            .filter_map(|input| {
+                let value = format!("${}", self.formatting.variable_name(&input.name));
+                let check = self.runtime_check(input.ty.as_ref(), &value)?;
+                Some(format!(
+                    "{}{};{}",
+                    self.formatting.indent(depth + 1),
+                    check,
+                    self.formatting.new_line
+                ))
+            })
+            .collect::<String>();
+        let generics = func.generics.clone();
+        erase_function(&mut func, &generics);
+        for input in func.inputs.iter_mut() {
+            input.ty = input.ty.as_ref().map(|ty| self.resolve_aliases(ty));
+        }
+        func.outputs = func.outputs.as_ref().map(|ty| self.resolve_aliases(ty));
+        let inputs = func
+            .inputs
+            .iter()
+            .map(|input| {
+                let name = self.formatting.variable_name(&input.name);
+                let spread = if input.variadic { "..." } else { "" };
+                match &input.ty {
+                    Some(ty) => format!("{} {}${}", process_type(ty), spread, name),
+                    None => format!("{}${}", spread, name),
+                }
+            })
+            .collect::<Vec<String>>();
+        let outputs = match &func.outputs {
+            Some(ty) => format!(": {}", process_type(ty)),
+            None => "".to_string(),
+        };
+        let visibility = match visibility {
+            Some(v) => format!("{} ", v),
+            None => "".to_string(),
+        };
+        let docblock = self.process_docblock(&func, depth);
+
+        let scope = func
+            .inputs
+            .iter()
+            .filter_map(|input| Some((input.name.clone(), input.body_type()?)))
+            .collect();
+        self.scopes.borrow_mut().push(scope);
+        let body = checks + &self.process_body(*func.body, depth + 1);
+        self.scopes.borrow_mut().pop();
+
+        format!(
+            "{}{}function {}({}){}{}{}{}{}}}",
+            docblock,
+            visibility,
+            func.name.unwrap_or_default(),
+            inputs.join(", "),
+            outputs,
+            self.formatting
+                .open_brace(&self.formatting.function_brace, depth),
+            self.formatting.new_line,
+            body,
+            self.formatting.indent(depth)
+        )
+    }
+
+    /// A docblock with the signatures of the function types and the shapes of the tuples
+    /// a function takes or returns, php only has `callable` and `array` to hint them with.
+    fn process_docblock(&self, func: &Function, depth: usize) -> String {
+        let mut tags = func
+            .inputs
+            .iter()
+            .filter_map(|input| match &input.ty {
+                Some(ty) if needs_docblock(ty) => Some(format!(
+                    "@param {} {}${}",
+                    docblock_type(ty),
+                    if input.variadic { "..." } else { "" },
+                    self.formatting.variable_name(&input.name)
+                )),
+                _ => None,
+            })
+            .collect::<Vec<String>>();
+        if let Some(ty) = func.outputs.as_ref().filter(|ty| needs_docblock(ty)) {
+            tags.push(format!("@return {}", docblock_type(ty)));
+        }
+        if tags.is_empty() {
+            return "".to_string();
+        }
+
+        let indent = self.formatting.indent(depth);
+        let new_line = self.formatting.new_line;
+        let mut docblock = format!("/**{}", new_line);
+        for tag in tags {
+            docblock.push_str(&format!("{} * {}{}", indent, tag, new_line));
+        }
+        docblock.push_str(&format!("{} */{}{}", indent, new_line, indent));
+        docblock
+    }
+
+    /// Replaces the references to type aliases with the type they alias.
+    fn resolve_aliases(&self, ty: &TypeKind) -> TypeKind {
+        resolve_aliases(ty, &self.aliases, self.aliases.len())
+    }
+
+    fn process_class(&self, class: Class, depth: usize) -> String {
+        let mut header = format!("class {}", class.name);
+        if let Some(extends) = &class.extends {
+            header.push_str(&format!(" extends {}", extends));
+        }
+        if let Some(implements) = &class.implements {
+            header.push_str(&format!(" implements {}", implements.join(", ")));
+        }
+
+        // php has no generics, the type parameters of the class become their bounds.
+        let generics = class.generics.clone();
+        let accessed = class
+            .body
+            .all_properties()
+            .filter(|property| !property.accessors.is_empty())
+            .cloned()
+            .collect::<Vec<ClassProperty>>();
+        let mut members: Vec<String> = Vec::new();
+        for mut property in class.body.properties {
+            property.ty = property.ty.map(|ty| erase(&ty, &generics));
+            members.push(self.process_property(property, false));
+        }
+        for mut method in class.body.methods {
+            erase_function(&mut method, &generics);
+            let visibility = process_visibility(&method.visibility);
+            members.push(self.process_function(method, Some(visibility), depth + 1));
+        }
+        for mut other in class.body.other {
+            erase_member(&mut other, &generics);
+            members.push(self.process_class_statement(other, false, depth + 1));
+        }
+        for property in accessed.iter() {
+            members.extend(self.process_accessors(property, &generics, depth + 1));
+        }
+        if self.formatting.accessors == AccessorStyle::Magic {
+            members.extend(self.magic_accessors(&accessed, depth + 1));
+        }
+
+        let indent = self.formatting.indent(depth + 1);
+        let body = members
+            .into_iter()
+            .filter(|m| !m.is_empty())
+            .map(|m| format!("{}{}{}", indent, m, self.formatting.new_line))
+            .collect::<String>();
+
+        format!(
+            "{}{}{}{}{}}}",
+            header,
+            self.formatting
+                .open_brace(&self.formatting.class_brace, depth),
+            self.formatting.new_line,
+            body,
+            self.formatting.indent(depth)
+        )
+    }
+
+    fn process_class_statement(
+        &self,
+        stmt: ClassAllowedStatement,
+        is_static: bool,
+        depth: usize,
+    ) -> String {
+        match stmt {
+            ClassAllowedStatement::Property(property) => self.process_property(property, is_static),
+            ClassAllowedStatement::Method(method) => {
+                let visibility = process_visibility(&method.visibility);
+                let visibility = if is_static {
+                    format!("{} static", visibility)
+                } else {
+                    visibility.to_string()
+                };
+                self.process_function(method, Some(&visibility), depth)
+            }
+            ClassAllowedStatement::Constant(constant) => {
+                // php constants are public unless they are declared otherwise.
+                match constant.visibility {
+                    Visibility::Public | Visibility::Module => self.process_const(constant),
+                    _ => {
+                        let visibility = process_visibility(&constant.visibility);
+                        format!("{} {}", visibility, self.process_const(constant))
+                    }
+                }
+            }
+            ClassAllowedStatement::Static(inner) => {
+                self.process_class_statement(*inner, true, depth)
+            }
+            ClassAllowedStatement::Import(path) => format!("use {};", process_path(&path)),
+            ClassAllowedStatement::Macro(invocation) => self.process_macro(invocation, depth),
+        }
+    }
+
+    /// `php!` bodies are written out as is, only their indentation is adjusted to the depth.
+    /// `target!` is replaced by the arm for php, or by nothing if there is none.
+    fn process_macro(&self, invocation: CompilerMacro, depth: usize) -> String {
+        if invocation.name == "target" {
+            let arm = match invocation.arm_for("php") {
+                Some(arm) => arm.body.clone(),
+                None => return "".to_string(),
+            };
+            return arm
+                .get_program()
+                .iter()
+                .map(|node| match node.inner.clone() {
+                    NodeKind::Statement(stmt) => self.process_statement(stmt, depth),
+                    NodeKind::Expression(expr) => format!("{};", self.process_expression(expr)),
+                })
+                .filter(|code| !code.is_empty())
+                .collect::<Vec<String>>()
+                .join(&format!(
+                    "{}{}",
+                    self.formatting.new_line,
+                    self.formatting.indent(depth)
+                ));
+        }
+
+        let mut lines = invocation.body.lines().collect::<Vec<&str>>();
+        while lines.first().is_some_and(|l| l.trim().is_empty()) {
+            lines.remove(0);
+        }
+        while lines.last().is_some_and(|l| l.trim().is_empty()) {
+            lines.pop();
+        }
+        // a body that starts on the line of the brace doesn't count towards the indentation.
+        let same_line = !invocation
+            .body
+            .trim_start_matches([' ', '\t'])
+            .starts_with(['\n', '\r']);
+        let margin = lines
+            .iter()
+            .skip(usize::from(same_line))
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| l.len() - l.trim_start().len())
+            .min()
+            .unwrap_or(0);
+
+        let separator = format!(
+            "{}{}",
+            self.formatting.new_line,
+            self.formatting.indent(depth)
+        );
+        lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| match i {
+                0 if same_line => line.trim(),
+                _ => line.get(margin..).unwrap_or(line.trim_start()).trim_end(),
+            })
+            .collect::<Vec<&str>>()
+            .join(&separator)
+    }
+
+    /// The methods that read and write a property with accessors, eg: `getName()`.
+    /// The accessors of public properties are private when `__get` and `__set` call them.
+    fn process_accessors(
+        &self,
+        property: &ClassProperty,
+        generics: &[TypeParam],
+        depth: usize,
+    ) -> Vec<String> {
+        let visibility = match self.formatting.accessors {
+            AccessorStyle::Magic if is_public(&property.visibility) => {
+                process_visibility(&Visibility::Private)
+            }
+            _ => process_visibility(&property.visibility),
+        };
+        property
+            .accessors
+            .iter()
+            .map(|accessor| {
+                let mut method = accessor_method(property, accessor);
+                erase_function(&mut method, generics);
+                self.process_function(method, Some(visibility), depth)
+            })
+            .collect()
+    }
+
+    /// `__get` and `__set`, which call the accessors of the public properties by their name.
+    fn magic_accessors(&self, properties: &[ClassProperty], depth: usize) -> Vec<String> {
+        let indent = |depth: usize| self.formatting.indent(depth);
+        let new_line = self.formatting.new_line;
+        let open = self
+            .formatting
+            .open_brace(&self.formatting.function_brace, depth);
+        let undefined =
+            "default => throw new \\Error('Undefined property: ' . static::class . '::$' . $name),";
+        let mut methods = Vec::new();
+        for (kind, signature, call) in [
+            (
+                AccessorKind::Get,
+                "__get(string $name): mixed",
+                "return match ($name) {",
+            ),
+            (
+                AccessorKind::Set,
+                "__set(string $name, mixed $value): void",
+                "match ($name) {",
+            ),
+        ] {
+            let arms = properties
+                .iter()
+                .filter(|property| {
+                    is_public(&property.visibility) && property.accessor(kind).is_some()
+                })
+                .map(|property| {
+                    let argument = match kind {
+                        AccessorKind::Get => "",
+                        AccessorKind::Set => "$value",
+                    };
+                    format!(
+                        "{}'{}' => $this->{}({}),{}",
+                        indent(depth + 2),
+                        property.name,
+                        accessor_name(kind, &property.name),
+                        argument,
+                        new_line
+                    )
+                })
+                .collect::<String>();
+            if arms.is_empty() {
+                continue;
+            }
+            methods.push(format!(
+                "public function {}{}{nl}{}{}{nl}{}{}{}{nl}{}}};{nl}{}}}",
+                signature,
+                open,
+                indent(depth + 1),
+                call,
+                arms,
+                indent(depth + 2),
+                undefined,
+                indent(depth + 1),
+                indent(depth),
+                nl = new_line
+            ));
+        }
+        methods
+    }
+
+    /// Calls the accessor a member access reads or writes through, eg: `$user->getName()`,
+    /// when accessors are methods and the class of the value is known.
+    /// `this` accesses the property itself, which is what the accessors do.
+    fn accessor_call(&self, origin: &str, name: &Expression) -> Option<String> {
+        if self.formatting.accessors != AccessorStyle::Methods || origin == "this" {
+            return None;
+        }
+        let class = match self.infer_type(&expr::name(origin))? {
+            TypeKind::Reference(reference) => reference.name,
+            _ => return None,
+        };
+        let class = class.rsplit('\\').next().unwrap_or(&class);
+        let properties = self.accessors.get(class)?;
+        let value = format!("${}", self.formatting.variable_name(origin));
+        match name {
+            Expression::Literal(property) if property.ty.is_none() => {
+                let kinds = properties.get(&property.value)?;
+                kinds.contains(&AccessorKind::Get).then(|| {
+                    let getter = accessor_name(AccessorKind::Get, &property.value);
+                    format!("{}->{}()", value, getter)
+                })
+            }
+            // the parser reads `user.name = "a"` as an assignment to `name` on `user`.
+            Expression::Operation(operation)
+                if matches!(operation.op, AnyOperation::AssignmentOp(AssignmentOp::Eq)) =>
+            {
+                let Expression::Literal(property) = operation.left.as_ref() else {
+                    return None;
+                };
+                let kinds = properties.get(&property.value)?;
+                kinds.contains(&AccessorKind::Set).then(|| {
+                    let setter = accessor_name(AccessorKind::Set, &property.value);
+                    let argument = self.process_expression(*operation.right.clone());
+                    format!("{}->{}({})", value, setter, argument)
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn process_property(&self, mut property: ClassProperty, is_static: bool) -> String {
+        property.ty = property.ty.as_ref().map(|ty| self.resolve_aliases(ty));
+        // a property with accessors is only read and written through them.
+        let mut code = match property.accessors.is_empty() {
+            true => process_visibility(&property.visibility).to_string(),
+            false => process_visibility(&Visibility::Private).to_string(),
+        };
+        if is_static {
+            code.push_str(" static");
+        }
+        match &property.ty {
+            // properties can't be hinted as `callable`.
+            Some(ty) if has_function_type(ty) => {
+                code = format!("/** @var {} */ {}", docblock_type(ty), code);
+            }
+            Some(ty) if needs_docblock(ty) => {
+                code = format!(
+                    "/** @var {} */ {} {}",
+                    docblock_type(ty),
+                    code,
+                    process_type(ty)
+                );
+            }
+            Some(ty) => code.push_str(&format!(" {}", process_type(ty))),
+            None => {}
+        }
+        code.push_str(&format!(
+            " ${}",
+            self.formatting.variable_name(&property.name)
+        ));
+        if let Some(value) = property.assignment {
+            code.push_str(&format!(" = {}", self.process_expression(value)));
+        }
+        code.push(';');
+        code
+    }
+
+    fn process_enum(&self, e: Enum, depth: usize) -> String {
+        let backed = e.variants.iter().any(|v| v.value.is_some());
+        let indent = self.formatting.indent(depth + 1);
+        let cases = e
+            .variants
+            .into_iter()
+            .map(|variant| match variant.value {
+                Some(value) => format!(
+                    "{}case {} = {};{}",
+                    indent,
+                    variant.name,
+                    self.process_expression(value),
+                    self.formatting.new_line
+                ),
+                None => format!(
+                    "{}case {};{}",
+                    indent, variant.name, self.formatting.new_line
+                ),
+            })
+            .collect::<String>();
+
         format!(
-            "static {} = {};",
-            var.name,
-            self.process_expression(var.assignment.unwrap())
+            "enum {}{}{}{}{}{}}}",
+            e.name,
+            if backed { ": int" } else { "" },
+            self.formatting
+                .open_brace(&self.formatting.class_brace, depth),
+            self.formatting.new_line,
+            cases,
+            self.formatting.indent(depth)
         )
     }
+
+    /// Generates the statements of a body, one per line at the given depth.
+    fn process_body(&self, body: Statement, depth: usize) -> String {
+        match body {
+            Statement::Block(exprs) => self.process_block(exprs, depth),
+            stmt => {
+                let code = self.process_statement(stmt, depth);
+                format!(
+                    "{}{}{}",
+                    self.formatting.indent(depth),
+                    code,
+                    self.formatting.new_line
+                )
+            }
+        }
+    }
+
+    fn process_block(&self, exprs: Vec<Expression>, depth: usize) -> String {
+        let mut output = String::new();
+        for expr in exprs {
+            let code = match expr {
+                Expression::Statement(stmt) => self.process_statement(*stmt, depth),
+                Expression::EndOfLine => continue,
+                expr => format!("{};", self.process_expression(expr)),
+            };
+            if code.is_empty() {
+                continue;
+            }
+            output.push_str(&self.formatting.indent(depth));
+            output.push_str(&code);
+            output.push(self.formatting.new_line);
+        }
+        output
+    }
+
+    /// Generates the code of a body, a script is generated without the `<?php` tag,
+    /// eg: for the REPL.
+    fn generate(
+        &self,
+        ast: AstBody,
+        options: CompilerOptions,
+        format: &FormatOptions,
+        open_tag: bool,
+    ) -> (String, SourceMap) {
+        let mut generator = PhpGenerator::with_format(format.clone());
+        generator.aliases = type_aliases(&ast);
+        generator.declared = SymbolTable::build(&ast)
+            .global()
+            .symbols
+            .iter()
+            .filter(|symbol| symbol.kind == SymbolKind::Function)
+            .map(|symbol| symbol.name.clone())
+            .collect();
+        generator.intrinsics = self.intrinsics.clone();
+        generator.signatures = collect_signatures(&stdlib::body());
+        generator.signatures.extend(collect_signatures(&ast));
+        generator.accessors = property_accessors(&ast);
+        generator.statics = static_properties(&ast);
+        if options.runtime_checks {
+            generator.runtime_types = generator
+                .aliases
+                .iter()
+                .filter(|(_, kind)| matches!(kind, TypeKind::RuntimeType(_)))
+                .map(|(name, _)| name.clone())
+                .collect();
+        }
+        let mut writer = MappedWriter::new();
+        if open_tag {
+            writer.write(&format!("<?php{0}{0}", format.new_line));
+        }
+        if options.strict_types {
+            writer.write(&format!("declare(strict_types=1);{0}{0}", format.new_line));
+        }
+        for node in ast.get_program() {
+            let code = generator.process_node(node.clone());
+            if code.is_empty() {
+                continue;
+            }
+            let name = match &node.inner {
+                NodeKind::Statement(Statement::Var(var))
+                | NodeKind::Statement(Statement::Const(var)) => Some(var.name.as_str()),
+                _ => None,
+            };
+            writer.write_mapped(&code, node.start()..node.end(), node.position, name);
+            writer.write(&format.new_line.to_string());
+        }
+        writer.finish()
+    }
 }
 
 impl Generator for PhpGenerator {
-    fn generate_to_string(&self, ast: AstBody, options: CompilerOptions) -> String {
-        let mut output = String::new();
+    fn generate_to_string(
+        &self,
+        ast: AstBody,
+        options: CompilerOptions,
+        format: &FormatOptions,
+    ) -> String {
+        self.generate(ast, options, format, false).0
+    }
+
+    /// The code of a file, it starts with the `<?php` tag.
+    fn generate_with_map(
+        &self,
+        ast: AstBody,
+        options: CompilerOptions,
+        format: &FormatOptions,
+    ) -> (String, SourceMap) {
+        self.generate(ast, options, format, true)
+    }
+
+    fn supports_macro(&self, name: &str) -> bool {
+        matches!(name, "php" | "target")
+    }
+
+    fn intrinsics_mut(&mut self) -> Option<&mut Intrinsics> {
+        Some(&mut self.intrinsics)
+    }
+
+    fn lossy_lowerings(&self, ast: &AstBody) -> Vec<LossyLowering> {
+        let mut finder = LossyFinder {
+            span: 0..0,
+            classes: Vec::new(),
+            functions: Vec::new(),
+            lowerings: Vec::new(),
+        };
         for node in ast.get_program() {
-            output.push_str(&self.process_node(node.clone()));
+            finder.span = node.start()..node.end();
+            visit::walk_node(&mut finder, node);
+        }
+        finder.lowerings
+    }
+
+    fn generate_with(
+        &mut self,
+        provider: &dyn SourceProvider,
+        path: &str,
+        options: CompilerOptions,
+    ) -> Result<(), String> {
+        driver::generate_path(provider, self, path, EXTENSION, &options, &self.formatting)?;
+        let root = std::path::Path::new(path);
+        if !provider.is_dir(root) {
+            return Ok(());
+        }
+        self.write_support(provider, root, &driver::default_output_root(root), &options)
+    }
+
+    fn write_support(
+        &self,
+        provider: &dyn SourceProvider,
+        source_root: &std::path::Path,
+        output_root: &std::path::Path,
+        options: &CompilerOptions,
+    ) -> Result<(), String> {
+        if options.preload {
+            preload::write_preload(provider, source_root, output_root, EXTENSION, options)?;
+        }
+        if options.framework == Some(Framework::Laravel) {
+            laravel::write_provider(provider, source_root, output_root, options)?;
+        }
+        Ok(())
+    }
+}
+
+/// Finds the places php can't keep the meaning of the source.
+struct LossyFinder {
+    /// The span of the top level node being searched.
+    span: Range<usize>,
+    /// The span of every class being searched, the innermost last.
+    classes: Vec<Range<usize>>,
+    /// The name and span of every function being searched, and whether
+    /// its `await` was reported already. The innermost last.
+    functions: Vec<(Option<String>, Range<usize>, bool)>,
+    lowerings: Vec<LossyLowering>,
+}
+
+impl LossyFinder {
+    fn push(&mut self, lint: &'static str, message: String, span: &Range<usize>) {
+        let span = if span.is_empty() {
+            self.span.clone()
+        } else {
+            span.clone()
+        };
+        self.lowerings.push(LossyLowering {
+            lint,
+            message,
+            span,
+        });
+    }
+
+    fn erased(&mut self, name: Option<&str>, generics: &[TypeParam], span: &Range<usize>) {
+        let erased = generics
+            .iter()
+            .filter_map(|g| {
+                let name = g.name.as_ref()?;
+                let erased = erase(&TypeKind::reference(name.clone(), None), generics);
+                Some(format!("`{}` as `{}`", name, process_type(&erased)))
+            })
+            .collect::<Vec<String>>();
+        if erased.is_empty() {
+            return;
+        }
+        let of = match name {
+            Some(name) => format!("`{}`", name),
+            None => "this function".to_string(),
+        };
+        self.push(
+            ERASED_GENERICS,
+            format!(
+                "The type parameters of {} are erased, php checks {}.",
+                of,
+                erased.join(", ")
+            ),
+            span,
+        );
+    }
+
+    fn narrowed<'a>(&mut self, types: impl IntoIterator<Item = &'a TypeKind>, span: &Range<usize>) {
+        if types.into_iter().any(has_long) {
+            self.push(
+                NARROWED_INTEGERS,
+                "`long` is narrowed to php's 64 bit `int`, values that don't fit become floats."
+                    .to_string(),
+                span,
+            );
+        }
+    }
+}
+
+impl Visitor for LossyFinder {
+    fn visit_expression(&mut self, expr: &Expression) {
+        if let Expression::Await(_) = expr {
+            match self.functions.last_mut() {
+                Some((_, _, reported)) if *reported => {}
+                Some((name, span, reported)) => {
+                    *reported = true;
+                    let (name, span) = (name.clone(), span.clone());
+                    let runs = match name {
+                        Some(name) => format!("`{}`", name),
+                        None => "this function".to_string(),
+                    };
+                    self.push(
+                        FLATTENED_ASYNC,
+                        format!("`await` is dropped, php runs {} synchronously.", runs),
+                        &span,
+                    );
+                }
+                None => {
+                    let span = self.span.clone();
+                    self.push(
+                        FLATTENED_ASYNC,
+                        "`await` is dropped, php runs the script synchronously.".to_string(),
+                        &span,
+                    );
+                }
+            }
+        }
+        visit::walk_expression(self, expr);
+    }
+
+    fn visit_variable(&mut self, var: &Variable) {
+        self.narrowed(var.ty.iter(), &var.span);
+        visit::walk_variable(self, var);
+    }
+
+    fn visit_function(&mut self, func: &Function) {
+        self.erased(func.name.as_deref(), &func.generics, &func.span);
+        let types = func.inputs.iter().filter_map(|input| input.ty.as_ref());
+        self.narrowed(types.chain(func.outputs.iter()), &func.span);
+        if func.is_async {
+            let runs = match &func.name {
+                Some(name) => format!("`{}`", name),
+                None => "this function".to_string(),
+            };
+            self.push(
+                FLATTENED_ASYNC,
+                format!("`async` is dropped, php runs {} synchronously.", runs),
+                &func.span,
+            );
+        }
+        // an async function is reported already, its `await`s are not reported again.
+        self.functions
+            .push((func.name.clone(), func.span.clone(), func.is_async));
+        visit::walk_function(self, func);
+        self.functions.pop();
+    }
+
+    fn visit_class(&mut self, class: &Class) {
+        self.erased(Some(&class.name), &class.generics, &class.span);
+        self.classes.push(class.span.clone());
+        visit::walk_class(self, class);
+        self.classes.pop();
+    }
+
+    fn visit_class_property(&mut self, property: &ClassProperty) {
+        // properties have no span of their own.
+        let span = self.classes.last().cloned().unwrap_or_default();
+        self.narrowed(property.ty.iter(), &span);
+        visit::walk_class_property(self, property);
+    }
+}
+
+/// Whether `long` is used anywhere in the type.
+fn has_long(ty: &TypeKind) -> bool {
+    match ty {
+        TypeKind::BuiltIn(BuiltInType::Long) => true,
+        TypeKind::BuiltIn(BuiltInType::Array(inner)) | TypeKind::Nullable(inner) => has_long(inner),
+        TypeKind::BuiltIn(BuiltInType::Map(key, value)) => has_long(key) || has_long(value),
+        TypeKind::Union(union) => union.types.iter().any(has_long),
+        TypeKind::Function { params, ret } => params.iter().any(has_long) || has_long(ret),
+        TypeKind::Tuple(types) => types.iter().any(has_long),
+        TypeKind::Object(shape) => shape.properties.iter().any(|p| has_long(&p.ty)),
+        _ => false,
+    }
+}
+
+fn process_type(ty: &TypeKind) -> String {
+    match ty {
+        TypeKind::Union(union) => {
+            // `?string` can't be part of a union, it is spelled out as `string|null` instead.
+            let mut types: Vec<String> = Vec::new();
+            for ty in union.types.iter() {
+                let (ty, nullable) = match ty {
+                    TypeKind::Nullable(inner) => (inner.as_ref(), true),
+                    ty => (ty, false),
+                };
+                for name in [Some(process_type(ty)), nullable.then(|| "null".to_string())]
+                    .into_iter()
+                    .flatten()
+                {
+                    if !types.contains(&name) {
+                        types.push(name);
+                    }
+                }
+            }
+            if types.iter().any(|t| t == "mixed") {
+                "mixed".to_string()
+            } else {
+                types.join("|")
+            }
+        }
+        TypeKind::Function { .. } => "callable".to_string(),
+        TypeKind::Tuple(_) | TypeKind::Object(_) => "array".to_string(),
+        TypeKind::Nullable(inner) => match process_type(inner) {
+            // `mixed` already includes null.
+            inner if inner == "mixed" => inner,
+            inner if inner.contains('|') => format!("{}|null", inner),
+            inner => format!("?{}", inner),
+        },
+        TypeKind::Reference(reference) => reference.name.clone(),
+        TypeKind::RuntimeType(_) => "mixed".to_string(),
+        TypeKind::BuiltIn(built_in) => match built_in {
+            BuiltInType::Strict(_)
+            | BuiltInType::Byte
+            | BuiltInType::Short
+            | BuiltInType::Int
+            | BuiltInType::Long => "int".to_string(),
+            BuiltInType::Float | BuiltInType::Double => "float".to_string(),
+            BuiltInType::Bool => "bool".to_string(),
+            BuiltInType::String => "string".to_string(),
+            BuiltInType::Array(_) | BuiltInType::Map(..) => "array".to_string(),
+            BuiltInType::Any => "mixed".to_string(),
+            BuiltInType::Null => "null".to_string(),
+        },
+    }
+}
+
+/// The docblock type of a type, which unlike the type hint keeps the signature of a
+/// function type or the shape of a tuple or object, eg: `callable(int, string): bool`
+fn docblock_type(ty: &TypeKind) -> String {
+    match ty {
+        TypeKind::Tuple(types) => format!(
+            "array{{{}}}",
+            types
+                .iter()
+                .map(docblock_type)
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+        TypeKind::Function { params, ret } => format!(
+            "callable({}): {}",
+            params
+                .iter()
+                .map(docblock_type)
+                .collect::<Vec<String>>()
+                .join(", "),
+            docblock_type(ret)
+        ),
+        TypeKind::Object(shape) => format!(
+            "array{{{}}}",
+            shape
+                .properties
+                .iter()
+                .map(|p| format!("{}: {}", p.name, docblock_type(&p.ty)))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+        TypeKind::Nullable(inner) if has_function_type(inner) => {
+            format!("({})|null", docblock_type(inner))
+        }
+        TypeKind::Nullable(inner) if needs_docblock(inner) => {
+            format!("{}|null", docblock_type(inner))
+        }
+        TypeKind::Union(union) => union
+            .types
+            .iter()
+            .map(|ty| match ty {
+                TypeKind::Function { .. } => format!("({})", docblock_type(ty)),
+                ty => docblock_type(ty),
+            })
+            .collect::<Vec<String>>()
+            .join("|"),
+        TypeKind::BuiltIn(BuiltInType::Array(inner)) if needs_docblock(inner) => {
+            format!("array<{}>", docblock_type(inner))
+        }
+        TypeKind::BuiltIn(BuiltInType::Array(inner)) if !is_any(inner) => {
+            match process_type(inner) {
+                inner if inner.contains('|') || inner.starts_with('?') => format!("({})[]", inner),
+                inner => format!("{}[]", inner),
+            }
+        }
+        TypeKind::BuiltIn(BuiltInType::Map(key, value)) => {
+            format!("array<{}, {}>", docblock_type(key), docblock_type(value))
+        }
+        ty => process_type(ty),
+    }
+}
+
+/// Whether the type contains a function type, which only a docblock can describe.
+fn has_function_type(ty: &TypeKind) -> bool {
+    match ty {
+        TypeKind::Function { .. } => true,
+        TypeKind::Nullable(inner) => has_function_type(inner),
+        TypeKind::Union(union) => union.types.iter().any(has_function_type),
+        TypeKind::BuiltIn(BuiltInType::Array(inner)) => has_function_type(inner),
+        TypeKind::BuiltIn(BuiltInType::Map(key, value)) => {
+            has_function_type(key) || has_function_type(value)
         }
-        return output;
+        _ => false,
     }
+}
+
+/// Whether the type contains a function type, a tuple, an object shape or the types of
+/// the values of an array or a map, the type hint loses all of them.
+fn needs_docblock(ty: &TypeKind) -> bool {
+    match ty {
+        TypeKind::Tuple(_) | TypeKind::Object(_) => true,
+        TypeKind::Nullable(inner) => needs_docblock(inner),
+        TypeKind::Union(union) => union.types.iter().any(needs_docblock),
+        TypeKind::BuiltIn(BuiltInType::Array(inner)) => !is_any(inner),
+        TypeKind::BuiltIn(BuiltInType::Map(key, value)) => !is_any(key) || !is_any(value),
+        ty => has_function_type(ty),
+    }
+}
+
+fn is_any(ty: &TypeKind) -> bool {
+    matches!(ty, TypeKind::BuiltIn(BuiltInType::Any))
+}
+
+/// Replaces the type parameters of a declaration with their bounds, an unbounded one
+/// becomes `any`, which php knows as `mixed`.
+/// A union with `any` in it becomes `any` as a whole, php doesn't allow `mixed` in a union.
+fn erase(ty: &TypeKind, generics: &[TypeParam]) -> TypeKind {
+    match ty {
+        TypeKind::Reference(reference) => {
+            match generics
+                .iter()
+                .find(|g| g.name.as_ref() == Some(&reference.name))
+            {
+                // the parameters a bound names are `any`, eg: `T` in `T extends Comparable<T>`.
+                Some(g) => {
+                    let unbounded = generics
+                        .iter()
+                        .filter_map(|g| g.name.clone())
+                        .map(|name| TypeParam::named(name, TypeKind::BuiltIn(BuiltInType::Any)))
+                        .collect::<Vec<TypeParam>>();
+                    erase(&g.kind, &unbounded)
+                }
+                None => ty.clone(),
+            }
+        }
+        TypeKind::Union(union) => {
+            let types = union
+                .types
+                .iter()
+                .map(|ty| erase(ty, generics))
+                .collect::<Vec<TypeKind>>();
+            if types
+                .iter()
+                .any(|ty| matches!(ty, TypeKind::BuiltIn(BuiltInType::Any)))
+            {
+                TypeKind::BuiltIn(BuiltInType::Any)
+            } else {
+                TypeKind::Union(Box::new(TypeUnion { types }))
+            }
+        }
+        TypeKind::BuiltIn(BuiltInType::Array(inner)) => {
+            TypeKind::BuiltIn(BuiltInType::Array(Box::new(erase(inner, generics))))
+        }
+        TypeKind::BuiltIn(BuiltInType::Map(key, value)) => TypeKind::BuiltIn(BuiltInType::Map(
+            Box::new(erase(key, generics)),
+            Box::new(erase(value, generics)),
+        )),
+        TypeKind::Nullable(inner) => TypeKind::nullable(erase(inner, generics)),
+        TypeKind::Function { params, ret } => TypeKind::function(
+            params.iter().map(|ty| erase(ty, generics)).collect(),
+            erase(ret, generics),
+        ),
+        TypeKind::Tuple(types) => {
+            TypeKind::Tuple(types.iter().map(|ty| erase(ty, generics)).collect())
+        }
+        TypeKind::Object(shape) => TypeKind::Object(ObjectShape::new(
+            shape
+                .properties
+                .iter()
+                .map(|p| ShapeProperty {
+                    name: p.name.clone(),
+                    ty: erase(&p.ty, generics),
+                })
+                .collect(),
+        )),
+        other => other.clone(),
+    }
+}
+
+/// `depth` bounds how many aliases are followed, so an alias of itself is left as is.
+fn resolve_aliases(ty: &TypeKind, aliases: &HashMap<String, TypeKind>, depth: usize) -> TypeKind {
+    let resolve = |ty: &TypeKind| resolve_aliases(ty, aliases, depth);
+    match ty {
+        TypeKind::Reference(reference) if depth > 0 && reference.params.is_none() => {
+            match aliases.get(&reference.name) {
+                Some(alias) => resolve_aliases(alias, aliases, depth - 1),
+                None => ty.clone(),
+            }
+        }
+        TypeKind::Union(union) => TypeKind::union(union.types.iter().map(resolve).collect()),
+        TypeKind::BuiltIn(BuiltInType::Array(inner)) => {
+            TypeKind::BuiltIn(BuiltInType::Array(Box::new(resolve(inner))))
+        }
+        TypeKind::BuiltIn(BuiltInType::Map(key, value)) => TypeKind::BuiltIn(BuiltInType::Map(
+            Box::new(resolve(key)),
+            Box::new(resolve(value)),
+        )),
+        TypeKind::Nullable(inner) => TypeKind::nullable(resolve(inner)),
+        TypeKind::Function { params, ret } => {
+            TypeKind::function(params.iter().map(resolve).collect(), resolve(ret))
+        }
+        TypeKind::Tuple(types) => TypeKind::Tuple(types.iter().map(resolve).collect()),
+        TypeKind::Object(shape) => TypeKind::Object(ObjectShape::new(
+            shape
+                .properties
+                .iter()
+                .map(|p| ShapeProperty {
+                    name: p.name.clone(),
+                    ty: resolve(&p.ty),
+                })
+                .collect(),
+        )),
+        other => other.clone(),
+    }
+}
+
+/// The function that checks the values of a runtime type, eg: `assert_Positive`.
+fn runtime_check(name: &str) -> String {
+    format!("assert_{}", name)
+}
+
+/// The method an accessor is generated as, eg: `getName()` for `get` of `name`.
+fn accessor_method(property: &ClassProperty, accessor: &Accessor) -> Function {
+    let name = accessor_name(accessor.kind, &property.name);
+    let ty = property.ty.clone();
+    match (&accessor.body, accessor.kind) {
+        (Some(body), kind) => {
+            let mut method = body.clone();
+            method.name = Some(name);
+            match kind {
+                AccessorKind::Get => method.outputs = method.outputs.or(ty),
+                AccessorKind::Set => {
+                    if let Some(input) = method.inputs.first_mut() {
+                        input.ty = input.ty.take().or(ty);
+                    }
+                }
+            }
+            method
+        }
+        (None, AccessorKind::Get) => {
+            let getter =
+                FunctionBuilder::new(&name).returning(expr::member("this", &property.name));
+            match ty {
+                Some(ty) => getter.returns(ty).build(),
+                None => getter.build(),
+            }
+        }
+        (None, AccessorKind::Set) => FunctionBuilder::new(&name)
+            .input("value", ty.unwrap_or(TypeKind::BuiltIn(BuiltInType::Any)))
+            .expression(expr::assign(
+                expr::member("this", &property.name),
+                expr::name("value"),
+            ))
+            .build(),
+    }
+}
+
+/// The name of the method of an accessor, eg: `getName`.
+fn accessor_name(kind: AccessorKind, property: &str) -> String {
+    let mut chars = property.chars();
+    let capitalized = match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+        None => String::new(),
+    };
+    format!("{}{}", kind, capitalized)
+}
+
+/// The accessors of the properties of every class of a body, by class and property.
+fn property_accessors(body: &AstBody) -> HashMap<String, HashMap<String, Vec<AccessorKind>>> {
+    struct Collector(HashMap<String, HashMap<String, Vec<AccessorKind>>>);
+    impl Visitor for Collector {
+        fn visit_class(&mut self, class: &Class) {
+            let properties = class
+                .body
+                .all_properties()
+                .filter(|property| !property.accessors.is_empty())
+                .map(|property| {
+                    let kinds = property.accessors.iter().map(|a| a.kind).collect();
+                    (property.name.clone(), kinds)
+                })
+                .collect();
+            self.0.insert(class.name.clone(), properties);
+            visit::walk_class(self, class);
+        }
+    }
+    let mut collector = Collector(HashMap::new());
+    visit::walk_body(&mut collector, body);
+    collector.0
+}
+
+fn static_properties(body: &AstBody) -> HashMap<String, HashSet<String>> {
+    struct Collector(HashMap<String, HashSet<String>>);
+    impl Visitor for Collector {
+        fn visit_class(&mut self, class: &Class) {
+            let properties = class
+                .body
+                .static_properties()
+                .map(|property| property.name.clone())
+                .collect();
+            self.0.insert(class.name.clone(), properties);
+            visit::walk_class(self, class);
+        }
+    }
+    let mut collector = Collector(HashMap::new());
+    visit::walk_body(&mut collector, body);
+    collector.0
+}
+
+fn erase_member(member: &mut ClassAllowedStatement, generics: &[TypeParam]) {
+    match member {
+        ClassAllowedStatement::Property(property) => {
+            property.ty = property.ty.as_ref().map(|ty| erase(ty, generics));
+        }
+        ClassAllowedStatement::Method(method) => erase_function(method, generics),
+        ClassAllowedStatement::Constant(constant) => {
+            constant.ty = constant.ty.as_ref().map(|ty| erase(ty, generics));
+        }
+        ClassAllowedStatement::Static(inner) => erase_member(inner, generics),
+        ClassAllowedStatement::Import(_) | ClassAllowedStatement::Macro(_) => {}
+    }
+}
+
+fn erase_function(func: &mut Function, generics: &[TypeParam]) {
+    for input in func.inputs.iter_mut() {
+        input.ty = input.ty.as_ref().map(|ty| erase(ty, generics));
+    }
+    func.outputs = func.outputs.as_ref().map(|ty| erase(ty, generics));
+}
+
+fn is_public(visibility: &Visibility) -> bool {
+    matches!(visibility, Visibility::Public | Visibility::Module)
+}
+
+fn process_visibility(visibility: &Visibility) -> &'static str {
+    match visibility {
+        Visibility::Public | Visibility::Module => "public",
+        Visibility::Private => "private",
+        Visibility::Protected => "protected",
+    }
+}
+
+fn process_path(path: &Path) -> String {
+    match path.parts.len() {
+        0 => match &path.alias {
+            Some(alias) => format!("{} as {}", path.name, alias),
+            None => path.name.to_string(),
+        },
+        1 => format!("{}\\{}", path.name, process_path(&path.parts[0])),
+        _ => format!(
+            "{}\\{{{}}}",
+            path.name,
+            path.parts
+                .iter()
+                .map(process_path)
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+    }
+}
+
+/// A double quoted php string of a value, `$` is escaped so php doesn't interpolate it.
+fn php_string(value: &str) -> String {
+    let mut string = String::from('"');
+    for c in value.chars() {
+        match c {
+            '\n' => string.push_str("\\n"),
+            '\t' => string.push_str("\\t"),
+            '\r' => string.push_str("\\r"),
+            '\\' => string.push_str("\\\\"),
+            '"' => string.push_str("\\\""),
+            '$' => string.push_str("\\$"),
+            c if c.is_control() => string.push_str(&format!("\\u{{{:X}}}", c as u32)),
+            c => string.push(c),
+        }
+    }
+    string.push('"');
+    string
+}
+
+fn process_unary(op: &UnaryOp, operand: String) -> String {
+    match op {
+        UnaryOp::IncP => format!("++{}", operand),
+        UnaryOp::Inc => format!("{}++", operand),
+        UnaryOp::DecP => format!("--{}", operand),
+        UnaryOp::Dec => format!("{}--", operand),
+        UnaryOp::Neg => format!("-{}", operand),
+        UnaryOp::Pos => format!("+{}", operand),
+        UnaryOp::Not => format!("!{}", operand),
+        UnaryOp::Delete => format!("unset({})", operand),
+        UnaryOp::Object => format!("{} = []", operand),
+    }
+}
 
-    fn generate(&mut self, _path: &str, _options: CompilerOptions) -> Result<(), String> {
-        unimplemented!()
+fn operator(op: &AnyOperation) -> &'static str {
+    match op {
+        AnyOperation::BinOp(op) => match op {
+            BinOp::Plus => "+",
+            BinOp::Minus => "-",
+            BinOp::Star => "*",
+            BinOp::Slash => "/",
+            BinOp::Percent => "%",
+            BinOp::Caret => "^",
+            BinOp::Not => "!",
+            BinOp::Flip => "~",
+            BinOp::And => "&",
+            BinOp::Or => "|",
+            BinOp::Shl => "<<",
+            BinOp::Shr | BinOp::UShr => ">>",
+        },
+        AnyOperation::LogicalOp(op) => match op {
+            LogicalOp::And => "&&",
+            LogicalOp::Or => "||",
+            LogicalOp::Coalasce => "??",
+        },
+        AnyOperation::ComparisonOp(op) => match op {
+            ComparisonOp::Eq => "===",
+            ComparisonOp::NotEq => "!==",
+            ComparisonOp::GreaterThan => ">",
+            ComparisonOp::GreaterThanOrEqual => ">=",
+            ComparisonOp::LessThan => "<",
+            ComparisonOp::LessThanOrEqual => "<=",
+            // lowered to `in_array` by `process_expression`.
+            ComparisonOp::Contains | ComparisonOp::In => "",
+            ComparisonOp::InstanceOf => "instanceof",
+        },
+        AnyOperation::AssignmentOp(op) => match op {
+            AssignmentOp::Eq => "=",
+            AssignmentOp::Add => "+=",
+            AssignmentOp::Sub => "-=",
+            AssignmentOp::Mul => "*=",
+            AssignmentOp::Div => "/=",
+            AssignmentOp::Rem => "%=",
+            AssignmentOp::BitAnd => "&=",
+            AssignmentOp::BitOr => "|=",
+            AssignmentOp::BitXor => "^=",
+            AssignmentOp::BitSh1 => "<<=",
+            AssignmentOp::BitShr | AssignmentOp::BitUshr => ">>=",
+            AssignmentOp::BoolAnd => "&&=",
+            AssignmentOp::BoolOr => "||=",
+            AssignmentOp::Coalesce => "??=",
+        },
+        AnyOperation::UnaryOp(_) => "",
     }
 }
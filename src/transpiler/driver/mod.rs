@@ -0,0 +1,477 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    compiler::{
+        ast::{
+            visit::{self, Visitor},
+            AstBody, Class, ClassAllowedStatement, Statement,
+        },
+        lower::lower,
+        optimize::optimize,
+        parser::Parser,
+        CompilerOptions,
+    },
+    report::{diagnostic::Diagnostic, Report, ReportKind},
+    util::{
+        provider::{collect_sources, SourceProvider},
+        source::SourceBuffer,
+    },
+};
+
+use super::{
+    format::FormatOptions,
+    langs::Generator,
+    sourcemap::{SourceMap, SIDECAR_EXTENSION},
+};
+
+pub use crate::util::provider::SOURCE_EXTENSION;
+
+/// An error that occurred while transpiling a single file.
+#[derive(Debug)]
+pub enum FileError {
+    /// The file could not be read, or the output could not be written.
+    Io(String),
+    /// The file could not be parsed, or uses something the language can't generate.
+    Parse(Report),
+}
+
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FileError::Io(message) => write!(f, "{}", message),
+            FileError::Parse(report) => write!(f, "{}", report.message),
+        }
+    }
+}
+
+/// The outcome of transpiling a single source file.
+#[derive(Debug)]
+pub struct FileOutput {
+    /// The source file that was transpiled.
+    pub source: PathBuf,
+    /// Where the generated code was (or would have been) written.
+    pub output: PathBuf,
+    /// The notices of the file when it was transpiled.
+    pub result: Result<Vec<Report>, FileError>,
+}
+
+/// The outcome of transpiling a source tree.
+/// Every file is attempted, a failing file does not stop the rest of the batch.
+#[derive(Debug)]
+pub struct BatchReport {
+    pub files: Vec<FileOutput>,
+}
+
+impl BatchReport {
+    /// Whether or not every file was transpiled.
+    pub fn is_ok(&self) -> bool {
+        self.files.iter().all(|f| f.result.is_ok())
+    }
+
+    /// The files that failed to transpile.
+    pub fn failed(&self) -> Vec<&FileOutput> {
+        self.files.iter().filter(|f| f.result.is_err()).collect()
+    }
+
+    /// The warnings and notices of the files that were transpiled that fail the build,
+    /// see `CompilerOptions::fails`.
+    pub fn failing(&self, options: &CompilerOptions) -> Vec<&Report> {
+        self.files
+            .iter()
+            .filter_map(|f| f.result.as_ref().ok())
+            .flatten()
+            .filter(|notice| options.fails(notice.kind))
+            .collect()
+    }
+
+    /// Prints the notices of every file that was transpiled.
+    pub fn print_notices(&self) {
+        for file in self.files.iter() {
+            if let Ok(notices) = &file.result {
+                notices.iter().for_each(Report::print);
+            }
+        }
+    }
+
+    /// Prints the error of every file that failed.
+    pub fn print_errors(&self) {
+        for file in self.failed() {
+            match file.result.as_ref().unwrap_err() {
+                FileError::Parse(report) => report.print(),
+                FileError::Io(message) => {
+                    eprintln!("Error! {} [{}]", message, file.source.display())
+                }
+            }
+        }
+    }
+}
+
+/// The directory a source tree is mirrored to when no output directory is given.
+/// This is an `out` directory next to the source directory.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use std::path::Path;
+/// # use surn::transpiler::driver::default_output_root;
+/// default_output_root(Path::new("project/src")); // project/out
+/// ```
+pub fn default_output_root(source_root: &Path) -> PathBuf {
+    match source_root.parent() {
+        Some(parent) => parent.join("out"),
+        None => PathBuf::from("out"),
+    }
+}
+
+/// The shared implementation of `Generator::generate_with`.
+/// - A file is transpiled next to itself, eg: `main.surn` -> `main.php`
+/// - A directory is mirrored to its `default_output_root`.
+///
+/// Sources are read and generated code is written with the provider.
+/// Errors of every failing file are printed before the summary error is returned.
+pub fn generate_path(
+    provider: &dyn SourceProvider,
+    generator: &dyn Generator,
+    path: &str,
+    extension: &str,
+    options: &CompilerOptions,
+    format: &FormatOptions,
+) -> Result<(), String> {
+    let path = Path::new(path);
+    if provider.is_dir(path) {
+        let report = generate_tree(
+            provider,
+            generator,
+            path,
+            &default_output_root(path),
+            extension,
+            options,
+            format,
+        )?;
+        report.print_notices();
+        report.print_errors();
+        if !report.is_ok() {
+            Err(format!(
+                "{} of {} files failed to transpile.",
+                report.failed().len(),
+                report.files.len()
+            ))
+        } else {
+            failing_notices(report.failing(options).len(), options)
+        }
+    } else {
+        let name = path.file_name().map(PathBuf::from).unwrap_or_default();
+        let result = generate_file(
+            provider,
+            generator,
+            path,
+            &name,
+            &path.with_extension(extension),
+            options,
+            format,
+        );
+        match &result {
+            Ok(notices) => notices.iter().for_each(Report::print),
+            Err(FileError::Parse(report)) => report.print(),
+            Err(FileError::Io(_)) => {}
+        }
+        let notices = result.map_err(|e| e.to_string())?;
+        failing_notices(
+            notices.iter().filter(|n| options.fails(n.kind)).count(),
+            options,
+        )
+    }
+}
+
+/// Fails a build that transpiled with warnings or notices the options fail on.
+pub fn failing_notices(count: usize, options: &CompilerOptions) -> Result<(), String> {
+    match count {
+        0 => Ok(()),
+        count => Err(format!(
+            "{} reports fail the build, it fails on {}s, see `fail_on`.",
+            count,
+            options.fail_on.to_string().to_lowercase()
+        )),
+    }
+}
+
+/// Transpiles every `.surn` file under `source_root` and writes the generated
+/// code to the same relative location under `output_root`, with the given extension.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use std::path::Path;
+/// # use surn::compiler::CompilerOptions;
+/// # use surn::transpiler::{driver::generate_tree, format::FormatOptions, Transpiler};
+/// # use surn::util::provider::OsProvider;
+/// # let mut transpiler = Transpiler::new();
+/// # transpiler.register_defaults();
+/// # let generator = transpiler.get("php").unwrap().generator.as_ref();
+/// # let (options, format) = (CompilerOptions::default(), FormatOptions::default());
+/// // src/main.surn      -> out/main.php
+/// // src/http/req.surn  -> out/http/req.php
+/// let report = generate_tree(&OsProvider, generator, Path::new("src"), Path::new("out"), "php", &options, &format)?;
+/// # Ok::<(), String>(())
+/// ```
+///
+/// An `Err` is only returned if the source tree itself can not be read.
+pub fn generate_tree(
+    provider: &dyn SourceProvider,
+    generator: &dyn Generator,
+    source_root: &Path,
+    output_root: &Path,
+    extension: &str,
+    options: &CompilerOptions,
+    format: &FormatOptions,
+) -> Result<BatchReport, String> {
+    let mut sources: Vec<PathBuf> = Vec::new();
+    collect_sources(provider, source_root, &mut sources)?;
+    // keep the batch order stable regardless of the file system.
+    sources.sort();
+
+    // the files are read and parsed together, then generated one by one in order.
+    let mut contents: Vec<Result<String, FileError>> = Vec::new();
+    let mut scripts: Vec<(String, String)> = Vec::new();
+    for source in sources.iter() {
+        let relative = source.strip_prefix(source_root).unwrap();
+        match read_source(provider, source) {
+            Ok(text) => {
+                scripts.push((relative.to_string_lossy().to_string(), text.clone()));
+                contents.push(Ok(text));
+            }
+            Err(e) => contents.push(Err(e)),
+        }
+    }
+    let mut parser = Parser::new(options.clone());
+    // the include paths are parsed first, every file fails when they don't parse.
+    let parsed = parser
+        .parse_includes_from(provider)
+        .and_then(|_| parser.parse_scripts(scripts));
+    let (mut parsed, includes) = match parsed {
+        Ok(parsed) => (parsed.into_iter(), None),
+        Err(report) => (Vec::new().into_iter(), Some(report)),
+    };
+
+    let mut files: Vec<FileOutput> = Vec::new();
+    for (source, text) in sources.into_iter().zip(contents) {
+        let relative = source.strip_prefix(source_root).unwrap().to_path_buf();
+        let output = output_root.join(&relative).with_extension(extension);
+        let result = text.and_then(|text| {
+            if let Some(report) = &includes {
+                return Err(FileError::Parse(report.clone()));
+            }
+            let script = parsed.next().expect("every file that was read is parsed");
+            let ast = script.result.map_err(FileError::Parse)?;
+            let mut notices = warning_notices(&script.warnings, &relative, &text);
+            notices.extend(emit_file(
+                provider, generator, ast, &relative, &text, &output, options, format,
+            )?);
+            Ok(notices)
+        });
+        files.push(FileOutput {
+            source,
+            output,
+            result,
+        });
+    }
+
+    Ok(BatchReport { files })
+}
+
+/// Transpiles a single file to the given output path, creating any missing directories.
+/// When `options.source_maps` is set, the source map is written next to the output,
+/// eg: `out/main.php.map`
+///
+/// The lint warnings of the source and the notices of the places the generated code
+/// loses the meaning of the source are returned.
+pub fn generate_file(
+    provider: &dyn SourceProvider,
+    generator: &dyn Generator,
+    source: &Path,
+    name: &Path,
+    output: &Path,
+    options: &CompilerOptions,
+    format: &FormatOptions,
+) -> Result<Vec<Report>, FileError> {
+    let contents = read_source(provider, source)?;
+    let mut parser = Parser::new(options.clone());
+    parser
+        .parse_includes_from(provider)
+        .map_err(FileError::Parse)?;
+    let ast = parser
+        .try_parse_script(name.to_string_lossy().to_string(), contents.clone())
+        .map_err(FileError::Parse)?;
+    let mut notices = warning_notices(parser.warnings(), name, &contents);
+    notices.extend(emit_file(
+        provider, generator, ast, name, &contents, output, options, format,
+    )?);
+    Ok(notices)
+}
+
+fn read_source(provider: &dyn SourceProvider, source: &Path) -> Result<String, FileError> {
+    provider
+        .load(source)
+        .map_err(|e| FileError::Io(format!("Could not read source file: {}", e)))
+}
+
+/// The lint warnings of a source as reports.
+pub(crate) fn warning_notices(warnings: &[Diagnostic], name: &Path, contents: &str) -> Vec<Report> {
+    warnings
+        .iter()
+        .map(|warning| {
+            warning.clone().into_report(
+                name.to_string_lossy().to_string(),
+                SourceBuffer::new(contents.to_string()),
+            )
+        })
+        .collect()
+}
+
+/// Generates the code of a parsed source and writes it to the output path,
+/// the notices of the places the generated code loses the meaning of the source are returned.
+#[allow(clippy::too_many_arguments)]
+fn emit_file(
+    provider: &dyn SourceProvider,
+    generator: &dyn Generator,
+    ast: AstBody,
+    name: &Path,
+    contents: &str,
+    output: &Path,
+    options: &CompilerOptions,
+    format: &FormatOptions,
+) -> Result<Vec<Report>, FileError> {
+    let (code, mut map, notices) = generate_source(generator, ast, name, contents, options, format)
+        .map_err(FileError::Parse)?;
+    provider
+        .write(output, &code)
+        .map_err(|e| FileError::Io(format!("Could not write output file: {}", e)))?;
+
+    if options.source_maps {
+        map.source = name.to_string_lossy().to_string();
+        map.file = output
+            .file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_default();
+        provider
+            .write(&sidecar_path(output), &map.to_json(contents))
+            .map_err(|e| FileError::Io(format!("Could not write source map: {}", e)))?;
+    }
+    Ok(notices)
+}
+
+/// Generates the code of a parsed source without writing it, along with its source map
+/// and the notices of the places the generated code loses the meaning of the source.
+/// The report of an unsupported macro or a denied lowering is returned instead.
+pub fn generate_source(
+    generator: &dyn Generator,
+    mut ast: AstBody,
+    name: &Path,
+    contents: &str,
+    options: &CompilerOptions,
+    format: &FormatOptions,
+) -> Result<(String, SourceMap, Vec<Report>), Report> {
+    check_macros(generator, &ast, name, contents)?;
+    let notices = lossy_notices(generator, &ast, name, contents, options)?;
+    lower(&mut ast, options);
+    optimize(&mut ast, options);
+    let (code, map) = generator.generate_with_map(ast, options.clone(), format);
+    Ok((code, map, notices))
+}
+
+/// Reports the lossy lowerings of the body at the level of their lint.
+/// The first denied lowering is returned as an error.
+fn lossy_notices(
+    generator: &dyn Generator,
+    ast: &AstBody,
+    name: &Path,
+    contents: &str,
+    options: &CompilerOptions,
+) -> Result<Vec<Report>, Report> {
+    let mut notices: Vec<Report> = Vec::new();
+    for lowering in generator.lossy_lowerings(ast) {
+        let Some(mut diagnostic) = options
+            .lints
+            .apply(Diagnostic::from(lowering), ast.lint_scopes())
+        else {
+            continue;
+        };
+        let denied = diagnostic.is_error();
+        if !denied {
+            diagnostic.severity = ReportKind::Notice;
+        }
+        let report = diagnostic.into_report(
+            name.to_string_lossy().to_string(),
+            SourceBuffer::new(contents.to_string()),
+        );
+        if denied {
+            return Err(report);
+        }
+        notices.push(report);
+    }
+    Ok(notices)
+}
+
+/// Checks that every macro the body invokes is supported by the generator.
+/// The report points at the top level node the first unsupported invocation is in.
+fn check_macros(
+    generator: &dyn Generator,
+    ast: &AstBody,
+    name: &Path,
+    contents: &str,
+) -> Result<(), Report> {
+    let mut collector = MacroCollector { names: Vec::new() };
+    for node in ast.get_program() {
+        visit::walk_node(&mut collector, node);
+        if let Some(invoked) = collector
+            .names
+            .drain(..)
+            .find(|m| !generator.supports_macro(m))
+        {
+            return Err(Report::new()
+                .set_source(SourceBuffer::new(contents.to_string()))
+                .set_name(name.to_string_lossy().to_string())
+                .set_message(format!(
+                    "The `{}!` macro is not supported by this language.",
+                    invoked
+                ))
+                .make_snippet(
+                    node.start()..node.end(),
+                    format!("`{}!` is invoked here.", invoked),
+                    None,
+                ));
+        }
+    }
+    Ok(())
+}
+
+struct MacroCollector {
+    names: Vec<String>,
+}
+
+impl Visitor for MacroCollector {
+    fn visit_statement(&mut self, stmt: &Statement) {
+        if let Statement::MacroInvocation(invocation) = stmt {
+            self.names.push(invocation.name.clone());
+        }
+        visit::walk_statement(self, stmt);
+    }
+
+    fn visit_class(&mut self, class: &Class) {
+        for stmt in class.body.other.iter() {
+            if let ClassAllowedStatement::Macro(invocation) = stmt {
+                self.names.push(invocation.name.clone());
+            }
+        }
+        visit::walk_class(self, class);
+    }
+}
+
+/// The path of the source map sidecar for a generated file.
+pub fn sidecar_path(output: &Path) -> PathBuf {
+    let mut path = output.as_os_str().to_owned();
+    path.push(".");
+    path.push(SIDECAR_EXTENSION);
+    PathBuf::from(path)
+}
@@ -1,9 +1,55 @@
+pub mod printer;
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum BraceType {
     Allman,
     KandR,
     AllmanMix,
 }
 
+impl BraceType {
+    /// The style of a name, eg: `allman` or `k&r`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "allman" => Some(BraceType::Allman),
+            "k&r" | "kandr" => Some(BraceType::KandR),
+            "allman-mix" => Some(BraceType::AllmanMix),
+            _ => None,
+        }
+    }
+
+    /// Whether or not the opening brace goes on its own line.
+    pub fn opens_on_new_line(&self) -> bool {
+        match self {
+            BraceType::Allman | BraceType::AllmanMix => true,
+            BraceType::KandR => false,
+        }
+    }
+}
+
+/// How the accessors of properties are generated, eg: `name: string { get; set; }`.
+/// The property itself is always private.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccessorStyle {
+    /// `__get` and `__set` read and write the property, so accessing it doesn't change.
+    Magic,
+    /// `getName()` and `setName()` read and write the property, they are called
+    /// instead of accessing it on values whose class is known.
+    Methods,
+}
+
+impl AccessorStyle {
+    /// The style of a name, eg: `magic` or `methods`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "magic" => Some(AccessorStyle::Magic),
+            "methods" => Some(AccessorStyle::Methods),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct FormatOptions {
     pub tab_size: usize,
     pub indent_size: usize,
@@ -17,10 +63,12 @@ pub struct FormatOptions {
     pub while_brace: BraceType,
     pub for_brace: BraceType,
     pub match_brace: BraceType,
+    pub try_brace: BraceType,
+    pub accessors: AccessorStyle,
 }
 
 impl FormatOptions {
-    pub fn PSR_4() -> Self {
+    pub fn psr_4() -> Self {
         FormatOptions {
             tab_size: 4,
             indent_size: 4,
@@ -34,10 +82,12 @@ impl FormatOptions {
             while_brace: BraceType::Allman,
             for_brace: BraceType::Allman,
             match_brace: BraceType::Allman,
+            try_brace: BraceType::Allman,
+            accessors: AccessorStyle::Magic,
         }
     }
 
-    pub fn Rust() -> Self {
+    pub fn rust() -> Self {
         FormatOptions {
             tab_size: 4,
             indent_size: 4,
@@ -51,10 +101,56 @@ impl FormatOptions {
             while_brace: BraceType::KandR,
             for_brace: BraceType::KandR,
             match_brace: BraceType::KandR,
+            try_brace: BraceType::KandR,
+            accessors: AccessorStyle::Magic,
         }
     }
 
+    #[deprecated(note = "use `FormatOptions::psr_4` instead")]
+    #[allow(non_snake_case)]
+    pub fn PSR_4() -> Self {
+        Self::psr_4()
+    }
+
+    #[deprecated(note = "use `FormatOptions::rust` instead")]
+    #[allow(non_snake_case)]
+    pub fn Rust() -> Self {
+        Self::rust()
+    }
+
+    #[deprecated(note = "use `FormatOptions::default` instead")]
+    #[allow(non_snake_case)]
     pub fn Default() -> Self {
+        <Self as Default>::default()
+    }
+
+    /// The indentation for the given depth.
+    pub fn indent(&self, depth: usize) -> String {
+        " ".repeat(self.indent_size * depth)
+    }
+
+    /// The opening brace of a block whose header is at the given depth,
+    /// eg: ` {` for K&R or `\n{` for Allman.
+    pub fn open_brace(&self, style: &BraceType, depth: usize) -> String {
+        if style.opens_on_new_line() {
+            format!("{}{}{{", self.new_line, self.indent(depth))
+        } else {
+            " {".to_string()
+        }
+    }
+
+    /// Formats the name of a variable, converting it to snake case if `snake_case_vars` is set.
+    pub fn variable_name(&self, name: &str) -> String {
+        if self.snake_case_vars {
+            to_snake_case(name)
+        } else {
+            name.to_string()
+        }
+    }
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
         FormatOptions {
             tab_size: 4,
             indent_size: 4,
@@ -68,6 +164,37 @@ impl FormatOptions {
             while_brace: BraceType::KandR,
             for_brace: BraceType::KandR,
             match_brace: BraceType::KandR,
+            try_brace: BraceType::KandR,
+            accessors: AccessorStyle::Magic,
+        }
+    }
+}
+
+/// Converts a camel or pascal case identifier to snake case.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::transpiler::format::to_snake_case;
+/// to_snake_case("myHTTPValue"); // my_http_value
+/// ```
+pub fn to_snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut result = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            let prev = if i > 0 { chars.get(i - 1) } else { None };
+            let next = chars.get(i + 1);
+            // a new word starts after a lowercase letter, or at the last capital of an acronym.
+            let starts_word = prev.is_some_and(|p| p.is_lowercase() || p.is_numeric())
+                || (prev.is_some_and(|p| p.is_uppercase())
+                    && next.is_some_and(|n| n.is_lowercase()));
+            if starts_word && !result.ends_with('_') {
+                result.push('_');
+            }
+            result.extend(c.to_lowercase());
+        } else {
+            result.push(*c);
         }
     }
+    result
 }
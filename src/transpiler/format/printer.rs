@@ -0,0 +1,660 @@
+use crate::{
+    compiler::{
+        ast::{
+            ops::{AnyOperation, UnaryOp},
+            types::{BuiltInType, TypeKind, TypeParam},
+            AstBody, Attribute, Class, ClassAllowedStatement, ClassProperty, Expression, Function,
+            MemberLookup, Path, Statement, Variable, Visibility,
+        },
+        lexer::{
+            escape::escape,
+            tokenizer::tokenize,
+            trivia::{Comment, Trivia},
+        },
+        parser::Parser,
+        CompilerOptions,
+    },
+    report::Report,
+};
+
+use super::{BraceType, FormatOptions};
+
+/// Parses and formats the given surn source.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::transpiler::format::{printer::format_source, FormatOptions};
+/// let formatted = format_source("main.surn", "var   x=5;", &FormatOptions::default())?;
+/// // var x = 5;
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub fn format_source(name: &str, source: &str, options: &FormatOptions) -> Result<String, Report> {
+    let mut parser = Parser::new(CompilerOptions::default());
+    let body = parser.try_parse_script(name.to_string(), source.to_string())?;
+    Ok(Printer::new(source, options).print(&body))
+}
+
+/// Prints an `AstBody` back to canonical surn source.
+///
+/// The AST doesn't hold comments, so they are taken from the trivia of the source
+/// and placed around the nodes they were written next to.
+/// A single empty line between top level nodes is kept, any more are collapsed.
+pub struct Printer<'a> {
+    options: &'a FormatOptions,
+    chars: Vec<char>,
+    trivia: Trivia,
+    /// The index of the next comment that has not been printed.
+    next_comment: usize,
+    output: String,
+}
+
+impl<'a> Printer<'a> {
+    pub fn new(source: &str, options: &'a FormatOptions) -> Self {
+        Printer {
+            options,
+            chars: source.chars().collect(),
+            trivia: Trivia::collect(source, &tokenize(source)),
+            next_comment: 0,
+            output: String::new(),
+        }
+    }
+
+    pub fn print(mut self, body: &AstBody) -> String {
+        let mut prev_end: Option<usize> = None;
+        for node in body.get_program() {
+            if let Some(prev_end) = prev_end {
+                if self.trivia.has_blank_line(prev_end..node.start()) {
+                    self.newline();
+                }
+            }
+            self.leading_comments(node.start(), 0);
+
+            match &node.inner {
+                crate::compiler::ast::NodeKind::Statement(stmt) => {
+                    self.statement(stmt, 0, Some(node.end.start))
+                }
+                crate::compiler::ast::NodeKind::Expression(expr) => {
+                    self.indent(0);
+                    self.expression(expr);
+                    self.output.push(';');
+                }
+            }
+            self.trailing_comment(node.end());
+            self.newline();
+            prev_end = Some(node.end());
+        }
+        self.leading_comments(usize::MAX, 0);
+        self.output
+    }
+
+    fn newline(&mut self) {
+        self.output.push(self.options.new_line);
+    }
+
+    fn indent(&mut self, depth: usize) {
+        let indent = self.options.indent(depth);
+        self.output.push_str(&indent);
+    }
+
+    /// Prints every comment before the offset on its own line.
+    fn leading_comments(&mut self, before: usize, depth: usize) {
+        while let Some(comment) = self.comment_before(before) {
+            self.indent(depth);
+            self.output.push_str(&comment.text);
+            self.newline();
+            self.next_comment += 1;
+        }
+    }
+
+    /// Prints the comment that follows the offset on the same line, if any.
+    fn trailing_comment(&mut self, after: usize) {
+        if let Some(comment) = self.trivia.comments.get(self.next_comment).cloned() {
+            let same_line = comment.range.start >= after
+                && !self.chars[after..comment.range.start].contains(&'\n');
+            if !comment.own_line && same_line {
+                self.output.push(' ');
+                self.output.push_str(&comment.text);
+                self.next_comment += 1;
+            }
+        }
+    }
+
+    fn comment_before(&self, before: usize) -> Option<Comment> {
+        self.trivia
+            .comments
+            .get(self.next_comment)
+            .filter(|c| c.range.start < before)
+            .cloned()
+    }
+
+    /// Prints a statement starting at the current line, `end` is the offset
+    /// of the closing brace when it is known.
+    fn statement(&mut self, stmt: &Statement, depth: usize, end: Option<usize>) {
+        self.indent(depth);
+        self.statement_inline(stmt, depth, end);
+    }
+
+    fn statement_inline(&mut self, stmt: &Statement, depth: usize, end: Option<usize>) {
+        match stmt {
+            Statement::Var(var) => self.variable("var", var),
+            Statement::Const(var) => self.variable("const", var),
+            Statement::Destructure(destructure) => {
+                self.output
+                    .push_str(&format!("var ({})", destructure.names.join(", ")));
+                if let Some(ty) = &destructure.ty {
+                    self.output.push_str(&format!(": {}", ty));
+                }
+                self.output.push_str(" = ");
+                self.expression(&destructure.assignment);
+                self.output.push(';');
+            }
+            Statement::Static(s) => {
+                self.visibility(&s.visibility);
+                self.output.push_str("static ");
+                self.statement_inline(&s.statement, depth, end);
+            }
+            Statement::Function(func) => self.function(func, depth, end),
+            Statement::Class(class) => self.class(class, depth, end),
+            Statement::Enum(e) => {
+                self.output.push_str(&format!("enum {}", e.name));
+                self.open_brace(&self.options.class_brace.clone(), depth);
+                for variant in e.variants.iter() {
+                    self.indent(depth + 1);
+                    self.output.push_str(&variant.name);
+                    if let Some(value) = &variant.value {
+                        self.output.push_str(" = ");
+                        self.expression(value);
+                    }
+                    self.output.push(',');
+                    self.newline();
+                }
+                self.close_brace(depth, end);
+            }
+            Statement::Block(exprs) => {
+                self.output.push('{');
+                self.newline();
+                self.block(exprs, depth + 1);
+                self.close_brace(depth, end);
+            }
+            Statement::Import(path) => {
+                self.output.push_str(&format!("use {};", print_path(path)));
+            }
+            Statement::Namespace(namespace) => {
+                let path = std::iter::once(namespace.path.name.to_string())
+                    .chain(namespace.path.parts.iter().map(|p| p.name.to_string()))
+                    .collect::<Vec<String>>()
+                    .join("\\");
+                self.output.push_str(&format!("namespace {}", path));
+                match &namespace.body {
+                    Some(body) => {
+                        self.open_brace(&self.options.class_brace.clone(), depth);
+                        self.body(body, depth + 1);
+                        self.close_brace(depth, end);
+                        self.output.push(';');
+                    }
+                    None => self.output.push(';'),
+                }
+            }
+            Statement::TypeDef(def) => {
+                self.output.push_str(&format!("type {}", def.name));
+                if let Some(params) = &def.params {
+                    self.output.push_str(&print_generic_params(params));
+                }
+                match &def.kind {
+                    TypeKind::RuntimeType(runtime) => {
+                        if let Some(value) = runtime.params.iter().flatten().next() {
+                            let name = value.name.as_deref().unwrap_or("value");
+                            self.output.push_str(&format!("({}", name));
+                            if !matches!(value.kind, TypeKind::BuiltIn(BuiltInType::Any)) {
+                                self.output.push_str(&format!(": {}", value.kind));
+                            }
+                            self.output.push(')');
+                        }
+                        self.output.push_str(" = ");
+                        self.expression(&runtime.body);
+                        self.output.push(';');
+                    }
+                    kind => self.output.push_str(&format!(" = {};", kind)),
+                }
+            }
+            Statement::Return(ret) => {
+                self.output.push_str("return");
+                if let Some(expr) = &ret.expression {
+                    self.output.push(' ');
+                    self.expression(expr);
+                }
+                self.output.push(';');
+            }
+            Statement::If(i) => {
+                // an `else if` is printed on the line the `else` is on.
+                let mut blocks = Vec::new();
+                let mut next = Some(i);
+                let mut header = "if";
+                let mut otherwise = None;
+                while let Some(i) = next.take() {
+                    let condition = print_expression(&i.condition);
+                    let brace = self.options.if_brace.clone();
+                    blocks.push((
+                        format!("{} ({})", header, condition),
+                        brace,
+                        i.body.as_ref(),
+                    ));
+                    match i.otherwise.as_deref() {
+                        Some(Statement::If(inner)) => next = Some(inner),
+                        other => otherwise = other,
+                    }
+                    header = " else if";
+                }
+                if let Some(otherwise) = otherwise {
+                    let brace = self.options.else_brace.clone();
+                    blocks.push((" else".to_string(), brace, otherwise));
+                }
+                let last = blocks.len() - 1;
+                for (i, (header, brace, body)) in blocks.into_iter().enumerate() {
+                    self.output.push_str(&header);
+                    self.open_brace(&brace, depth);
+                    self.body(body, depth + 1);
+                    self.close_brace(depth, if i == last { end } else { None });
+                }
+            }
+            Statement::Try(t) => {
+                let mut blocks = vec![("try".to_string(), t.body.as_ref())];
+                for catch in t.catches.iter() {
+                    let header = match &catch.ty {
+                        Some(ty) => format!(" catch ({}: {})", catch.name, ty),
+                        None => format!(" catch ({})", catch.name),
+                    };
+                    blocks.push((header, catch.body.as_ref()));
+                }
+                if let Some(finally) = &t.finally {
+                    blocks.push((" finally".to_string(), finally.as_ref()));
+                }
+                let last = blocks.len() - 1;
+                for (i, (header, body)) in blocks.into_iter().enumerate() {
+                    self.output.push_str(&header);
+                    self.open_brace(&self.options.try_brace.clone(), depth);
+                    self.body(body, depth + 1);
+                    // comments after the last block may still be inside of it.
+                    self.close_brace(depth, if i == last { end } else { None });
+                }
+            }
+            Statement::Throw(expr) => {
+                self.output.push_str("throw ");
+                self.expression(expr);
+                self.output.push(';');
+            }
+            Statement::MacroInvocation(m) => {
+                self.output.push_str(&format!("{}! {{{}}}", m.name, m.body));
+            }
+        }
+    }
+
+    fn variable(&mut self, keyword: &str, var: &Variable) {
+        self.visibility(&var.visibility);
+        self.output.push_str(&format!("{} {}", keyword, var.name));
+        if let Some(ty) = &var.ty {
+            self.output.push_str(&format!(": {}", ty));
+        }
+        if let Some(assignment) = &var.assignment {
+            self.output.push_str(" = ");
+            self.expression(assignment);
+        }
+        self.output.push(';');
+    }
+
+    /// Private is the default visibility, so it is never written.
+    fn visibility(&mut self, visibility: &Visibility) {
+        match visibility {
+            Visibility::Public => self.output.push_str("pub "),
+            Visibility::Protected => self.output.push_str("prot "),
+            Visibility::Private | Visibility::Module => {}
+        }
+    }
+
+    fn function(&mut self, func: &Function, depth: usize, end: Option<usize>) {
+        let inputs = func
+            .inputs
+            .iter()
+            .map(|input| {
+                let spread = if input.variadic { "..." } else { "" };
+                match &input.ty {
+                    Some(ty) => format!("{}{}: {}", spread, input.name, ty),
+                    None => format!("{}{}", spread, input.name),
+                }
+            })
+            .collect::<Vec<String>>();
+        if func.is_async {
+            self.output.push_str("async ");
+        }
+        self.output.push_str(&format!(
+            "fn {}{}({})",
+            func.name.clone().unwrap_or_default(),
+            print_generic_params(&func.generics),
+            inputs.join(", ")
+        ));
+        if let Some(outputs) = &func.outputs {
+            self.output.push_str(&format!(": {}", outputs));
+        }
+        self.open_brace(&self.options.function_brace.clone(), depth);
+        self.body(&func.body, depth + 1);
+        self.close_brace(depth, end);
+    }
+
+    /// Writes every attribute on a line of its own, the declaration follows at `depth`.
+    fn attributes(&mut self, attributes: &[Attribute], depth: usize) {
+        for attribute in attributes.iter() {
+            self.output.push_str(&format!("#[{}", attribute.name));
+            if !attribute.arguments.is_empty() {
+                self.output
+                    .push_str(&format!("({})", attribute.arguments.join(", ")));
+            }
+            self.output.push(']');
+            self.newline();
+            self.indent(depth);
+        }
+    }
+
+    fn class(&mut self, class: &Class, depth: usize, end: Option<usize>) {
+        self.attributes(&class.attributes, depth);
+        self.output.push_str(&format!(
+            "class {}{}",
+            class.name,
+            print_generic_params(&class.generics)
+        ));
+        if let Some(extends) = &class.extends {
+            self.output.push_str(&format!(" extends {}", extends));
+        }
+        if let Some(implements) = &class.implements {
+            self.output
+                .push_str(&format!(" implements {}", implements.join(", ")));
+        }
+        self.open_brace(&self.options.class_brace.clone(), depth);
+        for property in class.body.properties.iter() {
+            self.indent(depth + 1);
+            self.property(property, depth + 1);
+            self.newline();
+        }
+        for method in class.body.methods.iter() {
+            self.indent(depth + 1);
+            self.attributes(&method.attributes, depth + 1);
+            self.method(method, depth + 1);
+            self.newline();
+        }
+        for other in class.body.other.iter() {
+            self.indent(depth + 1);
+            if let Some(method) = other.method() {
+                self.attributes(&method.attributes, depth + 1);
+            }
+            self.class_statement(other, depth + 1);
+            self.newline();
+        }
+        self.close_brace(depth, end);
+    }
+
+    fn class_statement(&mut self, stmt: &ClassAllowedStatement, depth: usize) {
+        match stmt {
+            ClassAllowedStatement::Property(property) => self.property(property, depth),
+            ClassAllowedStatement::Method(method) => self.method(method, depth),
+            ClassAllowedStatement::Constant(constant) => {
+                // unlike properties, constants are public unless a visibility is written.
+                match constant.visibility {
+                    Visibility::Private => self.output.push_str("priv "),
+                    Visibility::Protected => self.output.push_str("prot "),
+                    Visibility::Public | Visibility::Module => {}
+                }
+                let constant = Variable {
+                    visibility: Visibility::Module,
+                    ..constant.clone()
+                };
+                self.variable("const", &constant);
+            }
+            ClassAllowedStatement::Static(inner) => {
+                // the visibility goes before `static`, eg: `pub static fn`
+                let start = self.output.len();
+                self.class_statement(inner, depth);
+                let member = self.output.split_off(start);
+                let (visibility, rest) = ["pub ", "priv ", "prot "]
+                    .iter()
+                    .find_map(|v| member.strip_prefix(v).map(|rest| (*v, rest)))
+                    .unwrap_or(("", &member));
+                self.output
+                    .push_str(&format!("{}static {}", visibility, rest));
+            }
+            ClassAllowedStatement::Import(path) => {
+                self.output.push_str(&format!("use {};", print_path(path)));
+            }
+            ClassAllowedStatement::Macro(m) => {
+                self.output.push_str(&format!("{}! {{{}}}", m.name, m.body));
+            }
+        }
+    }
+
+    fn method(&mut self, method: &Function, depth: usize) {
+        self.visibility(&method.visibility);
+        self.function(method, depth, None);
+    }
+
+    /// Explicit accessors are written after the property, at the same depth.
+    fn property(&mut self, property: &ClassProperty, depth: usize) {
+        self.visibility(&property.visibility);
+        self.output.push_str(&property.name);
+        if let Some(ty) = &property.ty {
+            self.output.push_str(&format!(": {}", ty));
+        }
+        if let Some(assignment) = &property.assignment {
+            self.output.push_str(" = ");
+            self.expression(assignment);
+        }
+        let implicit = property
+            .accessors
+            .iter()
+            .filter(|accessor| accessor.body.is_none())
+            .map(|accessor| format!("{};", accessor.kind))
+            .collect::<Vec<String>>();
+        match implicit.is_empty() {
+            true => self.output.push(';'),
+            false => self
+                .output
+                .push_str(&format!(" {{ {} }}", implicit.join(" "))),
+        }
+        for accessor in property.accessors.iter() {
+            let Some(body) = &accessor.body else {
+                continue;
+            };
+            self.newline();
+            self.indent(depth);
+            // accessors are written like functions, with their kind instead of `fn`.
+            let start = self.output.len();
+            self.function(body, depth, None);
+            self.output
+                .replace_range(start..start + "fn".len(), &accessor.kind.to_string());
+        }
+    }
+
+    fn open_brace(&mut self, style: &BraceType, depth: usize) {
+        let brace = self.options.open_brace(style, depth);
+        self.output.push_str(&brace);
+        self.newline();
+    }
+
+    /// Closes a block, printing any comments left inside of it first.
+    fn close_brace(&mut self, depth: usize, end: Option<usize>) {
+        if let Some(end) = end {
+            self.leading_comments(end, depth + 1);
+        }
+        self.indent(depth);
+        self.output.push('}');
+    }
+
+    fn body(&mut self, body: &Statement, depth: usize) {
+        match body {
+            Statement::Block(exprs) => self.block(exprs, depth),
+            stmt => {
+                self.statement(stmt, depth, None);
+                self.newline();
+            }
+        }
+    }
+
+    fn block(&mut self, exprs: &[Expression], depth: usize) {
+        for expr in exprs.iter() {
+            match expr {
+                Expression::EndOfLine => continue,
+                Expression::Statement(stmt) => {
+                    let span = match stmt.as_ref() {
+                        Statement::Var(var) | Statement::Const(var) => Some(var.span.clone()),
+                        Statement::Destructure(destructure) => Some(destructure.span.clone()),
+                        _ => None,
+                    };
+                    if let Some(span) = &span {
+                        self.leading_comments(span.start, depth);
+                    }
+                    self.statement(stmt, depth, None);
+                    if let Some(span) = span {
+                        self.trailing_comment(span.end);
+                    }
+                }
+                expr => {
+                    self.indent(depth);
+                    self.expression(expr);
+                    self.output.push(';');
+                }
+            }
+            self.newline();
+        }
+    }
+
+    fn expression(&mut self, expr: &Expression) {
+        let code = print_expression(expr);
+        self.output.push_str(&code);
+    }
+}
+
+fn print_expression(expr: &Expression) -> String {
+    match expr {
+        Expression::Literal(literal) => match &literal.ty {
+            Some(TypeKind::BuiltIn(crate::compiler::ast::types::BuiltInType::String)) => {
+                format!("\"{}\"", escape(&literal.value))
+            }
+            _ => literal.value.clone(),
+        },
+        Expression::Await(inner) => format!("await {}", print_expression(inner)),
+        Expression::Is(test) => format!("{} is {}", print_expression(&test.value), test.ty),
+        Expression::Spread(inner) => format!("...{}", print_expression(inner)),
+        Expression::Call(call) => format!(
+            "{}{}({})",
+            call.name,
+            print_type_args(&call.generics),
+            print_list(&call.arguments)
+        ),
+        Expression::MethodCall(call) => format!(
+            "{}.{}({})",
+            print_expression(&call.callee),
+            call.name,
+            print_list(&call.arguments)
+        ),
+        Expression::New(call) => format!(
+            "new {}{}({})",
+            call.name,
+            print_type_args(&call.generics),
+            print_list(&call.arguments)
+        ),
+        Expression::Array(array) => format!("[{}]", print_list(&array.values)),
+        Expression::Tuple(values) => format!("({})", print_list(values)),
+        Expression::Object(object) => {
+            if object.properties.is_empty() {
+                return "{}".to_string();
+            }
+            let properties = object
+                .properties
+                .iter()
+                .map(|p| format!("{}: {}", p.name, print_expression(&p.value)))
+                .collect::<Vec<String>>();
+            format!("{{ {} }}", properties.join(", "))
+        }
+        Expression::Operation(op) => match &op.op {
+            AnyOperation::UnaryOp(UnaryOp::Inc | UnaryOp::Dec) => {
+                format!("{}{}", print_expression(&op.right), op.op)
+            }
+            AnyOperation::UnaryOp(UnaryOp::Delete) => {
+                format!("delete {}", print_expression(&op.right))
+            }
+            AnyOperation::UnaryOp(_) => format!("{}{}", op.op, print_expression(&op.right)),
+            _ => format!(
+                "{} {} {}",
+                print_expression(&op.left),
+                op.op,
+                print_expression(&op.right)
+            ),
+        },
+        Expression::Member(member) => {
+            let origin = member.origin.value().unwrap_or_default();
+            let name = print_expression(&member.name);
+            match member.lookup {
+                MemberLookup::Static => format!("{}::{}", origin, name),
+                MemberLookup::Dynamic => format!("{}.{}", origin, name),
+                MemberLookup::Index => format!("{}[{}]", origin, name),
+            }
+        }
+        Expression::Statement(stmt) => {
+            // statements in expression position are printed on a single line.
+            let options = FormatOptions::default();
+            let mut printer = Printer::new("", &options);
+            printer.statement_inline(stmt, 0, None);
+            printer.output
+        }
+        Expression::EndOfLine => "".to_string(),
+    }
+}
+
+/// Prints the type parameters of a declaration, eg: `<K, V extends Model>`
+fn print_generic_params(params: &[TypeParam]) -> String {
+    if params.is_empty() {
+        return String::new();
+    }
+    let names = params
+        .iter()
+        .filter_map(|p| match p.bound() {
+            Some(bound) => Some(format!("{} extends {}", p.name.as_ref()?, bound)),
+            None => p.name.clone(),
+        })
+        .collect::<Vec<String>>();
+    format!("<{}>", names.join(", "))
+}
+
+/// Prints the type arguments of a call, eg: `<int, string>`
+fn print_type_args(args: &[TypeKind]) -> String {
+    if args.is_empty() {
+        return String::new();
+    }
+    let args = args.iter().map(|a| a.to_string()).collect::<Vec<String>>();
+    format!("<{}>", args.join(", "))
+}
+
+fn print_list(values: &[Expression]) -> String {
+    values
+        .iter()
+        .map(print_expression)
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn print_path(path: &Path) -> String {
+    let name = match &path.alias {
+        Some(alias) => format!("{} as {}", path.name, alias),
+        None => path.name.to_string(),
+    };
+    match path.parts.len() {
+        0 => name,
+        1 => format!("{}::{}", name, print_path(&path.parts[0])),
+        _ => format!(
+            "{}::{{{}}}",
+            name,
+            path.parts
+                .iter()
+                .map(print_path)
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+    }
+}
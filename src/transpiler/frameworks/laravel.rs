@@ -0,0 +1,219 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    compiler::{
+        ast::{
+            visit::{self, Visitor},
+            AstBody, Class, Expression, Statement,
+        },
+        parser::Parser,
+        CompilerOptions,
+    },
+    util::provider::{collect_sources, SourceProvider},
+};
+
+/// The class name of the generated service provider.
+pub const PROVIDER_NAME: &str = "SurnServiceProvider";
+/// The namespace of the generated service provider, the one Laravel keeps its providers in.
+pub const PROVIDER_NAMESPACE: &str = "App\\Providers";
+
+/// A class registered in the Laravel container.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Binding {
+    /// The fully qualified interface the class is bound to, `None` binds the class to itself.
+    pub contract: Option<String>,
+    /// The fully qualified name of the class.
+    pub class: String,
+    /// Whether the container shares a single instance, ie: `#[Singleton]`
+    pub shared: bool,
+}
+
+/// Collects the bindings of every class with a `#[Service]` or `#[Singleton]` attribute.
+///
+/// The class is bound to the interface given to the attribute, eg: `#[Service(Mailer)]`.
+/// Without one, a class that implements a single interface is bound to that interface,
+/// and any other class is bound to itself.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, CompilerOptions};
+/// # use surn::transpiler::frameworks::laravel::collect_bindings;
+/// // namespace App\Mail;
+/// // #[Singleton]
+/// // class SmtpMailer implements Mailer {}
+/// # let source = "namespace App\\Mail;\n#[Singleton]\nclass SmtpMailer implements Mailer {}\n";
+/// # let body = Parser::new(CompilerOptions::default())
+/// #     .try_parse_script("main.surn".to_string(), source.to_string())
+/// #     .map_err(|report| report.message)?;
+/// collect_bindings(&body)?; // [App\Mail\Mailer => App\Mail\SmtpMailer (shared)]
+/// # Ok::<(), String>(())
+/// ```
+pub fn collect_bindings(body: &AstBody) -> Result<Vec<Binding>, String> {
+    let mut collector = BindingCollector {
+        namespace: None,
+        imports: HashMap::new(),
+        bindings: Vec::new(),
+        errors: Vec::new(),
+    };
+    visit::walk_body(&mut collector, body);
+    match collector.errors.into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(collector.bindings),
+    }
+}
+
+/// The source of a service provider that registers the given bindings.
+pub fn provider_source(bindings: &[Binding]) -> String {
+    let mut registrations = String::new();
+    for binding in bindings {
+        let method = if binding.shared { "singleton" } else { "bind" };
+        let arguments = match &binding.contract {
+            Some(contract) => format!("\\{}::class, \\{}::class", contract, binding.class),
+            None => format!("\\{}::class", binding.class),
+        };
+        registrations.push_str(&format!("        $this->app->{}({});\n", method, arguments));
+    }
+
+    format!(
+        "<?php\n\n// Generated by surn, register this provider in `config/app.php`.\nnamespace {};\n\nuse Illuminate\\Support\\ServiceProvider;\n\nclass {} extends ServiceProvider\n{{\n    public function register(): void\n    {{\n{}    }}\n}}\n",
+        PROVIDER_NAMESPACE, PROVIDER_NAME, registrations
+    )
+}
+
+/// Writes the service provider for every `.surn` file under the source root to
+/// `Providers/SurnServiceProvider.php` in the output root.
+/// Files that fail to parse are skipped, the transpiler reports them on its own.
+pub fn write_provider(
+    provider: &dyn SourceProvider,
+    source_root: &Path,
+    output_root: &Path,
+    options: &CompilerOptions,
+) -> Result<PathBuf, String> {
+    let mut sources: Vec<PathBuf> = Vec::new();
+    collect_sources(provider, source_root, &mut sources)?;
+    sources.sort();
+
+    let mut bindings: Vec<Binding> = Vec::new();
+    for source in sources {
+        let contents = provider
+            .load(&source)
+            .map_err(|e| format!("Could not read source file: {}", e))?;
+        let mut parser = Parser::new(options.clone());
+        let parsed = parser
+            .parse_includes_from(provider)
+            .and_then(|_| parser.try_parse_script(source.to_string_lossy().to_string(), contents));
+        if let Ok(body) = parsed {
+            bindings.extend(
+                collect_bindings(&body).map_err(|e| format!("{} [{}]", e, source.display()))?,
+            );
+        }
+    }
+
+    let output = output_root
+        .join("Providers")
+        .join(format!("{}.php", PROVIDER_NAME));
+    provider
+        .write(&output, &provider_source(&bindings))
+        .map_err(|e| format!("Could not write {}: {}", output.display(), e))?;
+    Ok(output)
+}
+
+struct BindingCollector {
+    namespace: Option<String>,
+    /// The fully qualified name of every imported binding.
+    imports: HashMap<String, String>,
+    bindings: Vec<Binding>,
+    errors: Vec<String>,
+}
+
+impl BindingCollector {
+    /// The fully qualified name of a class name used in the current namespace.
+    fn qualify(&self, name: &str) -> String {
+        match (self.imports.get(name), &self.namespace) {
+            (Some(imported), _) => imported.clone(),
+            (None, Some(namespace)) => format!("{}\\{}", namespace, name),
+            (None, None) => name.to_string(),
+        }
+    }
+}
+
+impl Visitor for BindingCollector {
+    fn visit_statement(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Namespace(namespace) => {
+                let path = std::iter::once(namespace.path.name.to_string())
+                    .chain(namespace.path.parts.iter().map(|p| p.name.to_string()))
+                    .collect::<Vec<String>>()
+                    .join("\\");
+                match &namespace.body {
+                    // a namespace block only applies to its own body.
+                    Some(body) => {
+                        let outer = self.namespace.replace(path);
+                        self.visit_statement(body);
+                        self.namespace = outer;
+                    }
+                    None => self.namespace = Some(path),
+                }
+            }
+            Statement::Import(path) => {
+                for leaf in path.leaves() {
+                    let full = leaf
+                        .iter()
+                        .map(|p| p.name.as_str())
+                        .collect::<Vec<&str>>()
+                        .join("\\");
+                    let last = leaf.last().unwrap();
+                    let binding = last.alias.clone().unwrap_or(last.name.to_string());
+                    self.imports.insert(binding, full);
+                }
+            }
+            _ => visit::walk_statement(self, stmt),
+        }
+    }
+
+    fn visit_expression(&mut self, expr: &Expression) {
+        if let Expression::Statement(stmt) = expr {
+            self.visit_statement(stmt);
+        }
+    }
+
+    fn visit_class(&mut self, class: &Class) {
+        let registered = class
+            .attributes
+            .iter()
+            .filter(|a| a.name == "Service" || a.name == "Singleton")
+            .collect::<Vec<_>>();
+        let attribute = match registered.as_slice() {
+            [] => return,
+            [attribute] => attribute,
+            _ => {
+                self.errors.push(format!(
+                    "`{}` can only be registered once, use either `#[Service]` or `#[Singleton]`.",
+                    class.name
+                ));
+                return;
+            }
+        };
+
+        let contract = match (attribute.arguments.as_slice(), &class.implements) {
+            ([contract], _) => Some(contract.clone()),
+            ([], Some(implements)) if implements.len() == 1 => Some(implements[0].clone()),
+            ([], _) => None,
+            _ => {
+                self.errors.push(format!(
+                    "`#[{}]` on `{}` takes a single interface to bind to.",
+                    attribute.name, class.name
+                ));
+                return;
+            }
+        };
+        self.bindings.push(Binding {
+            contract: contract.map(|c| self.qualify(&c)),
+            class: self.qualify(&class.name),
+            shared: attribute.name == "Singleton",
+        });
+    }
+}
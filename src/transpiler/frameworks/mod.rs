@@ -0,0 +1,19 @@
+pub mod laravel;
+
+/// A php framework the generated code can be plugged into.
+/// Each framework writes its own glue code next to the generated classes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Framework {
+    /// Registers `#[Service]` and `#[Singleton]` classes in a service provider,
+    /// see `laravel::provider_source`.
+    Laravel,
+}
+
+impl Framework {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "laravel" => Some(Framework::Laravel),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! Intrinsics, the calls a language generates its own code for instead of a call,
+//! eg: `len(x)` is `count($x)` for an array and `strlen($x)` for a string in php.
+//! The standard library is declared in `compiler::stdlib`, every language lowers it here.
+use std::collections::HashMap;
+
+use crate::compiler::ast::types::TypeKind;
+
+/// An argument of a call to an intrinsic.
+#[derive(Debug, Clone)]
+pub struct Argument {
+    /// The generated code of the argument.
+    pub code: String,
+    /// The type of the argument, when it is known.
+    pub ty: Option<TypeKind>,
+}
+
+/// How a call to an intrinsic is generated.
+#[derive(Debug, Clone)]
+pub enum Lowering {
+    /// Code where `{0}` is the first argument, `{1}` the second and so on.
+    Template(String),
+    /// Code worked out from the arguments, eg: from their types.
+    Custom(fn(&[Argument]) -> String),
+}
+
+/// The intrinsics of a language, by the name they are called with.
+/// A function declared in the script with the same name is called instead.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::transpiler::langs::intrinsics::{Argument, Intrinsics};
+/// # let arguments = [
+/// #     Argument { code: "$line".to_string(), ty: None },
+/// #     Argument { code: "','".to_string(), ty: None },
+/// # ];
+/// let mut intrinsics = Intrinsics::new();
+/// intrinsics.template("split", "explode({1}, {0})");
+/// intrinsics.lower("split", &arguments); // Some(explode(',', $line))
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Intrinsics {
+    lowerings: HashMap<String, Lowering>,
+}
+
+impl Intrinsics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers an intrinsic, replacing the lowering it had.
+    pub fn register(&mut self, name: &str, lowering: Lowering) -> &mut Self {
+        self.lowerings.insert(name.to_string(), lowering);
+        self
+    }
+
+    /// Registers an intrinsic generated from a template, see `Lowering::Template`.
+    pub fn template(&mut self, name: &str, template: &str) -> &mut Self {
+        self.register(name, Lowering::Template(template.to_string()))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Lowering> {
+        self.lowerings.get(name)
+    }
+
+    /// The names of the intrinsics, sorted.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.lowerings.keys().map(|name| name.as_str()).collect();
+        names.sort();
+        names
+    }
+
+    /// The code of a call to an intrinsic, `None` when there is no intrinsic of that name
+    /// or its template uses more arguments than the call has.
+    pub fn lower(&self, name: &str, arguments: &[Argument]) -> Option<String> {
+        match self.get(name)? {
+            Lowering::Template(template) => fill(template, arguments),
+            Lowering::Custom(lower) => Some(lower(arguments)),
+        }
+    }
+}
+
+/// Fills the placeholders of a template in one pass, so the code of an argument is
+/// never filled in itself, eg: a string `"{2}"` given as the first argument.
+fn fill(template: &str, arguments: &[Argument]) -> Option<String> {
+    let mut code = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        code.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let digits = after.len() - after.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        match after[digits..].starts_with('}') && digits > 0 {
+            true => {
+                let index: usize = after[..digits].parse().ok()?;
+                code.push_str(&arguments.get(index)?.code);
+                rest = &after[digits + 1..];
+            }
+            false => {
+                code.push('{');
+                rest = after;
+            }
+        }
+    }
+    code.push_str(rest);
+    Some(code)
+}
@@ -1,4 +1,15 @@
-use crate::compiler::{ast::AstBody, CompilerOptions};
+use std::{ops::Range, path::Path};
+
+use crate::{
+    compiler::{ast::AstBody, CompilerOptions},
+    util::provider::{OsProvider, SourceProvider},
+};
+
+use super::{format::FormatOptions, sourcemap::SourceMap};
+
+use self::intrinsics::Intrinsics;
+
+pub mod intrinsics;
 
 pub enum ApiVersion {
     V1,
@@ -15,17 +26,109 @@ pub struct Language {
     pub api: ApiVersion,
     /// The author of the language.
     pub author: String,
+    /// The file extension of generated files, without the leading dot.
+    pub extension: String,
     /// The generator of the language.
     pub generator: Box<dyn Generator>,
 }
 
+impl Language {
+    /// The intrinsics of the language, to add or replace how calls are generated.
+    /// `None` when the language generates every call as it is.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::transpiler::Transpiler;
+    /// # let mut transpiler = Transpiler::new();
+    /// # transpiler.register_defaults();
+    /// let php = transpiler.get_mut("php").unwrap();
+    /// php.intrinsics_mut().unwrap().template("now", "time()");
+    /// ```
+    pub fn intrinsics_mut(&mut self) -> Option<&mut Intrinsics> {
+        self.generator.intrinsics_mut()
+    }
+}
+
+/// A place where the generated code can't keep the meaning of the source,
+/// eg: type parameters that are erased.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LossyLowering {
+    /// The lint that controls whether it is reported, see `compiler::lints`.
+    pub lint: &'static str,
+    /// What the generated code does instead.
+    pub message: String,
+    /// The span of the declaration it happens in.
+    pub span: Range<usize>,
+}
+
 // A trait that allows transformation of surn to another language.
 pub trait Generator {
     /// Generates given ast body to a given language and returns the string.
+    /// The output is laid out according to `format`, eg: brace styles and variable casing.
     /// Useful for scripts.
-    fn generate_to_string(&self, ast: AstBody, options: CompilerOptions) -> String;
+    fn generate_to_string(
+        &self,
+        ast: AstBody,
+        options: CompilerOptions,
+        format: &FormatOptions,
+    ) -> String;
+
+    /// Generates given ast body along with a source map of the generated lines.
+    /// By default nothing is mapped.
+    fn generate_with_map(
+        &self,
+        ast: AstBody,
+        options: CompilerOptions,
+        format: &FormatOptions,
+    ) -> (String, SourceMap) {
+        (
+            self.generate_to_string(ast, options, format),
+            SourceMap::new(),
+        )
+    }
+
+    /// Whether or not the language can generate invocations of the given macro.
+    /// Files that invoke a macro that isn't supported fail to transpile.
+    fn supports_macro(&self, _name: &str) -> bool {
+        false
+    }
+
+    /// The places where the generated code of the body loses the meaning of the source.
+    /// By default the language is assumed to keep everything.
+    fn lossy_lowerings(&self, _ast: &AstBody) -> Vec<LossyLowering> {
+        Vec::new()
+    }
 
-    /// Generates a script from a path given in CLI.
+    /// The intrinsics the language generates calls with, see `Language::intrinsics_mut`.
+    /// By default every call is generated as it is.
+    fn intrinsics_mut(&mut self) -> Option<&mut Intrinsics> {
+        None
+    }
+
+    /// Writes the files that go along with a transpiled source tree, eg: a preload script,
+    /// with the provider the tree was read from. Nothing is written by default.
+    fn write_support(
+        &self,
+        _provider: &dyn SourceProvider,
+        _source_root: &Path,
+        _output_root: &Path,
+        _options: &CompilerOptions,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Generates a script from a path, reading and writing the files with a provider.
     /// This CAN be a file or a directory.
-    fn generate(&mut self, path: &str, options: CompilerOptions) -> Result<(), String>;
+    /// Directories are mirrored to an output tree, see `driver::generate_path`.
+    fn generate_with(
+        &mut self,
+        provider: &dyn SourceProvider,
+        path: &str,
+        options: CompilerOptions,
+    ) -> Result<(), String>;
+
+    /// Generates a script from a path given in CLI, on the disk, see `generate_with`.
+    fn generate(&mut self, path: &str, options: CompilerOptions) -> Result<(), String> {
+        self.generate_with(&OsProvider, path, options)
+    }
 }
@@ -1,9 +1,19 @@
 use self::langs::Language;
-use std::collections::HashMap;
+use crate::{
+    project::config::Config,
+    util::provider::{OsProvider, SourceProvider},
+};
+use std::{collections::HashMap, path::Path};
 
+pub mod audit;
 mod defaults;
+pub mod driver;
 pub mod format;
+pub mod frameworks;
 pub mod langs;
+pub mod preload;
+pub mod scaffold;
+pub mod sourcemap;
 
 pub struct Transpiler {
     registered: HashMap<&'static str, Language>,
@@ -23,4 +33,79 @@ impl Transpiler {
     pub fn get(&self, lang: &str) -> Option<&Language> {
         self.registered.get(lang)
     }
+
+    pub fn get_mut(&mut self, lang: &str) -> Option<&mut Language> {
+        self.registered.get_mut(lang)
+    }
+
+    /// The registered languages by name, in the order of their names.
+    pub fn languages(&self) -> Vec<(&'static str, &Language)> {
+        let mut languages: Vec<(&'static str, &Language)> = self
+            .registered
+            .iter()
+            .map(|(name, language)| (*name, language))
+            .collect();
+        languages.sort_by_key(|(name, _)| *name);
+        languages
+    }
+
+    /// Transpiles a file or directory with the language, options and format of a config,
+    /// see `driver::generate_path`.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use std::path::Path;
+    /// # use surn::{project::Project, transpiler::Transpiler};
+    /// # let mut transpiler = Transpiler::new();
+    /// # transpiler.register_defaults();
+    /// let config = Project::load(Path::new("app"))?.config()?;
+    /// transpiler.generate("app/src/main.surn", &config)?;
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn generate(&self, path: &str, config: &Config) -> Result<(), String> {
+        self.generate_with(&OsProvider, path, config)
+    }
+
+    /// Transpiles a file or directory of a provider, the generated files are written to it.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::{project::config::Config, transpiler::Transpiler, util::provider::MemoryProvider};
+    /// # let mut transpiler = Transpiler::new();
+    /// # transpiler.register_defaults();
+    /// # let config = Config::default();
+    /// let files = MemoryProvider::new();
+    /// files.insert("src/main.surn", "var x = 1;");
+    /// transpiler.generate_with(&files, "src", &config)?; // out/main.php
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn generate_with(
+        &self,
+        provider: &dyn SourceProvider,
+        path: &str,
+        config: &Config,
+    ) -> Result<(), String> {
+        let language = self
+            .get(&config.lang)
+            .ok_or(format!("Unknown language: {}", config.lang))?;
+        let generator = language.generator.as_ref();
+        driver::generate_path(
+            provider,
+            generator,
+            path,
+            &language.extension,
+            &config.options,
+            &config.format,
+        )?;
+        let root = Path::new(path);
+        if provider.is_dir(root) {
+            generator.write_support(
+                provider,
+                root,
+                &driver::default_output_root(root),
+                &config.options,
+            )?;
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,197 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    compiler::{
+        ast::{
+            visit::{self, Visitor},
+            AstBody, Class, Statement,
+        },
+        parser::Parser,
+        CompilerOptions,
+    },
+    util::provider::{collect_sources, SourceProvider},
+};
+
+/// The name of the preloading script written to the output root.
+pub const PRELOAD_FILE: &str = "preload.php";
+
+/// A generated file that declares classes, with the classes it depends on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreloadFile {
+    /// The path of the generated file, relative to the output root.
+    pub path: PathBuf,
+    /// The classes and enums the file declares.
+    pub declares: Vec<String>,
+    /// The classes the file imports, extends or implements.
+    pub depends_on: Vec<String>,
+}
+
+impl PreloadFile {
+    pub fn from_ast(path: PathBuf, body: &AstBody) -> Self {
+        let mut collector = ClassCollector {
+            declares: Vec::new(),
+            depends_on: Vec::new(),
+        };
+        visit::walk_body(&mut collector, body);
+        PreloadFile {
+            path,
+            declares: collector.declares,
+            depends_on: collector.depends_on,
+        }
+    }
+}
+
+/// Orders the files that declare classes so every file comes after the files it depends on.
+/// Files without classes are left out, they have nothing for opcache to preload.
+///
+/// Dependencies that no file declares are ignored, and files in a dependency cycle
+/// keep the order they were given in.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use std::path::PathBuf;
+/// # use surn::compiler::{parser::Parser, CompilerOptions};
+/// # use surn::transpiler::preload::{preload_order, PreloadFile};
+/// # let mut parser = Parser::new(CompilerOptions::default());
+/// // models/user.surn:  class User extends Model {}
+/// // models/model.surn: class Model {}
+/// # let mut files = Vec::new();
+/// # for (name, source) in [("user", "class User extends Model {}"), ("model", "class Model {}")] {
+/// #     let body = parser.try_parse_script(format!("models/{}.surn", name), source.to_string())?;
+/// #     files.push(PreloadFile::from_ast(PathBuf::from(format!("models/{}.php", name)), &body));
+/// # }
+/// preload_order(files); // [models/model.php, models/user.php]
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+pub fn preload_order(files: Vec<PreloadFile>) -> Vec<PreloadFile> {
+    let files = files
+        .into_iter()
+        .filter(|f| !f.declares.is_empty())
+        .collect::<Vec<PreloadFile>>();
+    let declared_in = files
+        .iter()
+        .enumerate()
+        .flat_map(|(i, f)| f.declares.iter().map(move |class| (class.as_str(), i)))
+        .collect::<HashMap<&str, usize>>();
+
+    let mut ordered: Vec<usize> = Vec::new();
+    let mut visited: HashSet<usize> = HashSet::new();
+    for i in 0..files.len() {
+        visit_file(i, &files, &declared_in, &mut visited, &mut ordered);
+    }
+    ordered.into_iter().map(|i| files[i].clone()).collect()
+}
+
+fn visit_file(
+    index: usize,
+    files: &[PreloadFile],
+    declared_in: &HashMap<&str, usize>,
+    visited: &mut HashSet<usize>,
+    ordered: &mut Vec<usize>,
+) {
+    // a file is marked before its dependencies, so a cycle stops here.
+    if !visited.insert(index) {
+        return;
+    }
+    for dependency in files[index].depends_on.iter() {
+        if let Some(&other) = declared_in.get(dependency.as_str()) {
+            visit_file(other, files, declared_in, visited, ordered);
+        }
+    }
+    ordered.push(index);
+}
+
+/// The contents of the preloading script for files that are already in `preload_order`.
+/// Paths are relative to the script, which is written to the output root.
+pub fn preload_script(files: &[PreloadFile]) -> String {
+    let mut script = String::from("<?php\n\n");
+    script.push_str("// Generated by surn, point `opcache.preload` at this file.\n");
+    for file in files {
+        let path = file
+            .path
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect::<Vec<String>>()
+            .join("/");
+        script.push_str(&format!("opcache_compile_file(__DIR__ . '/{}');\n", path));
+    }
+    script
+}
+
+/// Writes `preload.php` to the output root for every `.surn` file under the source root.
+/// Files that fail to parse are skipped, the transpiler reports them on its own.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use std::path::Path;
+/// # use surn::{compiler::CompilerOptions, transpiler::preload::write_preload, util::provider::OsProvider};
+/// # let options = CompilerOptions::default();
+/// write_preload(&OsProvider, Path::new("src"), Path::new("out"), "php", &options)?; // out/preload.php
+/// # Ok::<(), String>(())
+/// ```
+pub fn write_preload(
+    provider: &dyn SourceProvider,
+    source_root: &Path,
+    output_root: &Path,
+    extension: &str,
+    options: &CompilerOptions,
+) -> Result<PathBuf, String> {
+    let mut sources: Vec<PathBuf> = Vec::new();
+    collect_sources(provider, source_root, &mut sources)?;
+    sources.sort();
+
+    let mut files: Vec<PreloadFile> = Vec::new();
+    for source in sources {
+        let relative = source.strip_prefix(source_root).unwrap().to_path_buf();
+        let contents = provider
+            .load(&source)
+            .map_err(|e| format!("Could not read source file: {}", e))?;
+        let mut parser = Parser::new(options.clone());
+        let parsed = parser.parse_includes_from(provider).and_then(|_| {
+            parser.try_parse_script(relative.to_string_lossy().to_string(), contents)
+        });
+        if let Ok(body) = parsed {
+            files.push(PreloadFile::from_ast(
+                relative.with_extension(extension),
+                &body,
+            ));
+        }
+    }
+
+    let output = output_root.join(PRELOAD_FILE);
+    provider
+        .write(&output, &preload_script(&preload_order(files)))
+        .map_err(|e| format!("Could not write {}: {}", PRELOAD_FILE, e))?;
+    Ok(output)
+}
+
+struct ClassCollector {
+    declares: Vec<String>,
+    depends_on: Vec<String>,
+}
+
+impl Visitor for ClassCollector {
+    fn visit_statement(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Enum(e) => self.declares.push(e.name.clone()),
+            Statement::Import(path) => {
+                for leaf in path.leaves() {
+                    self.depends_on.push(leaf.last().unwrap().name.to_string());
+                }
+            }
+            _ => {}
+        }
+        visit::walk_statement(self, stmt);
+    }
+
+    fn visit_class(&mut self, class: &Class) {
+        self.declares.push(class.name.clone());
+        self.depends_on.extend(class.extends.iter().cloned());
+        self.depends_on
+            .extend(class.implements.iter().flatten().cloned());
+        visit::walk_class(self, class);
+    }
+}
@@ -0,0 +1,181 @@
+use std::path::PathBuf;
+#[cfg(feature = "fs")]
+use std::{fs, path::Path};
+
+use crate::project::Manifest;
+
+/// A project skeleton `surnc init` can write.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Template {
+    /// A php library published with composer, the generated `out` directory is autoloaded.
+    ComposerLib,
+}
+
+impl Template {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "composer-lib" => Some(Template::ComposerLib),
+            _ => None,
+        }
+    }
+}
+
+/// A composer package name, eg: `acme/string-utils`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Package {
+    pub vendor: String,
+    pub name: String,
+}
+
+impl Package {
+    /// Parses a `vendor/name` package name, composer only allows lowercase names.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        let valid = |part: &str| {
+            !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "-_.".contains(c))
+        };
+        match name.split_once('/') {
+            Some((vendor, name)) if valid(vendor) && valid(name) => Ok(Package {
+                vendor: vendor.to_string(),
+                name: name.to_string(),
+            }),
+            _ => Err(format!(
+                "Invalid package name `{}`, expected a lowercase `vendor/name`.",
+                name
+            )),
+        }
+    }
+
+    /// The php namespace of the package.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::transpiler::scaffold::Package;
+    /// Package::parse("acme/string-utils")?.namespace(); // Acme\StringUtils
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn namespace(&self) -> String {
+        let studly = |part: &str| {
+            part.split(['-', '_', '.'])
+                .map(|word| {
+                    let mut chars = word.chars();
+                    match chars.next() {
+                        Some(first) => first.to_uppercase().chain(chars).collect(),
+                        None => String::new(),
+                    }
+                })
+                .collect::<String>()
+        };
+        format!("{}\\{}", studly(&self.vendor), studly(&self.name))
+    }
+}
+
+/// The files of a template, with paths relative to the project root.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::transpiler::scaffold::{scaffold_files, Package, Template};
+/// let package = Package::parse("acme/greeter")?;
+/// scaffold_files(Template::ComposerLib, &package);
+/// // [surn.toml, composer.json, .gitignore, src/Calculator.surn, tests/CalculatorTest.php]
+/// # Ok::<(), String>(())
+/// ```
+pub fn scaffold_files(template: Template, package: &Package) -> Vec<(PathBuf, String)> {
+    match template {
+        Template::ComposerLib => composer_lib(package),
+    }
+}
+
+/// Writes the files of a template into the project root.
+/// Nothing is written when one of the files already exists.
+#[cfg(feature = "fs")]
+pub fn scaffold(
+    root: &Path,
+    template: Template,
+    package: &Package,
+) -> Result<Vec<PathBuf>, String> {
+    write_files(root, scaffold_files(template, package))
+}
+
+/// Writes files with paths relative to the root, creating any missing directories.
+/// Nothing is written when one of the files already exists.
+#[cfg(feature = "fs")]
+pub(crate) fn write_files(
+    root: &Path,
+    files: Vec<(PathBuf, String)>,
+) -> Result<Vec<PathBuf>, String> {
+    if let Some((path, _)) = files.iter().find(|(path, _)| root.join(path).exists()) {
+        return Err(format!(
+            "{} already exists, refusing to overwrite it.",
+            root.join(path).display()
+        ));
+    }
+
+    let mut written: Vec<PathBuf> = Vec::new();
+    for (path, contents) in files {
+        let path = root.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Could not create {}: {}", parent.display(), e))?;
+        }
+        fs::write(&path, contents)
+            .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+fn composer_lib(package: &Package) -> Vec<(PathBuf, String)> {
+    let namespace = package.namespace();
+    // composer.json escapes the namespace separator.
+    let escaped = namespace.replace('\\', "\\\\");
+
+    let manifest = Manifest::new(&format!("{}/{}", package.vendor, package.name)).to_toml();
+    let composer = format!(
+        r#"{{
+    "name": "{}/{}",
+    "type": "library",
+    "require": {{
+        "php": ">=8.1"
+    }},
+    "require-dev": {{
+        "phpunit/phpunit": "^10.0"
+    }},
+    "autoload": {{
+        "psr-4": {{
+            "{}\\": "out/"
+        }}
+    }},
+    "autoload-dev": {{
+        "psr-4": {{
+            "{}\\Tests\\": "tests/"
+        }}
+    }},
+    "scripts": {{
+        "build": "surnc build src",
+        "test": "phpunit tests"
+    }}
+}}
+"#,
+        package.vendor, package.name, escaped, escaped
+    );
+    let source = format!(
+        "namespace {};\n\nclass Calculator {{\n    pub fn add(a: int, b: int): int {{\n        return a + b;\n    }}\n}}\n",
+        namespace
+    );
+    let test = format!(
+        "<?php\n\nnamespace {}\\Tests;\n\nuse {}\\Calculator;\nuse PHPUnit\\Framework\\TestCase;\n\nclass CalculatorTest extends TestCase\n{{\n    public function testAdd(): void\n    {{\n        $this->assertSame(3, (new Calculator())->add(1, 2));\n    }}\n}}\n",
+        namespace, namespace
+    );
+
+    vec![
+        (PathBuf::from("surn.toml"), manifest),
+        (PathBuf::from("composer.json"), composer),
+        // the generated `out` directory is committed, it is what composer installs.
+        (PathBuf::from(".gitignore"), "/vendor/\n".to_string()),
+        (PathBuf::from("src/Calculator.surn"), source),
+        (PathBuf::from("tests/CalculatorTest.php"), test),
+    ]
+}
@@ -0,0 +1,219 @@
+use std::ops::Range;
+
+use crate::{compiler::lexer::pos::Position, util::source::SourceBuffer};
+
+/// The extension appended to a generated file for its source map sidecar,
+/// eg: `main.php` -> `main.php.map`
+pub const SIDECAR_EXTENSION: &str = "map";
+
+/// Links a position in the generated code back to the node it was generated from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mapping {
+    /// The line in the generated file, starting at 1 like php stack traces.
+    pub generated_line: usize,
+    /// The column in the generated line, starting at 0.
+    pub generated_column: usize,
+    /// The range of the originating node in the `.surn` source.
+    pub original: Range<usize>,
+    /// The line and column the originating node starts at, line 0 when the node
+    /// has no position and it has to be resolved from `original`.
+    pub position: Position,
+    /// The identifier emitted at this position, if any.
+    pub name: Option<String>,
+}
+
+/// A source map for a single generated file.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::{parser::Parser, CompilerOptions};
+/// # use surn::transpiler::{format::FormatOptions, langs::Generator, Transpiler};
+/// # let mut transpiler = Transpiler::new();
+/// # transpiler.register_defaults();
+/// # let generator = &transpiler.get("php").unwrap().generator;
+/// # let (options, format) = (CompilerOptions::default(), FormatOptions::default());
+/// # let source = "var x = 1;\n";
+/// # let ast = Parser::new(options.clone())
+/// #     .try_parse_script("main.surn".to_string(), source.to_string())?;
+/// let (code, map) = generator.generate_with_map(ast, options, &format);
+/// map.original_position(3, &source); // Some((1, 4)) -> main.surn:1:4
+/// # Ok::<(), surn::report::Report>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    /// The path of the `.surn` source file.
+    pub source: String,
+    /// The path of the generated file.
+    pub file: String,
+    pub mappings: Vec<Mapping>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        SourceMap {
+            source: String::new(),
+            file: String::new(),
+            mappings: Vec::new(),
+        }
+    }
+
+    /// Gets the mapping of the line, which is the first one recorded for that line.
+    pub fn lookup(&self, generated_line: usize) -> Option<&Mapping> {
+        self.mappings
+            .iter()
+            .find(|m| m.generated_line == generated_line)
+    }
+
+    /// Maps a generated line, eg: from a php stack trace, back to the line and column
+    /// in the given source. Both are 1 based.
+    pub fn original_position(&self, generated_line: usize, source: &str) -> Option<(usize, usize)> {
+        let mapping = self.lookup(generated_line)?;
+        if mapping.position.line > 0 {
+            return Some((mapping.position.line, mapping.position.column));
+        }
+        SourceBuffer::new(source.to_string()).line_col(mapping.original.start)
+    }
+
+    /// Serializes the map to the JSON sidecar format.
+    /// The original line and column of every mapping are resolved against `source`
+    /// so tooling doesn't need the `.surn` file to read the map.
+    ///
+    /// ```json
+    /// {"version":1,"file":"main.php","source":"main.surn","mappings":[
+    ///   {"line":2,"column":0,"start":0,"end":10,"original_line":1,"original_column":1,"name":"x"}
+    /// ]}
+    /// ```
+    pub fn to_json(&self, source: &str) -> String {
+        let buffer = SourceBuffer::new(source.to_string());
+        let mappings = self
+            .mappings
+            .iter()
+            .map(|m| {
+                let (line, column) = match m.position {
+                    Position { line: 0, .. } => {
+                        buffer.line_col(m.original.start).unwrap_or((0, 0))
+                    }
+                    position => (position.line, position.column),
+                };
+                let name = match &m.name {
+                    Some(name) => escape_json(name),
+                    None => "null".to_string(),
+                };
+                format!(
+                    "{{\"line\":{},\"column\":{},\"start\":{},\"end\":{},\"original_line\":{},\"original_column\":{},\"name\":{}}}",
+                    m.generated_line,
+                    m.generated_column,
+                    m.original.start,
+                    m.original.end,
+                    line,
+                    column,
+                    name
+                )
+            })
+            .collect::<Vec<String>>();
+
+        format!(
+            "{{\"version\":1,\"file\":{},\"source\":{},\"mappings\":[{}]}}",
+            escape_json(&self.file),
+            escape_json(&self.source),
+            mappings.join(",")
+        )
+    }
+}
+
+/// Builds generated code and its source map at the same time.
+/// Generators push their output through this so that the line each piece
+/// of code ends up on is known.
+#[derive(Debug, Clone)]
+pub struct MappedWriter {
+    output: String,
+    line: usize,
+    column: usize,
+    map: SourceMap,
+}
+
+impl MappedWriter {
+    pub fn new() -> Self {
+        MappedWriter {
+            output: String::new(),
+            line: 1,
+            column: 0,
+            map: SourceMap::new(),
+        }
+    }
+
+    /// Writes code that does not originate from the source, eg: `<?php`
+    pub fn write(&mut self, code: &str) {
+        for c in code.chars() {
+            self.output.push(c);
+            if c == '\n' {
+                self.line += 1;
+                self.column = 0;
+            } else {
+                self.column += 1;
+            }
+        }
+    }
+
+    /// Writes code that was generated from the node at `original`, which starts at `position`.
+    /// Every line the code touches is mapped back to the node, the first
+    /// one is given the `name` of the identifier the node declares.
+    pub fn write_mapped(
+        &mut self,
+        code: &str,
+        original: Range<usize>,
+        position: Position,
+        name: Option<&str>,
+    ) {
+        let mut name = name.map(|n| n.to_string());
+        let mut start_of_line = true;
+        for c in code.chars() {
+            if start_of_line && c != '\n' {
+                self.mark(original.clone(), position, name.take());
+                start_of_line = false;
+            }
+            self.write(&c.to_string());
+            if c == '\n' {
+                start_of_line = true;
+            }
+        }
+    }
+
+    /// Records a mapping at the current position.
+    pub fn mark(&mut self, original: Range<usize>, position: Position, name: Option<String>) {
+        self.map.mappings.push(Mapping {
+            generated_line: self.line,
+            generated_column: self.column,
+            original,
+            position,
+            name,
+        });
+    }
+
+    pub fn finish(self) -> (String, SourceMap) {
+        (self.output, self.map)
+    }
+}
+
+impl Default for MappedWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub(crate) fn escape_json(value: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
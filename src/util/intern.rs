@@ -0,0 +1,172 @@
+//! Interned strings, every name is stored once and referred to by a `Symbol`.
+//!
+//! A name of a large file is written many times, eg: a function and every call to it,
+//! interning it allocates the name once instead of once for every time it is written.
+//! Interned names live for as long as the program.
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Deref,
+    sync::{OnceLock, RwLock},
+};
+
+/// An interned name, comparing and copying a symbol doesn't touch its string.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::util::intern::Symbol;
+/// let name = Symbol::intern("main");
+/// assert_eq!(name, Symbol::intern("main"));
+/// assert_eq!(name.resolve(), "main");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Symbol(u32);
+
+#[derive(Default)]
+struct Interner {
+    symbols: HashMap<&'static str, Symbol>,
+    names: Vec<&'static str>,
+}
+
+fn interner() -> &'static RwLock<Interner> {
+    static INTERNER: OnceLock<RwLock<Interner>> = OnceLock::new();
+    INTERNER.get_or_init(Default::default)
+}
+
+impl Symbol {
+    /// The symbol of a name, the name is only allocated the first time it is interned.
+    pub fn intern(name: &str) -> Self {
+        if let Some(symbol) = interner().read().unwrap().symbols.get(name) {
+            return *symbol;
+        }
+        let mut interner = interner().write().unwrap();
+        // another thread can intern the name between the read and the write.
+        if let Some(symbol) = interner.symbols.get(name) {
+            return *symbol;
+        }
+        let name: &'static str = Box::leak(name.to_string().into_boxed_str());
+        let symbol = Symbol(u32::try_from(interner.names.len()).expect("less than u32::MAX names"));
+        interner.names.push(name);
+        interner.symbols.insert(name, symbol);
+        symbol
+    }
+
+    /// The name of the symbol.
+    pub fn resolve(self) -> &'static str {
+        interner().read().unwrap().names[self.0 as usize]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        self.resolve()
+    }
+
+    /// The number of names interned so far.
+    pub fn count() -> usize {
+        interner().read().unwrap().names.len()
+    }
+}
+
+impl Deref for Symbol {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.resolve()
+    }
+}
+
+impl Default for Symbol {
+    fn default() -> Self {
+        Symbol::intern("")
+    }
+}
+
+impl Hash for Symbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+/// Symbols are sorted by their names, not by the order they were interned in.
+impl Ord for Symbol {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match self.0 == other.0 {
+            true => Ordering::Equal,
+            false => self.resolve().cmp(other.resolve()),
+        }
+    }
+}
+
+impl PartialOrd for Symbol {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.resolve())
+    }
+}
+
+/// Written like the string, so a symbol reads the same as the name in a debug dump.
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.resolve(), f)
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(name: &str) -> Self {
+        Symbol::intern(name)
+    }
+}
+
+impl From<String> for Symbol {
+    fn from(name: String) -> Self {
+        Symbol::intern(&name)
+    }
+}
+
+impl From<&String> for Symbol {
+    fn from(name: &String) -> Self {
+        Symbol::intern(name)
+    }
+}
+
+impl From<Symbol> for String {
+    fn from(symbol: Symbol) -> Self {
+        symbol.resolve().to_string()
+    }
+}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        self.resolve() == other
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        self.resolve() == *other
+    }
+}
+
+impl PartialEq<String> for Symbol {
+    fn eq(&self, other: &String) -> bool {
+        self.resolve() == other
+    }
+}
+
+impl PartialEq<Symbol> for String {
+    fn eq(&self, other: &Symbol) -> bool {
+        self == other.resolve()
+    }
+}
+
+impl PartialEq<Symbol> for &str {
+    fn eq(&self, other: &Symbol) -> bool {
+        *self == other.resolve()
+    }
+}
@@ -1,3 +1,5 @@
+pub mod intern;
+pub mod provider;
 pub mod source;
 pub mod token_stream;
 
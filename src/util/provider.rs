@@ -0,0 +1,327 @@
+//! Where sources are read from and generated code is written to.
+//!
+//! The compiler reads and writes through a `SourceProvider`, so it can run where there is
+//! no file system, eg: in a web service or in WASM, with a `MemoryProvider`.
+//! `OsProvider` reads and writes the disk.
+//!
+//! **Example:**
+//! ```rust no_run
+//! # use std::path::Path;
+//! # use surn::compiler::CompilerOptions;
+//! # use surn::transpiler::{driver::generate_tree, format::FormatOptions, Transpiler};
+//! # use surn::util::provider::MemoryProvider;
+//! # let mut transpiler = Transpiler::new();
+//! # transpiler.register_defaults();
+//! # let generator = transpiler.get("php").unwrap().generator.as_ref();
+//! # let (options, format) = (CompilerOptions::default(), FormatOptions::default());
+//! let files = MemoryProvider::new();
+//! files.insert("src/main.surn", "var x = 1;");
+//! generate_tree(&files, generator, Path::new("src"), Path::new("out"), "php", &options, &format)?;
+//! files.get("out/main.php"); // Some("<?php\n\n$x = 1;\n")
+//! # Ok::<(), String>(())
+//! ```
+#[cfg(feature = "fs")]
+use std::fs;
+use std::{
+    collections::BTreeMap,
+    io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, SystemTime},
+};
+
+/// Reads the sources of origins that have a path, see `SourceOrigin::with_loader`.
+/// Embedders implement it to compile sources that aren't on the disk, eg: in memory or
+/// fetched over the network.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use std::{collections::HashMap, io, path::{Path, PathBuf}};
+/// # use surn::util::provider::FileLoader;
+/// struct Fixtures(HashMap<PathBuf, String>);
+///
+/// impl FileLoader for Fixtures {
+///     fn load(&self, path: &Path) -> io::Result<String> {
+///         self.0.get(path).cloned().ok_or(io::ErrorKind::NotFound.into())
+///     }
+/// }
+/// ```
+pub trait FileLoader: Send + Sync {
+    fn load(&self, path: &Path) -> io::Result<String>;
+
+    /// When the source was last changed, a source that was read before is read again
+    /// once this changes. `None` when it can't be told, the source is then only read once.
+    fn modified(&self, _path: &Path) -> Option<SystemTime> {
+        None
+    }
+}
+
+/// A file system the compiler reads sources from and writes generated code to.
+pub trait SourceProvider: FileLoader {
+    /// The files and directories in a directory, in no particular order.
+    fn list(&self, dir: &Path) -> io::Result<Vec<PathBuf>>;
+
+    fn is_dir(&self, path: &Path) -> bool;
+
+    /// Writes a file, along with the directories it is in.
+    fn write(&self, path: &Path, contents: &str) -> io::Result<()>;
+
+    /// Starts watching the files under a path, see `Watcher::changed`.
+    fn watch(&self, root: &Path) -> io::Result<Watcher> {
+        Watcher::new(self, root)
+    }
+}
+
+/// The extension of surn source files.
+pub const SOURCE_EXTENSION: &str = "surn";
+
+/// Every file under a path, a file is the only one under itself.
+pub fn files<P: SourceProvider + ?Sized>(provider: &P, root: &Path) -> io::Result<Vec<PathBuf>> {
+    if !provider.is_dir(root) {
+        return Ok(vec![root.to_path_buf()]);
+    }
+    let mut found = Vec::new();
+    for path in provider.list(root)? {
+        found.extend(files(provider, &path)?);
+    }
+    found.sort();
+    Ok(found)
+}
+
+/// Adds the surn sources under a directory to `sources`.
+pub(crate) fn collect_sources(
+    provider: &dyn SourceProvider,
+    dir: &Path,
+    sources: &mut Vec<PathBuf>,
+) -> Result<(), String> {
+    if !provider.is_dir(dir) {
+        return Err(format!("Could not read directory {}", dir.display()));
+    }
+    let files = files(provider, dir)
+        .map_err(|e| format!("Could not read directory {}: {}", dir.display(), e))?;
+    sources.extend(
+        files
+            .into_iter()
+            .filter(|path| path.extension().is_some_and(|e| e == SOURCE_EXTENSION)),
+    );
+    Ok(())
+}
+
+/// Tells which files under a path changed, by when they were last changed.
+/// There is no thread, the files are looked at every time `changed` is called.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use std::{path::Path, thread, time::Duration};
+/// # use surn::util::provider::{OsProvider, SourceProvider};
+/// let mut watcher = OsProvider.watch(Path::new("src"))?;
+/// loop {
+///     for path in watcher.changed(&OsProvider)? {
+///         println!("{} changed", path.display());
+///     }
+///     thread::sleep(Duration::from_millis(500));
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Watcher {
+    root: PathBuf,
+    seen: BTreeMap<PathBuf, Option<SystemTime>>,
+}
+
+impl Watcher {
+    pub fn new<P: SourceProvider + ?Sized>(provider: &P, root: &Path) -> io::Result<Self> {
+        Ok(Self {
+            root: root.to_path_buf(),
+            seen: snapshot(provider, root)?,
+        })
+    }
+
+    /// The files that were added, changed or removed since the last time, in order.
+    pub fn changed<P: SourceProvider + ?Sized>(
+        &mut self,
+        provider: &P,
+    ) -> io::Result<Vec<PathBuf>> {
+        let now = snapshot(provider, &self.root)?;
+        let mut changed: Vec<PathBuf> = now
+            .iter()
+            .filter(|(path, modified)| self.seen.get(*path) != Some(modified))
+            .map(|(path, _)| path.clone())
+            .collect();
+        changed.extend(self.seen.keys().filter(|p| !now.contains_key(*p)).cloned());
+        changed.sort();
+        self.seen = now;
+        Ok(changed)
+    }
+}
+
+fn snapshot<P: SourceProvider + ?Sized>(
+    provider: &P,
+    root: &Path,
+) -> io::Result<BTreeMap<PathBuf, Option<SystemTime>>> {
+    Ok(files(provider, root)?
+        .into_iter()
+        .map(|path| {
+            let modified = provider.modified(&path);
+            (path, modified)
+        })
+        .collect())
+}
+
+/// Reads and writes the disk. Without the `fs` feature there is no disk, eg: in WASM,
+/// every read and write fails as unsupported.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsProvider;
+
+#[cfg(feature = "fs")]
+impl FileLoader for OsProvider {
+    fn load(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+
+    fn modified(&self, path: &Path) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+}
+
+#[cfg(feature = "fs")]
+impl SourceProvider for OsProvider {
+    fn list(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        fs::read_dir(dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn write(&self, path: &Path, contents: &str) -> io::Result<()> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, contents)
+    }
+}
+
+#[cfg(not(feature = "fs"))]
+impl FileLoader for OsProvider {
+    fn load(&self, path: &Path) -> io::Result<String> {
+        Err(no_disk(path))
+    }
+}
+
+#[cfg(not(feature = "fs"))]
+impl SourceProvider for OsProvider {
+    fn list(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        Err(no_disk(dir))
+    }
+
+    fn is_dir(&self, _path: &Path) -> bool {
+        false
+    }
+
+    fn write(&self, path: &Path, _contents: &str) -> io::Result<()> {
+        Err(no_disk(path))
+    }
+}
+
+#[cfg(not(feature = "fs"))]
+fn no_disk(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!(
+            "{}: there is no disk without the `fs` feature",
+            path.display()
+        ),
+    )
+}
+
+/// Files kept in memory, a directory is there for as long as a file is in it.
+/// Every write counts as a change, see `Watcher`.
+#[derive(Debug, Default)]
+pub struct MemoryProvider {
+    /// The contents of every file, with the write that last changed it.
+    files: Mutex<BTreeMap<PathBuf, (String, u64)>>,
+    writes: AtomicU64,
+}
+
+impl MemoryProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&self, path: impl Into<PathBuf>, contents: impl Into<String>) {
+        let version = self.writes.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut files = self.files.lock().unwrap();
+        files.insert(path.into(), (contents.into(), version));
+    }
+
+    pub fn get(&self, path: impl AsRef<Path>) -> Option<String> {
+        let files = self.files.lock().unwrap();
+        files
+            .get(path.as_ref())
+            .map(|(contents, _)| contents.clone())
+    }
+
+    pub fn remove(&self, path: impl AsRef<Path>) -> Option<String> {
+        let mut files = self.files.lock().unwrap();
+        files.remove(path.as_ref()).map(|(contents, _)| contents)
+    }
+
+    /// The path of every file, in order.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.files.lock().unwrap().keys().cloned().collect()
+    }
+}
+
+impl FileLoader for MemoryProvider {
+    fn load(&self, path: &Path) -> io::Result<String> {
+        self.get(path).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not in memory", path.display()),
+            )
+        })
+    }
+
+    fn modified(&self, path: &Path) -> Option<SystemTime> {
+        let files = self.files.lock().unwrap();
+        let (_, version) = files.get(path)?;
+        Some(SystemTime::UNIX_EPOCH + Duration::from_nanos(*version))
+    }
+}
+
+impl SourceProvider for MemoryProvider {
+    fn list(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        if !self.is_dir(dir) {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not a directory in memory", dir.display()),
+            ));
+        }
+        let files = self.files.lock().unwrap();
+        let mut entries: Vec<PathBuf> = files
+            .keys()
+            .filter_map(|path| path.strip_prefix(dir).ok()?.components().next())
+            .map(|first| dir.join(first))
+            .collect();
+        entries.dedup();
+        Ok(entries)
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        let files = self.files.lock().unwrap();
+        files
+            .keys()
+            .any(|file| file != path && file.starts_with(path))
+    }
+
+    fn write(&self, path: &Path, contents: &str) -> io::Result<()> {
+        self.insert(path, contents);
+        Ok(())
+    }
+}
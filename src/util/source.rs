@@ -1,10 +1,52 @@
-use std::{ops::Range, str::Chars};
+use std::{ops::Range, str::Chars, sync::Arc};
+
+use crate::{
+    compiler::lexer::pos::{Position, Region},
+    report::Suggestion,
+};
+
+/// How many columns a tab is printed as in snippets, unless the buffer is given
+/// another width, see `SourceBuffer::with_tab_width`.
+pub const TAB_WIDTH: usize = 4;
+
+/// How many columns a char is printed as in a terminal.
+///
+/// Wide chars, eg: `名` or `😀`, take two and combining marks none. The ranges are
+/// the common ones of the east asian width of unicode, not every char of it.
+fn char_width(c: char, tab_width: usize) -> usize {
+    match c {
+        '\t' => tab_width,
+        '\u{0300}'..='\u{036F}' | '\u{200B}'..='\u{200F}' | '\u{FE00}'..='\u{FE0F}' => 0,
+        '\u{1100}'..='\u{115F}'
+        | '\u{2E80}'..='\u{303E}'
+        | '\u{3041}'..='\u{33FF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{A000}'..='\u{A4CF}'
+        | '\u{AC00}'..='\u{D7A3}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{FE30}'..='\u{FE4F}'
+        | '\u{FF00}'..='\u{FF60}'
+        | '\u{FFE0}'..='\u{FFE6}'
+        | '\u{1F300}'..='\u{1F64F}'
+        | '\u{1F900}'..='\u{1F9FF}'
+        | '\u{20000}'..='\u{3FFFD}' => 2,
+        _ => 1,
+    }
+}
 
 /// Keeps a cache of the source buffer for the given context.
 /// You can clear this using drop or `clean` on the struct.
+///
+/// Offsets are in chars. The offset every line starts at is indexed when the
+/// buffer is created, so finding the line of an offset doesn't scan the source.
 #[derive(Clone, Debug)]
 pub struct SourceBuffer {
     pub(crate) source: String,
+    /// The char and byte offset of the start of every line, shared by clones.
+    lines: Arc<Vec<(usize, usize)>>,
+    /// How many columns a tab is printed as, `TAB_WIDTH` by default.
+    tab_width: usize,
 }
 
 #[derive(Clone, Debug)]
@@ -13,37 +55,62 @@ pub struct SourceLine {
     len: usize,
     line: usize,
     source: String,
+    tab_width: usize,
 }
 
 impl SourceLine {
     pub fn new(offset: usize, line: usize, source: String) -> Self {
         Self {
             offset,
-            len: source.len(),
+            len: source.chars().count(),
             line,
             source,
+            tab_width: TAB_WIDTH,
         }
     }
 
+    /// The line with tabs printed as `width` columns.
+    pub fn with_tab_width(mut self, width: usize) -> Self {
+        self.tab_width = width;
+        self
+    }
+
     pub fn offset_relative(&self, range: Range<usize>) -> Range<usize> {
         let start = range.start - self.offset;
         let end = range.end - self.offset;
         start..end
     }
 
-    /// Returns the location of the error relative to the line with trimming.
+    /// Returns the location of the error relative to the line with trimming,
+    /// in the columns the trimmed line is printed with, see `printed`.
     pub fn spaces_until(&self, range: Range<usize>) -> usize {
-        let trimmed = self.source.trim_start().to_string();
         let relative = self.offset_relative(range);
+        let indent = self.len - self.source.trim_start().chars().count();
+        self.width(indent..relative.start.max(indent)) + 1
+    }
 
-        // get the offset based on the amount that was trimmed off.
-        let trimmed_amt = self.len() - trimmed.len();
-        let start = if relative.start < trimmed_amt {
-            0
-        } else {
-            relative.start - trimmed_amt
-        };
-        start + 1
+    /// How many columns the chars of the line in the range are printed as,
+    /// counted from 0. A tab is as wide as the tab width of the line.
+    pub fn width(&self, range: Range<usize>) -> usize {
+        self.source
+            .chars()
+            .skip(range.start)
+            .take(range.len())
+            .map(|c| char_width(c, self.tab_width))
+            .sum()
+    }
+
+    /// The line without its indentation, with tabs expanded to spaces so it is
+    /// as wide as `width` counts it.
+    pub fn printed(&self) -> String {
+        let mut printed = String::new();
+        for c in self.source.trim_start().chars() {
+            match c {
+                '\t' => printed.push_str(&" ".repeat(self.tab_width)),
+                c => printed.push(c),
+            }
+        }
+        printed
     }
 
     pub fn offset(&self) -> usize {
@@ -74,16 +141,42 @@ impl SourceLine {
 
 impl SourceBuffer {
     pub fn new(source: String) -> Self {
-        Self { source }
+        let mut lines = vec![(0, 0)];
+        for (i, (byte, c)) in source.char_indices().enumerate() {
+            if c == '\n' {
+                lines.push((i + 1, byte + 1));
+            }
+        }
+        Self {
+            source,
+            lines: Arc::new(lines),
+            tab_width: TAB_WIDTH,
+        }
     }
 
     pub fn empty() -> Self {
-        Self {
-            source: String::new(),
-        }
+        Self::new(String::new())
+    }
+
+    /// The buffer with tabs printed as `width` columns in the snippets of its reports,
+    /// eg: the width the editor of the source shows them with.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::{report::Report, util::source::SourceBuffer};
+    /// let buffer = SourceBuffer::new("\tvar x = 1\n".to_string()).with_tab_width(2);
+    /// Report::new().set_source(buffer).make_snippet(5..6, "Here.".to_string(), None);
+    /// ```
+    pub fn with_tab_width(mut self, width: usize) -> Self {
+        self.tab_width = width;
+        self
+    }
+
+    pub fn tab_width(&self) -> usize {
+        self.tab_width
     }
 
-    /// Gets a range of the source buffer.
+    /// Gets a range of the source buffer, padded with spaces past the end of the source.
     /// eg:
     /// ```rust no_run
     /// use std::ops::Range;
@@ -93,44 +186,103 @@ impl SourceBuffer {
     /// buffer.get(0..5);
     /// ```
     pub fn get(&self, rng: Range<usize>) -> String {
-        let mut result = String::new();
-        for i in rng {
-            result.push(self.chars().nth(i).unwrap_or(' '));
-        }
+        let mut result = self.slice(rng.clone()).to_string();
+        let missing = rng.len() - result.chars().count();
+        result.push_str(&" ".repeat(missing));
         result
     }
 
+    /// The source within a range, the range is cut off at the end of the source.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::util::source::SourceBuffer;
+    /// let buffer = SourceBuffer::new("var test = 10;".to_string());
+    /// buffer.slice(4..8); // "test"
+    /// ```
+    pub fn slice(&self, range: Range<usize>) -> &str {
+        let start = self.byte_offset(range.start);
+        let end = self.byte_offset(range.end.max(range.start));
+        &self.source[start..end]
+    }
+
+    /// The source with the suggestions applied, the spans are offsets into this buffer.
+    /// A suggestion that overlaps one before it is left out.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::{report::{Report, Suggestion}, util::source::SourceBuffer};
+    /// # let report = Report::new().add_suggestion(Suggestion::insert(9, ";", "Add a `;`.".to_string()));
+    /// let buffer = SourceBuffer::new("var x = 1\n".to_string());
+    /// buffer.apply(report.suggestions()); // "var x = 1;\n"
+    /// ```
+    pub fn apply<'a>(&self, suggestions: impl IntoIterator<Item = &'a Suggestion>) -> SourceBuffer {
+        let mut sorted: Vec<&Suggestion> = suggestions.into_iter().collect();
+        sorted.sort_by_key(|s| (s.span.start, s.span.end));
+        let mut applied = String::new();
+        let mut end = 0;
+        for suggestion in sorted {
+            if suggestion.span.start < end {
+                continue;
+            }
+            applied.push_str(self.slice(end..suggestion.span.start));
+            applied.push_str(&suggestion.replacement);
+            end = suggestion.span.end;
+        }
+        applied.push_str(self.slice(end..usize::MAX));
+        SourceBuffer::new(applied).with_tab_width(self.tab_width)
+    }
+
+    /// The 1 based line and column of an offset.
+    /// Returns None if the offset is past the end of the source.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::util::source::SourceBuffer;
+    /// let buffer = SourceBuffer::new("var test = 10;\nvar apple = 4;".to_string());
+    /// buffer.line_col(19); // Some((2, 5))
+    /// ```
+    pub fn line_col(&self, offset: usize) -> Option<(usize, usize)> {
+        let index = self.line_index(offset);
+        let column = offset - self.lines[index].0;
+        // only the last line doesn't end with a line break.
+        if index + 1 == self.lines.len() && column > self.line(index).len() {
+            return None;
+        }
+        Some((index + 1, column + 1))
+    }
+
+    /// The lines and columns a range covers, see `line_col`.
+    /// A range that goes past the end of the source ends at the end of it.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::util::source::SourceBuffer;
+    /// let buffer = SourceBuffer::new("var test = 10;\nvar apple = 4;".to_string());
+    /// buffer.region_of(4..19); // 1:5 to 2:5
+    /// ```
+    pub fn region_of(&self, range: Range<usize>) -> Region {
+        let position = |offset: usize| {
+            let (line, column) = self.line_col(offset).unwrap_or_else(|| {
+                let last = self.line(self.lines.len() - 1);
+                (last.line, last.len + 1)
+            });
+            Position::new(line, column)
+        };
+        Region::new(position(range.start), position(range.end), None)
+    }
+
+    /// The number of lines in the source.
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
     pub fn chars(&self) -> Chars {
         self.source.chars()
     }
 
     pub fn get_lines(&self) -> Vec<SourceLine> {
-        let mut lines = Vec::new();
-        let mut line_start = 0;
-        let mut line_end = 0;
-        let mut line: usize = 1;
-        for (i, c) in self.chars().enumerate() {
-            if c == '\n' {
-                lines.push(SourceLine {
-                    offset: line_start,
-                    len: line_end - line_start,
-                    source: self.get(line_start..line_end),
-                    line,
-                });
-                line += 1;
-                line_start = i + 1;
-                line_end = line_start;
-            } else {
-                line_end += 1;
-            }
-        }
-        lines.push(SourceLine {
-            offset: line_start,
-            len: line_end - line_start,
-            source: self.get(line_start..line_end),
-            line,
-        });
-        lines
+        (0..self.lines.len()).map(|line| self.line(line)).collect()
     }
 
     /// Attempts to find the line at the given offset and returns the entire line.
@@ -143,8 +295,37 @@ impl SourceBuffer {
     /// buffer.get_line(4); // returns: "var test = 10;"
     /// ```
     pub fn get_line_at(&self, offset: usize) -> Option<SourceLine> {
-        self.get_lines()
-            .into_iter()
-            .find(|line| (offset >= line.offset()) && (offset < line.offset_max()))
+        Some(self.line(self.line_index(offset))).filter(|line| offset < line.offset_max())
+    }
+
+    /// The 0 based index of the line an offset is on.
+    fn line_index(&self, offset: usize) -> usize {
+        self.lines.partition_point(|(start, _)| *start <= offset) - 1
+    }
+
+    /// The line at a 0 based index, without its line break.
+    fn line(&self, index: usize) -> SourceLine {
+        let (offset, byte) = self.lines[index];
+        let end = match self.lines.get(index + 1) {
+            Some((_, next)) => next - 1,
+            None => self.source.len(),
+        };
+        let source = self.source[byte..end].to_string();
+        SourceLine {
+            offset,
+            len: source.chars().count(),
+            line: index + 1,
+            source,
+            tab_width: self.tab_width,
+        }
+    }
+
+    /// The byte offset of a char offset, offsets past the end are the end of the source.
+    fn byte_offset(&self, offset: usize) -> usize {
+        let (start, byte) = self.lines[self.line_index(offset)];
+        self.source[byte..]
+            .char_indices()
+            .nth(offset - start)
+            .map_or(self.source.len(), |(i, _)| byte + i)
     }
 }
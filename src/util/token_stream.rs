@@ -1,50 +1,130 @@
-use std::collections::VecDeque;
+use std::{ops::Range, sync::Arc};
 
-use crate::compiler::lexer::token::Token;
+use crate::compiler::lexer::token::{Token, TokenType};
 
 use super::StreamBuffer;
 
+/// A useful utility for handling of tokens.
+/// Tokens are cheap to copy, their values are shared with the source, see `TokenValue`.
+/// The tokens are shared by every clone of a stream and by the streams sliced from it,
+/// a stream only keeps its position and where it ends.
 #[derive(Debug, Clone)]
-struct TokenIterator {
-    pub(super) buffer: VecDeque<Token>,
+pub struct TokenStream {
+    tokens: Arc<[Token]>,
+    /// The index of the first token of the stream, see `slice`.
+    start: usize,
+    /// The index of the next token.
+    position: usize,
+    /// The index after the last token of the stream.
+    end: usize,
+}
+
+/// A position of a token stream to go back to.
+///
+/// **Example:**
+/// ```rust no_run
+/// # use surn::compiler::lexer::tokenizer::tokenize;
+/// # use surn::util::{token_stream::TokenStream, StreamBuffer};
+/// # let mut tokens = TokenStream::new(tokenize("var x = 1;"));
+/// let checkpoint = tokens.checkpoint();
+/// tokens.peek();
+/// tokens.rewind(checkpoint); // the token is next again.
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    position: usize,
 }
 
-impl TokenIterator {
-    pub fn new(tokens: Vec<Token>) -> Self {
-        Self {
-            buffer: VecDeque::from(tokens),
+impl TokenStream {
+    /// Creates a new token stream with the given initial length.
+    pub fn new(tokens: Vec<Token>) -> TokenStream {
+        let end = tokens.len();
+        TokenStream {
+            tokens: tokens.into(),
+            start: 0,
+            position: 0,
+            end,
+        }
+    }
+
+    /// A stream of the tokens in the range, counted from the next token like `nth`.
+    /// The range is cut at the end of the stream, and this stream doesn't move.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::{compiler::lexer::tokenizer::tokenize, util::token_stream::TokenStream};
+    /// // foo(a, b)
+    /// # let tokens = TokenStream::new(tokenize("foo(a, b)"));
+    /// let arguments = tokens.slice(2..5); // a, b
+    /// ```
+    pub fn slice(&self, range: Range<usize>) -> TokenStream {
+        let end = (self.position + range.end).min(self.end);
+        let start = (self.position + range.start).min(end);
+        TokenStream {
+            tokens: self.tokens.clone(),
+            start,
+            position: start,
+            end,
         }
     }
-}
 
-impl Iterator for TokenIterator {
-    type Item = Token;
+    /// The tokens between the delimiter that is next and the one that closes it,
+    /// eg: the body of `{ ... }`, this stream moves past the closing delimiter.
+    /// `None` when the next token doesn't open anything or is never closed,
+    /// then this stream doesn't move.
+    ///
+    /// **Example:**
+    /// ```rust no_run
+    /// # use surn::compiler::lexer::tokenizer::tokenize;
+    /// # use surn::util::{token_stream::TokenStream, StreamBuffer};
+    /// // { a { b } } c
+    /// # let mut tokens = TokenStream::new(tokenize("{ a { b } } c"));
+    /// let body = tokens.balanced().unwrap(); // a { b }
+    /// tokens.first(); // c
+    /// ```
+    pub fn balanced(&mut self) -> Option<TokenStream> {
+        let open = self.first()?;
+        if delimiter(open.kind()) != Some(true) {
+            return None;
+        }
+        let mut depth = 0usize;
+        for (n, token) in self.tokens[self.position..self.end].iter().enumerate() {
+            match delimiter(token.kind()) {
+                Some(true) => depth += 1,
+                Some(false) => depth -= 1,
+                None => continue,
+            }
+            if depth == 0 {
+                let inner = self.slice(1..n);
+                self.position += n + 1;
+                return Some(inner);
+            }
+        }
+        None
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.buffer.pop_front()
+    /// The position of the stream, see `rewind`.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            position: self.position,
+        }
     }
-}
 
-/// A useful utility for handling of tokens.
-#[derive(Debug, Clone)]
-pub struct TokenStream {
-    /// A constantly resized vector of tokens
-    /// this is internally an iterator over tokens.
-    buffer: TokenIterator,
-    /// The initial length of the buffer before iterating
-    /// over it.
-    initial_length: usize,
-    prev: Option<Token>,
+    /// Goes back to a checkpoint, the tokens peeked since are peeked again.
+    /// A checkpoint can be rewound to more than once.
+    pub fn rewind(&mut self, checkpoint: Checkpoint) {
+        self.position = checkpoint.position;
+    }
 }
 
-impl TokenStream {
-    /// Creates a new token stream with the given initial length.
-    pub fn new(tokens: Vec<Token>) -> TokenStream {
-        TokenStream {
-            buffer: TokenIterator::new(tokens.clone()),
-            initial_length: tokens.len(),
-            prev: None,
+/// Whether a token opens a delimiter, `Some(false)` when it closes one.
+fn delimiter(kind: TokenType) -> Option<bool> {
+    match kind {
+        TokenType::LeftParenthesis | TokenType::LeftBracket | TokenType::LeftBrace => Some(true),
+        TokenType::RightParenthesis | TokenType::RightBracket | TokenType::RightBrace => {
+            Some(false)
         }
+        _ => None,
     }
 }
 
@@ -54,27 +134,35 @@ impl StreamBuffer for TokenStream {
     /// Peeks the next item in the iterator
     /// Basically a `next` call on the iterator.
     fn peek(&mut self) -> Option<Self::Item> {
-        let next = self.buffer.next();
-        self.prev = next.clone();
+        let next = self.first();
+        if next.is_some() {
+            self.position += 1;
+        }
         next
     }
 
     /// Attempts to reverse the last peeked item
-    /// If the last peeked item was not reversed, it will return `None`
+    /// If nothing was peeked yet, it will return `None`
     fn unpeek(&mut self) -> Option<Self::Item> {
-        // TODO: Implement this properly
-        None
+        if self.position == self.start {
+            return None;
+        }
+        self.position -= 1;
+        self.first()
     }
 
     /// Returns the last peeked item
-    /// If the last peeked item was not reversed, it will return `None`
+    /// If nothing was peeked yet, it will return `None`
     fn prev(&self) -> Option<Self::Item> {
-        self.prev.clone()
+        if self.position == self.start {
+            return None;
+        }
+        self.tokens.get(self.position - 1).cloned()
     }
 
     /// Returns whether or not the buffer is empty.
     fn is_eof(&self) -> bool {
-        self.buffer.buffer.is_empty()
+        self.position >= self.end
     }
 
     /// Returns the first item in the buffer without removing it.
@@ -89,17 +177,16 @@ impl StreamBuffer for TokenStream {
 
     /// Gets the `nth` item of the buffer without consuming it.
     fn nth(&self, n: usize) -> Option<Self::Item> {
-        let mut soft_copy = self.buffer.clone();
-        soft_copy.nth(n)
+        self.tokens[self.position..self.end].get(n).cloned()
     }
 
     /// Returns a copy of the buffer without consuming it.
     fn items(&self) -> Vec<Self::Item> {
-        self.buffer.buffer.clone().into_iter().collect()
+        self.tokens[self.position..self.end].to_vec()
     }
 
     /// Returns the amount of items in the buffer have been consumed.
     fn eaten(&self) -> usize {
-        self.initial_length - self.buffer.buffer.len()
+        self.position - self.start
     }
 }
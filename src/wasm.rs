@@ -0,0 +1,32 @@
+//! The compiler for javascript, eg: a playground that compiles in the browser.
+//!
+//! There is no disk, process or threads in `wasm32-unknown-unknown`, so it is built
+//! without the default features, `wasm-bindgen` then writes the javascript module:
+//! `cargo build --target wasm32-unknown-unknown --no-default-features --features wasm`
+//!
+//! **Example:**
+//! ```js
+//! import { compile_to_php } from "./surn.js";
+//! compile_to_php("var x = 5;"); // "<?php\n\n$x = 5;\n"
+//! ```
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    compile_str,
+    compiler::CompilerOptions,
+    report::{Report, ReportTheme},
+};
+
+/// Compiles a source to php, the reports of a source that doesn't compile are thrown,
+/// rendered without colors.
+#[wasm_bindgen]
+pub fn compile_to_php(source: &str) -> Result<String, JsValue> {
+    let output = compile_str(source, &CompilerOptions::default())
+        .map_err(|reports| JsValue::from_str(&render(&reports)))?;
+    Ok(output.code.get("php").cloned().unwrap_or_default())
+}
+
+fn render(reports: &[Report]) -> String {
+    let theme = ReportTheme::plain();
+    reports.iter().map(|report| report.render(&theme)).collect()
+}
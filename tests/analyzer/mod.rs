@@ -1,7 +1,10 @@
 use std::fs;
 
-use surn::compiler::lexer::analysis::analyze;
+use crate::dev;
+use surn::compiler::lexer::analysis::{analyze, misspelled_keywords, unbalanced};
 use surn::compiler::lexer::tokenizer::tokenize;
+use surn::compiler::parser::Parser;
+use surn::report::{codes, ReportKind};
 
 // Tests the tokenizer with the given file.
 pub const TEST_A: &str = "tests/resources/test_a.surn";
@@ -12,11 +15,11 @@ pub fn test_analyze() {
     let contents = fs::read_to_string(TEST_A).unwrap();
     let tokens = tokenize(contents.as_str());
 
-    let results = analyze(tokens);
-    if results.is_ok() {
+    let Err(diagnostics) = analyze(tokens) else {
         panic!("Analyzer failed to detect errors.");
-    } else {
-        println!("{}", results.unwrap_err());
+    };
+    for diagnostic in diagnostics {
+        println!("{}", diagnostic);
     }
 }
 
@@ -26,3 +29,72 @@ pub fn test_print() {
     let contents = fs::read_to_string(ERROR_A).unwrap();
     println!("{}", contents);
 }
+
+#[test]
+pub fn test_unbalanced_delimiters() {
+    let source = "fn main() {\n    call(items[0;\n}\n)\n";
+    let found = unbalanced(&tokenize(source));
+    let found: Vec<(&str, &str)> = found
+        .iter()
+        .map(|d| (d.message.as_str(), &source[d.spans[0].span.clone()]))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            ("This `(` is never closed.", "("),
+            ("This `[` is never closed.", "["),
+            ("This `)` closes nothing.", ")"),
+        ]
+    );
+
+    // the parser reports the delimiter where it is opened.
+    let mut parser = Parser::new(dev());
+    let report = parser
+        .try_parse_script(
+            "unclosed.surn".to_string(),
+            "fn main() {\n    var x = 1;\n".to_string(),
+        )
+        .unwrap_err();
+    assert_eq!(report.code, codes::UNCLOSED_DELIMITER);
+    assert_eq!(report.snippets[0].range(), 10..11);
+}
+
+#[test]
+pub fn test_analyzer_reports() {
+    // every problem is found, in the order it is in the source.
+    let source = "fn main() {\n    var path = \"C:\\data\";\n    call(1;\n}\n";
+    let found = analyze(tokenize(source)).unwrap_err();
+    assert_eq!(
+        found.iter().map(|d| d.code).collect::<Vec<u64>>(),
+        vec![codes::INVALID_ESCAPE, codes::UNCLOSED_DELIMITER]
+    );
+
+    let errors = surn::compile_str(source, &dev()).unwrap_err();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(&source[errors[1].snippets[0].range()], "(");
+}
+
+#[test]
+pub fn test_misspelled_keywords() {
+    let source = "clas User {\n}\nfunction main() {\n    retrun 1;\n    var clas = 2;\n}\n";
+    let found = misspelled_keywords(&tokenize(source));
+    assert_eq!(
+        found
+            .iter()
+            .map(|d| d.suggestions[0].replacement.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["class", "fn", "return"]
+    );
+    assert_eq!(found[0].severity, ReportKind::Notice);
+    assert_eq!(found[0].code, codes::MISSPELLED_KEYWORD);
+    assert_eq!(&source[found[1].suggestions[0].span.clone()], "function");
+
+    // the notices are kept with the warnings of the parser, the source still fails to parse.
+    let mut parser = Parser::new(dev());
+    let report = parser
+        .try_parse_script("typos.surn".to_string(), source.to_string())
+        .unwrap_err();
+    // `clas User` reads as a name missing its semicolon, the parser stops there.
+    assert_eq!(report.suggestions()[0].replacement, "class");
+    assert_eq!(parser.warnings().len(), 3);
+}
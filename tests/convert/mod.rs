@@ -0,0 +1,172 @@
+// tests for converting php to surn.
+use crate::dev;
+use surn::compiler::parser::Parser;
+use surn::convert::declarations::{php_to_declarations, surn_type};
+use surn::convert::php_to_surn::php_to_surn;
+use surn::transpiler::{format::FormatOptions, Transpiler};
+
+#[test]
+pub fn convert_php_declarations() {
+    let php = r#"<?php
+namespace Legacy\Billing;
+
+use Legacy\Support\Model;
+use Legacy\Support\{Money, Currency};
+use \Legacy\Support\Clock as SystemClock;
+use function Legacy\Support\format_money;
+
+const VERSION = '1.2', DEBUG = false;
+
+/**
+ * @param callable(int): bool $filter
+ * @return list<int>
+ */
+function filter_ids(array $ids, callable $filter, int ...$extra): array
+{
+    return array_filter($ids, $filter);
+}
+
+interface Payable extends Countable {}
+
+final class Invoice extends Model implements Payable
+{
+    const TAX = 0.2;
+    private const RATES = ['EUR' => 1.0];
+    protected const DEFAULT_TAX = self::TAX;
+
+    /** @var Line[] */
+    protected array $lines = [];
+    public static ?string $currency = 'EUR';
+
+    public function __construct(private int $id, string $note = "{ not a block }") {}
+
+    /** @return static */
+    public static function find(int $id): ?self
+    {
+        return new static($id);
+    }
+
+    public function total(): float|int { return 0; }
+
+    abstract protected function lock(): void;
+}
+"#;
+    let declarations = php_to_declarations(php);
+    assert_eq!(
+        declarations,
+        "// Generated by surn from php, only the signatures are kept.
+namespace Legacy\\Billing;
+use Legacy::Support::Model;
+use Legacy::Support::Clock as SystemClock;
+
+const VERSION = \"1.2\";
+const DEBUG = false;
+
+fn filter_ids(ids: array, filter: (int) => bool, extra: array): array {
+}
+
+// interface
+class Payable implements Countable {
+}
+
+class Invoice extends Model implements Payable {
+    const TAX = 0.2;
+    // TODO: the value of `RATES` can't be converted to surn.
+    prot const DEFAULT_TAX = self::TAX;
+    priv id: int;
+    prot lines: array;
+    pub static currency: string?;
+    pub fn __construct(id: int, note: string) {
+    }
+    pub static fn find(id: int): Invoice {
+    }
+    pub fn total(): float | int {
+    }
+    prot fn lock() {
+    }
+}
+"
+    );
+
+    let mut parser = Parser::new(dev());
+    assert!(parser
+        .try_parse_script("Invoice.d.surn".to_string(), declarations)
+        .is_ok());
+
+    assert_eq!(
+        surn_type("?\\App\\User", None).unwrap().to_string(),
+        "User?"
+    );
+    assert_eq!(
+        surn_type("Collection<int, User>|null", None)
+            .unwrap()
+            .to_string(),
+        "Collection<int, User>?"
+    );
+    assert!(surn_type("void", None).is_none());
+}
+
+#[test]
+pub fn convert_php_to_surn() {
+    let php = r#"<?php
+namespace App\Billing;
+
+use App\Support\Model;
+
+class Invoice extends Model
+{
+    const TAX = 2;
+    public static ?string $currency = 'EUR';
+
+    public function __construct(private int $id)
+    {
+        $total = $id * 2;
+        if ($total > 10) {
+            $total = 10;
+        }
+        $this->total = $total;
+    }
+}
+"#;
+    let surn = php_to_surn(php);
+    assert_eq!(
+        surn,
+        "namespace App\\Billing;
+use App::Support::Model;
+
+class Invoice extends Model {
+    // TODO: `const` can't be converted to surn yet.
+    php! {
+        const TAX = 2;
+    }
+    pub static currency: string? = \"EUR\";
+
+    priv id: int;
+
+    pub fn __construct(id: int) {
+        this.id = id;
+        var total = id * 2;
+        // TODO: `if` can't be converted to surn yet.
+        php! {
+            if ($total > 10) {
+                $total = 10;
+            }
+        }
+        this.total = total;
+    }
+}
+"
+    );
+
+    let mut transpiler = Transpiler::new();
+    transpiler.register_defaults();
+    let mut parser = Parser::new(dev());
+    let body = parser.parse_script("Invoice.surn".to_string(), surn);
+    let output = transpiler.get("php").unwrap().generator.generate_to_string(
+        body,
+        dev(),
+        &FormatOptions::default(),
+    );
+    assert!(output.contains("    const TAX = 2;\n    public static ?string $currency = \"EUR\";"));
+    assert!(output.contains("        if ($total > 10) {\n            $total = 10;\n        }\n        $this->total = $total;"));
+}
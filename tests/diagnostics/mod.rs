@@ -0,0 +1,161 @@
+// compiles every case in tests/resources/errors and checks the diagnostics it reports.
+//
+// a case marks the line it expects a diagnostic on with a comment:
+//
+// var x = 1 //~ ERROR missing semicolon
+// //~^ ERROR ...  expects it on the line above, a `^` per line
+//
+// the kind is `ERROR`, `WARNING` or `NOTICE`, what follows is a code, eg: `E0102`,
+// the title of a code, eg: `missing semicolon`, or a part of the message.
+// every diagnostic has to be expected and every expectation reported.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::dev;
+use surn::{
+    compile_str,
+    report::{codes, Report, ReportKind},
+};
+
+pub const CASES: &str = "tests/resources/errors";
+
+#[derive(Debug)]
+struct Expectation {
+    line: usize,
+    kind: ReportKind,
+    text: String,
+}
+
+/// The expectations of a case, or why one of them can't be read.
+fn expectations(code: &str) -> Result<Vec<Expectation>, String> {
+    let mut expected = Vec::new();
+    for (index, line) in code.lines().enumerate() {
+        let Some((_, annotation)) = line.split_once("//~") else {
+            continue;
+        };
+        let above = annotation.chars().take_while(|c| *c == '^').count();
+        let (kind, text) = annotation[above..]
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| format!("line {}: `//~` expects a kind and a text", index + 1))?;
+        let kind = match kind {
+            "ERROR" => ReportKind::Error,
+            "WARNING" => ReportKind::Warning,
+            "NOTICE" => ReportKind::Notice,
+            kind => return Err(format!("line {}: unknown kind `{}`", index + 1, kind)),
+        };
+        let line = (index + 1)
+            .checked_sub(above)
+            .filter(|line| *line > 0)
+            .ok_or_else(|| format!("line {}: `^` points before the first line", index + 1))?;
+        expected.push(Expectation {
+            line,
+            kind,
+            text: text.trim().to_string(),
+        });
+    }
+    Ok(expected)
+}
+
+/// Whether a report is what an expectation describes.
+fn matches(expectation: &Expectation, report: &Report) -> bool {
+    if report.kind != expectation.kind
+        || report.location().map(|(line, _)| line) != Some(expectation.line)
+    {
+        return false;
+    }
+    if let Some(code) = codes::parse_code(&expectation.text) {
+        return report.code == code;
+    }
+    let text = expectation.text.as_str();
+    codes::lookup(report.code).is_some_and(|code| code.title.eq_ignore_ascii_case(text))
+        || report.message.contains(text)
+        || report
+            .snippets
+            .iter()
+            .any(|snippet| snippet.get_print().contains(text))
+}
+
+fn describe(report: &Report) -> String {
+    let line = report.location().map(|(line, _)| line).unwrap_or_default();
+    format!(
+        "{} {} at line {}: {}",
+        report.kind,
+        codes::format_code(report.code),
+        line,
+        report
+            .snippets
+            .first()
+            .map(|s| s.get_print())
+            .unwrap_or_else(|| report.message.clone())
+    )
+}
+
+/// Checks a single case, returns why it failed.
+fn check(case: &Path) -> Result<(), String> {
+    let name = case.file_name().unwrap().to_string_lossy().to_string();
+    let code = fs::read_to_string(case).unwrap();
+    let expected = expectations(&code).map_err(|e| format!("{}: {}", name, e))?;
+
+    let mut options = dev();
+    options.post_semantic_checks = true;
+    options.ast_only = true;
+    let reports = match compile_str(&code, &options) {
+        Ok(output) => output.diagnostics,
+        Err(errors) => errors,
+    };
+
+    let mut problems = Vec::new();
+    let mut matched = vec![false; reports.len()];
+    for expectation in expected.iter() {
+        let found = reports
+            .iter()
+            .enumerate()
+            .find(|(i, report)| !matched[*i] && matches(expectation, report));
+        match found {
+            Some((i, _)) => matched[i] = true,
+            None => problems.push(format!(
+                "expected {} `{}` at line {}",
+                expectation.kind, expectation.text, expectation.line
+            )),
+        }
+    }
+    for (report, _) in reports.iter().zip(matched).filter(|(_, matched)| !matched) {
+        problems.push(format!("unexpected {}", describe(report)));
+    }
+    match problems.is_empty() {
+        true => Ok(()),
+        false => Err(format!("{}:\n{}", name, problems.join("\n"))),
+    }
+}
+
+#[test]
+pub fn expected_diagnostics() {
+    let mut cases: Vec<PathBuf> = fs::read_dir(CASES)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|e| e == "surn"))
+        .collect();
+    cases.sort();
+    assert!(!cases.is_empty(), "there are no cases in {}", CASES);
+
+    let failures: Vec<String> = cases.iter().filter_map(|case| check(case).err()).collect();
+    assert!(failures.is_empty(), "{}", failures.join("\n\n"));
+}
+
+#[test]
+pub fn test_expectations() {
+    let code = "var x = 1 //~ ERROR missing semicolon\n\n//~^^ WARNING E0205\n";
+    let expected = expectations(code).unwrap();
+    assert_eq!(expected.len(), 2);
+    assert_eq!((expected[0].line, expected[0].kind), (1, ReportKind::Error));
+    assert_eq!(expected[0].text, "missing semicolon");
+    assert_eq!(
+        (expected[1].line, expected[1].kind),
+        (1, ReportKind::Warning)
+    );
+    assert!(expectations("//~^ ERROR x").is_err());
+    assert!(expectations("//~ FATAL x").is_err());
+}
@@ -0,0 +1,103 @@
+// runs the generated php of every fixture in tests/resources/golden and checks what it prints.
+//
+// each `name.surn` fixture is compiled with the default options and run with `php`
+// (or the binary in `SURN_PHP`), its stdout must equal `name.stdout` and its exit code
+// `name.exit` (0 when there is none).
+//
+// cargo test --features golden golden
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use surn::compiler::{optimize::optimize, parser::Parser, CompilerOptions};
+use surn::transpiler::{format::FormatOptions, Transpiler};
+
+pub const FIXTURES: &str = "tests/resources/golden";
+
+fn php_binary() -> Option<String> {
+    let binary = env::var("SURN_PHP").unwrap_or_else(|_| "php".to_string());
+    let available = Command::new(&binary)
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success());
+    available.then_some(binary)
+}
+
+fn compile(name: &str, code: &str) -> Result<String, String> {
+    let options = CompilerOptions::default();
+    let mut parser = Parser::new(options.clone());
+    let mut body = parser
+        .try_parse_script(name.to_string(), code.to_string())
+        .map_err(|report| report.message)?;
+    optimize(&mut body, &options);
+    let mut transpiler = Transpiler::new();
+    transpiler.register_defaults();
+    let php = transpiler.get("php").unwrap();
+    let (generated, _) = php
+        .generator
+        .generate_with_map(body, options, &FormatOptions::psr_4());
+    Ok(generated)
+}
+
+/// Runs a single fixture, returns why it failed.
+fn run(php: &str, fixture: &Path, scratch: &Path) -> Result<(), String> {
+    let name = fixture.file_name().unwrap().to_string_lossy().to_string();
+    let code = fs::read_to_string(fixture).unwrap();
+    let generated = compile(&name, &code).map_err(|e| format!("{}: {}", name, e))?;
+    let script = scratch.join(&name).with_extension("php");
+    fs::write(&script, &generated).unwrap();
+
+    let output = Command::new(php)
+        .arg(&script)
+        .output()
+        .map_err(|e| format!("{}: could not run php: {}", name, e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let expected = fs::read_to_string(fixture.with_extension("stdout")).unwrap_or_default();
+    if stdout != expected {
+        return Err(format!(
+            "{}: expected the output\n{}\nbut got\n{}\nfrom\n{}",
+            name, expected, stdout, generated
+        ));
+    }
+    let exit = fs::read_to_string(fixture.with_extension("exit"))
+        .map(|code| code.trim().parse::<i32>().unwrap())
+        .unwrap_or(0);
+    if output.status.code() != Some(exit) {
+        return Err(format!(
+            "{}: expected the exit code {} but got {:?}\n{}",
+            name,
+            exit,
+            output.status.code(),
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+pub fn golden_php_output() {
+    let php = match php_binary() {
+        Some(php) => php,
+        None => {
+            println!("php was not found, set SURN_PHP to run the golden tests.");
+            return;
+        }
+    };
+    let mut fixtures: Vec<PathBuf> = fs::read_dir(FIXTURES)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|e| e == "surn"))
+        .collect();
+    fixtures.sort();
+
+    let scratch = env::temp_dir().join(format!("surn-golden-{}", std::process::id()));
+    fs::create_dir_all(&scratch).unwrap();
+    let failures: Vec<String> = fixtures
+        .iter()
+        .filter_map(|fixture| run(&php, fixture, &scratch).err())
+        .collect();
+    fs::remove_dir_all(&scratch).unwrap();
+    assert!(failures.is_empty(), "{}", failures.join("\n\n"));
+}
@@ -0,0 +1,232 @@
+use crate::dev;
+use surn::compiler::{
+    parser::{
+        context::{Context, SourceOrigin},
+        Parser,
+    },
+    stdlib::STDLIB_NAME,
+};
+use surn::ide::{
+    apply_edits, code_actions,
+    imports::organize_imports,
+    inlay::{inlay_hints, InlayHintKind},
+    on_type::on_type_formatting,
+    project::{Location, Project},
+    quick_fix::binding_fixes,
+    refactor::{extract_function, inline_variable},
+};
+use surn::transpiler::format::{BraceType, FormatOptions};
+
+#[test]
+pub fn test_inlay_hints() {
+    let code = "fn add(a: int, b: int): int {\n    return a;\n}\nvar x = 5;\nvar total = add(x, 2);\nvar name: string = \"surn\";\n";
+    let mut parser = Parser::new(dev());
+    let body = parser.parse_script("inlay.surn".to_string(), code.to_string());
+
+    let hints = inlay_hints(code, &body);
+    let labels = hints
+        .iter()
+        .map(|h| (h.label.as_str(), &code[h.offset..h.offset + 1]))
+        .collect::<Vec<(&str, &str)>>();
+
+    // `name` is annotated, so it has no hint.
+    assert_eq!(
+        labels,
+        vec![(": int", " "), (": int", " "), ("a:", "x"), ("b:", "2")]
+    );
+    assert_eq!(hints[2].kind, InlayHintKind::Parameter);
+}
+
+#[test]
+pub fn test_on_type_formatting() {
+    let options = FormatOptions::default();
+
+    let code = "fn main() {\nvar x = foo( 1 ,2 ) ;\n}\n";
+    let offset = code.find(';').unwrap() + 1;
+    let edits = on_type_formatting(code, offset, ';', &options);
+    assert_eq!(
+        apply_edits(code, &edits),
+        "fn main() {\n    var x = foo(1, 2);\n}\n"
+    );
+
+    let code = "fn main()\n{\nvar x = 1;\n  }\n";
+    let offset = code.rfind('}').unwrap() + 1;
+    let edits = on_type_formatting(code, offset, '}', &options);
+    assert_eq!(
+        apply_edits(code, &edits),
+        "fn main() {\n    var x = 1;\n}\n"
+    );
+
+    let allman = FormatOptions {
+        function_brace: BraceType::Allman,
+        ..FormatOptions::default()
+    };
+    let code = "fn main() {\nvar x = 1;\n}\n";
+    let edits = on_type_formatting(code, code.len() - 1, '}', &allman);
+    assert_eq!(
+        apply_edits(code, &edits),
+        "fn main()\n{\n    var x = 1;\n}\n"
+    );
+}
+
+#[test]
+pub fn test_organize_imports() {
+    let code = "use std::io;\nuse app::Zed;\nvar x = alpha(Zed, Io);\nuse app::{alpha, Zed};\nuse std::fs as Io;\n";
+    let mut parser = Parser::new(dev());
+    let body = parser.parse_script("imports.surn".to_string(), code.to_string());
+
+    let edits = organize_imports(code, &body);
+    let organized = apply_edits(code, &edits);
+    assert_eq!(
+        organized,
+        "use app::{alpha, Zed};\nuse std::fs as Io;\nvar x = alpha(Zed, Io);\n"
+    );
+
+    // organizing twice changes nothing.
+    let body = parser.parse_script("imports.surn".to_string(), organized.clone());
+    assert!(organize_imports(&organized, &body).is_empty());
+}
+
+#[test]
+pub fn test_binding_fixes() {
+    let code = "fn main(a: int) {\n    var x = 1;\n    const y = 2;\n    y = x;\n    var z = 3;\n    z = 4;\n    a = 5;\n}\n";
+    let mut parser = Parser::new(dev());
+    let body = parser.parse_script("bindings.surn".to_string(), code.to_string());
+
+    let actions = binding_fixes(&body);
+    let titles = actions
+        .iter()
+        .map(|a| a.title.as_str())
+        .collect::<Vec<&str>>();
+    assert_eq!(
+        titles,
+        vec!["Change `var x` to `const`", "Change `const y` to `var`"]
+    );
+    assert_eq!(
+        apply_edits(code, &actions[1].edits),
+        code.replace("const y", "var y")
+    );
+}
+
+#[test]
+pub fn test_extract_function() {
+    let code = "fn main(a: int) {\n    var b = a * 2;\n    var c = b + 1;\n}\n";
+    let mut parser = Parser::new(dev());
+    let mut context = Context::new(
+        SourceOrigin::new_virtual("extract.surn".to_string(), code.to_string()),
+        1,
+    );
+    context.body = parser.parse_script("extract.surn".to_string(), code.to_string());
+
+    let start = code.find("var b").unwrap();
+    let end = code.find("\n    var c").unwrap();
+    let extraction = extract_function(&context, start..end, "double").unwrap();
+    assert_eq!(extraction.function.inputs.len(), 1);
+    assert_eq!(
+        apply_edits(code, &extraction.edits),
+        "fn main(a: int) {\n    var b = double(a);\n    var c = b + 1;\n}\n\nfn double(a: int): int {\n    var b = a * 2;\n    return b;\n}\n"
+    );
+
+    // a selection that is only an expression is returned as is.
+    let start = code.find("b + 1").unwrap();
+    let extraction = extract_function(&context, start..start + 5, "next").unwrap();
+    assert_eq!(
+        apply_edits(code, &extraction.edits[..1]),
+        code.replace("b + 1", "next(b)")
+    );
+}
+
+#[test]
+pub fn test_inline_variable() {
+    let code = "fn main(b: int) {\n    var a = b + 1;\n    var c = a * 2;\n    var d = a;\n}\n";
+    let mut parser = Parser::new(dev());
+    let body = parser.parse_script("inline.surn".to_string(), code.to_string());
+
+    let offset = code.find("a = b").unwrap();
+    let inline = code_actions(code, &body, offset..offset)
+        .into_iter()
+        .find(|a| a.title == "Inline variable `a`")
+        .unwrap();
+    assert_eq!(
+        apply_edits(code, &inline.edits),
+        "fn main(b: int) {\n    var c = (b + 1) * 2;\n    var d = (b + 1);\n}\n"
+    );
+
+    // `b` changes between the declaration and the use, so `a` can't be moved.
+    let code = "fn main(b: int) {\n    var a = b;\n    b = 2;\n    var c = a;\n}\n";
+    let body = parser.parse_script("inline.surn".to_string(), code.to_string());
+    assert!(inline_variable(code, &body, code.find("a = b").unwrap()).is_err());
+
+    // calls can't be duplicated.
+    let code = "fn main() {\n    var a = foo();\n    var c = a;\n    var d = a;\n}\n";
+    let body = parser.parse_script("inline.surn".to_string(), code.to_string());
+    assert!(inline_variable(code, &body, code.find("a = foo").unwrap()).is_err());
+}
+
+#[test]
+pub fn test_project_definitions() {
+    let lib = "pub fn double(n: int): int {\n    return n;\n}\n";
+    let main = "var x = 1;\nfn f(x: int): int {\n    return x;\n}\nvar y = double(x);\n";
+    let mut project = Project::new(dev());
+    project.open("lib.surn", lib).unwrap();
+    project.open("main.surn", main).unwrap();
+
+    // the `x` returned by `f` is its parameter, the one given to `double` the variable.
+    let parameter = project.definition_at("main.surn", main.find("return x").unwrap() + 7);
+    assert_eq!(&main[parameter.unwrap().location.span], "x: int");
+    let variable = project
+        .definition_at("main.surn", main.rfind('x').unwrap())
+        .unwrap();
+    assert_eq!(&main[variable.location.span.clone()], "var x = 1;");
+    assert_eq!(
+        project.references(&variable),
+        vec![Location {
+            file: "main.surn".to_string(),
+            span: main.rfind('x').unwrap()..main.rfind('x').unwrap() + 1,
+        }]
+    );
+
+    let double = project
+        .definition_at("main.surn", main.find("double").unwrap())
+        .unwrap();
+    assert_eq!(double.location.file, "lib.surn");
+    assert_eq!(&lib[double.location.span.clone()], "fn double");
+    assert_eq!(project.references(&double).len(), 1);
+}
+
+#[test]
+pub fn test_project_hover() {
+    let main = "fn add(a: int, b: int): int {\n    return a + b;\n}\nfn log(message: string) {\n    echo(message);\n}\nvar x = add(1, 2);\n";
+    let mut project = Project::new(dev());
+    project.open("main.surn", main).unwrap();
+
+    let add = project
+        .hover("main.surn", main.rfind("add").unwrap())
+        .unwrap();
+    assert!(add.starts_with("function `add`"));
+    assert!(add.ends_with("\npure"));
+    let log = project
+        .hover("main.surn", main.find("log").unwrap())
+        .unwrap();
+    assert!(log.ends_with("\nimpure, calls `echo`, which does I/O"));
+}
+
+#[test]
+pub fn test_project_stdlib() {
+    let main = "fn shout(message: string) {\n    println(to_upper(message));\n}\n";
+    let mut project = Project::new(dev());
+    project.open("main.surn", main).unwrap();
+
+    let println = project
+        .definition_at("main.surn", main.find("println").unwrap())
+        .unwrap();
+    assert_eq!(println.location.file, STDLIB_NAME);
+    let hover = project
+        .hover("main.surn", main.find("to_upper").unwrap())
+        .unwrap();
+    assert_eq!(hover, "function `to_upper`: (string) => string\npure");
+    let shout = project
+        .hover("main.surn", main.find("shout").unwrap())
+        .unwrap();
+    assert!(shout.ends_with("\nimpure, calls `println`, which does I/O"));
+}
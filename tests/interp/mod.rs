@@ -0,0 +1,108 @@
+use surn::compiler::{
+    ast::{
+        expr,
+        ops::{AnyOperation, BinOp, ComparisonOp, LogicalOp},
+        AstBody, Expression, MemberLookup, Operation,
+    },
+    interp::{Interpreter, Value},
+    parser::Parser,
+    CompilerOptions,
+};
+
+fn parse(code: &str) -> AstBody {
+    let mut parser = Parser::new(CompilerOptions::default());
+    parser
+        .try_parse_script("interp.surn".to_string(), code.to_string())
+        .unwrap()
+}
+
+#[test]
+pub fn interpret_functions_and_values() {
+    let body = parse(
+        "var total = 0;\nfn add(n: int): int { total = total + n; return total; }\nvar first = add(2 + 3 * 4);\nvar second = add(1);\nvar items = [first, second, ...[7]];\nvar point = { x: 1, y: 2.5 };\nvar y = point.y;\nvar (a, b) = (\"a\", true);\nprintln(join([\"a\", \"b\"], \"-\"));\nlen(items);\n",
+    );
+    let mut interpreter = Interpreter::new();
+    let last = interpreter.run(&body).unwrap();
+    assert_eq!(last, Some(Value::Int(3)));
+    assert_eq!(interpreter.get("total"), Some(&Value::Int(15)));
+    assert_eq!(interpreter.get("first"), Some(&Value::Int(14)));
+    assert_eq!(interpreter.get("y"), Some(&Value::Float(2.5)));
+    assert_eq!(interpreter.get("b"), Some(&Value::Bool(true)));
+    assert_eq!(
+        interpreter.get("point").unwrap().to_string(),
+        "{ x: 1, y: 2.5 }"
+    );
+    assert_eq!(interpreter.output(), "a-b\n");
+    assert_eq!(
+        interpreter.call("add", vec![Value::Int(5)]),
+        Ok(Value::Int(20))
+    );
+
+    // items[2]
+    let mut element = expr::member("items", "2");
+    if let Expression::Member(member) = &mut element {
+        member.lookup = MemberLookup::Index;
+        *member.name = expr::int(2);
+    }
+    assert_eq!(interpreter.evaluate(&element), Ok(Value::Int(7)));
+}
+
+#[test]
+pub fn interpret_control_flow() {
+    let body = parse(
+        "fn fail(): int { throw \"nope\"; }\nfn safe(): string { try { fail(); } catch (e) { return e; } finally { println(\"done\"); } }\nvar caught = safe();\nfn forever(n: int): int { return forever(n + 1); }\n",
+    );
+    let mut interpreter = Interpreter::new();
+    interpreter.run(&body).unwrap();
+    assert_eq!(
+        interpreter.get("caught"),
+        Some(&Value::String("nope".into()))
+    );
+    assert_eq!(interpreter.output(), "done\n");
+
+    // false && fail() == 1, the call is never made.
+    let lazy = Expression::Operation(Operation::new(
+        expr::bool(false),
+        AnyOperation::LogicalOp(LogicalOp::And),
+        Expression::Operation(Operation::new(
+            expr::call("fail", []),
+            AnyOperation::ComparisonOp(ComparisonOp::Eq),
+            expr::int(1),
+        )),
+    ));
+    assert_eq!(interpreter.evaluate(&lazy), Ok(Value::Bool(false)));
+    let sum = expr::binary(expr::string("a"), BinOp::Minus, expr::int(1));
+    assert_eq!(
+        interpreter.evaluate(&sum).unwrap_err().message,
+        "`-` can't be applied to `\"a\"` and `1`."
+    );
+
+    let error = interpreter
+        .call("forever", vec![Value::Int(0)])
+        .unwrap_err();
+    assert!(error.message.starts_with("Calls nest deeper than"));
+    let error = interpreter.run(&parse("fail();")).unwrap_err();
+    assert_eq!(error.message, "Uncaught \"nope\".");
+    let error = interpreter.run(&parse("var z = 1 + missing;")).unwrap_err();
+    assert_eq!(error.message, "`missing` is not defined.");
+    assert_eq!(error.span, Some(0..19));
+
+    interpreter
+        .run(&parse(
+            "fn kind(x: int | string): string { if (x is int) { return \"int\"; } else if (x is string) { return \"string\"; } else { return \"other\"; } }\nvar number = kind(1);\nvar text = kind(\"a\");\n",
+        ))
+        .unwrap();
+    assert_eq!(
+        interpreter.get("number"),
+        Some(&Value::String("int".into()))
+    );
+    assert_eq!(
+        interpreter.get("text"),
+        Some(&Value::String("string".into()))
+    );
+    let error = interpreter.run(&parse("if (1) { }")).unwrap_err();
+    assert_eq!(
+        error.message,
+        "`1` is not a `bool`, an if only tests a `bool`."
+    );
+}
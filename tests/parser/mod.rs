@@ -2,28 +2,1266 @@
 
 use std::{
     fs::{self, File},
-    io::Write,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
 };
 
-use surn::compiler::{parser::Parser, CompilerOptions};
+use crate::{dev, parse};
+use surn::{
+    compiler::{
+        ast::{arena::NodeMap, Expression, NodeKind, Statement},
+        eval::check_consts,
+        infer::{
+            awaits::check_awaits, checker::check_types, generics::check_generics,
+            nullability::check_nulls, purity::function_purities, shapes::check_shapes,
+        },
+        lexer::tokenizer::tokenize,
+        lints::{LintLevel, UNUSED_VARIABLE},
+        names::NameGenerator,
+        optimize::dead_calls::remove_dead_calls,
+        parser::{
+            context::{Context, SourceMap, SourceOrigin},
+            fix_its,
+            generator::AstGenerator,
+            trace::Outcome,
+            Parser,
+        },
+        resolve::{
+            cycles::find_cycles, inheritance::check_inheritance, members::check_members, resolve,
+            types::TypeResolver, NameErrorKind,
+        },
+        symbols::SymbolKind,
+        CompilerOptions,
+    },
+    report::{codes, diagnostic::Diagnostic, Report, ReportKind, ReportTheme},
+    util::{provider::FileLoader, source::SourceBuffer, token_stream::TokenStream},
+};
 
 pub const FULL_TEST: &str = "tests/resources/test_a.surn";
 pub const EXPRESSIONS: &str = "tests/resources/expressions.surn";
 
+/// Parses `code` as the script `name` and returns the report it fails with.
+fn parse_error(name: &str, code: &str) -> Report {
+    Parser::new(dev())
+        .try_parse_script(name.to_string(), code.to_string())
+        .unwrap_err()
+}
+
 #[test]
 pub fn test_parse() {
     let contents = fs::read_to_string(FULL_TEST).unwrap();
-    let mut parser = Parser::new(CompilerOptions::dev());
+    let mut parser = Parser::new(dev());
     let body = parser.parse_script("tests/parser/test.surn".to_string(), contents);
     let mut f = File::create("tests/resources/test.surn.ast").unwrap();
-    f.write_all(format!("{:#?}", body).as_bytes()).unwrap();
+    f.write_all(body.to_pretty_string().as_bytes()).unwrap();
 }
 
 #[test]
 pub fn test_parse_expressions() {
     let contents = fs::read_to_string(EXPRESSIONS).unwrap();
-    let mut parser = Parser::new(CompilerOptions::dev());
+    let mut parser = Parser::new(dev());
     let body = parser.parse_script("tests/parser/test.surn".to_string(), contents);
     let mut f = File::create("tests/resources/test.surn.ast").unwrap();
-    f.write_all(format!("{:#?}", body).as_bytes()).unwrap();
+    f.write_all(body.to_pretty_string().as_bytes()).unwrap();
+}
+
+#[test]
+pub fn test_parse_generics() {
+    let code = "fn first<T>(items: any): T {\n    return items;\n}\nclass Pair<K, V> {\n}\nfn main(p: Pair<int, string>) {\n    var a = first<int>(items);\n    var b = first(items);\n    var c = a < b;\n    var d = first<int, string>(items);\n    var e = new Pair<int>();\n}\n";
+    let body = parse("generics.surn", code);
+    let errors = check_generics(&body);
+    assert_eq!(
+        errors.iter().map(|e| e.message()).collect::<Vec<String>>(),
+        vec![
+            "`first` expects 1 type argument but 2 were given.",
+            "`Pair` expects 2 type arguments but 1 was given.",
+        ]
+    );
+    assert_eq!(&code[errors[0].span.clone()], "first<int, string>(items)");
+
+    let mut parser = Parser::new(CompilerOptions::default());
+    assert!(parser
+        .try_parse_script("generics.surn".to_string(), code.to_string())
+        .is_err());
+}
+
+#[test]
+pub fn test_parse_function_types() {
+    let mut parser = Parser::new(dev());
+    assert!(parser
+        .try_parse_script(
+            "callable.surn".to_string(),
+            "var f: (int, string) => bool = check;\n".to_string()
+        )
+        .is_ok());
+    assert!(parser
+        .try_parse_script(
+            "callable.surn".to_string(),
+            "var f: (int, string) bool = check;\n".to_string()
+        )
+        .is_err());
+}
+
+#[test]
+pub fn test_parse_tuple_types() {
+    let mut parser = Parser::new(dev());
+    assert!(parser
+        .try_parse_script(
+            "tuple.surn".to_string(),
+            "var (a, b): (int, string) = (1, \"b\");\n".to_string()
+        )
+        .is_ok());
+    assert!(parser
+        .try_parse_script("tuple.surn".to_string(), "var a: (int) = b;\n".to_string())
+        .is_err());
+}
+
+#[test]
+pub fn test_parse_null_safety() {
+    let code = "fn greet(name: string): string? {\n    return null;\n}\nfn upper(name: string): string {\n    return greet(name);\n}\nvar a: string? = greet(\"b\");\nvar b: string = a;\nvar c: int|null = null;\nvar d = upper(null);\n";
+    let body = parse("nulls.surn", code);
+    let errors = check_nulls(&body);
+    assert_eq!(
+        errors.iter().map(|e| e.message.as_str()).collect::<Vec<&str>>(),
+        vec![
+            "The function returns `string` which is not nullable, but a value of type `string?` was returned.",
+            "`b` is not nullable but was given a value of type `string?`.",
+            "`name` of `upper` is not nullable but was given a value of type `null`.",
+        ]
+    );
+    assert_eq!(&code[errors[1].span.clone()], "var b: string = a;");
+
+    let mut parser = Parser::new(CompilerOptions::default());
+    assert!(parser
+        .try_parse_script("nulls.surn".to_string(), code.to_string())
+        .is_err());
+}
+
+#[test]
+pub fn test_parse_object_shapes() {
+    let code = "type Point = { x: int, y: int };\ntype Line = { from: Point, to: Point? };\nvar p: Point = { x: 1, y: \"2\", z: 3 };\nvar l: Line = { from: { x: 1 }, to: null };\n";
+    let body = parse("shapes.surn", code);
+    let errors = check_shapes(&body);
+    assert_eq!(
+        errors
+            .iter()
+            .map(|e| e.message.as_str())
+            .collect::<Vec<&str>>(),
+        vec![
+            "`y` of `Point` is `int`, but was given a value of type `string`.",
+            "`Point` has no property `z`.",
+            "`Point` is missing the property `y` of type `int`.",
+        ]
+    );
+    assert_eq!(&code[errors[0].span.clone()], "y: \"2\"");
+    assert_eq!(&code[errors[2].span.clone()], "{ x: 1 }");
+
+    let mut parser = Parser::new(CompilerOptions::default());
+    assert!(parser
+        .try_parse_script("shapes.surn".to_string(), code.to_string())
+        .is_err());
+}
+
+#[test]
+pub fn test_parse_type_checks() {
+    let code = "fn pad(text: string, width: int): string {\n    return text;\n}\nfn main() {\n    var count: int = 0;\n    count = \"1\";\n    var label: string = 2;\n    pad(\"a\", \"2\");\n    var half = \"10\" - 2;\n    var total = count + 1.5;\n    var name = \"a\" + \"b\";\n}\n";
+    let body = parse("types.surn", code);
+    let errors = check_types(&body);
+    assert_eq!(
+        errors
+            .iter()
+            .map(|e| e.message.as_str())
+            .collect::<Vec<&str>>(),
+        vec![
+            "`count` is declared as `int`, but was given a value of type `string`.",
+            "`label` is declared as `string`, but was given a value of type `int`.",
+            "`width` of `pad` is `int`, but was given a value of type `string`.",
+            "`-` can't be applied to `string` and `int`.",
+            "`+` can't be applied to `string` and `string`.",
+        ]
+    );
+    // the value and the declaration it is checked against.
+    assert_eq!(
+        &code[errors[0].declaration.clone().unwrap()],
+        "var count: int = 0;"
+    );
+    assert_eq!(&code[errors[1].span.clone()], "var label: string = 2;");
+    assert_eq!(&code[errors[2].span.clone()], "pad(\"a\", \"2\")");
+    assert_eq!(&code[errors[2].declaration.clone().unwrap()], "fn pad");
+    assert_eq!(&code[errors[3].span.clone()], "\"10\" - 2");
+    assert_eq!(&code[errors[4].span.clone()], "\"a\" + \"b\"");
+
+    let mut parser = Parser::new(CompilerOptions::default());
+    let report = parser
+        .try_parse_script("types.surn".to_string(), code.to_string())
+        .unwrap_err();
+    assert_eq!(report.code, codes::TYPE_MISMATCH);
+    assert_eq!(report.snippets.len(), 2);
+}
+
+#[test]
+pub fn test_parse_null_narrowing() {
+    let code = "fn find(): int? {\n    return null;\n}\nfn g(x: int?): int {\n    if (x == null) {\n        return 0;\n    }\n    return x;\n}\nvar x = find();\nif (x != null) {\n    var y: int = x;\n} else {\n    var z: int = x;\n}\nvar w: int = x;\n";
+    let body = parse("nulls.surn", code);
+    let errors = check_nulls(&body);
+    assert_eq!(
+        errors
+            .iter()
+            .map(|e| e.message.as_str())
+            .collect::<Vec<&str>>(),
+        vec![
+            "`z` is not nullable but was given a value of type `null`.",
+            "`w` is not nullable but was given a value of type `int?`.",
+        ]
+    );
+
+    // members and operators are only used on a nullable value once it was checked.
+    let code = "fn f(s: string?, n: int?) {\n    var a = s.length;\n    var b = n + 1;\n    var c = s.trim();\n    if (s != null) {\n        var d = s.length;\n        var g = s.trim();\n    }\n    if (n == null) {\n        return;\n    }\n    var e = n + 1;\n}\n";
+    let body = parse("nulls.surn", code);
+    let errors = check_nulls(&body);
+    assert_eq!(
+        errors
+            .iter()
+            .map(|e| e.message.as_str())
+            .collect::<Vec<&str>>(),
+        vec![
+            "A member of `s` can't be accessed, it is of type `string?` and may be `null`.",
+            "`+` can't be applied to a value of type `int?`, it may be `null`.",
+            "A member of `s` can't be accessed, it is of type `string?` and may be `null`.",
+        ]
+    );
+    assert_eq!(&code[errors[0].span.clone()], "s.length");
+    assert_eq!(&code[errors[1].span.clone()], "n + 1");
+}
+
+#[test]
+pub fn test_parse_null_narrowing_with_is() {
+    let code = "fn f(x: int?, y: int|null): int {\n    if (x is int) {\n        var a: int = x;\n    }\n    if (y is null) {\n        return 0;\n    }\n    var b: int = y;\n    var c: int = x;\n    return b;\n}\n";
+    let body = parse("nulls.surn", code);
+    let errors = check_nulls(&body);
+    assert_eq!(
+        errors
+            .iter()
+            .map(|e| e.message.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["`c` is not nullable but was given a value of type `int?`."]
+    );
+    assert!(check_types(&body).is_empty());
+}
+
+#[test]
+pub fn test_parse_type_narrowing() {
+    let code = "fn scale(x: int | string): int {\n    if (x is int) {\n        var a = x * 2;\n    } else {\n        var b = x - 1;\n    }\n    var c = x * 2;\n    var d = x is int && x * 2;\n    var e = x is bool;\n    return 0;\n}\n";
+    let body = parse("scale.surn", code);
+    let errors = check_types(&body);
+    assert_eq!(
+        errors
+            .iter()
+            .map(|e| e.message.as_str())
+            .collect::<Vec<&str>>(),
+        vec![
+            "`-` can't be applied to `string` and `int`.",
+            "`*` can't be applied to every type of `int | string`, narrow it with `is` first.",
+            "`x` is never a `bool`, it is `int | string`.",
+        ]
+    );
+    assert_eq!(&code[errors[1].span.clone()], "x * 2");
+    assert_eq!(&code[errors[2].span.clone()], "x is bool");
+}
+
+#[test]
+pub fn test_parse_generic_bounds() {
+    let code = "class Model {\n    pub id: int;\n}\nclass User extends Model {\n    pub name: string;\n}\ntype Box<T extends Model> = { value: T };\nfn save<T extends Model>(item: T): T {\n    return item;\n}\nfn main(u: User) {\n    save(u);\n    save(\"a\");\n    save<int>(1);\n    var b: Box<string> = { value: \"a\" };\n    var c: Box<User> = { value: u };\n}\n";
+    let body = parse("bounds.surn", code);
+    let errors = check_types(&body);
+    assert_eq!(
+        errors
+            .iter()
+            .map(|e| e.message.as_str())
+            .collect::<Vec<&str>>(),
+        vec![
+            "`T` of `save` extends `Model`, but was given a value of type `string`.",
+            "`T` of `save` extends `Model`, but was given `int`.",
+            "`T` of `Box` extends `Model`, but was given `string`.",
+        ]
+    );
+    assert_eq!(&code[errors[0].span.clone()], "save(\"a\")");
+    assert_eq!(&code[errors[1].declaration.clone().unwrap()], "fn save");
+    assert_eq!(
+        &code[errors[2].span.clone()],
+        "var b: Box<string> = { value: \"a\" };"
+    );
+}
+
+#[test]
+pub fn test_parse_collection_types() {
+    let code = "var ids: int[] = [1, \"2\"];\nvar scores: float[] = [1, 2];\nvar ages: map<string, int> = { ada: 36, bob: \"x\" };\n";
+    let body = parse("collections.surn", code);
+    let errors = check_types(&body);
+    assert_eq!(
+        errors
+            .iter()
+            .map(|e| e.message.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["`ids` is declared as `int[]`, but was given a value of type `(int | string)[]`."]
+    );
+    let errors = check_shapes(&body);
+    assert_eq!(
+        errors
+            .iter()
+            .map(|e| e.message.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["`bob` of `map<string, int>` is `int`, but was given a value of type `string`."]
+    );
+    assert_eq!(code[errors[0].span.clone()].trim_end(), "bob: \"x\"");
+
+    let report = parse_error("map.surn", "var m: map<int> = 1;");
+    assert_eq!(report.code, codes::EXPECTED_TYPE);
+}
+
+#[test]
+pub fn test_parse_member_access() {
+    let code = "class Model {\n    prot id: int;\n    pub fn save() {\n    }\n}\nclass User extends Model {\n    name: string;\n    pub static count: int;\n    pub fn greet(): string {\n        return this.name;\n    }\n    pub fn key(): int {\n        return this.id;\n    }\n    priv fn secret() {\n    }\n    pub static fn create(): User {\n        return new User();\n    }\n}\nfn main(u: User) {\n    var n = u.name;\n    u.secret();\n    u.save();\n    var i = u.id;\n    var c = User::count;\n    var d = u.count;\n    User::greet();\n    u.nmae();\n}\n";
+    let body = parse("members.surn", code);
+    let errors = check_members(&body);
+    assert_eq!(
+        errors.iter().map(|e| e.message()).collect::<Vec<String>>(),
+        vec![
+            "`name` of `User` is private, only `User` can access it.",
+            "`secret` of `User` is private, only `User` can access it.",
+            "`id` of `User` is protected, only `Model` and the classes that extend it can access it.",
+            "`count` of `User` is static, access it with `User::count`.",
+            "`greet` of `User` is not static, access it on an instance with `.`.",
+            "`User` has no method `nmae`.",
+        ]
+    );
+    assert_eq!(&code[errors[0].span.clone()], "u.name");
+    assert_eq!(&code[errors[1].span.clone()], "u.secret()");
+    assert_eq!(&code[errors[4].span.clone()], "User::greet()");
+
+    let mut parser = Parser::new(CompilerOptions::default());
+    let report = parser
+        .try_parse_script("members.surn".to_string(), code.to_string())
+        .unwrap_err();
+    assert_eq!(report.code, codes::INACCESSIBLE_MEMBER);
+}
+
+#[test]
+pub fn test_parse_inheritance() {
+    let code = "class Model {\n    pub fn save(): bool {\n        return true;\n    }\n    pub fn find(id: int): Model {\n        return this;\n    }\n}\nclass Jsonable {\n    pub fn json(): string {\n        return \"\";\n    }\n}\nclass User extends Model implements Jsonable {\n    pub fn find(id: string): User {\n        return this;\n    }\n    #[Override]\n    pub fn delete() {\n    }\n}\nclass A extends B {\n}\nclass B extends A {\n}\n";
+    let body = parse("inheritance.surn", code);
+    let errors = check_inheritance(&body);
+    assert_eq!(
+        errors
+            .iter()
+            .map(|e| e.message.as_str())
+            .collect::<Vec<&str>>(),
+        vec![
+            "`find` of `User` doesn't match `find` of `Model`, `id` is `string` instead of `int`.",
+            "`delete` of `User` is marked `#[Override]` but no class `User` extends or implements declares `delete`.",
+            "`User` implements `Jsonable` but doesn't declare `json`.",
+            "`A` inherits from itself: A -> B -> A.",
+        ]
+    );
+    assert_eq!(&code[errors[1].span.clone()], "fn delete");
+    assert_eq!(&code[errors[3].span.clone()], "class A");
+
+    // the classes a class names are resolved with the other names.
+    let mut parser = Parser::new(dev());
+    parser.parse_script(
+        "user.surn".to_string(),
+        "class User extends Model {\n}\n".to_string(),
+    );
+    let errors = resolve(parser.contexts());
+    assert_eq!(errors[0].message(), "`Model` is not declared.");
+}
+
+#[test]
+pub fn test_parse_const_initializers() {
+    let code =
+        "var x = 1;\nconst A = 2 * 3;\nconst B = A + x;\nconst C = now();\nconst D = A / 0;\n";
+    let body = parse("consts.surn", code);
+    let errors = check_consts(&body);
+    assert_eq!(
+        errors
+            .iter()
+            .map(|e| e.message.as_str())
+            .collect::<Vec<&str>>(),
+        vec![
+            "`x` is not a constant, a const can only be initialized with literals and other consts.",
+            "A call can't be evaluated at compile time.",
+            "Division by zero.",
+        ]
+    );
+    assert_eq!(&code[errors[0].span.clone()], "const B = A + x;");
+
+    // the smallest int divided by `-1` doesn't fit an int.
+    let code = "const M: int = 0 - 9223372036854775807 - 1;\nconst N: int = 0 - 1;\nconst B: int = M / N;\n";
+    let body = parse("consts.surn", code);
+    let errors = check_consts(&body);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message, "`/` overflows an int.");
+
+    // the consts of a function are only known inside its body.
+    let code = "fn f() {\n    const L = 3;\n    const K = L + 1;\n}\nconst M = L + 1;\n";
+    let body = parse("consts.surn", code);
+    let errors = check_consts(&body);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(&code[errors[0].span.clone()], "const M = L + 1;");
+}
+
+#[test]
+pub fn test_parse_class_constants() {
+    let code = "class Page {\n    const SIZE: int = 10;\n    const NOW = now();\n}\n";
+    let body = parse("page.surn", code);
+    let errors = check_consts(&body);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].message,
+        "A call can't be evaluated at compile time."
+    );
+    assert_eq!(&code[errors[0].span.clone()], "const NOW = now();");
+
+    // a class constant can be an operand, also of a constant of another class.
+    let code = "class P {\n    const MAX = 10;\n    const MIN = self::MAX * 2;\n}\nclass Q {\n    const NEXT = P::MAX + 1;\n}\nconst R = 2 * P::MIN + 1;\n";
+    let body = parse("page.surn", code);
+    assert!(check_consts(&body).is_empty());
+
+    let code = "class Page {\n    const SIZE;\n}\n";
+    let report = parse_error("page.surn", code);
+    assert_eq!(report.code, codes::EXPECTED_EXPRESSION);
+}
+
+#[test]
+pub fn test_parse_return_statements() {
+    let code = "fn stop() {\n    return;\n}\nfn twice(x: int): int {\n    return x * 2;\n}\n";
+    let body = parse("return.surn", code);
+    let returns = body
+        .get_program()
+        .iter()
+        .filter_map(|node| match &node.inner {
+            NodeKind::Statement(Statement::Function(func)) => match func.body.as_ref() {
+                Statement::Block(exprs) => exprs.first().cloned(),
+                _ => None,
+            },
+            NodeKind::Statement(stmt) => Some(Expression::Statement(Box::new(stmt.clone()))),
+            _ => None,
+        })
+        .map(|expr| match expr {
+            Expression::Statement(stmt) => match *stmt {
+                Statement::Return(ret) => ret.expression.is_some(),
+                other => panic!("expected a return, found {:?}", other),
+            },
+            other => panic!("expected a return, found {:?}", other),
+        })
+        .collect::<Vec<bool>>();
+    assert_eq!(returns, vec![false, true]);
+
+    let code = "fn twice(x: int): int {\n    return x * 2\n}\n";
+    let report = parse_error("return.surn", code);
+    assert_eq!(report.code, codes::MISSING_SEMICOLON);
+}
+
+#[test]
+pub fn test_parse_spans() {
+    let code = "class User {\n    name: string = \"a\";\n}\nfn greet(...names: string): int {\n    log([1, 2] + new User());\n    return 1;\n}\n";
+    let body = parse("spans.surn", code);
+    let class = body.classes().next().unwrap();
+    assert_eq!(
+        &code[class.body.properties[0].span.clone()],
+        "name: string = \"a\";"
+    );
+
+    let func = body.functions().next().unwrap();
+    assert_eq!(&code[func.inputs[0].span.clone()], "...names: string");
+    let Statement::Block(exprs) = func.body.as_ref() else {
+        panic!("expected a block");
+    };
+    let spans = exprs
+        .iter()
+        .filter_map(|expr| expr.span())
+        .map(|span| &code[span])
+        .collect::<Vec<&str>>();
+    assert_eq!(spans, vec!["log([1, 2] + new User())", "return 1;"]);
+    let Expression::Call(call) = &exprs[0] else {
+        panic!("expected a call");
+    };
+    let Expression::Operation(op) = &call.arguments[0] else {
+        panic!("expected an operation");
+    };
+    assert_eq!(&code[op.left.span().unwrap()], "[1, 2]");
+    assert_eq!(&code[op.right.span().unwrap()], "new User()");
+}
+
+#[test]
+pub fn test_pretty_print() {
+    let code = "fn add(a: int, b: int): int {\n    return a + b;\n}\n";
+    let mut parser = Parser::new(dev());
+    let body = parser.parse_script("pretty.surn".to_string(), code.to_string());
+    assert_eq!(
+        body.to_pretty_string(),
+        "function pub add: int @0..49\n  param a: int @7..13\n  param b: int @15..21\n  block\n    return @34..47\n      operation + @41..46\n        name a @41..42\n        name b @45..46\n"
+    );
+    assert_eq!(
+        body.to_sexpr_string(),
+        "(function pub add: int (param a: int) (param b: int) (block (return (operation + (name a) (name b)))))\n"
+    );
+
+    let code = "class User {\n    pub name: string = \"x\";\n}\nvar user = new User(...args);\n";
+    let body = parser.parse_script("pretty.surn".to_string(), code.to_string());
+    assert_eq!(
+        body.to_sexpr_string(),
+        "(class User (property pub name: string (literal \"x\": string)))\n(var priv user (new User (spread (name args))))\n"
+    );
+}
+
+#[test]
+pub fn test_parse_invalid_accessors() {
+    for (code, expected, span) in [
+        (
+            "class User {\n    name: string;\n    set name() {\n    }\n}\n",
+            codes::INVALID_ACCESSOR,
+            "set name",
+        ),
+        (
+            "class User {\n    get name(): string {\n    }\n}\n",
+            codes::INVALID_ACCESSOR,
+            "get name",
+        ),
+        (
+            "class User {\n    name: string { get; get; }\n}\n",
+            codes::DUPLICATE_DECLARATION,
+            "get",
+        ),
+    ] {
+        let report = parse_error("user.surn", code);
+        assert_eq!(report.code, expected, "{}", code);
+        assert_eq!(&code[report.snippets[0].range()], span, "{}", code);
+    }
+}
+
+#[test]
+pub fn test_parse_variadic_parameters() {
+    let code = "fn log(level: int, ...messages: string) {\n    var first: string = messages;\n}\nfn main(lines: array) {\n    log(1, \"a\", 2);\n    log(1, ...lines);\n}\n";
+    let body = parse("log.surn", code);
+    let errors = check_types(&body);
+    assert_eq!(
+        errors
+            .iter()
+            .map(|e| e.message.as_str())
+            .collect::<Vec<&str>>(),
+        vec![
+            "`first` is declared as `string`, but was given a value of type `string[]`.",
+            "`messages` of `log` is `string`, but was given a value of type `int`.",
+        ]
+    );
+
+    let code = "fn log(...messages: string, level: int) {\n}\n";
+    let report = parse_error("log.surn", code);
+    assert_eq!(report.code, codes::MISPLACED_VARIADIC);
+    assert_eq!(&code[report.snippets[0].range()], "level");
+}
+
+#[test]
+pub fn test_fresh_names() {
+    let code = "var __surn_tmp_2_2 = 1;\nfn twice(x: int): int {\n    return x * 2;\n}\n";
+    let body = parse("names.surn", code);
+    // the standard library is the first context.
+    assert_eq!(body.context(), 2);
+    let mut names = NameGenerator::for_body(&body);
+    assert!(names.is_taken("twice") && names.is_taken("x"));
+    assert_eq!(names.fresh(), "__surn_tmp_2_1");
+    assert_eq!(names.fresh(), "__surn_tmp_2_3");
+    assert_eq!(names.unique("twice"), "twice_2");
+    assert_eq!(names.unique("twice"), "twice_3");
+    assert_eq!(names.unique("thrice"), "thrice");
+
+    // the same body always gets the same names.
+    let mut again = NameGenerator::for_body(&body);
+    assert_eq!(again.fresh(), "__surn_tmp_2_1");
+    assert_eq!(NameGenerator::new(3).fresh(), "__surn_tmp_3_1");
+}
+
+#[test]
+pub fn test_symbol_table() {
+    let code = "var x = 1;\nfn double(x: int): int {\n    var y = x * 2;\n    return y;\n}\nclass Box {\n    pub value: int;\n    pub fn get(): int {\n        return this.value;\n    }\n}\n";
+    let mut parser = Parser::new(dev());
+    parser.parse_script("symbols.surn".to_string(), code.to_string());
+    let symbols = &parser.contexts().find("symbols.surn").unwrap().symbols;
+    assert_eq!(
+        symbols
+            .global()
+            .symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.kind))
+            .collect::<Vec<(&str, SymbolKind)>>(),
+        vec![
+            ("x", SymbolKind::Variable),
+            ("double", SymbolKind::Function),
+            ("Box", SymbolKind::Class),
+        ]
+    );
+
+    let body = code.find("var y").unwrap();
+    assert_eq!(
+        symbols.lookup("x", body + 8).unwrap().kind,
+        SymbolKind::Parameter
+    );
+    assert!(symbols.lookup("y", body - 1).is_none());
+    assert_eq!(
+        symbols.lookup("y", body + 15).unwrap().span,
+        body..body + 14
+    );
+    assert_eq!(
+        symbols.lookup("x", code.len() - 1).unwrap().kind,
+        SymbolKind::Variable
+    );
+    // classes can be used before they are declared.
+    assert!(symbols.lookup("Box", 0).is_some());
+    assert_eq!(
+        symbols
+            .members("Box")
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["value", "get"]
+    );
+}
+
+#[test]
+pub fn test_parse_trace() {
+    let mut options = dev();
+    options.trace_parse = true;
+    let mut parser = Parser::new(options);
+    assert!(parser
+        .try_parse_script("trace.surn".to_string(), "var x = 1 +;".to_string())
+        .is_err());
+
+    let trace = parser.trace().unwrap();
+    let first = &trace.events[0];
+    assert_eq!((first.rule, first.depth, first.cursor), ("statement", 0, 0));
+    // every rule that was entered is exited.
+    let (enters, exits): (Vec<_>, Vec<_>) = trace.events.iter().partition(|e| e.outcome.is_none());
+    assert_eq!(enters.len(), exits.len());
+    assert!(trace.events.iter().any(|e| e.rule == "variable"
+        && e.outcome
+            == Some(Outcome::Failed(
+                "Expected an expression to follow an operation.".to_string()
+            ))));
+    assert!(trace
+        .explain("variable")
+        .starts_with("variable failed, Expected an expression to follow an operation. at `var`\n"));
+    assert!(trace.to_json().starts_with(
+        "[{\"rule\":\"statement\",\"depth\":0,\"cursor\":0,\"token\":\"var\",\"start\":0,\"end\":3,\"event\":\"enter\"}"
+    ));
+
+    // nothing is recorded unless asked for.
+    let mut parser = Parser::new(dev());
+    parser.parse_script("trace.surn".to_string(), "var x = 1;".to_string());
+    assert!(parser.trace().is_none());
+}
+
+#[test]
+pub fn test_parse_size_limits() {
+    let code = "var a = 1;\nvar b = 2;\n".to_string();
+    let mut options = dev();
+    options.max_file_size = Some(16);
+    let mut parser = Parser::new(options.clone());
+    let report = parser
+        .try_parse_script("large.surn".to_string(), code.clone())
+        .unwrap_err();
+    assert_eq!(
+        report.message,
+        "The file is too large to compile, it is 22 bytes, the limit is 16. \
+         Raise `max_file_size` in the compiler options to compile it anyway."
+    );
+
+    options.max_file_size = None;
+    options.max_tokens = Some(8);
+    let mut parser = Parser::new(options.clone());
+    let report = parser
+        .try_parse_script("large.surn".to_string(), code.clone())
+        .unwrap_err();
+    assert!(report
+        .message
+        .starts_with("The file is too large to compile"));
+
+    options.max_tokens = None;
+    let mut parser = Parser::new(options);
+    assert!(parser
+        .try_parse_script("large.surn".to_string(), code)
+        .is_ok());
+}
+
+#[test]
+pub fn test_parallel_parsing() {
+    let scripts: Vec<(String, String)> = (0..8)
+        .map(|i| {
+            let source = match i {
+                3 => "var broken = ;\n".to_string(),
+                _ => format!("fn f{}(): int {{\n    return {};\n}}\n", i, i),
+            };
+            (format!("s{}.surn", i), source)
+        })
+        .collect();
+    let mut options = dev();
+    options.jobs = Some(4);
+    let mut parser = Parser::new(options.clone());
+    let parsed = parser.parse_scripts(scripts.clone()).unwrap();
+    assert_eq!(parsed.len(), 8);
+    assert!(parsed[3].result.is_err());
+    assert_eq!(
+        parsed.iter().filter(|script| script.result.is_ok()).count(),
+        7
+    );
+
+    // the contexts are in the order of the scripts, whichever thread parsed them.
+    let summary = |parser: &Parser| -> Vec<(String, usize)> {
+        parser
+            .contexts()
+            .contexts()
+            .into_iter()
+            .map(|context| (context.source.name.clone(), context.body.ids().len()))
+            .collect()
+    };
+    options.jobs = Some(1);
+    let mut sequential = Parser::new(options);
+    sequential.parse_scripts(scripts).unwrap();
+    assert_eq!(summary(&parser), summary(&sequential));
+    assert_eq!(summary(&parser)[1], ("s0.surn".to_string(), 1));
+    assert!(parser.contexts().find("s7.surn").is_some());
+}
+
+#[test]
+pub fn test_context_lifecycle() {
+    let mut parser = Parser::new(dev());
+    parser.parse_script("repl.surn".to_string(), "var x = 1;".to_string());
+    let first = parser.contexts().find("repl.surn").unwrap().id();
+
+    // parsing a script again drops the context it had.
+    parser.parse_script("repl.surn".to_string(), "var y = 2;".to_string());
+    let context = parser.contexts().find("repl.surn").unwrap();
+    assert!(context.id() > first);
+    assert!(parser.contexts().get(first).is_none());
+    assert_eq!(context.symbols.global().symbols[0].name, "y");
+
+    let removed = parser.remove_script("repl.surn").unwrap();
+    assert_eq!(removed.body.ids().len(), 1);
+    assert!(parser.contexts().find("repl.surn").is_none());
+    assert!(parser.remove_script("std").is_none());
+    assert_eq!(parser.contexts().contexts().len(), 1);
+}
+
+#[test]
+pub fn test_lookup_global() {
+    let mut parser = Parser::new(dev());
+    parser.parse_script(
+        "config.surn".to_string(),
+        "const DEBUG = true;\nfn log(x: int): int {\n    var inner = x;\n    return inner;\n}\nnamespace app;\nfn run(): int {\n    return 1;\n}\n".to_string(),
+    );
+    let contexts = parser.contexts();
+    let (context, symbol) = contexts.lookup_global("DEBUG").unwrap();
+    assert_eq!(context.source.name, "config.surn");
+    assert_eq!(symbol.kind, SymbolKind::Constant);
+    assert!(contexts.lookup_global("log").is_some());
+    assert!(contexts.lookup_global("inner").is_none());
+    assert!(contexts.lookup_global("run").is_none());
+    let (context, _) = contexts.lookup_global("println").unwrap();
+    assert_eq!(context.source.name, "std");
+
+    // the names of a script parsed again are the ones it declares now.
+    parser.parse_script("config.surn".to_string(), "var verbose = 1;".to_string());
+    assert!(parser.contexts().lookup_global("DEBUG").is_none());
+    assert!(parser.contexts().lookup_global("verbose").is_some());
+}
+
+/// Serves a single file from memory and counts how often it is read.
+struct CountingLoader {
+    contents: Mutex<(String, u64)>,
+    loads: Mutex<usize>,
+}
+
+impl FileLoader for CountingLoader {
+    fn load(&self, _path: &Path) -> io::Result<String> {
+        *self.loads.lock().unwrap() += 1;
+        Ok(self.contents.lock().unwrap().0.clone())
+    }
+
+    fn modified(&self, _path: &Path) -> Option<SystemTime> {
+        let version = self.contents.lock().unwrap().1;
+        Some(SystemTime::UNIX_EPOCH + Duration::from_secs(version))
+    }
+}
+
+#[test]
+pub fn test_source_caching() {
+    let loader = Arc::new(CountingLoader {
+        contents: Mutex::new(("var x = 1;".to_string(), 1)),
+        loads: Mutex::new(0),
+    });
+    let mut map = SourceMap::with_loader(loader.clone());
+    let id = map.add_path(PathBuf::from("/src/main.surn"));
+    assert_eq!(map.add_path(PathBuf::from("/src/main.surn")), id);
+    assert_eq!(map.get(id).unwrap().name, "main.surn");
+    assert_eq!(*loader.loads.lock().unwrap(), 0);
+
+    // the file is read once until it changes.
+    assert_eq!(map.contents(id).unwrap(), "var x = 1;");
+    assert_eq!(map.contents(id).unwrap(), "var x = 1;");
+    assert_eq!(*loader.loads.lock().unwrap(), 1);
+    *loader.contents.lock().unwrap() = ("var x = 2;".to_string(), 2);
+    assert_eq!(map.contents(id).unwrap(), "var x = 2;");
+    assert_eq!(*loader.loads.lock().unwrap(), 2);
+
+    map.get(id).unwrap().invalidate();
+    map.contents(id);
+    assert_eq!(*loader.loads.lock().unwrap(), 3);
+    assert!(map.add(SourceOrigin::new_virtual(
+        "a.surn".to_string(),
+        String::new()
+    )));
+    assert!(!map.add(SourceOrigin::new_virtual(
+        "a.surn".to_string(),
+        String::new()
+    )));
+}
+
+#[test]
+pub fn test_skipped_trivia() {
+    // the range of the last token leaves out the last character of a source, its value doesn't.
+    let source = "var x = 1;\n\n// a\n// note\n\n";
+    let mut generator = AstGenerator::new(
+        SourceOrigin::new_virtual("trivia.surn".to_string(), source.to_string()),
+        0,
+    );
+    generator
+        .begin_parse(TokenStream::new(tokenize(source)))
+        .unwrap();
+
+    // the trailing trivia is the last thing skipped.
+    let skipped = generator.skipped();
+    assert_eq!(skipped.range.start, 10);
+    assert_eq!(skipped.newlines, 5);
+    assert!(skipped.has_blank_line());
+    assert_eq!(skipped.comments, vec![12..16, 17..24]);
+}
+
+#[test]
+pub fn test_reports_share_source() {
+    let mut parser = Parser::new(dev());
+    let report = parser
+        .try_parse_script("shared.surn".to_string(), "var x = 1 +;".to_string())
+        .unwrap_err();
+    let context = parser.contexts().find("shared.surn").unwrap();
+    assert!(Arc::ptr_eq(&report.source, &context.buffer));
+}
+
+#[test]
+pub fn test_parse_fix_its() {
+    let fixed = |code: &str| {
+        let report = parse_error("fix.surn", code);
+        let buffer = SourceBuffer::new(code.to_string()).apply(report.suggestions());
+        buffer.slice(0..usize::MAX).to_string()
+    };
+    assert_eq!(fixed("var x = 1\nvar y = 2;"), "var x = 1;\nvar y = 2;");
+    assert_eq!(fixed("var x = add(1 2);"), "var x = add(1, 2);");
+    assert_eq!(fixed("fn f() {}\nretrun 1;"), "fn f() {}\nreturn 1;");
+    assert_eq!(fix_its::edit_distance("retrun", "return"), 1);
+}
+
+#[test]
+pub fn test_parse_expression_statements() {
+    let code = "save(1);\nfn main() {\n    save(2);\n}\n";
+    let body = parse("save.surn", code);
+    assert!(matches!(
+        body.get_program()[0].inner,
+        NodeKind::Expression(Expression::Call(_))
+    ));
+
+    // a missing semicolon is reported the same way in a block and at the top level.
+    for code in ["save(1)\nvar x = 1;", "fn main() {\n    save(2)\n}\n"] {
+        let report = parse_error("save.surn", code);
+        assert_eq!(report.code, codes::MISSING_SEMICOLON, "{}", code);
+        assert_eq!(report.suggestions()[0].replacement, ";", "{}", code);
+    }
+}
+
+#[test]
+pub fn test_function_purity() {
+    let code = "fn add(a: int, b: int): int {\n    return a + b;\n}\nfn twice(n: int): int {\n    return add(n, n);\n}\nfn log(message: string) {\n    echo(message);\n}\nfn warn(n: int) {\n    log(\"!\");\n}\nfn query() {\n    var id = _GET;\n    return id;\n}\nfn main() {\n    add(1, 2);\n    log(\"done\");\n}\n";
+    let mut parser = Parser::new(dev());
+    let mut body = parser.parse_script("purity.surn".to_string(), code.to_string());
+    let purities = function_purities(&body);
+    assert!(purities["add"].is_pure());
+    assert!(purities["twice"].is_pure());
+    assert_eq!(
+        purities["log"].to_string(),
+        "impure, calls `echo`, which does I/O"
+    );
+    assert_eq!(
+        purities["warn"].to_string(),
+        "impure, calls `log`, which is impure"
+    );
+    assert_eq!(
+        purities["query"].to_string(),
+        "impure, reads the superglobal `$_GET`"
+    );
+
+    // only the call to `add` in `main` has no effect.
+    assert_eq!(remove_dead_calls(&mut body), vec!["add".to_string()]);
+
+    // a call that can throw, or changes its arguments, is kept.
+    let code = "fn f(x: any): int {\n    return 1;\n}\nfn half(n: int): float {\n    return n / 2;\n}\nfn ratio(a: int, b: int): float {\n    return a / b;\n}\nfn main(s: string, xs: int[]) {\n    f(1 / 0);\n    f(explode(\"\", s));\n    f(split(s, \",\"));\n    push(xs, 1);\n    f(5 % 2);\n}\n";
+    let mut body = parser.parse_script("throws.surn".to_string(), code.to_string());
+    let purities = function_purities(&body);
+    assert!(purities["half"].is_pure());
+    assert_eq!(
+        purities["ratio"].to_string(),
+        "impure, divides by a value that may be zero"
+    );
+    assert_eq!(remove_dead_calls(&mut body), vec!["f".to_string()]);
+}
+
+#[test]
+pub fn test_async_await() {
+    let code = "async fn load(): int {\n    return await fetch() + 1;\n}\nfn wait(): int {\n    return await fetch();\n}\n";
+    let body = parse("async.surn", code);
+    let NodeKind::Statement(Statement::Function(load)) = &body.get_program()[0].inner else {
+        panic!("expected a function");
+    };
+    assert!(load.is_async);
+    // `await` binds to the call, not to the sum.
+    let block = load.body.get_block().unwrap();
+    let Some(Expression::Statement(stmt)) = block.first() else {
+        panic!("expected a statement");
+    };
+    let Statement::Return(ret) = stmt.as_ref() else {
+        panic!("expected a return");
+    };
+    let Some(Expression::Operation(sum)) = &ret.expression else {
+        panic!("expected an operation");
+    };
+    assert!(matches!(sum.left.as_ref(), Expression::Await(_)));
+
+    let errors = check_awaits(&body);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].function.as_deref(), Some("wait"));
+
+    let mut parser = Parser::new(CompilerOptions::default());
+    let report = parser
+        .try_parse_script("async.surn".to_string(), code.to_string())
+        .unwrap_err();
+    assert_eq!(report.code, codes::AWAIT_OUTSIDE_ASYNC);
+}
+
+#[test]
+pub fn test_namespace_resolution() {
+    let models =
+        "namespace app\\models;\nclass User {\n}\nfn find(id: int): int {\n    return id;\n}\n";
+    let http = "namespace app\\http;\nuse app::models::User;\nfn show(id: int): int {\n    var user = new User(id);\n    var post = new models\\Post(id);\n    var found = missing(id);\n    var upper = to_upper(\"a\");\n    fn local(x: int): int {\n        return x;\n    }\n    return local(id);\n}\n";
+    let mut parser = Parser::new(dev());
+    parser.parse_script("models.surn".to_string(), models.to_string());
+    let body = parser.parse_script("http.surn".to_string(), http.to_string());
+    let Some(NodeKind::Statement(Statement::Function(show))) =
+        body.get_program().get(2).map(|node| &node.inner)
+    else {
+        panic!("expected a function");
+    };
+    let Statement::Block(statements) = show.body.as_ref() else {
+        panic!("expected a block");
+    };
+    let Expression::Statement(post) = &statements[1] else {
+        panic!("expected a statement");
+    };
+    let Statement::Var(post) = post.as_ref() else {
+        panic!("expected a variable");
+    };
+    assert!(matches!(&post.assignment, Some(Expression::New(call)) if call.name == "models\\Post"));
+
+    // the standard library and functions declared in a function resolve.
+    let errors = resolve(parser.contexts());
+    let messages: Vec<String> = errors.iter().map(|error| error.message()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "`models\\Post` is not declared in `app\\http` or the global namespace.",
+            "`missing` is not declared in `app\\http` or the global namespace.",
+        ]
+    );
+    assert_eq!(&http[errors[1].span.clone().unwrap()], "missing(id)");
+
+    // a second declaration of `app\models\User` makes the import ambiguous.
+    parser.parse_script(
+        "copy.surn".to_string(),
+        "namespace app\\models;\nclass User {\n}\n".to_string(),
+    );
+    let errors = resolve(parser.contexts());
+    assert_eq!(
+        errors[0].kind,
+        NameErrorKind::Ambiguous(vec![
+            "app\\models\\User (models.surn)".to_string(),
+            "app\\models\\User (copy.surn)".to_string(),
+        ])
+    );
+    assert_eq!(parser.check_names()[0].code, codes::AMBIGUOUS_NAME);
+}
+
+#[test]
+pub fn test_circular_imports() {
+    let files = [
+        (
+            "a.surn",
+            "namespace app\\a;\nuse app::b::second;\nfn first(): int {\n    return 1;\n}\n",
+        ),
+        (
+            "b.surn",
+            "namespace app\\b;\nuse app::c::third;\nfn second(): int {\n    return 2;\n}\n",
+        ),
+        (
+            "c.surn",
+            "namespace app\\c;\nuse app::a::first;\nfn third(): int {\n    return 3;\n}\n",
+        ),
+    ];
+    let mut parser = Parser::new(dev());
+    for (name, code) in files {
+        parser.parse_script(name.to_string(), code.to_string());
+    }
+    let cycles = find_cycles(parser.contexts());
+    assert_eq!(cycles.len(), 1);
+    assert_eq!(
+        cycles[0].message(),
+        "Circular import: a.surn -> b.surn -> c.surn -> a.surn."
+    );
+    let sites: Vec<&str> = cycles[0]
+        .imports
+        .iter()
+        .zip(files)
+        .map(|(site, (_, code))| &code[site.span.clone()])
+        .collect();
+    assert_eq!(sites, ["app::b::second", "app::c::third", "app::a::first"]);
+
+    let report = parser.check_names().pop().unwrap();
+    assert_eq!(report.code, codes::CIRCULAR_IMPORT);
+    assert_eq!(report.snippets.len(), 3);
+}
+
+#[test]
+pub fn test_duplicate_type_definitions() {
+    let code = "type Id = int;\nvar x = 1;\ntype Id = string;\n";
+    let report = parse_error("ids.surn", code);
+    assert_eq!(report.code, codes::DUPLICATE_DECLARATION);
+    assert_eq!(report.message, "The type `Id` is defined twice.");
+    let spans: Vec<&str> = report
+        .snippets
+        .iter()
+        .map(|snippet| code[snippet.range()].trim_end())
+        .collect();
+    assert_eq!(spans, ["type Id = string;", "type Id = int;"]);
+}
+
+#[test]
+pub fn test_resolve_types_across_contexts() {
+    let files = [
+        ("ids.surn", "namespace app\\ids;\ntype Id = int;\n"),
+        ("names.surn", "namespace app\\names;\ntype Id = string;\n"),
+        (
+            "main.surn",
+            "use app::ids::Id;\nuse app::names::Id;\ntype Name = string;\n",
+        ),
+    ];
+    let mut parser = Parser::new(dev());
+    for (name, code) in files {
+        parser.parse_script(name.to_string(), code.to_string());
+    }
+    let main = parser.contexts().find("main.surn").unwrap().id();
+    let resolver = TypeResolver::new(parser.contexts());
+
+    let (context, definition) = resolver.resolve(main, "Id").unwrap();
+    assert_eq!(context.source.name, "ids.surn");
+    assert_eq!(definition.kind.to_string(), "int");
+    let (context, _) = resolver.resolve(main, "Name").unwrap();
+    assert_eq!(context.source.name, "main.surn");
+    assert!(resolver.resolve(main, "Missing").is_none());
+}
+
+#[test]
+pub fn test_ast_arena() {
+    let code = "var x = 1;\nfn f(): int {\n    return 2;\n}\nclass A {\n}\n";
+    let mut parser = Parser::new(CompilerOptions::default());
+    let body = parser.parse_script("arena.surn".to_string(), code.to_string());
+
+    let mut clone = body.clone();
+    assert!(clone.shares_nodes(&body));
+    let mut lines: NodeMap<usize> = NodeMap::new();
+    for (id, node) in body.nodes() {
+        lines.insert(id, node.start());
+    }
+    assert_eq!(lines.len(), 3);
+
+    let func = body.functions().next().unwrap();
+    let node = body.declaration(func.node_id).unwrap();
+    assert!(matches!(
+        node.inner,
+        NodeKind::Statement(Statement::Function(_))
+    ));
+    assert!(body.declaration(0).is_none());
+    assert_eq!(
+        body.classes().map(|c| c.name.as_str()).collect::<Vec<_>>(),
+        ["A"]
+    );
+
+    // the node under the cursor, and the nodes a selection overlaps.
+    let at = |offset: usize| body.nodes_in_range(offset..offset).map(|(id, _)| id);
+    assert_eq!(
+        at(code.find("return").unwrap()).collect::<Vec<_>>(),
+        [body.ids()[1]]
+    );
+    assert_eq!(body.nodes_in_range(5..15).count(), 2);
+
+    clone.get_program_mut()[0].position.line = 10;
+    assert!(!clone.shares_nodes(&body));
+    assert_ne!(body.get_program()[0].position.line, 10);
+}
+
+#[test]
+pub fn test_misplaced_statements() {
+    for (code, keyword) in [
+        ("return 1;", "return"),
+        ("fn f() {\n    static var y = 1;\n}", "static"),
+        ("fn f() {\n    use a::b;\n}", "use"),
+        ("fn f() {\n    namespace A;\n}", "namespace"),
+        ("fn f() {\n    break;\n}", "break"),
+    ] {
+        let report = parse_error("misplaced.surn", code);
+        assert_eq!(report.code, codes::MISPLACED_STATEMENT, "{}", code);
+        assert_eq!(&code[report.snippets[0].range()], keyword);
+    }
+
+    // statics are allowed at the top level and returns in methods and nested functions.
+    let code = "static var count = 0;\nclass A {\n    pub fn f(): int {\n        fn g() {\n            return 1;\n        }\n        return 2;\n    }\n}\n";
+    let mut parser = Parser::new(dev());
+    assert!(parser
+        .try_parse_script("placed.surn".to_string(), code.to_string())
+        .is_ok());
+}
+
+#[test]
+pub fn test_context_diagnostics() {
+    let origin = SourceOrigin::new_virtual("collect.surn".to_string(), "var a = 1;".to_string());
+    let context = &mut Context::new(origin, 0);
+    context.push_diagnostic(Diagnostic::warning("A warning.".to_string()));
+    context.push_diagnostic(Diagnostic::notice("A notice.".to_string()));
+    assert_eq!(context.diagnostics().len(), 2);
+    let taken = context.take_diagnostics();
+    assert_eq!(taken[1].severity, ReportKind::Notice);
+    assert!(context.diagnostics().is_empty());
+}
+
+#[test]
+pub fn test_lint_levels() {
+    let code = "fn f(): int {\n    var x = 1;\n    var _y = 2;\n    return 3;\n}\n#[allow(unused_variable)]\nfn g(): int {\n    var z = 1;\n    return 2;\n}\n";
+    let mut parser = Parser::new(CompilerOptions::default());
+    let body = parser.parse_script("lints.surn".to_string(), code.to_string());
+    assert_eq!(body.lint_scopes().len(), 1);
+    assert_eq!(
+        parser
+            .warnings()
+            .iter()
+            .map(|w| w.message.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["`x` is never used."]
+    );
+    assert_eq!(
+        &code[parser.warnings()[0].spans[0].span.clone()],
+        "var x = 1;"
+    );
+
+    let mut options = CompilerOptions::default();
+    options.lints.set(UNUSED_VARIABLE, LintLevel::Deny);
+    let mut parser = Parser::new(options.clone());
+    assert!(parser
+        .try_parse_script("lints.surn".to_string(), code.to_string())
+        .is_err());
+    // the attribute wins over the options.
+    let allowed = code.replace("var x = 1;\n", "");
+    assert!(Parser::new(options.clone())
+        .try_parse_script("lints.surn".to_string(), allowed)
+        .is_ok());
+
+    options.lints.set("unused_vars", LintLevel::Allow);
+    assert!(options.validate().is_err());
+}
+
+#[test]
+pub fn test_truncated_sources() {
+    // a source cut anywhere is parsed or reported, errors at the end point into the source.
+    let full = fs::read_to_string(FULL_TEST).unwrap();
+    let mut sources: Vec<&str> = (0..full.len())
+        .filter(|end| full.is_char_boundary(*end))
+        .map(|end| &full[..end])
+        .collect();
+    sources.extend([
+        "namespace Test",
+        "class ",
+        "use ",
+        "static ",
+        "print('",
+        "fn f(a: ",
+    ]);
+    for source in sources {
+        let mut generator = AstGenerator::new(
+            SourceOrigin::new_virtual("truncated.surn".to_string(), source.to_string()),
+            1,
+        );
+        if let Err(report) = generator.begin_parse(TokenStream::new(tokenize(source))) {
+            for snippet in report.snippets.iter() {
+                assert!(snippet.range().end <= source.len(), "{:?}", source);
+            }
+        }
+    }
+}
+
+#[test]
+pub fn test_unexpected_eof() {
+    // an unclosed delimiter is reported where it is opened, see `test_unbalanced_delimiters`.
+    for source in ["var x =", "namespace ", "use a::", "fn f(): "] {
+        let report = parse_error("eof.surn", source);
+        assert_eq!(report.code, codes::UNEXPECTED_EOF, "{:?}", source);
+        // the report points at the last character that isn't whitespace.
+        let end = source.trim_end().len();
+        assert_eq!(report.snippets[0].range(), end - 1..end);
+        assert!(report
+            .render(&ReportTheme::plain())
+            .contains("The file ends after this."));
+    }
+}
+
+#[test]
+pub fn test_inferred_variables() {
+    let code = "var x = 5;\nvar s = \"a\";\nvar f: float = 1;\nvar u = unknown();\n";
+    let body = parse("inferred.surn", code);
+    let types: Vec<(Option<String>, Option<String>)> = body
+        .get_program()
+        .iter()
+        .map(|node| match &node.inner {
+            NodeKind::Statement(Statement::Var(var)) => (
+                var.inferred.as_ref().map(|ty| ty.to_string()),
+                var.type_of().map(|ty| ty.to_string()),
+            ),
+            _ => unreachable!(),
+        })
+        .collect();
+    let some = |ty: &str| Some(ty.to_string());
+    assert_eq!(
+        types,
+        vec![
+            (some("int"), some("int")),
+            (some("string"), some("string")),
+            // an annotation is never replaced.
+            (None, some("float")),
+            (None, None),
+        ]
+    );
 }
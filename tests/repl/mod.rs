@@ -0,0 +1,43 @@
+use surn::{
+    compiler::{interp::Value, CompilerOptions},
+    repl::{Repl, ReplMode},
+};
+
+#[test]
+pub fn test_repl_keeps_variables() {
+    let mut repl = Repl::new(CompilerOptions::default());
+    assert_eq!(
+        repl.eval_line("var x = 1 + 2").unwrap(),
+        Some("x = 3".to_string())
+    );
+    assert_eq!(repl.eval_line("x * 2;").unwrap(), Some("6".to_string()));
+    assert_eq!(
+        repl.eval_line("x = x + 1").unwrap(),
+        Some("x = 4".to_string())
+    );
+    assert_eq!(repl.value("x"), Some(&Value::Int(4)));
+    assert_eq!(
+        repl.eval_line("fn double(n: int): int { return n * 2; }")
+            .unwrap(),
+        None
+    );
+    assert_eq!(repl.eval_line("double(x)").unwrap(), Some("8".to_string()));
+
+    // a line that doesn't parse or evaluate changes nothing.
+    assert!(repl.eval_line("var y = ;").is_err());
+    assert!(repl.eval_line("y + 1").is_err());
+    assert_eq!(repl.value("x"), Some(&Value::Int(4)));
+    assert_eq!(repl.eval_line("").unwrap(), None);
+}
+
+#[test]
+pub fn test_repl_php_mode() {
+    let mut repl = Repl::new(CompilerOptions::default());
+    assert_eq!(repl.eval_line(":php").unwrap(), None);
+    assert_eq!(repl.mode(), ReplMode::Php);
+    assert_eq!(
+        repl.eval_line("var x = 5").unwrap(),
+        Some("$x = 5;".to_string())
+    );
+    assert!(repl.eval_line(":jit").is_err());
+}
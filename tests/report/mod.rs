@@ -1,4 +1,12 @@
-use surn::{report::Report, util::source::SourceBuffer};
+use crate::dev;
+use surn::{
+    compiler::{
+        lexer::{analysis::diagnose, tokenizer::tokenize},
+        parser::Parser,
+    },
+    report::{codes, diagnostic::Diagnostic, Report, ReportKind, ReportTheme},
+    util::source::SourceBuffer,
+};
 
 #[test]
 pub fn test_snippet_print() {
@@ -18,3 +26,180 @@ pub fn test_snippet_print() {
         .set_message("This is a test.".to_string())
         .print();
 }
+
+#[test]
+pub fn test_source_buffer_lines() {
+    let buffer = SourceBuffer::new("var é = 10;\nvar apple = 4;".to_string());
+    assert_eq!(buffer.line_count(), 2);
+    assert_eq!(buffer.line_col(16), Some((2, 5)));
+    assert_eq!(buffer.line_col(11), Some((1, 12)));
+    assert_eq!(buffer.line_col(26), Some((2, 15)));
+    assert_eq!(buffer.line_col(27), None);
+    assert_eq!(buffer.slice(4..5), "é");
+    assert_eq!(buffer.slice(16..21), "apple");
+    assert_eq!(buffer.get(24..28), "4;  ");
+    assert_eq!(buffer.get_line_at(20).unwrap().source(), "var apple = 4;");
+    assert!(buffer.get_line_at(11).is_none());
+
+    // every snippet looks its line up in the index instead of scanning the source.
+    let large = "var a = 1;\n".repeat(50_000);
+    let report = (0..200).fold(
+        Report::new().set_source(SourceBuffer::new(large)),
+        |report, i| report.make_snippet(i * 2_200..i * 2_200 + 3, "Here.".to_string(), None),
+    );
+    assert!(report
+        .snippets
+        .iter()
+        .all(|s| s.get_print().contains("var a = 1;")));
+}
+
+#[test]
+pub fn test_snippet_alignment() {
+    // the underline is under the name, whatever the line is indented with
+    // and however many bytes the chars before it take.
+    let code = "fn main() {\n\tvar s = \"é\"; var \tname = 1;\n}";
+    let start = code[..code.find("name").unwrap()].chars().count();
+    let buffer = SourceBuffer::new(code.to_string());
+    assert_eq!(buffer.slice(start..start + 4), "name");
+    let report = Report::new().set_source(buffer.clone()).make_snippet(
+        start..start + 4,
+        "Here.".to_string(),
+        None,
+    );
+    let printed = report.snippets[0].get_print();
+    let lines: Vec<&str> = printed.lines().collect();
+    let column = |line: &str, text: &str| line.find(text).map(|i| line[..i].chars().count());
+    assert_eq!(column(lines[0], "name"), column(lines[1], "~~~~ "));
+    assert!(!lines[0].contains('\t'));
+
+    let region = buffer.region_of(start..start + 4);
+    assert_eq!((region.start.line, region.start.column), (2, 20));
+    assert_eq!((region.end.line, region.end.column), (2, 24));
+    let end = buffer.region_of(0..1000).end;
+    assert_eq!((end.line, end.column), (3, 2));
+
+    let tokens = tokenize("var s = \"a\nbc\";");
+    let region = tokens[6].region();
+    assert_eq!((region.start.line, region.start.column), (1, 9));
+    assert_eq!((region.end.line, region.end.column), (2, 4));
+    let region = tokens[2].region();
+    assert_eq!((region.start.column, region.end.column), (5, 6));
+}
+
+#[test]
+pub fn test_wide_char_alignment() {
+    // chars printed two columns wide push the underline as far as they push the name.
+    let code = "var s = \"名前 😀\"; var name = 1;";
+    let start = code[..code.find("name").unwrap()].chars().count();
+    let report = Report::new()
+        .set_source(SourceBuffer::new(code.to_string()))
+        .make_snippet(start..start + 4, "Here.".to_string(), None);
+    let printed = report.snippets[0].get_print();
+    let lines: Vec<&str> = printed.lines().collect();
+    let wide = |c: char| match c {
+        '名' | '前' | '😀' => 2,
+        _ => 1,
+    };
+    let column = |line: &str, text: &str| {
+        line.find(text)
+            .map(|i| line[..i].chars().map(wide).sum::<usize>())
+    };
+    assert_eq!(column(lines[0], "name"), column(lines[1], "~~~~ "));
+
+    // a wide name is underlined as wide as it is printed.
+    let code = "var 名前 = 1;";
+    let report = Report::new()
+        .set_source(SourceBuffer::new(code.to_string()))
+        .make_snippet(4..6, "Here.".to_string(), None);
+    let printed = report.snippets[0].get_print();
+    assert_eq!(
+        printed.lines().nth(1).map(str::trim_end),
+        Some("    |     ~~~~")
+    );
+}
+
+#[test]
+pub fn test_tab_width() {
+    let code = "fn main() {\n\tvar\tname = 1;\n}";
+    let start = code.find("name").unwrap();
+    let buffer = SourceBuffer::new(code.to_string()).with_tab_width(2);
+    assert_eq!(buffer.tab_width(), 2);
+    let report =
+        Report::new()
+            .set_source(buffer)
+            .make_snippet(start..start + 4, "Here.".to_string(), None);
+    let column = |line: &str, text: &str| line.find(text);
+
+    let printed = report.snippets[0].get_print();
+    let lines: Vec<&str> = printed.lines().collect();
+    assert!(lines[0].ends_with("var  name = 1;"));
+    assert_eq!(column(lines[0], "name"), column(lines[1], "~~~~ "));
+
+    // the width of the report is used over the one of the source.
+    let printed = report.set_tab_width(8).snippets[0].get_print();
+    let lines: Vec<&str> = printed.lines().collect();
+    assert!(lines[0].ends_with("var        name = 1;"));
+    assert_eq!(column(lines[0], "name"), column(lines[1], "~~~~ "));
+}
+
+#[test]
+pub fn test_report_theme() {
+    let report = Report::new()
+        .set_source(SourceBuffer::new("var a = 1;\n".to_string()))
+        .make_snippet(4..5, "Here.".to_string(), None)
+        .set_kind(ReportKind::Warning)
+        .set_message("Unused.".to_string());
+
+    let plain = report.render(&ReportTheme::plain());
+    assert!(plain.starts_with("Warning! Unused.\n"));
+    assert!(!plain.contains('\x1b'));
+
+    let colored = report.render(&ReportTheme::colored());
+    assert!(colored.starts_with("\x1b[1;33mWarning!\x1b[0m Unused.\n"));
+    assert!(colored.contains("\x1b[2m    |\x1b[0m"));
+}
+
+#[test]
+pub fn test_report_codes() {
+    let mut parser = Parser::new(dev());
+    let report = parser
+        .try_parse_script(
+            "codes.surn".to_string(),
+            "var x = 1\nvar y = 2;".to_string(),
+        )
+        .unwrap_err();
+    assert_eq!(report.code, codes::MISSING_SEMICOLON);
+    assert!(report
+        .render(&ReportTheme::plain())
+        .starts_with("Error[E0102]! "));
+
+    let explanation = Report::explain("E0102").unwrap();
+    assert!(explanation.starts_with("E0102: missing semicolon\n"));
+    assert_eq!(Report::explain("e102"), Report::explain("E0102"));
+    assert!(Report::explain("E9999").is_none());
+    // every code is documented once.
+    let mut documented: Vec<u64> = codes::CODES.iter().map(|c| c.code).collect();
+    documented.dedup();
+    assert_eq!(documented.len(), codes::CODES.len());
+}
+
+#[test]
+pub fn test_diagnostics() {
+    let diagnostic = Diagnostic::warning("`x` is never used.".to_string())
+        .with_code(codes::EXPECTED_NAME)
+        .with_span(4..5, "Declared here.".to_string())
+        .with_note("Prefix it with `_` to keep it.".to_string());
+    let report = diagnostic.clone().into_report(
+        "unused.surn".to_string(),
+        SourceBuffer::new("var x = 1;\n".to_string()),
+    );
+    assert_eq!(report.kind, ReportKind::Warning);
+    assert!(report
+        .render(&ReportTheme::plain())
+        .ends_with("    | note: Prefix it with `_` to keep it.\n"));
+    assert_eq!(Diagnostic::from(report), diagnostic);
+
+    let found = diagnose(tokenize("fn f(a;"));
+    assert_eq!(found[0].code, codes::UNCLOSED_DELIMITER);
+    assert_eq!(found[0].spans[0].span, 4..5);
+}
@@ -0,0 +1,74 @@
+// parses every case in tests/resources/cases and checks the ast it prints.
+//
+// each `name.surn` case is parsed with the dev options and printed with
+// `AstBody::to_pretty_string`, which must equal `name.expected`.
+// set `SURN_BLESS` to write the printed ast to the `.expected` files instead:
+//
+// SURN_BLESS=1 cargo test snapshots
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::dev;
+use surn::compiler::parser::Parser;
+
+pub const CASES: &str = "tests/resources/cases";
+
+fn bless() -> bool {
+    env::var_os("SURN_BLESS").is_some_and(|value| !value.is_empty())
+}
+
+/// The first line the printed ast differs from the expected one at, counted from 1.
+fn first_difference(expected: &str, found: &str) -> usize {
+    let mut lines = expected.lines().zip(found.lines());
+    let same = lines.by_ref().take_while(|(a, b)| a == b).count();
+    same + 1
+}
+
+/// Checks a single case, returns why it failed.
+fn check(case: &Path) -> Result<(), String> {
+    let name = case.file_name().unwrap().to_string_lossy().to_string();
+    let code = fs::read_to_string(case).unwrap();
+    let mut parser = Parser::new(dev());
+    let body = parser
+        .try_parse_script(name.clone(), code)
+        .map_err(|report| format!("{}: {}", name, report.message))?;
+    let printed = body.to_pretty_string();
+
+    let expected_path = case.with_extension("expected");
+    if bless() {
+        fs::write(&expected_path, &printed).unwrap();
+        return Ok(());
+    }
+    let expected = fs::read_to_string(&expected_path).map_err(|_| {
+        format!(
+            "{}: there is no `{}`, run with SURN_BLESS=1 to write it",
+            name,
+            expected_path.display()
+        )
+    })?;
+    if printed != expected {
+        return Err(format!(
+            "{}: the ast differs from the expected one at line {}, run with SURN_BLESS=1 to update it\n{}",
+            name,
+            first_difference(&expected, &printed),
+            printed
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+pub fn parser_snapshots() {
+    let mut cases: Vec<PathBuf> = fs::read_dir(CASES)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|e| e == "surn"))
+        .collect();
+    cases.sort();
+    assert!(!cases.is_empty(), "there are no cases in {}", CASES);
+
+    let failures: Vec<String> = cases.iter().filter_map(|case| check(case).err()).collect();
+    assert!(failures.is_empty(), "{}", failures.join("\n\n"));
+}
@@ -1,5 +1,27 @@
 mod analyzer;
+mod convert;
+mod diagnostics;
+#[cfg(feature = "golden")]
+mod golden;
+mod ide;
+mod interp;
 mod parser;
+mod repl;
 mod report;
+mod snapshots;
 mod tokenize;
 mod transpiler;
+
+use surn::compiler::{ast::AstBody, parser::Parser, CompilerOptions};
+
+/// The options the tests compile with, see `CompilerOptionsBuilder::dev`.
+fn dev() -> CompilerOptions {
+    CompilerOptions::builder().dev().build().unwrap()
+}
+
+/// Parses `code` as the script `name` with the dev options.
+fn parse(name: &str, code: &str) -> AstBody {
+    Parser::new(dev())
+        .try_parse_script(name.to_string(), code.to_string())
+        .unwrap()
+}
@@ -1,6 +1,17 @@
-use std::fs;
+use std::{collections::HashSet, fs};
 
-use surn::compiler::lexer::{token::TokenType, tokenizer::tokenize};
+use crate::dev;
+use surn::compiler::ast::{NodeKind, Statement};
+use surn::compiler::lexer::{
+    analysis::diagnose,
+    escape::{escape, unescape},
+    pos::Position,
+    token::{same_tokens, Token, TokenType},
+    tokenizer::tokenize,
+};
+use surn::compiler::parser::Parser;
+use surn::report::codes;
+use surn::util::{intern::Symbol, StreamBuffer, TokenStream};
 
 // Tests the tokenizer with the given file.
 pub const TEST_A: &str = "tests/resources/test_a.surn";
@@ -36,5 +47,187 @@ pub fn test_token_comparison() {
     assert_ne!(TokenType::LeftBrace, TokenType::RightBrace);
 }
 
+#[test]
+pub fn test_token_positions() {
+    let code = "var a = 1;\n    var b = 2;\n";
+    let positions = tokenize(code)
+        .into_iter()
+        .filter(|t| t.value().as_deref() == Some("b") || t.value().as_deref() == Some("1"))
+        .map(|t| t.position())
+        .collect::<Vec<Position>>();
+    assert_eq!(positions, vec![Position::new(1, 9), Position::new(2, 9)]);
+
+    // nodes start where their first token does.
+    let mut parser = Parser::new(dev());
+    let body = parser.parse_script("positions.surn".to_string(), code.to_string());
+    let node = &body.get_program()[1];
+    assert!(matches!(node.inner, NodeKind::Statement(_)));
+    assert_eq!(node.position, Position::new(2, 5));
+}
+
 #[test]
 pub fn apple() {}
+
+#[test]
+pub fn test_token_kinds() {
+    let kinds = TokenType::all();
+    let names = kinds.iter().map(|k| k.name()).collect::<HashSet<String>>();
+    assert_eq!(names.len(), kinds.len());
+    assert!(names.contains("keyword.fn"));
+
+    let code = fs::read_to_string(TEST_A).unwrap();
+    assert!(tokenize(&code).iter().all(|t| kinds.contains(&t.kind())));
+
+    assert!(same_tokens(
+        &tokenize("var x = 1;"),
+        &tokenize("var x=1;\n")
+    ));
+    assert!(!same_tokens(
+        &tokenize("var x = 1;"),
+        &tokenize("var y = 1;")
+    ));
+    let first = tokenize("x x;");
+    assert!(first[0].same_kind_and_value(&first[2]));
+}
+
+#[test]
+pub fn test_string_escapes() {
+    // an escaped quote doesn't close the string.
+    let code = r#"var s = "say \"hi\"\t\u{1F600}";"#;
+    let string = tokenize(code)
+        .into_iter()
+        .find(|t| t.kind().is_string())
+        .unwrap();
+    assert_eq!(string.value().unwrap(), r#"say \"hi\"\t\u{1F600}"#);
+    assert_eq!(string.cooked().unwrap(), "say \"hi\"\t\u{1F600}");
+    assert_eq!(escape("a\"\n\\"), r#"a\"\n\\"#);
+
+    let error = unescape(r"a\qb").unwrap_err();
+    assert_eq!(error.range, 1..3);
+    assert!(unescape(r"\u{110000}").is_err());
+
+    let found = diagnose(tokenize(r#"var path = "C:\data";"#));
+    assert_eq!(found[0].code, codes::INVALID_ESCAPE);
+    assert_eq!(found[0].spans[0].span, 14..16);
+    let mut parser = Parser::new(dev());
+    let report = parser
+        .try_parse_script(
+            "escapes.surn".to_string(),
+            r#"var path = "C:\data";"#.to_string(),
+        )
+        .unwrap_err();
+    assert_eq!(report.code, codes::INVALID_ESCAPE);
+}
+
+#[test]
+pub fn test_unicode_identifiers() {
+    let tokens: Vec<Token> = tokenize("var café = 1; var 名前 = \"😀\";")
+        .into_iter()
+        .filter(|t| !t.kind().is_whitespace())
+        .collect();
+    let names: Vec<&str> = tokens
+        .iter()
+        .filter(|t| t.kind().is_identifier())
+        .filter_map(|t| t.text())
+        .collect();
+    assert_eq!(names, vec!["café", "名前"]);
+    // columns and ranges count chars, not bytes.
+    assert_eq!(tokens[2].range(), 9..10);
+    assert_eq!(tokens[2].position(), Position::new(1, 10));
+
+    let found = diagnose(tokenize("var mood = 😀;"));
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].code, codes::UNEXPECTED_CHARACTER);
+    assert_eq!(found[0].spans[0].span, 11..12);
+}
+
+#[test]
+pub fn test_interned_names() {
+    let tokens = tokenize("var total = total + other;");
+    let names: Vec<Symbol> = tokens
+        .iter()
+        .filter(|t| t.kind().is_identifier())
+        .filter_map(|t| t.symbol())
+        .collect();
+    assert_eq!(names[0], Symbol::intern("total"));
+    assert_eq!(names[0], names[1]);
+    assert_ne!(names[1], names[2]);
+    assert_eq!(names[2].resolve(), "other");
+    assert!(Symbol::intern("apple") < Symbol::intern("banana"));
+
+    let mut parser = Parser::new(dev());
+    let body = parser.parse_script("names.surn".to_string(), "var count = 1;".to_string());
+    match &body.get_program()[0].inner {
+        NodeKind::Statement(Statement::Var(var)) => {
+            assert_eq!(var.name, Symbol::intern("count"));
+            assert_eq!(var.name, "count");
+        }
+        _ => panic!("expected a variable"),
+    }
+}
+
+#[test]
+pub fn test_token_values() {
+    let source = "#[ route(\"/é\") ]\nvar name = \"é\\n\"; php! { echo \"ü\"; }";
+    let tokens: Vec<Token> = tokenize(source)
+        .into_iter()
+        .filter(|t| !t.kind().is_whitespace())
+        .collect();
+    assert_eq!(tokens[0].text(), Some("route(\"/é\")"));
+    assert_eq!(tokens[2].text(), Some("name"));
+    assert_eq!(tokens[4].text(), Some("é\\n"));
+    assert_eq!(tokens[4].cooked().as_deref(), Some("é\n"));
+    assert_eq!(tokens[2].cooked(), None);
+    let body = tokens.iter().find(|t| t.kind() == TokenType::MacroBody);
+    assert_eq!(body.unwrap().text(), Some(" echo \"ü\"; "));
+    assert_eq!(tokens[2].value(), Some("name".to_string()));
+}
+
+#[test]
+pub fn test_stream_checkpoints() {
+    let mut tokens = TokenStream::new(tokenize("var (a, b) = pair;"));
+    assert!(tokens.prev().is_none());
+    let start = tokens.checkpoint();
+    tokens.peek_inc(3);
+    let after = tokens.checkpoint();
+    assert!(tokens.prev().unwrap().kind().is_left_parenthesis());
+
+    // a checkpoint can be rewound to more than once, and forwards too.
+    tokens.rewind(start);
+    assert_eq!(tokens.eaten(), 0);
+    assert!(tokens.first().unwrap().kind().is_keyword());
+    tokens.rewind(after);
+    assert_eq!(tokens.first().unwrap().text(), Some("a"));
+    assert!(tokens.unpeek().unwrap().kind().is_left_parenthesis());
+
+    // a clone shares the tokens but moves on its own.
+    let mut clone = tokens.clone();
+    clone.peek_inc(100);
+    assert!(clone.is_eof() && !tokens.is_eof());
+    assert!(clone.prev().unwrap().kind().is_statement_end());
+}
+
+#[test]
+pub fn test_stream_balanced() {
+    let mut tokens = TokenStream::new(tokenize("{ a(b) { c } } d"));
+    let mut body = tokens.balanced().unwrap();
+    assert_eq!(tokens.first().unwrap().text(), Some(" "));
+    assert_eq!(tokens.second().unwrap().text(), Some("d"));
+
+    // the body ends before the brace that closes it, and can be split again.
+    assert_eq!(body.items().len(), 12);
+    assert!(body.prev().is_none());
+    body.peek_inc(2);
+    let arguments = body.balanced().unwrap();
+    assert_eq!(arguments.items().len(), 1);
+    assert_eq!(body.eaten(), 5);
+    body.peek_inc(100);
+    assert!(body.prev().unwrap().kind().is_whitespace());
+
+    // nothing is taken when the next token doesn't open anything or is never closed.
+    let mut unclosed = TokenStream::new(tokenize("(a, (b)"));
+    assert!(unclosed.balanced().is_none());
+    assert_eq!(unclosed.eaten(), 0);
+    let slice = unclosed.slice(3..100);
+    assert_eq!(slice.items().len(), 4);
+}
@@ -1,24 +1,1139 @@
 use std::fs::{self, File};
 use std::io::Write;
+use std::path::Path;
 
+use crate::{dev, parse};
+use surn::compiler::ast::{
+    build::{self, BodyBuilder, ClassBuilder, FunctionBuilder},
+    expr,
+    ops::BinOp,
+    types::{BuiltInType, TypeKind},
+    AstBody, Visibility,
+};
 use surn::compiler::parser::Parser;
-use surn::compiler::CompilerOptions;
+use surn::compiler::{
+    lints::{LintLevel, ERASED_GENERICS, NARROWED_INTEGERS, UNUSED_VARIABLE},
+    optimize::{optimize, profile::Profile},
+    CompilerOptions, PhpTarget,
+};
+use surn::project::{new_project, FormatProfile, Manifest, Project};
+use surn::report::{codes, ReportKind};
+use surn::transpiler::audit::{audit, Coverage};
+use surn::transpiler::driver::{generate_file, generate_tree, sidecar_path};
+use surn::transpiler::format::{printer::format_source, AccessorStyle, BraceType, FormatOptions};
+use surn::transpiler::frameworks::Framework;
+use surn::transpiler::langs::Generator;
+use surn::transpiler::preload::PRELOAD_FILE;
+use surn::transpiler::scaffold::{scaffold, Package, Template};
 use surn::transpiler::Transpiler;
+use surn::util::provider::{files, MemoryProvider, OsProvider, SourceProvider};
 
 pub const EXPRESSIONS: &str = "tests/resources/transpiler.surn";
 
+/// A transpiler with the default languages registered.
+fn transpiler() -> Transpiler {
+    let mut transpiler = Transpiler::new();
+    transpiler.register_defaults();
+    transpiler
+}
+
+/// Transpiles `code` to php with the default format.
+fn php(code: &str, options: CompilerOptions) -> String {
+    let body = Parser::new(options.clone())
+        .try_parse_script("test.surn".to_string(), code.to_string())
+        .unwrap();
+    transpiler()
+        .get("php")
+        .unwrap()
+        .generator
+        .generate_to_string(body, options, &FormatOptions::default())
+}
+
 #[test]
 pub fn transpile_php() {
     let mut transpiler = Transpiler::new();
     transpiler.register_defaults();
     let contents = fs::read_to_string(EXPRESSIONS).unwrap();
-    let mut parser = Parser::new(CompilerOptions::dev());
+    let mut parser = Parser::new(dev());
     let body = parser.parse_script(EXPRESSIONS.to_string(), contents);
-    let code = transpiler
-        .get("php")
-        .unwrap()
-        .generator
-        .generate_to_string(body, CompilerOptions::dev());
+    let code = transpiler.get("php").unwrap().generator.generate_to_string(
+        body,
+        dev(),
+        &FormatOptions::psr_4(),
+    );
     let mut f = File::create("tests/resources/test.php").unwrap();
     f.write_all(code.as_bytes()).unwrap();
 }
+
+#[test]
+pub fn transpile_directory_tree() {
+    let root = std::env::temp_dir().join(format!("surn-driver-{}", std::process::id()));
+    let src = root.join("src");
+    fs::create_dir_all(src.join("nested")).unwrap();
+    fs::write(src.join("main.surn"), "var x = 5;\n").unwrap();
+    fs::write(src.join("nested/util.surn"), "var y = 10;\n").unwrap();
+    fs::write(src.join("broken.surn"), "var z = ;\n").unwrap();
+    fs::write(src.join("notes.txt"), "not a source file").unwrap();
+
+    let transpiler = transpiler();
+    let php = transpiler.get("php").unwrap();
+    let out = root.join("out");
+    let report = generate_tree(
+        &OsProvider,
+        php.generator.as_ref(),
+        &src,
+        &out,
+        &php.extension,
+        &dev(),
+        &FormatOptions::psr_4(),
+    )
+    .unwrap();
+
+    // the broken file doesn't stop the rest of the batch.
+    assert_eq!(report.files.len(), 3);
+    assert_eq!(report.failed().len(), 1);
+    assert!(report.failed()[0].source.ends_with("broken.surn"));
+    assert!(out.join("main.php").is_file());
+    assert!(out.join("nested/util.php").is_file());
+    assert!(!out.join("broken.php").exists());
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+pub fn transpile_memory_provider() {
+    let files = MemoryProvider::new();
+    files.insert("src/main.surn", "var x = 5;\n");
+    files.insert("src/nested/util.surn", "var y = 10;\n");
+    files.insert("src/notes.txt", "not a source file");
+
+    let transpiler = transpiler();
+    let php = transpiler.get("php").unwrap();
+    let mut options = dev();
+    options.preload = true;
+    let mut watcher = files.watch(Path::new("src")).unwrap();
+    let report = generate_tree(
+        &files,
+        php.generator.as_ref(),
+        Path::new("src"),
+        Path::new("out"),
+        &php.extension,
+        &options,
+        &FormatOptions::psr_4(),
+    )
+    .unwrap();
+    assert!(report.failed().is_empty());
+    php.generator
+        .write_support(&files, Path::new("src"), Path::new("out"), &options)
+        .unwrap();
+
+    // nothing is written to the disk.
+    assert!(!Path::new("out").exists());
+    assert_eq!(files.get("out/main.php").unwrap(), "<?php\n\n$x = 5;\n");
+    assert!(files.get("out/nested/util.php").is_some());
+    assert!(files.get(Path::new("out").join(PRELOAD_FILE)).is_some());
+
+    assert!(watcher.changed(&files).unwrap().is_empty());
+    files.insert("src/main.surn", "var x = 6;\n");
+    files.remove("src/nested/util.surn");
+    assert_eq!(
+        watcher.changed(&files).unwrap(),
+        vec![
+            Path::new("src/main.surn"),
+            Path::new("src/nested/util.surn")
+        ]
+    );
+}
+
+#[test]
+pub fn transpile_source_map() {
+    let root = std::env::temp_dir().join(format!("surn-sourcemap-{}", std::process::id()));
+    fs::create_dir_all(&root).unwrap();
+    let source = root.join("main.surn");
+    let code = "var x = 5;\n\nvar y = 10;\n";
+    fs::write(&source, code).unwrap();
+
+    let transpiler = transpiler();
+    let php = transpiler.get("php").unwrap();
+    let mut options = dev();
+    options.source_maps = true;
+    let output = root.join("main.php");
+    generate_file(
+        &OsProvider,
+        php.generator.as_ref(),
+        &source,
+        Path::new("main.surn"),
+        &output,
+        &options,
+        &FormatOptions::psr_4(),
+    )
+    .unwrap();
+
+    let mut parser = Parser::new(options.clone());
+    let body = parser.parse_script("main.surn".to_string(), code.to_string());
+    let (_, map) = php
+        .generator
+        .generate_with_map(body, options, &FormatOptions::psr_4());
+    // `var y` is on the third line of the source but the fourth line of the output,
+    // after the `<?php` tag.
+    assert_eq!(map.original_position(4, code), Some((3, 1)));
+    assert_eq!(map.lookup(4).unwrap().name.as_deref(), Some("y"));
+
+    let json = fs::read_to_string(sidecar_path(&output)).unwrap();
+    assert!(json.starts_with("{\"version\":1,\"file\":\"main.php\",\"source\":\"main.surn\""));
+    assert!(json.contains("\"line\":4,\"column\":0,\"start\":12"));
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+pub fn transpile_generics() {
+    let code = "class Box<T> {\n    pub fn get(): T {\n        return 1;\n    }\n}\nfn wrap<T>(value: T): Box<T> {\n    return new Box<T>();\n}\nvar b = wrap<int>(5);\n";
+    let output = php(code, dev());
+    assert_eq!(
+        output,
+        "class Box {\n    public function get(): mixed {\n        return 1;\n    }\n}\nfunction wrap(mixed $value): Box {\n    return new Box();\n}\n$b = wrap(5);\n"
+    );
+
+    let formatted = format_source("generics.surn", code, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, code);
+}
+
+#[test]
+pub fn transpile_nullable_types() {
+    let code = "fn find(id: int?, key: int | string?): string? {\n    return null;\n}\nfn wrap<T>(value: T?): T? {\n    return value;\n}\nvar a: string? = null;\n";
+    let output = php(code, dev());
+    assert_eq!(
+        output,
+        "function find(?int $id, int|string|null $key): ?string {\n    return null;\n}\nfunction wrap(mixed $value): mixed {\n    return $value;\n}\n$a = null;\n"
+    );
+
+    let formatted = format_source("nullable.surn", code, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, code);
+}
+
+#[test]
+pub fn transpile_exceptions() {
+    let code = "fn load(path: string): string {\n    try {\n        return read(path);\n    } catch (e: IOError) {\n        throw new LoadError(path);\n    } catch (e) {\n        log(e);\n    } finally {\n        close(path);\n    }\n}\n";
+    let output = php(code, dev());
+    assert_eq!(
+        output,
+        "function load(string $path): string {\n    try {\n        return read($path);\n    } catch (IOError $e) {\n        throw new LoadError($path);\n    } catch (\\Throwable $e) {\n        log($e);\n    } finally {\n        close($path);\n    }\n}\n"
+    );
+
+    let formatted = format_source("exceptions.surn", code, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, code);
+
+    let mut parser = Parser::new(dev());
+    let report = parser
+        .try_parse_script(
+            "try.surn".to_string(),
+            "try {\n    load();\n}\n".to_string(),
+        )
+        .unwrap_err();
+    assert_eq!(report.code, codes::INVALID_SYNTAX);
+}
+
+#[test]
+pub fn transpile_stdlib_calls() {
+    let code = "fn words(line: string): array {\n    println(to_upper(line));\n    return split(trim(line), \" \");\n}\nfn trim(value: string): string {\n    return value;\n}\n";
+    let output = php(code, dev());
+    // `trim` is declared in the script, so it isn't the one of the standard library.
+    assert!(output.contains("printf(\"%s\" . PHP_EOL, strtoupper($line));"));
+    assert!(output.contains("return explode(\" \", trim($line));"));
+}
+
+#[test]
+pub fn transpile_intrinsics() {
+    let code = "fn sizes(name: string, values: array) {\n    var letters = len(name);\n    var count = len(values);\n    var upper = len(to_upper(name));\n    var stamp = now();\n}\n";
+    let mut transpiler = transpiler();
+    let php = transpiler.get_mut("php").unwrap();
+    php.intrinsics_mut().unwrap().template("now", "time()");
+    let body = parse("intrinsics.surn", code);
+    let output = php
+        .generator
+        .generate_to_string(body, dev(), &FormatOptions::default());
+    // the type of the argument picks the function, calls return the type they're declared with.
+    assert!(output.contains("$letters = strlen($name);"));
+    assert!(output.contains("$count = count($values);"));
+    assert!(output.contains("$upper = strlen(strtoupper($name));"));
+    assert!(output.contains("$stamp = time();"));
+
+    // a placeholder in the code of an argument is left as it is.
+    let code = "fn swap(a: string, b: string): string {\n    return replace(\"{2}\", a, b);\n}\n";
+    let body = parse("intrinsics.surn", code);
+    let output = php
+        .generator
+        .generate_to_string(body, dev(), &FormatOptions::default());
+    assert!(output.contains("return str_replace($a, $b, \"{2}\");"));
+}
+
+#[test]
+pub fn transpile_async_functions() {
+    let code = "async fn load(url: string): string {\n    var page = await fetch(url);\n    return page;\n}\n";
+    let transpiler = transpiler();
+    let php = transpiler.get("php").unwrap();
+    let body = parse("async.surn", code);
+    let lowerings = php.generator.lossy_lowerings(&body);
+    assert_eq!(lowerings.len(), 1);
+    assert_eq!(
+        lowerings[0].message,
+        "`async` is dropped, php runs `load` synchronously."
+    );
+    assert_eq!(&code[lowerings[0].span.clone()], "async fn load");
+
+    let output = php
+        .generator
+        .generate_to_string(body, dev(), &FormatOptions::default());
+    assert_eq!(
+        output,
+        "function load(string $url): string {\n    $page = fetch($url);\n    return $page;\n}\n"
+    );
+
+    let formatted = format_source("async.surn", code, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, code);
+}
+
+#[test]
+pub fn transpile_string_escapes() {
+    let code = r#"var greeting = 'Hi "$name"\n\u{1F600}';"#;
+    let output = php(code, dev());
+    // `$` is escaped, or php would interpolate `$name`.
+    assert_eq!(output, "$greeting = \"Hi \\\"\\$name\\\"\\n\u{1F600}\";\n");
+
+    let formatted = format_source("escapes.surn", code, &FormatOptions::default()).unwrap();
+    assert_eq!(
+        formatted,
+        "var greeting = \"Hi \\\"$name\\\"\\n\u{1F600}\";\n"
+    );
+}
+
+#[test]
+pub fn transpile_function_types() {
+    let code = "fn apply(f: (int) => bool, g: (int, string) => bool | null): (int) => string {\n    return f;\n}\nclass Handler {\n    pub cb: (string) => int;\n}\n";
+    let output = php(code, dev());
+    assert_eq!(
+        output,
+        "/**\n * @param callable(int): bool $f\n * @param (callable(int, string): bool)|null $g\n * @return callable(int): string\n */\nfunction apply(callable $f, callable|null $g): callable {\n    return $f;\n}\nclass Handler {\n    /** @var callable(string): int */ public $cb;\n}\n"
+    );
+
+    let formatted = format_source("callable.surn", code, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, code);
+}
+
+#[test]
+pub fn transpile_tuples() {
+    let code = "fn divide(a: int, b: int): (int, int) {\n    return (a / b, a % b);\n}\nvar (q, r) = divide(7, 2);\nclass Pair {\n    pub pair: (int, string)?;\n}\n";
+    let output = php(code, dev());
+    assert_eq!(
+        output,
+        "/**\n * @return array{int, int}\n */\nfunction divide(int $a, int $b): array {\n    return [$a / $b, $a % $b];\n}\n[$q, $r] = divide(7, 2);\nclass Pair {\n    /** @var array{int, string}|null */ public ?array $pair;\n}\n"
+    );
+
+    let formatted = format_source("tuple.surn", code, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, code);
+}
+
+#[test]
+pub fn transpile_folded_consts() {
+    let code = "const A = 2 * 3 + 1;\nconst B = A - 1;\nconst F = 7 / 2;\nconst R = [A, B > 5];\n";
+    let transpiler = transpiler();
+    let php = transpiler.get("php").unwrap();
+    let mut body = parse("consts.surn", code);
+    optimize(&mut body, &CompilerOptions::default());
+    let output = php
+        .generator
+        .generate_to_string(body, dev(), &FormatOptions::default());
+    assert_eq!(
+        output,
+        "const A = 7;\nconst B = 6;\nconst F = 3.5;\nconst R = [7, true];\n"
+    );
+
+    // `==` compares values of different types the way php does.
+    let code = "const E = [1 == 1.0, \"1\" == 1, \"abc\" == 0, null == false, \"1e1\" == \"10\"];\nconst N = 1 != 1.0;\n";
+    let mut body = parse("consts.surn", code);
+    optimize(&mut body, &CompilerOptions::default());
+    let output = php
+        .generator
+        .generate_to_string(body, dev(), &FormatOptions::default());
+    assert_eq!(
+        output,
+        "const E = [true, true, false, true, true];\nconst N = false;\n"
+    );
+
+    // a const of a function body is not folded into the code after it.
+    let code = "fn f() {\n    const L = 3;\n}\nconst M = L + 1;\n";
+    let mut body = parse("consts.surn", code);
+    optimize(&mut body, &CompilerOptions::default());
+    let output = php
+        .generator
+        .generate_to_string(body, dev(), &FormatOptions::default());
+    assert_eq!(
+        output,
+        "function f() {\n    const L = 3;\n}\nconst M = $L + 1;\n"
+    );
+}
+
+#[test]
+pub fn transpile_class_constants() {
+    let code = "class Page extends Model {\n    const SIZE = 10;\n    priv const MAX = 5 * 10;\n    const PAGE = self::MAX;\n}\nconst LIMIT = Page::SIZE;\n";
+    let transpiler = transpiler();
+    let php = transpiler.get("php").unwrap();
+    let mut body = parse("page.surn", code);
+    optimize(&mut body, &CompilerOptions::default());
+    let output = php
+        .generator
+        .generate_to_string(body, dev(), &FormatOptions::default());
+    assert_eq!(
+        output,
+        "class Page extends Model {\n    const SIZE = 10;\n    private const MAX = 50;\n    const PAGE = 50;\n}\nconst LIMIT = 10;\n"
+    );
+    let formatted = format_source("page.surn", code, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, code);
+
+    let code = "class P {\n    const MAX = 10;\n    const MIN = self::MAX * 2;\n}\nclass Q {\n    const NEXT = P::MAX + 1;\n}\nconst R = 2 * P::MIN + 1;\n";
+    let mut body = parse("page.surn", code);
+    optimize(&mut body, &CompilerOptions::default());
+    let output = php
+        .generator
+        .generate_to_string(body, dev(), &FormatOptions::default());
+    assert_eq!(
+        output,
+        "class P {\n    const MAX = 10;\n    const MIN = 20;\n}\nclass Q {\n    const NEXT = 11;\n}\nconst R = 41;\n"
+    );
+}
+
+#[test]
+pub fn transpile_property_accessors() {
+    let code = "class User {\n    pub name: string { get; set; }\n    pub age: int = 0;\n    get age(): int {\n        return this.age;\n    }\n}\nfn rename(user: User) {\n    user.name = \"a\";\n}\n";
+    let transpiler = transpiler();
+    let php = transpiler.get("php").unwrap();
+    let body = parse("user.surn", code);
+    let output = php
+        .generator
+        .generate_to_string(body.clone(), dev(), &FormatOptions::default());
+    assert_eq!(output, "class User {\n    private string $name;\n    private int $age = 0;\n    private function getName(): string {\n        return $this->name;\n    }\n    private function setName(string $value) {\n        $this->name = $value;\n    }\n    private function getAge(): int {\n        return $this->age;\n    }\n    public function __get(string $name): mixed {\n        return match ($name) {\n            'name' => $this->getName(),\n            'age' => $this->getAge(),\n            default => throw new \\Error('Undefined property: ' . static::class . '::$' . $name),\n        };\n    }\n    public function __set(string $name, mixed $value): void {\n        match ($name) {\n            'name' => $this->setName($value),\n            default => throw new \\Error('Undefined property: ' . static::class . '::$' . $name),\n        };\n    }\n}\nfunction rename(User $user) {\n    $user->name = \"a\";\n}\n");
+
+    let methods = FormatOptions {
+        accessors: AccessorStyle::Methods,
+        ..FormatOptions::default()
+    };
+    let output = php.generator.generate_to_string(body, dev(), &methods);
+    assert_eq!(output, "class User {\n    private string $name;\n    private int $age = 0;\n    public function getName(): string {\n        return $this->name;\n    }\n    public function setName(string $value) {\n        $this->name = $value;\n    }\n    public function getAge(): int {\n        return $this->age;\n    }\n}\nfunction rename(User $user) {\n    $user->setName(\"a\");\n}\n");
+
+    let formatted = format_source("user.surn", code, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, code);
+}
+
+#[test]
+pub fn transpile_variadic_parameters() {
+    let code = "fn sum(base: int, ...numbers: int): int {\n    return base;\n}\nvar total = sum(1, 2, 3);\nvar values = [2, 3];\nvar spread = sum(1, ...values);\nvar joined = [1, ...values];\n";
+    let output = php(code, dev());
+    assert_eq!(output, "function sum(int $base, int ...$numbers): int {\n    return $base;\n}\n$total = sum(1, 2, 3);\n/** @var int[] $values */ $values = [2, 3];\n$spread = sum(1, ...$values);\n$joined = [1, ...$values];\n");
+
+    let formatted = format_source("sum.surn", code, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, code);
+}
+
+#[test]
+pub fn transpile_object_shapes() {
+    let code = "type Point = { x: int, y: int };\nfn zero(): Point {\n    return { x: 0, y: 0 };\n}\nvar p: { x: int, label: string } = { x: 1, label: \"a\" };\n";
+    let output = php(code, dev());
+    assert_eq!(
+        output,
+        "/**\n * @return array{x: int, y: int}\n */\nfunction zero(): array {\n    return ['x' => 0, 'y' => 0];\n}\n/** @var array{x: int, label: string} $p */ $p = ['x' => 1, 'label' => \"a\"];\n"
+    );
+
+    let formatted = format_source("shape.surn", code, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, code);
+}
+
+#[test]
+pub fn transpile_laravel_provider() {
+    let root = std::env::temp_dir().join(format!("surn-laravel-{}", std::process::id()));
+    let src = root.join("src");
+    fs::create_dir_all(&src).unwrap();
+    let mail = "namespace App\\Mail;\nuse App::Contracts::Mailer;\n#[Singleton]\nclass SmtpMailer implements Mailer {\n}\n#[Service(Clock)]\nclass SystemClock {\n}\n#[Service]\nclass Report {\n}\n";
+    fs::write(src.join("mail.surn"), mail).unwrap();
+
+    let mut options = dev();
+    options.framework = Some(Framework::Laravel);
+    transpiler()
+        .get_mut("php")
+        .unwrap()
+        .generator
+        .generate(src.to_str().unwrap(), options)
+        .unwrap();
+
+    let provider = fs::read_to_string(root.join("out/Providers/SurnServiceProvider.php")).unwrap();
+    assert!(provider.contains("class SurnServiceProvider extends ServiceProvider"));
+    assert!(provider.contains(
+        "        $this->app->singleton(\\App\\Contracts\\Mailer::class, \\App\\Mail\\SmtpMailer::class);\n        $this->app->bind(\\App\\Mail\\Clock::class, \\App\\Mail\\SystemClock::class);\n        $this->app->bind(\\App\\Mail\\Report::class);\n"
+    ));
+
+    let formatted = format_source("mail.surn", mail, &FormatOptions::default()).unwrap();
+    assert!(formatted.contains("#[Service(Clock)]\nclass SystemClock {"));
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+pub fn transpile_preload_script() {
+    let root = std::env::temp_dir().join(format!("surn-preload-{}", std::process::id()));
+    let src = root.join("src");
+    fs::create_dir_all(src.join("models")).unwrap();
+    fs::write(
+        src.join("models/admin.surn"),
+        "use models::User;\nclass Admin extends User {\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        src.join("models/user.surn"),
+        "class User implements Named {\n}\n",
+    )
+    .unwrap();
+    fs::write(src.join("named.surn"), "class Named {\n}\n").unwrap();
+    fs::write(src.join("main.surn"), "var x = 5;\n").unwrap();
+
+    let mut options = dev();
+    options.preload = true;
+    transpiler()
+        .get_mut("php")
+        .unwrap()
+        .generator
+        .generate(src.to_str().unwrap(), options)
+        .unwrap();
+
+    let script = fs::read_to_string(root.join("out").join(PRELOAD_FILE)).unwrap();
+    assert_eq!(
+        script,
+        "<?php\n\n// Generated by surn, point `opcache.preload` at this file.\nopcache_compile_file(__DIR__ . '/named.php');\nopcache_compile_file(__DIR__ . '/models/user.php');\nopcache_compile_file(__DIR__ . '/models/admin.php');\n"
+    );
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+pub fn transpile_format_profiles() {
+    let code = "fn addValues(firstValue: int, b: int): int {\n    var myTotal = firstValue + b;\n    return myTotal;\n}\nclass Counter {\n    pub currentCount: int = 0;\n}\n";
+    let transpiler = transpiler();
+    let php = transpiler.get("php").unwrap();
+    let body = parse("format.surn", code);
+
+    let psr = php
+        .generator
+        .generate_to_string(body.clone(), dev(), &FormatOptions::psr_4());
+    assert_eq!(
+        psr,
+        "function addValues(int $firstValue, int $b): int\n{\n    $myTotal = $firstValue + $b;\n    return $myTotal;\n}\nclass Counter\n{\n    public int $currentCount = 0;\n}\n"
+    );
+
+    let rust = php
+        .generator
+        .generate_to_string(body, dev(), &FormatOptions::rust());
+    assert_eq!(
+        rust,
+        "function addValues(int $first_value, int $b): int {\n    $my_total = $first_value + $b;\n    return $my_total;\n}\nclass Counter {\n    public int $current_count = 0;\n}\n"
+    );
+
+    // the names the profiles had before are kept as aliases.
+    #[allow(deprecated)]
+    let renamed = [
+        (FormatOptions::PSR_4(), FormatOptions::psr_4()),
+        (FormatOptions::Rust(), FormatOptions::rust()),
+        (FormatOptions::Default(), FormatOptions::default()),
+    ];
+    for (old, new) in renamed {
+        assert_eq!(format!("{:?}", old), format!("{:?}", new));
+    }
+}
+
+#[test]
+pub fn format_surn_source() {
+    let source = "// entry\nvar   x:int=5; // five\n\n\n\nfn add(a:int,b:int):int{\n    // sum\n    var c=a+b;\n}\n";
+    let formatted = format_source("format.surn", source, &FormatOptions::default()).unwrap();
+    assert_eq!(
+        formatted,
+        "// entry\nvar x: int = 5; // five\n\nfn add(a: int, b: int): int {\n    // sum\n    var c = a + b;\n}\n"
+    );
+    // formatting is idempotent.
+    assert_eq!(
+        format_source("format.surn", &formatted, &FormatOptions::default()).unwrap(),
+        formatted
+    );
+
+    let psr = format_source("format.surn", source, &FormatOptions::psr_4()).unwrap();
+    assert!(psr.contains("fn add(a: int, b: int): int\n{\n"));
+
+    // the visibility of a static member stays in front of `static`.
+    let class = "class A {\n    pub static count: int;\n    pub static fn make(): A {\n    }\n}\n";
+    assert_eq!(
+        format_source("format.surn", class, &FormatOptions::default()).unwrap(),
+        class
+    );
+}
+
+#[test]
+pub fn transpile_specialized_functions() {
+    let code = "fn add(a: any, b: any): any {\n    return a + b;\n}\nvar x = add(1, 2);\nvar y = add(x, 3);\nvar z = add(\"a\", \"b\");\n";
+    let transpiler = transpiler();
+    let php = transpiler.get("php").unwrap();
+    let options = CompilerOptions {
+        specialize: true,
+        hot_functions: vec!["add".to_string()],
+        ..dev()
+    };
+    let mut parser = Parser::new(options.clone());
+    let mut body = parser.parse_script("specialize.surn".to_string(), code.to_string());
+    optimize(&mut body, &options);
+
+    let code = php
+        .generator
+        .generate_to_string(body, options.clone(), &FormatOptions::default());
+    assert!(code.contains("function add(mixed $a, mixed $b): mixed {"));
+    assert!(code.contains("function add__int_int(int $a, int $b): mixed {"));
+    assert!(code.contains("$x = add__int_int(1, 2);"));
+    assert!(code.contains("$z = add__string_string(\"a\", \"b\");"));
+    // `x` is the result of an erased function, so its type is unknown.
+    assert!(code.contains("$y = add($x, 3);"));
+
+    // a copy never takes the name of a function the source declares.
+    let code = "fn add(a: any, b: any): any {\n    return a + b;\n}\nfn add__int_int(): int {\n    return 0;\n}\nvar x = add(1, 2);\nvar y = add(3, 4);\n";
+    let mut body = parser.parse_script("taken.surn".to_string(), code.to_string());
+    optimize(&mut body, &options);
+    let code = php
+        .generator
+        .generate_to_string(body, options.clone(), &FormatOptions::default());
+    assert!(code.contains("function add__int_int(): int {"));
+    assert!(code.contains("function add__int_int_2(int $a, int $b): mixed {"));
+    assert!(code.contains("$x = add__int_int_2(1, 2);"));
+    assert!(code.contains("$y = add__int_int_2(3, 4);"));
+
+    // the type parameters of a generic are bound by the arguments, or given with the call.
+    let code = "fn pick<T>(value: T, fallback: T): T {\n    return value;\n}\nfn all<T>(items: T[]): T[] {\n    return items;\n}\nvar a = pick(1, 2);\nvar b = pick<string>(\"x\", \"y\");\nvar c = pick(1, \"y\");\nvar d = all([1, 2]);\n";
+    let options = CompilerOptions {
+        hot_functions: vec!["pick".to_string(), "all".to_string()],
+        ..options
+    };
+    let mut body = parser.parse_script("generic.surn".to_string(), code.to_string());
+    optimize(&mut body, &options);
+    let code = php
+        .generator
+        .generate_to_string(body, options, &FormatOptions::default());
+    assert!(code.contains("function pick(mixed $value, mixed $fallback): mixed {"));
+    assert!(code.contains("function pick__int(int $value, int $fallback): int {"));
+    assert!(code.contains("function pick__string(string $value, string $fallback): string {"));
+    assert!(code.contains("$a = pick__int(1, 2);"));
+    assert!(code.contains("$b = pick__string(\"x\", \"y\");"));
+    // `T` can't be both an `int` and a `string`.
+    assert!(code.contains("$c = pick(1, \"y\");"));
+    assert!(code.contains("$d = all__int([1, 2]);"));
+}
+
+struct PlainGenerator;
+
+impl Generator for PlainGenerator {
+    fn generate_to_string(&self, _: AstBody, _: CompilerOptions, _: &FormatOptions) -> String {
+        String::new()
+    }
+
+    fn generate_with(
+        &mut self,
+        _: &dyn SourceProvider,
+        _: &str,
+        _: CompilerOptions,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[test]
+pub fn transpile_php_macro() {
+    let code = "php! { echo \"}\"; }\nfn main() {\n    php! {\n        if ($ready) {\n            run();\n        }\n    }\n}\nclass Foo {\n    php! { use Bar; }\n}\n";
+    let output = php(code, dev());
+    assert_eq!(
+        output,
+        "echo \"}\";\nfunction main() {\n    if ($ready) {\n        run();\n    }\n}\nclass Foo {\n    use Bar;\n}\n"
+    );
+
+    // other languages can't pass php through.
+    let root = std::env::temp_dir().join(format!("surn-macro-{}", std::process::id()));
+    fs::create_dir_all(&root).unwrap();
+    let source = root.join("macro.surn");
+    fs::write(&source, code).unwrap();
+    let result = generate_file(
+        &OsProvider,
+        &PlainGenerator,
+        &source,
+        Path::new("macro.surn"),
+        &root.join("macro.txt"),
+        &dev(),
+        &FormatOptions::default(),
+    );
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "The `php!` macro is not supported by this language."
+    );
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+pub fn transpile_target_macro() {
+    let code = "target! {\n    php => { var x = 1; },\n    js | ts => { var x = 2; }\n}\nfn main() {\n    target! {\n        js => { var y = 1; }\n        _ => {\n            var y = 2;\n            var z = 3;\n        }\n    }\n}\ntarget! { js => { var z = 1; } }\n";
+    let output = php(code, dev());
+    assert_eq!(
+        output,
+        "$x = 1;\nfunction main() {\n    $y = 2;\n    $z = 3;\n}\n"
+    );
+
+    let mut parser = Parser::new(dev());
+    let result = parser.try_parse_script(
+        "target.surn".to_string(),
+        "target! { php { var x = 1; } }".to_string(),
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+pub fn transpile_with_profile() {
+    let callgrind = "version: 1\nevents: Time_(µs) Memory_(bytes)\n\nfl=(1) /app/main.php\nfn=(1) {main}\n0 10 0\ncfl=(1)\ncfn=(2) \\App\\add\ncalls=1200 0 0\n5 100 0\ncfl=(1)\ncfn=(3) log\ncalls=5 0 0\n6 2 0\ncfn=(2)\ncalls=300 0 0\n";
+    let profile = Profile::parse(callgrind).unwrap();
+    assert_eq!(profile.calls("add"), 1500);
+    assert_eq!(profile.hot_functions(), vec!["add".to_string()]);
+    assert_eq!(Profile::parse("add 12\nlog,3\n").unwrap().calls("log"), 3);
+
+    let code = "fn add(a: any, b: any): any {\n    return a + b;\n}\nfn log(a: any) {\n    return a;\n}\nvar x = add(1, 2);\n";
+    let options = CompilerOptions {
+        specialize: true,
+        hot_functions: vec!["log".to_string()],
+        profile: Some(profile),
+        ..dev()
+    };
+    let mut parser = Parser::new(options.clone());
+    let mut body = parser.parse_script("profile.surn".to_string(), code.to_string());
+    let stats = optimize(&mut body, &options);
+    assert_eq!(
+        stats.report(options.profile.as_ref()),
+        "Hot functions:\n    log (5 calls): not optimized\n    add (1500 calls): specialized as add__int_int"
+    );
+}
+
+#[test]
+pub fn transpile_scaffolded_composer_lib() {
+    let root = std::env::temp_dir().join(format!("surn-scaffold-{}", std::process::id()));
+    let package = Package::parse("acme/string-utils").unwrap();
+    assert_eq!(package.namespace(), "Acme\\StringUtils");
+    assert!(Package::parse("Acme/utils").is_err());
+
+    scaffold(&root, Template::ComposerLib, &package).unwrap();
+    let composer = fs::read_to_string(root.join("composer.json")).unwrap();
+    assert!(composer.contains("\"Acme\\\\StringUtils\\\\\": \"out/\""));
+    // a second run would overwrite the project.
+    assert!(scaffold(&root, Template::ComposerLib, &package).is_err());
+
+    transpiler()
+        .get_mut("php")
+        .unwrap()
+        .generator
+        .generate(root.join("src").to_str().unwrap(), dev())
+        .unwrap();
+    let output = fs::read_to_string(root.join("out/Calculator.php")).unwrap();
+    assert!(output.contains("namespace Acme\\StringUtils;"));
+    assert!(output.contains("class Calculator"));
+    // composer autoloads the generated files, php only runs what follows the tag.
+    for path in files(&OsProvider, &root.join("out")).unwrap() {
+        let code = fs::read_to_string(&path).unwrap();
+        assert_eq!(code.lines().next(), Some("<?php"), "{}", path.display());
+    }
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+pub fn build_new_project() {
+    let root = std::env::temp_dir().join(format!("surn-new-{}", std::process::id()));
+    new_project(&root, "app").unwrap();
+    assert!(root.join("src/main.surn").is_file());
+    assert!(root.join("tests").is_dir());
+
+    let mut project = Project::load(&root).unwrap();
+    assert_eq!(project.manifest, Manifest::new("app"));
+    assert_eq!(
+        Manifest::parse(&project.manifest.to_toml()).unwrap(),
+        project.manifest
+    );
+    assert!(
+        Manifest::parse("[package]\nname = \"app\"\n[build]\nsource = \"src\"\n")
+            .unwrap_err()
+            .contains("`build.source`")
+    );
+
+    project.manifest.format = FormatProfile::Rust;
+    let transpiler = transpiler();
+    let mut config = project.config().unwrap();
+    config.options = dev();
+    let report = project.build(&transpiler, &config).unwrap();
+    assert!(report.is_ok());
+    let output = fs::read_to_string(root.join("out/main.php")).unwrap();
+    assert!(output.contains("function main(): string {"));
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+pub fn transpile_with_manifest_config() {
+    let root = std::env::temp_dir().join(format!("surn-config-{}", std::process::id()));
+    fs::create_dir_all(root.join("types")).unwrap();
+    fs::write(
+        root.join("types/Mailer.d.surn"),
+        "class Mailer {\n    pub fn send(to: string): bool {}\n}\n",
+    )
+    .unwrap();
+    let manifest = Manifest::parse(
+        "[package]\nname = \"app\"\n\n[build]\ninclude = [\"types\"]\n\n\
+         [compiler]\nstrict_types = true\npost_semantic_checks = false\ntarget = \"8.1\"\nfail_on = \"notice\"\n\n\
+         [format]\nbraces = \"k&r\"\nclass_brace = \"allman\"\nindent_size = 2\n\n\
+         [lints]\nunused_variable = \"deny\"\n",
+    )
+    .unwrap();
+    assert_eq!(Manifest::parse(&manifest.to_toml()).unwrap(), manifest);
+
+    let config = manifest.config(&root).unwrap();
+    assert!(config.options.strict_types);
+    assert_eq!(config.options.target, Some(PhpTarget::Php81));
+    assert_eq!(config.options.fail_on, ReportKind::Notice);
+    assert_eq!(config.options.include_paths, vec![root.join("types")]);
+    assert_eq!(config.options.lints.level(UNUSED_VARIABLE), LintLevel::Deny);
+    assert_eq!(config.format.indent_size, 2);
+    assert_eq!(config.format.function_brace, BraceType::KandR);
+    assert_eq!(config.format.class_brace, BraceType::Allman);
+
+    // the declarations of the include paths are parsed before the script.
+    let mut parser = Parser::with_config(&config);
+    parser
+        .try_parse_script("main.surn".to_string(), "var a = 1;".to_string())
+        .unwrap();
+    let included = root.join("types/Mailer.d.surn").display().to_string();
+    assert!(parser.contexts().find(&included).is_some());
+
+    let source = root.join("main.surn");
+    fs::write(&source, "fn ok(): bool {\n    return true;\n}\n").unwrap();
+    let transpiler = transpiler();
+    transpiler
+        .generate(source.to_str().unwrap(), &config)
+        .unwrap();
+    let output = fs::read_to_string(root.join("main.php")).unwrap();
+    assert!(output.contains("declare(strict_types=1);"));
+    assert!(output.contains("function ok(): bool {\n  return true;"));
+
+    for (manifest, error) in [
+        (
+            "[compiler]\noptimise = true",
+            "Unknown key: `compiler.optimise`",
+        ),
+        ("[compiler]\nstrict_types = \"yes\"", "has to be a boolean"),
+        ("[format]\nbraces = \"gnu\"", "`gnu` is not a brace style"),
+        (
+            "[lints]\nunused_variable = \"loud\"",
+            "`loud` is not a lint level",
+        ),
+        (
+            "[lints]\nunused_vars = \"deny\"",
+            "`unused_vars` is not a lint",
+        ),
+    ] {
+        let manifest = format!("[package]\nname = \"app\"\n{}\n", manifest);
+        let message = Manifest::parse(&manifest).unwrap_err();
+        assert!(message.contains(error), "{}", message);
+    }
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+pub fn transpile_lossy_lowerings() {
+    let code = "fn first<T>(item: T): T {\n    return item;\n}\nvar big: long = 1;\n";
+    let transpiler = transpiler();
+    let php = transpiler.get("php").unwrap();
+    let body = parse("lossy.surn", code);
+    let lowerings = php.generator.lossy_lowerings(&body);
+    assert_eq!(
+        lowerings
+            .iter()
+            .map(|l| l.message.as_str())
+            .collect::<Vec<&str>>(),
+        vec![
+            "The type parameters of `first` are erased, php checks `T` as `mixed`.",
+            "`long` is narrowed to php's 64 bit `int`, values that don't fit become floats.",
+        ]
+    );
+    assert_eq!(&code[lowerings[0].span.clone()], "fn first");
+    assert_eq!(&code[lowerings[1].span.clone()], "var big: long = 1;");
+
+    let root = std::env::temp_dir().join(format!("surn-lossy-{}", std::process::id()));
+    fs::create_dir_all(&root).unwrap();
+    let source = root.join("lossy.surn");
+    fs::write(&source, code).unwrap();
+    let generate = |options: &CompilerOptions| {
+        generate_file(
+            &OsProvider,
+            php.generator.as_ref(),
+            &source,
+            Path::new("lossy.surn"),
+            &root.join("lossy.php"),
+            options,
+            &FormatOptions::default(),
+        )
+    };
+    let mut options = dev();
+    options.lints.set(ERASED_GENERICS, LintLevel::Allow);
+    assert_eq!(generate(&options).unwrap().len(), 1);
+    options.lints.set(NARROWED_INTEGERS, LintLevel::Deny);
+    assert!(generate(&options).is_err());
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+pub fn transpile_backend_audit() {
+    let transpiler = transpiler();
+    let php = transpiler.get("php").unwrap();
+    let audit = audit(php.generator.as_ref());
+    assert_eq!(audit.entries.len(), 38);
+    assert!(audit.panics().is_empty(), "{}", audit.report());
+    let coverage = |category: &str, variant: &str| {
+        audit
+            .entries
+            .iter()
+            .find(|e| e.category == category && e.variant == variant)
+            .map(|e| e.coverage.clone())
+            .unwrap()
+    };
+    assert_eq!(coverage("Expression", "Call"), Coverage::Generated);
+    assert!(matches!(
+        coverage("Expression", "Await"),
+        Coverage::Lowered(_)
+    ));
+}
+
+#[test]
+pub fn test_options_builder() {
+    let options = CompilerOptions::builder()
+        .optimize(true)
+        .strict_types(true)
+        .target(PhpTarget::Php81)
+        .build()
+        .unwrap();
+    assert_eq!(options.target, Some(PhpTarget::Php81));
+
+    assert!(php("var x = 1;", options).starts_with("declare(strict_types=1);\n\n"));
+
+    let report = CompilerOptions::builder()
+        .ast_only(true)
+        .target(PhpTarget::Php81)
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        report.message,
+        "`ast_only` stops before any code is generated, so `target` can't be set with it."
+    );
+    assert!(CompilerOptions::builder()
+        .optimize(false)
+        .specialize(true)
+        .build()
+        .is_err());
+
+    // the generated code uses enums, which php 8.0 doesn't have.
+    assert_eq!(PhpTarget::from_version("8.0"), None);
+    assert_eq!(PhpTarget::from_version("8.1"), Some(PhpTarget::Php81));
+}
+
+#[test]
+pub fn transpile_built_ast() {
+    let int = || TypeKind::BuiltIn(BuiltInType::Int);
+    let add = FunctionBuilder::new("add")
+        .input("a", int())
+        .input("b", int())
+        .returns(int())
+        .returning(expr::binary(expr::name("a"), BinOp::Plus, expr::name("b")))
+        .build();
+    let point = ClassBuilder::new("Point")
+        .property("x", Visibility::Public, Some(int()), Some(expr::int(0)))
+        .method(
+            FunctionBuilder::new("show")
+                .expression(expr::call("echo", [expr::member("this", "x")]))
+                .build(),
+        )
+        .build();
+    let body = BodyBuilder::new()
+        .function(add)
+        .class(point)
+        .statement(build::var(
+            "sum",
+            None,
+            Some(expr::call("add", [expr::int(1), expr::int(2)])),
+        ))
+        .build();
+    assert_eq!(body.get_program().len(), 3);
+
+    let transpiler = transpiler();
+    let php = transpiler.get("php").unwrap();
+    let code = php
+        .generator
+        .generate_to_string(body, dev(), &FormatOptions::psr_4());
+    assert!(code.starts_with("function add(int $a, int $b): int\n{\n    return $a + $b;\n}\n"));
+    assert!(code.contains("    public int $x = 0;\n"));
+    assert!(code.contains("        echo($this->x);\n"));
+    assert!(code.contains("$sum = add(1, 2);"));
+}
+
+#[test]
+pub fn test_compile_str() {
+    let source = "fn double(x: int): int {\n    var unused = 1;\n    return x * 2;\n}\n";
+    let output = surn::compile_str(source, &CompilerOptions::default()).unwrap();
+    assert_eq!(output.ast.get_program().len(), 1);
+    assert!(output.symbols.lookup("double", 0).is_some());
+    assert!(output.code["php"].contains("function double("));
+    assert_eq!(output.diagnostics[0].message, "`unused` is never used.");
+
+    let mut options = CompilerOptions {
+        ast_only: true,
+        ..CompilerOptions::default()
+    };
+    assert!(surn::compile_str(source, &options).unwrap().code.is_empty());
+
+    let errors = surn::compile_str("var z = ;\n", &options).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].name, surn::compile::SOURCE_NAME);
+
+    // the warning fails the compile once the options fail on warnings.
+    options.fail_on = ReportKind::Warning;
+    assert!(options.fails(ReportKind::Error) && !options.fails(ReportKind::Notice));
+    let errors = surn::compile_str(source, &options).unwrap_err();
+    assert_eq!(errors[0].kind, ReportKind::Warning);
+}
+
+#[test]
+pub fn transpile_globals_class() {
+    let source = "static var count: int = 0;\nvar config = load();\nvar local = 3;\nfn load(): int {\n    return 1;\n}\nfn bump(local: int): int {\n    count = count + local;\n    return config;\n}\nvar total = count + local;\n";
+    let mut options = dev();
+    options.globals_class = "App".to_string();
+    let output = surn::compile_str(source, &options).unwrap();
+    let php = &output.code["php"];
+    assert!(php.starts_with(
+        "<?php\n\nclass App {\n    public static int $count = 0;\n    public static $config;\n}\nApp::$config = load();\n$local = 3;\n"
+    ));
+    assert!(php.contains("    App::$count = App::$count + $local;\n    return App::$config;\n"));
+    assert!(php.ends_with("$total = App::$count + $local;\n"));
+
+    // the class is numbered when the source already declares one with its name.
+    let source =
+        "class App {\n}\nstatic var count: int = 0;\nfn bump() {\n    count = count + 1;\n}\n";
+    let php = &surn::compile_str(source, &options).unwrap().code["php"];
+    assert!(php.contains("class App {\n}\nclass App_2 {\n    public static int $count = 0;\n}\n"));
+    assert!(php.contains("    App_2::$count = App_2::$count + 1;\n"));
+
+    options.globals_class = "1st".to_string();
+    assert!(options.validate().is_err());
+}
+
+#[test]
+pub fn transpile_inferred_variables() {
+    let code = "fn pair(): (int, string) {\n    return (1, \"a\");\n}\nfn main() {\n    var p = pair();\n    var n = 1 + 2;\n}\n";
+    let output = php(code, dev());
+    assert!(
+        output.contains("    /** @var array{int, string} $p */ $p = pair();\n    $n = 1 + 2;\n")
+    );
+}
+
+#[test]
+pub fn transpile_string_addition() {
+    // php's `+` adds strings as numbers, so `string + string` never reaches the output.
+    let code = "fn greet(name: string): string {\n    return \"hi \" + name;\n}\n";
+    let mut parser = Parser::new(CompilerOptions::default());
+    let report = parser
+        .try_parse_script("test.surn".to_string(), code.to_string())
+        .unwrap_err();
+    assert_eq!(report.code, codes::TYPE_MISMATCH);
+
+    let code = "fn twice(n: int): int {\n    return n + n;\n}\n";
+    let output = php(code, CompilerOptions::default());
+    assert_eq!(
+        output,
+        "function twice(int $n): int {\n    return $n + $n;\n}\n"
+    );
+}
+
+#[test]
+pub fn format_method_attributes() {
+    let code = "class Model {\n    pub fn save() {\n    }\n}\nclass User extends Model {\n    #[Override]\n    pub fn save() {\n    }\n}\n";
+    let formatted = format_source("override.surn", code, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, code);
+}
+
+#[test]
+pub fn transpile_runtime_types() {
+    let code = "type Positive(x: int) = x > 0 && x < 100;\nfn grow(size: Positive): int {\n    return size * 2;\n}\nvar small: Positive = 5;\n";
+    let output = php(code, dev());
+    assert_eq!(
+        output,
+        "function grow(mixed $size): int {\n    return $size * 2;\n}\n$small = 5;\n"
+    );
+
+    let options = CompilerOptions {
+        runtime_checks: true,
+        ..dev()
+    };
+    let output = php(code, options.clone());
+    assert_eq!(
+        output,
+        "function assert_Positive($x) {\n    if (!($x > 0 && $x < 100)) {\n        throw new \\TypeError(\"The value is not a `Positive`.\");\n    }\n    return $x;\n}\nfunction grow(mixed $size): int {\n    assert_Positive($size);\n    return $size * 2;\n}\n$small = assert_Positive(5);\n"
+    );
+
+    let formatted = format_source("runtime.surn", code, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, code);
+
+    // aliases of a runtime type are checked, and so are the values of nullable and array types.
+    let code = "type Positive(x: int) = x > 0;\ntype Both = Positive;\nfn total(xs: Positive[]): int {\n    return 0;\n}\nvar p: Positive? = 3;\nvar s: Both = 3;\n";
+    let output = php(code, options);
+    assert!(output.contains(
+        "function total(array $xs): int {\n    array_map(fn($value) => assert_Positive($value), $xs);\n"
+    ));
+    assert!(output
+        .contains("$p = (fn($value) => $value === null ? null : assert_Positive($value))(3);\n"));
+    assert!(output.contains("$s = assert_Positive(3);\n"));
+}
+
+#[test]
+pub fn transpile_type_tests() {
+    let code = "fn describe(x: int | string | null): string {\n    if (x is int) {\n        return \"number\";\n    } else if (x is string?) {\n        return \"text\";\n    } else {\n        return \"other\";\n    }\n}\n";
+    let output = php(code, dev());
+    assert_eq!(
+        output,
+        "function describe(int|string|null $x): string {\n    if (is_int($x)) {\n        return \"number\";\n    } elseif ((is_null($x) || is_string($x))) {\n        return \"text\";\n    } else {\n        return \"other\";\n    }\n}\n"
+    );
+
+    let formatted = format_source("describe.surn", code, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, code);
+}
+
+#[test]
+pub fn transpile_generic_bounds() {
+    let code = "class Model {\n    pub id: int;\n}\nfn save<T extends Model, U>(item: T, extra: U): T {\n    return item;\n}\n";
+    let output = php(code, dev());
+    assert!(output.contains("function save(Model $item, mixed $extra): Model {\n"));
+
+    let formatted = format_source("bounds.surn", code, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, code);
+}
+
+#[test]
+pub fn transpile_array_and_map_types() {
+    let code = "fn total(counts: map<string, int>, names: string[]): int[] {\n    return [];\n}\nvar ids: int[] = [1, 2];\nvar mixed: (int | string)[] = [1, \"a\"];\nvar ages: map<string, int> = { ada: 36 };\nvar maybe: int[]? = null;\n";
+    let output = php(code, dev());
+    assert_eq!(
+        output,
+        "/**\n * @param array<string, int> $counts\n * @param string[] $names\n * @return int[]\n */\nfunction total(array $counts, array $names): array {\n    return [];\n}\n/** @var int[] $ids */ $ids = [1, 2];\n/** @var (int|string)[] $mixed */ $mixed = [1, \"a\"];\n/** @var array<string, int> $ages */ $ages = ['ada' => 36];\n/** @var int[]|null $maybe */ $maybe = null;\n"
+    );
+
+    let formatted = format_source("collections.surn", code, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, code);
+}